@@ -1,98 +1,544 @@
 //! B4AE JNI bindings for Android/Kotlin
+//!
+//! Errors never return a bare null: invalid arguments throw
+//! `IllegalArgumentException`, crypto failures throw `com.b4ae.B4AEException`.
+//!
+//! `nativeEncryptDirect`/`nativeDecryptDirect` work on direct `ByteBuffer`s
+//! without intermediate copies, for high-rate callers such as camera frames.
+//! Like `java.nio`, they read and write between each buffer's position and
+//! limit; the output limit is then set to the end of the written bytes.
 
-use jni::objects::{JByteArray, JClass};
-use jni::sys::jbyteArray;
+use jni::objects::{JByteArray, JByteBuffer, JClass, JValue};
+use jni::sys::{jbyteArray, jint};
 use jni::JNIEnv;
 
+use b4ae_ffi_impl::CryptoError;
+
 const KEY_SIZE: usize = 32;
 const NONCE_SIZE: usize = 12;
 
+const ILLEGAL_ARGUMENT_EXCEPTION: &str = "java/lang/IllegalArgumentException";
+const B4AE_EXCEPTION: &str = "com/b4ae/B4AEException";
+
+/// Throw `class` with `msg` unless a Java exception is already pending.
+/// Always returns null so callers can `return throw(...)`.
+fn throw(env: &mut JNIEnv, class: &str, msg: &str) -> jbyteArray {
+    if !env.exception_check().unwrap_or(false) {
+        let _ = env.throw_new(class, msg);
+    }
+    std::ptr::null_mut()
+}
+
+/// Copy a Java byte[] argument, throwing IllegalArgumentException on null/invalid.
+fn read_bytes(env: &mut JNIEnv, array: &JByteArray, name: &str) -> Option<Vec<u8>> {
+    match env.convert_byte_array(array) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            throw(env, ILLEGAL_ARGUMENT_EXCEPTION, &format!("{}: {}", name, e));
+            None
+        }
+    }
+}
+
+/// Convert a crypto result into a Java byte[], throwing B4AEException on error.
+fn to_java(env: &mut JNIEnv, result: Result<Vec<u8>, CryptoError>) -> jbyteArray {
+    match result {
+        // On allocation failure the JVM already has an OutOfMemoryError pending.
+        Ok(bytes) => match env.byte_array_from_slice(&bytes) {
+            Ok(arr) => arr.into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        },
+        Err(e) => throw(env, B4AE_EXCEPTION, &e.to_string()),
+    }
+}
+
+/// Copy a 32-byte key into a stack array, throwing IllegalArgumentException on error.
+fn read_key(env: &mut JNIEnv, key: &JByteArray) -> Option<[u8; KEY_SIZE]> {
+    let len = match env.get_array_length(key) {
+        Ok(len) => len as usize,
+        Err(e) => {
+            throw(env, ILLEGAL_ARGUMENT_EXCEPTION, &format!("key: {}", e));
+            return None;
+        }
+    };
+    if len != KEY_SIZE {
+        let msg = CryptoError::InvalidKeySize(len).to_string();
+        throw(env, ILLEGAL_ARGUMENT_EXCEPTION, &msg);
+        return None;
+    }
+    let mut buf = [0i8; KEY_SIZE];
+    if let Err(e) = env.get_byte_array_region(key, 0, &mut buf) {
+        throw(env, ILLEGAL_ARGUMENT_EXCEPTION, &format!("key: {}", e));
+        return None;
+    }
+    Some(buf.map(|b| b as u8))
+}
+
+/// Start address, position and remaining length of a direct ByteBuffer's
+/// `position..limit` window.
+/// Throws IllegalArgumentException if the buffer is null or not direct.
+fn direct_buffer(env: &mut JNIEnv, buf: &JByteBuffer, name: &str) -> Option<(*mut u8, usize, usize)> {
+    let addr = env.get_direct_buffer_address(buf);
+    let capacity = env.get_direct_buffer_capacity(buf);
+    let (Ok(ptr), Ok(capacity)) = (addr, capacity) else {
+        let msg = format!("{} must be a direct ByteBuffer", name);
+        throw(env, ILLEGAL_ARGUMENT_EXCEPTION, &msg);
+        return None;
+    };
+    let position = buffer_index(env, buf, "position", name)?;
+    let limit = buffer_index(env, buf, "limit", name)?;
+    if position > limit || limit > capacity {
+        let msg = format!("{}: position {} / limit {} out of bounds", name, position, limit);
+        throw(env, ILLEGAL_ARGUMENT_EXCEPTION, &msg);
+        return None;
+    }
+    // SAFETY: position <= capacity, so the offset stays inside the buffer.
+    Some((unsafe { ptr.add(position) }, position, limit - position))
+}
+
+/// Call the no-argument `int` getter `method` (`position` or `limit`) on `buf`.
+fn buffer_index(env: &mut JNIEnv, buf: &JByteBuffer, method: &str, name: &str) -> Option<usize> {
+    match env.call_method(buf, method, "()I", &[]).and_then(|v| v.i()) {
+        Ok(index) if index >= 0 => Some(index as usize),
+        _ => {
+            throw(env, ILLEGAL_ARGUMENT_EXCEPTION, &format!("{}: cannot read {}", name, method));
+            None
+        }
+    }
+}
+
+/// Set the limit of `buf` to `limit`, so position..limit covers the bytes written.
+fn set_limit(env: &mut JNIEnv, buf: &JByteBuffer, limit: usize) -> Option<()> {
+    let result = jint::try_from(limit).map_err(|_| ()).and_then(|limit| {
+        env.call_method(buf, "limit", "(I)Ljava/nio/Buffer;", &[JValue::Int(limit)])
+            .map(|_| ())
+            .map_err(|_| ())
+    });
+    if result.is_err() {
+        throw(env, ILLEGAL_ARGUMENT_EXCEPTION, "output: cannot set limit");
+        return None;
+    }
+    Some(())
+}
+
+/// True if `[a, a + a_len)` and `[b, b + b_len)` share any byte.
+fn ranges_overlap(a: usize, a_len: usize, b: usize, b_len: usize) -> bool {
+    a_len != 0 && b_len != 0 && a < b.saturating_add(b_len) && b < a.saturating_add(a_len)
+}
+
+/// Borrow the `position..limit` windows of an input and an output direct
+/// ByteBuffer, plus the output position.
+/// Throws IllegalArgumentException if either is not direct or they overlap,
+/// since the two slices would otherwise alias.
+fn direct_buffers<'a>(
+    env: &mut JNIEnv,
+    input: &JByteBuffer,
+    input_name: &str,
+    output: &JByteBuffer,
+) -> Option<(&'a [u8], &'a mut [u8], usize)> {
+    let (in_ptr, _, in_len) = direct_buffer(env, input, input_name)?;
+    let (out_ptr, out_position, out_len) = direct_buffer(env, output, "output")?;
+    if ranges_overlap(in_ptr as usize, in_len, out_ptr as usize, out_len) {
+        let msg = format!("{} and output must not overlap", input_name);
+        throw(env, ILLEGAL_ARGUMENT_EXCEPTION, &msg);
+        return None;
+    }
+    // SAFETY: both ranges come from live direct buffers and are disjoint.
+    unsafe {
+        Some((
+            std::slice::from_raw_parts(in_ptr, in_len),
+            std::slice::from_raw_parts_mut(out_ptr, out_len),
+            out_position,
+        ))
+    }
+}
+
+/// Map an in-place crypto result to a jint, throwing B4AEException on error.
+/// On success the limit of `output` (written from `out_position`) is set to
+/// the end of the written bytes.
+fn to_jint(
+    env: &mut JNIEnv,
+    output: &JByteBuffer,
+    out_position: usize,
+    result: Result<usize, CryptoError>,
+) -> jint {
+    match result {
+        Ok(n) => match set_limit(env, output, out_position + n) {
+            Some(()) => n as jint,
+            None => -1,
+        },
+        Err(e) => {
+            throw(env, B4AE_EXCEPTION, &e.to_string());
+            -1
+        }
+    }
+}
+
 /// Generate 32-byte key. Returns byte array.
 #[no_mangle]
 #[allow(non_snake_case)]
 pub extern "system" fn Java_com_b4ae_B4AE_nativeGenerateKey(
-    env: JNIEnv,
+    mut env: JNIEnv,
     _class: JClass,
 ) -> jbyteArray {
     let key = b4ae_ffi_impl::generate_key();
-    let arr = env.byte_array_from_slice(&key).unwrap();
-    arr.into_raw()
+    to_java(&mut env, key)
 }
 
 /// Encrypt plaintext. key and plaintext are byte arrays, returns encrypted [nonce||ciphertext].
 #[no_mangle]
 #[allow(non_snake_case)]
 pub extern "system" fn Java_com_b4ae_B4AE_nativeEncrypt(
-    env: JNIEnv,
+    mut env: JNIEnv,
     _class: JClass,
     key: JByteArray,
     plaintext: JByteArray,
 ) -> jbyteArray {
-    let key_vec: Vec<u8> = env.convert_byte_array(key).unwrap();
-    let plain_vec: Vec<u8> = env.convert_byte_array(plaintext).unwrap();
-    match b4ae_ffi_impl::encrypt(&key_vec, &plain_vec) {
-        Ok(enc) => env.byte_array_from_slice(&enc).unwrap().into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+    let Some(key_vec) = read_bytes(&mut env, &key, "key") else {
+        return std::ptr::null_mut();
+    };
+    let Some(plain_vec) = read_bytes(&mut env, &plaintext, "plaintext") else {
+        return std::ptr::null_mut();
+    };
+    let result = b4ae_ffi_impl::encrypt(&key_vec, &plain_vec);
+    to_java(&mut env, result)
 }
 
-/// Decrypt [nonce||ciphertext]. Returns plaintext or null on error.
+/// Decrypt [nonce||ciphertext]. Returns plaintext, throws B4AEException on error.
 #[no_mangle]
 #[allow(non_snake_case)]
 pub extern "system" fn Java_com_b4ae_B4AE_nativeDecrypt(
-    env: JNIEnv,
+    mut env: JNIEnv,
     _class: JClass,
     key: JByteArray,
     encrypted: JByteArray,
 ) -> jbyteArray {
-    let key_vec: Vec<u8> = env.convert_byte_array(key).unwrap();
-    let enc_vec: Vec<u8> = env.convert_byte_array(encrypted).unwrap();
-    match b4ae_ffi_impl::decrypt(&key_vec, &enc_vec) {
-        Ok(dec) => env.byte_array_from_slice(&dec).unwrap().into_raw(),
-        Err(_) => std::ptr::null_mut(),
-    }
+    let Some(key_vec) = read_bytes(&mut env, &key, "key") else {
+        return std::ptr::null_mut();
+    };
+    let Some(enc_vec) = read_bytes(&mut env, &encrypted, "encrypted") else {
+        return std::ptr::null_mut();
+    };
+    let result = b4ae_ffi_impl::decrypt(&key_vec, &enc_vec);
+    to_java(&mut env, result)
+}
+
+/// Encrypt the remaining bytes of direct buffer `plaintext` into direct buffer
+/// `output` at its position as [nonce||ciphertext||tag]. Returns bytes written
+/// and sets the output limit to their end, or -1 with a pending exception.
+/// `output` needs remaining >= plaintext remaining + 28; overlapping buffers
+/// throw IllegalArgumentException. Positions are left unchanged.
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_b4ae_B4AE_nativeEncryptDirect(
+    mut env: JNIEnv,
+    _class: JClass,
+    key: JByteArray,
+    plaintext: JByteBuffer,
+    output: JByteBuffer,
+) -> jint {
+    let Some(key) = read_key(&mut env, &key) else {
+        return -1;
+    };
+    let Some((plain, out, out_position)) = direct_buffers(&mut env, &plaintext, "plaintext", &output) else {
+        return -1;
+    };
+    let result = b4ae_ffi_impl::encrypt_into(&key, plain, out);
+    to_jint(&mut env, &output, out_position, result)
+}
+
+/// Decrypt the remaining bytes of direct buffer `encrypted` ([nonce||ciphertext||tag])
+/// into direct buffer `output` at its position. Returns plaintext length and
+/// sets the output limit to its end, or -1 with a pending exception;
+/// overlapping buffers throw IllegalArgumentException. Positions are left
+/// unchanged.
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_b4ae_B4AE_nativeDecryptDirect(
+    mut env: JNIEnv,
+    _class: JClass,
+    key: JByteArray,
+    encrypted: JByteBuffer,
+    output: JByteBuffer,
+) -> jint {
+    let Some(key) = read_key(&mut env, &key) else {
+        return -1;
+    };
+    let Some((enc, out, out_position)) = direct_buffers(&mut env, &encrypted, "encrypted", &output) else {
+        return -1;
+    };
+    let result = b4ae_ffi_impl::decrypt_into(&key, enc, out);
+    to_jint(&mut env, &output, out_position, result)
 }
 
 mod b4ae_ffi_impl {
     use aes_gcm::{
-        aead::{Aead, KeyInit, Payload},
-        Aes256Gcm,
+        aead::{Aead, AeadInPlace, KeyInit, Payload},
+        Aes256Gcm, Tag,
     };
+    use std::fmt;
 
-    const KEY_SIZE: usize = 32;
-    const NONCE_SIZE: usize = 12;
+    use super::{KEY_SIZE, NONCE_SIZE};
 
-    pub fn generate_key() -> Vec<u8> {
+    const TAG_SIZE: usize = 16;
+
+    /// Crypto failure reason, surfaced to Kotlin as the B4AEException message.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum CryptoError {
+        InvalidKeySize(usize),
+        EncryptedTooShort(usize),
+        RandomFailed,
+        OutputTooSmall { needed: usize, got: usize },
+        EncryptionFailed,
+        AuthenticationFailed,
+    }
+
+    impl fmt::Display for CryptoError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                CryptoError::InvalidKeySize(n) => {
+                    write!(f, "Invalid key size: expected {} bytes, got {}", KEY_SIZE, n)
+                }
+                CryptoError::EncryptedTooShort(n) => write!(
+                    f,
+                    "Encrypted data too short: expected at least {} bytes, got {}",
+                    NONCE_SIZE, n
+                ),
+                CryptoError::OutputTooSmall { needed, got } => write!(
+                    f,
+                    "Output buffer too small: need {} bytes, got {}",
+                    needed, got
+                ),
+                CryptoError::RandomFailed => write!(f, "Random number generation failed"),
+                CryptoError::EncryptionFailed => write!(f, "Encryption failed"),
+                CryptoError::AuthenticationFailed => {
+                    write!(f, "Decryption failed: authentication tag mismatch")
+                }
+            }
+        }
+    }
+
+    pub fn generate_key() -> Result<Vec<u8>, CryptoError> {
         let mut key = [0u8; KEY_SIZE];
-        getrandom::getrandom(&mut key).expect("getrandom");
-        key.to_vec()
+        getrandom::getrandom(&mut key).map_err(|_| CryptoError::RandomFailed)?;
+        Ok(key.to_vec())
     }
 
-    pub fn encrypt(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, ()> {
+    pub fn encrypt(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
         if key.len() != KEY_SIZE {
-            return Err(());
+            return Err(CryptoError::InvalidKeySize(key.len()));
         }
         let mut nonce = [0u8; NONCE_SIZE];
-        getrandom::getrandom(&mut nonce).map_err(|_| ())?;
-        let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| ())?;
+        getrandom::getrandom(&mut nonce).map_err(|_| CryptoError::RandomFailed)?;
+        let cipher =
+            Aes256Gcm::new_from_slice(key).map_err(|_| CryptoError::InvalidKeySize(key.len()))?;
         let payload = Payload { msg: plaintext, aad: &[] };
-        let ciphertext = cipher.encrypt((&nonce).into(), payload).map_err(|_| ())?;
+        let ciphertext = cipher
+            .encrypt((&nonce).into(), payload)
+            .map_err(|_| CryptoError::EncryptionFailed)?;
         let mut result = nonce.to_vec();
         result.extend(ciphertext);
         Ok(result)
     }
 
-    pub fn decrypt(key: &[u8], encrypted: &[u8]) -> Result<Vec<u8>, ()> {
-        if key.len() != KEY_SIZE || encrypted.len() < NONCE_SIZE {
-            return Err(());
+    pub fn decrypt(key: &[u8], encrypted: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if key.len() != KEY_SIZE {
+            return Err(CryptoError::InvalidKeySize(key.len()));
+        }
+        if encrypted.len() < NONCE_SIZE {
+            return Err(CryptoError::EncryptedTooShort(encrypted.len()));
         }
         let (nonce_bytes, ciphertext) = encrypted.split_at(NONCE_SIZE);
         let nonce = aes_gcm::Nonce::from_slice(nonce_bytes);
-        let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| ())?;
+        let cipher =
+            Aes256Gcm::new_from_slice(key).map_err(|_| CryptoError::InvalidKeySize(key.len()))?;
         let payload = Payload {
             msg: ciphertext,
             aad: &[],
         };
-        cipher.decrypt(nonce, payload).map_err(|_| ())
+        cipher
+            .decrypt(nonce, payload)
+            .map_err(|_| CryptoError::AuthenticationFailed)
+    }
+
+    /// Encrypt into `out` as [nonce||ciphertext||tag] without heap allocation.
+    pub fn encrypt_into(
+        key: &[u8; KEY_SIZE],
+        plaintext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, CryptoError> {
+        let needed = NONCE_SIZE + plaintext.len() + TAG_SIZE;
+        if out.len() < needed {
+            return Err(CryptoError::OutputTooSmall { needed, got: out.len() });
+        }
+        let (nonce, rest) = out.split_at_mut(NONCE_SIZE);
+        getrandom::getrandom(nonce).map_err(|_| CryptoError::RandomFailed)?;
+        let (body, rest) = rest.split_at_mut(plaintext.len());
+        body.copy_from_slice(plaintext);
+        let cipher = Aes256Gcm::new(key.into());
+        let tag = cipher
+            .encrypt_in_place_detached((&*nonce).into(), &[], body)
+            .map_err(|_| CryptoError::EncryptionFailed)?;
+        rest[..TAG_SIZE].copy_from_slice(&tag);
+        Ok(needed)
+    }
+
+    /// Decrypt [nonce||ciphertext||tag] into `out` without heap allocation.
+    /// `out` is left zeroed on authentication failure.
+    pub fn decrypt_into(
+        key: &[u8; KEY_SIZE],
+        encrypted: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize, CryptoError> {
+        if encrypted.len() < NONCE_SIZE + TAG_SIZE {
+            return Err(CryptoError::EncryptedTooShort(encrypted.len()));
+        }
+        let (nonce, rest) = encrypted.split_at(NONCE_SIZE);
+        let (ciphertext, tag) = rest.split_at(rest.len() - TAG_SIZE);
+        if out.len() < ciphertext.len() {
+            return Err(CryptoError::OutputTooSmall {
+                needed: ciphertext.len(),
+                got: out.len(),
+            });
+        }
+        let body = &mut out[..ciphertext.len()];
+        body.copy_from_slice(ciphertext);
+        let cipher = Aes256Gcm::new(key.into());
+        if cipher
+            .decrypt_in_place_detached(nonce.into(), &[], body, Tag::from_slice(tag))
+            .is_err()
+        {
+            body.fill(0);
+            return Err(CryptoError::AuthenticationFailed);
+        }
+        Ok(ciphertext.len())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::alloc::{GlobalAlloc, Layout, System};
+        use std::cell::Cell;
+
+        /// Counts heap allocations made by the current thread.
+        struct CountingAlloc;
+
+        thread_local! {
+            static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        unsafe impl GlobalAlloc for CountingAlloc {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                ALLOCATIONS.with(|c| c.set(c.get() + 1));
+                System.alloc(layout)
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                System.dealloc(ptr, layout)
+            }
+        }
+
+        #[global_allocator]
+        static GLOBAL: CountingAlloc = CountingAlloc;
+
+        fn allocations() -> usize {
+            ALLOCATIONS.with(|c| c.get())
+        }
+
+        #[test]
+        fn test_roundtrip() {
+            let key = generate_key().unwrap();
+            let enc = encrypt(&key, b"hello").unwrap();
+            assert_eq!(decrypt(&key, &enc).unwrap(), b"hello");
+        }
+
+        #[test]
+        fn test_error_messages() {
+            let key = generate_key().unwrap();
+
+            let err = encrypt(&key[..16], b"hello").unwrap_err();
+            assert_eq!(err, CryptoError::InvalidKeySize(16));
+            assert_eq!(err.to_string(), "Invalid key size: expected 32 bytes, got 16");
+
+            let err = decrypt(&key, &[0u8; 5]).unwrap_err();
+            assert_eq!(err, CryptoError::EncryptedTooShort(5));
+            assert_eq!(
+                err.to_string(),
+                "Encrypted data too short: expected at least 12 bytes, got 5"
+            );
+
+            let mut enc = encrypt(&key, b"hello").unwrap();
+            let last = enc.len() - 1;
+            enc[last] ^= 0x01;
+            let err = decrypt(&key, &enc).unwrap_err();
+            assert_eq!(err, CryptoError::AuthenticationFailed);
+            assert_eq!(
+                err.to_string(),
+                "Decryption failed: authentication tag mismatch"
+            );
+        }
+    
+        #[test]
+        fn test_direct_roundtrip() {
+            let key = [3u8; KEY_SIZE];
+            let frame = vec![0xABu8; 1024];
+            let mut enc = vec![0u8; frame.len() + NONCE_SIZE + TAG_SIZE];
+            let n = encrypt_into(&key, &frame, &mut enc).unwrap();
+            assert_eq!(n, enc.len());
+
+            // Compatible with the byte[] path.
+            assert_eq!(decrypt(&key, &enc).unwrap(), frame);
+
+            let mut out = vec![0u8; frame.len()];
+            assert_eq!(decrypt_into(&key, &enc, &mut out).unwrap(), frame.len());
+            assert_eq!(out, frame);
+
+            enc[NONCE_SIZE] ^= 0x01;
+            assert_eq!(
+                decrypt_into(&key, &enc, &mut out).unwrap_err(),
+                CryptoError::AuthenticationFailed
+            );
+            assert!(out.iter().all(|&b| b == 0));
+        }
+
+        #[test]
+        fn test_direct_output_too_small() {
+            let key = [3u8; KEY_SIZE];
+            let mut out = [0u8; 20];
+            let err = encrypt_into(&key, b"hello", &mut out).unwrap_err();
+            assert_eq!(err, CryptoError::OutputTooSmall { needed: 33, got: 20 });
+            assert_eq!(err.to_string(), "Output buffer too small: need 33 bytes, got 20");
+        }
+
+        #[test]
+        fn test_ranges_overlap() {
+            use crate::ranges_overlap;
+            assert!(ranges_overlap(100, 10, 100, 10));
+            assert!(ranges_overlap(100, 10, 109, 10));
+            assert!(ranges_overlap(100, 10, 95, 6));
+            assert!(!ranges_overlap(100, 10, 110, 10));
+            assert!(!ranges_overlap(100, 10, 90, 10));
+            assert!(!ranges_overlap(100, 0, 100, 10));
+        }
+
+        #[test]
+        fn bench_direct_hot_path_does_not_allocate() {
+            // 30 fps * 10 s of 64 KiB frames.
+            const FRAMES: usize = 300;
+            let key = [9u8; KEY_SIZE];
+            let frame = vec![0x5Au8; 64 * 1024];
+            let mut enc = vec![0u8; frame.len() + NONCE_SIZE + TAG_SIZE];
+            let mut dec = vec![0u8; frame.len()];
+
+            let before = allocations();
+            for _ in 0..FRAMES {
+                encrypt_into(&key, &frame, &mut enc).unwrap();
+                decrypt_into(&key, &enc, &mut dec).unwrap();
+            }
+            let after = allocations();
+
+            assert_eq!(after - before, 0, "hot path must not allocate");
+            assert_eq!(dec, frame);
+        }
     }
 }
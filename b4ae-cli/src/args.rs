@@ -0,0 +1,212 @@
+//! Command-line parsing
+//!
+//! Options that carry secrets (`--key`, `--passphrase`) take a file path or
+//! `-` for stdin, never the secret itself, so it cannot leak through `ps` or
+//! shell history.
+
+use std::path::PathBuf;
+
+/// Usage text printed by `help` and on argument errors
+pub const USAGE: &str = "\
+usage:
+  b4ae-cli keygen  --out <secret-key> [--public-out <public-key>]
+  b4ae-cli encrypt --in <file> --out <file> (--recipient <public-key> | --key <key>)
+  b4ae-cli decrypt --in <file> --out <file> --key <key>
+  b4ae-cli derive  --passphrase <file> --out <key> [--salt <hex>]
+
+--key, --recipient and --passphrase take a file path, or - for stdin.
+exit codes: 0 ok, 2 usage, 3 I/O error, 4 authentication failed, 5 invalid key or file";
+
+/// Where a key or passphrase is read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Standard input
+    Stdin,
+    /// A file
+    File(PathBuf),
+}
+
+impl Source {
+    fn parse(value: String) -> Self {
+        if value == "-" {
+            Source::Stdin
+        } else {
+            Source::File(PathBuf::from(value))
+        }
+    }
+}
+
+/// Parsed subcommand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Print usage
+    Help,
+    /// Generate a hybrid (X25519 + Kyber1024) key pair
+    Keygen {
+        /// Secret key file (created, never overwritten)
+        out: PathBuf,
+        /// Public key file; defaults to `<out>.pub`
+        public_out: PathBuf,
+    },
+    /// Encrypt a file to a recipient public key or a symmetric key
+    Encrypt {
+        /// Plaintext file
+        input: PathBuf,
+        /// Encrypted file
+        output: PathBuf,
+        /// Key to encrypt to
+        key: EncryptKey,
+    },
+    /// Decrypt a file with a secret or symmetric key
+    Decrypt {
+        /// Encrypted file
+        input: PathBuf,
+        /// Plaintext file
+        output: PathBuf,
+        /// Secret key (from `keygen`) or symmetric key (from `derive`)
+        key: Source,
+    },
+    /// Derive a symmetric key from a passphrase (Argon2id)
+    Derive {
+        /// Passphrase file; a single trailing newline is ignored
+        passphrase: Source,
+        /// Symmetric key file (created, never overwritten)
+        out: PathBuf,
+        /// Salt to re-derive an earlier key; random when absent
+        salt: Option<Vec<u8>>,
+    },
+}
+
+/// Key selection for `encrypt`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptKey {
+    /// Recipient public key from `keygen`
+    Recipient(Source),
+    /// Symmetric key from `derive`
+    Symmetric(Source),
+}
+
+/// Parse arguments (without the program name)
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let command = match args.next() {
+        Some(command) => command,
+        None => return Err("missing command".to_string()),
+    };
+    if matches!(command.as_str(), "help" | "--help" | "-h") {
+        return Ok(Command::Help);
+    }
+
+    let mut options: Vec<(String, String)> = Vec::new();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("missing value for {}", flag))?;
+        if options.iter().any(|(f, _)| *f == flag) {
+            return Err(format!("{} given more than once", flag));
+        }
+        options.push((flag, value));
+    }
+
+    let allowed: &[&str] = match command.as_str() {
+        "keygen" => &["--out", "--public-out"],
+        "encrypt" => &["--in", "--out", "--recipient", "--key"],
+        "decrypt" => &["--in", "--out", "--key"],
+        "derive" => &["--passphrase", "--out", "--salt"],
+        other => return Err(format!("unknown command {}", other)),
+    };
+    if let Some((flag, _)) = options.iter().find(|(f, _)| !allowed.contains(&f.as_str())) {
+        return Err(format!("unknown argument {} for {}", flag, command));
+    }
+
+    let mut take = |flag: &str| {
+        options
+            .iter()
+            .position(|(f, _)| f == flag)
+            .map(|i| options.swap_remove(i).1)
+    };
+
+    match command.as_str() {
+        "keygen" => {
+            let out = PathBuf::from(take("--out").ok_or("keygen requires --out")?);
+            let public_out = match take("--public-out") {
+                Some(path) => PathBuf::from(path),
+                None => {
+                    let mut path = out.clone().into_os_string();
+                    path.push(".pub");
+                    PathBuf::from(path)
+                }
+            };
+            Ok(Command::Keygen { out, public_out })
+        }
+        "encrypt" => {
+            let input = PathBuf::from(take("--in").ok_or("encrypt requires --in")?);
+            let output = PathBuf::from(take("--out").ok_or("encrypt requires --out")?);
+            let key = match (take("--recipient"), take("--key")) {
+                (Some(recipient), None) => EncryptKey::Recipient(Source::parse(recipient)),
+                (None, Some(key)) => EncryptKey::Symmetric(Source::parse(key)),
+                _ => return Err("encrypt requires exactly one of --recipient, --key".to_string()),
+            };
+            Ok(Command::Encrypt { input, output, key })
+        }
+        "decrypt" => {
+            let input = PathBuf::from(take("--in").ok_or("decrypt requires --in")?);
+            let output = PathBuf::from(take("--out").ok_or("decrypt requires --out")?);
+            let key = Source::parse(take("--key").ok_or("decrypt requires --key")?);
+            Ok(Command::Decrypt { input, output, key })
+        }
+        _ => {
+            let passphrase = Source::parse(take("--passphrase").ok_or("derive requires --passphrase")?);
+            let out = PathBuf::from(take("--out").ok_or("derive requires --out")?);
+            let salt = take("--salt")
+                .map(|hex_salt| hex::decode(&hex_salt).map_err(|e| format!("invalid --salt: {}", e)))
+                .transpose()?;
+            Ok(Command::Derive { passphrase, out, salt })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(args: &[&str]) -> Result<Command, String> {
+        parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            parse_str(&["keygen", "--out", "id"]).unwrap(),
+            Command::Keygen { out: "id".into(), public_out: "id.pub".into() }
+        );
+        assert_eq!(
+            parse_str(&["encrypt", "--in", "a", "--out", "b", "--recipient", "id.pub"]).unwrap(),
+            Command::Encrypt {
+                input: "a".into(),
+                output: "b".into(),
+                key: EncryptKey::Recipient(Source::File("id.pub".into())),
+            }
+        );
+        assert_eq!(
+            parse_str(&["decrypt", "--key", "-", "--in", "b", "--out", "a"]).unwrap(),
+            Command::Decrypt { input: "b".into(), output: "a".into(), key: Source::Stdin }
+        );
+        assert_eq!(
+            parse_str(&["derive", "--passphrase", "-", "--out", "k", "--salt", "0011"]).unwrap(),
+            Command::Derive { passphrase: Source::Stdin, out: "k".into(), salt: Some(vec![0x00, 0x11]) }
+        );
+        assert_eq!(parse_str(&["--help"]).unwrap(), Command::Help);
+    }
+
+    #[test]
+    fn test_rejects_invalid_arguments() {
+        assert!(parse_str(&[]).is_err());
+        assert!(parse_str(&["shred"]).is_err());
+        assert!(parse_str(&["keygen"]).is_err());
+        assert!(parse_str(&["keygen", "--out"]).is_err());
+        assert!(parse_str(&["keygen", "--out", "a", "--out", "b"]).is_err());
+        assert!(parse_str(&["decrypt", "--in", "a", "--out", "b", "--recipient", "k"]).is_err());
+        assert!(parse_str(&["encrypt", "--in", "a", "--out", "b"]).is_err());
+        assert!(parse_str(&["encrypt", "--in", "a", "--out", "b", "--key", "k", "--recipient", "r"]).is_err());
+        assert!(parse_str(&["derive", "--passphrase", "-", "--out", "k", "--salt", "xyz"]).is_err());
+    }
+}
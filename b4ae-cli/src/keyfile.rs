@@ -0,0 +1,154 @@
+//! Armored key files
+//!
+//! ```text
+//! -----BEGIN B4AE SECRET KEY-----
+//! <hex, 64 characters per line>
+//! -----END B4AE SECRET KEY-----
+//! ```
+//!
+//! Text so keys survive copy/paste and stdin; the label says which command
+//! produced the key. Secret and symmetric keys are created with mode 0600 on
+//! Unix and are never overwritten.
+
+use crate::args::Source;
+use crate::CliError;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// Kind of key held in a key file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    /// Hybrid public key (`keygen`)
+    Public,
+    /// Hybrid secret key (`keygen`)
+    Secret,
+    /// 32-byte symmetric key (`derive`)
+    Symmetric,
+}
+
+impl KeyKind {
+    fn label(self) -> &'static str {
+        match self {
+            KeyKind::Public => "PUBLIC KEY",
+            KeyKind::Secret => "SECRET KEY",
+            KeyKind::Symmetric => "SYMMETRIC KEY",
+        }
+    }
+}
+
+/// Encode `bytes` as an armored key file
+pub fn armor(kind: KeyKind, bytes: &[u8]) -> Zeroizing<String> {
+    let encoded = Zeroizing::new(hex::encode(bytes));
+    let mut out = Zeroizing::new(format!("-----BEGIN B4AE {}-----\n", kind.label()));
+    for line in encoded.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).expect("hex is ASCII"));
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END B4AE {}-----\n", kind.label()));
+    out
+}
+
+/// Decode an armored key file
+pub fn dearmor(text: &str) -> Result<(KeyKind, Zeroizing<Vec<u8>>), CliError> {
+    let invalid = |msg: &str| CliError::Invalid(format!("key file: {}", msg));
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+
+    let begin = lines.next().ok_or_else(|| invalid("empty"))?;
+    let kind = [KeyKind::Public, KeyKind::Secret, KeyKind::Symmetric]
+        .into_iter()
+        .find(|k| begin == format!("-----BEGIN B4AE {}-----", k.label()))
+        .ok_or_else(|| invalid("missing BEGIN B4AE line"))?;
+
+    let end = format!("-----END B4AE {}-----", kind.label());
+    let mut encoded = Zeroizing::new(String::new());
+    for line in lines.by_ref() {
+        if line == end {
+            let bytes = hex::decode(encoded.as_bytes()).map_err(|_| invalid("body is not hex"))?;
+            return Ok((kind, Zeroizing::new(bytes)));
+        }
+        encoded.push_str(line);
+    }
+    Err(invalid("missing END line"))
+}
+
+/// Read a key or passphrase from a file or stdin
+pub fn read_source(source: &Source) -> Result<Zeroizing<Vec<u8>>, CliError> {
+    let mut data = Zeroizing::new(Vec::new());
+    match source {
+        Source::Stdin => std::io::stdin().read_to_end(&mut data).map(|_| ()),
+        Source::File(path) => std::fs::File::open(path).and_then(|mut f| f.read_to_end(&mut data).map(|_| ())),
+    }
+    .map_err(|e| CliError::Io(format!("{}: {}", source_name(source), e)))?;
+    Ok(data)
+}
+
+/// Read and decode a key file, requiring one of `expected`
+pub fn read_key(source: &Source, expected: &[KeyKind]) -> Result<(KeyKind, Zeroizing<Vec<u8>>), CliError> {
+    let data = read_source(source)?;
+    let text = std::str::from_utf8(&data)
+        .map_err(|_| CliError::Invalid(format!("{}: not a key file", source_name(source))))?;
+    let (kind, bytes) = dearmor(text)?;
+    if !expected.contains(&kind) {
+        return Err(CliError::Invalid(format!(
+            "{}: expected {}, found {}",
+            source_name(source),
+            expected.iter().map(|k| k.label()).collect::<Vec<_>>().join(" or "),
+            kind.label()
+        )));
+    }
+    Ok((kind, bytes))
+}
+
+/// Write a key file; fails if `path` already exists
+pub fn write_key(path: &Path, kind: KeyKind, bytes: &[u8]) -> Result<(), CliError> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if kind != KeyKind::Public {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(armor(kind, bytes).as_bytes()))
+        .map_err(|e| CliError::Io(format!("{}: {}", path.display(), e)))
+}
+
+fn source_name(source: &Source) -> String {
+    match source {
+        Source::Stdin => "stdin".to_string(),
+        Source::File(path) => path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_armor_roundtrip() {
+        let bytes: Vec<u8> = (0..=255u8).collect();
+        let text = armor(KeyKind::Secret, &bytes);
+        assert!(text.starts_with("-----BEGIN B4AE SECRET KEY-----\n"));
+        assert!(text.lines().all(|l| l.len() <= 64 || l.starts_with("-----")));
+
+        let (kind, decoded) = dearmor(&text).unwrap();
+        assert_eq!(kind, KeyKind::Secret);
+        assert_eq!(decoded.as_slice(), bytes.as_slice());
+
+        // CRLF line endings from copy/paste are accepted
+        let (_, decoded) = dearmor(&text.replace('\n', "\r\n")).unwrap();
+        assert_eq!(decoded.as_slice(), bytes.as_slice());
+    }
+
+    #[test]
+    fn test_dearmor_rejects_malformed() {
+        assert!(dearmor("").is_err());
+        assert!(dearmor("00ff\n").is_err());
+        assert!(dearmor("-----BEGIN B4AE SECRET KEY-----\n00ff\n").is_err());
+        assert!(dearmor("-----BEGIN B4AE SECRET KEY-----\nzz\n-----END B4AE SECRET KEY-----\n").is_err());
+        assert!(dearmor("-----BEGIN B4AE SECRET KEY-----\n00\n-----END B4AE PUBLIC KEY-----\n").is_err());
+    }
+}
@@ -0,0 +1,291 @@
+//! B4AE command-line tool
+//!
+//! Encrypts files for scripts and backups with the streaming storage format
+//! ([`b4ae::storage::stream`]), so files of any size are processed in
+//! constant memory.
+//!
+//! ```text
+//! b4ae-cli keygen  --out id                      # writes id (secret) and id.pub
+//! b4ae-cli encrypt --in db.tar --out db.tar.b4ae --recipient id.pub
+//! b4ae-cli decrypt --in db.tar.b4ae --out db.tar --key id
+//! b4ae-cli derive  --passphrase - --out backup.key < passphrase.txt
+//! ```
+//!
+//! # File format
+//!
+//! ```text
+//! file = magic "B4CF" (4) || version (1) || mode (1) || [kex ciphertext] || stream
+//! ```
+//!
+//! Mode 0x01 encrypts to a hybrid X25519 + Kyber1024 public key and carries
+//! the KEX ciphertext; mode 0x02 uses a symmetric key from `derive`. The file
+//! key is HKDF over the shared secret (or symmetric key) and everything before
+//! the stream, so the prefix cannot be swapped. `stream` is an XChaCha20-Poly1305
+//! [`EncryptedWriter`] stream.
+
+pub mod args;
+pub mod keyfile;
+
+use args::{Command, EncryptKey, Source};
+use b4ae::crypto::hkdf;
+use b4ae::crypto::hybrid_kex::{self, HybridKexCiphertext, HybridKexPublicKey, HybridKexSecretKey};
+use b4ae::crypto::random::fill_random;
+use b4ae::key_hierarchy::StorageKey;
+use b4ae::key_store::{Argon2Params, KeyStore};
+use b4ae::storage::{EncryptedReader, EncryptedWriter, StorageCipher, StorageError};
+use keyfile::KeyKind;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// File magic
+pub const FILE_MAGIC: [u8; 4] = *b"B4CF";
+/// File format version
+pub const FILE_VERSION: u8 = 0x01;
+/// Mode byte: encrypted to a hybrid public key
+pub const MODE_RECIPIENT: u8 = 0x01;
+/// Mode byte: encrypted with a symmetric key
+pub const MODE_SYMMETRIC: u8 = 0x02;
+
+/// Exit code for bad arguments
+pub const EXIT_USAGE: i32 = 2;
+/// Exit code for I/O errors (missing file, permission denied, ...)
+pub const EXIT_IO: i32 = 3;
+/// Exit code for authentication failure (wrong key or tampered file)
+pub const EXIT_AUTH: i32 = 4;
+/// Exit code for malformed keys or files
+pub const EXIT_INVALID: i32 = 5;
+
+const FILE_KEY_INFO: &[u8] = b"B4AE-cli-file-v1";
+const SALT_SIZE: usize = 16;
+
+/// CLI error, one variant per exit code
+#[derive(Debug)]
+pub enum CliError {
+    /// Bad arguments
+    Usage(String),
+    /// Reading or writing a file failed
+    Io(String),
+    /// Wrong key or tampered file
+    Auth(String),
+    /// Malformed key or file
+    Invalid(String),
+}
+
+impl CliError {
+    /// Process exit code
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::Io(_) => EXIT_IO,
+            CliError::Auth(_) => EXIT_AUTH,
+            CliError::Invalid(_) => EXIT_INVALID,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(msg) => write!(f, "{}\n\n{}", msg, args::USAGE),
+            CliError::Io(msg) | CliError::Invalid(msg) => write!(f, "{}", msg),
+            CliError::Auth(msg) => write!(f, "authentication failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<StorageError> for CliError {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::Io(e) => CliError::Io(e.to_string()),
+            StorageError::CorruptChunk { .. } => {
+                CliError::Auth(format!("{} (wrong key or tampered file)", err))
+            }
+            other => CliError::Invalid(other.to_string()),
+        }
+    }
+}
+
+fn invalid(err: impl fmt::Display) -> CliError {
+    CliError::Invalid(err.to_string())
+}
+
+fn io_error(path: &Path, err: io::Error) -> CliError {
+    CliError::Io(format!("{}: {}", path.display(), err))
+}
+
+/// Run a parsed command
+pub fn run(command: Command) -> Result<(), CliError> {
+    match command {
+        Command::Help => {
+            println!("{}", args::USAGE);
+            Ok(())
+        }
+        Command::Keygen { out, public_out } => keygen(&out, &public_out),
+        Command::Encrypt { input, output, key } => encrypt(&input, &output, &key),
+        Command::Decrypt { input, output, key } => decrypt(&input, &output, &key),
+        Command::Derive { passphrase, out, salt } => derive(&passphrase, &out, salt),
+    }
+}
+
+fn keygen(out: &Path, public_out: &Path) -> Result<(), CliError> {
+    let pair = hybrid_kex::generate_keypair().map_err(invalid)?;
+    keyfile::write_key(out, KeyKind::Secret, &pair.secret_key.to_bytes())?;
+    keyfile::write_key(public_out, KeyKind::Public, &pair.public_key.to_bytes())
+}
+
+fn encrypt(input: &Path, output: &Path, key: &EncryptKey) -> Result<(), CliError> {
+    let mut prefix = Vec::with_capacity(6 + HybridKexCiphertext::serialized_size());
+    prefix.extend_from_slice(&FILE_MAGIC);
+    prefix.push(FILE_VERSION);
+
+    let secret = match key {
+        EncryptKey::Recipient(source) => {
+            let (_, bytes) = keyfile::read_key(source, &[KeyKind::Public])?;
+            let public_key = HybridKexPublicKey::from_bytes(&bytes).map_err(invalid)?;
+            let (shared, ciphertext) = hybrid_kex::encapsulate(&public_key).map_err(invalid)?;
+            prefix.push(MODE_RECIPIENT);
+            prefix.extend_from_slice(&ciphertext.to_bytes());
+            Zeroizing::new(shared.to_vec())
+        }
+        EncryptKey::Symmetric(source) => {
+            prefix.push(MODE_SYMMETRIC);
+            keyfile::read_key(source, &[KeyKind::Symmetric])?.1
+        }
+    };
+    let file_key = file_key(&secret, &prefix)?;
+
+    let reader = File::open(input).map_err(|e| io_error(input, e))?;
+    with_output(output, |out| {
+        out.write_all(&prefix).map_err(|e| io_error(output, e))?;
+        let mut writer = EncryptedWriter::new(out, &file_key, StorageCipher::XChaCha20Poly1305)?;
+        io::copy(&mut BufReader::new(reader), &mut writer).map_err(StorageError::from)?;
+        writer.finish()?.flush().map_err(|e| io_error(output, e))
+    })
+}
+
+fn decrypt(input: &Path, output: &Path, key: &Source) -> Result<(), CliError> {
+    let (kind, key_bytes) = keyfile::read_key(key, &[KeyKind::Secret, KeyKind::Symmetric])?;
+    let mut reader = BufReader::new(File::open(input).map_err(|e| io_error(input, e))?);
+
+    let mut prefix = vec![0u8; 6];
+    read_prefix(&mut reader, &mut prefix, input)?;
+    if prefix[..4] != FILE_MAGIC {
+        return Err(CliError::Invalid(format!("{}: not a b4ae-cli file", input.display())));
+    }
+    if prefix[4] != FILE_VERSION {
+        return Err(CliError::Invalid(format!("{}: unsupported version 0x{:02x}", input.display(), prefix[4])));
+    }
+
+    let secret = match (prefix[5], kind) {
+        (MODE_RECIPIENT, KeyKind::Secret) => {
+            let secret_key = HybridKexSecretKey::from_bytes(&key_bytes).map_err(invalid)?;
+            prefix.resize(6 + HybridKexCiphertext::serialized_size(), 0);
+            read_prefix(&mut reader, &mut prefix[6..], input)?;
+            let ciphertext = HybridKexCiphertext::from_bytes(&prefix[6..]).map_err(invalid)?;
+            let shared = hybrid_kex::decapsulate(&secret_key, &ciphertext).map_err(invalid)?;
+            Zeroizing::new(shared.to_vec())
+        }
+        (MODE_SYMMETRIC, KeyKind::Symmetric) => key_bytes,
+        (MODE_RECIPIENT, _) => {
+            return Err(CliError::Invalid("file was encrypted to a public key; use its secret key".to_string()))
+        }
+        (MODE_SYMMETRIC, _) => {
+            return Err(CliError::Invalid("file was encrypted with a symmetric key; use that key".to_string()))
+        }
+        (mode, _) => return Err(CliError::Invalid(format!("{}: unknown mode 0x{:02x}", input.display(), mode))),
+    };
+    let file_key = file_key(&secret, &prefix)?;
+
+    let mut plaintext = EncryptedReader::new(reader, &file_key)?;
+    with_output(output, |out| {
+        io::copy(&mut plaintext, out).map_err(StorageError::from)?;
+        out.flush().map_err(|e| io_error(output, e))
+    })
+}
+
+fn derive(passphrase: &Source, out: &Path, salt: Option<Vec<u8>>) -> Result<(), CliError> {
+    let mut passphrase = keyfile::read_source(passphrase)?;
+    if passphrase.ends_with(b"\n") {
+        passphrase.pop();
+        if passphrase.ends_with(b"\r") {
+            passphrase.pop();
+        }
+    }
+    if passphrase.is_empty() {
+        return Err(CliError::Invalid("passphrase is empty".to_string()));
+    }
+
+    let salt = match salt {
+        Some(salt) if salt.len() < SALT_SIZE => {
+            return Err(CliError::Usage(format!("--salt must be at least {} bytes", SALT_SIZE)))
+        }
+        Some(salt) => salt,
+        None => {
+            let mut salt = vec![0u8; SALT_SIZE];
+            fill_random(&mut salt).map_err(invalid)?;
+            salt
+        }
+    };
+
+    let key = KeyStore::derive_key(&passphrase, &salt, &Argon2Params::default()).map_err(invalid)?;
+    keyfile::write_key(out, KeyKind::Symmetric, key.as_bytes())?;
+    println!("salt {}", hex::encode(&salt));
+    Ok(())
+}
+
+fn file_key(secret: &[u8], prefix: &[u8]) -> Result<StorageKey, CliError> {
+    let okm = Zeroizing::new(hkdf::derive_key(&[secret, prefix], FILE_KEY_INFO, 32).map_err(invalid)?);
+    StorageKey::from_bytes(&okm).map_err(invalid)
+}
+
+fn read_prefix(reader: &mut impl Read, buf: &mut [u8], path: &Path) -> Result<(), CliError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => CliError::Invalid(format!("{}: file is truncated", path.display())),
+        _ => io_error(path, e),
+    })
+}
+
+/// Create `path` (never overwriting) and run `write`; removes the partial
+/// file if it fails, so a failed decrypt leaves no unauthenticated plaintext
+fn with_output(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), CliError>,
+) -> Result<(), CliError> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| io_error(path, e))?;
+    let result = write(&mut BufWriter::new(file));
+    if result.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_errors_map_to_exit_codes() {
+        let auth = CliError::from(StorageError::CorruptChunk { index: 3 });
+        assert_eq!(auth.exit_code(), EXIT_AUTH);
+        let io = CliError::from(StorageError::Io(io::Error::from(io::ErrorKind::PermissionDenied)));
+        assert_eq!(io.exit_code(), EXIT_IO);
+        let header = CliError::from(StorageError::InvalidHeader("bad magic".to_string()));
+        assert_eq!(header.exit_code(), EXIT_INVALID);
+    }
+
+    #[test]
+    fn test_file_key_binds_prefix() {
+        let a = file_key(&[1u8; 32], b"B4CF\x01\x02").unwrap();
+        let b = file_key(&[1u8; 32], b"B4CF\x01\x01").unwrap();
+        assert_ne!(a.as_slice(), b.as_slice());
+    }
+}
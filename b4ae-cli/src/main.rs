@@ -0,0 +1,13 @@
+//! `b4ae-cli` entry point
+
+use b4ae_cli::{args, run, CliError};
+
+fn main() {
+    let result = args::parse(std::env::args().skip(1))
+        .map_err(CliError::Usage)
+        .and_then(run);
+    if let Err(err) = result {
+        eprintln!("b4ae-cli: {}", err);
+        std::process::exit(err.exit_code());
+    }
+}
@@ -0,0 +1,215 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use b4ae_cli::{EXIT_AUTH, EXIT_INVALID, EXIT_IO, EXIT_USAGE};
+
+/// Fresh directory under the system temp dir, removed on drop
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "b4ae-cli-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn cli(args: &[&Path], stdin: Option<&[u8]>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_b4ae-cli"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut pipe = child.stdin.take().unwrap();
+    if let Some(input) = stdin {
+        pipe.write_all(input).unwrap();
+    }
+    drop(pipe);
+    child.wait_with_output().unwrap()
+}
+
+fn run_ok(args: &[&str], stdin: Option<&[u8]>) -> Output {
+    let output = cli(&args.iter().map(Path::new).collect::<Vec<_>>(), stdin);
+    assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+fn exit_code(args: &[&str], stdin: Option<&[u8]>) -> i32 {
+    cli(&args.iter().map(Path::new).collect::<Vec<_>>(), stdin).status.code().unwrap()
+}
+
+/// Deterministic, non-repeating test data spanning several stream chunks
+fn test_data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i as u32).wrapping_mul(2_654_435_761).to_be_bytes()[0]).collect()
+}
+
+fn s(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn test_keygen_encrypt_decrypt_roundtrip() {
+    let dir = TempDir::new();
+    let (id, id_pub) = (dir.path("id"), dir.path("id.pub"));
+    let (plain, sealed, opened) = (dir.path("data.bin"), dir.path("data.b4ae"), dir.path("data.out"));
+    let data = test_data(200 * 1024 + 17);
+    fs::write(&plain, &data).unwrap();
+
+    run_ok(&["keygen", "--out", s(&id)], None);
+    assert!(id_pub.exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&id).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    run_ok(&["encrypt", "--in", s(&plain), "--out", s(&sealed), "--recipient", s(&id_pub)], None);
+    assert_ne!(fs::read(&sealed).unwrap()[..], data[..]);
+    run_ok(&["decrypt", "--in", s(&sealed), "--out", s(&opened), "--key", s(&id)], None);
+    assert_eq!(fs::read(&opened).unwrap(), data);
+
+    // Secret key piped through stdin
+    let from_stdin = dir.path("stdin.out");
+    let secret = fs::read(&id).unwrap();
+    run_ok(&["decrypt", "--in", s(&sealed), "--out", s(&from_stdin), "--key", "-"], Some(&secret));
+    assert_eq!(fs::read(&from_stdin).unwrap(), data);
+
+    // Existing files are never overwritten
+    assert_eq!(exit_code(&["keygen", "--out", s(&id)], None), EXIT_IO);
+    assert_eq!(
+        exit_code(&["decrypt", "--in", s(&sealed), "--out", s(&opened), "--key", s(&id)], None),
+        EXIT_IO
+    );
+}
+
+#[test]
+fn test_wrong_key_and_tampering_fail_authentication() {
+    let dir = TempDir::new();
+    let (id, other) = (dir.path("id"), dir.path("other"));
+    let (plain, sealed) = (dir.path("data.bin"), dir.path("data.b4ae"));
+    fs::write(&plain, test_data(150 * 1024)).unwrap();
+    run_ok(&["keygen", "--out", s(&id)], None);
+    run_ok(&["keygen", "--out", s(&other)], None);
+    run_ok(&["encrypt", "--in", s(&plain), "--out", s(&sealed), "--recipient", s(&dir.path("id.pub"))], None);
+
+    let out = dir.path("wrong.out");
+    assert_eq!(
+        exit_code(&["decrypt", "--in", s(&sealed), "--out", s(&out), "--key", s(&other)], None),
+        EXIT_AUTH
+    );
+    assert!(!out.exists(), "partial plaintext left behind");
+
+    // Flip a byte in the second chunk: the first chunk decrypts, then fails
+    let mut bytes = fs::read(&sealed).unwrap();
+    let len = bytes.len();
+    bytes[len - 20 * 1024] ^= 0x01;
+    let tampered = dir.path("tampered.b4ae");
+    fs::write(&tampered, &bytes).unwrap();
+    assert_eq!(
+        exit_code(&["decrypt", "--in", s(&tampered), "--out", s(&out), "--key", s(&id)], None),
+        EXIT_AUTH
+    );
+    assert!(!out.exists(), "partial plaintext left behind");
+
+    // Truncation drops the final chunk
+    let truncated = dir.path("truncated.b4ae");
+    fs::write(&truncated, &fs::read(&sealed).unwrap()[..len - 1000]).unwrap();
+    assert_eq!(
+        exit_code(&["decrypt", "--in", s(&truncated), "--out", s(&out), "--key", s(&id)], None),
+        EXIT_AUTH
+    );
+}
+
+#[test]
+fn test_derive_symmetric_roundtrip() {
+    let dir = TempDir::new();
+    let (key, again) = (dir.path("backup.key"), dir.path("again.key"));
+    let (plain, sealed, opened) = (dir.path("data.bin"), dir.path("data.b4ae"), dir.path("data.out"));
+    let data = test_data(70 * 1024);
+    fs::write(&plain, &data).unwrap();
+
+    let output = run_ok(&["derive", "--passphrase", "-", "--out", s(&key)], Some(b"correct horse battery\n"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let salt = stdout.trim().strip_prefix("salt ").unwrap().to_string();
+
+    // Same passphrase and salt re-derive the same key; trailing CRLF is ignored
+    let passphrase = dir.path("passphrase.txt");
+    fs::write(&passphrase, b"correct horse battery\r\n").unwrap();
+    run_ok(&["derive", "--passphrase", s(&passphrase), "--out", s(&again), "--salt", &salt], None);
+    assert_eq!(fs::read(&key).unwrap(), fs::read(&again).unwrap());
+
+    run_ok(&["encrypt", "--in", s(&plain), "--out", s(&sealed), "--key", s(&key)], None);
+    run_ok(&["decrypt", "--in", s(&sealed), "--out", s(&opened), "--key", s(&again)], None);
+    assert_eq!(fs::read(&opened).unwrap(), data);
+
+    // A different passphrase gives a different key
+    let wrong = dir.path("wrong.key");
+    run_ok(&["derive", "--passphrase", "-", "--out", s(&wrong), "--salt", &salt], Some(b"incorrect horse"));
+    let out = dir.path("wrong.out");
+    assert_eq!(
+        exit_code(&["decrypt", "--in", s(&sealed), "--out", s(&out), "--key", s(&wrong)], None),
+        EXIT_AUTH
+    );
+
+    // Keypair keys cannot open symmetric files
+    run_ok(&["keygen", "--out", s(&dir.path("id"))], None);
+    assert_eq!(
+        exit_code(&["decrypt", "--in", s(&sealed), "--out", s(&out), "--key", s(&dir.path("id"))], None),
+        EXIT_INVALID
+    );
+    assert_eq!(exit_code(&["derive", "--passphrase", "-", "--out", s(&dir.path("empty.key"))], Some(b"\n")), EXIT_INVALID);
+}
+
+#[test]
+fn test_usage_and_io_errors() {
+    let dir = TempDir::new();
+    run_ok(&["keygen", "--out", s(&dir.path("id"))], None);
+
+    assert_eq!(exit_code(&[], None), EXIT_USAGE);
+    assert_eq!(exit_code(&["encrypt", "--in", "a"], None), EXIT_USAGE);
+    assert_eq!(exit_code(&["decrypt", "--in", "a", "--out", "b", "--passphrase", "p"], None), EXIT_USAGE);
+
+    let missing = dir.path("missing.bin");
+    let out = dir.path("out.b4ae");
+    assert_eq!(
+        exit_code(&["encrypt", "--in", s(&missing), "--out", s(&out), "--recipient", s(&dir.path("id.pub"))], None),
+        EXIT_IO
+    );
+    assert!(!out.exists());
+    assert_eq!(
+        exit_code(&["decrypt", "--in", s(&missing), "--out", s(&out), "--key", s(&dir.path("missing.key"))], None),
+        EXIT_IO
+    );
+
+    // Not a key file, and not a b4ae-cli file
+    let junk = dir.path("junk");
+    fs::write(&junk, b"hello").unwrap();
+    assert_eq!(
+        exit_code(&["encrypt", "--in", s(&junk), "--out", s(&out), "--recipient", s(&junk)], None),
+        EXIT_INVALID
+    );
+    assert_eq!(
+        exit_code(&["decrypt", "--in", s(&junk), "--out", s(&out), "--key", s(&dir.path("id"))], None),
+        EXIT_INVALID
+    );
+}
@@ -1,18 +1,124 @@
 //! B4AE C FFI - AES-GCM + full protocol for Swift/Kotlin bindings
 //!
-//! Default: generate_key, encrypt, decrypt (AES subset).
+//! Default: generate_key, encrypt, decrypt (AES subset), plus ChaCha20-Poly1305
+//! variants for devices without AES hardware acceleration.
+//! Streaming contexts for large files live in [`stream`].
+//! Passphrase keys: generate_salt, derive_key (Argon2id).
+//! Text transport: to_base64, from_base64 (URL-safe alphabet, no padding).
 //! With `full-protocol` feature: handshake, quantum-resistant encrypt/decrypt.
+//!
+//! The extern "C" functions draw keys, salts and nonces from the OS via
+//! getrandom. Each one is a thin shim over a public `*_with_rng` function
+//! that takes the generator as a parameter, so Rust embedders linking the
+//! rlib (FIPS modules, test vectors) can supply their own. Whatever generator
+//! is used is the root of all security here: a predictable one makes every
+//! key and ciphertext recoverable.
 
 use aes_gcm::{
-    aead::{Aead, KeyInit, Payload},
+    aead::{
+        rand_core::{CryptoRng, RngCore},
+        Aead, KeyInit, OsRng, Payload,
+    },
     Aes256Gcm,
 };
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use chacha20poly1305::ChaCha20Poly1305;
 
 const KEY_SIZE: usize = 32;
 const NONCE_SIZE: usize = 12;
 
-fn fill_random(buf: &mut [u8]) -> Result<(), getrandom::Error> {
-    getrandom::getrandom(buf)
+const SALT_SIZE: usize = 16;
+
+/// Argon2id memory cost in KiB (19 MiB, OWASP mobile-friendly baseline).
+/// Changing any Argon2 parameter changes every derived key.
+pub const B4AE_ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+/// Argon2id iteration count.
+pub const B4AE_ARGON2_ITERATIONS: u32 = 2;
+/// Argon2id parallelism (lanes).
+pub const B4AE_ARGON2_PARALLELISM: u32 = 1;
+
+/// Algorithm tag prefixed to ChaCha20-Poly1305 output: [0x02||nonce(12)||ciphertext]
+pub const B4AE_ALG_CHACHA20_POLY1305: u8 = 0x02;
+
+/// Fill `buf` from `rng`; fails instead of returning weak bytes.
+fn fill_random_with_rng<R: RngCore + CryptoRng>(rng: &mut R, buf: &mut [u8]) -> Option<()> {
+    rng.try_fill_bytes(buf).ok()
+}
+
+/// Copy `prefix||data` into a buffer from b4ae_alloc. Returns null on failure.
+fn into_ffi(prefix: &[u8], data: &[u8], out_len: *mut usize) -> *mut u8 {
+    let total_len = prefix.len() + data.len();
+    let ptr = b4ae_alloc(total_len);
+    if ptr.is_null() {
+        return std::ptr::null_mut();
+    }
+    unsafe {
+        std::ptr::copy_nonoverlapping(prefix.as_ptr(), ptr, prefix.len());
+        std::ptr::copy_nonoverlapping(data.as_ptr(), ptr.add(prefix.len()), data.len());
+        *out_len = total_len;
+    }
+    ptr
+}
+
+/// Random 32-byte key from `rng`.
+pub fn generate_key_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Option<[u8; KEY_SIZE]> {
+    let mut key = [0u8; KEY_SIZE];
+    fill_random_with_rng(rng, &mut key)?;
+    Some(key)
+}
+
+/// Random Argon2id salt from `rng`.
+pub fn generate_salt_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Option<[u8; SALT_SIZE]> {
+    let mut salt = [0u8; SALT_SIZE];
+    fill_random_with_rng(rng, &mut salt)?;
+    Some(salt)
+}
+
+/// AES-256-GCM with a nonce from `rng`. Returns [nonce(12)||ciphertext].
+pub fn encrypt_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    key: &[u8],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Option<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_SIZE];
+    fill_random_with_rng(rng, &mut nonce)?;
+    let cipher = Aes256Gcm::new_from_slice(key).ok()?;
+    let ciphertext = cipher.encrypt((&nonce).into(), Payload { msg: plaintext, aad }).ok()?;
+    let mut out = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Some(out)
+}
+
+/// ChaCha20-Poly1305 with a nonce from `rng`. Returns [alg(1)=0x02||nonce(12)||ciphertext].
+pub fn encrypt_chacha_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    key: &[u8],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Option<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_SIZE];
+    fill_random_with_rng(rng, &mut nonce)?;
+    let cipher = ChaCha20Poly1305::new_from_slice(key).ok()?;
+    let ciphertext = cipher.encrypt((&nonce).into(), Payload { msg: plaintext, aad }).ok()?;
+    let mut out = Vec::with_capacity(1 + NONCE_SIZE + ciphertext.len());
+    out.push(B4AE_ALG_CHACHA20_POLY1305);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Some(out)
+}
+
+/// Borrow an optional input buffer from FFI. Null is accepted only when len is 0.
+fn opt_slice<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        return Some(&[]);
+    }
+    if ptr.is_null() {
+        return None;
+    }
+    Some(unsafe { std::slice::from_raw_parts(ptr, len) })
 }
 
 /// Allocate buffer for FFI. Caller must free with b4ae_free.
@@ -48,8 +154,54 @@ pub extern "C" fn b4ae_generate_key(out_len: *mut usize) -> *mut u8 {
     if out_len.is_null() {
         return std::ptr::null_mut();
     }
+    match generate_key_with_rng(&mut OsRng) {
+        Some(key) => into_ffi(&[], &key, out_len),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Generate 16-byte random salt for b4ae_derive_key. Caller frees.
+/// Store the salt next to the ciphertext; it is not secret.
+#[no_mangle]
+pub extern "C" fn b4ae_generate_salt(out_len: *mut usize) -> *mut u8 {
+    if out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    match generate_salt_with_rng(&mut OsRng) {
+        Some(salt) => into_ffi(&[], &salt, out_len),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Derive 32-byte key from passphrase with Argon2id (v0x13), using
+/// B4AE_ARGON2_MEMORY_KIB / B4AE_ARGON2_ITERATIONS / B4AE_ARGON2_PARALLELISM.
+/// Same passphrase+salt yields the same key on every device.
+/// Salt must be at least 8 bytes. Returns ptr (caller frees), null on error.
+#[no_mangle]
+pub extern "C" fn b4ae_derive_key(
+    passphrase: *const u8,
+    passphrase_len: usize,
+    salt: *const u8,
+    salt_len: usize,
+    out_len: *mut usize,
+) -> *mut u8 {
+    if passphrase.is_null() || salt.is_null() || out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    let params = match Params::new(
+        B4AE_ARGON2_MEMORY_KIB,
+        B4AE_ARGON2_ITERATIONS,
+        B4AE_ARGON2_PARALLELISM,
+        Some(KEY_SIZE),
+    ) {
+        Ok(p) => p,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    let pass = unsafe { std::slice::from_raw_parts(passphrase, passphrase_len) };
+    let salt = unsafe { std::slice::from_raw_parts(salt, salt_len) };
     let mut key = [0u8; KEY_SIZE];
-    if fill_random(&mut key).is_err() {
+    if argon2.hash_password_into(pass, salt, &mut key).is_err() {
         return std::ptr::null_mut();
     }
     let ptr = b4ae_alloc(KEY_SIZE);
@@ -60,10 +212,12 @@ pub extern "C" fn b4ae_generate_key(out_len: *mut usize) -> *mut u8 {
         std::ptr::copy_nonoverlapping(key.as_ptr(), ptr, KEY_SIZE);
         *out_len = KEY_SIZE;
     }
+    key.iter_mut().for_each(|b| *b = 0);
     ptr
 }
 
 /// Encrypt plaintext. Returns [nonce(12)||ciphertext], caller frees.
+/// `aad` is authenticated but not encrypted; it may be null when aad_len is 0.
 /// Returns null on error.
 #[no_mangle]
 pub extern "C" fn b4ae_encrypt(
@@ -71,65 +225,139 @@ pub extern "C" fn b4ae_encrypt(
     key_len: usize,
     plaintext: *const u8,
     plaintext_len: usize,
+    aad: *const u8,
+    aad_len: usize,
     out_len: *mut usize,
 ) -> *mut u8 {
     if key.is_null() || plaintext.is_null() || out_len.is_null() || key_len != KEY_SIZE {
         return std::ptr::null_mut();
     }
-    let mut nonce = [0u8; NONCE_SIZE];
-    if fill_random(&mut nonce).is_err() {
+    let aad = match opt_slice(aad, aad_len) {
+        Some(a) => a,
+        None => return std::ptr::null_mut(),
+    };
+    let key = unsafe { std::slice::from_raw_parts(key, key_len) };
+    let plain = unsafe { std::slice::from_raw_parts(plaintext, plaintext_len) };
+    match encrypt_with_rng(&mut OsRng, key, plain, aad) {
+        Some(encrypted) => into_ffi(&[], &encrypted, out_len),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Decrypt [nonce(12)||ciphertext]. Caller frees result.
+/// `aad` must match the value given to b4ae_encrypt, otherwise null is returned.
+#[no_mangle]
+pub extern "C" fn b4ae_decrypt(
+    key: *const u8,
+    key_len: usize,
+    encrypted: *const u8,
+    encrypted_len: usize,
+    aad: *const u8,
+    aad_len: usize,
+    out_len: *mut usize,
+) -> *mut u8 {
+    if key.is_null()
+        || encrypted.is_null()
+        || out_len.is_null()
+        || key_len != KEY_SIZE
+        || encrypted_len < NONCE_SIZE
+    {
         return std::ptr::null_mut();
     }
+    let aad = match opt_slice(aad, aad_len) {
+        Some(a) => a,
+        None => return std::ptr::null_mut(),
+    };
+    let encrypted_slice = unsafe { std::slice::from_raw_parts(encrypted, encrypted_len) };
+    let (nonce_bytes, ciphertext) = encrypted_slice.split_at(NONCE_SIZE);
+    let nonce = aes_gcm::Nonce::from_slice(nonce_bytes);
     let cipher = match Aes256Gcm::new_from_slice(unsafe {
         std::slice::from_raw_parts(key, key_len)
     }) {
         Ok(c) => c,
         Err(_) => return std::ptr::null_mut(),
     };
-    let plain = unsafe { std::slice::from_raw_parts(plaintext, plaintext_len) };
-    let payload = Payload { msg: plain, aad: &[] };
-    let ciphertext = match cipher.encrypt((&nonce).into(), payload) {
-        Ok(ct) => ct,
+    let payload = Payload {
+        msg: ciphertext,
+        aad,
+    };
+    let plaintext = match cipher.decrypt(nonce, payload) {
+        Ok(p) => p,
         Err(_) => return std::ptr::null_mut(),
     };
-    let total_len = NONCE_SIZE + ciphertext.len();
-    let ptr = b4ae_alloc(total_len);
+    let len = plaintext.len();
+    let ptr = b4ae_alloc(len);
     if ptr.is_null() {
         return std::ptr::null_mut();
     }
     unsafe {
-        std::ptr::copy_nonoverlapping(nonce.as_ptr(), ptr, NONCE_SIZE);
-        std::ptr::copy_nonoverlapping(
-            ciphertext.as_ptr(),
-            ptr.add(NONCE_SIZE),
-            ciphertext.len(),
-        );
-        *out_len = total_len;
+        std::ptr::copy_nonoverlapping(plaintext.as_ptr(), ptr, len);
+        *out_len = len;
     }
     ptr
 }
 
-/// Decrypt [nonce(12)||ciphertext]. Caller frees result.
+/// Encrypt plaintext with ChaCha20-Poly1305.
+/// Returns [alg(1)=0x02||nonce(12)||ciphertext], caller frees.
+/// `aad` is authenticated but not encrypted; it may be null when aad_len is 0.
+/// Returns null on error.
 #[no_mangle]
-pub extern "C" fn b4ae_decrypt(
+pub extern "C" fn b4ae_encrypt_chacha(
+    key: *const u8,
+    key_len: usize,
+    plaintext: *const u8,
+    plaintext_len: usize,
+    aad: *const u8,
+    aad_len: usize,
+    out_len: *mut usize,
+) -> *mut u8 {
+    if key.is_null() || plaintext.is_null() || out_len.is_null() || key_len != KEY_SIZE {
+        return std::ptr::null_mut();
+    }
+    let aad = match opt_slice(aad, aad_len) {
+        Some(a) => a,
+        None => return std::ptr::null_mut(),
+    };
+    let key = unsafe { std::slice::from_raw_parts(key, key_len) };
+    let plain = unsafe { std::slice::from_raw_parts(plaintext, plaintext_len) };
+    match encrypt_chacha_with_rng(&mut OsRng, key, plain, aad) {
+        Some(encrypted) => into_ffi(&[], &encrypted, out_len),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Decrypt [alg(1)=0x02||nonce(12)||ciphertext]. Caller frees result.
+/// Returns null if the algorithm tag is not ChaCha20-Poly1305 or `aad` differs
+/// from the value given to b4ae_encrypt_chacha.
+#[no_mangle]
+pub extern "C" fn b4ae_decrypt_chacha(
     key: *const u8,
     key_len: usize,
     encrypted: *const u8,
     encrypted_len: usize,
+    aad: *const u8,
+    aad_len: usize,
     out_len: *mut usize,
 ) -> *mut u8 {
     if key.is_null()
         || encrypted.is_null()
         || out_len.is_null()
         || key_len != KEY_SIZE
-        || encrypted_len < NONCE_SIZE
+        || encrypted_len < 1 + NONCE_SIZE
     {
         return std::ptr::null_mut();
     }
+    let aad = match opt_slice(aad, aad_len) {
+        Some(a) => a,
+        None => return std::ptr::null_mut(),
+    };
     let encrypted_slice = unsafe { std::slice::from_raw_parts(encrypted, encrypted_len) };
-    let (nonce_bytes, ciphertext) = encrypted_slice.split_at(NONCE_SIZE);
-    let nonce = aes_gcm::Nonce::from_slice(nonce_bytes);
-    let cipher = match Aes256Gcm::new_from_slice(unsafe {
+    if encrypted_slice[0] != B4AE_ALG_CHACHA20_POLY1305 {
+        return std::ptr::null_mut();
+    }
+    let (nonce_bytes, ciphertext) = encrypted_slice[1..].split_at(NONCE_SIZE);
+    let nonce = chacha20poly1305::Nonce::from_slice(nonce_bytes);
+    let cipher = match ChaCha20Poly1305::new_from_slice(unsafe {
         std::slice::from_raw_parts(key, key_len)
     }) {
         Ok(c) => c,
@@ -137,7 +365,7 @@ pub extern "C" fn b4ae_decrypt(
     };
     let payload = Payload {
         msg: ciphertext,
-        aad: &[],
+        aad,
     };
     let plaintext = match cipher.decrypt(nonce, payload) {
         Ok(p) => p,
@@ -155,5 +383,321 @@ pub extern "C" fn b4ae_decrypt(
     ptr
 }
 
+pub mod stream;
+
 #[cfg(feature = "full-protocol")]
 pub mod full_protocol;
+
+/// Encode bytes as URL-safe base64 without padding (same as b4ae-wasm to_base64url).
+/// Returns NUL-terminated text, caller frees; *out_len excludes the NUL.
+/// `data` may be null when data_len is 0. Returns null on error.
+#[no_mangle]
+pub extern "C" fn b4ae_to_base64(data: *const u8, data_len: usize, out_len: *mut usize) -> *mut u8 {
+    if out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    let data = match opt_slice(data, data_len) {
+        Some(d) => d,
+        None => return std::ptr::null_mut(),
+    };
+    let encoded = Base64UrlUnpadded::encode_string(data);
+    let ptr = into_ffi(encoded.as_bytes(), &[0], out_len);
+    if !ptr.is_null() {
+        unsafe { *out_len = encoded.len() };
+    }
+    ptr
+}
+
+/// Decode URL-safe base64 without padding. Caller frees result.
+/// Padding, the standard `+`/`/` alphabet and whitespace are rejected.
+/// `text` may be null when text_len is 0; empty text decodes to a non-null
+/// buffer with *out_len 0. Returns null on invalid input.
+#[no_mangle]
+pub extern "C" fn b4ae_from_base64(text: *const u8, text_len: usize, out_len: *mut usize) -> *mut u8 {
+    if out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    let text = match opt_slice(text, text_len) {
+        Some(t) => t,
+        None => return std::ptr::null_mut(),
+    };
+    let text = match std::str::from_utf8(text) {
+        Ok(t) => t,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    let decoded = match Base64UrlUnpadded::decode_vec(text) {
+        Ok(decoded) => decoded,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    // Trailing NUL keeps the allocation non-empty, as in b4ae_to_base64
+    let ptr = into_ffi(&decoded, &[0], out_len);
+    if !ptr.is_null() {
+        unsafe { *out_len = decoded.len() };
+    }
+    ptr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(ptr: *mut u8, len: usize) -> Vec<u8> {
+        assert!(!ptr.is_null());
+        let v = unsafe { std::slice::from_raw_parts(ptr, len) }.to_vec();
+        b4ae_free(ptr);
+        v
+    }
+
+    fn key() -> Vec<u8> {
+        let mut len = 0;
+        let ptr = b4ae_generate_key(&mut len);
+        take(ptr, len)
+    }
+
+    fn encrypt(key: &[u8], plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+        let mut len = 0;
+        let ptr = b4ae_encrypt(
+            key.as_ptr(),
+            key.len(),
+            plaintext.as_ptr(),
+            plaintext.len(),
+            aad.as_ptr(),
+            aad.len(),
+            &mut len,
+        );
+        take(ptr, len)
+    }
+
+    fn decrypt(key: &[u8], encrypted: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+        let mut len = 0;
+        let ptr = b4ae_decrypt(
+            key.as_ptr(),
+            key.len(),
+            encrypted.as_ptr(),
+            encrypted.len(),
+            aad.as_ptr(),
+            aad.len(),
+            &mut len,
+        );
+        if ptr.is_null() {
+            None
+        } else {
+            Some(take(ptr, len))
+        }
+    }
+
+    fn encrypt_chacha(key: &[u8], plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+        let mut len = 0;
+        let ptr = b4ae_encrypt_chacha(
+            key.as_ptr(),
+            key.len(),
+            plaintext.as_ptr(),
+            plaintext.len(),
+            aad.as_ptr(),
+            aad.len(),
+            &mut len,
+        );
+        take(ptr, len)
+    }
+
+    fn decrypt_chacha(key: &[u8], encrypted: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+        let mut len = 0;
+        let ptr = b4ae_decrypt_chacha(
+            key.as_ptr(),
+            key.len(),
+            encrypted.as_ptr(),
+            encrypted.len(),
+            aad.as_ptr(),
+            aad.len(),
+            &mut len,
+        );
+        if ptr.is_null() {
+            None
+        } else {
+            Some(take(ptr, len))
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_with_aad() {
+        let key = key();
+        let encrypted = encrypt(&key, b"hello", b"msg-id:42");
+        assert_eq!(encrypted.len(), NONCE_SIZE + 5 + 16);
+        assert_eq!(decrypt(&key, &encrypted, b"msg-id:42").unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_altered_aad_rejected() {
+        let key = key();
+        let encrypted = encrypt(&key, b"hello", b"msg-id:42");
+        assert!(decrypt(&key, &encrypted, b"msg-id:43").is_none());
+        assert!(decrypt(&key, &encrypted, b"").is_none());
+    }
+
+    #[test]
+    fn test_null_aad_with_zero_len() {
+        let key = key();
+        let plaintext = b"no aad";
+        let mut len = 0;
+        let ptr = b4ae_encrypt(
+            key.as_ptr(),
+            key.len(),
+            plaintext.as_ptr(),
+            plaintext.len(),
+            std::ptr::null(),
+            0,
+            &mut len,
+        );
+        let encrypted = take(ptr, len);
+        assert_eq!(decrypt(&key, &encrypted, b"").unwrap(), plaintext);
+
+        let ptr = b4ae_encrypt(
+            key.as_ptr(),
+            key.len(),
+            plaintext.as_ptr(),
+            plaintext.len(),
+            std::ptr::null(),
+            4,
+            &mut len,
+        );
+        assert!(ptr.is_null());
+    }
+
+    #[test]
+    fn test_chacha_roundtrip() {
+        let key = key();
+        let encrypted = encrypt_chacha(&key, b"hello arm", b"sender:alice");
+        assert_eq!(encrypted[0], B4AE_ALG_CHACHA20_POLY1305);
+        assert_eq!(encrypted.len(), 1 + NONCE_SIZE + 9 + 16);
+        assert_eq!(
+            decrypt_chacha(&key, &encrypted, b"sender:alice").unwrap(),
+            b"hello arm"
+        );
+        assert!(decrypt_chacha(&key, &encrypted, b"sender:bob").is_none());
+    }
+
+    #[test]
+    fn test_chacha_rejects_aes_gcm_ciphertext() {
+        let key = key();
+        let aes = encrypt(&key, b"hello", b"");
+        assert!(decrypt_chacha(&key, &aes, b"").is_none());
+
+        // Even with a forged algorithm tag the AEAD check must fail.
+        let mut forged = vec![B4AE_ALG_CHACHA20_POLY1305];
+        forged.extend_from_slice(&aes);
+        assert!(decrypt_chacha(&key, &forged, b"").is_none());
+
+        let chacha = encrypt_chacha(&key, b"hello", b"");
+        assert!(decrypt(&key, &chacha, b"").is_none());
+    }
+
+    #[test]
+    fn test_with_rng_reproducible() {
+        use rand_chacha::rand_core::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        let run = || {
+            let mut rng = ChaCha20Rng::from_seed([5u8; 32]);
+            let key = generate_key_with_rng(&mut rng).unwrap();
+            let salt = generate_salt_with_rng(&mut rng).unwrap();
+            let aes = encrypt_with_rng(&mut rng, &key, b"hello", b"aad").unwrap();
+            let chacha = encrypt_chacha_with_rng(&mut rng, &key, b"hello", b"aad").unwrap();
+            (key, salt, aes, chacha)
+        };
+
+        let (key, salt, aes, chacha) = run();
+        assert_eq!(run(), (key, salt, aes.clone(), chacha.clone()));
+        assert_ne!(key, generate_key_with_rng(&mut ChaCha20Rng::from_seed([6u8; 32])).unwrap());
+
+        // Output stays compatible with the extern "C" decrypt functions
+        assert_eq!(decrypt(&key, &aes, b"aad").unwrap(), b"hello");
+        assert_eq!(decrypt_chacha(&key, &chacha, b"aad").unwrap(), b"hello");
+    }
+
+    fn derive(passphrase: &[u8], salt: &[u8]) -> Option<Vec<u8>> {
+        let mut len = 0;
+        let ptr = b4ae_derive_key(
+            passphrase.as_ptr(),
+            passphrase.len(),
+            salt.as_ptr(),
+            salt.len(),
+            &mut len,
+        );
+        if ptr.is_null() {
+            None
+        } else {
+            Some(take(ptr, len))
+        }
+    }
+
+    #[test]
+    fn test_derive_key_deterministic() {
+        let mut len = 0;
+        let salt = take(b4ae_generate_salt(&mut len), len);
+        assert_eq!(salt.len(), SALT_SIZE);
+
+        let k1 = derive(b"correct horse battery staple", &salt).unwrap();
+        let k2 = derive(b"correct horse battery staple", &salt).unwrap();
+        assert_eq!(k1.len(), KEY_SIZE);
+        assert_eq!(k1, k2);
+
+        let other_salt = take(b4ae_generate_salt(&mut len), len);
+        assert_ne!(salt, other_salt);
+        let k3 = derive(b"correct horse battery staple", &other_salt).unwrap();
+        assert_ne!(k1, k3);
+    }
+
+    #[test]
+    fn test_derive_key_rejects_short_salt() {
+        assert!(derive(b"passphrase", b"short").is_none());
+    }
+
+    fn to_base64(data: &[u8]) -> Vec<u8> {
+        let mut len = 0;
+        let ptr = b4ae_to_base64(data.as_ptr(), data.len(), &mut len);
+        assert_eq!(unsafe { *ptr.add(len) }, 0);
+        take(ptr, len)
+    }
+
+    fn from_base64(text: &[u8]) -> Option<Vec<u8>> {
+        let mut len = 0;
+        let ptr = b4ae_from_base64(text.as_ptr(), text.len(), &mut len);
+        if ptr.is_null() {
+            None
+        } else {
+            Some(take(ptr, len))
+        }
+    }
+
+    #[test]
+    fn test_base64_roundtrip() {
+        assert_eq!(to_base64(&[0xfb, 0xff, 0xbf]), b"-_-_");
+        assert_eq!(to_base64(b"B4AE"), b"QjRBRQ");
+        let mut len = 7;
+        let ptr = b4ae_to_base64(std::ptr::null(), 0, &mut len);
+        assert_eq!(len, 0);
+        assert_eq!(take(ptr, len), b"");
+
+        assert_eq!(from_base64(b"").unwrap(), b"");
+        let mut len = 7;
+        let ptr = b4ae_from_base64(std::ptr::null(), 0, &mut len);
+        assert_eq!(len, 0);
+        assert_eq!(take(ptr, len), b"");
+
+        let data: Vec<u8> = (0..=255).collect();
+        for n in 0..data.len() {
+            let encoded = to_base64(&data[..n]);
+            assert_eq!(from_base64(&encoded).unwrap(), &data[..n]);
+        }
+    }
+
+    #[test]
+    fn test_base64_rejects_invalid() {
+        assert!(from_base64(b"QjRBRQ==").is_none());
+        assert!(from_base64(b"+/+/").is_none());
+        assert!(from_base64(b"QjRB RQ").is_none());
+        assert!(from_base64(b"QjRBR").is_none());
+        assert!(from_base64(b"QjRBRR").is_none());
+        assert!(from_base64(&[0xff, 0xfe]).is_none());
+    }
+}
@@ -0,0 +1,366 @@
+//! B4AE Streaming FFI - chunked AES-256-GCM for large files
+//!
+//! Uses the RustCrypto STREAM construction (big-endian 32-bit counter):
+//! every chunk gets a distinct nonce derived from a random 7-byte base nonce
+//! plus the chunk counter, and the final chunk carries a "last" flag so that
+//! truncation is detected on decrypt.
+//!
+//! Framing: the first output of an encrypt context is prefixed with the
+//! 7-byte base nonce. Each buffer returned by `b4ae_encrypt_update` /
+//! `b4ae_encrypt_final` must be passed, unchanged and in order, to exactly one
+//! `b4ae_decrypt_update` / `b4ae_decrypt_final` call.
+
+use aes_gcm::{
+    aead::{
+        rand_core::{CryptoRng, RngCore},
+        stream::{DecryptorBE32, EncryptorBE32},
+        KeyInit, OsRng,
+    },
+    Aes256Gcm,
+};
+
+use crate::{fill_random_with_rng, into_ffi, KEY_SIZE};
+
+/// Base nonce size for STREAM over AES-256-GCM (12-byte nonce - 5-byte counter/flag)
+pub const STREAM_NONCE_SIZE: usize = 7;
+
+/// Opaque streaming encryption context. Free with b4ae_ctx_free.
+pub struct B4aeEncryptCtx {
+    nonce: [u8; STREAM_NONCE_SIZE],
+    nonce_sent: bool,
+    encryptor: Option<EncryptorBE32<Aes256Gcm>>,
+}
+
+/// Opaque streaming decryption context. Free with b4ae_decrypt_ctx_free.
+pub struct B4aeDecryptCtx {
+    cipher: Aes256Gcm,
+    decryptor: Option<DecryptorBE32<Aes256Gcm>>,
+    finished: bool,
+}
+
+impl B4aeEncryptCtx {
+    /// Context with a base nonce from `rng`. None on invalid key or RNG failure.
+    fn new_with_rng<R: RngCore + CryptoRng>(rng: &mut R, key: &[u8]) -> Option<Self> {
+        let cipher = Aes256Gcm::new_from_slice(key).ok()?;
+        let mut nonce = [0u8; STREAM_NONCE_SIZE];
+        fill_random_with_rng(rng, &mut nonce)?;
+        let encryptor = EncryptorBE32::from_aead(cipher, (&nonce).into());
+        Some(B4aeEncryptCtx {
+            nonce,
+            nonce_sent: false,
+            encryptor: Some(encryptor),
+        })
+    }
+
+    /// Nonce prefix to emit before the next chunk (only for the first one).
+    fn take_prefix(&mut self) -> Vec<u8> {
+        if self.nonce_sent {
+            Vec::new()
+        } else {
+            self.nonce_sent = true;
+            self.nonce.to_vec()
+        }
+    }
+}
+
+impl B4aeDecryptCtx {
+    /// Strip the base nonce from the first chunk and set up the decryptor.
+    fn start<'a>(&mut self, chunk: &'a [u8]) -> Option<&'a [u8]> {
+        if self.decryptor.is_some() {
+            return Some(chunk);
+        }
+        if chunk.len() < STREAM_NONCE_SIZE {
+            return None;
+        }
+        let (nonce, rest) = chunk.split_at(STREAM_NONCE_SIZE);
+        self.decryptor = Some(DecryptorBE32::from_aead(
+            self.cipher.clone(),
+            nonce.into(),
+        ));
+        Some(rest)
+    }
+}
+
+/// Create streaming encryption context with a random base nonce.
+/// Returns null on invalid key or RNG failure.
+#[no_mangle]
+pub extern "C" fn b4ae_encrypt_init(key: *const u8, key_len: usize) -> *mut B4aeEncryptCtx {
+    if key.is_null() || key_len != KEY_SIZE {
+        return std::ptr::null_mut();
+    }
+    let key = unsafe { std::slice::from_raw_parts(key, key_len) };
+    match B4aeEncryptCtx::new_with_rng(&mut OsRng, key) {
+        Some(ctx) => Box::into_raw(Box::new(ctx)),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Encrypt one chunk. Returns [nonce(7)]?||ciphertext||tag(16), caller frees.
+/// `chunk` may be null when chunk_len is 0.
+/// Returns null on error or after b4ae_encrypt_final.
+#[no_mangle]
+pub extern "C" fn b4ae_encrypt_update(
+    ctx: *mut B4aeEncryptCtx,
+    chunk: *const u8,
+    chunk_len: usize,
+    out_len: *mut usize,
+) -> *mut u8 {
+    if ctx.is_null() || out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    let ctx = unsafe { &mut *ctx };
+    let plain = match crate::opt_slice(chunk, chunk_len) {
+        Some(p) => p,
+        None => return std::ptr::null_mut(),
+    };
+    let ciphertext = match ctx.encryptor.as_mut() {
+        Some(enc) => match enc.encrypt_next(plain) {
+            Ok(ct) => ct,
+            Err(_) => return std::ptr::null_mut(),
+        },
+        None => return std::ptr::null_mut(),
+    };
+    let prefix = ctx.take_prefix();
+    into_ffi(&prefix, &ciphertext, out_len)
+}
+
+/// Finish the stream. Returns [nonce(7)]?||tag(16) of the empty last chunk,
+/// caller frees. The context can no longer encrypt but must still be freed.
+#[no_mangle]
+pub extern "C" fn b4ae_encrypt_final(ctx: *mut B4aeEncryptCtx, out_len: *mut usize) -> *mut u8 {
+    if ctx.is_null() || out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    let ctx = unsafe { &mut *ctx };
+    let ciphertext = match ctx.encryptor.take() {
+        Some(enc) => match enc.encrypt_last(&[][..]) {
+            Ok(ct) => ct,
+            Err(_) => return std::ptr::null_mut(),
+        },
+        None => return std::ptr::null_mut(),
+    };
+    let prefix = ctx.take_prefix();
+    into_ffi(&prefix, &ciphertext, out_len)
+}
+
+/// Free encryption context. Safe to call with null.
+#[no_mangle]
+pub extern "C" fn b4ae_ctx_free(ctx: *mut B4aeEncryptCtx) {
+    if !ctx.is_null() {
+        unsafe { drop(Box::from_raw(ctx)); }
+    }
+}
+
+/// Create streaming decryption context. Returns null on invalid key.
+#[no_mangle]
+pub extern "C" fn b4ae_decrypt_init(key: *const u8, key_len: usize) -> *mut B4aeDecryptCtx {
+    if key.is_null() || key_len != KEY_SIZE {
+        return std::ptr::null_mut();
+    }
+    let cipher = match Aes256Gcm::new_from_slice(unsafe {
+        std::slice::from_raw_parts(key, key_len)
+    }) {
+        Ok(c) => c,
+        Err(_) => return std::ptr::null_mut(),
+    };
+    Box::into_raw(Box::new(B4aeDecryptCtx {
+        cipher,
+        decryptor: None,
+        finished: false,
+    }))
+}
+
+/// Decrypt one chunk produced by b4ae_encrypt_update. Caller frees result.
+/// Returns null on tampering, reordering, or if the chunk is the final one.
+/// An empty plaintext chunk yields a non-null buffer with *out_len = 0.
+#[no_mangle]
+pub extern "C" fn b4ae_decrypt_update(
+    ctx: *mut B4aeDecryptCtx,
+    chunk: *const u8,
+    chunk_len: usize,
+    out_len: *mut usize,
+) -> *mut u8 {
+    if ctx.is_null() || chunk.is_null() || out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    let ctx = unsafe { &mut *ctx };
+    if ctx.finished {
+        return std::ptr::null_mut();
+    }
+    let data = unsafe { std::slice::from_raw_parts(chunk, chunk_len) };
+    let ciphertext = match ctx.start(data) {
+        Some(ct) => ct,
+        None => return std::ptr::null_mut(),
+    };
+    let plaintext = match ctx.decryptor.as_mut() {
+        Some(dec) => match dec.decrypt_next(ciphertext) {
+            Ok(p) => p,
+            Err(_) => return std::ptr::null_mut(),
+        },
+        None => return std::ptr::null_mut(),
+    };
+    // Trailing NUL keeps the allocation non-empty for an empty chunk
+    let ptr = into_ffi(&plaintext, &[0], out_len);
+    if !ptr.is_null() {
+        unsafe { *out_len = plaintext.len() };
+    }
+    ptr
+}
+
+/// Verify the final chunk produced by b4ae_encrypt_final.
+/// Returns 0 when the stream is complete and authentic, -1 otherwise
+/// (including when the final chunk was dropped and a regular chunk is given).
+#[no_mangle]
+pub extern "C" fn b4ae_decrypt_final(
+    ctx: *mut B4aeDecryptCtx,
+    chunk: *const u8,
+    chunk_len: usize,
+) -> i32 {
+    if ctx.is_null() || chunk.is_null() {
+        return -1;
+    }
+    let ctx = unsafe { &mut *ctx };
+    if ctx.finished {
+        return -1;
+    }
+    let data = unsafe { std::slice::from_raw_parts(chunk, chunk_len) };
+    let ciphertext = match ctx.start(data) {
+        Some(ct) => ct,
+        None => return -1,
+    };
+    ctx.finished = true;
+    match ctx.decryptor.take() {
+        Some(dec) => match dec.decrypt_last(ciphertext) {
+            Ok(p) if p.is_empty() => 0,
+            _ => -1,
+        },
+        None => -1,
+    }
+}
+
+/// Free decryption context. Safe to call with null.
+#[no_mangle]
+pub extern "C" fn b4ae_decrypt_ctx_free(ctx: *mut B4aeDecryptCtx) {
+    if !ctx.is_null() {
+        unsafe { drop(Box::from_raw(ctx)); }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::b4ae_free;
+
+    const KEY: [u8; KEY_SIZE] = [7u8; KEY_SIZE];
+
+    fn take(ptr: *mut u8, len: usize) -> Vec<u8> {
+        assert!(!ptr.is_null());
+        let v = unsafe { std::slice::from_raw_parts(ptr, len) }.to_vec();
+        b4ae_free(ptr);
+        v
+    }
+
+    fn encrypt_chunks(chunks: &[&[u8]]) -> (Vec<Vec<u8>>, Vec<u8>) {
+        let ctx = b4ae_encrypt_init(KEY.as_ptr(), KEY.len());
+        assert!(!ctx.is_null());
+        let mut out = Vec::new();
+        for chunk in chunks {
+            let mut len = 0;
+            out.push(take(b4ae_encrypt_update(ctx, chunk.as_ptr(), chunk.len(), &mut len), len));
+        }
+        let mut len = 0;
+        let last = take(b4ae_encrypt_final(ctx, &mut len), len);
+        b4ae_ctx_free(ctx);
+        (out, last)
+    }
+
+    fn decrypt_chunk(ctx: *mut B4aeDecryptCtx, chunk: &[u8]) -> Option<Vec<u8>> {
+        let mut len = 0;
+        let ptr = b4ae_decrypt_update(ctx, chunk.as_ptr(), chunk.len(), &mut len);
+        if ptr.is_null() {
+            None
+        } else {
+            Some(take(ptr, len))
+        }
+    }
+
+    #[test]
+    fn test_multi_chunk_roundtrip() {
+        let chunks: [&[u8]; 3] = [b"first chunk", b"second", b"third and last data"];
+        let (encrypted, last) = encrypt_chunks(&chunks);
+        assert_eq!(encrypted[0].len(), STREAM_NONCE_SIZE + chunks[0].len() + 16);
+        assert_eq!(encrypted[1].len(), chunks[1].len() + 16);
+        assert_eq!(last.len(), 16);
+
+        let ctx = b4ae_decrypt_init(KEY.as_ptr(), KEY.len());
+        let mut plaintext = Vec::new();
+        for chunk in &encrypted {
+            plaintext.extend(decrypt_chunk(ctx, chunk).unwrap());
+        }
+        assert_eq!(b4ae_decrypt_final(ctx, last.as_ptr(), last.len()), 0);
+        b4ae_decrypt_ctx_free(ctx);
+        assert_eq!(plaintext, chunks.concat());
+    }
+
+    #[test]
+    fn test_empty_chunk_roundtrip() {
+        let chunks: [&[u8]; 3] = [b"", b"data", b""];
+        let (encrypted, last) = encrypt_chunks(&chunks);
+        let ctx = b4ae_encrypt_init(KEY.as_ptr(), KEY.len());
+        let mut len = 0;
+        let ptr = b4ae_encrypt_update(ctx, std::ptr::null(), 0, &mut len);
+        assert_eq!(take(ptr, len).len(), STREAM_NONCE_SIZE + 16);
+        b4ae_ctx_free(ctx);
+
+        let ctx = b4ae_decrypt_init(KEY.as_ptr(), KEY.len());
+        let decrypted: Vec<Vec<u8>> = encrypted
+            .iter()
+            .map(|chunk| decrypt_chunk(ctx, chunk).unwrap())
+            .collect();
+        assert_eq!(decrypted, chunks);
+        assert_eq!(b4ae_decrypt_final(ctx, last.as_ptr(), last.len()), 0);
+        b4ae_decrypt_ctx_free(ctx);
+    }
+
+    #[test]
+    fn test_dropped_final_chunk_fails() {
+        let (encrypted, _last) = encrypt_chunks(&[b"a", b"b", b"c"]);
+        let ctx = b4ae_decrypt_init(KEY.as_ptr(), KEY.len());
+        decrypt_chunk(ctx, &encrypted[0]).unwrap();
+        decrypt_chunk(ctx, &encrypted[1]).unwrap();
+        // Presenting a regular chunk as final must not authenticate.
+        let tail = &encrypted[2];
+        assert_eq!(b4ae_decrypt_final(ctx, tail.as_ptr(), tail.len()), -1);
+        b4ae_decrypt_ctx_free(ctx);
+    }
+
+    #[test]
+    fn test_reordered_chunks_fail() {
+        let (encrypted, _last) = encrypt_chunks(&[b"a", b"b", b"c"]);
+        let ctx = b4ae_decrypt_init(KEY.as_ptr(), KEY.len());
+        decrypt_chunk(ctx, &encrypted[0]).unwrap();
+        assert!(decrypt_chunk(ctx, &encrypted[2]).is_none());
+        b4ae_decrypt_ctx_free(ctx);
+    }
+
+    #[test]
+    fn test_empty_stream() {
+        let (encrypted, last) = encrypt_chunks(&[]);
+        assert!(encrypted.is_empty());
+        assert_eq!(last.len(), STREAM_NONCE_SIZE + 16);
+        let ctx = b4ae_decrypt_init(KEY.as_ptr(), KEY.len());
+        assert_eq!(b4ae_decrypt_final(ctx, last.as_ptr(), last.len()), 0);
+        b4ae_decrypt_ctx_free(ctx);
+    }
+
+    #[test]
+    fn test_update_after_final_fails() {
+        let ctx = b4ae_encrypt_init(KEY.as_ptr(), KEY.len());
+        let mut len = 0;
+        let last = b4ae_encrypt_final(ctx, &mut len);
+        b4ae_free(last);
+        let data = b"late";
+        assert!(b4ae_encrypt_update(ctx, data.as_ptr(), data.len(), &mut len).is_null());
+        b4ae_ctx_free(ctx);
+    }
+}
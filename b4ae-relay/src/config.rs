@@ -0,0 +1,129 @@
+//! Relay configuration from CLI arguments and environment
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Default listen address
+pub const DEFAULT_BIND: &str = "0.0.0.0:8473";
+/// Default per-source packet rate (packets/sec)
+pub const DEFAULT_RATE: f64 = 200.0;
+/// Default per-source burst (packets)
+pub const DEFAULT_BURST: f64 = 400.0;
+/// Default rate for cookie requests from unadmitted sources, shared (packets/sec)
+pub const DEFAULT_CHALLENGE_RATE: f64 = 1000.0;
+
+/// Relay settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelayConfig {
+    /// Listen address
+    pub bind: SocketAddr,
+    /// Per-source packet rate for admitted sources
+    pub rate: f64,
+    /// Per-source burst for admitted sources
+    pub burst: f64,
+    /// Global rate for cookie traffic from unadmitted sources
+    pub challenge_rate: f64,
+    /// Bound on admitted sources and on routed sessions
+    pub max_sessions: usize,
+    /// Time after which an unused route or admitted source is forgotten
+    pub idle_timeout: Duration,
+}
+
+impl Default for RelayConfig {
+    fn default() -> Self {
+        Self {
+            bind: DEFAULT_BIND.parse().expect("valid default bind address"),
+            rate: DEFAULT_RATE,
+            burst: DEFAULT_BURST,
+            challenge_rate: DEFAULT_CHALLENGE_RATE,
+            max_sessions: crate::DEFAULT_MAX_SESSIONS,
+            idle_timeout: crate::DEFAULT_IDLE_TIMEOUT,
+        }
+    }
+}
+
+impl RelayConfig {
+    /// Build from `--bind/--rate/--burst/--challenge-rate/--max-sessions/
+    /// --idle-timeout`, falling back to `B4AE_RELAY_BIND`, `B4AE_RELAY_RATE`,
+    /// `B4AE_RELAY_BURST`, `B4AE_RELAY_CHALLENGE_RATE`,
+    /// `B4AE_RELAY_MAX_SESSIONS`, `B4AE_RELAY_IDLE_TIMEOUT`, then defaults
+    pub fn from_args_and_env(
+        args: impl IntoIterator<Item = String>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, String> {
+        let mut config = Self::default();
+        let mut values: Vec<(String, String)> = Vec::new();
+        for (flag, var) in [
+            ("--bind", "B4AE_RELAY_BIND"),
+            ("--rate", "B4AE_RELAY_RATE"),
+            ("--burst", "B4AE_RELAY_BURST"),
+            ("--challenge-rate", "B4AE_RELAY_CHALLENGE_RATE"),
+            ("--max-sessions", "B4AE_RELAY_MAX_SESSIONS"),
+            ("--idle-timeout", "B4AE_RELAY_IDLE_TIMEOUT"),
+        ] {
+            if let Some(v) = env(var) {
+                values.push((flag.to_string(), v));
+            }
+        }
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| format!("missing value for {}", flag))?;
+            values.push((flag, value));
+        }
+
+        for (flag, value) in values {
+            let invalid = |e: &dyn std::fmt::Display| format!("invalid {} '{}': {}", flag, value, e);
+            match flag.as_str() {
+                "--bind" => config.bind = value.parse().map_err(|e| invalid(&e))?,
+                "--rate" => config.rate = parse_positive(&value).map_err(|e| invalid(&e))?,
+                "--burst" => config.burst = parse_positive(&value).map_err(|e| invalid(&e))?,
+                "--challenge-rate" => {
+                    config.challenge_rate = parse_positive(&value).map_err(|e| invalid(&e))?
+                }
+                "--max-sessions" => config.max_sessions = value.parse().map_err(|e| invalid(&e))?,
+                "--idle-timeout" => {
+                    let secs = parse_positive(&value).map_err(|e| invalid(&e))?;
+                    config.idle_timeout = Duration::try_from_secs_f64(secs).map_err(|e| invalid(&e))?
+                }
+                other => return Err(format!("unknown argument {}", other)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn parse_positive(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(v) if v.is_finite() && v > 0.0 => Ok(v),
+        Ok(_) => Err("must be a positive number".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args_override_env() {
+        let env = |k: &str| match k {
+            "B4AE_RELAY_RATE" => Some("5".to_string()),
+            "B4AE_RELAY_BURST" => Some("7".to_string()),
+            _ => None,
+        };
+        let args = ["--rate", "9", "--bind", "127.0.0.1:9000"].map(String::from);
+        let config = RelayConfig::from_args_and_env(args, env).unwrap();
+        assert_eq!(config.rate, 9.0);
+        assert_eq!(config.burst, 7.0);
+        assert_eq!(config.bind, "127.0.0.1:9000".parse().unwrap());
+    }
+
+    #[test]
+    fn test_rejects_invalid_values() {
+        let none = |_: &str| None;
+        assert!(RelayConfig::from_args_and_env(["--rate", "0"].map(String::from), none).is_err());
+        assert!(RelayConfig::from_args_and_env(["--burst"].map(String::from), none).is_err());
+        assert!(RelayConfig::from_args_and_env(["--bogus", "1"].map(String::from), none).is_err());
+        assert!(RelayConfig::from_args_and_env(["--idle-timeout", "1e300"].map(String::from), none).is_err());
+    }
+}
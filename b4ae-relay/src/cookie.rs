@@ -0,0 +1,139 @@
+//! Stateless cookie admission for new sources
+//!
+//! Relay control datagrams start with [`CONTROL_MAGIC`], which can never be
+//! the first byte of a B4AE message (version 1.0 starts with `0x01`).
+//!
+//! ```text
+//! request:   [0xB4][0x01][client_random 32][zero padding 8]
+//! challenge: [0xB4][0x02][timestamp u64 BE][cookie 32]
+//! echo:      [0xB4][0x03][client_random 32][timestamp u64 BE][cookie 32]
+//! ```
+//!
+//! The request is padded to the challenge size so the relay never amplifies.
+//! Cookies expire after [`COOKIE_TIMEOUT_SECONDS`] and are single use: the
+//! relay remembers every accepted cookie until it expires, so a captured
+//! echo cannot be replayed to admit another source.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use b4ae::protocol::v2::cookie_challenge::{generate_cookie, verify_cookie, ServerSecret};
+use b4ae::protocol::v2::{COOKIE_SIZE, COOKIE_TIMEOUT_SECONDS};
+
+/// First byte of relay control datagrams
+pub const CONTROL_MAGIC: u8 = 0xB4;
+/// Cookie request
+pub const COOKIE_REQUEST: u8 = 0x01;
+/// Cookie challenge (relay → client)
+pub const COOKIE_CHALLENGE: u8 = 0x02;
+/// Cookie echo (client → relay)
+pub const COOKIE_ECHO: u8 = 0x03;
+
+const CLIENT_RANDOM_SIZE: usize = 32;
+/// Size of a challenge datagram
+pub const CHALLENGE_SIZE: usize = 2 + 8 + COOKIE_SIZE;
+/// Size of a request datagram (padded to [`CHALLENGE_SIZE`])
+pub const REQUEST_SIZE: usize = CHALLENGE_SIZE;
+/// Size of an echo datagram
+pub const ECHO_SIZE: usize = 2 + CLIENT_RANDOM_SIZE + 8 + COOKIE_SIZE;
+/// Bound on remembered spent cookies; echoes are refused while it is full
+pub const MAX_SPENT_COOKIES: usize = 65_536;
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Build a cookie request for `client_random`
+pub fn request(client_random: &[u8; CLIENT_RANDOM_SIZE]) -> Vec<u8> {
+    let mut out = vec![CONTROL_MAGIC, COOKIE_REQUEST];
+    out.extend_from_slice(client_random);
+    out.resize(REQUEST_SIZE, 0);
+    out
+}
+
+/// Build the echo for a received challenge, or `None` if it is malformed
+pub fn echo(client_random: &[u8; CLIENT_RANDOM_SIZE], challenge: &[u8]) -> Option<Vec<u8>> {
+    if challenge.len() != CHALLENGE_SIZE || challenge[..2] != [CONTROL_MAGIC, COOKIE_CHALLENGE] {
+        return None;
+    }
+    let mut out = vec![CONTROL_MAGIC, COOKIE_ECHO];
+    out.extend_from_slice(client_random);
+    out.extend_from_slice(&challenge[2..]);
+    Some(out)
+}
+
+/// Issues and verifies single-use cookies bound to the source IP
+pub struct CookieIssuer {
+    secret: ServerSecret,
+    /// Accepted cookies → their timestamp, kept until they expire
+    spent: HashMap<[u8; COOKIE_SIZE], u64>,
+}
+
+impl CookieIssuer {
+    /// Issuer with a freshly generated secret
+    pub fn new() -> Self {
+        Self {
+            secret: ServerSecret::generate(),
+            spent: HashMap::new(),
+        }
+    }
+
+    /// Answer a request with a challenge; `None` if the request is malformed
+    pub fn challenge(&self, datagram: &[u8], src: SocketAddr) -> Option<Vec<u8>> {
+        if datagram.len() != REQUEST_SIZE {
+            return None;
+        }
+        let client_random = &datagram[2..2 + CLIENT_RANDOM_SIZE];
+        let timestamp = now_secs();
+        let cookie = generate_cookie(&self.secret, &src.ip().to_string(), timestamp, client_random).ok()?;
+        let mut out = vec![CONTROL_MAGIC, COOKIE_CHALLENGE];
+        out.extend_from_slice(&timestamp.to_be_bytes());
+        out.extend_from_slice(&cookie);
+        Some(out)
+    }
+
+    /// Whether an echo carries a valid, fresh, unspent cookie for `src`;
+    /// an accepted cookie is spent
+    pub fn verify(&mut self, datagram: &[u8], src: SocketAddr) -> bool {
+        if datagram.len() != ECHO_SIZE {
+            return false;
+        }
+        let client_random = &datagram[2..2 + CLIENT_RANDOM_SIZE];
+        let ts_start = 2 + CLIENT_RANDOM_SIZE;
+        let mut timestamp = [0u8; 8];
+        timestamp.copy_from_slice(&datagram[ts_start..ts_start + 8]);
+        let timestamp = u64::from_be_bytes(timestamp);
+        let mut cookie = [0u8; COOKIE_SIZE];
+        cookie.copy_from_slice(&datagram[ts_start + 8..]);
+        if verify_cookie(&cookie, &self.secret, &src.ip().to_string(), timestamp, client_random).is_err()
+            || self.spent.contains_key(&cookie)
+        {
+            return false;
+        }
+        if self.spent.len() >= MAX_SPENT_COOKIES {
+            self.expire();
+            if self.spent.len() >= MAX_SPENT_COOKIES {
+                return false;
+            }
+        }
+        self.spent.insert(cookie, timestamp);
+        true
+    }
+
+    /// Forget spent cookies that have expired and can no longer verify
+    pub fn expire(&mut self) {
+        let now = now_secs();
+        self.spent
+            .retain(|_, timestamp| timestamp.saturating_add(COOKIE_TIMEOUT_SECONDS) >= now);
+    }
+}
+
+impl Default for CookieIssuer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,290 @@
+//! B4AE Secure Relay
+//!
+//! Oblivious UDP relay: each datagram is a complete B4AE network message
+//! (`SecurityMessageHeader` + payload). The first [`SESSION_ID_SIZE`] bytes
+//! of the payload are the plaintext relay envelope carrying the routing
+//! session ID; everything after it is opaque ciphertext the relay never
+//! decrypts. Datagrams are forwarded byte-for-byte.
+//!
+//! Routes are learned per session: the first source seen for a session ID
+//! becomes one endpoint, the next distinct source the other, and every later
+//! datagram from one endpoint is forwarded to the other. A route neither
+//! endpoint has used for the configured idle timeout is forgotten, so the
+//! session table does not fill up with abandoned sessions.
+//!
+//! A new source must first complete the stateless cookie exchange in
+//! [`cookie`]; until then the relay keeps no state for it. Admitted sources
+//! are rate limited per `SocketAddr` and must repeat the exchange after the
+//! idle timeout. Anything else is dropped silently.
+
+pub mod config;
+pub mod cookie;
+pub mod limiter;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use b4ae::security::SecurityNetworkParser;
+use tokio::net::UdpSocket;
+
+use crate::config::RelayConfig;
+use crate::cookie::{CookieIssuer, CONTROL_MAGIC, COOKIE_ECHO, COOKIE_REQUEST};
+use crate::limiter::{RateLimiter, TokenBucket};
+
+/// Size of the routing session ID at the start of the payload
+pub const SESSION_ID_SIZE: usize = 16;
+/// Default bound on tracked sessions
+pub const DEFAULT_MAX_SESSIONS: usize = 65_536;
+/// Default time after which an unused route is forgotten
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+/// How often the relay sweeps idle state
+const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+/// Largest UDP datagram the relay reads
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+/// Routing session ID
+pub type SessionId = [u8; SESSION_ID_SIZE];
+
+#[derive(Debug, Clone, Copy)]
+struct Route {
+    first: SocketAddr,
+    second: Option<SocketAddr>,
+    /// Last time either endpoint sent on this route
+    last_seen: Instant,
+}
+
+/// Session ID → endpoint pair routing table
+#[derive(Debug)]
+pub struct RoutingTable {
+    sessions: HashMap<SessionId, Route>,
+    max_sessions: usize,
+    idle_timeout: Duration,
+}
+
+impl RoutingTable {
+    /// Create an empty table tracking at most `max_sessions` sessions, each
+    /// forgotten after `idle_timeout` without traffic from its endpoints
+    pub fn new(max_sessions: usize, idle_timeout: Duration) -> Self {
+        Self {
+            sessions: HashMap::new(),
+            max_sessions,
+            idle_timeout,
+        }
+    }
+
+    /// Record `src` for `session` at `now` and return the peer to forward
+    /// to, if known
+    pub fn route(&mut self, session: SessionId, src: SocketAddr, now: Instant) -> Option<SocketAddr> {
+        if let Some(route) = self.sessions.get_mut(&session) {
+            if now.saturating_duration_since(route.last_seen) < self.idle_timeout {
+                let (peer, endpoint) = match route.second {
+                    Some(second) if src == route.first => (Some(second), true),
+                    Some(second) if src == second => (Some(route.first), true),
+                    // Session already has both endpoints; third parties are
+                    // dropped and do not keep the route alive
+                    Some(_) => (None, false),
+                    None if src == route.first => (None, true),
+                    None => {
+                        route.second = Some(src);
+                        (Some(route.first), true)
+                    }
+                };
+                if endpoint {
+                    route.last_seen = now;
+                }
+                return peer;
+            }
+            // Idle route: forget it and learn the session afresh
+            self.sessions.remove(&session);
+        }
+        if self.sessions.len() >= self.max_sessions {
+            self.expire_idle(now);
+        }
+        if self.sessions.len() < self.max_sessions {
+            self.sessions.insert(session, Route { first: src, second: None, last_seen: now });
+        }
+        None
+    }
+
+    /// Forget every route idle for at least the idle timeout at `now`
+    pub fn expire_idle(&mut self, now: Instant) {
+        let idle_timeout = self.idle_timeout;
+        self.sessions
+            .retain(|_, route| now.saturating_duration_since(route.last_seen) < idle_timeout);
+    }
+
+    /// Number of tracked sessions
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Whether no sessions are tracked
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+}
+
+/// What to do with a received datagram
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Drop silently
+    Drop,
+    /// Send this reply back to the source
+    Reply(Vec<u8>),
+    /// Forward the datagram unchanged to this address
+    Forward(SocketAddr),
+}
+
+/// Relay state: admission, rate limiting, message validation and routing
+pub struct Relay {
+    parser: SecurityNetworkParser,
+    routes: RoutingTable,
+    cookies: CookieIssuer,
+    /// Per-source buckets; a bucket exists only for admitted sources
+    admitted: RateLimiter,
+    /// Shared bucket for cookie traffic from unadmitted sources
+    challenges: TokenBucket,
+    /// Next time idle state is swept
+    next_sweep: Instant,
+}
+
+impl Relay {
+    /// Create a relay with default parser limits
+    pub fn new(config: &RelayConfig) -> Self {
+        Self {
+            parser: SecurityNetworkParser::new(),
+            routes: RoutingTable::new(config.max_sessions, config.idle_timeout),
+            cookies: CookieIssuer::new(),
+            admitted: RateLimiter::new(config.rate, config.burst, config.max_sessions, config.idle_timeout),
+            challenges: TokenBucket::new(config.challenge_rate, config.challenge_rate, Instant::now()),
+            next_sweep: Instant::now() + SWEEP_INTERVAL,
+        }
+    }
+
+    /// Decide what to do with a datagram from `src` received at `now`
+    pub fn handle(&mut self, datagram: &[u8], src: SocketAddr, now: Instant) -> Action {
+        if now >= self.next_sweep {
+            self.routes.expire_idle(now);
+            self.admitted.expire_idle(now);
+            self.cookies.expire();
+            self.next_sweep = now + SWEEP_INTERVAL;
+        }
+        if datagram.first() == Some(&CONTROL_MAGIC) {
+            return self.handle_control(datagram, src, now);
+        }
+        if !self.admitted.contains(&src) || !self.admitted.allow(src, now) {
+            return Action::Drop;
+        }
+        self.route(datagram, src, now).map_or(Action::Drop, Action::Forward)
+    }
+
+    fn handle_control(&mut self, datagram: &[u8], src: SocketAddr, now: Instant) -> Action {
+        if self.admitted.contains(&src) || !self.challenges.try_take(now) {
+            return Action::Drop;
+        }
+        match datagram.get(1) {
+            Some(&COOKIE_REQUEST) => self
+                .cookies
+                .challenge(datagram, src)
+                .map_or(Action::Drop, Action::Reply),
+            Some(&COOKIE_ECHO) if self.cookies.verify(datagram, src) => {
+                // Admission allocates the source's bucket (and its first token)
+                self.admitted.allow(src, now);
+                Action::Drop
+            }
+            _ => Action::Drop,
+        }
+    }
+
+    /// Validate a B4AE message and return the peer to forward it to.
+    ///
+    /// Returns `None` for malformed datagrams (bad header, declared length
+    /// mismatch, missing session ID) and for datagrams with no known peer.
+    fn route(&mut self, datagram: &[u8], src: SocketAddr, now: Instant) -> Option<SocketAddr> {
+        let message = self.parser.parse_message(datagram).ok()?;
+        let session: SessionId = message.payload.get(..SESSION_ID_SIZE)?.try_into().ok()?;
+        self.routes.route(session, src, now)
+    }
+
+    /// Whether `src` has completed the cookie exchange
+    pub fn is_admitted(&self, src: &SocketAddr) -> bool {
+        self.admitted.contains(src)
+    }
+
+    /// Routing table
+    pub fn routes(&self) -> &RoutingTable {
+        &self.routes
+    }
+}
+
+/// Serve the relay on `socket` until an I/O error occurs
+pub async fn run(socket: UdpSocket, mut relay: Relay) -> std::io::Result<()> {
+    let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+    loop {
+        let (len, src) = socket.recv_from(&mut buf).await?;
+        // Best effort, like UDP itself
+        match relay.handle(&buf[..len], src, Instant::now()) {
+            Action::Drop => {}
+            Action::Reply(reply) => {
+                let _ = socket.send_to(&reply, src).await;
+            }
+            Action::Forward(dest) => {
+                let _ = socket.send_to(&buf[..len], dest).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_idle_routes_expire() {
+        let start = Instant::now();
+        let idle = Duration::from_secs(10);
+        let mut table = RoutingTable::new(1, idle);
+        let (a, b, c) = (addr(1), addr(2), addr(3));
+        assert_eq!(table.route([1; SESSION_ID_SIZE], a, start), None);
+        assert_eq!(table.route([1; SESSION_ID_SIZE], b, start), Some(a));
+
+        // Full table refuses new sessions while the route is live
+        assert_eq!(table.route([2; SESSION_ID_SIZE], c, start), None);
+        assert_eq!(table.route([2; SESSION_ID_SIZE], a, start), None);
+
+        // Third-party traffic does not keep the route alive
+        assert_eq!(table.route([1; SESSION_ID_SIZE], c, start + idle / 2), None);
+        let later = start + idle;
+        assert_eq!(table.route([2; SESSION_ID_SIZE], c, later), None);
+        assert_eq!(table.route([2; SESSION_ID_SIZE], a, later), Some(c));
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_idle_route_is_relearned() {
+        let start = Instant::now();
+        let idle = Duration::from_secs(10);
+        let mut table = RoutingTable::new(4, idle);
+        let session = [1; SESSION_ID_SIZE];
+        let (a, b, c) = (addr(1), addr(2), addr(3));
+        table.route(session, a, start);
+        assert_eq!(table.route(session, b, start), Some(a));
+
+        // Endpoint traffic refreshes the route
+        assert_eq!(table.route(session, a, start + idle / 2), Some(b));
+        assert_eq!(table.route(session, b, start + idle), Some(a));
+
+        // Once idle, the session is learned afresh
+        let later = start + idle * 2;
+        assert_eq!(table.route(session, c, later), None);
+        assert_eq!(table.route(session, b, later), Some(c));
+
+        table.expire_idle(later + idle);
+        assert!(table.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+//! Token-bucket rate limiting
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// Classic token bucket refilled continuously at `rate` tokens/sec
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    /// Full bucket holding up to `burst` tokens
+    pub fn new(rate: f64, burst: f64, now: Instant) -> Self {
+        Self { rate, burst, tokens: burst, last: now }
+    }
+
+    /// Take one token if available
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Last time a token was requested
+    pub fn last_used(&self) -> Instant {
+        self.last
+    }
+}
+
+/// Per-source token buckets, bounded to `max_sources` entries; a source
+/// silent for `idle_timeout` loses its bucket
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    max_sources: usize,
+    idle_timeout: Duration,
+    buckets: HashMap<SocketAddr, TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Limit each source to `rate` packets/sec with bursts of `burst`
+    pub fn new(rate: f64, burst: f64, max_sources: usize, idle_timeout: Duration) -> Self {
+        Self {
+            rate,
+            burst,
+            max_sources,
+            idle_timeout,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Whether `src` may send one more packet; when the bound is reached,
+    /// idle buckets are evicted first and unknown sources are refused if
+    /// none are idle
+    pub fn allow(&mut self, src: SocketAddr, now: Instant) -> bool {
+        if let Some(bucket) = self.buckets.get_mut(&src) {
+            return bucket.try_take(now);
+        }
+        if self.buckets.len() >= self.max_sources {
+            self.expire_idle(now);
+        }
+        if self.buckets.len() >= self.max_sources {
+            return false;
+        }
+        let mut bucket = TokenBucket::new(self.rate, self.burst, now);
+        let allowed = bucket.try_take(now);
+        self.buckets.insert(src, bucket);
+        allowed
+    }
+
+    /// Drop the bucket of every source idle for at least the idle timeout
+    /// at `now`
+    pub fn expire_idle(&mut self, now: Instant) {
+        let idle_timeout = self.idle_timeout;
+        self.buckets
+            .retain(|_, bucket| now.saturating_duration_since(bucket.last_used()) < idle_timeout);
+    }
+
+    /// Whether `src` has a bucket (i.e. has been admitted)
+    pub fn contains(&self, src: &SocketAddr) -> bool {
+        self.buckets.contains_key(src)
+    }
+
+    /// Number of tracked sources
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Whether no sources are tracked
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_refills() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, 2.0, start);
+        assert!(bucket.try_take(start));
+        assert!(bucket.try_take(start));
+        assert!(!bucket.try_take(start));
+        assert!(bucket.try_take(start + Duration::from_millis(100)));
+        assert!(!bucket.try_take(start + Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_limiter_bounds_sources() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(1.0, 1.0, 1, Duration::from_secs(10));
+        let a: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        let b: SocketAddr = "127.0.0.1:2".parse().unwrap();
+        assert!(limiter.allow(a, now));
+        assert!(!limiter.allow(b, now));
+        assert_eq!(limiter.len(), 1);
+    }
+
+    #[test]
+    fn test_limiter_evicts_idle_sources() {
+        let start = Instant::now();
+        let idle = Duration::from_secs(10);
+        let mut limiter = RateLimiter::new(1.0, 1.0, 1, idle);
+        let a: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        let b: SocketAddr = "127.0.0.1:2".parse().unwrap();
+        assert!(limiter.allow(a, start));
+        assert!(!limiter.allow(b, start + idle / 2));
+
+        // One more packet from `a` keeps its bucket alive
+        assert!(limiter.allow(a, start + idle / 2));
+        assert!(!limiter.allow(b, start + idle));
+
+        assert!(limiter.allow(b, start + idle / 2 + idle));
+        assert!(!limiter.contains(&a));
+
+        limiter.expire_idle(start + idle * 3);
+        assert!(limiter.is_empty());
+    }
+}
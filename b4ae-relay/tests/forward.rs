@@ -0,0 +1,281 @@
+use std::net::SocketAddr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use b4ae_relay::{config::RelayConfig, cookie, run, Action, Relay, SessionId, SESSION_ID_SIZE};
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+/// Header length field offset: version(2) + type(1) + suite(1) + message_id(8)
+const LENGTH_OFFSET: usize = 12;
+
+fn datagram(session: SessionId, ciphertext: &[u8]) -> Vec<u8> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let payload_len = (SESSION_ID_SIZE + ciphertext.len()) as u32;
+    let mut out = Vec::new();
+    out.extend_from_slice(&[0x01, 0x00]); // version 1.0
+    out.push(0x04); // Data
+    out.push(0x03); // AES-256-GCM
+    out.extend_from_slice(&42u64.to_be_bytes());
+    out.extend_from_slice(&payload_len.to_be_bytes());
+    out.extend_from_slice(&now.to_be_bytes());
+    out.extend_from_slice(&session);
+    out.extend_from_slice(ciphertext);
+    out
+}
+
+async fn start_relay() -> std::net::SocketAddr {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+    tokio::spawn(run(socket, Relay::new(&RelayConfig::default())));
+    addr
+}
+
+async fn unadmitted_peer(relay: std::net::SocketAddr) -> UdpSocket {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    socket.connect(relay).await.unwrap();
+    socket
+}
+
+/// Peer that has completed the cookie exchange
+async fn peer(relay: std::net::SocketAddr) -> UdpSocket {
+    let socket = unadmitted_peer(relay).await;
+    // Cookies are single use, so each peer needs its own client random
+    let client_random = client_random(socket.local_addr().unwrap());
+    socket.send(&cookie::request(&client_random)).await.unwrap();
+    let challenge = recv(&socket).await.expect("cookie challenge");
+    assert_eq!(challenge.len(), cookie::CHALLENGE_SIZE);
+    socket.send(&cookie::echo(&client_random, &challenge).unwrap()).await.unwrap();
+    socket
+}
+
+async fn recv(socket: &UdpSocket) -> Option<Vec<u8>> {
+    let mut buf = vec![0u8; 2048];
+    let len = timeout(Duration::from_millis(300), socket.recv(&mut buf)).await.ok()?.ok()?;
+    buf.truncate(len);
+    Some(buf)
+}
+
+#[tokio::test]
+async fn forwards_between_session_endpoints() {
+    let relay = start_relay().await;
+    let alice = peer(relay).await;
+    let bob = peer(relay).await;
+    let session = [7u8; SESSION_ID_SIZE];
+
+    // Alice registers; nobody to forward to yet
+    alice.send(&datagram(session, b"hello?")).await.unwrap();
+    assert!(recv(&bob).await.is_none());
+
+    let from_bob = datagram(session, b"opaque ciphertext from bob");
+    bob.send(&from_bob).await.unwrap();
+    assert_eq!(recv(&alice).await, Some(from_bob));
+
+    let from_alice = datagram(session, b"opaque ciphertext from alice");
+    alice.send(&from_alice).await.unwrap();
+    assert_eq!(recv(&bob).await, Some(from_alice));
+}
+
+#[tokio::test]
+async fn drops_malformed_and_tampered_datagrams() {
+    let relay = start_relay().await;
+    let alice = peer(relay).await;
+    let bob = peer(relay).await;
+    let session = [9u8; SESSION_ID_SIZE];
+
+    alice.send(&datagram(session, b"register")).await.unwrap();
+    bob.send(&datagram(session, b"register")).await.unwrap();
+    assert!(recv(&alice).await.is_some());
+
+    // Declared length no longer matches
+    let mut tampered = datagram(session, b"payload");
+    tampered[LENGTH_OFFSET + 3] ^= 0x01;
+    bob.send(&tampered).await.unwrap();
+    assert!(recv(&alice).await.is_none());
+
+    // Unknown message type
+    let mut tampered = datagram(session, b"payload");
+    tampered[2] = 0x7f;
+    bob.send(&tampered).await.unwrap();
+    assert!(recv(&alice).await.is_none());
+
+    // Garbage and payloads too short for a session ID
+    bob.send(b"not a b4ae message").await.unwrap();
+    let mut short = datagram(session, b"");
+    short.truncate(short.len() - 1);
+    short[LENGTH_OFFSET..LENGTH_OFFSET + 4].copy_from_slice(&((SESSION_ID_SIZE - 1) as u32).to_be_bytes());
+    bob.send(&short).await.unwrap();
+    assert!(recv(&alice).await.is_none());
+
+    // Relay still forwards valid traffic afterwards
+    let valid = datagram(session, b"still works");
+    bob.send(&valid).await.unwrap();
+    assert_eq!(recv(&alice).await, Some(valid));
+}
+
+/// Client random unique to `src`
+fn client_random(src: SocketAddr) -> [u8; 32] {
+    let mut out = [0x11u8; 32];
+    out[..2].copy_from_slice(&src.port().to_be_bytes());
+    out
+}
+
+/// Run the cookie exchange against an in-process relay
+fn admit(relay: &mut Relay, src: SocketAddr, now: Instant) {
+    let client_random = client_random(src);
+    let Action::Reply(challenge) = relay.handle(&cookie::request(&client_random), src, now) else {
+        panic!("expected cookie challenge");
+    };
+    let echo = cookie::echo(&client_random, &challenge).unwrap();
+    assert_eq!(relay.handle(&echo, src, now), Action::Drop);
+    assert!(relay.is_admitted(&src));
+}
+
+fn addr(port: u16) -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], port))
+}
+
+#[test]
+fn third_party_cannot_join_full_session() {
+    let now = Instant::now();
+    let mut relay = Relay::new(&RelayConfig::default());
+    let session = [1u8; SESSION_ID_SIZE];
+    let (a, b, c) = (addr(1000), addr(2000), addr(3000));
+    for src in [a, b, c] {
+        admit(&mut relay, src, now);
+    }
+    let msg = datagram(session, b"x");
+    assert_eq!(relay.handle(&msg, a, now), Action::Drop);
+    assert_eq!(relay.handle(&msg, b, now), Action::Forward(a));
+    assert_eq!(relay.handle(&msg, c, now), Action::Drop);
+    assert_eq!(relay.handle(&msg, a, now), Action::Forward(b));
+    assert_eq!(relay.routes().len(), 1);
+}
+
+#[test]
+fn unadmitted_sources_get_no_state() {
+    let now = Instant::now();
+    let mut relay = Relay::new(&RelayConfig::default());
+    let src = addr(4000);
+    let msg = datagram([2u8; SESSION_ID_SIZE], b"x");
+
+    assert_eq!(relay.handle(&msg, src, now), Action::Drop);
+    assert!(relay.routes().is_empty());
+
+    // Reply is never larger than the request (no amplification)
+    let request = cookie::request(&[0u8; 32]);
+    let Action::Reply(challenge) = relay.handle(&request, src, now) else {
+        panic!("expected cookie challenge");
+    };
+    assert!(challenge.len() <= request.len());
+    assert!(!relay.is_admitted(&src));
+
+    // Forged cookie is rejected
+    let mut forged = cookie::echo(&[0u8; 32], &challenge).unwrap();
+    let last = forged.len() - 1;
+    forged[last] ^= 0x01;
+    assert_eq!(relay.handle(&forged, src, now), Action::Drop);
+    assert!(!relay.is_admitted(&src));
+
+    // Cookie is bound to the source IP
+    let echo = cookie::echo(&[0u8; 32], &challenge).unwrap();
+    assert_eq!(relay.handle(&echo, "10.0.0.1:4000".parse().unwrap(), now), Action::Drop);
+    assert!(!relay.is_admitted(&"10.0.0.1:4000".parse().unwrap()));
+
+    // Valid cookie grants forwarding
+    assert_eq!(relay.handle(&echo, src, now), Action::Drop);
+    assert!(relay.is_admitted(&src));
+    let peer = addr(4001);
+    admit(&mut relay, peer, now);
+    assert_eq!(relay.handle(&msg, src, now), Action::Drop);
+    assert_eq!(relay.handle(&msg, peer, now), Action::Forward(src));
+}
+
+#[test]
+fn replayed_cookie_is_refused() {
+    let now = Instant::now();
+    let mut relay = Relay::new(&RelayConfig::default());
+    let (src, replayer) = (addr(4100), addr(4101));
+    let client_random = client_random(src);
+    let Action::Reply(challenge) = relay.handle(&cookie::request(&client_random), src, now) else {
+        panic!("expected cookie challenge");
+    };
+    let echo = cookie::echo(&client_random, &challenge).unwrap();
+    relay.handle(&echo, src, now);
+    assert!(relay.is_admitted(&src));
+
+    // Same IP, so the cookie would otherwise verify
+    relay.handle(&echo, replayer, now);
+    assert!(!relay.is_admitted(&replayer));
+}
+
+#[test]
+fn idle_source_must_be_readmitted() {
+    let config = RelayConfig { idle_timeout: Duration::from_secs(10), ..RelayConfig::default() };
+    let start = Instant::now();
+    let mut relay = Relay::new(&config);
+    let (a, b) = (addr(4200), addr(4201));
+    admit(&mut relay, a, start);
+    admit(&mut relay, b, start);
+    let msg = datagram([5u8; SESSION_ID_SIZE], b"x");
+    relay.handle(&msg, a, start);
+    assert_eq!(relay.handle(&msg, b, start), Action::Forward(a));
+
+    let later = start + Duration::from_secs(11);
+    assert_eq!(relay.handle(&msg, b, later), Action::Drop);
+    assert!(!relay.is_admitted(&a));
+    assert!(!relay.is_admitted(&b));
+    assert!(relay.routes().is_empty());
+}
+
+#[test]
+fn flooding_source_is_rate_limited() {
+    let config = RelayConfig { rate: 10.0, burst: 5.0, ..RelayConfig::default() };
+    let start = Instant::now();
+    let mut relay = Relay::new(&config);
+    let (flooder, peer) = (addr(5000), addr(5001));
+    admit(&mut relay, flooder, start);
+    admit(&mut relay, peer, start);
+    let session = [3u8; SESSION_ID_SIZE];
+    relay.handle(&datagram(session, b"register"), peer, start);
+
+    // Admission took one token; the rest of the burst is forwarded
+    let msg = datagram(session, b"flood");
+    let forwarded = (0..100)
+        .filter(|_| relay.handle(&msg, flooder, start) == Action::Forward(peer))
+        .count();
+    assert_eq!(forwarded, 4);
+
+    // Refills at the configured rate
+    let later = start + Duration::from_millis(500);
+    let forwarded = (0..100)
+        .filter(|_| relay.handle(&msg, flooder, later) == Action::Forward(peer))
+        .count();
+    assert_eq!(forwarded, 5);
+
+    // Other sources are unaffected
+    assert_eq!(relay.handle(&msg, peer, later), Action::Forward(flooder));
+}
+
+#[test]
+fn cookie_requests_share_a_global_limit() {
+    let config = RelayConfig { challenge_rate: 3.0, ..RelayConfig::default() };
+    let now = Instant::now();
+    let mut relay = Relay::new(&config);
+    let request = cookie::request(&[0u8; 32]);
+    let replies = (0..10u16)
+        .filter(|i| matches!(relay.handle(&request, addr(6000 + i), now), Action::Reply(_)))
+        .count();
+    assert_eq!(replies, 3);
+}
+
+#[tokio::test]
+async fn unadmitted_socket_is_not_forwarded() {
+    let relay = start_relay().await;
+    let alice = peer(relay).await;
+    let mallory = unadmitted_peer(relay).await;
+    let session = [4u8; SESSION_ID_SIZE];
+
+    alice.send(&datagram(session, b"register")).await.unwrap();
+    mallory.send(&datagram(session, b"inject")).await.unwrap();
+    assert!(recv(&alice).await.is_none());
+}
@@ -1,74 +1,193 @@
 //! B4AE WebAssembly bindings
 //!
 //! Subset API untuk browser: symmetric encrypt/decrypt dengan AES-GCM.
+//!
+//! Error dikembalikan sebagai object `{ code, message }` agar aplikasi
+//! dapat bercabang berdasarkan `code`.
+//! Streaming encrypt untuk file besar: [`Encryptor`] / [`Decryptor`].
+//! Konversi bytes ke teks: [`to_base64url`] / [`from_base64url`].
 
 use aes_gcm::{
     aead::{Aead, KeyInit, Payload},
     Aes256Gcm,
 };
+use base64ct::{Base64UrlUnpadded, Encoding};
 use getrandom::getrandom;
+use serde::Serialize;
 use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
+
+mod stream;
+
+pub use stream::{Decryptor, Encryptor};
 
 const KEY_SIZE: usize = 32;
 const NONCE_SIZE: usize = 12;
+/// Output maksimum HKDF-SHA3-256 (255 * HashLen)
+const HKDF_MAX_OUTPUT: usize = 255 * 32;
+/// Batas panjang `info` untuk derive_key
+const HKDF_MAX_INFO: usize = 1024;
+
+#[wasm_bindgen(typescript_custom_section)]
+const B4AE_ERROR_TS: &'static str = r#"
+/** Error thrown by every fallible b4ae-wasm function. */
+export interface B4aeError {
+  code: "invalid_key" | "invalid_input" | "rng_failed" | "encryption_failed" | "auth_failed" | "invalid_state";
+  message: string;
+}
+"#;
+
+/// Kode error yang stabil untuk dicocokkan di JavaScript
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidKey,
+    InvalidInput,
+    RngFailed,
+    EncryptionFailed,
+    AuthFailed,
+    InvalidState,
+}
+
+/// Error terstruktur, diserialisasi ke JS sebagai `{ code, message }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct B4aeError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl B4aeError {
+    fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<B4aeError> for JsValue {
+    fn from(e: B4aeError) -> JsValue {
+        serde_wasm_bindgen::to_value(&e).unwrap_or_else(|_| JsValue::from_str(&e.message))
+    }
+}
 
-fn fill_random(buf: &mut [u8]) -> Result<(), getrandom::Error> {
-    getrandom(buf)
+fn fill_random(buf: &mut [u8]) -> Result<(), B4aeError> {
+    getrandom(buf).map_err(|e| B4aeError::new(ErrorCode::RngFailed, e.to_string()))
 }
 
 /// Generate random key untuk AES-256-GCM
 #[wasm_bindgen]
 pub fn generate_key() -> Vec<u8> {
     let mut key = [0u8; KEY_SIZE];
-    fill_random(&mut key).expect("getrandom");
+    getrandom(&mut key).expect("getrandom");
     key.to_vec()
 }
 
+/// Derive subkey dari master key dengan HKDF-SHA3-256 (salt kosong)
+/// melalui `b4ae::crypto::hkdf::derive_key(&[master], info, length)`.
+/// `length` harus 1..=8160 byte dan `info` maksimal 1024 byte.
+/// Salinan key di memori wasm di-zeroize setelah disalin ke `Uint8Array`.
+#[wasm_bindgen]
+pub fn derive_key(master: &[u8], info: &[u8], length: usize) -> Result<js_sys::Uint8Array, JsValue> {
+    let key = hkdf_sha3(master, info, length).map_err(JsValue::from)?;
+    Ok(js_sys::Uint8Array::from(key.as_slice()))
+}
+
+fn hkdf_sha3(master: &[u8], info: &[u8], length: usize) -> Result<Zeroizing<Vec<u8>>, B4aeError> {
+    if master.is_empty() {
+        return Err(B4aeError::new(ErrorCode::InvalidKey, "Master key must not be empty"));
+    }
+    if length == 0 || length > HKDF_MAX_OUTPUT {
+        return Err(B4aeError::new(
+            ErrorCode::InvalidInput,
+            format!("Output length must be 1..={} bytes, got {}", HKDF_MAX_OUTPUT, length),
+        ));
+    }
+    if info.len() > HKDF_MAX_INFO {
+        return Err(B4aeError::new(
+            ErrorCode::InvalidInput,
+            format!("Info must be at most {} bytes, got {}", HKDF_MAX_INFO, info.len()),
+        ));
+    }
+    b4ae::crypto::hkdf::derive_key(&[master], info, length)
+        .map(Zeroizing::new)
+        .map_err(|e| B4aeError::new(ErrorCode::InvalidInput, e.to_string()))
+}
+
+/// Encode bytes sebagai base64 URL-safe tanpa padding
+/// (sama dengan `b4ae_to_base64` di b4ae-ffi).
+#[wasm_bindgen]
+pub fn to_base64url(bytes: &[u8]) -> String {
+    Base64UrlUnpadded::encode_string(bytes)
+}
+
+/// Decode base64 URL-safe tanpa padding.
+/// Padding, alfabet standar `+`/`/` dan spasi ditolak dengan error `invalid_input`.
+#[wasm_bindgen]
+pub fn from_base64url(s: &str) -> Result<Vec<u8>, JsValue> {
+    decode_base64url(s).map_err(JsValue::from)
+}
+
+fn decode_base64url(s: &str) -> Result<Vec<u8>, B4aeError> {
+    Base64UrlUnpadded::decode_vec(s)
+        .map_err(|_| B4aeError::new(ErrorCode::InvalidInput, "Invalid base64url (URL-safe, no padding)"))
+}
+
 /// Encrypt plaintext dengan AES-256-GCM
-/// Returns [nonce (12) || ciphertext] as single Vec
+/// Returns [nonce (12) || ciphertext] as single Vec.
+/// `aad` (opsional) diautentikasi tetapi tidak dienkripsi.
 #[wasm_bindgen]
-pub fn encrypt(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, JsValue> {
+pub fn encrypt(key: &[u8], plaintext: &[u8], aad: Option<Vec<u8>>) -> Result<Vec<u8>, JsValue> {
+    encrypt_with_aad(key, plaintext, aad.as_deref().unwrap_or(&[])).map_err(JsValue::from)
+}
+
+/// Decrypt [nonce (12) || ciphertext] dengan AES-256-GCM.
+/// `aad` harus sama dengan saat encrypt, jika tidak error `auth_failed`.
+#[wasm_bindgen]
+pub fn decrypt(key: &[u8], encrypted: &[u8], aad: Option<Vec<u8>>) -> Result<Vec<u8>, JsValue> {
+    decrypt_with_aad(key, encrypted, aad.as_deref().unwrap_or(&[])).map_err(JsValue::from)
+}
+
+fn cipher(key: &[u8]) -> Result<Aes256Gcm, B4aeError> {
     if key.len() != KEY_SIZE {
-        return Err(JsValue::from_str("Key must be 32 bytes"));
+        return Err(B4aeError::new(ErrorCode::InvalidKey, "Key must be 32 bytes"));
     }
+    Aes256Gcm::new_from_slice(key).map_err(|e| B4aeError::new(ErrorCode::InvalidKey, e.to_string()))
+}
 
-    let mut nonce = [0u8; NONCE_SIZE];
-    fill_random(&mut nonce).map_err(|e| JsValue::from_str(&e.to_string()))?;
+fn encrypt_with_aad(key: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, B4aeError> {
+    let cipher = cipher(key)?;
 
-    let cipher = Aes256Gcm::new_from_slice(key)
-        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mut nonce = [0u8; NONCE_SIZE];
+    fill_random(&mut nonce)?;
 
-    let payload = Payload { msg: plaintext, aad: &[] };
+    let payload = Payload { msg: plaintext, aad };
     let ciphertext = cipher
         .encrypt((&nonce).into(), payload)
-        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        .map_err(|_| B4aeError::new(ErrorCode::EncryptionFailed, "Encryption failed"))?;
 
     let mut result = nonce.to_vec();
     result.extend(ciphertext);
     Ok(result)
 }
 
-/// Decrypt [nonce (12) || ciphertext] dengan AES-256-GCM
-#[wasm_bindgen]
-pub fn decrypt(key: &[u8], encrypted: &[u8]) -> Result<Vec<u8>, JsValue> {
-    if key.len() != KEY_SIZE {
-        return Err(JsValue::from_str("Key must be 32 bytes"));
-    }
+fn decrypt_with_aad(key: &[u8], encrypted: &[u8], aad: &[u8]) -> Result<Vec<u8>, B4aeError> {
+    let cipher = cipher(key)?;
     if encrypted.len() < NONCE_SIZE {
-        return Err(JsValue::from_str("Encrypted data too short"));
+        return Err(B4aeError::new(ErrorCode::InvalidInput, "Encrypted data too short"));
     }
 
     let (nonce_bytes, ciphertext) = encrypted.split_at(NONCE_SIZE);
     let nonce = aes_gcm::Nonce::from_slice(nonce_bytes);
 
-    let cipher = Aes256Gcm::new_from_slice(key)
-        .map_err(|e| JsValue::from_str(&e.to_string()))?;
-
     let payload = Payload {
         msg: ciphertext,
-        aad: &[],
+        aad,
     };
-    cipher
-        .decrypt(nonce, payload)
-        .map_err(|e| JsValue::from_str(&e.to_string()))
+    cipher.decrypt(nonce, payload).map_err(|_| {
+        B4aeError::new(
+            ErrorCode::AuthFailed,
+            "Decryption failed: wrong key, tampered data or AAD mismatch",
+        )
+    })
 }
@@ -0,0 +1,171 @@
+//! Streaming AES-256-GCM (STREAM construction) untuk upload besar
+//!
+//! Setiap chunk mendapat nonce unik dari base nonce acak 7 byte + counter
+//! 32-bit, dan chunk terakhir diberi flag "last" sehingga pemotongan stream
+//! terdeteksi. Output pertama `Encryptor` diawali base nonce; setiap output
+//! `update`/`finalize` harus diberikan apa adanya dan berurutan ke satu
+//! panggilan `Decryptor.update`/`Decryptor.finalize`.
+
+use aes_gcm::{
+    aead::{
+        stream::{DecryptorBE32, EncryptorBE32},
+        KeyInit,
+    },
+    Aes256Gcm,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{fill_random, B4aeError, ErrorCode, KEY_SIZE};
+
+/// Ukuran base nonce STREAM untuk AES-256-GCM (12 - 5 byte counter/flag)
+pub const STREAM_NONCE_SIZE: usize = 7;
+
+fn stream_cipher(key: &[u8]) -> Result<Aes256Gcm, B4aeError> {
+    if key.len() != KEY_SIZE {
+        return Err(B4aeError::new(ErrorCode::InvalidKey, "Key must be 32 bytes"));
+    }
+    Aes256Gcm::new_from_slice(key).map_err(|e| B4aeError::new(ErrorCode::InvalidKey, e.to_string()))
+}
+
+fn finished() -> B4aeError {
+    B4aeError::new(ErrorCode::InvalidState, "Stream already finalized")
+}
+
+/// Encryptor stateful: `new(key)`, `update(chunk)`, `finalize()`
+#[wasm_bindgen]
+pub struct Encryptor {
+    prefix: Option<[u8; STREAM_NONCE_SIZE]>,
+    inner: Option<EncryptorBE32<Aes256Gcm>>,
+}
+
+#[wasm_bindgen]
+impl Encryptor {
+    /// Buat encryptor dengan base nonce acak
+    #[wasm_bindgen(constructor)]
+    pub fn new(key: &[u8]) -> Result<Encryptor, JsValue> {
+        Self::create(key).map_err(JsValue::from)
+    }
+
+    /// Encrypt satu chunk. Output pertama diawali base nonce (7 byte).
+    pub fn update(&mut self, chunk: &[u8]) -> Result<Vec<u8>, JsValue> {
+        self.encrypt_chunk(chunk).map_err(JsValue::from)
+    }
+
+    /// Tutup stream dengan chunk terakhir (kosong, hanya tag).
+    pub fn finalize(&mut self) -> Result<Vec<u8>, JsValue> {
+        self.encrypt_last().map_err(JsValue::from)
+    }
+}
+
+impl Encryptor {
+    fn create(key: &[u8]) -> Result<Encryptor, B4aeError> {
+        let cipher = stream_cipher(key)?;
+        let mut nonce = [0u8; STREAM_NONCE_SIZE];
+        fill_random(&mut nonce)?;
+        Ok(Encryptor {
+            prefix: Some(nonce),
+            inner: Some(EncryptorBE32::from_aead(cipher, (&nonce).into())),
+        })
+    }
+
+    fn with_prefix(&mut self, ciphertext: Vec<u8>) -> Vec<u8> {
+        match self.prefix.take() {
+            Some(nonce) => {
+                let mut out = nonce.to_vec();
+                out.extend(ciphertext);
+                out
+            }
+            None => ciphertext,
+        }
+    }
+
+    fn encrypt_chunk(&mut self, chunk: &[u8]) -> Result<Vec<u8>, B4aeError> {
+        let inner = self.inner.as_mut().ok_or_else(finished)?;
+        let ciphertext = inner
+            .encrypt_next(chunk)
+            .map_err(|_| B4aeError::new(ErrorCode::EncryptionFailed, "Chunk encryption failed"))?;
+        Ok(self.with_prefix(ciphertext))
+    }
+
+    fn encrypt_last(&mut self) -> Result<Vec<u8>, B4aeError> {
+        let inner = self.inner.take().ok_or_else(finished)?;
+        let ciphertext = inner
+            .encrypt_last(&[][..])
+            .map_err(|_| B4aeError::new(ErrorCode::EncryptionFailed, "Chunk encryption failed"))?;
+        Ok(self.with_prefix(ciphertext))
+    }
+}
+
+/// Decryptor pasangan `Encryptor`; menolak chunk yang diubah, ditukar
+/// urutannya, atau stream yang terpotong.
+#[wasm_bindgen]
+pub struct Decryptor {
+    cipher: Aes256Gcm,
+    inner: Option<DecryptorBE32<Aes256Gcm>>,
+    done: bool,
+}
+
+#[wasm_bindgen]
+impl Decryptor {
+    #[wasm_bindgen(constructor)]
+    pub fn new(key: &[u8]) -> Result<Decryptor, JsValue> {
+        let cipher = stream_cipher(key).map_err(JsValue::from)?;
+        Ok(Decryptor {
+            cipher,
+            inner: None,
+            done: false,
+        })
+    }
+
+    /// Decrypt satu chunk hasil `Encryptor.update`.
+    pub fn update(&mut self, chunk: &[u8]) -> Result<Vec<u8>, JsValue> {
+        self.decrypt_chunk(chunk).map_err(JsValue::from)
+    }
+
+    /// Verifikasi chunk terakhir hasil `Encryptor.finalize`. Gagal dengan
+    /// `auth_failed` jika stream terpotong.
+    pub fn finalize(&mut self, chunk: &[u8]) -> Result<(), JsValue> {
+        self.decrypt_last(chunk).map_err(JsValue::from)
+    }
+}
+
+impl Decryptor {
+    fn auth_failed() -> B4aeError {
+        B4aeError::new(
+            ErrorCode::AuthFailed,
+            "Chunk authentication failed: tampered, reordered or truncated stream",
+        )
+    }
+
+    /// Ambil base nonce dari chunk pertama dan siapkan decryptor.
+    fn start<'a>(&mut self, chunk: &'a [u8]) -> Result<&'a [u8], B4aeError> {
+        if self.done {
+            return Err(finished());
+        }
+        if self.inner.is_some() {
+            return Ok(chunk);
+        }
+        if chunk.len() < STREAM_NONCE_SIZE {
+            return Err(B4aeError::new(ErrorCode::InvalidInput, "First chunk too short"));
+        }
+        let (nonce, rest) = chunk.split_at(STREAM_NONCE_SIZE);
+        self.inner = Some(DecryptorBE32::from_aead(self.cipher.clone(), nonce.into()));
+        Ok(rest)
+    }
+
+    fn decrypt_chunk(&mut self, chunk: &[u8]) -> Result<Vec<u8>, B4aeError> {
+        let ciphertext = self.start(chunk)?;
+        let inner = self.inner.as_mut().ok_or_else(finished)?;
+        inner.decrypt_next(ciphertext).map_err(|_| Self::auth_failed())
+    }
+
+    fn decrypt_last(&mut self, chunk: &[u8]) -> Result<(), B4aeError> {
+        let ciphertext = self.start(chunk)?;
+        self.done = true;
+        let inner = self.inner.take().ok_or_else(finished)?;
+        match inner.decrypt_last(ciphertext) {
+            Ok(p) if p.is_empty() => Ok(()),
+            _ => Err(Self::auth_failed()),
+        }
+    }
+}
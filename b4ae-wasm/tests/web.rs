@@ -0,0 +1,146 @@
+//! wasm-bindgen tests. Run with: wasm-pack test --node b4ae-wasm
+
+#![cfg(target_arch = "wasm32")]
+
+use b4ae_wasm::*;
+use serde::Deserialize;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+#[derive(Deserialize)]
+struct JsError {
+    code: String,
+    message: String,
+}
+
+fn js_error(e: JsValue) -> JsError {
+    serde_wasm_bindgen::from_value(e).expect("structured error object")
+}
+
+#[wasm_bindgen_test]
+fn encrypt_decrypt_with_aad() {
+    let key = generate_key();
+    let enc = encrypt(&key, b"hello", Some(b"room:42".to_vec())).unwrap();
+    let dec = decrypt(&key, &enc, Some(b"room:42".to_vec())).unwrap();
+    assert_eq!(dec, b"hello");
+}
+
+#[wasm_bindgen_test]
+fn aad_mismatch_is_auth_failed() {
+    let key = generate_key();
+    let enc = encrypt(&key, b"hello", Some(b"room:42".to_vec())).unwrap();
+
+    let err = js_error(decrypt(&key, &enc, Some(b"room:43".to_vec())).unwrap_err());
+    assert_eq!(err.code, "auth_failed");
+    assert!(!err.message.is_empty());
+
+    let err = js_error(decrypt(&key, &enc, None).unwrap_err());
+    assert_eq!(err.code, "auth_failed");
+}
+
+#[wasm_bindgen_test]
+fn invalid_key_code() {
+    let err = js_error(encrypt(&[0u8; 16], b"hello", None).unwrap_err());
+    assert_eq!(err.code, "invalid_key");
+}
+
+#[wasm_bindgen_test]
+fn derive_key_separates_purposes() {
+    let master = generate_key();
+    let enc_key = derive_key(&master, b"B4AE-v1-encryption-key", 32).unwrap().to_vec();
+    let mac_key = derive_key(&master, b"B4AE-v1-authentication-key", 32).unwrap().to_vec();
+    assert_eq!(enc_key.len(), 32);
+    assert_ne!(enc_key, mac_key);
+    assert_eq!(enc_key, derive_key(&master, b"B4AE-v1-encryption-key", 32).unwrap().to_vec());
+    assert_eq!(enc_key, b4ae::crypto::hkdf::derive_key(&[&master], b"B4AE-v1-encryption-key", 32).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn derive_key_rejects_bad_lengths() {
+    let master = generate_key();
+    assert_eq!(js_error(derive_key(&master, b"info", 0).unwrap_err()).code, "invalid_input");
+    assert_eq!(js_error(derive_key(&master, b"info", 255 * 32 + 1).unwrap_err()).code, "invalid_input");
+    assert_eq!(derive_key(&master, b"info", 255 * 32).unwrap().length(), 255 * 32);
+    assert_eq!(js_error(derive_key(&master, &[0u8; 1025], 32).unwrap_err()).code, "invalid_input");
+}
+
+#[wasm_bindgen_test]
+fn base64url_roundtrip() {
+    assert_eq!(to_base64url(&[0xfb, 0xff, 0xbf]), "-_-_");
+    assert_eq!(to_base64url(b"B4AE"), "QjRBRQ");
+    assert_eq!(to_base64url(&[]), "");
+    assert_eq!(from_base64url("").unwrap(), Vec::<u8>::new());
+
+    let data: Vec<u8> = (0..=255).collect();
+    for n in 0..data.len() {
+        assert_eq!(from_base64url(&to_base64url(&data[..n])).unwrap(), &data[..n]);
+    }
+}
+
+#[wasm_bindgen_test]
+fn base64url_rejects_invalid() {
+    for invalid in ["QjRBRQ==", "+/+/", "QjRB RQ", "QjRBR", "QjRBRR"] {
+        assert_eq!(js_error(from_base64url(invalid).unwrap_err()).code, "invalid_input");
+    }
+}
+
+fn encrypt_stream(key: &[u8], data: &[u8], sizes: &[usize]) -> (Vec<Vec<u8>>, Vec<u8>) {
+    let mut enc = Encryptor::new(key).unwrap();
+    let mut chunks = Vec::new();
+    let mut rest = data;
+    for &size in sizes.iter().cycle() {
+        if rest.is_empty() {
+            break;
+        }
+        let (chunk, tail) = rest.split_at(size.min(rest.len()));
+        chunks.push(enc.update(chunk).unwrap());
+        rest = tail;
+    }
+    (chunks, enc.finalize().unwrap())
+}
+
+#[wasm_bindgen_test]
+fn stream_roundtrip_uneven_chunks() {
+    let key = generate_key();
+    let data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let (chunks, last) = encrypt_stream(&key, &data, &[65536, 1, 100_003, 7777]);
+
+    let mut dec = Decryptor::new(&key).unwrap();
+    let mut out = Vec::with_capacity(data.len());
+    for chunk in &chunks {
+        out.extend(dec.update(chunk).unwrap());
+    }
+    dec.finalize(&last).unwrap();
+    assert_eq!(out, data);
+}
+
+#[wasm_bindgen_test]
+fn stream_truncation_fails() {
+    let key = generate_key();
+    let (chunks, _last) = encrypt_stream(&key, &[1u8; 1000], &[300]);
+
+    let mut dec = Decryptor::new(&key).unwrap();
+    for chunk in &chunks[..chunks.len() - 1] {
+        dec.update(chunk).unwrap();
+    }
+    let err = js_error(dec.finalize(&chunks[chunks.len() - 1]).unwrap_err());
+    assert_eq!(err.code, "auth_failed");
+}
+
+#[wasm_bindgen_test]
+fn stream_out_of_order_fails() {
+    let key = generate_key();
+    let (chunks, _last) = encrypt_stream(&key, &[2u8; 1000], &[300]);
+
+    let mut dec = Decryptor::new(&key).unwrap();
+    dec.update(&chunks[0]).unwrap();
+    assert_eq!(js_error(dec.update(&chunks[2]).unwrap_err()).code, "auth_failed");
+}
+
+#[wasm_bindgen_test]
+fn stream_update_after_finalize_fails() {
+    let key = generate_key();
+    let mut enc = Encryptor::new(&key).unwrap();
+    enc.finalize().unwrap();
+    assert_eq!(js_error(enc.update(b"late").unwrap_err()).code, "invalid_state");
+}
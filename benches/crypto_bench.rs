@@ -105,6 +105,34 @@ fn bench_aes_gcm_decrypt(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_aes_gcm_batch(c: &mut Criterion) {
+    let key = aes_gcm::AesKey::generate();
+    let records: Vec<Vec<u8>> = (0..4096).map(|i| vec![i as u8; 256]).collect();
+    let items: Vec<&[u8]> = records.iter().map(|r| r.as_slice()).collect();
+
+    let mut group = c.benchmark_group("aes_gcm_batch_4096x256");
+    group.throughput(Throughput::Elements(items.len() as u64));
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let sealed: Vec<Vec<u8>> = items
+                .iter()
+                .map(|item| aes_gcm::encrypt_combined(&key, item, b"").unwrap())
+                .collect();
+            black_box(sealed)
+        })
+    });
+
+    // Parallel only with `--features rayon`; otherwise measures the batch overhead
+    group.bench_function("encrypt_batch", |b| {
+        b.iter(|| {
+            black_box(aes_gcm::encrypt_batch(&key, &items).unwrap())
+        })
+    });
+
+    group.finish();
+}
+
 fn bench_hkdf_derive(c: &mut Criterion) {
     let secret = vec![0x42; 32];
     let info = b"benchmark-info";
@@ -204,6 +232,7 @@ criterion_group!(
     bench_dilithium_sign,
     bench_dilithium_verify,
     bench_aes_gcm_encrypt,
+    bench_aes_gcm_batch,
     bench_aes_gcm_decrypt,
     bench_hkdf_derive,
     bench_hybrid_keygen,
@@ -15,22 +15,108 @@ void b4ae_free(uint8_t *ptr);
 /** Generate 32-byte key. Caller must free with b4ae_free. */
 uint8_t *b4ae_generate_key(size_t *out_len);
 
-/** Encrypt plaintext. Returns [nonce(12)||ciphertext], caller frees. */
+/* Argon2id parameters used by b4ae_derive_key (fixed for cross-device reproducibility) */
+#define B4AE_ARGON2_MEMORY_KIB 19456
+#define B4AE_ARGON2_ITERATIONS 2
+#define B4AE_ARGON2_PARALLELISM 1
+
+/** Generate 16-byte random salt. Caller must free with b4ae_free. */
+uint8_t *b4ae_generate_salt(size_t *out_len);
+
+/** Derive 32-byte key from passphrase+salt (salt >= 8 bytes) with Argon2id. Caller frees. */
+uint8_t *b4ae_derive_key(
+    const uint8_t *passphrase,
+    size_t passphrase_len,
+    const uint8_t *salt,
+    size_t salt_len,
+    size_t *out_len);
+
+/** Encrypt plaintext. Returns [nonce(12)||ciphertext], caller frees.
+ *  aad is authenticated but not encrypted; may be NULL when aad_len is 0. */
 uint8_t *b4ae_encrypt(
     const uint8_t *key,
     size_t key_len,
     const uint8_t *plaintext,
     size_t plaintext_len,
+    const uint8_t *aad,
+    size_t aad_len,
     size_t *out_len);
 
-/** Decrypt [nonce(12)||ciphertext]. Caller frees result. */
+/** Decrypt [nonce(12)||ciphertext]. Caller frees result.
+ *  Returns NULL if aad differs from the value used at encryption. */
 uint8_t *b4ae_decrypt(
     const uint8_t *key,
     size_t key_len,
     const uint8_t *encrypted,
     size_t encrypted_len,
+    const uint8_t *aad,
+    size_t aad_len,
+    size_t *out_len);
+
+/** Algorithm tag prefixed to ChaCha20-Poly1305 output */
+#define B4AE_ALG_CHACHA20_POLY1305 0x02
+
+/** Encrypt with ChaCha20-Poly1305 (for devices without AES acceleration).
+ *  Returns [alg(1)=0x02||nonce(12)||ciphertext], caller frees. */
+uint8_t *b4ae_encrypt_chacha(
+    const uint8_t *key,
+    size_t key_len,
+    const uint8_t *plaintext,
+    size_t plaintext_len,
+    const uint8_t *aad,
+    size_t aad_len,
+    size_t *out_len);
+
+/** Decrypt [alg(1)=0x02||nonce(12)||ciphertext]. Caller frees result.
+ *  Returns NULL on wrong algorithm tag, AAD mismatch or tampering. */
+uint8_t *b4ae_decrypt_chacha(
+    const uint8_t *key,
+    size_t key_len,
+    const uint8_t *encrypted,
+    size_t encrypted_len,
+    const uint8_t *aad,
+    size_t aad_len,
+    size_t *out_len);
+
+/* Streaming AES-256-GCM (STREAM construction) for large files.
+ * Pass every buffer returned by encrypt_update/encrypt_final, unchanged and
+ * in order, to one decrypt_update/decrypt_final call. */
+typedef struct B4aeEncryptCtx B4aeEncryptCtx;
+typedef struct B4aeDecryptCtx B4aeDecryptCtx;
+
+/** Create encryption context. Free with b4ae_ctx_free. */
+B4aeEncryptCtx *b4ae_encrypt_init(const uint8_t *key, size_t key_len);
+
+/** Encrypt one chunk. First output is prefixed with the 7-byte base nonce. */
+uint8_t *b4ae_encrypt_update(
+    B4aeEncryptCtx *ctx,
+    const uint8_t *chunk,
+    size_t chunk_len,
     size_t *out_len);
 
+/** Emit the final (last-flagged) chunk. Caller frees. */
+uint8_t *b4ae_encrypt_final(B4aeEncryptCtx *ctx, size_t *out_len);
+
+/** Free encryption context. Safe with NULL. */
+void b4ae_ctx_free(B4aeEncryptCtx *ctx);
+
+/** Create decryption context. Free with b4ae_decrypt_ctx_free. */
+B4aeDecryptCtx *b4ae_decrypt_init(const uint8_t *key, size_t key_len);
+
+/** Decrypt one chunk. Returns NULL on tampering or reordering; an empty
+ *  chunk yields a non-NULL buffer with *out_len = 0. */
+uint8_t *b4ae_decrypt_update(
+    B4aeDecryptCtx *ctx,
+    const uint8_t *chunk,
+    size_t chunk_len,
+    size_t *out_len);
+
+/** Verify final chunk. Returns 0 if the stream is complete, -1 if truncated/tampered. */
+int32_t b4ae_decrypt_final(B4aeDecryptCtx *ctx, const uint8_t *chunk, size_t chunk_len);
+
+/** Free decryption context. Safe with NULL. */
+void b4ae_decrypt_ctx_free(B4aeDecryptCtx *ctx);
+
 #ifdef __cplusplus
 }
 #endif
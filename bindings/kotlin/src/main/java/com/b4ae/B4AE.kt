@@ -3,6 +3,9 @@ package com.b4ae
 /**
  * B4AE Kotlin/Android bindings - AES-256-GCM encrypt/decrypt
  * Loads libb4ae_android.so from JNI
+ *
+ * Native calls throw IllegalArgumentException for invalid arguments and
+ * B4AEException (with the failure reason) for crypto errors.
  */
 object B4AE {
 
@@ -13,9 +16,18 @@ object B4AE {
     }
 
     external fun nativeGenerateKey(): ByteArray
+    @Throws(B4AEException::class)
     external fun nativeEncrypt(key: ByteArray, plaintext: ByteArray): ByteArray?
+    @Throws(B4AEException::class)
     external fun nativeDecrypt(key: ByteArray, encrypted: ByteArray): ByteArray?
 
+    /** Encrypt the remaining bytes of direct [plaintext] into direct [output] at its position (must not overlap); returns bytes written and sets the output limit to their end. */
+    @Throws(B4AEException::class)
+    external fun nativeEncryptDirect(key: ByteArray, plaintext: java.nio.ByteBuffer, output: java.nio.ByteBuffer): Int
+    /** Decrypt the remaining bytes of direct [encrypted] into direct [output] at its position (must not overlap); returns plaintext length and sets the output limit to its end. */
+    @Throws(B4AEException::class)
+    external fun nativeDecryptDirect(key: ByteArray, encrypted: java.nio.ByteBuffer, output: java.nio.ByteBuffer): Int
+
     fun generateKey(): ByteArray = nativeGenerateKey()
 
     fun encrypt(key: ByteArray, plaintext: ByteArray): ByteArray {
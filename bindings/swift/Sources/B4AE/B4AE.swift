@@ -14,6 +14,8 @@ private func b4ae_encrypt(
     _ keyLen: Int,
     _ plaintext: UnsafePointer<UInt8>?,
     _ plaintextLen: Int,
+    _ aad: UnsafePointer<UInt8>?,
+    _ aadLen: Int,
     _ outLen: UnsafeMutablePointer<Int>?
 ) -> UnsafeMutablePointer<UInt8>?
 
@@ -23,6 +25,8 @@ private func b4ae_decrypt(
     _ keyLen: Int,
     _ encrypted: UnsafePointer<UInt8>?,
     _ encryptedLen: Int,
+    _ aad: UnsafePointer<UInt8>?,
+    _ aadLen: Int,
     _ outLen: UnsafeMutablePointer<Int>?
 ) -> UnsafeMutablePointer<UInt8>?
 
@@ -43,20 +47,25 @@ public enum B4AE {
     }
 
     /// Encrypt plaintext. Returns [nonce(12) || ciphertext]
-    public static func encrypt(key: Data, plaintext: Data) throws -> Data {
+    /// `aad` is authenticated but not encrypted (e.g. message ID, sender ID)
+    public static func encrypt(key: Data, plaintext: Data, aad: Data = Data()) throws -> Data {
         guard key.count == keySize else {
             throw B4AEError.invalidKeySize
         }
         var outLen: Int = 0
         let result = key.withUnsafeBytes { k in
             plaintext.withUnsafeBytes { p in
-                b4ae_encrypt(
-                    k.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                    key.count,
-                    p.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                    plaintext.count,
-                    &outLen
-                )
+                aad.withUnsafeBytes { a in
+                    b4ae_encrypt(
+                        k.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                        key.count,
+                        p.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                        plaintext.count,
+                        a.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                        aad.count,
+                        &outLen
+                    )
+                }
             }
         }
         guard let result = result, outLen > 0 else {
@@ -67,7 +76,8 @@ public enum B4AE {
     }
 
     /// Decrypt [nonce(12) || ciphertext]
-    public static func decrypt(key: Data, encrypted: Data) throws -> Data {
+    /// `aad` must match the value passed to `encrypt`
+    public static func decrypt(key: Data, encrypted: Data, aad: Data = Data()) throws -> Data {
         guard key.count == keySize else {
             throw B4AEError.invalidKeySize
         }
@@ -77,13 +87,17 @@ public enum B4AE {
         var outLen: Int = 0
         let result = key.withUnsafeBytes { k in
             encrypted.withUnsafeBytes { e in
-                b4ae_decrypt(
-                    k.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                    key.count,
-                    e.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                    encrypted.count,
-                    &outLen
-                )
+                aad.withUnsafeBytes { a in
+                    b4ae_decrypt(
+                        k.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                        key.count,
+                        e.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                        encrypted.count,
+                        a.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                        aad.count,
+                        &outLen
+                    )
+                }
             }
         }
         guard let result = result, outLen > 0 else {
@@ -0,0 +1,108 @@
+//! SQLite-backed audit event storage
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+
+use crate::AuditEventItem;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS audit_events (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp_ms INTEGER NOT NULL,
+    event_type   TEXT NOT NULL,
+    peer_id_hash TEXT,
+    context      TEXT
+);
+CREATE INDEX IF NOT EXISTS audit_events_timestamp ON audit_events (timestamp_ms, id);
+";
+
+/// Optional filters for [`AuditStore::list`]; the time range is inclusive.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub event_type: Option<String>,
+    pub from_ms: Option<u64>,
+    pub to_ms: Option<u64>,
+}
+
+/// Shared WHERE clause; a NULL parameter disables its filter
+const FILTER_CLAUSE: &str = "
+    WHERE (?1 IS NULL OR event_type = ?1)
+      AND (?2 IS NULL OR timestamp_ms >= ?2)
+      AND (?3 IS NULL OR timestamp_ms <= ?3)";
+
+/// Audit event store; the schema is created on open if absent.
+pub struct AuditStore {
+    conn: Mutex<Connection>,
+}
+
+impl AuditStore {
+    /// Open (or create) a database file
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Open a private in-memory database (tests, ephemeral deployments)
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Insert one event
+    pub fn insert(&self, event: &AuditEventItem) -> rusqlite::Result<()> {
+        self.conn().execute(
+            "INSERT INTO audit_events (timestamp_ms, event_type, peer_id_hash, context)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                event.timestamp_ms as i64,
+                event.event_type,
+                event.peer_id_hash,
+                event.context
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// One page of matching events, oldest first, plus the total match count
+    pub fn list(
+        &self,
+        filter: &AuditFilter,
+        limit: u32,
+        offset: u32,
+    ) -> rusqlite::Result<(Vec<AuditEventItem>, usize)> {
+        // Clamp to SQLite's signed range; out-of-range bounds still compare correctly
+        let from = filter.from_ms.map(|v| v.min(i64::MAX as u64) as i64);
+        let to = filter.to_ms.map(|v| v.min(i64::MAX as u64) as i64);
+        let conn = self.conn();
+        let total: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM audit_events {}", FILTER_CLAUSE),
+            params![filter.event_type, from, to],
+            |r| r.get(0),
+        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT timestamp_ms, event_type, peer_id_hash, context
+             FROM audit_events {} ORDER BY timestamp_ms, id LIMIT ?4 OFFSET ?5",
+            FILTER_CLAUSE
+        ))?;
+        let events = stmt
+            .query_map(params![filter.event_type, from, to, limit, offset], |row| {
+                Ok(AuditEventItem {
+                    timestamp_ms: row.get::<_, i64>(0)? as u64,
+                    event_type: row.get(1)?,
+                    peer_id_hash: row.get(2)?,
+                    context: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok((events, total as usize))
+    }
+}
@@ -0,0 +1,233 @@
+//! B4AE Enterprise Control Plane MVP
+//!
+//! Minimal REST API for audit events, persisted in SQLite.
+
+pub mod db;
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Query, State,
+    },
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use tower_http::cors::{Any, CorsLayer};
+
+use crate::db::{AuditFilter, AuditStore};
+
+/// Default page size for `GET /audit/events`
+const DEFAULT_LIMIT: u32 = 50;
+/// Maximum page size for `GET /audit/events`
+const MAX_LIMIT: u32 = 500;
+/// Maximum length of `event_type`
+const MAX_EVENT_TYPE_LEN: usize = 64;
+/// Length of `peer_id_hash` (hex of the 8-byte keyed hash)
+const PEER_ID_HASH_LEN: usize = 16;
+/// Maximum length of `context`
+const MAX_CONTEXT_LEN: usize = 1024;
+/// Buffered events per `/audit/stream` subscriber before it starts lagging
+const STREAM_CAPACITY: usize = 256;
+/// Interval between SSE keep-alive comments
+const STREAM_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Serialize)]
+struct HealthResponse {
+    status: String,
+    version: String,
+}
+
+/// Audit event as exposed by the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEventItem {
+    pub timestamp_ms: u64,
+    pub event_type: String,
+    pub peer_id_hash: Option<String>,
+    pub context: Option<String>,
+}
+
+impl AuditEventItem {
+    fn validate(&self) -> Result<(), String> {
+        if self.timestamp_ms > i64::MAX as u64 {
+            return Err("timestamp_ms out of range".to_string());
+        }
+        if self.event_type.is_empty() || self.event_type.len() > MAX_EVENT_TYPE_LEN {
+            return Err(format!("event_type must be 1-{} characters", MAX_EVENT_TYPE_LEN));
+        }
+        if !self.event_type.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err("event_type must be alphanumeric or '_'".to_string());
+        }
+        // Only `b4ae::audit::hash_peer_id` output is accepted, so raw peer IDs
+        // are never stored
+        if let Some(hash) = &self.peer_id_hash {
+            let well_formed = hash.len() == PEER_ID_HASH_LEN
+                && hash.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+            if !well_formed {
+                return Err(format!("peer_id_hash must be {} lowercase hex characters", PEER_ID_HASH_LEN));
+            }
+        }
+        if self.context.as_ref().is_some_and(|c| c.len() > MAX_CONTEXT_LEN) {
+            return Err(format!("context must be at most {} bytes", MAX_CONTEXT_LEN));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AuditListResponse {
+    pub events: Vec<AuditEventItem>,
+    pub total: usize,
+}
+
+#[derive(Deserialize)]
+struct AuditQuery {
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    offset: Option<u32>,
+    #[serde(default)]
+    event_type: Option<String>,
+    #[serde(default)]
+    from_ms: Option<u64>,
+    #[serde(default)]
+    to_ms: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct StreamQuery {
+    #[serde(default)]
+    event_type: Option<String>,
+}
+
+/// JSON error body: `{"error": "..."}`
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Internal(String),
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(e: rusqlite::Error) -> Self {
+        ApiError::Internal(format!("database error: {}", e))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(m) => (StatusCode::BAD_REQUEST, m),
+            ApiError::Internal(m) => (StatusCode::INTERNAL_SERVER_ERROR, m),
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+/// Shared handler state
+#[derive(Clone)]
+pub struct AppState {
+    store: Arc<AuditStore>,
+    /// Feeds `/audit/stream` with every successfully stored event
+    events: broadcast::Sender<AuditEventItem>,
+}
+
+impl AppState {
+    pub fn new(store: AuditStore) -> Self {
+        let (events, _) = broadcast::channel(STREAM_CAPACITY);
+        Self { store: Arc::new(store), events }
+    }
+}
+
+/// Build the API router
+pub fn app(state: AppState) -> Router {
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any);
+
+    Router::new()
+        .route("/health", get(health))
+        .route("/audit/events", get(audit_events).post(create_audit_event))
+        .route("/audit/stream", get(audit_stream))
+        .layer(cors)
+        .with_state(state)
+}
+
+async fn health() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    })
+}
+
+async fn audit_events(
+    State(state): State<AppState>,
+    query: Result<Query<AuditQuery>, QueryRejection>,
+) -> Result<Json<AuditListResponse>, ApiError> {
+    let Query(params) = query.map_err(|e| ApiError::BadRequest(e.body_text()))?;
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let offset = params.offset.unwrap_or(0);
+    if let (Some(from), Some(to)) = (params.from_ms, params.to_ms) {
+        if from > to {
+            return Err(ApiError::BadRequest(format!(
+                "from_ms ({}) must be <= to_ms ({})",
+                from, to
+            )));
+        }
+    }
+    let filter = AuditFilter {
+        event_type: params.event_type,
+        from_ms: params.from_ms,
+        to_ms: params.to_ms,
+    };
+
+    let store = state.store.clone();
+    let (events, total) = tokio::task::spawn_blocking(move || store.list(&filter, limit, offset))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))??;
+    Ok(Json(AuditListResponse { events, total }))
+}
+
+async fn create_audit_event(
+    State(state): State<AppState>,
+    body: Result<Json<AuditEventItem>, JsonRejection>,
+) -> Result<(StatusCode, Json<AuditEventItem>), ApiError> {
+    let Json(event) = body.map_err(|e| ApiError::BadRequest(e.body_text()))?;
+    event.validate().map_err(ApiError::BadRequest)?;
+
+    let store = state.store.clone();
+    let stored = event.clone();
+    tokio::task::spawn_blocking(move || store.insert(&stored))
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))??;
+    // No subscribers is not an error
+    let _ = state.events.send(event.clone());
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+async fn audit_stream(
+    State(state): State<AppState>,
+    query: Result<Query<StreamQuery>, QueryRejection>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let Query(params) = query.map_err(|e| ApiError::BadRequest(e.body_text()))?;
+    let stream = BroadcastStream::new(state.events.subscribe()).filter_map(move |item| {
+        // Lagged subscribers skip the missed events rather than disconnecting
+        let event = item.ok()?;
+        if params.event_type.as_ref().is_some_and(|t| *t != event.event_type) {
+            return None;
+        }
+        Event::default().json_data(&event).ok().map(Ok)
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(STREAM_KEEP_ALIVE)))
+}
@@ -0,0 +1,231 @@
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use b4ae_enterprise_api::{app, db::AuditStore, AppState, AuditEventItem, AuditListResponse};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+fn test_app() -> Router {
+    app(AppState::new(AuditStore::open_in_memory().unwrap()))
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+fn post_event(body: String) -> Request<Body> {
+    Request::post("/audit/events")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn event(i: u64) -> AuditEventItem {
+    AuditEventItem {
+        timestamp_ms: 1_700_000_000_000 + i,
+        event_type: "HandshakeCompleted".to_string(),
+        peer_id_hash: Some(format!("{:016x}", i)),
+        context: Some(format!("event-{}", i)),
+    }
+}
+
+async fn list(app: &Router, query: &str) -> AuditListResponse {
+    let request = Request::get(format!("/audit/events{}", query)).body(Body::empty()).unwrap();
+    let (status, body) = send(app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    serde_json::from_value(body).unwrap()
+}
+
+#[tokio::test]
+async fn insert_and_paginate() {
+    let app = test_app();
+    for i in 0..3 {
+        let (status, _) = send(&app, post_event(serde_json::to_string(&event(i)).unwrap())).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let all = list(&app, "").await;
+    assert_eq!(all.total, 3);
+    assert_eq!(all.events, vec![event(0), event(1), event(2)]);
+
+    let page = list(&app, "?limit=2").await;
+    assert_eq!(page.total, 3);
+    assert_eq!(page.events, vec![event(0), event(1)]);
+
+    let page = list(&app, "?limit=2&offset=2").await;
+    assert_eq!(page.events, vec![event(2)]);
+
+    let page = list(&app, "?offset=3").await;
+    assert!(page.events.is_empty());
+    assert_eq!(page.total, 3);
+}
+
+#[tokio::test]
+async fn rejects_invalid_events_with_json_errors() {
+    let app = test_app();
+
+    let (status, body) = send(&app, post_event("not json".to_string())).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].is_string());
+
+    let mut bad = event(0);
+    bad.event_type = String::new();
+    let (status, body) = send(&app, post_event(serde_json::to_string(&bad).unwrap())).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("event_type"));
+
+    // Anything but a keyed peer-ID hash is rejected, including raw hex IDs
+    for raw in ["not-hex", "alice", "a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4", "00112233AABBCCDD"] {
+        let mut bad = event(0);
+        bad.peer_id_hash = Some(raw.to_string());
+        let (status, body) = send(&app, post_event(serde_json::to_string(&bad).unwrap())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", raw);
+        assert!(body["error"].as_str().unwrap().contains("peer_id_hash"));
+    }
+
+    assert_eq!(list(&app, "").await.total, 0);
+}
+
+async fn seed(app: &Router) -> Vec<AuditEventItem> {
+    let events: Vec<AuditEventItem> = (0..6)
+        .map(|i| AuditEventItem {
+            event_type: if i % 2 == 0 { "HandshakeCompleted" } else { "AuthFailed" }.to_string(),
+            ..event(i * 10)
+        })
+        .collect();
+    for e in &events {
+        let (status, _) = send(app, post_event(serde_json::to_string(e).unwrap())).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+    events
+}
+
+#[tokio::test]
+async fn filters_by_event_type() {
+    let app = test_app();
+    let events = seed(&app).await;
+
+    let page = list(&app, "?event_type=AuthFailed").await;
+    assert_eq!(page.total, 3);
+    assert_eq!(page.events, vec![events[1].clone(), events[3].clone(), events[5].clone()]);
+
+    assert_eq!(list(&app, "?event_type=KeyRotation").await.total, 0);
+}
+
+#[tokio::test]
+async fn filters_by_time_range() {
+    let app = test_app();
+    let events = seed(&app).await;
+    let base = events[0].timestamp_ms;
+
+    let page = list(&app, &format!("?from_ms={}", base + 30)).await;
+    assert_eq!(page.events, events[3..].to_vec());
+
+    let page = list(&app, &format!("?to_ms={}", base + 20)).await;
+    assert_eq!(page.events, events[..3].to_vec());
+
+    let page = list(&app, &format!("?from_ms={}&to_ms={}", base + 10, base + 30)).await;
+    assert_eq!(page.events, events[1..4].to_vec());
+    assert_eq!(page.total, 3);
+}
+
+#[tokio::test]
+async fn filters_combined_with_pagination() {
+    let app = test_app();
+    let events = seed(&app).await;
+    let base = events[0].timestamp_ms;
+
+    let query = format!("?event_type=HandshakeCompleted&from_ms={}&to_ms={}", base + 10, base + 50);
+    let page = list(&app, &query).await;
+    assert_eq!(page.events, vec![events[2].clone(), events[4].clone()]);
+
+    let page = list(&app, &format!("{}&limit=1&offset=1", query)).await;
+    assert_eq!(page.events, vec![events[4].clone()]);
+    assert_eq!(page.total, 2);
+}
+
+#[tokio::test]
+async fn rejects_inverted_time_range() {
+    let app = test_app();
+    let request = Request::get("/audit/events?from_ms=200&to_ms=100").body(Body::empty()).unwrap();
+    let (status, body) = send(&app, request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("from_ms"));
+}
+
+#[tokio::test]
+async fn rejects_malformed_query_with_json_errors() {
+    let app = test_app();
+    for uri in ["/audit/events?limit=abc", "/audit/events?from_ms=-1", "/audit/stream?event_type=a&event_type=b"] {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let (status, body) = send(&app, request).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert!(body["error"].is_string(), "{}", uri);
+    }
+}
+
+/// Read SSE `data:` payloads until `count` arrive or the timeout expires
+async fn read_sse(body: Body, count: usize) -> Vec<AuditEventItem> {
+    let mut body = body;
+    let mut buffer = String::new();
+    let mut events = Vec::new();
+    let read = async {
+        while events.len() < count {
+            let frame = body.frame().await.unwrap().unwrap();
+            let Some(chunk) = frame.data_ref() else { continue };
+            buffer.push_str(std::str::from_utf8(chunk).unwrap());
+            while let Some(end) = buffer.find("\n\n") {
+                let message: String = buffer.drain(..end + 2).collect();
+                for line in message.lines() {
+                    if let Some(data) = line.strip_prefix("data:") {
+                        events.push(serde_json::from_str(data.trim()).unwrap());
+                    }
+                }
+            }
+        }
+    };
+    tokio::time::timeout(std::time::Duration::from_secs(5), read)
+        .await
+        .expect("timed out waiting for SSE events");
+    events
+}
+
+#[tokio::test]
+async fn streams_new_events() {
+    let app = test_app();
+    let response = app
+        .clone()
+        .oneshot(Request::get("/audit/stream").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/event-stream");
+
+    let (status, _) = send(&app, post_event(serde_json::to_string(&event(7)).unwrap())).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    assert_eq!(read_sse(response.into_body(), 1).await, vec![event(7)]);
+}
+
+#[tokio::test]
+async fn stream_filters_by_event_type() {
+    let app = test_app();
+    let response = app
+        .clone()
+        .oneshot(Request::get("/audit/stream?event_type=AuthFailed").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    let failed = AuditEventItem { event_type: "AuthFailed".to_string(), ..event(2) };
+    for e in [event(1), failed.clone()] {
+        let (status, _) = send(&app, post_event(serde_json::to_string(&e).unwrap())).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    assert_eq!(read_sse(response.into_body(), 1).await, vec![failed]);
+}
@@ -0,0 +1,28 @@
+//! Fuzz target untuk SecurityHandshakeMessageParser
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use b4ae::security::{SecretBuffer, SecurityBuffer, SecurityHandshakeMessageParser as Parser, SecurityResult};
+
+/// Parse `data`; if accepted, serialize and parse again and require equality
+fn check_roundtrip<T: PartialEq + std::fmt::Debug>(
+    data: &[u8],
+    parse: fn(&mut SecretBuffer) -> SecurityResult<T>,
+    serialize: fn(&T, &mut SecurityBuffer) -> SecurityResult<()>,
+) {
+    let Ok(mut buffer) = SecretBuffer::from_slice(data) else { return };
+    let Ok(message) = parse(&mut buffer) else { return };
+
+    let mut encoded = SecurityBuffer::new(data.len()).expect("message buffer");
+    serialize(&message, &mut encoded).expect("serialize accepted message");
+    let mut encoded = SecretBuffer::from_slice(encoded.as_slice()).expect("message buffer");
+    let reparsed = parse(&mut encoded).expect("reparse serialized message");
+    assert_eq!(reparsed, message);
+    assert_eq!(encoded.remaining(), 0);
+}
+
+fuzz_target!(|data: &[u8]| {
+    check_roundtrip(data, Parser::parse_init, Parser::serialize_init);
+    check_roundtrip(data, Parser::parse_response, Parser::serialize_response);
+    check_roundtrip(data, Parser::parse_complete, Parser::serialize_complete);
+});
@@ -0,0 +1,17 @@
+//! Fuzz target untuk SecurityMessageHeader::parse_security
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use b4ae::security::{SecurityBuffer, SecurityMessageHeader};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(mut buffer) = SecurityBuffer::from_slice(data) else { return };
+    let Ok(header) = SecurityMessageHeader::parse_security(&mut buffer) else { return };
+
+    // An accepted header must survive parse -> serialize -> parse unchanged
+    let mut encoded = SecurityBuffer::new(SecurityMessageHeader::SIZE).expect("header buffer");
+    header.serialize_security(&mut encoded).expect("serialize accepted header");
+    assert_eq!(encoded.len(), SecurityMessageHeader::SIZE);
+    let reparsed = SecurityMessageHeader::parse_security(&mut encoded).expect("reparse serialized header");
+    assert_eq!(reparsed, header);
+});
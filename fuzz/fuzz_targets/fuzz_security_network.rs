@@ -0,0 +1,36 @@
+//! Fuzz target untuk SecurityNetworkParser dan SecurityIncrementalParser
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use b4ae::security::fuzzing::NetworkFuzzingHarness;
+use b4ae::security::{
+    FuzzingConfig, FuzzingResult, SecurityBuffer, SecurityIncrementalParser, SecurityMessageHeader,
+    SecurityNetworkParser, SecurityStreamStatus, SEED_CORPUS_TIMESTAMP,
+};
+use b4ae::time::{Clock, MockClock};
+use std::sync::Arc;
+
+fuzz_target!(|data: &[u8]| {
+    // Timestamps are judged against the corpus's fixed clock, so the seeds never go stale
+    let clock: Arc<dyn Clock> = Arc::new(MockClock::new(SEED_CORPUS_TIMESTAMP));
+
+    // The module's harness classifies errors; a crash report is a finding
+    let mut harness = NetworkFuzzingHarness::new(FuzzingConfig::default()).with_clock(clock.clone());
+    assert!(!matches!(harness.fuzz_network_parsing(data), FuzzingResult::Crash(_)));
+
+    let parser = SecurityNetworkParser::new().with_clock(clock.clone());
+    let Ok(message) = parser.parse_message(data) else { return };
+
+    // An accepted message must survive parse -> serialize -> parse unchanged
+    let mut encoded = SecurityBuffer::new(SecurityMessageHeader::SIZE + message.payload.len()).expect("message buffer");
+    message.header.serialize_security(&mut encoded).expect("serialize accepted header");
+    encoded.write_slice(&message.payload).expect("serialize accepted payload");
+    let reparsed = parser.parse_message(encoded.data()).expect("reparse serialized message");
+    assert_eq!(reparsed, message);
+
+    // The incremental parser must agree with the one-shot parser
+    let mut incremental = SecurityIncrementalParser::with_parser(SecurityNetworkParser::new().with_clock(clock));
+    let (consumed, status) = incremental.feed(data).expect("incremental parse of accepted message");
+    assert_eq!(consumed, data.len());
+    assert_eq!(status, SecurityStreamStatus::Message(message));
+});
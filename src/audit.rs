@@ -2,36 +2,268 @@
 //!
 //! Events penting (handshake, key rotation, auth failure) dicatat
 //! untuk audit trail tanpa menyimpan data sensitif.
+//!
+//! Peer ID tidak pernah disimpan mentah: event hanya menerima [`PeerIdHash`]
+//! dari [`hash_peer_id`], yang di-key dengan salt milik deployment (lihat
+//! [`AuditSink::peer_id_salt`]).
 
+use crate::crypto::CryptoError;
+use crate::error::{B4aeError, B4aeResult};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
 use sha3::{Digest, Sha3_256};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::SystemTime;
 
 /// Hash data for audit (privacy-preserving, no raw IDs in logs)
+///
+/// Unkeyed; used for random identifiers such as session IDs. Peer IDs are
+/// guessable and must go through [`hash_peer_id`] instead.
 pub fn hash_for_audit(data: &[u8]) -> String {
     hex::encode(&Sha3_256::digest(data)[..8])
 }
 
+/// Bytes of HMAC output kept in a [`PeerIdHash`]
+pub const PEER_ID_HASH_SIZE: usize = 8;
+
+/// Minimum salt length accepted by the sinks' `with_peer_id_salt`
+pub const MIN_PEER_ID_SALT_LEN: usize = 16;
+
+fn check_peer_id_salt(salt: Vec<u8>) -> B4aeResult<Vec<u8>> {
+    if salt.len() < MIN_PEER_ID_SALT_LEN {
+        return Err(B4aeError::InvalidInput(format!(
+            "Peer ID salt too short: {} < {} bytes",
+            salt.len(),
+            MIN_PEER_ID_SALT_LEN
+        )));
+    }
+    Ok(salt)
+}
+
+/// Pseudonymous peer ID: hex of truncated `HMAC-SHA3-256(salt, peer_id)`
+///
+/// Only [`hash_peer_id`] creates one, so a raw ID cannot end up in an
+/// [`AuditEvent`]. Deserializing accepts only well-formed hashes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PeerIdHash(String);
+
+impl PeerIdHash {
+    /// Hex string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PeerIdHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for PeerIdHash {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let well_formed = value.len() == 2 * PEER_ID_HASH_SIZE
+            && value.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+        if well_formed {
+            Ok(PeerIdHash(value))
+        } else {
+            Err(format!("peer_id_hash must be {} lowercase hex characters", 2 * PEER_ID_HASH_SIZE))
+        }
+    }
+}
+
+impl From<PeerIdHash> for String {
+    fn from(hash: PeerIdHash) -> Self {
+        hash.0
+    }
+}
+
+/// Redact a peer ID for audit logs
+///
+/// `HMAC-SHA3-256` keyed with the deployment's `salt`: the same peer maps to
+/// the same hash within one deployment, so events can be joined, while
+/// deployments with different salts cannot correlate peers, and the ID cannot
+/// be recovered by hashing candidate IDs without the salt.
+pub fn hash_peer_id(peer_id: &[u8], salt: &[u8]) -> PeerIdHash {
+    let mut mac = <Hmac<Sha3_256> as Mac>::new_from_slice(salt).expect("HMAC accepts any key length");
+    mac.update(peer_id);
+    PeerIdHash(hex::encode(&mac.finalize().into_bytes()[..PEER_ID_HASH_SIZE]))
+}
+
+/// Random salt used by sinks that were not given one; lives for the process
+fn process_peer_id_salt() -> &'static [u8] {
+    static SALT: OnceLock<[u8; 32]> = OnceLock::new();
+    SALT.get_or_init(|| {
+        let mut salt = [0u8; 32];
+        crate::crypto::random::fill_random(&mut salt).expect("OS RNG unavailable");
+        salt
+    })
+}
+
+/// Handshake step at which a failure was detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HandshakeStage {
+    /// Mode negotiation (v2)
+    ModeNegotiation,
+    /// Cookie challenge (v2)
+    CookieChallenge,
+    /// Generating or processing HandshakeInit
+    Init,
+    /// Generating or processing HandshakeResponse
+    Response,
+    /// Generating or processing HandshakeComplete
+    Complete,
+    /// Deriving session keys and creating the session
+    Finalize,
+}
+
+/// Why a handshake failed (non-sensitive, stable for SIEM rules)
+///
+/// Derived from the error that aborted the handshake, see
+/// `From<&B4aeError>` and `From<&CryptoError>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum HandshakeFailureReason {
+    /// Peer signature did not verify (`CryptoError::VerificationFailed`)
+    BadSignature,
+    /// Peer identity does not match the pinned fingerprint
+    FingerprintMismatch,
+    /// Message was already seen
+    Replay,
+    /// DoS cookie missing, expired or forged
+    CookieInvalid,
+    /// Mode binding mismatch (downgrade attempt)
+    ModeDowngrade,
+    /// No mutually supported authentication mode
+    ModeNegotiationFailed,
+    /// Handshake did not finish in time
+    Timeout,
+    /// Message arrived with no matching handshake, or out of order
+    UnexpectedMessage,
+    /// Message could not be parsed or has invalid fields
+    MalformedMessage,
+    /// Key exchange or confirmation failed
+    KeyExchangeFailed,
+    /// Local failure (key generation, signing, configuration)
+    Internal,
+}
+
+impl From<&CryptoError> for HandshakeFailureReason {
+    fn from(err: &CryptoError) -> Self {
+        match err {
+            CryptoError::VerificationFailed(_) => HandshakeFailureReason::BadSignature,
+            CryptoError::FingerprintMismatch => HandshakeFailureReason::FingerprintMismatch,
+            CryptoError::InvalidInput(_) | CryptoError::InvalidKeySize(_) => HandshakeFailureReason::MalformedMessage,
+            CryptoError::DecryptionFailed(_) | CryptoError::AuthenticationFailed => {
+                HandshakeFailureReason::KeyExchangeFailed
+            }
+            _ => HandshakeFailureReason::Internal,
+        }
+    }
+}
+
+impl From<&B4aeError> for HandshakeFailureReason {
+    fn from(err: &B4aeError) -> Self {
+        match err {
+            B4aeError::CryptoError(e) => e.into(),
+            B4aeError::AuthenticationFailed => HandshakeFailureReason::BadSignature,
+            B4aeError::KeyExchangeFailed(_) => HandshakeFailureReason::KeyExchangeFailed,
+            B4aeError::InvalidInput(_) => HandshakeFailureReason::MalformedMessage,
+            B4aeError::ProtocolError(_) => HandshakeFailureReason::UnexpectedMessage,
+            _ => HandshakeFailureReason::Internal,
+        }
+    }
+}
+
+/// Error from one handshake step, with the reason to audit
+///
+/// Lets a step attach a more precise reason (e.g. [`HandshakeFailureReason::ModeDowngrade`])
+/// than the error alone carries.
+#[derive(Debug)]
+pub(crate) struct HandshakeFailure {
+    pub(crate) reason: HandshakeFailureReason,
+    pub(crate) error: B4aeError,
+}
+
+impl HandshakeFailure {
+    #[cfg(feature = "v2_protocol")]
+    pub(crate) fn new(reason: HandshakeFailureReason, error: B4aeError) -> Self {
+        Self { reason, error }
+    }
+}
+
+impl From<B4aeError> for HandshakeFailure {
+    fn from(error: B4aeError) -> Self {
+        Self { reason: (&error).into(), error }
+    }
+}
+
+impl From<CryptoError> for HandshakeFailure {
+    fn from(error: CryptoError) -> Self {
+        Self { reason: (&error).into(), error: error.into() }
+    }
+}
+
+/// Log the outcome of a handshake step, returning the plain result
+///
+/// Emits one `HandshakeFailed` on error; success is not logged here.
+pub(crate) fn audit_handshake_step<T>(
+    sink: Option<&Arc<dyn AuditSink>>,
+    peer_id: &[u8],
+    stage: HandshakeStage,
+    result: Result<T, HandshakeFailure>,
+) -> B4aeResult<T> {
+    result.map_err(|failure| {
+        if let Some(sink) = sink {
+            sink.log(AuditEntry::new(
+                AuditEvent::HandshakeFailed {
+                    peer_id_hash: hash_peer_id(peer_id, sink.peer_id_salt()),
+                    reason: failure.reason,
+                    stage,
+                },
+                None,
+            ));
+        }
+        failure.error
+    })
+}
+
 /// Audit event types
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum AuditEvent {
-    /// Handshake initiated
-    HandshakeInitiated {
+    /// Handshake started
+    #[serde(alias = "HandshakeInitiated")]
+    HandshakeStarted {
         /// Hash of peer ID (privacy-preserving, no raw ID stored)
-        peer_id_hash: String,
+        peer_id_hash: PeerIdHash,
     },
     /// Handshake completed
     HandshakeCompleted {
         /// Hash of peer ID
-        peer_id_hash: String,
+        peer_id_hash: PeerIdHash,
+        /// `"v1"`, or the v2 authentication mode (`"ModeA"`, `"ModeB"`)
+        #[serde(default)]
+        mode: String,
+        /// Time from handshake start to session creation on this side
+        #[serde(default)]
+        latency_ms: u64,
     },
     /// Handshake failed
     HandshakeFailed {
         /// Hash of peer ID
-        peer_id_hash: String,
+        peer_id_hash: PeerIdHash,
         /// Failure reason (non-sensitive)
-        reason: String,
+        reason: HandshakeFailureReason,
+        /// Step that failed
+        stage: HandshakeStage,
     },
     /// Key rotation triggered
     KeyRotation {
@@ -55,6 +287,31 @@ pub enum AuditEvent {
     },
 }
 
+impl AuditEvent {
+    /// Variant name, used as `event_type` in persisted records
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            AuditEvent::HandshakeStarted { .. } => "HandshakeStarted",
+            AuditEvent::HandshakeCompleted { .. } => "HandshakeCompleted",
+            AuditEvent::HandshakeFailed { .. } => "HandshakeFailed",
+            AuditEvent::KeyRotation { .. } => "KeyRotation",
+            AuditEvent::AuthFailed { .. } => "AuthFailed",
+            AuditEvent::SessionCreated { .. } => "SessionCreated",
+            AuditEvent::SessionClosed { .. } => "SessionClosed",
+        }
+    }
+
+    /// Peer ID hash, if the event is tied to a peer
+    pub fn peer_id_hash(&self) -> Option<&str> {
+        match self {
+            AuditEvent::HandshakeStarted { peer_id_hash }
+            | AuditEvent::HandshakeCompleted { peer_id_hash, .. }
+            | AuditEvent::HandshakeFailed { peer_id_hash, .. } => Some(peer_id_hash.as_str()),
+            _ => None,
+        }
+    }
+}
+
 /// Single audit log entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditEntry {
@@ -85,12 +342,21 @@ impl AuditEntry {
 pub trait AuditSink: Send + Sync {
     /// Log audit entry
     fn log(&self, entry: AuditEntry);
+
+    /// Salt for [`hash_peer_id`], configured per deployment
+    ///
+    /// Defaults to a random per-process salt, so hashes only join within one
+    /// run; sinks for persistent logs should return the operator's salt.
+    fn peer_id_salt(&self) -> &[u8] {
+        process_peer_id_salt()
+    }
 }
 
 /// In-memory audit sink (untuk testing)
 #[derive(Debug, Default)]
 pub struct MemoryAuditSink {
     entries: std::sync::Mutex<Vec<AuditEntry>>,
+    peer_id_salt: Option<Vec<u8>>,
 }
 
 impl MemoryAuditSink {
@@ -98,9 +364,19 @@ impl MemoryAuditSink {
     pub fn new() -> Self {
         Self {
             entries: std::sync::Mutex::new(Vec::new()),
+            peer_id_salt: None,
         }
     }
 
+    /// Use `salt` for peer-ID hashing instead of the per-process salt
+    ///
+    /// Fails with `B4aeError::InvalidInput` if `salt` is shorter than
+    /// [`MIN_PEER_ID_SALT_LEN`].
+    pub fn with_peer_id_salt(mut self, salt: impl Into<Vec<u8>>) -> B4aeResult<Self> {
+        self.peer_id_salt = Some(check_peer_id_salt(salt.into())?);
+        Ok(self)
+    }
+
     /// Get all logged entries
     pub fn entries(&self) -> Vec<AuditEntry> {
         self.entries
@@ -125,6 +401,13 @@ impl AuditSink for MemoryAuditSink {
             .unwrap_or_else(|e| e.into_inner())
             .push(entry);
     }
+
+    fn peer_id_salt(&self) -> &[u8] {
+        match &self.peer_id_salt {
+            Some(salt) => salt,
+            None => process_peer_id_salt(),
+        }
+    }
 }
 
 /// No-op sink (default when audit disabled)
@@ -135,15 +418,276 @@ impl AuditSink for NoOpAuditSink {
     fn log(&self, _entry: AuditEntry) {}
 }
 
+/// Domain label hashed into the genesis link of every audit chain
+const AUDIT_GENESIS_LABEL: &[u8] = b"B4AE-audit-chain-genesis-v1";
+
+/// Hash seeding the first `prev_hash` of an audit chain
+pub fn genesis_hash() -> [u8; 32] {
+    Sha3_256::digest(AUDIT_GENESIS_LABEL).into()
+}
+
+/// Every field of a [`JsonlAuditRecord`] except `entry_hash`, in file order
+#[derive(Serialize)]
+struct CanonicalRecord<'a> {
+    timestamp_ms: u64,
+    event_type: &'a str,
+    peer_id_hash: Option<&'a str>,
+    context: Option<&'a str>,
+    event: &'a AuditEvent,
+    prev_hash: &'a str,
+}
+
+/// `SHA3_256(prev_hash || canonical_record)`
+///
+/// The canonical record covers every stored field, including the
+/// `event_type` and `peer_id_hash` copies, so none can be edited unnoticed.
+fn chain_hash(prev_hash: &[u8], record: &JsonlAuditRecord) -> [u8; 32] {
+    let canonical = CanonicalRecord {
+        timestamp_ms: record.timestamp_ms,
+        event_type: &record.event_type,
+        peer_id_hash: record.peer_id_hash.as_deref(),
+        context: record.context.as_deref(),
+        event: &record.event,
+        prev_hash: &record.prev_hash,
+    };
+    let serialized = serde_json::to_vec(&canonical).unwrap_or_default();
+    let mut hasher = Sha3_256::new();
+    hasher.update(prev_hash);
+    hasher.update(&serialized);
+    hasher.finalize().into()
+}
+
+/// Verify a hash chain starting at [`genesis_hash`].
+///
+/// Returns the index of the first record whose link is broken, which
+/// detects deleted, reordered and mutated records.
+pub fn verify_chain(records: &[JsonlAuditRecord]) -> Result<(), usize> {
+    let mut prev = hex::encode(genesis_hash());
+    for (i, record) in records.iter().enumerate() {
+        if record.prev_hash != prev {
+            return Err(i);
+        }
+        let prev_bytes = hex::decode(&record.prev_hash).map_err(|_| i)?;
+        if hex::encode(chain_hash(&prev_bytes, record)) != record.entry_hash {
+            return Err(i);
+        }
+        prev = record.entry_hash.clone();
+    }
+    Ok(())
+}
+
+/// One line of a [`JsonlFileSink`] file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonlAuditRecord {
+    /// Timestamp (Unix ms)
+    pub timestamp_ms: u64,
+    /// Event variant name
+    pub event_type: String,
+    /// Hash of peer ID, if any
+    pub peer_id_hash: Option<String>,
+    /// Optional context (non-sensitive)
+    pub context: Option<String>,
+    /// Full event, so entries can be restored losslessly
+    pub event: AuditEvent,
+    /// Hex `entry_hash` of the previous record (genesis hash for the first)
+    #[serde(default)]
+    pub prev_hash: String,
+    /// Hex `SHA3_256(prev_hash || all other fields serialized)`
+    #[serde(default)]
+    pub entry_hash: String,
+}
+
+impl JsonlAuditRecord {
+    /// Build a record chained to `prev_hash`
+    fn chained(entry: &AuditEntry, prev_hash: &[u8; 32]) -> Self {
+        let mut record = Self {
+            timestamp_ms: entry.timestamp_ms,
+            event_type: entry.event.event_type().to_string(),
+            peer_id_hash: entry.event.peer_id_hash().map(str::to_string),
+            context: entry.context.clone(),
+            event: entry.event.clone(),
+            prev_hash: hex::encode(prev_hash),
+            entry_hash: String::new(),
+        };
+        record.entry_hash = hex::encode(chain_hash(prev_hash, &record));
+        record
+    }
+}
+
+impl From<JsonlAuditRecord> for AuditEntry {
+    fn from(record: JsonlAuditRecord) -> Self {
+        Self {
+            timestamp_ms: record.timestamp_ms,
+            event: record.event,
+            context: record.context,
+        }
+    }
+}
+
+struct JsonlFile {
+    file: File,
+    len: u64,
+    /// `entry_hash` of the last record written
+    last_hash: [u8; 32],
+}
+
+/// Persistent audit sink writing one JSON object per line.
+///
+/// The file is opened in append mode so entries survive restarts. Each entry
+/// is flushed immediately. When a write would push the file beyond
+/// `max_bytes`, it is renamed to the next free `<path>.N` and a fresh file
+/// is started; rotated files are never deleted.
+///
+/// Records are hash-chained inside the sink (see [`verify_chain`]); the chain
+/// continues across restarts and rotations.
+pub struct JsonlFileSink {
+    path: PathBuf,
+    max_bytes: u64,
+    file: Mutex<JsonlFile>,
+    peer_id_salt: Option<Vec<u8>>,
+}
+
+impl JsonlFileSink {
+    /// Open (or create) the sink at `path`, rotating beyond `max_bytes`
+    pub fn open(path: impl AsRef<Path>, max_bytes: u64) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = Self::open_append(&path)?;
+        let len = file.metadata()?.len();
+        let mut sink = Self {
+            path,
+            max_bytes,
+            file: Mutex::new(JsonlFile { file, len, last_hash: genesis_hash() }),
+            peer_id_salt: None,
+        };
+        let last_hash = sink.resume_chain()?;
+        sink.file.get_mut().unwrap_or_else(|e| e.into_inner()).last_hash = last_hash;
+        Ok(sink)
+    }
+
+    /// Use the deployment's `salt` for peer-ID hashing, so hashes in this
+    /// log stay joinable across restarts
+    ///
+    /// Fails with `B4aeError::InvalidInput` if `salt` is shorter than
+    /// [`MIN_PEER_ID_SALT_LEN`].
+    pub fn with_peer_id_salt(mut self, salt: impl Into<Vec<u8>>) -> B4aeResult<Self> {
+        self.peer_id_salt = Some(check_peer_id_salt(salt.into())?);
+        Ok(self)
+    }
+
+    /// Read all records (with chain hashes) from a JSONL audit file
+    pub fn read_records(path: impl AsRef<Path>) -> io::Result<Vec<JsonlAuditRecord>> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut records = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// Last `entry_hash` in the active file, or the newest rotated file
+    fn resume_chain(&self) -> io::Result<[u8; 32]> {
+        let mut candidates = vec![self.path.clone()];
+        let mut index = 1;
+        while self.rotated_path(index).exists() {
+            index += 1;
+        }
+        candidates.extend((1..index).rev().map(|i| self.rotated_path(i)));
+
+        for path in candidates {
+            if let Some(last) = Self::read_records(&path)?.pop() {
+                let bytes = hex::decode(&last.entry_hash)
+                    .ok()
+                    .and_then(|b| <[u8; 32]>::try_from(b).ok())
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid audit entry_hash"))?;
+                return Ok(bytes);
+            }
+        }
+        Ok(genesis_hash())
+    }
+
+    /// Active log file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read all entries from a JSONL audit file, in write order
+    pub fn read_entries(path: impl AsRef<Path>) -> io::Result<Vec<AuditEntry>> {
+        Ok(Self::read_records(path)?.into_iter().map(AuditEntry::from).collect())
+    }
+
+    fn open_append(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    fn rotated_path(&self, index: u32) -> PathBuf {
+        let mut name = self.path.as_os_str().to_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&self, state: &mut JsonlFile) -> io::Result<()> {
+        let mut index = 1;
+        while self.rotated_path(index).exists() {
+            index += 1;
+        }
+        std::fs::rename(&self.path, self.rotated_path(index))?;
+        state.file = Self::open_append(&self.path)?;
+        state.len = 0;
+        Ok(())
+    }
+
+    fn write_entry(&self, entry: &AuditEntry) -> io::Result<()> {
+        let mut state = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        let record = JsonlAuditRecord::chained(entry, &state.last_hash);
+        let mut line = serde_json::to_vec(&record)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        line.push(b'\n');
+
+        if state.len > 0 && state.len + line.len() as u64 > self.max_bytes {
+            self.rotate(&mut state)?;
+        }
+        state.file.write_all(&line)?;
+        state.file.flush()?;
+        state.len += line.len() as u64;
+        state.last_hash = chain_hash(&state.last_hash, &record);
+        Ok(())
+    }
+}
+
+impl AuditSink for JsonlFileSink {
+    fn log(&self, entry: AuditEntry) {
+        if let Err(e) = self.write_entry(&entry) {
+            tracing::warn!("Failed to write audit entry to {}: {}", self.path.display(), e);
+        }
+    }
+
+    fn peer_id_salt(&self) -> &[u8] {
+        match &self.peer_id_salt {
+            Some(salt) => salt,
+            None => process_peer_id_salt(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const SALT: &[u8] = b"deployment-1-audit-salt";
+
     #[test]
     fn test_audit_entry_creation() {
         let entry = AuditEntry::new(
             AuditEvent::HandshakeCompleted {
-                peer_id_hash: "abc123".to_string(),
+                peer_id_hash: hash_peer_id(b"alice", SALT),
+                mode: "v1".to_string(),
+                latency_ms: 12,
             },
             Some("test".to_string()),
         );
@@ -167,4 +711,230 @@ mod tests {
             AuditEvent::SessionCreated { .. }
         ));
     }
+
+    #[test]
+    fn test_hash_peer_id() {
+        // Stable for one deployment, so events can be joined
+        assert_eq!(hash_peer_id(b"alice", SALT), hash_peer_id(b"alice", SALT));
+        assert_ne!(hash_peer_id(b"alice", SALT), hash_peer_id(b"bob", SALT));
+        // Another deployment's salt gives unrelated hashes
+        assert_ne!(hash_peer_id(b"alice", SALT), hash_peer_id(b"alice", b"deployment-2-audit-salt"));
+
+        // Neither the raw ID nor its unkeyed hash appears in the output
+        let hash = hash_peer_id(b"alice", SALT);
+        assert_eq!(hash.as_str().len(), 2 * PEER_ID_HASH_SIZE);
+        assert!(!hash.as_str().contains(&hex::encode(b"alice")));
+        assert_ne!(hash.as_str(), hash_for_audit(b"alice"));
+        assert_ne!(hash.as_str(), &hex::encode(Sha3_256::digest([SALT, b"alice"].concat()))[..16]);
+
+        // Raw IDs cannot be smuggled in through deserialization
+        assert!(serde_json::from_str::<PeerIdHash>(r#""alice""#).is_err());
+        assert!(serde_json::from_str::<PeerIdHash>(r#""0011223344556677889900""#).is_err());
+        let roundtrip: PeerIdHash = serde_json::from_str(&serde_json::to_string(&hash).unwrap()).unwrap();
+        assert_eq!(roundtrip, hash);
+    }
+
+    #[test]
+    fn test_sink_peer_id_salt() {
+        let configured = MemoryAuditSink::new().with_peer_id_salt(SALT).unwrap();
+        assert_eq!(configured.peer_id_salt(), SALT);
+        assert!(MemoryAuditSink::new().with_peer_id_salt(Vec::new()).is_err());
+        assert!(MemoryAuditSink::new().with_peer_id_salt(&SALT[..MIN_PEER_ID_SALT_LEN - 1]).is_err());
+        let path = temp_audit_path("salt");
+        assert!(JsonlFileSink::open(&path, 1024).unwrap().with_peer_id_salt(b"short".to_vec()).is_err());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        // Unconfigured sinks share one random salt per process
+        assert_eq!(MemoryAuditSink::new().peer_id_salt(), NoOpAuditSink.peer_id_salt());
+        assert_eq!(MemoryAuditSink::new().peer_id_salt().len(), 32);
+    }
+
+    #[test]
+    fn test_handshake_failure_reason_mapping() {
+        let reason = |e: CryptoError| HandshakeFailureReason::from(&B4aeError::CryptoError(e));
+        assert_eq!(reason(CryptoError::VerificationFailed("sig".into())), HandshakeFailureReason::BadSignature);
+        assert_eq!(reason(CryptoError::FingerprintMismatch), HandshakeFailureReason::FingerprintMismatch);
+        assert_eq!(reason(CryptoError::InvalidInput("len".into())), HandshakeFailureReason::MalformedMessage);
+        assert_eq!(reason(CryptoError::AuthenticationFailed), HandshakeFailureReason::KeyExchangeFailed);
+        assert_eq!(reason(CryptoError::KeyGenerationFailed("rng".into())), HandshakeFailureReason::Internal);
+        assert_eq!(
+            HandshakeFailureReason::from(&B4aeError::ProtocolError("No pending handshake".into())),
+            HandshakeFailureReason::UnexpectedMessage
+        );
+    }
+
+    #[test]
+    fn test_handshake_events_serde() {
+        let failed = AuditEntry::new(
+            AuditEvent::HandshakeFailed {
+                peer_id_hash: hash_peer_id(b"mallory", SALT),
+                reason: HandshakeFailureReason::BadSignature,
+                stage: HandshakeStage::Response,
+            },
+            None,
+        );
+        let value = serde_json::to_value(&failed).unwrap();
+        assert_eq!(value["event"]["HandshakeFailed"]["reason"], "BadSignature");
+        assert_eq!(value["event"]["HandshakeFailed"]["stage"], "Response");
+
+        // Entries written before structured handshake events still load
+        let old: AuditEntry = serde_json::from_str(
+            r#"{"timestamp_ms":1,"event":{"HandshakeInitiated":{"peer_id_hash":"00112233aabbccdd"}},"context":null}"#,
+        ).unwrap();
+        assert_eq!(old.event.peer_id_hash(), Some("00112233aabbccdd"));
+        assert!(matches!(old.event, AuditEvent::HandshakeStarted { .. }));
+        let old: AuditEntry = serde_json::from_str(
+            r#"{"timestamp_ms":1,"event":{"HandshakeCompleted":{"peer_id_hash":"00112233aabbccdd"}},"context":null}"#,
+        ).unwrap();
+        assert!(matches!(old.event, AuditEvent::HandshakeCompleted { latency_ms: 0, .. }));
+    }
+
+    fn temp_audit_path(name: &str) -> PathBuf {
+        crate::storage::temp_file::test_path("audit", name, "audit.jsonl")
+    }
+
+    fn handshake(peer: &str) -> AuditEntry {
+        AuditEntry::new(
+            AuditEvent::HandshakeCompleted {
+                peer_id_hash: hash_peer_id(peer.as_bytes(), SALT),
+                mode: "ModeA".to_string(),
+                latency_ms: 3,
+            },
+            Some(peer.to_string()),
+        )
+    }
+
+    #[test]
+    fn test_jsonl_sink_persists_across_reopen() {
+        let path = temp_audit_path("reopen");
+        {
+            let sink = JsonlFileSink::open(&path, u64::MAX).unwrap();
+            sink.log(handshake("alice"));
+            sink.log(handshake("bob"));
+        }
+        {
+            let sink = JsonlFileSink::open(&path, u64::MAX).unwrap();
+            sink.log(AuditEntry::new(
+                AuditEvent::AuthFailed { reason: "bad signature".to_string() },
+                None,
+            ));
+        }
+
+        let entries = JsonlFileSink::read_entries(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].context.as_deref(), Some("alice"));
+        assert_eq!(entries[1].context.as_deref(), Some("bob"));
+        assert!(matches!(entries[2].event, AuditEvent::AuthFailed { .. }));
+
+        let first_line = std::fs::read_to_string(&path).unwrap().lines().next().unwrap().to_string();
+        let value: serde_json::Value = serde_json::from_str(&first_line).unwrap();
+        assert_eq!(value["event_type"], "HandshakeCompleted");
+        assert_eq!(value["peer_id_hash"], hash_peer_id(b"alice", SALT).as_str());
+        assert!(value["timestamp_ms"].as_u64().unwrap() > 0);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_jsonl_sink_rotates() {
+        let path = temp_audit_path("rotate");
+        let sink = JsonlFileSink::open(&path, 1000).unwrap();
+        for i in 0..6 {
+            sink.log(handshake(&format!("peer-{}", i)));
+        }
+
+        let mut all = Vec::new();
+        let mut index = 1;
+        while sink.rotated_path(index).exists() {
+            all.extend(JsonlFileSink::read_entries(sink.rotated_path(index)).unwrap());
+            index += 1;
+        }
+        assert!(index > 1, "expected at least one rotation");
+        all.extend(JsonlFileSink::read_entries(&path).unwrap());
+
+        let mut records = Vec::new();
+        for i in 1..index {
+            records.extend(JsonlFileSink::read_records(sink.rotated_path(i)).unwrap());
+        }
+        records.extend(JsonlFileSink::read_records(&path).unwrap());
+        assert_eq!(verify_chain(&records), Ok(()));
+
+        let contexts: Vec<_> = all.iter().filter_map(|e| e.context.clone()).collect();
+        let expected: Vec<_> = (0..6).map(|i| format!("peer-{}", i)).collect();
+        assert_eq!(contexts, expected);
+        assert!(std::fs::metadata(&path).unwrap().len() <= 1000);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_jsonl_sink_concurrent_writers() {
+        let path = temp_audit_path("concurrent");
+        let sink = std::sync::Arc::new(JsonlFileSink::open(&path, u64::MAX).unwrap());
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let sink = sink.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        sink.log(handshake(&format!("{}-{}", t, i)));
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(JsonlFileSink::read_entries(&path).unwrap().len(), 100);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_audit_chain_detects_tampering() {
+        let path = temp_audit_path("chain");
+        {
+            let sink = JsonlFileSink::open(&path, u64::MAX).unwrap();
+            sink.log(handshake("alice"));
+            sink.log(handshake("bob"));
+        }
+        // Chain continues after reopen
+        let sink = JsonlFileSink::open(&path, u64::MAX).unwrap();
+        sink.log(handshake("carol"));
+        sink.log(handshake("dave"));
+
+        let records = JsonlFileSink::read_records(&path).unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].prev_hash, hex::encode(genesis_hash()));
+        assert_eq!(verify_chain(&records), Ok(()));
+
+        // Deleted middle entry
+        let mut deleted = records.clone();
+        deleted.remove(1);
+        assert_eq!(verify_chain(&deleted), Err(1));
+
+        // Every stored field is covered by the chain
+        let tampers: [fn(&mut JsonlAuditRecord); 7] = [
+            |r| r.timestamp_ms += 1,
+            |r| r.event_type = "KeyRotation".to_string(),
+            |r| r.peer_id_hash = Some("00".repeat(PEER_ID_HASH_SIZE)),
+            |r| r.peer_id_hash = None,
+            |r| r.context = Some("mallory".to_string()),
+            |r| r.event = AuditEvent::KeyRotation { session_id_hash: "00".to_string() },
+            |r| r.entry_hash = "00".repeat(32),
+        ];
+        for tamper in tampers {
+            let mut mutated = records.clone();
+            tamper(&mut mutated[2]);
+            assert_eq!(verify_chain(&mutated), Err(2));
+        }
+        let mut mutated = records.clone();
+        mutated[2].prev_hash = "00".repeat(32);
+        assert_eq!(verify_chain(&mutated), Err(2));
+
+        // Reordered entries
+        let mut reordered = records;
+        reordered.swap(2, 3);
+        assert_eq!(verify_chain(&reordered), Err(2));
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
 }
@@ -1,21 +1,36 @@
 // B4AE High-Level Client API
 // Provides a simplified interface for common operations
 
-use crate::audit::{AuditEntry, AuditEvent, AuditSink, hash_for_audit};
-use crate::crypto::{CryptoConfig, SecurityLevel, CryptoError};
+use crate::audit::{audit_handshake_step, hash_for_audit, hash_peer_id, AuditEntry, AuditEvent, AuditSink, HandshakeStage};
+use crate::crypto::{CryptoConfig, CryptoError, SecurityLevel, CryptoResult};
+use crate::crypto::aes_gcm::{self, AesKey};
 use crate::crypto::hkdf;
+use crate::crypto::hybrid::{HybridKeyPair, HybridPublicKey};
+use crate::crypto::multi_recipient::{self, MultiRecipientMessage};
+use crate::crypto::random;
+use crate::crypto::xeddsa::DeniableHybridKeyPair;
+use crate::key_hierarchy::MasterIdentityKey;
 use crate::metadata::{MetadataProtection, ProtectionLevel};
-use crate::protocol::{SecurityProfile, ProtocolConfig};
+use crate::protocol::{AnonymizationConfig, SecurityProfile, ProtocolConfig};
+#[cfg(feature = "v2_protocol")]
+use crate::protocol::v2::AuthenticationMode;
 use crate::protocol::handshake::{
-    HandshakeConfig, HandshakeInitiator, HandshakeResponder,
+    self, HandshakeConfig, HandshakeInitiator, HandshakeResponder,
     HandshakeInit, HandshakeResponse, HandshakeComplete
 };
-use crate::protocol::session::Session;
+use crate::protocol::session::{CloseReason, Session, SessionEvent};
 use crate::protocol::message::{Message, MessageContent, EncryptedMessage};
 use crate::error::{B4aeError, B4aeResult};
+use crate::telemetry::{otel_handshake_span, otel_message_span, otel_record_cipher_suite, otel_record_session};
 use crate::time;
 use std::collections::HashMap;
 use std::sync::Arc;
+use zeroize::Zeroizing;
+
+#[cfg(feature = "async")]
+pub mod async_transport;
+#[cfg(feature = "async")]
+pub use async_transport::{AsyncB4aeClient, AsyncTransport};
 
 /// B4AE Client Configuration
 #[derive(Clone)]
@@ -30,17 +45,25 @@ pub struct B4aeConfig {
     pub handshake_config: HandshakeConfig,
     /// Optional audit sink for compliance logging
     pub audit_sink: Option<Arc<dyn AuditSink>>,
+    /// Preferred v2 authentication mode, applied by
+    /// [`B4aeClientV2::from_config`](crate::client_v2::B4aeClientV2::from_config)
+    /// (None = Mode B above the Standard profile, Mode A otherwise)
+    #[cfg(feature = "v2_protocol")]
+    pub authentication_mode: Option<AuthenticationMode>,
 }
 
 impl std::fmt::Debug for B4aeConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("B4aeConfig")
+        let mut debug = f.debug_struct("B4aeConfig");
+        debug
             .field("security_profile", &self.security_profile)
             .field("crypto_config", &self.crypto_config)
             .field("protocol_config", &self.protocol_config)
             .field("handshake_config", &self.handshake_config)
-            .field("audit_sink", &self.audit_sink.as_ref().map(|_| "Some(..)"))
-            .finish()
+            .field("audit_sink", &self.audit_sink.as_ref().map(|_| "Some(..)"));
+        #[cfg(feature = "v2_protocol")]
+        debug.field("authentication_mode", &self.authentication_mode);
+        debug.finish()
     }
 }
 
@@ -52,6 +75,8 @@ impl Default for B4aeConfig {
             protocol_config: ProtocolConfig::default(),
             handshake_config: HandshakeConfig::default(),
             audit_sink: None,
+            #[cfg(feature = "v2_protocol")]
+            authentication_mode: None,
         }
     }
 }
@@ -74,15 +99,182 @@ impl B4aeConfig {
             protocol_config: profile.to_config(),
             handshake_config: HandshakeConfig::default(),
             audit_sink: None,
+            #[cfg(feature = "v2_protocol")]
+            authentication_mode: None,
         }
     }
+
+    /// Start a validated builder (Standard profile unless overridden)
+    pub fn builder() -> B4aeConfigBuilder {
+        B4aeConfigBuilder::new()
+    }
+}
+
+/// Fluent builder for [`B4aeConfig`]
+///
+/// Starts from [`B4aeConfig::from_profile`] and applies only the settings
+/// given, so new config fields never break existing call sites. `build()`
+/// rejects combinations the client would silently reinterpret:
+///
+/// ```rust
+/// use b4ae::client::B4aeConfig;
+/// use b4ae::metadata::ProtectionLevel;
+/// use b4ae::protocol::SecurityProfile;
+///
+/// let config = B4aeConfig::builder()
+///     .security_profile(SecurityProfile::High)
+///     .protection_level(ProtectionLevel::Standard)
+///     .build()
+///     .unwrap();
+/// assert!(!config.protocol_config.dummy_traffic);
+///
+/// // Maximum profile implies maximum metadata protection
+/// assert!(B4aeConfig::builder()
+///     .security_profile(SecurityProfile::Maximum)
+///     .protection_level(ProtectionLevel::None)
+///     .build()
+///     .is_err());
+/// ```
+#[derive(Default)]
+pub struct B4aeConfigBuilder {
+    security_profile: Option<SecurityProfile>,
+    protection_level: Option<ProtectionLevel>,
+    anonymization: Option<AnonymizationConfig>,
+    handshake_config: Option<HandshakeConfig>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    #[cfg(feature = "v2_protocol")]
+    authentication_mode: Option<AuthenticationMode>,
 }
 
+impl B4aeConfigBuilder {
+    /// Create an empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Security profile preset (sets crypto level and protocol defaults)
+    pub fn security_profile(mut self, profile: SecurityProfile) -> Self {
+        self.security_profile = Some(profile);
+        self
+    }
+
+    /// Metadata protection level, overriding the profile's padding/timing/dummy flags
+    pub fn protection_level(mut self, level: ProtectionLevel) -> Self {
+        self.protection_level = Some(level);
+        self
+    }
+
+    /// IP anonymization (proxy / Tor)
+    pub fn anonymization(mut self, anonymization: AnonymizationConfig) -> Self {
+        self.anonymization = Some(anonymization);
+        self
+    }
+
+    /// Handshake configuration
+    pub fn handshake_config(mut self, handshake_config: HandshakeConfig) -> Self {
+        self.handshake_config = Some(handshake_config);
+        self
+    }
+
+    /// Audit sink for compliance logging
+    pub fn audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(sink);
+        self
+    }
+
+    /// Preferred v2 authentication mode
+    #[cfg(feature = "v2_protocol")]
+    pub fn authentication_mode(mut self, mode: AuthenticationMode) -> Self {
+        self.authentication_mode = Some(mode);
+        self
+    }
+
+    /// Validate and assemble the config
+    ///
+    /// # Errors
+    /// `B4aeError::ConfigError` when:
+    /// - the Maximum profile is combined with a protection level below Maximum,
+    ///   or Maximum protection is requested without the Maximum profile
+    /// - Tor is enabled without a proxy URL
+    /// - (v2) a non-post-quantum or research-only mode is requested while the
+    ///   profile requires quantum resistance beyond Standard
+    pub fn build(self) -> B4aeResult<B4aeConfig> {
+        let profile = self.security_profile.unwrap_or(SecurityProfile::Standard);
+        let mut config = B4aeConfig::from_profile(profile);
+
+        if let Some(level) = self.protection_level {
+            match (profile, level) {
+                (SecurityProfile::Maximum, ProtectionLevel::Maximum) => {}
+                (SecurityProfile::Maximum, other) => {
+                    return Err(B4aeError::ConfigError(format!(
+                        "Maximum security profile requires Maximum metadata protection, got {:?}",
+                        other
+                    )));
+                }
+                (_, ProtectionLevel::Maximum) => {
+                    return Err(B4aeError::ConfigError(
+                        "Maximum metadata protection requires the Maximum security profile".to_string(),
+                    ));
+                }
+                _ => {}
+            }
+            let pc = &mut config.protocol_config;
+            pc.metadata_protection = level.padding_enabled();
+            pc.timing_obfuscation = level.timing_enabled();
+            pc.dummy_traffic = level.dummy_traffic_enabled();
+        }
+
+        if let Some(anonymization) = self.anonymization {
+            if anonymization.use_tor && anonymization.proxy_url.is_none() {
+                return Err(B4aeError::ConfigError(
+                    "Tor anonymization requires a proxy_url (e.g. socks5://127.0.0.1:9050)".to_string(),
+                ));
+            }
+            config.protocol_config.anonymization = anonymization;
+        }
+
+        #[cfg(feature = "v2_protocol")]
+        if let Some(mode) = self.authentication_mode {
+            if mode == AuthenticationMode::ModeC {
+                return Err(B4aeError::ConfigError(
+                    "Mode C is research-only and cannot be configured".to_string(),
+                ));
+            }
+            if profile != SecurityProfile::Standard && !mode.is_post_quantum() {
+                return Err(B4aeError::ConfigError(format!(
+                    "{:?} security profile requires a post-quantum authentication mode, got {:?}",
+                    profile, mode
+                )));
+            }
+            config.authentication_mode = Some(mode);
+        }
+
+        if let Some(handshake_config) = self.handshake_config {
+            config.handshake_config = handshake_config;
+        }
+        config.audit_sink = self.audit_sink;
+        Ok(config)
+    }
+}
+
+/// Version byte of the [`B4aeClient::export_sessions`] format
+pub const SESSION_EXPORT_VERSION: u8 = 1;
+
+/// Magic prefix of the [`B4aeClient::export_sessions`] format
+const SESSION_EXPORT_MAGIC: &[u8; 4] = b"B4SX";
+
+/// HKDF info for the session export key
+const SESSION_EXPORT_INFO: &[u8] = b"B4AE-v1-session-export";
+
+const SESSION_EXPORT_SALT_SIZE: usize = 32;
+
 /// B4AE Client
 /// High-level API for secure communication
 pub struct B4aeClient {
     /// Client configuration
     config: B4aeConfig,
+    /// Long-term identity signing every handshake; peers pin its fingerprint
+    identity: Arc<DeniableHybridKeyPair>,
     /// Active sessions indexed by peer ID
     sessions: HashMap<Vec<u8>, Session>,
     /// Pending handshakes (initiator side)
@@ -94,42 +286,38 @@ pub struct B4aeClient {
 impl B4aeClient {
     /// Create new B4AE client with security profile
     pub fn new(profile: SecurityProfile) -> B4aeResult<Self> {
-        let config = B4aeConfig::from_profile(profile);
-        Ok(B4aeClient {
-            config,
-            sessions: HashMap::new(),
-            pending_initiators: HashMap::new(),
-            pending_responders: HashMap::new(),
-        })
+        Self::with_config(B4aeConfig::from_profile(profile))
     }
 
     /// Create client with custom configuration
     pub fn with_config(config: B4aeConfig) -> B4aeResult<Self> {
         Ok(B4aeClient {
             config,
+            identity: Arc::new(DeniableHybridKeyPair::generate()?),
             sessions: HashMap::new(),
             pending_initiators: HashMap::new(),
             pending_responders: HashMap::new(),
         })
     }
 
+    /// Fingerprint of this client's long-term identity, for a peer's
+    /// [`HandshakeConfig::expected_peer_fingerprint`]
+    pub fn identity_fingerprint(&self) -> [u8; 32] {
+        handshake::identity_fingerprint(&self.identity.public_key())
+    }
+
     /// Initiate handshake with peer
     /// Returns HandshakeInit message to send to peer
     pub fn initiate_handshake(&mut self, peer_id: &[u8]) -> B4aeResult<HandshakeInit> {
-        if let Some(sink) = &self.config.audit_sink {
-            sink.log(AuditEntry::new(
-                AuditEvent::HandshakeInitiated {
-                    peer_id_hash: hash_for_audit(peer_id),
-                },
-                None,
-            ));
-        }
-        let mut initiator = HandshakeInitiator::new(self.config.handshake_config.clone())
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
-        let init = initiator.generate_init()
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
+        otel_handshake_span!(HandshakeStage::Init, "v1");
+        self.audit_handshake_started(peer_id);
+        let step = (|| {
+            let mut initiator = HandshakeInitiator::with_identity(self.config.handshake_config.clone(), Arc::clone(&self.identity))?;
+            let init = initiator.generate_init()?;
+            Ok((initiator, init))
+        })();
+        let (initiator, init) = audit_handshake_step(self.config.audit_sink.as_ref(), peer_id, HandshakeStage::Init, step)?;
+
         self.pending_initiators.insert(peer_id.to_vec(), initiator);
         Ok(init)
     }
@@ -137,12 +325,16 @@ impl B4aeClient {
     /// Respond to handshake initiation
     /// Returns HandshakeResponse to send back
     pub fn respond_to_handshake(&mut self, peer_id: &[u8], init: HandshakeInit) -> B4aeResult<HandshakeResponse> {
-        let mut responder = HandshakeResponder::new(self.config.handshake_config.clone())
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
-        let response = responder.process_init(init)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
+        otel_handshake_span!(HandshakeStage::Init, "v1");
+        self.audit_handshake_started(peer_id);
+        let step = (|| {
+            let mut responder = HandshakeResponder::with_identity(self.config.handshake_config.clone(), Arc::clone(&self.identity))?;
+            let response = responder.process_init(init)?;
+            Ok((responder, response))
+        })();
+        let (responder, response) = audit_handshake_step(self.config.audit_sink.as_ref(), peer_id, HandshakeStage::Init, step)?;
+        otel_record_cipher_suite!(responder.selected_algorithms());
+
         self.pending_responders.insert(peer_id.to_vec(), responder);
         Ok(response)
     }
@@ -150,65 +342,74 @@ impl B4aeClient {
     /// Process handshake response (initiator side)
     /// Returns HandshakeComplete to send to peer
     pub fn process_response(&mut self, peer_id: &[u8], response: HandshakeResponse) -> B4aeResult<HandshakeComplete> {
-        let initiator = self.pending_initiators.get_mut(peer_id)
-            .ok_or_else(|| B4aeError::ProtocolError("No pending handshake".to_string()))?;
-        
-        initiator.process_response(response)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
-        let complete = initiator.generate_complete()
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
-        Ok(complete)
+        otel_handshake_span!(HandshakeStage::Response, "v1");
+        let step = (|| {
+            let initiator = self.pending_initiators.get_mut(peer_id)
+                .ok_or_else(|| B4aeError::ProtocolError("No pending handshake".to_string()))?;
+            initiator.process_response(response)?;
+            otel_record_cipher_suite!(initiator.selected_algorithms());
+            Ok(initiator.generate_complete()?)
+        })();
+        audit_handshake_step(self.config.audit_sink.as_ref(), peer_id, HandshakeStage::Response, step)
     }
 
     /// Finalize handshake (initiator side)
     pub fn finalize_initiator(&mut self, peer_id: &[u8]) -> B4aeResult<()> {
-        let initiator = self.pending_initiators.remove(peer_id)
-            .ok_or_else(|| B4aeError::ProtocolError("No pending handshake".to_string()))?;
-        
-        let result = initiator.finalize()
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
-        let session = Session::from_handshake(result, peer_id.to_vec(), self.config.audit_sink.clone())
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
+        otel_handshake_span!(HandshakeStage::Finalize, "v1");
+        let audit_sink = self.config.audit_sink.clone();
+        let step = (|| {
+            let initiator = self.pending_initiators.remove(peer_id)
+                .ok_or_else(|| B4aeError::ProtocolError("No pending handshake".to_string()))?;
+            let result = initiator.finalize()?;
+            let session = Session::from_handshake(result, peer_id.to_vec(), audit_sink.clone())?;
+            Ok((session, initiator.elapsed_ms()))
+        })();
+        let (session, latency_ms) = audit_handshake_step(audit_sink.as_ref(), peer_id, HandshakeStage::Finalize, step)?;
+
+        otel_record_session!(session);
+        self.audit_handshake_completed(peer_id, &session, latency_ms);
+        self.sessions.insert(peer_id.to_vec(), session);
+        Ok(())
+    }
+
+    /// Process handshake complete (responder side) and finalize
+    pub fn complete_handshake(&mut self, peer_id: &[u8], complete: HandshakeComplete) -> B4aeResult<()> {
+        otel_handshake_span!(HandshakeStage::Complete, "v1");
+        let audit_sink = self.config.audit_sink.clone();
+        let step = (|| {
+            let mut responder = self.pending_responders.remove(peer_id)
+                .ok_or_else(|| B4aeError::ProtocolError("No pending handshake".to_string()))?;
+            responder.process_complete(complete)?;
+            let result = responder.finalize()?;
+            let session = Session::from_handshake(result, peer_id.to_vec(), audit_sink.clone())?;
+            Ok((session, responder.elapsed_ms()))
+        })();
+        let (session, latency_ms) = audit_handshake_step(audit_sink.as_ref(), peer_id, HandshakeStage::Complete, step)?;
+
+        otel_record_session!(session);
+        self.audit_handshake_completed(peer_id, &session, latency_ms);
+        self.sessions.insert(peer_id.to_vec(), session);
+        Ok(())
+    }
+
+    fn audit_handshake_started(&self, peer_id: &[u8]) {
         if let Some(sink) = &self.config.audit_sink {
             sink.log(AuditEntry::new(
-                AuditEvent::HandshakeCompleted {
-                    peer_id_hash: hash_for_audit(peer_id),
-                },
-                None,
-            ));
-            sink.log(AuditEntry::new(
-                AuditEvent::SessionCreated {
-                    session_id_hash: hash_for_audit(session.session_id()),
+                AuditEvent::HandshakeStarted {
+                    peer_id_hash: hash_peer_id(peer_id, sink.peer_id_salt()),
                 },
                 None,
             ));
         }
-        self.sessions.insert(peer_id.to_vec(), session);
-        Ok(())
     }
 
-    /// Process handshake complete (responder side) and finalize
-    pub fn complete_handshake(&mut self, peer_id: &[u8], complete: HandshakeComplete) -> B4aeResult<()> {
-        let mut responder = self.pending_responders.remove(peer_id)
-            .ok_or_else(|| B4aeError::ProtocolError("No pending handshake".to_string()))?;
-        
-        responder.process_complete(complete)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
-        let result = responder.finalize()
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
-        let session = Session::from_handshake(result, peer_id.to_vec(), self.config.audit_sink.clone())
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-        
+    fn audit_handshake_completed(&self, peer_id: &[u8], session: &Session, latency_ms: u64) {
         if let Some(sink) = &self.config.audit_sink {
             sink.log(AuditEntry::new(
                 AuditEvent::HandshakeCompleted {
-                    peer_id_hash: hash_for_audit(peer_id),
+                    peer_id_hash: hash_peer_id(peer_id, sink.peer_id_salt()),
+                    mode: "v1".to_string(),
+                    latency_ms,
                 },
                 None,
             ));
@@ -219,8 +420,6 @@ impl B4aeClient {
                 None,
             ));
         }
-        self.sessions.insert(peer_id.to_vec(), session);
-        Ok(())
     }
 
     fn protection_level(&self) -> ProtectionLevel {
@@ -256,7 +455,7 @@ impl B4aeClient {
             &[session.metadata_key()],
             b"B4AE-v1-onion-layer",
             32,
-        ).map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
+        ).map_err(B4aeError::CryptoError)?;
         let mut arr = [0u8; 32];
         arr.copy_from_slice(&key);
         Ok(Some(arr))
@@ -267,8 +466,20 @@ impl B4aeClient {
     ///
     /// # Blocking behavior
     /// When timing obfuscation is enabled, this method blocks the current thread for a random delay
-    /// (via `std::thread::sleep`). Do not call from an async executor without spawning a blocking task.
+    /// (via `std::thread::sleep`). Do not call from an async executor without spawning a blocking task;
+    /// `AsyncB4aeClient` (feature `async`) applies the same delay without blocking.
     pub fn encrypt_message(&mut self, peer_id: &[u8], plaintext: &[u8]) -> B4aeResult<Vec<EncryptedMessage>> {
+        otel_message_span!("b4ae.encrypt", self.sessions, peer_id, "v1");
+        let messages = self.seal_message(peer_id, plaintext)?;
+        let delay = self.obfuscation_delay(plaintext.len());
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+        Ok(messages)
+    }
+
+    /// Padding + dummy injection + encryption, without the timing delay
+    fn seal_message(&mut self, peer_id: &[u8], plaintext: &[u8]) -> B4aeResult<Vec<EncryptedMessage>> {
         if plaintext.len() > crate::MAX_MESSAGE_SIZE {
             return Err(B4aeError::InvalidInput(format!(
                 "Message too large: {} > {}",
@@ -282,21 +493,13 @@ impl B4aeClient {
         let session = self.sessions.get_mut(peer_id)
             .ok_or_else(|| B4aeError::ProtocolError("No session with peer".to_string()))?;
         
-        let protection = MetadataProtection::new(protocol_config.clone(), level)
+        let protection = MetadataProtection::new(protocol_config, level)
             .with_metadata_key(session.metadata_key());
         let data = if level.padding_enabled() {
             protection.protect_message(plaintext)?
         } else {
             plaintext.to_vec()
         };
-        
-        // Apply timing obfuscation (blocking delay)
-        if level.timing_enabled() && self.config.protocol_config.timing_obfuscation {
-            let delay_ms = protection.get_timing_delay_ms();
-            if delay_ms > 0 {
-                std::thread::sleep(std::time::Duration::from_millis(delay_ms));
-            }
-        }
 
         let mut messages = Vec::new();
 
@@ -304,34 +507,60 @@ impl B4aeClient {
         if level.dummy_traffic_enabled() && protection.should_generate_dummy() {
             let mut dummy = vec![0u8; 64];
             crate::crypto::random::fill_random(&mut dummy)
-                .map_err(|e| B4aeError::CryptoError(format!("Random generation failed: {}", e)))?;
+                .map_err(B4aeError::CryptoError)?;
             let dummy_msg = Message::binary(dummy);
             let enc_dummy = session.send_dummy(&dummy_msg)
-                .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
+                .map_err(B4aeError::CryptoError)?;
             messages.push(enc_dummy);
         }
 
         let message = Message::binary(data);
         let enc = session.send(&message)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
+            .map_err(B4aeError::CryptoError)?;
         messages.push(enc);
 
         Ok(messages)
     }
 
+    /// Timing obfuscation delay to apply before transmitting a `size`-byte message (zero when disabled)
+    fn obfuscation_delay(&self, size: usize) -> std::time::Duration {
+        let level = self.protection_level();
+        if level.timing_enabled() && self.config.protocol_config.timing_obfuscation {
+            std::time::Duration::from_millis(self.timing_delay_for_size_ms(size))
+        } else {
+            std::time::Duration::ZERO
+        }
+    }
+
     /// Decrypt message from peer (removes metadata protection)
+    ///
+    /// Dummy traffic and ACKs decrypt to an empty vector. A CLOSE from the
+    /// peer removes the session and returns `B4aeError::ProtocolError`.
     pub fn decrypt_message(&mut self, peer_id: &[u8], encrypted: &EncryptedMessage) -> B4aeResult<Vec<u8>> {
+        otel_message_span!("b4ae.decrypt", self.sessions, peer_id, "v1");
+        self.open_message(peer_id, encrypted).map(Option::unwrap_or_default)
+    }
+
+    /// Like `decrypt_message`, but `None` for dummy traffic and ACKs
+    pub(crate) fn open_message(&mut self, peer_id: &[u8], encrypted: &EncryptedMessage) -> B4aeResult<Option<Vec<u8>>> {
         let level = self.protection_level();
         let protocol_config = self.config.protocol_config.clone();
         
         let session = self.sessions.get_mut(peer_id)
             .ok_or_else(|| B4aeError::ProtocolError("No session with peer".to_string()))?;
         
-        let message = session.receive(encrypted)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
+        // ACKs and CLOSE frames are applied by the session, not surfaced as data
+        let message = match session.process(encrypted)? {
+            SessionEvent::Message(message) => message,
+            SessionEvent::Acked(_) => return Ok(None),
+            SessionEvent::Closed(reason) => {
+                self.sessions.remove(peer_id);
+                return Err(B4aeError::ProtocolError(format!("Peer closed the session ({:?})", reason)));
+            }
+        };
         
         let data = match &message.content {
-            MessageContent::Dummy => return Ok(vec![]), // Discard dummy traffic
+            MessageContent::Dummy => return Ok(None), // Discard dummy traffic
             MessageContent::Binary(d) => d.clone(),
             MessageContent::Text(t) => t.clone().into_bytes(),
             MessageContent::File { data, .. } => data.clone(),
@@ -340,12 +569,31 @@ impl B4aeClient {
         let protection = MetadataProtection::new(protocol_config, level)
             .with_metadata_key(session.metadata_key());
         if level.padding_enabled() {
-            protection.unprotect_message(&data)
+            protection.unprotect_message(&data).map(Some)
         } else {
-            Ok(data)
+            Ok(Some(data))
         }
     }
 
+    /// Encrypt one message for several recipients at once.
+    /// The payload is encrypted once; only the content key is wrapped per recipient,
+    /// and the message does not list who the recipients are. No session is needed.
+    pub fn encrypt_multi(recipients: &[HybridPublicKey], plaintext: &[u8]) -> CryptoResult<MultiRecipientMessage> {
+        multi_recipient::encrypt(recipients, plaintext)
+    }
+
+    /// Like `encrypt_multi`, but each recipient stanza carries a key ID so
+    /// recipients skip trial decryption (reveals recipient identities to anyone
+    /// holding their public keys).
+    pub fn encrypt_multi_with_key_ids(recipients: &[HybridPublicKey], plaintext: &[u8]) -> CryptoResult<MultiRecipientMessage> {
+        multi_recipient::encrypt_with_key_ids(recipients, plaintext)
+    }
+
+    /// Open a multi-recipient message with this recipient's keypair.
+    pub fn decrypt_multi(keypair: &HybridKeyPair, message: &MultiRecipientMessage) -> CryptoResult<Vec<u8>> {
+        multi_recipient::decrypt(keypair, message)
+    }
+
     /// Whether dummy traffic should be generated (for transport to inject).
     pub fn should_generate_dummy(&self) -> bool {
         let level = self.protection_level();
@@ -363,10 +611,10 @@ impl B4aeClient {
         
         let mut dummy = vec![0u8; 64];
         crate::crypto::random::fill_random(&mut dummy)
-            .map_err(|e| B4aeError::CryptoError(format!("Random generation failed: {}", e)))?;
+            .map_err(B4aeError::CryptoError)?;
         let message = Message::binary(dummy);
         session.send_dummy(&message)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))
+            .map_err(B4aeError::CryptoError)
     }
 
     /// Recommended timing delay (ms) before transmit. Use for timing obfuscation.
@@ -376,13 +624,24 @@ impl B4aeClient {
         protection.get_timing_delay_ms()
     }
 
+    /// Recommended timing delay (ms) before transmitting a `size`-byte message.
+    /// The window widens with size up to `max_size_timing_delay_ms`.
+    pub fn timing_delay_for_size_ms(&self, size: usize) -> u64 {
+        let level = self.protection_level();
+        let protection = MetadataProtection::new(self.config.protocol_config.clone(), level);
+        protection.get_timing_delay_for_size_ms(size)
+    }
+
     /// Check if session exists with peer
     pub fn has_session(&self, peer_id: &[u8]) -> bool {
         self.sessions.contains_key(peer_id)
     }
 
     /// Close session with peer
-    pub fn close_session(&mut self, peer_id: &[u8]) {
+    ///
+    /// Returns the authenticated CLOSE frame to deliver to the peer, or `None`
+    /// if there was no active session. The local keys are wiped either way.
+    pub fn close_session(&mut self, peer_id: &[u8]) -> Option<EncryptedMessage> {
         if let Some(mut session) = self.sessions.remove(peer_id) {
             if let Some(sink) = &self.config.audit_sink {
                 sink.log(AuditEntry::new(
@@ -392,8 +651,78 @@ impl B4aeClient {
                     None,
                 ));
             }
-            session.close();
+            // Keys are wiped even if no frame could be produced
+            return session.close(CloseReason::Normal).ok();
         }
+        None
+    }
+
+    /// Export all established sessions, encrypted under `mik`, for device migration
+    ///
+    /// ```text
+    /// blob = magic "B4SX" (4) || version (1) || salt (32) || nonce (12) || ciphertext
+    /// ```
+    ///
+    /// The key is HKDF(MIK, salt, "B4AE-v1-session-export") and the header is
+    /// authenticated as AAD. Pending handshakes are not exported. After a
+    /// successful [`import_sessions`](Self::import_sessions) elsewhere, stop
+    /// using these sessions here: two live copies would reuse message keys.
+    pub fn export_sessions(&self, mik: &MasterIdentityKey) -> CryptoResult<Vec<u8>> {
+        let mut states = Vec::with_capacity(self.sessions.len());
+        for (peer_id, session) in &self.sessions {
+            states.push((peer_id.clone(), session.export_state()?));
+        }
+        let payload = Zeroizing::new(bincode::serialize(&states).map_err(|e| {
+            CryptoError::InvalidInput(format!("Session export encoding failed: {}", e))
+        })?);
+
+        let mut header = Vec::with_capacity(SESSION_EXPORT_MAGIC.len() + 1 + SESSION_EXPORT_SALT_SIZE);
+        header.extend_from_slice(SESSION_EXPORT_MAGIC);
+        header.push(SESSION_EXPORT_VERSION);
+        header.extend_from_slice(&random::random_bytes(SESSION_EXPORT_SALT_SIZE));
+
+        let key = session_export_key(mik, &header[SESSION_EXPORT_MAGIC.len() + 1..])?;
+        let (nonce, ciphertext) = aes_gcm::encrypt(&key, &payload, &header)?;
+
+        let mut blob = header;
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&ciphertext);
+        Ok(blob)
+    }
+
+    /// Import sessions from [`export_sessions`](Self::export_sessions); returns how many
+    ///
+    /// Imported sessions replace existing sessions with the same peer. A wrong
+    /// MIK or a modified blob fails with `AuthenticationFailed` and imports
+    /// nothing; a blob of another format version is rejected.
+    pub fn import_sessions(&mut self, mik: &MasterIdentityKey, blob: &[u8]) -> CryptoResult<usize> {
+        let header_len = SESSION_EXPORT_MAGIC.len() + 1 + SESSION_EXPORT_SALT_SIZE;
+        if blob.len() < header_len + aes_gcm::NONCE_SIZE || !blob.starts_with(SESSION_EXPORT_MAGIC) {
+            return Err(CryptoError::InvalidInput("Not a session export".to_string()));
+        }
+        let version = blob[SESSION_EXPORT_MAGIC.len()];
+        if version != SESSION_EXPORT_VERSION {
+            return Err(CryptoError::InvalidInput(format!(
+                "Unsupported session export version {} (expected {})",
+                version, SESSION_EXPORT_VERSION
+            )));
+        }
+
+        let (header, rest) = blob.split_at(header_len);
+        let (nonce, ciphertext) = rest.split_at(aes_gcm::NONCE_SIZE);
+        let key = session_export_key(mik, &header[SESSION_EXPORT_MAGIC.len() + 1..])?;
+        let payload = Zeroizing::new(aes_gcm::decrypt(&key, nonce, ciphertext, header)?);
+
+        let states: Vec<(Vec<u8>, Zeroizing<Vec<u8>>)> = bincode::deserialize(&payload)
+            .map_err(|e| CryptoError::InvalidInput(format!("Session export decoding failed: {}", e)))?;
+        let mut sessions = Vec::with_capacity(states.len());
+        for (peer_id, state) in states {
+            sessions.push((peer_id, Session::import_state(&state, self.config.audit_sink.clone())?));
+        }
+
+        let count = sessions.len();
+        self.sessions.extend(sessions);
+        Ok(count)
     }
 
     /// Get configuration
@@ -424,6 +753,12 @@ impl B4aeClient {
     }
 }
 
+fn session_export_key(mik: &MasterIdentityKey, salt: &[u8]) -> CryptoResult<AesKey> {
+    let mik = Zeroizing::new(mik.to_bytes());
+    let key = Zeroizing::new(hkdf::derive_key_with_salt(salt, &[mik.as_slice()], SESSION_EXPORT_INFO, 32)?);
+    AesKey::from_bytes(&key)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -440,6 +775,80 @@ mod tests {
         assert_eq!(config.security_profile, SecurityProfile::Maximum);
     }
 
+    #[test]
+    fn test_config_builder_minimal() {
+        let config = B4aeConfig::builder().build().unwrap();
+        assert_eq!(config.security_profile, SecurityProfile::Standard);
+        assert!(config.protocol_config.metadata_protection);
+        assert!(config.audit_sink.is_none());
+        assert!(B4aeClient::with_config(config).is_ok());
+    }
+
+    #[test]
+    fn test_config_builder_overrides() {
+        let builder = B4aeConfig::builder()
+            .security_profile(SecurityProfile::High)
+            .protection_level(ProtectionLevel::Basic)
+            .anonymization(AnonymizationConfig {
+                proxy_url: Some("socks5://127.0.0.1:9050".to_string()),
+                use_tor: true,
+            });
+        #[cfg(feature = "v2_protocol")]
+        let builder = builder.authentication_mode(AuthenticationMode::ModeB);
+        let config = builder.build().unwrap();
+
+        assert_eq!(config.security_profile, SecurityProfile::High);
+        assert!(config.protocol_config.metadata_protection);
+        assert!(!config.protocol_config.timing_obfuscation);
+        assert!(!config.protocol_config.dummy_traffic);
+        assert!(config.protocol_config.anonymization.use_tor);
+        #[cfg(feature = "v2_protocol")]
+        assert_eq!(config.authentication_mode, Some(AuthenticationMode::ModeB));
+
+        let client = B4aeClient::with_config(config).unwrap();
+        assert_eq!(client.get_protection_level(), ProtectionLevel::Basic);
+    }
+
+    #[test]
+    fn test_config_builder_rejects_inconsistent_settings() {
+        let err = B4aeConfig::builder()
+            .security_profile(SecurityProfile::Maximum)
+            .protection_level(ProtectionLevel::Standard)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, B4aeError::ConfigError(_)));
+
+        assert!(B4aeConfig::builder()
+            .protection_level(ProtectionLevel::Maximum)
+            .build()
+            .is_err());
+        assert!(B4aeConfig::builder()
+            .anonymization(AnonymizationConfig { proxy_url: None, use_tor: true })
+            .build()
+            .is_err());
+        #[cfg(feature = "v2_protocol")]
+        assert!(B4aeConfig::builder()
+            .security_profile(SecurityProfile::Maximum)
+            .authentication_mode(AuthenticationMode::ModeA)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_encrypt_multi() {
+        use crate::crypto::hybrid;
+
+        let recipients: Vec<_> = (0..3).map(|_| hybrid::generate_keypair().unwrap()).collect();
+        let public_keys: Vec<_> = recipients.iter().map(|kp| kp.public_key.clone()).collect();
+        let outsider = hybrid::generate_keypair().unwrap();
+
+        let message = B4aeClient::encrypt_multi(&public_keys, b"team broadcast").unwrap();
+        for recipient in &recipients {
+            assert_eq!(B4aeClient::decrypt_multi(recipient, &message).unwrap(), b"team broadcast");
+        }
+        assert!(B4aeClient::decrypt_multi(&outsider, &message).is_err());
+    }
+
     #[test]
     fn test_full_handshake_and_messaging() {
         // Create two clients
@@ -482,4 +891,209 @@ mod tests {
         }
         assert_eq!(decrypted, plaintext);
     }
+
+    fn audited_client() -> (B4aeClient, Arc<crate::audit::MemoryAuditSink>) {
+        let sink = Arc::new(crate::audit::MemoryAuditSink::new());
+        let config = B4aeConfig {
+            audit_sink: Some(sink.clone() as Arc<dyn AuditSink>),
+            ..B4aeConfig::default()
+        };
+        (B4aeClient::with_config(config).unwrap(), sink)
+    }
+
+    fn handshake_failures(sink: &crate::audit::MemoryAuditSink) -> Vec<AuditEvent> {
+        sink.entries()
+            .into_iter()
+            .map(|e| e.event)
+            .filter(|e| matches!(e, AuditEvent::HandshakeFailed { .. }))
+            .collect()
+    }
+
+    #[test]
+    fn test_handshake_audit_events() {
+        let (mut alice, alice_sink) = audited_client();
+        let (mut bob, bob_sink) = audited_client();
+
+        let init = alice.initiate_handshake(b"bob").unwrap();
+        let response = bob.respond_to_handshake(b"alice", init).unwrap();
+        let complete = alice.process_response(b"bob", response).unwrap();
+        bob.complete_handshake(b"alice", complete).unwrap();
+        alice.finalize_initiator(b"bob").unwrap();
+
+        for (sink, peer) in [(&alice_sink, &b"bob"[..]), (&bob_sink, &b"alice"[..])] {
+            let events: Vec<_> = sink.entries().into_iter().map(|e| e.event).collect();
+            assert_eq!(events[0], AuditEvent::HandshakeStarted { peer_id_hash: hash_peer_id(peer, sink.peer_id_salt()) });
+            assert!(matches!(
+                &events[1],
+                AuditEvent::HandshakeCompleted { peer_id_hash, mode, .. }
+                    if *peer_id_hash == hash_peer_id(peer, sink.peer_id_salt()) && mode == "v1"
+            ));
+            assert!(handshake_failures(sink).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_bad_signature_emits_one_handshake_failed() {
+        use crate::audit::{HandshakeFailureReason, HandshakeStage};
+
+        let (mut alice, sink) = audited_client();
+        let mut bob = B4aeClient::new(SecurityProfile::Standard).unwrap();
+
+        let init = alice.initiate_handshake(b"bob").unwrap();
+        let mut response = bob.respond_to_handshake(b"alice", init).unwrap();
+        let last = response.signature.len() - 1;
+        response.signature[last] ^= 0x01;
+
+        assert!(alice.process_response(b"bob", response).is_err());
+        assert_eq!(
+            handshake_failures(&sink),
+            vec![AuditEvent::HandshakeFailed {
+                peer_id_hash: hash_peer_id(b"bob", sink.peer_id_salt()),
+                reason: HandshakeFailureReason::BadSignature,
+                stage: HandshakeStage::Response,
+            }]
+        );
+
+        // A step with no pending handshake is audited as unexpected
+        assert!(alice.finalize_initiator(b"carol").is_err());
+        assert!(matches!(
+            handshake_failures(&sink)[1],
+            AuditEvent::HandshakeFailed { reason: HandshakeFailureReason::UnexpectedMessage, stage: HandshakeStage::Finalize, .. }
+        ));
+    }
+
+    #[test]
+    fn test_pinned_peer_fingerprint_across_handshakes() {
+        let mut bob = B4aeClient::new(SecurityProfile::Standard).unwrap();
+        let pinned = |fingerprint| B4aeConfig {
+            handshake_config: HandshakeConfig {
+                expected_peer_fingerprint: Some(fingerprint),
+                ..HandshakeConfig::default()
+            },
+            ..B4aeConfig::default()
+        };
+        let mut alice = B4aeClient::with_config(pinned(bob.identity_fingerprint())).unwrap();
+
+        // Bob's identity is long-term, so the pin holds for every handshake
+        for _ in 0..2 {
+            let init = alice.initiate_handshake(b"bob").unwrap();
+            let response = bob.respond_to_handshake(b"alice", init).unwrap();
+            let complete = alice.process_response(b"bob", response).unwrap();
+            bob.complete_handshake(b"alice", complete).unwrap();
+            alice.finalize_initiator(b"bob").unwrap();
+        }
+
+        let mut mallory = B4aeClient::new(SecurityProfile::Standard).unwrap();
+        let init = alice.initiate_handshake(b"bob").unwrap();
+        let response = mallory.respond_to_handshake(b"alice", init).unwrap();
+        assert!(matches!(
+            alice.process_response(b"bob", response),
+            Err(B4aeError::ProtocolError(msg)) if msg == "fingerprint mismatch"
+        ));
+    }
+
+    /// Alice and Bob with an established session
+    fn connected_clients() -> (B4aeClient, B4aeClient) {
+        let mut alice = B4aeClient::new(SecurityProfile::Standard).unwrap();
+        let mut bob = B4aeClient::new(SecurityProfile::Standard).unwrap();
+        let init = alice.initiate_handshake(b"bob").unwrap();
+        let response = bob.respond_to_handshake(b"alice", init).unwrap();
+        let complete = alice.process_response(b"bob", response).unwrap();
+        bob.complete_handshake(b"alice", complete).unwrap();
+        alice.finalize_initiator(b"bob").unwrap();
+        (alice, bob)
+    }
+
+    fn decrypt_all(client: &mut B4aeClient, peer_id: &[u8], messages: &[EncryptedMessage]) -> Vec<u8> {
+        let mut plaintext = Vec::new();
+        for message in messages {
+            if let Some(p) = client.open_message(peer_id, message).unwrap() {
+                plaintext = p;
+            }
+        }
+        plaintext
+    }
+
+    #[test]
+    fn test_export_import_sessions_continues_decrypting() {
+        let (mut alice, mut bob) = connected_clients();
+        let first = alice.encrypt_message(b"bob", b"before export").unwrap();
+        assert_eq!(decrypt_all(&mut bob, b"alice", &first), b"before export");
+
+        // Sent while Bob migrates devices
+        let in_flight = alice.encrypt_message(b"bob", b"in flight").unwrap();
+        let later = alice.encrypt_message(b"bob", b"after import").unwrap();
+
+        let mik = MasterIdentityKey::generate().unwrap();
+        let blob = bob.export_sessions(&mik).unwrap();
+        assert_eq!(blob[4], SESSION_EXPORT_VERSION);
+
+        let mut new_device = B4aeClient::new(SecurityProfile::Standard).unwrap();
+        assert_eq!(new_device.import_sessions(&mik, &blob).unwrap(), 1);
+        assert!(new_device.has_session(b"alice"));
+        assert_eq!(decrypt_all(&mut new_device, b"alice", &later), b"after import");
+        assert_eq!(decrypt_all(&mut new_device, b"alice", &in_flight), b"in flight");
+
+        // Replay protection carried over
+        assert!(new_device.decrypt_message(b"alice", first.last().unwrap()).is_err());
+
+        let reply = new_device.encrypt_message(b"alice", b"from the new device").unwrap();
+        assert_eq!(decrypt_all(&mut alice, b"bob", &reply), b"from the new device");
+    }
+
+    #[test]
+    fn test_close_session_returns_close_frame() {
+        let (mut alice, mut bob) = connected_clients();
+
+        let close = alice.close_session(b"bob").unwrap();
+        assert!(!alice.has_session(b"bob"));
+        assert!(alice.close_session(b"bob").is_none());
+
+        // Delivering the frame closes Bob's side too
+        assert!(matches!(bob.decrypt_message(b"alice", &close), Err(B4aeError::ProtocolError(_))));
+        assert!(!bob.has_session(b"alice"));
+    }
+
+    #[test]
+    fn test_import_sessions_rejects_tampering_wrong_mik_and_version() {
+        let (_alice, bob) = connected_clients();
+        let mik = MasterIdentityKey::generate().unwrap();
+        let blob = bob.export_sessions(&mik).unwrap();
+        let mut target = B4aeClient::new(SecurityProfile::Standard).unwrap();
+
+        let other_mik = MasterIdentityKey::generate().unwrap();
+        assert!(matches!(target.import_sessions(&other_mik, &blob), Err(CryptoError::AuthenticationFailed)));
+
+        for index in [10, 60, blob.len() - 1] {
+            let mut tampered = blob.clone();
+            tampered[index] ^= 0x01;
+            assert!(matches!(target.import_sessions(&mik, &tampered), Err(CryptoError::AuthenticationFailed)));
+        }
+
+        let mut future = blob.clone();
+        future[4] = SESSION_EXPORT_VERSION + 1;
+        assert!(matches!(
+            target.import_sessions(&mik, &future),
+            Err(CryptoError::InvalidInput(msg)) if msg.contains("version")
+        ));
+        assert!(target.import_sessions(&mik, &blob[..20]).is_err());
+        assert!(!target.has_session(b"alice"));
+    }
+
+    #[test]
+    fn test_timing_delay_for_size_uses_protocol_config() {
+        let mut config = B4aeConfig::from_profile(SecurityProfile::Standard);
+        config.protocol_config.max_timing_delay_ms = 300;
+        config.protocol_config.timing_delay_per_kib_ms = 100;
+        config.protocol_config.max_size_timing_delay_ms = 1_000;
+        // The saturating distribution lands on the window's upper bound
+        config.protocol_config.timing_distribution =
+            crate::metadata::timing::DelayDistribution::Poisson { mean: 1_000_000.0 };
+        let client = B4aeClient::with_config(config).unwrap();
+
+        assert_eq!(client.timing_delay_for_size_ms(0), 300);
+        assert_eq!(client.timing_delay_for_size_ms(4096), 700);
+        assert_eq!(client.timing_delay_for_size_ms(64 * 1024), 1_000);
+        assert_eq!(client.obfuscation_delay(4096), std::time::Duration::from_millis(700));
+    }
 }
@@ -0,0 +1,562 @@
+//! Async client over an injected transport
+//!
+//! [`AsyncB4aeClient`] binds a [`B4aeClient`] to one peer and one
+//! [`AsyncTransport`]. `send` and `recv` drive the handshake on first use,
+//! then apply the same metadata protection as the sync API: padding, dummy
+//! traffic, and timing obfuscation via `tokio::time::sleep` instead of
+//! blocking the thread. With `v2_protocol`, outbound frames can also be
+//! released through a [`GlobalTrafficScheduler`].
+//!
+//! ## Cancellation
+//!
+//! `recv` is cancel-safe when the transport's `recv` is: a frame is
+//! decrypted and returned without an intervening await, and handshake
+//! progress (including an unsent handshake frame) is kept on `self`, so a
+//! dropped `recv` resumes where it stopped. With a scheduler, a `recv`
+//! dropped while sending a cover frame may lose that frame, never an inbound
+//! one. `send` is not cancel-safe; a dropped `send` may have transmitted
+//! none, some or all of its frames.
+
+use super::B4aeClient;
+use crate::crypto::onion;
+use crate::error::{B4aeError, B4aeResult};
+use crate::protocol::handshake::{HandshakeComplete, HandshakeInit, HandshakeResponse};
+use crate::protocol::message::EncryptedMessage;
+use async_trait::async_trait;
+use bincode::config::DefaultOptions;
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "v2_protocol")]
+use crate::protocol::v2::{GlobalTrafficScheduler, Release, SessionId};
+
+/// Maximum accepted frame size (largest message plus padding and framing headroom)
+pub const MAX_FRAME_SIZE: usize = 2 * crate::MAX_MESSAGE_SIZE;
+
+/// Message-oriented async transport
+///
+/// Each `send` delivers one frame that the peer's `recv` returns whole
+/// (a channel, a length-prefixed stream, a WebSocket, ...). `recv` should be
+/// cancel-safe, as `tokio::sync::mpsc::Receiver::recv` is.
+#[async_trait]
+pub trait AsyncTransport: Send {
+    /// Send one frame
+    async fn send(&mut self, frame: Vec<u8>) -> B4aeResult<()>;
+
+    /// Receive the next frame
+    async fn recv(&mut self) -> B4aeResult<Vec<u8>>;
+}
+
+/// Frame carried by the transport
+#[derive(Debug, Serialize, Deserialize)]
+enum WireFrame {
+    HandshakeInit(HandshakeInit),
+    HandshakeResponse(HandshakeResponse),
+    HandshakeComplete(HandshakeComplete),
+    Encrypted(EncryptedMessage),
+    /// Serialized `EncryptedMessage` wrapped in one onion layer (ProtectionLevel::Maximum)
+    Onion(Vec<u8>),
+}
+
+impl WireFrame {
+    fn encode(&self) -> B4aeResult<Vec<u8>> {
+        bincode::serialize(self).map_err(|e| B4aeError::ProtocolError(e.to_string()))
+    }
+
+    fn decode(bytes: &[u8]) -> B4aeResult<Self> {
+        if bytes.len() > MAX_FRAME_SIZE {
+            return Err(B4aeError::ProtocolError(format!(
+                "Frame too large: {} > {}",
+                bytes.len(),
+                MAX_FRAME_SIZE
+            )));
+        }
+        // Same encoding as `bincode::serialize`, plus a size limit
+        DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(MAX_FRAME_SIZE as u64)
+            .deserialize(bytes)
+            .map_err(|e| B4aeError::ProtocolError(format!("Malformed frame: {}", e)))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            WireFrame::HandshakeInit(_) => "HandshakeInit",
+            WireFrame::HandshakeResponse(_) => "HandshakeResponse",
+            WireFrame::HandshakeComplete(_) => "HandshakeComplete",
+            WireFrame::Encrypted(_) => "Encrypted",
+            WireFrame::Onion(_) => "Onion",
+        }
+    }
+}
+
+/// Handshake progress, kept across cancelled calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// Initiator: HandshakeInit not generated yet
+    Connect,
+    /// Initiator: waiting for HandshakeResponse
+    AwaitResponse,
+    /// Responder: waiting for HandshakeInit
+    AwaitInit,
+    /// Responder: waiting for HandshakeComplete
+    AwaitComplete,
+    Established,
+}
+
+/// [`B4aeClient`] session with one peer over an [`AsyncTransport`]
+///
+/// ```rust,ignore
+/// let mut alice = AsyncB4aeClient::initiator(client, b"bob", transport);
+/// alice.send(b"hello").await?;      // handshakes first if needed
+/// let reply = alice.recv().await?;
+/// ```
+pub struct AsyncB4aeClient<T: AsyncTransport> {
+    client: B4aeClient,
+    transport: T,
+    peer_id: Vec<u8>,
+    phase: Phase,
+    /// Handshake frame generated but not yet sent
+    outbox: Option<Vec<u8>>,
+    #[cfg(feature = "v2_protocol")]
+    scheduler: Option<GlobalTrafficScheduler>,
+}
+
+impl<T: AsyncTransport> AsyncB4aeClient<T> {
+    /// Session that sends HandshakeInit on first `send`/`recv`
+    pub fn initiator(client: B4aeClient, peer_id: &[u8], transport: T) -> Self {
+        Self::with_phase(client, peer_id, transport, Phase::Connect)
+    }
+
+    /// Session that waits for the peer's HandshakeInit on first `send`/`recv`
+    pub fn responder(client: B4aeClient, peer_id: &[u8], transport: T) -> Self {
+        Self::with_phase(client, peer_id, transport, Phase::AwaitInit)
+    }
+
+    fn with_phase(client: B4aeClient, peer_id: &[u8], transport: T, phase: Phase) -> Self {
+        // A session that already exists (e.g. handshaken over another channel) is reused
+        let phase = if client.has_session(peer_id) { Phase::Established } else { phase };
+        Self {
+            client,
+            transport,
+            peer_id: peer_id.to_vec(),
+            phase,
+            outbox: None,
+            #[cfg(feature = "v2_protocol")]
+            scheduler: None,
+        }
+    }
+
+    /// Release outbound frames through `scheduler` instead of sending immediately
+    ///
+    /// The scheduler then owns send timing, so the per-message timing
+    /// obfuscation delay is skipped. While a `send` or `recv` is in
+    /// progress, every slot releases one frame: the next queued message, or
+    /// a dummy message queued the same way when there is none.
+    #[cfg(feature = "v2_protocol")]
+    pub fn with_traffic_scheduler(mut self, scheduler: GlobalTrafficScheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Whether the handshake has completed
+    pub fn is_established(&self) -> bool {
+        self.phase == Phase::Established
+    }
+
+    /// Peer this session talks to
+    pub fn peer_id(&self) -> &[u8] {
+        &self.peer_id
+    }
+
+    /// Underlying client
+    pub fn client(&self) -> &B4aeClient {
+        &self.client
+    }
+
+    /// Give back the client and transport
+    pub fn into_parts(self) -> (B4aeClient, T) {
+        (self.client, self.transport)
+    }
+
+    /// Complete the handshake if it has not completed yet
+    pub async fn handshake(&mut self) -> B4aeResult<()> {
+        loop {
+            if let Some(frame) = &self.outbox {
+                self.transport.send(frame.clone()).await?;
+                self.outbox = None;
+            }
+            match self.phase {
+                Phase::Established => return Ok(()),
+                Phase::Connect => {
+                    let init = self.client.initiate_handshake(&self.peer_id)?;
+                    self.outbox = Some(WireFrame::HandshakeInit(init).encode()?);
+                    self.phase = Phase::AwaitResponse;
+                }
+                Phase::AwaitResponse => match self.recv_frame().await? {
+                    WireFrame::HandshakeResponse(response) => {
+                        let complete = self.client.process_response(&self.peer_id, response)?;
+                        self.client.finalize_initiator(&self.peer_id)?;
+                        self.outbox = Some(WireFrame::HandshakeComplete(complete).encode()?);
+                        self.phase = Phase::Established;
+                    }
+                    other => return Err(unexpected("HandshakeResponse", &other)),
+                },
+                Phase::AwaitInit => match self.recv_frame().await? {
+                    WireFrame::HandshakeInit(init) => {
+                        let response = self.client.respond_to_handshake(&self.peer_id, init)?;
+                        self.outbox = Some(WireFrame::HandshakeResponse(response).encode()?);
+                        self.phase = Phase::AwaitComplete;
+                    }
+                    other => return Err(unexpected("HandshakeInit", &other)),
+                },
+                Phase::AwaitComplete => match self.recv_frame().await? {
+                    WireFrame::HandshakeComplete(complete) => {
+                        self.client.complete_handshake(&self.peer_id, complete)?;
+                        self.phase = Phase::Established;
+                    }
+                    other => return Err(unexpected("HandshakeComplete", &other)),
+                },
+            }
+        }
+    }
+
+    /// Encrypt and send `plaintext` (with dummy traffic and timing obfuscation when enabled)
+    pub async fn send(&mut self, plaintext: &[u8]) -> B4aeResult<()> {
+        self.handshake().await?;
+        let messages = self.client.seal_message(&self.peer_id, plaintext)?;
+
+        #[cfg(feature = "v2_protocol")]
+        if self.scheduler.is_some() {
+            return self.send_scheduled(messages).await;
+        }
+
+        let delay = self.client.obfuscation_delay(plaintext.len());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        for message in messages {
+            let frame = self.wrap(message)?;
+            self.transport.send(frame).await?;
+        }
+        Ok(())
+    }
+
+    /// Receive and decrypt the next message, skipping dummy traffic
+    pub async fn recv(&mut self) -> B4aeResult<Vec<u8>> {
+        self.handshake().await?;
+        loop {
+            let encrypted = match self.recv_frame_or_release().await? {
+                WireFrame::Encrypted(encrypted) => encrypted,
+                WireFrame::Onion(layer) => self.unwrap_onion(&layer)?,
+                other => return Err(unexpected("Encrypted", &other)),
+            };
+            if let Some(plaintext) = self.client.open_message(&self.peer_id, &encrypted)? {
+                return Ok(plaintext);
+            }
+        }
+    }
+
+    async fn recv_frame(&mut self) -> B4aeResult<WireFrame> {
+        let bytes = self.transport.recv().await?;
+        WireFrame::decode(&bytes)
+    }
+
+    /// Like `recv_frame`, but keeps releasing the scheduler's slots while waiting
+    #[cfg(feature = "v2_protocol")]
+    async fn recv_frame_or_release(&mut self) -> B4aeResult<WireFrame> {
+        loop {
+            let slot = match self.scheduler.as_mut() {
+                Some(scheduler) => scheduler.next_release(scheduler_now()),
+                None => None,
+            };
+            let Some(slot) = slot else {
+                return self.recv_frame().await;
+            };
+            if self.scheduler_mut().is_queue_empty() {
+                self.queue_cover()?;
+            }
+            tokio::select! {
+                bytes = self.transport.recv() => return WireFrame::decode(&bytes?),
+                _ = tokio::time::sleep_until(slot.into()) => self.release_slot().await?,
+            }
+        }
+    }
+
+    #[cfg(not(feature = "v2_protocol"))]
+    async fn recv_frame_or_release(&mut self) -> B4aeResult<WireFrame> {
+        self.recv_frame().await
+    }
+
+    /// Encode an outbound message, onion-wrapped when onion routing is enabled
+    fn wrap(&self, message: EncryptedMessage) -> B4aeResult<Vec<u8>> {
+        match self.client.onion_layer_key(&self.peer_id)? {
+            Some(layer_key) => {
+                let bytes = bincode::serialize(&message)
+                    .map_err(|e| B4aeError::ProtocolError(e.to_string()))?;
+                let path = [(Vec::new(), layer_key)]; // empty next_hop = final destination
+                let layer = onion::onion_encrypt(&path, &bytes)
+                    .map_err(B4aeError::CryptoError)?;
+                WireFrame::Onion(layer.as_bytes().to_vec()).encode()
+            }
+            None => WireFrame::Encrypted(message).encode(),
+        }
+    }
+
+    fn unwrap_onion(&self, layer: &[u8]) -> B4aeResult<EncryptedMessage> {
+        let layer_key = self.client.onion_layer_key(&self.peer_id)?.ok_or_else(|| {
+            B4aeError::ProtocolError("Onion frame received but onion routing is disabled".to_string())
+        })?;
+        let (_, payload) = onion::onion_decrypt_layer(&layer_key, layer)
+            .map_err(B4aeError::CryptoError)?;
+        DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(MAX_FRAME_SIZE as u64)
+            .deserialize(&payload)
+            .map_err(|e| B4aeError::ProtocolError(format!("Malformed onion payload: {}", e)))
+    }
+
+    /// Queue `messages` and release frames at the scheduler's slots until the queue drains
+    #[cfg(feature = "v2_protocol")]
+    async fn send_scheduled(&mut self, messages: Vec<EncryptedMessage>) -> B4aeResult<()> {
+        for message in messages {
+            let frame = self.wrap(message)?;
+            let session_id = self.scheduler_session_id();
+            self.scheduler_mut()
+                .schedule_message(session_id, frame, false)
+                .map_err(B4aeError::NetworkError)?;
+        }
+
+        while !self.scheduler_mut().is_queue_empty() {
+            match self.scheduler_mut().next_release(scheduler_now()) {
+                Some(slot) => {
+                    tokio::time::sleep_until(slot.into()).await;
+                    self.release_slot().await?;
+                }
+                // Idle policy: nothing will pace the queue, send it now
+                None => {
+                    if let Some(scheduled) = self.scheduler_mut().dequeue_message() {
+                        self.transport.send(scheduled.payload).await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Send the frame released at the current slot, if it has been reached
+    ///
+    /// Cover frames are queued ahead of their slot by `queue_cover`, so a
+    /// slot releases a real or a dummy frame through the same path.
+    #[cfg(feature = "v2_protocol")]
+    async fn release_slot(&mut self) -> B4aeResult<()> {
+        let frame = match self.scheduler_mut().poll_release(scheduler_now()) {
+            Some(Release::Message(scheduled)) => scheduled.payload,
+            // Only reached with an empty queue when no cover was queued
+            Some(Release::Cover) => {
+                let dummy = self.client.encrypt_dummy_message(&self.peer_id)?;
+                self.wrap(dummy)?
+            }
+            None => return Ok(()),
+        };
+        self.transport.send(frame).await
+    }
+
+    /// Queue a dummy frame for the next slot
+    #[cfg(feature = "v2_protocol")]
+    fn queue_cover(&mut self) -> B4aeResult<()> {
+        let dummy = self.client.encrypt_dummy_message(&self.peer_id)?;
+        let frame = self.wrap(dummy)?;
+        let session_id = self.scheduler_session_id();
+        self.scheduler_mut()
+            .schedule_message(session_id, frame, true)
+            .map_err(B4aeError::NetworkError)
+    }
+
+    #[cfg(feature = "v2_protocol")]
+    fn scheduler_session_id(&self) -> SessionId {
+        use sha3::{Digest, Sha3_256};
+        SessionId::new(Sha3_256::digest(&self.peer_id).into())
+    }
+
+    #[cfg(feature = "v2_protocol")]
+    fn scheduler_mut(&mut self) -> &mut GlobalTrafficScheduler {
+        self.scheduler.as_mut().expect("scheduler configured")
+    }
+}
+
+/// Scheduler clock; follows tokio's clock so paused-time tests stay in step
+#[cfg(feature = "v2_protocol")]
+fn scheduler_now() -> std::time::Instant {
+    tokio::time::Instant::now().into_std()
+}
+
+fn unexpected(expected: &str, got: &WireFrame) -> B4aeError {
+    B4aeError::ProtocolError(format!("Expected {} frame, got {}", expected, got.name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::B4aeConfig;
+    use crate::metadata::ProtectionLevel;
+    use crate::protocol::SecurityProfile;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// In-memory duplex transport; mpsc `recv` is cancel-safe
+    struct MemoryTransport {
+        tx: mpsc::UnboundedSender<Vec<u8>>,
+        rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    }
+
+    fn duplex() -> (MemoryTransport, MemoryTransport) {
+        let (a_tx, b_rx) = mpsc::unbounded_channel();
+        let (b_tx, a_rx) = mpsc::unbounded_channel();
+        (MemoryTransport { tx: a_tx, rx: a_rx }, MemoryTransport { tx: b_tx, rx: b_rx })
+    }
+
+    #[async_trait]
+    impl AsyncTransport for MemoryTransport {
+        async fn send(&mut self, frame: Vec<u8>) -> B4aeResult<()> {
+            self.tx
+                .send(frame)
+                .map_err(|_| B4aeError::NetworkError("peer closed".to_string()))
+        }
+
+        async fn recv(&mut self) -> B4aeResult<Vec<u8>> {
+            self.rx
+                .recv()
+                .await
+                .ok_or_else(|| B4aeError::NetworkError("peer closed".to_string()))
+        }
+    }
+
+    fn pair(profile: SecurityProfile) -> (AsyncB4aeClient<MemoryTransport>, AsyncB4aeClient<MemoryTransport>) {
+        let (a, b) = duplex();
+        let alice = AsyncB4aeClient::initiator(B4aeClient::new(profile).unwrap(), b"bob", a);
+        let bob = AsyncB4aeClient::responder(B4aeClient::new(profile).unwrap(), b"alice", b);
+        (alice, bob)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_bidirectional_exchange() {
+        let (mut alice, mut bob) = pair(SecurityProfile::High);
+
+        let (sent, received) = tokio::join!(alice.send(b"hello bob"), bob.recv());
+        sent.unwrap();
+        assert_eq!(received.unwrap(), b"hello bob");
+        assert!(alice.is_established() && bob.is_established());
+
+        for i in 0..5u8 {
+            let to_alice = vec![i; 100 + i as usize];
+            bob.send(&to_alice).await.unwrap();
+            alice.send(&[i]).await.unwrap();
+            assert_eq!(alice.recv().await.unwrap(), to_alice);
+            assert_eq!(bob.recv().await.unwrap(), vec![i]);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_dropped_recv_loses_nothing() {
+        let (mut alice, mut bob) = pair(SecurityProfile::Standard);
+
+        // Cancelled while waiting for HandshakeInit: handshake still completes later
+        assert!(tokio::time::timeout(Duration::from_millis(10), bob.recv()).await.is_err());
+        let (sent, received) = tokio::join!(alice.send(b"first"), bob.recv());
+        sent.unwrap();
+        assert_eq!(received.unwrap(), b"first");
+
+        // Cancelled on an idle established session, then messages arrive
+        assert!(tokio::time::timeout(Duration::from_millis(10), bob.recv()).await.is_err());
+        alice.send(b"second").await.unwrap();
+        alice.send(b"third").await.unwrap();
+        assert_eq!(bob.recv().await.unwrap(), b"second");
+        assert_eq!(bob.recv().await.unwrap(), b"third");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_malformed_frame() {
+        let config = B4aeConfig::builder()
+            .protection_level(ProtectionLevel::Basic)
+            .build()
+            .unwrap();
+        let (a, mut raw) = duplex();
+        let mut bob = AsyncB4aeClient::responder(B4aeClient::with_config(config).unwrap(), b"alice", a);
+
+        raw.send(vec![0xff; 3]).await.unwrap();
+        assert!(matches!(bob.recv().await, Err(B4aeError::ProtocolError(_))));
+        assert!(!bob.is_established());
+    }
+
+    #[cfg(feature = "v2_protocol")]
+    #[tokio::test]
+    async fn test_scheduled_send() {
+        let (mut alice, mut bob) = pair(SecurityProfile::Standard);
+        let (sent, received) = tokio::join!(alice.send(b"handshake"), bob.recv());
+        sent.unwrap();
+        assert_eq!(received.unwrap(), b"handshake");
+
+        // Rebinding the client keeps its session
+        let (client, transport) = alice.into_parts();
+        let mut alice_scheduled = AsyncB4aeClient::initiator(client, b"bob", transport)
+            .with_traffic_scheduler(GlobalTrafficScheduler::new(1000.0));
+        assert!(alice_scheduled.is_established());
+
+        for i in 0..3u8 {
+            alice_scheduled.send(&[i; 16]).await.unwrap();
+            assert_eq!(bob.recv().await.unwrap(), vec![i; 16]);
+        }
+    }
+
+    /// Transport that records when each frame was sent
+    #[cfg(feature = "v2_protocol")]
+    struct RecordingTransport {
+        inner: MemoryTransport,
+        sent: std::sync::Arc<std::sync::Mutex<Vec<tokio::time::Instant>>>,
+    }
+
+    #[cfg(feature = "v2_protocol")]
+    #[async_trait]
+    impl AsyncTransport for RecordingTransport {
+        async fn send(&mut self, frame: Vec<u8>) -> B4aeResult<()> {
+            self.sent.lock().unwrap().push(tokio::time::Instant::now());
+            self.inner.send(frame).await
+        }
+
+        async fn recv(&mut self) -> B4aeResult<Vec<u8>> {
+            self.inner.recv().await
+        }
+    }
+
+    #[cfg(feature = "v2_protocol")]
+    #[tokio::test(start_paused = true)]
+    async fn test_cover_and_real_frames_share_slots() {
+        let (a, b) = duplex();
+        let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let transport = RecordingTransport { inner: a, sent: sent.clone() };
+        // Constant rate: one slot per millisecond
+        let scheduler = GlobalTrafficScheduler::new(1000.0);
+        let client = B4aeClient::new(SecurityProfile::Standard).unwrap();
+        let mut alice = AsyncB4aeClient::initiator(client, b"bob", transport)
+            .with_traffic_scheduler(scheduler);
+        let mut bob = AsyncB4aeClient::responder(B4aeClient::new(SecurityProfile::Standard).unwrap(), b"alice", b);
+
+        let (sent_first, received) = tokio::join!(alice.send(b"first"), bob.recv());
+        sent_first.unwrap();
+        assert_eq!(received.unwrap(), b"first");
+        sent.lock().unwrap().clear();
+
+        // An idle recv keeps filling slots with cover, then a real send takes the next slot
+        assert!(tokio::time::timeout(Duration::from_millis(10), alice.recv()).await.is_err());
+        alice.send(b"second").await.unwrap();
+        assert_eq!(bob.recv().await.unwrap(), b"second");
+
+        let times = sent.lock().unwrap().clone();
+        assert!(times.len() >= 10, "only {} frames sent", times.len());
+        let gaps: Vec<_> = times.windows(2).map(|w| w[1] - w[0]).collect();
+        assert!(gaps.iter().all(|gap| *gap == gaps[0]), "uneven gaps: {:?}", gaps);
+    }
+}
@@ -4,14 +4,20 @@
 // traffic scheduler, mode binding, replay protection) ke high-level client API
 // yang kompatibel dengan README quickstart.
 
-use crate::audit::{AuditEntry, AuditEvent, AuditSink, hash_for_audit};
+use crate::audit::{
+    audit_handshake_step, hash_for_audit, hash_peer_id, AuditEntry, AuditEvent, AuditSink,
+    HandshakeFailure, HandshakeFailureReason, HandshakeStage,
+};
+use crate::client::B4aeConfig;
 use crate::crypto::CryptoError;
+use crate::crypto::dilithium::{self, VariantKeyPair, VariantPublicKey, VariantSignature};
 use crate::crypto::random;
+use crate::crypto::xeddsa::DeniableHybridKeyPair;
 use crate::error::{B4aeError, B4aeResult};
-use crate::protocol::session::Session;
+use crate::protocol::session::{CloseReason, Session, SessionEvent};
 use crate::protocol::message::{Message, MessageContent, EncryptedMessage};
 use crate::protocol::handshake::{
-    HandshakeConfig, HandshakeInitiator, HandshakeResponder,
+    identity_fingerprint, HandshakeConfig, HandshakeInitiator, HandshakeResponder,
     HandshakeInit as V1HandshakeInit,
     HandshakeResponse as V1HandshakeResponse,
     HandshakeComplete as V1HandshakeComplete,
@@ -28,13 +34,22 @@ use crate::protocol::v2::types::{
     HandshakeInit as V2HandshakeInit,
     HandshakeResponse as V2HandshakeResponse,
     HandshakeComplete as V2HandshakeComplete,
+    ClientHelloWithCookie,
+};
+use crate::protocol::v2::cookie_challenge::{generate_cookie, verify_cookie_with_clock, ServerSecret};
+use crate::protocol::v2::mode_binding::{
+    build_handshake_transcript, sign_mode_b_transcript, verify_mode_b_transcript,
 };
-use crate::protocol::v2::cookie_challenge::{generate_cookie, ServerSecret};
+use crate::protocol::v2::protocol_id::get_protocol_id;
+use crate::protocol::SecurityProfile;
 use crate::protocol::v2::replay_protection::ReplayProtection;
+use crate::security::hardened_core::CipherSuite;
+use crate::telemetry::{otel_handshake_span, otel_message_span, otel_record_session};
 use crate::time;
 use std::collections::HashMap;
 use std::sync::Arc;
 use bincode;
+use serde::{Deserialize, Serialize};
 
 /// Pending v2 handshake state (initiator side)
 struct V2InitiatorState {
@@ -50,6 +65,8 @@ struct V2InitiatorState {
     v1_initiator: HandshakeInitiator,
     /// Cached v1 HandshakeInit — generated in initiate_handshake_v2, used to build v2 envelope
     v1_init: Option<V1HandshakeInit>,
+    /// Timestamp of the ClientHello, echoed with the cookie in the HandshakeInit
+    hello_timestamp: Option<u64>,
     /// Timestamp of initiation (for timeout)
     started_at: u64,
 }
@@ -72,6 +89,14 @@ struct V2ResponderState {
     started_at: u64,
 }
 
+/// `signature` field of a Mode B handshake message: the signer's Dilithium
+/// public key and its signature over the v2 handshake transcript
+#[derive(Serialize, Deserialize)]
+struct ModeBSignature {
+    public_key: Vec<u8>,
+    signature: Vec<u8>,
+}
+
 /// Server-side v2 context (cookie challenge + replay protection)
 struct V2ServerContext {
     server_secret: ServerSecret,
@@ -82,7 +107,9 @@ struct V2ServerContext {
 /// B4AE v2.0 Client
 ///
 /// Implements the full B4AE v2.0 protocol flow:
-/// 1. Mode negotiation (Mode A = deniable XEdDSA, Mode B = PQ Dilithium5)
+/// 1. Mode negotiation (Mode A = deniable XEdDSA, Mode B = PQ Dilithium of the
+///    configured cipher suite: Dilithium5 by default, Dilithium3 under
+///    `CipherSuite::Standard`)
 /// 2. Stateless cookie challenge (DoS protection)
 /// 3. Handshake with mode-specific signatures
 /// 4. Encrypted messaging via global traffic scheduler
@@ -127,11 +154,19 @@ pub struct B4aeClientV2 {
     supported_modes: Vec<AuthenticationMode>,
     /// Handshake config (reuses v1 crypto machinery)
     handshake_config: HandshakeConfig,
+    /// Cipher suite selecting the Mode B Dilithium parameter set
+    cipher_suite: CipherSuite,
+    /// Mode B signing key for `cipher_suite`
+    mode_b_keypair: VariantKeyPair,
+    /// Long-term identity signing the v1 handshake payload; peers pin its fingerprint
+    identity: Arc<DeniableHybridKeyPair>,
     /// Optional audit sink
     audit_sink: Option<Arc<dyn AuditSink>>,
 
     /// Active v2 sessions indexed by peer_id
     sessions: HashMap<Vec<u8>, Session>,
+    /// Authentication mode of each active session
+    session_modes: HashMap<Vec<u8>, AuthenticationMode>,
 
     /// Pending initiator handshakes
     pending_initiators: HashMap<Vec<u8>, V2InitiatorState>,
@@ -160,13 +195,18 @@ impl B4aeClientV2 {
 
         // Advertise both production-ready modes; prefer the caller's choice
         let supported_modes = vec![AuthenticationMode::ModeA, AuthenticationMode::ModeB];
+        let cipher_suite = CipherSuite::High;
 
         Ok(B4aeClientV2 {
             preferred_mode,
             supported_modes,
             handshake_config: HandshakeConfig::default(),
+            cipher_suite,
+            mode_b_keypair: dilithium::keypair_with(cipher_suite.dilithium_variant())?,
+            identity: Arc::new(DeniableHybridKeyPair::generate()?),
             audit_sink: None,
             sessions: HashMap::new(),
+            session_modes: HashMap::new(),
             pending_initiators: HashMap::new(),
             pending_responders: HashMap::new(),
             server_ctx: None,
@@ -174,12 +214,66 @@ impl B4aeClientV2 {
         })
     }
 
+    /// Create a v2 client from a [`B4aeConfig`].
+    ///
+    /// Uses `config.authentication_mode` as the preferred mode; when unset,
+    /// Mode B under the High and Maximum profiles and Mode A under Standard.
+    /// Profiles above Standard advertise only the post-quantum Mode B, so
+    /// negotiation cannot settle on a weaker mode than the config allows.
+    /// The handshake config and audit sink are taken from `config` as well.
+    pub fn from_config(config: &B4aeConfig) -> B4aeResult<Self> {
+        let requires_pq = config.security_profile != SecurityProfile::Standard;
+        let preferred_mode = config.authentication_mode.unwrap_or(if requires_pq {
+            AuthenticationMode::ModeB
+        } else {
+            AuthenticationMode::ModeA
+        });
+        if requires_pq && !preferred_mode.is_post_quantum() {
+            return Err(B4aeError::ConfigError(format!(
+                "{:?} security profile requires a post-quantum authentication mode, got {:?}",
+                config.security_profile, preferred_mode
+            )));
+        }
+
+        let mut client = Self::new(preferred_mode)?;
+        if requires_pq {
+            client.supported_modes = vec![AuthenticationMode::ModeB];
+        }
+        client.handshake_config = config.handshake_config.clone();
+        client.audit_sink = config.audit_sink.clone();
+        Ok(client)
+    }
+
+    /// Fingerprint of this client's long-term identity, for a peer's
+    /// [`HandshakeConfig::expected_peer_fingerprint`]
+    pub fn identity_fingerprint(&self) -> [u8; 32] {
+        identity_fingerprint(&self.identity.public_key())
+    }
+
     /// Attach an audit sink for compliance logging.
     pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
         self.audit_sink = Some(sink);
         self
     }
 
+    /// Sign Mode B handshakes with the Dilithium parameter set of `suite`.
+    ///
+    /// The default is `CipherSuite::High` (Dilithium5). Both peers must use
+    /// suites with the same parameter set; a Mode B signature under another
+    /// one fails verification.
+    pub fn with_cipher_suite(mut self, suite: CipherSuite) -> B4aeResult<Self> {
+        if suite.dilithium_variant() != self.cipher_suite.dilithium_variant() {
+            self.mode_b_keypair = dilithium::keypair_with(suite.dilithium_variant())?;
+        }
+        self.cipher_suite = suite;
+        Ok(self)
+    }
+
+    /// Cipher suite used for Mode B signatures
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.cipher_suite
+    }
+
     /// Override the set of supported modes (must include preferred_mode).
     pub fn with_supported_modes(mut self, modes: Vec<AuthenticationMode>) -> B4aeResult<Self> {
         if !modes.contains(&self.preferred_mode) {
@@ -199,39 +293,36 @@ impl B4aeClientV2 {
     ///
     /// Returns a `ModeNegotiation` message to send to the server.
     pub fn initiate_mode_negotiation(&mut self, peer_id: &[u8]) -> B4aeResult<ModeNegotiation> {
-        let mut client_random = [0u8; 32];
-        random::fill_random(&mut client_random)
-            .map_err(|e| B4aeError::CryptoError(e.to_string()))?;
-
-        let negotiation = ModeNegotiation {
-            supported_modes: self.supported_modes.clone(),
-            preferred_mode: self.preferred_mode,
-            client_random,
-        };
+        otel_handshake_span!(HandshakeStage::ModeNegotiation, tracing::field::Empty);
+        self.audit_handshake_started(peer_id);
+        let step = (|| {
+            let mut client_random = [0u8; 32];
+            random::fill_random(&mut client_random)?;
 
-        // Store client_random under peer_id so we can use it after mode selection
-        // We store it in a placeholder initiator state (no v1_initiator yet)
-        let v1_initiator = HandshakeInitiator::new(self.handshake_config.clone())
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        self.pending_initiators.insert(peer_id.to_vec(), V2InitiatorState {
-            mode: self.preferred_mode, // tentative, overwritten in complete_mode_negotiation
-            client_random,
-            server_random: None,
-            mode_binding: None,
-            v1_initiator,
-            v1_init: None,
-            started_at: time::current_time_secs(),
-        });
-
-        if let Some(sink) = &self.audit_sink {
-            sink.log(AuditEntry::new(
-                AuditEvent::HandshakeInitiated { peer_id_hash: hash_for_audit(peer_id) },
-                None,
-            ));
-        }
+            let negotiation = ModeNegotiation {
+                supported_modes: self.supported_modes.clone(),
+                preferred_mode: self.preferred_mode,
+                client_random,
+            };
+
+            // Store client_random under peer_id so we can use it after mode selection
+            // We store it in a placeholder initiator state (no v1_initiator yet)
+            let v1_initiator = HandshakeInitiator::with_identity(self.handshake_config.clone(), Arc::clone(&self.identity))?;
+
+            self.pending_initiators.insert(peer_id.to_vec(), V2InitiatorState {
+                mode: self.preferred_mode, // tentative, overwritten in complete_mode_negotiation
+                client_random,
+                server_random: None,
+                mode_binding: None,
+                v1_initiator,
+                v1_init: None,
+                hello_timestamp: None,
+                started_at: time::current_time_secs(),
+            });
 
-        Ok(negotiation)
+            Ok(negotiation)
+        })();
+        self.audit_step(peer_id, HandshakeStage::ModeNegotiation, step)
     }
 
     /// **[Server]** Respond to a mode negotiation request.
@@ -242,36 +333,42 @@ impl B4aeClientV2 {
         peer_id: &[u8],
         negotiation: ModeNegotiation,
     ) -> B4aeResult<ModeSelection> {
-        let selected_mode = negotiate_authentication_mode(&negotiation, &self.supported_modes)
-            .map_err(|e: ModeNegotiationError| B4aeError::ProtocolError(e.to_string()))?;
-
-        let mut server_random = [0u8; 32];
-        random::fill_random(&mut server_random)
-            .map_err(|e| B4aeError::CryptoError(e.to_string()))?;
-
-        let client_random = negotiation.client_random;
-
-        // Ensure server context is initialised
-        self.ensure_server_ctx();
-
-        // Derive mode binding
-        let mode_binding = derive_mode_binding(&client_random, &server_random, selected_mode);
-
-        // Create pending responder state
-        let v1_responder = HandshakeResponder::new(self.handshake_config.clone())
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        self.pending_responders.insert(peer_id.to_vec(), V2ResponderState {
-            mode: selected_mode,
-            client_random: negotiation.client_random,
-            server_random,
-            mode_binding: Some(mode_binding),
-            v1_responder,
-            v1_response: None,
-            started_at: time::current_time_secs(),
-        });
+        otel_handshake_span!(HandshakeStage::ModeNegotiation, tracing::field::Empty);
+        self.audit_handshake_started(peer_id);
+        let step = (|| {
+            let selected_mode = negotiate_authentication_mode(&negotiation, &self.supported_modes)
+                .map_err(|e: ModeNegotiationError| HandshakeFailure::new(
+                    HandshakeFailureReason::ModeNegotiationFailed,
+                    B4aeError::ProtocolError(e.to_string()),
+                ))?;
+
+            let mut server_random = [0u8; 32];
+            random::fill_random(&mut server_random)?;
+
+            let client_random = negotiation.client_random;
+
+            // Ensure server context is initialised
+            self.ensure_server_ctx();
+
+            // Derive mode binding
+            let mode_binding = derive_mode_binding(&client_random, &server_random, selected_mode);
+
+            // Create pending responder state
+            let v1_responder = HandshakeResponder::with_identity(self.handshake_config.clone(), Arc::clone(&self.identity))?;
+
+            self.pending_responders.insert(peer_id.to_vec(), V2ResponderState {
+                mode: selected_mode,
+                client_random: negotiation.client_random,
+                server_random,
+                mode_binding: Some(mode_binding),
+                v1_responder,
+                v1_response: None,
+                started_at: time::current_time_secs(),
+            });
 
-        Ok(ModeSelection { selected_mode, server_random })
+            Ok(ModeSelection { selected_mode, server_random })
+        })();
+        self.audit_step(peer_id, HandshakeStage::ModeNegotiation, step)
     }
 
     /// **[Client]** Complete mode negotiation after receiving server's `ModeSelection`.
@@ -282,26 +379,33 @@ impl B4aeClientV2 {
         peer_id: &[u8],
         selection: ModeSelection,
     ) -> B4aeResult<()> {
-        let state = self.pending_initiators.get_mut(peer_id)
-            .ok_or_else(|| B4aeError::ProtocolError("No pending negotiation for peer".to_string()))?;
-
-        if !self.supported_modes.contains(&selection.selected_mode) {
-            return Err(B4aeError::ProtocolError(format!(
-                "Server selected unsupported mode: {:?}", selection.selected_mode
-            )));
-        }
+        otel_handshake_span!(HandshakeStage::ModeNegotiation, tracing::field::Empty);
+        let step = (|| {
+            let state = self.pending_initiators.get_mut(peer_id)
+                .ok_or_else(|| B4aeError::ProtocolError("No pending negotiation for peer".to_string()))?;
+
+            if !self.supported_modes.contains(&selection.selected_mode) {
+                return Err(HandshakeFailure::new(
+                    HandshakeFailureReason::ModeNegotiationFailed,
+                    B4aeError::ProtocolError(format!(
+                        "Server selected unsupported mode: {:?}", selection.selected_mode
+                    )),
+                ));
+            }
 
-        let mode_binding = derive_mode_binding(
-            &state.client_random,
-            &selection.server_random,
-            selection.selected_mode,
-        );
+            let mode_binding = derive_mode_binding(
+                &state.client_random,
+                &selection.server_random,
+                selection.selected_mode,
+            );
 
-        state.mode = selection.selected_mode;
-        state.server_random = Some(selection.server_random);
-        state.mode_binding = Some(mode_binding);
+            state.mode = selection.selected_mode;
+            state.server_random = Some(selection.server_random);
+            state.mode_binding = Some(mode_binding);
 
-        Ok(())
+            Ok(())
+        })();
+        self.audit_step(peer_id, HandshakeStage::ModeNegotiation, step)
     }
 
     // ─────────────────────────────────────────────────────────────────────────
@@ -309,14 +413,21 @@ impl B4aeClientV2 {
     // ─────────────────────────────────────────────────────────────────────────
 
     /// **[Client]** Send a minimal ClientHello to trigger a cookie challenge.
-    pub fn send_client_hello(&self, peer_id: &[u8]) -> B4aeResult<ClientHello> {
-        let state = self.pending_initiators.get(peer_id)
-            .ok_or_else(|| B4aeError::ProtocolError("No pending negotiation — call initiate_mode_negotiation first".to_string()))?;
-
-        Ok(ClientHello {
-            client_random: state.client_random,
-            timestamp: time::current_time_secs(),
-        })
+    pub fn send_client_hello(&mut self, peer_id: &[u8]) -> B4aeResult<ClientHello> {
+        otel_handshake_span!(HandshakeStage::CookieChallenge, tracing::field::Empty);
+        let step = (|| {
+            let state = self.pending_initiators.get_mut(peer_id)
+                .ok_or_else(|| B4aeError::ProtocolError("No pending negotiation — call initiate_mode_negotiation first".to_string()))?;
+
+            let timestamp = time::current_time_secs();
+            state.hello_timestamp = Some(timestamp);
+
+            Ok(ClientHello {
+                client_random: state.client_random,
+                timestamp,
+            })
+        })();
+        self.audit_step(peer_id, HandshakeStage::CookieChallenge, step)
     }
 
     /// **[Server]** Respond to a ClientHello with a stateless cookie challenge.
@@ -327,30 +438,36 @@ impl B4aeClientV2 {
         peer_id: &[u8],
         hello: ClientHello,
     ) -> B4aeResult<CookieChallenge> {
+        otel_handshake_span!(HandshakeStage::CookieChallenge, tracing::field::Empty);
         self.ensure_server_ctx();
 
-        let server_ctx = self.server_ctx.as_mut().unwrap();
+        let step = (|| {
+            let server_ctx = self.server_ctx.as_mut().unwrap();
 
-        let mut server_random = [0u8; 32];
-        random::fill_random(&mut server_random)
-            .map_err(|e| B4aeError::CryptoError(e.to_string()))?;
+            let mut server_random = [0u8; 32];
+            random::fill_random(&mut server_random)?;
 
-        let cookie_bytes = generate_cookie(
-            &server_ctx.server_secret,
-            "peer",           // IP not available in library context; use neutral placeholder
-            hello.timestamp,
-            &hello.client_random,
-        ).map_err(|e| B4aeError::ProtocolError(e.to_string()))?;
+            let cookie_bytes = generate_cookie(
+                &server_ctx.server_secret,
+                "peer",           // IP not available in library context; use neutral placeholder
+                hello.timestamp,
+                &hello.client_random,
+            ).map_err(|e| HandshakeFailure::new(
+                HandshakeFailureReason::Internal,
+                B4aeError::ProtocolError(e.to_string()),
+            ))?;
 
-        let mut cookie = [0u8; 32];
-        cookie.copy_from_slice(&cookie_bytes);
+            let mut cookie = [0u8; 32];
+            cookie.copy_from_slice(&cookie_bytes);
 
-        // If there is already a pending responder for this peer, update client_random
-        if let Some(state) = self.pending_responders.get_mut(peer_id) {
-            state.client_random = hello.client_random;
-        }
+            // If there is already a pending responder for this peer, update client_random
+            if let Some(state) = self.pending_responders.get_mut(peer_id) {
+                state.client_random = hello.client_random;
+            }
 
-        Ok(CookieChallenge { cookie, server_random })
+            Ok(CookieChallenge { cookie, server_random })
+        })();
+        self.audit_step(peer_id, HandshakeStage::CookieChallenge, step)
     }
 
     // ─────────────────────────────────────────────────────────────────────────
@@ -359,89 +476,159 @@ impl B4aeClientV2 {
 
     /// **[Client]** Initiate v2 handshake after cookie challenge.
     ///
-    /// `cookie_challenge` is the `CookieChallenge` received from the server.
+    /// `cookie_challenge` is the `CookieChallenge` received from the server;
+    /// its cookie is echoed in the returned `V2HandshakeInit`.
     /// Returns a `V2HandshakeInit` to send to the server.
     pub fn initiate_handshake_v2(
         &mut self,
         peer_id: &[u8],
-        _cookie_challenge: CookieChallenge,
+        cookie_challenge: CookieChallenge,
     ) -> B4aeResult<V2HandshakeInit> {
-        let state = self.pending_initiators.get_mut(peer_id)
-            .ok_or_else(|| B4aeError::ProtocolError("No pending v2 handshake for peer".to_string()))?;
-
-        let mode_binding = state.mode_binding.clone()
-            .ok_or_else(|| B4aeError::ProtocolError("Mode binding not set — complete mode negotiation first".to_string()))?;
-
-        // Generate v1 HandshakeInit (contains ephemeral keys, signature, client_random)
-        let v1_init = state.v1_initiator.generate_init()
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        // Serialize the full v1 init into ephemeral_kyber for transport.
-        // The responder will deserialize it and feed it to their v1 HandshakeResponder.
-        let v1_init_bytes = bincode::serialize(&v1_init)
-            .map_err(|e| B4aeError::CryptoError(format!("Serialize v1_init: {e}")))?;
-
-        let timestamp = time::current_time_secs();
-        let ephemeral_x25519 = state.client_random; // mode-negotiation binding anchor
-
-        // Cache for reference (not needed for crypto, kept for debugging)
-        state.v1_init = Some(v1_init);
-
-        Ok(V2HandshakeInit {
-            ephemeral_x25519,
-            ephemeral_kyber: v1_init_bytes,
-            signature: vec![0u8; 1], // placeholder — real sig is inside ephemeral_kyber payload
-            timestamp,
-            mode_binding,
-        })
+        otel_handshake_span!(HandshakeStage::Init, tracing::field::Empty);
+        let step = (|| {
+            let state = self.pending_initiators.get_mut(peer_id)
+                .ok_or_else(|| B4aeError::ProtocolError("No pending v2 handshake for peer".to_string()))?;
+
+            let mode_binding = state.mode_binding.clone()
+                .ok_or_else(|| B4aeError::ProtocolError("Mode binding not set — complete mode negotiation first".to_string()))?;
+
+            let hello_timestamp = state.hello_timestamp
+                .ok_or_else(|| B4aeError::ProtocolError("No ClientHello sent — call send_client_hello first".to_string()))?;
+            let cookie = ClientHelloWithCookie {
+                client_random: state.client_random,
+                cookie: cookie_challenge.cookie,
+                timestamp: hello_timestamp,
+            };
+
+            // Generate v1 HandshakeInit (contains ephemeral keys, signature, client_random)
+            let v1_init = state.v1_initiator.generate_init()?;
+
+            // Serialize the full v1 init into ephemeral_kyber for transport.
+            // The responder will deserialize it and feed it to their v1 HandshakeResponder.
+            let v1_init_bytes = bincode::serialize(&v1_init)
+                .map_err(|e| CryptoError::InvalidInput(format!("Serialize v1_init: {e}")))?;
+
+            let timestamp = time::current_time_secs();
+            let ephemeral_x25519 = state.client_random; // mode-negotiation binding anchor
+
+            // Cache for reference (not needed for crypto, kept for debugging)
+            state.v1_init = Some(v1_init);
+
+            let transcript = build_handshake_transcript(
+                get_protocol_id().as_bytes(),
+                &mode_binding,
+                &ephemeral_x25519,
+                &v1_init_bytes,
+                timestamp,
+            );
+            let signature = sign_mode_signature(state.mode, self.cipher_suite, &self.mode_b_keypair, &transcript)?;
+
+            Ok(V2HandshakeInit {
+                ephemeral_x25519,
+                ephemeral_kyber: v1_init_bytes,
+                signature,
+                timestamp,
+                mode_binding,
+                cookie,
+            })
+        })();
+        self.audit_step(peer_id, HandshakeStage::Init, step)
     }
 
     /// **[Server]** Respond to a v2 HandshakeInit.
     ///
-    /// Verifies mode binding, processes the init via v1 machinery, returns HandshakeResponse.
+    /// Verifies the echoed cookie and rejects replayed client randoms before
+    /// any expensive work, then verifies mode binding, processes the init via
+    /// v1 machinery, returns HandshakeResponse.
     pub fn respond_to_handshake_v2(
         &mut self,
         peer_id: &[u8],
         init: V2HandshakeInit,
     ) -> B4aeResult<V2HandshakeResponse> {
-        let state = self.pending_responders.get_mut(peer_id)
-            .ok_or_else(|| B4aeError::ProtocolError("No pending v2 responder for peer".to_string()))?;
-
-        let mode_binding = state.mode_binding.clone()
-            .ok_or_else(|| B4aeError::ProtocolError("Mode binding not set".to_string()))?;
-
-        // Verify mode binding to prevent downgrade attacks
-        verify_handshake_mode_binding(
-            &init.mode_binding,
-            &state.client_random,
-            &state.server_random,
-            state.mode,
-        ).map_err(|e: DowngradeError| B4aeError::ProtocolError(e.to_string()))?;
-
-        // Deserialize the v1 HandshakeInit that was serialized by the initiator
-        let v1_init: V1HandshakeInit = bincode::deserialize(&init.ephemeral_kyber)
-            .map_err(|e| B4aeError::CryptoError(format!("Deserialize v1_init: {e}")))?;
-
-        // Feed into v1 responder — this does the real crypto (signature verify, Kyber encaps)
-        let v1_response = state.v1_responder.process_init(v1_init)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        // Serialize the full v1 response for transport back to initiator
-        let v1_response_bytes = bincode::serialize(&v1_response)
-            .map_err(|e| B4aeError::CryptoError(format!("Serialize v1_response: {e}")))?;
-
-        // Cache v1_response for finalization step
-        state.v1_response = Some(v1_response);
-
-        let timestamp = time::current_time_secs();
-
-        Ok(V2HandshakeResponse {
-            ephemeral_x25519: state.server_random,
-            ephemeral_kyber: v1_response_bytes,
-            signature: vec![0u8; 1], // placeholder — real sig inside ephemeral_kyber payload
-            timestamp,
-            mode_binding,
-        })
+        otel_handshake_span!(HandshakeStage::Init, tracing::field::Empty);
+        let step = (|| {
+            let state = self.pending_responders.get_mut(peer_id)
+                .ok_or_else(|| B4aeError::ProtocolError("No pending v2 responder for peer".to_string()))?;
+
+            let mode_binding = state.mode_binding.clone()
+                .ok_or_else(|| B4aeError::ProtocolError("Mode binding not set".to_string()))?;
+
+            // Cookie first: cheap HMAC check before any signature or KEM work
+            let server_ctx = self.server_ctx.as_ref()
+                .ok_or_else(|| HandshakeFailure::new(
+                    HandshakeFailureReason::CookieInvalid,
+                    B4aeError::ProtocolError("No cookie challenge issued".to_string()),
+                ))?;
+            // Cookies are checked on the replay filter's clock and must not
+            // outlive the window in which it catches their reuse
+            verify_cookie_with_clock(
+                &init.cookie.cookie,
+                &server_ctx.server_secret,
+                "peer",
+                init.cookie.timestamp,
+                &init.cookie.client_random,
+                server_ctx.replay_filter.clock(),
+                server_ctx.replay_filter.expiry_window().as_secs(),
+            ).map_err(|e| HandshakeFailure::new(
+                HandshakeFailureReason::CookieInvalid,
+                B4aeError::ProtocolError(e.to_string()),
+            ))?;
+            server_ctx.replay_filter.check_and_insert(&init.cookie.client_random)
+                .map_err(|e| HandshakeFailure::new(
+                    HandshakeFailureReason::Replay,
+                    B4aeError::ProtocolError(e.to_string()),
+                ))?;
+
+            // Verify mode binding to prevent downgrade attacks
+            verify_handshake_mode_binding(
+                &init.mode_binding,
+                &state.client_random,
+                &state.server_random,
+                state.mode,
+            ).map_err(downgrade_failure)?;
+
+            let transcript = build_handshake_transcript(
+                get_protocol_id().as_bytes(),
+                &init.mode_binding,
+                &init.ephemeral_x25519,
+                &init.ephemeral_kyber,
+                init.timestamp,
+            );
+            verify_mode_signature(state.mode, self.cipher_suite, &transcript, &init.signature)?;
+
+            // Deserialize the v1 HandshakeInit that was serialized by the initiator
+            let v1_init: V1HandshakeInit = bincode::deserialize(&init.ephemeral_kyber)
+                .map_err(|e| CryptoError::InvalidInput(format!("Deserialize v1_init: {e}")))?;
+
+            // Feed into v1 responder — this does the real crypto (signature verify, Kyber encaps)
+            let v1_response = state.v1_responder.process_init(v1_init)?;
+
+            // Serialize the full v1 response for transport back to initiator
+            let v1_response_bytes = bincode::serialize(&v1_response)
+                .map_err(|e| CryptoError::InvalidInput(format!("Serialize v1_response: {e}")))?;
+
+            // Cache v1_response for finalization step
+            state.v1_response = Some(v1_response);
+
+            let timestamp = time::current_time_secs();
+            let transcript = build_handshake_transcript(
+                get_protocol_id().as_bytes(),
+                &mode_binding,
+                &state.server_random,
+                &v1_response_bytes,
+                timestamp,
+            );
+            let signature = sign_mode_signature(state.mode, self.cipher_suite, &self.mode_b_keypair, &transcript)?;
+
+            Ok(V2HandshakeResponse {
+                ephemeral_x25519: state.server_random,
+                ephemeral_kyber: v1_response_bytes,
+                signature,
+                timestamp,
+                mode_binding,
+            })
+        })();
+        self.audit_step(peer_id, HandshakeStage::Init, step)
     }
 
     /// **[Client]** Process v2 HandshakeResponse and return HandshakeComplete.
@@ -450,46 +637,57 @@ impl B4aeClientV2 {
         peer_id: &[u8],
         response: V2HandshakeResponse,
     ) -> B4aeResult<V2HandshakeComplete> {
-        let state = self.pending_initiators.get_mut(peer_id)
-            .ok_or_else(|| B4aeError::ProtocolError("No pending initiator for peer".to_string()))?;
-
-        let mode_binding = state.mode_binding.clone()
-            .ok_or_else(|| B4aeError::ProtocolError("Mode binding not set".to_string()))?;
-
-        let server_random = state.server_random
-            .ok_or_else(|| B4aeError::ProtocolError("No server random — complete mode negotiation first".to_string()))?;
-
-        // Verify mode binding against mode-negotiation randoms
-        verify_handshake_mode_binding(
-            &response.mode_binding,
-            &state.client_random,
-            &server_random,
-            state.mode,
-        ).map_err(|e: DowngradeError| B4aeError::ProtocolError(e.to_string()))?;
-
-        // Deserialize the v1 HandshakeResponse that was serialized by the responder
-        let v1_response: V1HandshakeResponse = bincode::deserialize(&response.ephemeral_kyber)
-            .map_err(|e| B4aeError::CryptoError(format!("Deserialize v1_response: {e}")))?;
-
-        // Feed into v1 initiator — verifies signature and decapsulates Kyber shared secret
-        state.v1_initiator.process_response(v1_response)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        // Generate v1 HandshakeComplete (confirmation + signature)
-        let v1_complete = state.v1_initiator.generate_complete()
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        // Serialize v1 complete for transport
-        let v1_complete_bytes = bincode::serialize(&v1_complete)
-            .map_err(|e| B4aeError::CryptoError(format!("Serialize v1_complete: {e}")))?;
-
-        let timestamp = time::current_time_secs();
-
-        Ok(V2HandshakeComplete {
-            signature: v1_complete_bytes, // carries full v1 complete payload
-            timestamp,
-            mode_binding,
-        })
+        otel_handshake_span!(HandshakeStage::Response, tracing::field::Empty);
+        let step = (|| {
+            let state = self.pending_initiators.get_mut(peer_id)
+                .ok_or_else(|| B4aeError::ProtocolError("No pending initiator for peer".to_string()))?;
+
+            let mode_binding = state.mode_binding.clone()
+                .ok_or_else(|| B4aeError::ProtocolError("Mode binding not set".to_string()))?;
+
+            let server_random = state.server_random
+                .ok_or_else(|| B4aeError::ProtocolError("No server random — complete mode negotiation first".to_string()))?;
+
+            // Verify mode binding against mode-negotiation randoms
+            verify_handshake_mode_binding(
+                &response.mode_binding,
+                &state.client_random,
+                &server_random,
+                state.mode,
+            ).map_err(downgrade_failure)?;
+
+            let transcript = build_handshake_transcript(
+                get_protocol_id().as_bytes(),
+                &response.mode_binding,
+                &response.ephemeral_x25519,
+                &response.ephemeral_kyber,
+                response.timestamp,
+            );
+            verify_mode_signature(state.mode, self.cipher_suite, &transcript, &response.signature)?;
+
+            // Deserialize the v1 HandshakeResponse that was serialized by the responder
+            let v1_response: V1HandshakeResponse = bincode::deserialize(&response.ephemeral_kyber)
+                .map_err(|e| CryptoError::InvalidInput(format!("Deserialize v1_response: {e}")))?;
+
+            // Feed into v1 initiator — verifies signature and decapsulates Kyber shared secret
+            state.v1_initiator.process_response(v1_response)?;
+
+            // Generate v1 HandshakeComplete (confirmation + signature)
+            let v1_complete = state.v1_initiator.generate_complete()?;
+
+            // Serialize v1 complete for transport
+            let v1_complete_bytes = bincode::serialize(&v1_complete)
+                .map_err(|e| CryptoError::InvalidInput(format!("Serialize v1_complete: {e}")))?;
+
+            let timestamp = time::current_time_secs();
+
+            Ok(V2HandshakeComplete {
+                signature: v1_complete_bytes, // carries full v1 complete payload
+                timestamp,
+                mode_binding,
+            })
+        })();
+        self.audit_step(peer_id, HandshakeStage::Response, step)
     }
 
     /// **[Server]** Process v2 HandshakeComplete and finalize the session.
@@ -498,69 +696,62 @@ impl B4aeClientV2 {
         peer_id: &[u8],
         complete: V2HandshakeComplete,
     ) -> B4aeResult<()> {
-        let state = self.pending_responders.remove(peer_id)
-            .ok_or_else(|| B4aeError::ProtocolError("No pending responder for peer".to_string()))?;
-
-        // Verify mode binding against mode-negotiation randoms
-        verify_handshake_mode_binding(
-            &complete.mode_binding,
-            &state.client_random,
-            &state.server_random,
-            state.mode,
-        ).map_err(|e: DowngradeError| B4aeError::ProtocolError(e.to_string()))?;
-
-        // Deserialize v1 HandshakeComplete from signature field
-        let v1_complete: V1HandshakeComplete = bincode::deserialize(&complete.signature)
-            .map_err(|e| B4aeError::CryptoError(format!("Deserialize v1_complete: {e}")))?;
-
-        let mut responder = state.v1_responder;
-        responder.process_complete(v1_complete)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        let result = responder.finalize()
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        let session = Session::from_handshake(result, peer_id.to_vec(), self.audit_sink.clone())
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        if let Some(sink) = &self.audit_sink {
-            sink.log(AuditEntry::new(
-                AuditEvent::HandshakeCompleted { peer_id_hash: hash_for_audit(peer_id) },
-                None,
-            ));
-            sink.log(AuditEntry::new(
-                AuditEvent::SessionCreated { session_id_hash: hash_for_audit(session.session_id()) },
-                None,
-            ));
-        }
-
+        otel_handshake_span!(HandshakeStage::Complete, tracing::field::Empty);
+        let step = (|| {
+            let state = self.pending_responders.remove(peer_id)
+                .ok_or_else(|| B4aeError::ProtocolError("No pending responder for peer".to_string()))?;
+
+            // Verify mode binding against mode-negotiation randoms
+            verify_handshake_mode_binding(
+                &complete.mode_binding,
+                &state.client_random,
+                &state.server_random,
+                state.mode,
+            ).map_err(downgrade_failure)?;
+
+            // Deserialize v1 HandshakeComplete from signature field
+            let v1_complete: V1HandshakeComplete = bincode::deserialize(&complete.signature)
+                .map_err(|e| CryptoError::InvalidInput(format!("Deserialize v1_complete: {e}")))?;
+
+            let mut responder = state.v1_responder;
+            responder.process_complete(v1_complete)?;
+
+            let result = responder.finalize()?;
+
+            let session = Session::from_handshake(result, peer_id.to_vec(), self.audit_sink.clone())?;
+            Ok((session, state.mode, responder.elapsed_ms()))
+        })();
+        let (session, mode, latency_ms) = self.audit_step(peer_id, HandshakeStage::Complete, step)?;
+
+        #[cfg(feature = "otel")]
+        Self::trace_mode(mode);
+        otel_record_session!(session);
+        self.audit_handshake_completed(peer_id, &session, mode, latency_ms);
         self.sessions.insert(peer_id.to_vec(), session);
+        self.session_modes.insert(peer_id.to_vec(), mode);
         Ok(())
     }
 
     /// **[Client]** Finalize initiator-side handshake and create session.
     pub fn finalize_initiator_v2(&mut self, peer_id: &[u8]) -> B4aeResult<()> {
-        let state = self.pending_initiators.remove(peer_id)
-            .ok_or_else(|| B4aeError::ProtocolError("No pending initiator for peer".to_string()))?;
-
-        let result = state.v1_initiator.finalize()
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        let session = Session::from_handshake(result, peer_id.to_vec(), self.audit_sink.clone())
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
-
-        if let Some(sink) = &self.audit_sink {
-            sink.log(AuditEntry::new(
-                AuditEvent::HandshakeCompleted { peer_id_hash: hash_for_audit(peer_id) },
-                None,
-            ));
-            sink.log(AuditEntry::new(
-                AuditEvent::SessionCreated { session_id_hash: hash_for_audit(session.session_id()) },
-                None,
-            ));
-        }
-
+        otel_handshake_span!(HandshakeStage::Finalize, tracing::field::Empty);
+        let step = (|| {
+            let state = self.pending_initiators.remove(peer_id)
+                .ok_or_else(|| B4aeError::ProtocolError("No pending initiator for peer".to_string()))?;
+
+            let result = state.v1_initiator.finalize()?;
+
+            let session = Session::from_handshake(result, peer_id.to_vec(), self.audit_sink.clone())?;
+            Ok((session, state.mode, state.v1_initiator.elapsed_ms()))
+        })();
+        let (session, mode, latency_ms) = self.audit_step(peer_id, HandshakeStage::Finalize, step)?;
+
+        #[cfg(feature = "otel")]
+        Self::trace_mode(mode);
+        otel_record_session!(session);
+        self.audit_handshake_completed(peer_id, &session, mode, latency_ms);
         self.sessions.insert(peer_id.to_vec(), session);
+        self.session_modes.insert(peer_id.to_vec(), mode);
         Ok(())
     }
 
@@ -577,6 +768,12 @@ impl B4aeClientV2 {
         peer_id: &[u8],
         plaintext: &[u8],
     ) -> B4aeResult<EncryptedMessage> {
+        otel_message_span!(
+            "b4ae.encrypt",
+            self.sessions,
+            peer_id,
+            tracing::field::debug(self.session_mode(peer_id).unwrap_or(self.preferred_mode))
+        );
         if plaintext.len() > crate::MAX_MESSAGE_SIZE {
             return Err(B4aeError::InvalidInput(format!(
                 "Message too large: {} > {}", plaintext.len(), crate::MAX_MESSAGE_SIZE
@@ -588,7 +785,7 @@ impl B4aeClientV2 {
 
         let message = Message::binary(plaintext.to_vec());
         let encrypted = session.send(&message)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
+            .map_err(B4aeError::CryptoError)?;
 
         // Schedule via global traffic scheduler for metadata protection
         let sid = SessionId::new(*session.session_id());
@@ -599,17 +796,33 @@ impl B4aeClientV2 {
 
     /// **[Both]** Decrypt a message from a peer.
     ///
-    /// Returns the plaintext, or an empty `Vec` if it was a dummy traffic message.
+    /// Returns the plaintext, or an empty `Vec` if it was a dummy traffic
+    /// message or an ACK. A CLOSE from the peer removes the session and
+    /// returns `B4aeError::ProtocolError`.
     pub fn decrypt_message_v2(
         &mut self,
         peer_id: &[u8],
         encrypted: &EncryptedMessage,
     ) -> B4aeResult<Vec<u8>> {
+        otel_message_span!(
+            "b4ae.decrypt",
+            self.sessions,
+            peer_id,
+            tracing::field::debug(self.session_mode(peer_id).unwrap_or(self.preferred_mode))
+        );
         let session = self.sessions.get_mut(peer_id)
             .ok_or_else(|| B4aeError::ProtocolError("No session with peer".to_string()))?;
 
-        let message = session.receive(encrypted)
-            .map_err(|e: CryptoError| B4aeError::CryptoError(e.to_string()))?;
+        // ACKs and CLOSE frames are applied by the session, not surfaced as data
+        let message = match session.process(encrypted)? {
+            SessionEvent::Message(message) => message,
+            SessionEvent::Acked(_) => return Ok(vec![]),
+            SessionEvent::Closed(reason) => {
+                self.sessions.remove(peer_id);
+                self.session_modes.remove(peer_id);
+                return Err(B4aeError::ProtocolError(format!("Peer closed the session ({:?})", reason)));
+            }
+        };
 
         let data = match &message.content {
             MessageContent::Dummy => return Ok(vec![]),
@@ -630,8 +843,17 @@ impl B4aeClientV2 {
         self.sessions.contains_key(peer_id)
     }
 
+    /// Authentication mode negotiated for the session with a peer.
+    pub fn session_mode(&self, peer_id: &[u8]) -> Option<AuthenticationMode> {
+        self.session_modes.get(peer_id).copied()
+    }
+
     /// Close and remove a session with a peer.
-    pub fn close_session(&mut self, peer_id: &[u8]) {
+    ///
+    /// Returns the authenticated CLOSE frame to deliver to the peer, or `None`
+    /// if there was no active session. The local keys are wiped either way.
+    pub fn close_session(&mut self, peer_id: &[u8]) -> Option<EncryptedMessage> {
+        self.session_modes.remove(peer_id);
         if let Some(mut session) = self.sessions.remove(peer_id) {
             if let Some(sink) = &self.audit_sink {
                 sink.log(AuditEntry::new(
@@ -641,8 +863,10 @@ impl B4aeClientV2 {
                     None,
                 ));
             }
-            session.close();
+            // Keys are wiped even if no frame could be produced
+            return session.close(CloseReason::Normal).ok();
         }
+        None
     }
 
     /// Remove inactive sessions older than `max_inactive_secs`.
@@ -652,13 +876,44 @@ impl B4aeClientV2 {
             let inactive = now.saturating_sub(session.info().last_activity);
             inactive < max_inactive_secs
         });
+        let sessions = &self.sessions;
+        self.session_modes.retain(|peer_id, _| sessions.contains_key(peer_id));
     }
 
     /// Remove stale pending handshakes (timed out after 60 s).
+    ///
+    /// Each removed handshake is audited as `HandshakeFailed` with reason
+    /// `Timeout` and the stage it was waiting in.
     pub fn cleanup_stale_handshakes(&mut self) {
         let now = time::current_time_secs();
-        self.pending_initiators.retain(|_, s| now.saturating_sub(s.started_at) < 60);
-        self.pending_responders.retain(|_, s| now.saturating_sub(s.started_at) < 60);
+        let sink = self.audit_sink.clone();
+        let timed_out = |peer_id: &[u8], stage: HandshakeStage| {
+            let failure = HandshakeFailure::new(
+                HandshakeFailureReason::Timeout,
+                B4aeError::ProtocolError("Handshake timed out".to_string()),
+            );
+            let _ = audit_handshake_step::<()>(sink.as_ref(), peer_id, stage, Err(failure));
+        };
+        self.pending_initiators.retain(|peer_id, s| {
+            let fresh = now.saturating_sub(s.started_at) < 60;
+            if !fresh {
+                let stage = match (&s.mode_binding, &s.v1_init) {
+                    (None, _) => HandshakeStage::ModeNegotiation,
+                    (Some(_), None) => HandshakeStage::CookieChallenge,
+                    (Some(_), Some(_)) => HandshakeStage::Response,
+                };
+                timed_out(peer_id, stage);
+            }
+            fresh
+        });
+        self.pending_responders.retain(|peer_id, s| {
+            let fresh = now.saturating_sub(s.started_at) < 60;
+            if !fresh {
+                let stage = if s.v1_response.is_some() { HandshakeStage::Complete } else { HandshakeStage::Init };
+                timed_out(peer_id, stage);
+            }
+            fresh
+        });
     }
 
     /// Combined cleanup: sessions + stale handshakes.
@@ -681,6 +936,52 @@ impl B4aeClientV2 {
     // INTERNAL HELPERS
     // ─────────────────────────────────────────────────────────────────────────
 
+    fn audit_step<T>(&self, peer_id: &[u8], stage: HandshakeStage, step: Result<T, HandshakeFailure>) -> B4aeResult<T> {
+        #[cfg(feature = "otel")]
+        if let Some((mode, algorithms)) = self.pending_initiators.get(peer_id)
+            .map(|s| (s.mode, s.v1_initiator.selected_algorithms()))
+            .or_else(|| self.pending_responders.get(peer_id).map(|s| (s.mode, s.v1_responder.selected_algorithms())))
+        {
+            Self::trace_mode(mode);
+            if !algorithms.is_empty() {
+                crate::telemetry::otel_record_cipher_suite!(algorithms);
+            }
+        }
+        audit_handshake_step(self.audit_sink.as_ref(), peer_id, stage, step)
+    }
+
+    /// Tag the current handshake span with the (tentative or negotiated) mode
+    #[cfg(feature = "otel")]
+    fn trace_mode(mode: AuthenticationMode) {
+        crate::telemetry::otel_record!("mode", tracing::field::debug(mode));
+    }
+
+    fn audit_handshake_started(&self, peer_id: &[u8]) {
+        if let Some(sink) = &self.audit_sink {
+            sink.log(AuditEntry::new(
+                AuditEvent::HandshakeStarted { peer_id_hash: hash_peer_id(peer_id, sink.peer_id_salt()) },
+                None,
+            ));
+        }
+    }
+
+    fn audit_handshake_completed(&self, peer_id: &[u8], session: &Session, mode: AuthenticationMode, latency_ms: u64) {
+        if let Some(sink) = &self.audit_sink {
+            sink.log(AuditEntry::new(
+                AuditEvent::HandshakeCompleted {
+                    peer_id_hash: hash_peer_id(peer_id, sink.peer_id_salt()),
+                    mode: format!("{:?}", mode),
+                    latency_ms,
+                },
+                None,
+            ));
+            sink.log(AuditEntry::new(
+                AuditEvent::SessionCreated { session_id_hash: hash_for_audit(session.session_id()) },
+                None,
+            ));
+        }
+    }
+
     fn ensure_server_ctx(&mut self) {
         if self.server_ctx.is_none() {
             self.server_ctx = Some(V2ServerContext {
@@ -692,6 +993,56 @@ impl B4aeClientV2 {
     }
 }
 
+/// `signature` field for a v2 init or response in `mode`
+///
+/// Mode B signs the transcript with the Dilithium parameter set of `suite`.
+/// Mode A carries a placeholder; its XEdDSA signature is inside the v1
+/// payload.
+fn sign_mode_signature(
+    mode: AuthenticationMode,
+    suite: CipherSuite,
+    keypair: &VariantKeyPair,
+    transcript: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    if mode != AuthenticationMode::ModeB {
+        return Ok(vec![0u8; 1]);
+    }
+    let signature = sign_mode_b_transcript(suite, &keypair.secret_key, transcript)?;
+    bincode::serialize(&ModeBSignature {
+        public_key: keypair.public_key.as_bytes().to_vec(),
+        signature: signature.as_bytes().to_vec(),
+    })
+    .map_err(|e| CryptoError::InvalidInput(format!("Serialize Mode B signature: {e}")))
+}
+
+/// Check the `signature` field of a v2 init or response in `mode`
+fn verify_mode_signature(
+    mode: AuthenticationMode,
+    suite: CipherSuite,
+    transcript: &[u8],
+    signature: &[u8],
+) -> Result<(), CryptoError> {
+    if mode != AuthenticationMode::ModeB {
+        return Ok(());
+    }
+    let envelope: ModeBSignature = bincode::deserialize(signature)
+        .map_err(|e| CryptoError::InvalidInput(format!("Deserialize Mode B signature: {e}")))?;
+    // Keys and signatures of another parameter set do not parse under `suite`
+    let variant = suite.dilithium_variant();
+    let mismatch = |_| CryptoError::VerificationFailed(format!("Mode B signature is not {:?}", variant));
+    let public_key = VariantPublicKey::from_bytes(variant, &envelope.public_key).map_err(mismatch)?;
+    let signature = VariantSignature::from_bytes(variant, &envelope.signature).map_err(mismatch)?;
+    if !verify_mode_b_transcript(suite, &public_key, transcript, &signature)? {
+        return Err(CryptoError::VerificationFailed("Mode B transcript signature invalid".to_string()));
+    }
+    Ok(())
+}
+
+/// Mode binding mismatch on a handshake message
+fn downgrade_failure(e: DowngradeError) -> HandshakeFailure {
+    HandshakeFailure::new(HandshakeFailureReason::ModeDowngrade, B4aeError::ProtocolError(e.to_string()))
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // TESTS
 // ─────────────────────────────────────────────────────────────────────────────
@@ -718,6 +1069,28 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_from_config_honours_authentication_mode() {
+        let config = B4aeConfig::builder()
+            .authentication_mode(AuthenticationMode::ModeB)
+            .build()
+            .unwrap();
+        let client = B4aeClientV2::from_config(&config).unwrap();
+        assert_eq!(client.preferred_mode(), AuthenticationMode::ModeB);
+
+        // Unset: the profile picks the mode, and above Standard only Mode B is offered
+        let standard = B4aeClientV2::from_config(&B4aeConfig::default()).unwrap();
+        assert_eq!(standard.preferred_mode(), AuthenticationMode::ModeA);
+        let high = B4aeClientV2::from_config(&B4aeConfig::from_profile(SecurityProfile::High)).unwrap();
+        assert_eq!(high.preferred_mode(), AuthenticationMode::ModeB);
+        assert_eq!(high.supported_modes, vec![AuthenticationMode::ModeB]);
+
+        // A directly constructed config bypasses the builder but not this check
+        let mut config = B4aeConfig::from_profile(SecurityProfile::Maximum);
+        config.authentication_mode = Some(AuthenticationMode::ModeA);
+        assert!(matches!(B4aeClientV2::from_config(&config), Err(B4aeError::ConfigError(_))));
+    }
+
     #[test]
     fn test_mode_negotiation_roundtrip() {
         let mut alice = B4aeClientV2::new(AuthenticationMode::ModeA).unwrap();
@@ -736,7 +1109,7 @@ mod tests {
 
     #[test]
     fn test_client_hello_requires_negotiation() {
-        let alice  = B4aeClientV2::new(AuthenticationMode::ModeA).unwrap();
+        let mut alice = B4aeClientV2::new(AuthenticationMode::ModeA).unwrap();
         let bob_id = b"bob".to_vec();
 
         // Should fail — no negotiation yet
@@ -777,9 +1150,187 @@ mod tests {
         assert_eq!(dec, plaintext);
     }
 
+    #[test]
+    fn test_close_session_frame_closes_peer() {
+        let (mut alice, mut bob, _sink, challenge) = negotiated_pair();
+        let init = alice.initiate_handshake_v2(b"bob", challenge).unwrap();
+        let response = bob.respond_to_handshake_v2(b"alice", init).unwrap();
+        let complete = alice.process_response_v2(b"bob", response).unwrap();
+        bob.complete_handshake_v2(b"alice", complete).unwrap();
+        alice.finalize_initiator_v2(b"bob").unwrap();
+
+        let close = alice.close_session(b"bob").unwrap();
+        assert!(alice.close_session(b"bob").is_none());
+        assert!(matches!(bob.decrypt_message_v2(b"alice", &close), Err(B4aeError::ProtocolError(_))));
+        assert!(!bob.has_session(b"alice"));
+        assert_eq!(bob.session_mode(b"alice"), None);
+    }
+
     #[test]
     fn test_cleanup_no_panic() {
         let mut client = B4aeClientV2::new(AuthenticationMode::ModeA).unwrap();
         client.cleanup_old_state(); // must not panic on empty state
     }
+
+    /// Clients for a v2 handshake, with Bob's audit events captured
+    fn negotiated_pair() -> (B4aeClientV2, B4aeClientV2, Arc<crate::audit::MemoryAuditSink>, CookieChallenge) {
+        let sink = Arc::new(crate::audit::MemoryAuditSink::new());
+        let mut alice = B4aeClientV2::new(AuthenticationMode::ModeA).unwrap();
+        let mut bob = B4aeClientV2::new(AuthenticationMode::ModeA).unwrap()
+            .with_audit_sink(sink.clone());
+
+        let negotiation = alice.initiate_mode_negotiation(b"bob").unwrap();
+        let selection = bob.respond_mode_negotiation(b"alice", negotiation).unwrap();
+        alice.complete_mode_negotiation(b"bob", selection).unwrap();
+        let hello = alice.send_client_hello(b"bob").unwrap();
+        let challenge = bob.respond_cookie_challenge(b"alice", hello).unwrap();
+        (alice, bob, sink, challenge)
+    }
+
+    fn handshake_failures(sink: &crate::audit::MemoryAuditSink) -> Vec<AuditEvent> {
+        sink.entries()
+            .into_iter()
+            .map(|e| e.event)
+            .filter(|e| matches!(e, AuditEvent::HandshakeFailed { .. }))
+            .collect()
+    }
+
+    #[test]
+    fn test_v2_handshake_audit_events() {
+        let (mut alice, mut bob, sink, challenge) = negotiated_pair();
+        let init = alice.initiate_handshake_v2(b"bob", challenge).unwrap();
+        let response = bob.respond_to_handshake_v2(b"alice", init).unwrap();
+        let complete = alice.process_response_v2(b"bob", response).unwrap();
+        bob.complete_handshake_v2(b"alice", complete).unwrap();
+
+        let events: Vec<_> = sink.entries().into_iter().map(|e| e.event).collect();
+        assert_eq!(events[0], AuditEvent::HandshakeStarted { peer_id_hash: hash_peer_id(b"alice", sink.peer_id_salt()) });
+        assert!(matches!(
+            &events[1],
+            AuditEvent::HandshakeCompleted { mode, .. } if mode == "ModeA"
+        ));
+        assert!(handshake_failures(&sink).is_empty());
+    }
+
+    #[test]
+    fn test_v2_bad_signature_emits_one_handshake_failed() {
+        let (mut alice, mut bob, sink, challenge) = negotiated_pair();
+        let mut init = alice.initiate_handshake_v2(b"bob", challenge).unwrap();
+
+        let mut v1_init: V1HandshakeInit = bincode::deserialize(&init.ephemeral_kyber).unwrap();
+        let last = v1_init.signature.len() - 1;
+        v1_init.signature[last] ^= 0x01;
+        init.ephemeral_kyber = bincode::serialize(&v1_init).unwrap();
+
+        assert!(bob.respond_to_handshake_v2(b"alice", init).is_err());
+        assert_eq!(
+            handshake_failures(&sink),
+            vec![AuditEvent::HandshakeFailed {
+                peer_id_hash: hash_peer_id(b"alice", sink.peer_id_salt()),
+                reason: HandshakeFailureReason::BadSignature,
+                stage: HandshakeStage::Init,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_v2_mode_downgrade_is_audited() {
+        let (mut alice, mut bob, sink, challenge) = negotiated_pair();
+        let mut init = alice.initiate_handshake_v2(b"bob", challenge).unwrap();
+        init.mode_binding = derive_mode_binding(&[0u8; 32], &[0u8; 32], AuthenticationMode::ModeA);
+
+        assert!(bob.respond_to_handshake_v2(b"alice", init).is_err());
+        assert!(matches!(
+            handshake_failures(&sink)[..],
+            [AuditEvent::HandshakeFailed { reason: HandshakeFailureReason::ModeDowngrade, stage: HandshakeStage::Init, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_v2_forged_cookie_is_audited() {
+        let (mut alice, mut bob, sink, challenge) = negotiated_pair();
+        let mut init = alice.initiate_handshake_v2(b"bob", challenge).unwrap();
+        init.cookie.cookie[0] ^= 0x01;
+
+        assert!(bob.respond_to_handshake_v2(b"alice", init).is_err());
+        assert!(matches!(
+            handshake_failures(&sink)[..],
+            [AuditEvent::HandshakeFailed { reason: HandshakeFailureReason::CookieInvalid, stage: HandshakeStage::Init, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_v2_replayed_init_is_audited() {
+        let (mut alice, mut bob, sink, challenge) = negotiated_pair();
+        let init = alice.initiate_handshake_v2(b"bob", challenge).unwrap();
+
+        bob.respond_to_handshake_v2(b"alice", init.clone()).unwrap();
+        assert!(bob.respond_to_handshake_v2(b"alice", init).is_err());
+        assert!(matches!(
+            handshake_failures(&sink)[..],
+            [AuditEvent::HandshakeFailed { reason: HandshakeFailureReason::Replay, stage: HandshakeStage::Init, .. }]
+        ));
+    }
+
+    /// Mode B clients under the given cipher suites, negotiated up to the cookie challenge
+    fn mode_b_pair(
+        alice_suite: CipherSuite,
+        bob_suite: CipherSuite,
+    ) -> (B4aeClientV2, B4aeClientV2, Arc<crate::audit::MemoryAuditSink>, CookieChallenge) {
+        let sink = Arc::new(crate::audit::MemoryAuditSink::new());
+        let mut alice = B4aeClientV2::new(AuthenticationMode::ModeB).unwrap()
+            .with_cipher_suite(alice_suite).unwrap();
+        let mut bob = B4aeClientV2::new(AuthenticationMode::ModeB).unwrap()
+            .with_cipher_suite(bob_suite).unwrap()
+            .with_audit_sink(sink.clone());
+
+        let negotiation = alice.initiate_mode_negotiation(b"bob").unwrap();
+        let selection = bob.respond_mode_negotiation(b"alice", negotiation).unwrap();
+        assert_eq!(selection.selected_mode, AuthenticationMode::ModeB);
+        alice.complete_mode_negotiation(b"bob", selection).unwrap();
+        let hello = alice.send_client_hello(b"bob").unwrap();
+        let challenge = bob.respond_cookie_challenge(b"alice", hello).unwrap();
+        (alice, bob, sink, challenge)
+    }
+
+    #[test]
+    fn test_mode_b_signs_with_suite_dilithium_variant() {
+        for (suite, variant) in [
+            (CipherSuite::Standard, dilithium::DilithiumVariant::Dilithium3),
+            (CipherSuite::High, dilithium::DilithiumVariant::Dilithium5),
+        ] {
+            let (mut alice, mut bob, _, challenge) = mode_b_pair(suite, suite);
+            let init = alice.initiate_handshake_v2(b"bob", challenge).unwrap();
+            let envelope: ModeBSignature = bincode::deserialize(&init.signature).unwrap();
+            assert_eq!(envelope.signature.len(), variant.signature_size());
+
+            let response = bob.respond_to_handshake_v2(b"alice", init).unwrap();
+            let complete = alice.process_response_v2(b"bob", response).unwrap();
+            bob.complete_handshake_v2(b"alice", complete).unwrap();
+            alice.finalize_initiator_v2(b"bob").unwrap();
+            assert_eq!(bob.session_mode(b"alice"), Some(AuthenticationMode::ModeB));
+        }
+    }
+
+    #[test]
+    fn test_mode_b_rejects_other_suite_and_tampered_transcript() {
+        // Alice signs with Dilithium3, Bob expects Dilithium5
+        let (mut alice, mut bob, sink, challenge) = mode_b_pair(CipherSuite::Standard, CipherSuite::High);
+        let init = alice.initiate_handshake_v2(b"bob", challenge).unwrap();
+        assert!(bob.respond_to_handshake_v2(b"alice", init).is_err());
+        assert!(matches!(
+            handshake_failures(&sink)[..],
+            [AuditEvent::HandshakeFailed { reason: HandshakeFailureReason::BadSignature, stage: HandshakeStage::Init, .. }]
+        ));
+
+        // The signature covers the whole v2 init
+        let (mut alice, mut bob, sink, challenge) = mode_b_pair(CipherSuite::High, CipherSuite::High);
+        let mut init = alice.initiate_handshake_v2(b"bob", challenge).unwrap();
+        init.timestamp += 1;
+        assert!(bob.respond_to_handshake_v2(b"alice", init).is_err());
+        assert!(matches!(
+            handshake_failures(&sink)[..],
+            [AuditEvent::HandshakeFailed { reason: HandshakeFailureReason::BadSignature, .. }]
+        ));
+    }
 }
@@ -1,12 +1,23 @@
 // B4AE AES-256-GCM Implementation
 // Authenticated Encryption with Associated Data (AEAD)
+//
+// Available without `std`; random keys and nonces then come from the
+// caller's RNG through the `*_with_rng` functions. That RNG must be a real
+// CSPRNG: a repeated GCM nonce reveals the authentication key and the XOR
+// of both plaintexts.
 
 use crate::crypto::{CryptoError, CryptoResult};
+use crate::crypto::hkdf::{key_commitment, verify_key_commitment, KEY_COMMITMENT_SIZE};
 use aes_gcm::{
-    aead::{Aead, KeyInit, Payload},
-    Aes256Gcm, Nonce,
+    aead::{AeadInPlace, KeyInit},
+    Aes256Gcm, Nonce, Tag,
 };
-use rand::RngCore;
+#[cfg(feature = "std")]
+use aes_gcm::aead::Aead;
+#[cfg(feature = "std")]
+use crate::crypto::random::SecureRng;
+use alloc::{format, string::ToString, vec::Vec};
+use rand::{CryptoRng, RngCore};
 
 /// AES-256 key size in bytes (256 bits).
 pub const KEY_SIZE: usize = 32;
@@ -34,9 +45,15 @@ impl AesKey {
     }
 
     /// Generate random key
+    #[cfg(feature = "std")]
     pub fn generate() -> Self {
+        Self::generate_with_rng(&mut SecureRng::new())
+    }
+
+    /// Generate random key from a caller-supplied CSPRNG
+    pub fn generate_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
         let mut key = [0u8; KEY_SIZE];
-        rand::thread_rng().fill_bytes(&mut key);
+        rng.fill_bytes(&mut key);
         AesKey { key }
     }
 
@@ -47,35 +64,127 @@ impl AesKey {
 }
 
 /// Generate random nonce
+#[cfg(feature = "std")]
 pub fn generate_nonce() -> [u8; NONCE_SIZE] {
+    generate_nonce_with_rng(&mut SecureRng::new())
+}
+
+/// Generate random nonce from a caller-supplied CSPRNG
+pub fn generate_nonce_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> [u8; NONCE_SIZE] {
     let mut nonce = [0u8; NONCE_SIZE];
-    rand::thread_rng().fill_bytes(&mut nonce);
+    rng.fill_bytes(&mut nonce);
     nonce
 }
 
+/// Size of the random prefix of a [`NonceSequence`] nonce
+pub const NONCE_PREFIX_SIZE: usize = 4;
+
+/// Deterministic nonce source: `prefix(4) || counter(8, BE)`
+///
+/// Random nonces are only safe for about 2^32 encryptions per key (birthday
+/// bound); a sequence issues up to 2^64 - 1 distinct nonces under one key.
+/// The prefix is random so separate sequences under the same key (one per
+/// writer) stay apart. The counter state is the next counter to issue:
+/// persist [`to_bytes`](Self::to_bytes) before a ciphertext leaves the
+/// process, and restore with [`from_bytes`](Self::from_bytes), so a restart
+/// never reissues a nonce. Deliberately not `Clone`: two live copies of one
+/// state would issue the same nonces under the same key.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct NonceSequence {
+    prefix: [u8; NONCE_PREFIX_SIZE],
+    counter: u64,
+}
+
+impl NonceSequence {
+    /// Serialized size: `prefix(4) || counter(8, BE)`
+    pub const STATE_SIZE: usize = NONCE_SIZE;
+
+    /// New sequence with a random prefix, starting at counter 0
+    #[cfg(feature = "std")]
+    pub fn new() -> Self {
+        Self::new_with_rng(&mut SecureRng::new())
+    }
+
+    /// [`new`](Self::new) with the prefix drawn from `rng`
+    pub fn new_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut prefix = [0u8; NONCE_PREFIX_SIZE];
+        rng.fill_bytes(&mut prefix);
+        NonceSequence { prefix, counter: 0 }
+    }
+
+    /// Next unique nonce
+    ///
+    /// Fails once the counter reaches `u64::MAX`; the key must be rotated then.
+    pub fn next_nonce(&mut self) -> CryptoResult<[u8; NONCE_SIZE]> {
+        if self.counter == u64::MAX {
+            return Err(CryptoError::EncryptionFailed(
+                "Nonce counter exhausted; rotate the key".to_string()
+            ));
+        }
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce[..NONCE_PREFIX_SIZE].copy_from_slice(&self.prefix);
+        nonce[NONCE_PREFIX_SIZE..].copy_from_slice(&self.counter.to_be_bytes());
+        self.counter += 1;
+        Ok(nonce)
+    }
+
+    /// Counter the next nonce will carry
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    /// Serialize the state (`prefix || next counter`)
+    pub fn to_bytes(&self) -> [u8; NONCE_SIZE] {
+        let mut state = [0u8; NONCE_SIZE];
+        state[..NONCE_PREFIX_SIZE].copy_from_slice(&self.prefix);
+        state[NONCE_PREFIX_SIZE..].copy_from_slice(&self.counter.to_be_bytes());
+        state
+    }
+
+    /// Restore a state written by [`to_bytes`](Self::to_bytes)
+    pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        let state: &[u8; NONCE_SIZE] = bytes.try_into().map_err(|_| CryptoError::InvalidInput(
+            format!("Nonce sequence state must be {} bytes, got {}", Self::STATE_SIZE, bytes.len())
+        ))?;
+        let (prefix, counter) = state.split_at(NONCE_PREFIX_SIZE);
+        Ok(NonceSequence {
+            prefix: prefix.try_into().expect("split at NONCE_PREFIX_SIZE"),
+            counter: u64::from_be_bytes(counter.try_into().expect("8-byte counter")),
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for NonceSequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Encrypt data with AES-256-GCM
 /// Returns: (nonce, ciphertext_with_tag)
+#[cfg(feature = "std")]
 pub fn encrypt(
     key: &AesKey,
     plaintext: &[u8],
     associated_data: &[u8],
 ) -> CryptoResult<(Vec<u8>, Vec<u8>)> {
-    let cipher = Aes256Gcm::new_from_slice(&key.key)
-        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
-
-    let nonce_bytes = generate_nonce();
-    let nonce = Nonce::from_slice(&nonce_bytes);
-
-    let payload = Payload {
-        msg: plaintext,
-        aad: associated_data,
-    };
+    encrypt_with_rng(&mut SecureRng::new(), key, plaintext, associated_data)
+}
 
-    let ciphertext = cipher
-        .encrypt(nonce, payload)
-        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+/// [`encrypt`] with the nonce drawn from `rng`
+pub fn encrypt_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    key: &AesKey,
+    plaintext: &[u8],
+    associated_data: &[u8],
+) -> CryptoResult<(Vec<u8>, Vec<u8>)> {
+    let nonce = generate_nonce_with_rng(rng);
+    let (mut ciphertext, tag) = encrypt_detached(key, &nonce, associated_data, plaintext)?;
+    ciphertext.extend_from_slice(&tag);
 
-    Ok((nonce_bytes.to_vec(), ciphertext))
+    Ok((nonce.to_vec(), ciphertext))
 }
 
 /// Decrypt data with AES-256-GCM
@@ -85,37 +194,118 @@ pub fn decrypt(
     ciphertext: &[u8],
     associated_data: &[u8],
 ) -> CryptoResult<Vec<u8>> {
-    if nonce.len() != NONCE_SIZE {
-        return Err(CryptoError::DecryptionFailed(
-            format!("Invalid nonce size: expected {}, got {}", NONCE_SIZE, nonce.len())
-        ));
+    let nonce: &[u8; NONCE_SIZE] = nonce.try_into().map_err(|_| CryptoError::DecryptionFailed(
+        format!("Invalid nonce size: expected {}, got {}", NONCE_SIZE, nonce.len())
+    ))?;
+    if ciphertext.len() < TAG_SIZE {
+        return Err(CryptoError::AuthenticationFailed);
     }
 
+    let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - TAG_SIZE);
+    let tag: &[u8; TAG_SIZE] = tag.try_into().expect("split at TAG_SIZE");
+    decrypt_detached(key, nonce, associated_data, ciphertext, tag)
+}
+
+/// Encrypt with a caller-supplied nonce, returning the tag separately
+/// Returns: (ciphertext, tag); `ciphertext || tag` is the [`encrypt`] output
+///
+/// For formats that store the tag apart from the ciphertext. The nonce must
+/// never repeat under the same key.
+pub fn encrypt_detached(
+    key: &AesKey,
+    nonce: &[u8; NONCE_SIZE],
+    associated_data: &[u8],
+    plaintext: &[u8],
+) -> CryptoResult<(Vec<u8>, [u8; TAG_SIZE])> {
     let cipher = Aes256Gcm::new_from_slice(&key.key)
-        .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
 
-    let nonce = Nonce::from_slice(nonce);
+    let mut ciphertext = plaintext.to_vec();
+    let tag = cipher
+        .encrypt_in_place_detached(Nonce::from_slice(nonce), associated_data, &mut ciphertext)
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
 
-    let payload = Payload {
-        msg: ciphertext,
-        aad: associated_data,
-    };
+    Ok((ciphertext, tag.into()))
+}
+
+/// Decrypt a ciphertext whose tag is stored separately ([`encrypt_detached`])
+pub fn decrypt_detached(
+    key: &AesKey,
+    nonce: &[u8; NONCE_SIZE],
+    associated_data: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8; TAG_SIZE],
+) -> CryptoResult<Vec<u8>> {
+    let cipher = Aes256Gcm::new_from_slice(&key.key)
+        .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
 
-    let plaintext = cipher
-        .decrypt(nonce, payload)
-        .map_err(|_| CryptoError::DecryptionFailed("Authentication failed".to_string()))?;
+    let mut plaintext = ciphertext.to_vec();
+    cipher
+        .decrypt_in_place_detached(Nonce::from_slice(nonce), associated_data, &mut plaintext, Tag::from_slice(tag))
+        .map_err(|_| CryptoError::AuthenticationFailed)?;
 
     Ok(plaintext)
 }
 
+/// Encrypt `buffer` in place, appending the tag
+///
+/// The buffer grows by [`TAG_SIZE`] bytes; reserve that much spare capacity
+/// (`Vec::with_capacity(len + TAG_SIZE)`) to avoid a reallocation. The nonce
+/// must never repeat under the same key.
+pub fn encrypt_in_place(
+    key: &AesKey,
+    nonce: &[u8; NONCE_SIZE],
+    associated_data: &[u8],
+    buffer: &mut Vec<u8>,
+) -> CryptoResult<()> {
+    let cipher = Aes256Gcm::new_from_slice(&key.key)
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+    cipher
+        .encrypt_in_place(Nonce::from_slice(nonce), associated_data, buffer)
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))
+}
+
+/// Decrypt `buffer` (ciphertext with tag) in place, stripping the tag
+///
+/// Needs no extra capacity. On failure the buffer is cleared, so no
+/// unauthenticated data is left in it.
+pub fn decrypt_in_place(
+    key: &AesKey,
+    nonce: &[u8; NONCE_SIZE],
+    associated_data: &[u8],
+    buffer: &mut Vec<u8>,
+) -> CryptoResult<()> {
+    let cipher = Aes256Gcm::new_from_slice(&key.key)
+        .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+
+    cipher
+        .decrypt_in_place(Nonce::from_slice(nonce), associated_data, buffer)
+        .map_err(|_| {
+            buffer.clear();
+            CryptoError::AuthenticationFailed
+        })
+}
+
 /// Encrypt with automatic nonce prepending
 /// Format: [nonce || ciphertext_with_tag]
+#[cfg(feature = "std")]
 pub fn encrypt_combined(
     key: &AesKey,
     plaintext: &[u8],
     associated_data: &[u8],
 ) -> CryptoResult<Vec<u8>> {
-    let (nonce, ciphertext) = encrypt(key, plaintext, associated_data)?;
+    encrypt_combined_with_rng(&mut SecureRng::new(), key, plaintext, associated_data)
+}
+
+/// [`encrypt_combined`] with the nonce drawn from `rng`
+pub fn encrypt_combined_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    key: &AesKey,
+    plaintext: &[u8],
+    associated_data: &[u8],
+) -> CryptoResult<Vec<u8>> {
+    let (nonce, ciphertext) = encrypt_with_rng(rng, key, plaintext, associated_data)?;
     
     let mut combined = Vec::with_capacity(nonce.len() + ciphertext.len());
     combined.extend_from_slice(&nonce);
@@ -124,6 +314,63 @@ pub fn encrypt_combined(
     Ok(combined)
 }
 
+/// Encrypt with the next nonce of `seq`
+/// Format: [nonce || ciphertext_with_tag], as [`encrypt_combined`]; decrypt
+/// with [`decrypt_combined`]
+pub fn encrypt_seq(
+    seq: &mut NonceSequence,
+    key: &AesKey,
+    associated_data: &[u8],
+    plaintext: &[u8],
+) -> CryptoResult<Vec<u8>> {
+    let nonce = seq.next_nonce()?;
+    let (ciphertext, tag) = encrypt_detached(key, &nonce, associated_data, plaintext)?;
+
+    let mut combined = Vec::with_capacity(NONCE_SIZE + ciphertext.len() + TAG_SIZE);
+    combined.extend_from_slice(&nonce);
+    combined.extend_from_slice(&ciphertext);
+    combined.extend_from_slice(&tag);
+    Ok(combined)
+}
+
+/// Encrypt many independent records, in parallel with the `rayon` feature
+/// Format of each output: [nonce || ciphertext_with_tag] (no AAD), as [`encrypt_combined`]
+///
+/// Every item gets its own random nonce, drawn up front on the calling thread
+/// (so a [`with_random_source`](crate::crypto::random::with_random_source)
+/// override applies) before the items are sealed; there is no shared counter
+/// for the workers to race on. Without the `rayon` feature the items are
+/// processed in order on the calling thread.
+#[cfg(feature = "std")]
+pub fn encrypt_batch(key: &AesKey, items: &[&[u8]]) -> CryptoResult<Vec<Vec<u8>>> {
+    let cipher = Aes256Gcm::new_from_slice(&key.key)
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+    // rayon workers do not see the calling thread's random source
+    let nonces: Vec<[u8; NONCE_SIZE]> = items.iter().map(|_| generate_nonce()).collect();
+
+    let seal = |(plaintext, nonce_bytes): (&&[u8], &[u8; NONCE_SIZE])| -> CryptoResult<Vec<u8>> {
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(nonce_bytes), *plaintext)
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+        let mut combined = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        combined.extend_from_slice(nonce_bytes);
+        combined.extend_from_slice(&ciphertext);
+        Ok(combined)
+    };
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().zip(nonces.par_iter()).map(seal).collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.iter().zip(nonces.iter()).map(seal).collect()
+    }
+}
+
 /// Decrypt with automatic nonce extraction
 /// Format: [nonce || ciphertext_with_tag]
 pub fn decrypt_combined(
@@ -141,6 +388,58 @@ pub fn decrypt_combined(
     decrypt(key, nonce, ciphertext, associated_data)
 }
 
+/// Encrypt with a key-commitment tag
+/// Format: [commitment(32) || nonce || ciphertext_with_tag]
+///
+/// Use this where one ciphertext may be tried against several keys
+/// (multi-recipient, abuse reporting), since plain GCM is not key-committing.
+#[cfg(feature = "std")]
+pub fn encrypt_committing(
+    key: &AesKey,
+    plaintext: &[u8],
+    associated_data: &[u8],
+) -> CryptoResult<Vec<u8>> {
+    encrypt_committing_with_rng(&mut SecureRng::new(), key, plaintext, associated_data)
+}
+
+/// [`encrypt_committing`] with the nonce drawn from `rng`
+pub fn encrypt_committing_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    key: &AesKey,
+    plaintext: &[u8],
+    associated_data: &[u8],
+) -> CryptoResult<Vec<u8>> {
+    let commitment = key_commitment(&key.key)?;
+    let combined = encrypt_combined_with_rng(rng, key, plaintext, associated_data)?;
+
+    let mut committed = Vec::with_capacity(KEY_COMMITMENT_SIZE + combined.len());
+    committed.extend_from_slice(&commitment);
+    committed.extend_from_slice(&combined);
+
+    Ok(committed)
+}
+
+/// Decrypt data produced by [`encrypt_committing`]
+///
+/// The commitment is verified before any decryption is attempted.
+pub fn decrypt_committing(
+    key: &AesKey,
+    committed: &[u8],
+    associated_data: &[u8],
+) -> CryptoResult<Vec<u8>> {
+    if committed.len() < KEY_COMMITMENT_SIZE + NONCE_SIZE {
+        return Err(CryptoError::DecryptionFailed(
+            "Data too short to contain key commitment and nonce".to_string()
+        ));
+    }
+
+    let (commitment, combined) = committed.split_at(KEY_COMMITMENT_SIZE);
+    verify_key_commitment(&key.key, commitment)
+        .map_err(|_| CryptoError::DecryptionFailed("Key commitment mismatch".to_string()))?;
+
+    decrypt_combined(key, combined, associated_data)
+}
+
 // Secure drop implementation
 impl Drop for AesKey {
     fn drop(&mut self) {
@@ -151,8 +450,8 @@ impl Drop for AesKey {
     }
 }
 
-impl std::fmt::Debug for AesKey {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for AesKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "AesKey([REDACTED])")
     }
 }
@@ -213,4 +512,241 @@ mod tests {
         let result = decrypt(&key, &nonce, &ciphertext, wrong_aad);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_detached_interoperates_with_combined() {
+        let key = AesKey::generate();
+        let nonce = generate_nonce();
+
+        let (ciphertext, tag) = encrypt_detached(&key, &nonce, b"metadata", b"Hello, B4AE!").unwrap();
+        assert_eq!(ciphertext.len(), 12);
+        let mut combined = ciphertext.clone();
+        combined.extend_from_slice(&tag);
+        assert_eq!(decrypt(&key, &nonce, &combined, b"metadata").unwrap(), b"Hello, B4AE!");
+
+        let (nonce, combined) = encrypt(&key, b"Hello, B4AE!", b"metadata").unwrap();
+        let nonce: [u8; NONCE_SIZE] = nonce.try_into().unwrap();
+        let (ciphertext, tag) = combined.split_at(combined.len() - TAG_SIZE);
+        let tag: [u8; TAG_SIZE] = tag.try_into().unwrap();
+        assert_eq!(decrypt_detached(&key, &nonce, b"metadata", ciphertext, &tag).unwrap(), b"Hello, B4AE!");
+    }
+
+    #[test]
+    fn test_detached_wrong_tag_fails() {
+        let key = AesKey::generate();
+        let nonce = generate_nonce();
+        let (ciphertext, mut tag) = encrypt_detached(&key, &nonce, b"", b"payload").unwrap();
+
+        assert!(decrypt_detached(&key, &nonce, b"other", &ciphertext, &tag).is_err());
+        tag[TAG_SIZE - 1] ^= 1;
+        assert!(matches!(
+            decrypt_detached(&key, &nonce, b"", &ciphertext, &tag),
+            Err(CryptoError::AuthenticationFailed)
+        ));
+        assert!(matches!(decrypt(&key, &nonce, &tag[..15], b""), Err(CryptoError::AuthenticationFailed)));
+    }
+
+    #[test]
+    fn test_in_place_matches_allocating() {
+        let key = AesKey::generate();
+        let nonce = generate_nonce();
+        let plaintext = b"reused buffer".to_vec();
+
+        let mut buffer = Vec::with_capacity(plaintext.len() + TAG_SIZE);
+        buffer.extend_from_slice(&plaintext);
+        let capacity = buffer.capacity();
+        encrypt_in_place(&key, &nonce, b"metadata", &mut buffer).unwrap();
+        assert_eq!(buffer.capacity(), capacity, "tag fit in the reserved capacity");
+
+        let (ciphertext, tag) = encrypt_detached(&key, &nonce, b"metadata", &plaintext).unwrap();
+        assert_eq!(buffer, [ciphertext.as_slice(), tag.as_slice()].concat());
+        assert_eq!(decrypt(&key, &nonce, &buffer, b"metadata").unwrap(), plaintext);
+
+        decrypt_in_place(&key, &nonce, b"metadata", &mut buffer).unwrap();
+        assert_eq!(buffer, plaintext);
+    }
+
+    #[test]
+    fn test_in_place_grows_small_buffer() {
+        let key = AesKey::generate();
+        let nonce = generate_nonce();
+        let mut buffer = vec![0xAB; 1000];
+        buffer.shrink_to_fit();
+
+        encrypt_in_place(&key, &nonce, b"", &mut buffer).unwrap();
+        assert_eq!(buffer.len(), 1000 + TAG_SIZE);
+        decrypt_in_place(&key, &nonce, b"", &mut buffer).unwrap();
+        assert_eq!(buffer, vec![0xAB; 1000]);
+
+        // Tampered input fails and leaves nothing behind
+        encrypt_in_place(&key, &nonce, b"", &mut buffer).unwrap();
+        buffer[0] ^= 1;
+        assert!(matches!(
+            decrypt_in_place(&key, &nonce, b"", &mut buffer),
+            Err(CryptoError::AuthenticationFailed)
+        ));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_committing_roundtrip() {
+        let key = AesKey::generate();
+        let other = AesKey::generate();
+
+        let committed = encrypt_committing(&key, b"Hello, B4AE!", b"metadata").unwrap();
+        assert_eq!(decrypt_committing(&key, &committed, b"metadata").unwrap(), b"Hello, B4AE!");
+        assert!(decrypt_committing(&other, &committed, b"metadata").is_err());
+        assert!(decrypt_committing(&key, &committed[..KEY_COMMITMENT_SIZE], b"metadata").is_err());
+    }
+
+    /// GF(2^128) multiply in GCM bit order (NIST SP 800-38D, Algorithm 1)
+    fn gf_mul(x: u128, y: u128) -> u128 {
+        let r = 0xe1u128 << 120;
+        let mut z = 0u128;
+        let mut v = y;
+        for i in 0..128 {
+            if (x >> (127 - i)) & 1 == 1 {
+                z ^= v;
+            }
+            v = if v & 1 == 1 { (v >> 1) ^ r } else { v >> 1 };
+        }
+        z
+    }
+
+    /// Multiplicative inverse via a^(2^128 - 2)
+    fn gf_inv(a: u128) -> u128 {
+        let mut result = 1u128 << 127; // multiplicative identity in GCM order
+        for i in 0..128 {
+            result = gf_mul(result, result);
+            if i < 127 {
+                result = gf_mul(result, a);
+            }
+        }
+        result
+    }
+
+    fn aes_block(key: &[u8], block: [u8; 16]) -> u128 {
+        use aes::cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit};
+        let cipher = aes::Aes256::new(GenericArray::from_slice(key));
+        let mut block = GenericArray::from(block);
+        cipher.encrypt_block(&mut block);
+        u128::from_be_bytes(block.into())
+    }
+
+    /// Build a two-block GCM ciphertext whose tag verifies under both keys
+    fn forge_multi_key_ciphertext(k1: &AesKey, k2: &AesKey, nonce: &[u8; NONCE_SIZE]) -> Vec<u8> {
+        let mut j0 = [0u8; 16];
+        j0[..12].copy_from_slice(nonce);
+        j0[15] = 1;
+
+        let (h1, h2) = (aes_block(k1.as_bytes(), [0u8; 16]), aes_block(k2.as_bytes(), [0u8; 16]));
+        let (s1, s2) = (aes_block(k1.as_bytes(), j0), aes_block(k2.as_bytes(), j0));
+        let len_block = 256u128; // no AAD, 32 bytes of ciphertext
+
+        // tag = c1*H^3 + c2*H^2 + L*H + S; pick c1 and solve for c2
+        let c1 = 0u128;
+        let h1_2 = gf_mul(h1, h1);
+        let h2_2 = gf_mul(h2, h2);
+        let rhs = gf_mul(c1, gf_mul(h1_2, h1) ^ gf_mul(h2_2, h2))
+            ^ gf_mul(len_block, h1 ^ h2)
+            ^ s1 ^ s2;
+        let c2 = gf_mul(rhs, gf_inv(h1_2 ^ h2_2));
+        let tag = gf_mul(c1, gf_mul(h1_2, h1)) ^ gf_mul(c2, h1_2) ^ gf_mul(len_block, h1) ^ s1;
+
+        let mut forged = Vec::with_capacity(48);
+        forged.extend_from_slice(&c1.to_be_bytes());
+        forged.extend_from_slice(&c2.to_be_bytes());
+        forged.extend_from_slice(&tag.to_be_bytes());
+        forged
+    }
+
+    #[test]
+    fn test_committing_rejects_multi_key_forgery() {
+        let k1 = AesKey::from_bytes(&[0x01; KEY_SIZE]).unwrap();
+        let k2 = AesKey::from_bytes(&[0x02; KEY_SIZE]).unwrap();
+        let nonce = [0x07u8; NONCE_SIZE];
+
+        // Plain GCM accepts the forgery under both keys
+        let forged = forge_multi_key_ciphertext(&k1, &k2, &nonce);
+        assert!(decrypt(&k1, &nonce, &forged, b"").is_ok());
+        assert!(decrypt(&k2, &nonce, &forged, b"").is_ok());
+
+        // The committing format binds it to the key it was committed under
+        let mut committed = key_commitment(k1.as_bytes()).unwrap().to_vec();
+        committed.extend_from_slice(&nonce);
+        committed.extend_from_slice(&forged);
+        assert!(decrypt_committing(&k1, &committed, b"").is_ok());
+        assert!(decrypt_committing(&k2, &committed, b"").is_err());
+    }
+
+    #[test]
+    fn test_encrypt_batch_matches_single_item() {
+        let key = AesKey::generate();
+        let records: Vec<Vec<u8>> = (0..200u32).map(|i| vec![i as u8; (i % 37) as usize]).collect();
+        let items: Vec<&[u8]> = records.iter().map(|r| r.as_slice()).collect();
+
+        let batch = encrypt_batch(&key, &items).unwrap();
+        assert_eq!(batch.len(), items.len());
+        for (item, sealed) in items.iter().zip(&batch) {
+            let single = encrypt_combined(&key, item, b"").unwrap();
+            assert_eq!(sealed.len(), single.len());
+            assert_eq!(decrypt_combined(&key, sealed, b"").unwrap(), *item);
+            assert_eq!(decrypt_combined(&key, &single, b"").unwrap(), *item);
+        }
+
+        let nonces: std::collections::HashSet<&[u8]> = batch.iter().map(|c| &c[..NONCE_SIZE]).collect();
+        assert_eq!(nonces.len(), batch.len());
+        assert!(encrypt_batch(&key, &[]).unwrap().is_empty());
+
+        // Nonces come from the caller's random source, even on rayon workers
+        use crate::crypto::random::{with_random_source, DeterministicRandomSource};
+        let seeded = || with_random_source(DeterministicRandomSource::from_seed([5u8; 32]), || encrypt_batch(&key, &items));
+        assert_eq!(seeded().unwrap(), seeded().unwrap());
+    }
+
+    #[test]
+    fn test_nonce_sequence_unique() {
+        let key = AesKey::generate();
+        let mut seq = NonceSequence::new();
+        let mut seen = std::collections::HashSet::new();
+        for i in 0..10_000u64 {
+            assert_eq!(seq.counter(), i);
+            assert!(seen.insert(seq.next_nonce().unwrap()));
+        }
+
+        let sealed = encrypt_seq(&mut seq, &key, b"aad", b"payload").unwrap();
+        assert!(seen.insert(sealed[..NONCE_SIZE].try_into().unwrap()));
+        assert_eq!(decrypt_combined(&key, &sealed, b"aad").unwrap(), b"payload");
+
+        // Separate sequences under one key get separate prefixes
+        assert_ne!(NonceSequence::new().to_bytes()[..NONCE_PREFIX_SIZE], seq.to_bytes()[..NONCE_PREFIX_SIZE]);
+    }
+
+    #[test]
+    fn test_nonce_sequence_overflow() {
+        let mut state = NonceSequence::new().to_bytes();
+        state[NONCE_PREFIX_SIZE..].copy_from_slice(&(u64::MAX - 1).to_be_bytes());
+        let mut seq = NonceSequence::from_bytes(&state).unwrap();
+
+        assert_eq!(seq.next_nonce().unwrap()[NONCE_PREFIX_SIZE..], (u64::MAX - 1).to_be_bytes());
+        assert!(seq.next_nonce().is_err());
+        assert!(encrypt_seq(&mut seq, &AesKey::generate(), b"", b"late").is_err());
+        assert_eq!(seq.counter(), u64::MAX);
+    }
+
+    #[test]
+    fn test_nonce_sequence_restore_never_reissues() {
+        let mut seq = NonceSequence::new();
+        let mut issued: Vec<_> = (0..100).map(|_| seq.next_nonce().unwrap()).collect();
+
+        // Restart from the persisted state, via bytes and via serde
+        let mut restored = NonceSequence::from_bytes(&seq.to_bytes()).unwrap();
+        let json = serde_json::to_string(&restored).unwrap();
+        assert_eq!(serde_json::from_str::<NonceSequence>(&json).unwrap(), restored);
+        issued.extend((0..100).map(|_| restored.next_nonce().unwrap()));
+
+        let unique: std::collections::HashSet<_> = issued.iter().collect();
+        assert_eq!(unique.len(), issued.len());
+        assert!(NonceSequence::from_bytes(&[0u8; 8]).is_err());
+    }
 }
@@ -0,0 +1,170 @@
+//! AES-256-GCM-SIV (RFC 8452) Nonce-Misuse-Resistant AEAD
+//!
+//! Unlike [`aes_gcm`](crate::crypto::aes_gcm), GCM-SIV derives its keystream
+//! from a synthetic IV computed over the whole message. Reusing a nonce under
+//! the same key therefore does not reveal the key or the XOR of plaintexts:
+//! the only leak is whether two messages (with the same AAD) were identical.
+//! Fresh random nonces should still be used whenever possible.
+//!
+//! Thin wrapper over the RustCrypto `aes-gcm-siv` crate, using the
+//! in-place detached encrypt/decrypt split of `AeadInPlace`.
+
+use crate::crypto::{CryptoError, CryptoResult};
+use aes_gcm_siv::aead::generic_array::GenericArray;
+use aes_gcm_siv::aead::{AeadInPlace, KeyInit};
+use aes_gcm_siv::Aes256GcmSiv;
+
+/// AES-256 key size in bytes.
+pub const KEY_SIZE: usize = 32;
+/// GCM-SIV nonce size in bytes.
+pub const NONCE_SIZE: usize = 12;
+/// GCM-SIV authentication tag size in bytes.
+pub const TAG_SIZE: usize = 16;
+
+/// Encrypt with AES-256-GCM-SIV
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - `ciphertext || tag` (`plaintext.len() + TAG_SIZE` bytes)
+/// * `Err(CryptoError)` - If the input exceeds the RFC 8452 length limit
+///
+/// # Security
+/// Encryption is deterministic in `(key, nonce, aad, plaintext)`: repeating a
+/// nonce reveals only that two plaintexts were equal.
+pub fn encrypt_siv(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> CryptoResult<Vec<u8>> {
+    let mut buffer = Vec::with_capacity(plaintext.len() + TAG_SIZE);
+    buffer.extend_from_slice(plaintext);
+
+    let tag = encrypt_in_place_detached(key, nonce, aad, &mut buffer)
+        .map_err(CryptoError::EncryptionFailed)?;
+
+    buffer.extend_from_slice(&tag);
+    Ok(buffer)
+}
+
+/// Decrypt `ciphertext || tag` produced by [`encrypt_siv`]
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - Plaintext
+/// * `Err(CryptoError)` - If the input is truncated or authentication fails
+pub fn decrypt_siv(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> CryptoResult<Vec<u8>> {
+    if ciphertext.len() < TAG_SIZE {
+        return Err(CryptoError::DecryptionFailed(
+            format!("Ciphertext too short: {} bytes, minimum {}", ciphertext.len(), TAG_SIZE)
+        ));
+    }
+
+    let (body, tag_bytes) = ciphertext.split_at(ciphertext.len() - TAG_SIZE);
+    let mut tag = [0u8; TAG_SIZE];
+    tag.copy_from_slice(tag_bytes);
+
+    let mut buffer = body.to_vec();
+    decrypt_in_place_detached(key, nonce, aad, &mut buffer, &tag)?;
+    Ok(buffer)
+}
+
+fn cipher(key: &[u8; KEY_SIZE]) -> Aes256GcmSiv {
+    Aes256GcmSiv::new(GenericArray::from_slice(key))
+}
+
+fn encrypt_in_place_detached(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    aad: &[u8],
+    buffer: &mut [u8],
+) -> Result<[u8; TAG_SIZE], String> {
+    // The crate rejects inputs over the RFC 8452 2^36-byte limit
+    cipher(key)
+        .encrypt_in_place_detached(GenericArray::from_slice(nonce), aad, buffer)
+        .map(Into::into)
+        .map_err(|_| "AES-GCM-SIV input exceeds 2^36 bytes".to_string())
+}
+
+fn decrypt_in_place_detached(
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8; NONCE_SIZE],
+    aad: &[u8],
+    buffer: &mut [u8],
+    tag: &[u8; TAG_SIZE],
+) -> CryptoResult<()> {
+    // On failure the crate re-encrypts the buffer, so no unauthenticated
+    // plaintext is released
+    cipher(key)
+        .decrypt_in_place_detached(GenericArray::from_slice(nonce), aad, buffer, GenericArray::from_slice(tag))
+        .map_err(|_| CryptoError::AuthenticationFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rfc_key() -> [u8; KEY_SIZE] {
+        let mut key = [0u8; KEY_SIZE];
+        key[0] = 0x01;
+        key
+    }
+
+    fn rfc_nonce() -> [u8; NONCE_SIZE] {
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce[0] = 0x03;
+        nonce
+    }
+
+    #[test]
+    fn test_rfc8452_vectors() {
+        // RFC 8452 Appendix C.2, AES-256-GCM-SIV
+        let empty = encrypt_siv(&rfc_key(), &rfc_nonce(), b"", b"").unwrap();
+        assert_eq!(hex::encode(&empty), "07f5f4169bbf55a8400cd47ea6fd400f");
+
+        let pt = hex::decode("0100000000000000").unwrap();
+        let ct = encrypt_siv(&rfc_key(), &rfc_nonce(), b"", &pt).unwrap();
+        assert_eq!(hex::encode(&ct), "c2ef328e5c71c83b843122130f7364b761e0b97427e3df28");
+        assert_eq!(decrypt_siv(&rfc_key(), &rfc_nonce(), b"", &ct).unwrap(), pt);
+    }
+
+    #[test]
+    fn test_roundtrip_and_tamper() {
+        let key = [0x42u8; KEY_SIZE];
+        let nonce = [0x24u8; NONCE_SIZE];
+        let plaintext = b"misuse-resistant message spanning several AES blocks";
+
+        let mut ct = encrypt_siv(&key, &nonce, b"header", plaintext).unwrap();
+        assert_eq!(ct.len(), plaintext.len() + TAG_SIZE);
+        assert_eq!(decrypt_siv(&key, &nonce, b"header", &ct).unwrap(), plaintext);
+
+        assert!(decrypt_siv(&key, &nonce, b"other", &ct).is_err());
+        assert!(decrypt_siv(&key, &nonce, b"header", &ct[..TAG_SIZE - 1]).is_err());
+        ct[0] ^= 0x01;
+        assert!(decrypt_siv(&key, &nonce, b"header", &ct).is_err());
+    }
+
+    #[test]
+    fn test_nonce_reuse_leaks_only_equality() {
+        let key = [0x11u8; KEY_SIZE];
+        let nonce = [0x00u8; NONCE_SIZE];
+
+        // Same nonce, same plaintext: identical output (expected for SIV)
+        let a1 = encrypt_siv(&key, &nonce, b"", b"attack at dawn").unwrap();
+        let a2 = encrypt_siv(&key, &nonce, b"", b"attack at dawn").unwrap();
+        assert_eq!(a1, a2);
+
+        // Same nonce, different plaintext: unrelated keystreams, so the XOR of
+        // ciphertexts is not the XOR of plaintexts as it would be with GCM
+        let b = encrypt_siv(&key, &nonce, b"", b"attack at dusk").unwrap();
+        assert_ne!(a1[TAG_SIZE..], b[TAG_SIZE..]);
+        assert_ne!(a1[..a1.len() - TAG_SIZE], b[..b.len() - TAG_SIZE]);
+
+        let pt_xor: Vec<u8> = b"attack at dawn".iter().zip(b"attack at dusk").map(|(x, y)| x ^ y).collect();
+        let ct_xor: Vec<u8> = a1.iter().zip(&b).take(pt_xor.len()).map(|(x, y)| x ^ y).collect();
+        assert_ne!(ct_xor, pt_xor);
+    }
+}
@@ -0,0 +1,154 @@
+//! BIP39 Mnemonic Encoding
+//!
+//! Encodes 128–256 bits of entropy as a human-transcribable phrase using the
+//! BIP39 English wordlist, for paper backups of key material:
+//!
+//! ```text
+//! checksum = SHA-256(entropy)[..len(entropy) / 32 bits]
+//! words    = 11-bit groups of (entropy || checksum), each indexing the wordlist
+//! ```
+//!
+//! 32 bytes of entropy give 24 words. Only the entropy encoding is
+//! implemented; the BIP39 seed derivation (PBKDF2 with a passphrase) is not,
+//! because B4AE keys are the entropy itself.
+
+use crate::crypto::{CryptoError, CryptoResult};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// BIP39 English wordlist, one word per line (SHA-256 2f5eed53…3b24dbda)
+const WORDLIST: &str = include_str!("bip39_english.txt");
+
+/// Number of words in the wordlist
+const WORD_COUNT: usize = 2048;
+
+pub(crate) fn wordlist() -> Vec<&'static str> {
+    WORDLIST.lines().collect()
+}
+
+/// Encode entropy (16, 20, 24, 28 or 32 bytes) as a BIP39 mnemonic
+///
+/// # Returns
+/// * `Ok(Zeroizing<String>)` - Space-separated words (12–24)
+/// * `Err(CryptoError)` - If the entropy length is not supported
+pub fn entropy_to_mnemonic(entropy: &[u8]) -> CryptoResult<Zeroizing<String>> {
+    if !(16..=32).contains(&entropy.len()) || !entropy.len().is_multiple_of(4) {
+        return Err(CryptoError::InvalidInput(
+            format!("BIP39 entropy must be 16-32 bytes in steps of 4, got {}", entropy.len())
+        ));
+    }
+
+    let words = wordlist();
+    let checksum_bits = entropy.len() / 4;
+    let checksum = Sha256::digest(entropy);
+    let bit = |i: usize| -> usize {
+        let byte = if i < entropy.len() * 8 { entropy[i / 8] } else { checksum[(i / 8) - entropy.len()] };
+        usize::from((byte >> (7 - i % 8)) & 1)
+    };
+
+    let total_bits = entropy.len() * 8 + checksum_bits;
+    let mut phrase = Zeroizing::new(String::new());
+    for group in 0..total_bits / 11 {
+        let index = (0..11).fold(0, |acc, j| (acc << 1) | bit(group * 11 + j));
+        if group > 0 {
+            phrase.push(' ');
+        }
+        phrase.push_str(words[index]);
+    }
+    Ok(phrase)
+}
+
+/// Decode a BIP39 mnemonic back to its entropy
+///
+/// Words are matched case-insensitively and may be separated by any
+/// whitespace.
+///
+/// # Returns
+/// * `Ok(Zeroizing<Vec<u8>>)` - Entropy (16–32 bytes)
+/// * `Err(CryptoError)` - On a wrong word count, unknown word or bad checksum
+pub fn mnemonic_to_entropy(phrase: &str) -> CryptoResult<Zeroizing<Vec<u8>>> {
+    let words = wordlist();
+    let input: Vec<Zeroizing<String>> = phrase
+        .split_whitespace()
+        .map(|word| Zeroizing::new(word.to_lowercase()))
+        .collect();
+    if !(12..=24).contains(&input.len()) || !input.len().is_multiple_of(3) {
+        return Err(CryptoError::InvalidInput(
+            format!("BIP39 mnemonic must have 12-24 words in steps of 3, got {}", input.len())
+        ));
+    }
+
+    let total_bits = input.len() * 11;
+    let checksum_bits = total_bits / 33;
+    let entropy_len = (total_bits - checksum_bits) / 8;
+
+    // Packed entropy || checksum, zero-padded to whole bytes
+    let mut bits = Zeroizing::new(vec![0u8; total_bits.div_ceil(8)]);
+    for (position, word) in input.iter().enumerate() {
+        let index = words
+            .binary_search(&word.as_str())
+            .map_err(|_| CryptoError::InvalidInput(format!("Unknown BIP39 word at position {}", position + 1)))?;
+        debug_assert!(index < WORD_COUNT);
+        for j in 0..11 {
+            if (index >> (10 - j)) & 1 == 1 {
+                let i = position * 11 + j;
+                bits[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+    }
+
+    let entropy = Zeroizing::new(bits[..entropy_len].to_vec());
+    let expected = Sha256::digest(entropy.as_slice())[0] >> (8 - checksum_bits);
+    let actual = bits[entropy_len] >> (8 - checksum_bits);
+    if expected != actual {
+        return Err(CryptoError::InvalidInput("BIP39 mnemonic checksum mismatch".to_string()));
+    }
+    Ok(entropy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wordlist_integrity() {
+        let words = wordlist();
+        assert_eq!(words.len(), WORD_COUNT);
+        assert!(words.windows(2).all(|pair| pair[0] < pair[1]), "wordlist must be sorted");
+        assert_eq!(
+            hex::encode(Sha256::digest(WORDLIST.as_bytes())),
+            "2f5eed53a4727b4bf8880d8f3f199efc90e58503646d9ff8eff3a2ed3b24dbda"
+        );
+    }
+
+    #[test]
+    fn test_reference_vectors() {
+        // Entropy / mnemonic pairs from the BIP39 reference test vectors
+        let vectors = [
+            ("00000000000000000000000000000000", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"),
+            ("7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f", "legal winner thank year wave sausage worth useful legal winner thank yellow"),
+            ("ffffffffffffffffffffffffffffffffffffffffffffffff", "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo when"),
+            (
+                "68a79eaca2324873eacc50cb9c6eca8cc68ea5d936f98787c60c7ebc74e6ce7c",
+                "hamster diagram private dutch cause delay private meat slide toddler razor book happy fancy gospel tennis maple dilemma loan word shrug inflict delay length",
+            ),
+        ];
+        for (entropy_hex, phrase) in vectors {
+            let entropy = hex::decode(entropy_hex).unwrap();
+            assert_eq!(entropy_to_mnemonic(&entropy).unwrap().as_str(), phrase);
+            assert_eq!(mnemonic_to_entropy(phrase).unwrap().as_slice(), entropy.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_invalid_mnemonics_rejected() {
+        // Valid words, wrong checksum (last word changed)
+        assert!(mnemonic_to_entropy("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon").is_err());
+        // Wrong word count
+        assert!(mnemonic_to_entropy("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about").is_err());
+        // Unknown word
+        assert!(mnemonic_to_entropy("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abouts").is_err());
+        // Unsupported entropy length
+        assert!(entropy_to_mnemonic(&[0u8; 15]).is_err());
+    }
+}
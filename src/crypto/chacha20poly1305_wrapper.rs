@@ -1,14 +1,24 @@
 //! ChaCha20-Poly1305 AEAD Wrapper
 //!
 //! Provides a simple interface for ChaCha20-Poly1305 authenticated encryption
-//! with deterministic nonce derivation to prevent nonce reuse vulnerabilities.
+//! with deterministic nonce derivation to prevent nonce reuse vulnerabilities,
+//! plus an XChaCha20-Poly1305 path whose 192-bit random nonces are safe for
+//! long-lived keys.
+//!
+//! Available without `std`; XChaCha20 nonces then come from the caller's RNG
+//! through [`encrypt_xchacha_with_rng`]. The 192-bit nonce space only helps if
+//! that RNG is unpredictable and never repeats.
 
 use crate::crypto::{CryptoResult, CryptoError};
 use chacha20poly1305::{
-    aead::{Aead, KeyInit, Payload},
-    ChaCha20Poly1305, Nonce, Key,
+    aead::{Aead, AeadInPlace, KeyInit, Payload},
+    ChaCha20Poly1305, Nonce, Key, Tag, XChaCha20Poly1305, XNonce,
 };
-use crate::crypto::hkdf::derive_key;
+#[cfg(feature = "std")]
+use crate::crypto::random::SecureRng;
+use alloc::{format, vec::Vec};
+use rand::{CryptoRng, RngCore};
+use crate::crypto::hkdf::{derive_key, key_commitment, verify_key_commitment, KEY_COMMITMENT_SIZE};
 
 /// Encrypt data using ChaCha20-Poly1305 with deterministic nonce
 ///
@@ -43,26 +53,7 @@ pub fn encrypt_chacha20poly1305(
     let mut nonce_bytes = [0u8; 12];
     nonce_bytes.copy_from_slice(&nonce_vec);
 
-    // Create cipher
-    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
-    let nonce = Nonce::from_slice(&nonce_bytes);
-
-    // Prepare payload
-    let payload = Payload {
-        msg: plaintext,
-        aad: aad.unwrap_or(&[]),
-    };
-
-    // Encrypt
-    let ciphertext_with_tag = cipher.encrypt(nonce, payload)
-        .map_err(|e| CryptoError::EncryptionFailed(format!("ChaCha20-Poly1305 encryption failed: {}", e)))?;
-
-    // Split ciphertext and tag
-    let tag_start = ciphertext_with_tag.len().saturating_sub(16);
-    let ciphertext = ciphertext_with_tag[..tag_start].to_vec();
-    let mut tag = [0u8; 16];
-    tag.copy_from_slice(&ciphertext_with_tag[tag_start..]);
-
+    let (ciphertext, tag) = encrypt_detached(key, &nonce_bytes, aad.unwrap_or(&[]), plaintext)?;
     Ok((ciphertext, tag, nonce_bytes))
 }
 
@@ -93,25 +84,234 @@ pub fn decrypt_chacha20poly1305(
     tag: &[u8; 16],
     aad: Option<&[u8]>,
 ) -> CryptoResult<Vec<u8>> {
-    // Create cipher
+    decrypt_detached(key, nonce, aad.unwrap_or(&[]), ciphertext, tag)
+}
+
+/// Encrypt with a caller-supplied nonce, returning the tag separately
+///
+/// # Returns
+/// * `Ok((ciphertext, tag))` - `ciphertext || tag` is the standard combined AEAD output
+/// * `Err(CryptoError)` - If encryption fails
+///
+/// # Security
+/// The nonce must never repeat under the same key; prefer
+/// [`encrypt_chacha20poly1305`], which derives it from a counter.
+pub fn encrypt_detached(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> CryptoResult<(Vec<u8>, [u8; 16])> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+
+    let mut ciphertext = plaintext.to_vec();
+    let tag = cipher.encrypt_in_place_detached(Nonce::from_slice(nonce), aad, &mut ciphertext)
+        .map_err(|e| CryptoError::EncryptionFailed(format!("ChaCha20-Poly1305 encryption failed: {}", e)))?;
+
+    Ok((ciphertext, tag.into()))
+}
+
+/// Decrypt a ciphertext whose tag is stored separately ([`encrypt_detached`])
+pub fn decrypt_detached(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8; 16],
+) -> CryptoResult<Vec<u8>> {
     let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
-    let nonce_obj = Nonce::from_slice(nonce);
 
-    // Reconstruct ciphertext with tag
-    let mut ciphertext_with_tag = ciphertext.to_vec();
-    ciphertext_with_tag.extend_from_slice(tag);
+    let mut plaintext = ciphertext.to_vec();
+    cipher.decrypt_in_place_detached(Nonce::from_slice(nonce), aad, &mut plaintext, Tag::from_slice(tag))
+        .map_err(|_| CryptoError::AuthenticationFailed)?;
+
+    Ok(plaintext)
+}
+
+/// Encrypt with a key-commitment tag prepended
+///
+/// # Returns
+/// * `Ok(data)` - `[commitment(32) || nonce(12) || ciphertext || tag(16)]`
+/// * `Err(CryptoError)` - If encryption fails
+///
+/// # Security
+/// Poly1305 is not key-committing; the commitment tag ensures a ciphertext
+/// only opens under the key it was produced with.
+pub fn encrypt_committing(
+    key: &[u8; 32],
+    counter: u64,
+    plaintext: &[u8],
+    aad: Option<&[u8]>,
+) -> CryptoResult<Vec<u8>> {
+    let commitment = key_commitment(key)?;
+    let (ciphertext, tag, nonce) = encrypt_chacha20poly1305(key, counter, plaintext, aad)?;
+
+    let mut data = Vec::with_capacity(KEY_COMMITMENT_SIZE + 12 + ciphertext.len() + 16);
+    data.extend_from_slice(&commitment);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    data.extend_from_slice(&tag);
+    Ok(data)
+}
+
+/// Decrypt data produced by [`encrypt_committing`]
+///
+/// The commitment is verified in constant time before decryption.
+pub fn decrypt_committing(
+    key: &[u8; 32],
+    data: &[u8],
+    aad: Option<&[u8]>,
+) -> CryptoResult<Vec<u8>> {
+    if data.len() < KEY_COMMITMENT_SIZE + 12 + 16 {
+        return Err(CryptoError::InvalidInput(
+            format!("Committed ciphertext too short: {} bytes", data.len())
+        ));
+    }
+
+    let (commitment, rest) = data.split_at(KEY_COMMITMENT_SIZE);
+    verify_key_commitment(key, commitment)?;
+
+    let (nonce_bytes, rest) = rest.split_at(12);
+    let (ciphertext, tag_bytes) = rest.split_at(rest.len() - 16);
+
+    let mut nonce = [0u8; 12];
+    nonce.copy_from_slice(nonce_bytes);
+    let mut tag = [0u8; 16];
+    tag.copy_from_slice(tag_bytes);
+
+    decrypt_chacha20poly1305(key, &nonce, ciphertext, &tag, aad)
+}
+
+/// Size overhead added by [`seal_single_use`]
+pub const SINGLE_USE_OVERHEAD: usize = 12 + 16;
+
+/// Encrypt under a key that protects exactly one message
+///
+/// # Returns
+/// * `Ok(data)` - `[nonce(12) || ciphertext || tag(16)]`
+/// * `Err(CryptoError)` - If encryption fails
+///
+/// # Security
+/// The nonce is derived from counter 0. That is only safe because `key` is
+/// fresh for this message (a random content key, or one derived from a fresh
+/// KEM exchange or ratchet step) and never encrypts anything else; use
+/// [`encrypt_chacha20poly1305`] with a counter for long-lived keys.
+pub fn seal_single_use(
+    key: &[u8; 32],
+    plaintext: &[u8],
+    aad: Option<&[u8]>,
+) -> CryptoResult<Vec<u8>> {
+    let (ciphertext, tag, nonce) = encrypt_chacha20poly1305(key, 0, plaintext, aad)?;
+
+    let mut data = Vec::with_capacity(SINGLE_USE_OVERHEAD + ciphertext.len());
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    data.extend_from_slice(&tag);
+    Ok(data)
+}
+
+/// Decrypt data produced by [`seal_single_use`]
+pub fn open_single_use(
+    key: &[u8; 32],
+    data: &[u8],
+    aad: Option<&[u8]>,
+) -> CryptoResult<Vec<u8>> {
+    if data.len() < SINGLE_USE_OVERHEAD {
+        return Err(CryptoError::InvalidInput(format!(
+            "Sealed data too short: {} bytes, minimum {}",
+            data.len(),
+            SINGLE_USE_OVERHEAD
+        )));
+    }
+
+    let (nonce_bytes, rest) = data.split_at(12);
+    let (ciphertext, tag_bytes) = rest.split_at(rest.len() - 16);
+
+    let mut nonce = [0u8; 12];
+    nonce.copy_from_slice(nonce_bytes);
+    let mut tag = [0u8; 16];
+    tag.copy_from_slice(tag_bytes);
+
+    decrypt_chacha20poly1305(key, &nonce, ciphertext, &tag, aad)
+}
+
+/// XChaCha20-Poly1305 nonce size in bytes (192 bits)
+pub const XCHACHA_NONCE_SIZE: usize = 24;
+
+/// Encrypt data using XChaCha20-Poly1305 with a random 24-byte nonce
+///
+/// # Arguments
+/// * `key` - 32-byte encryption key
+/// * `plaintext` - Data to encrypt
+/// * `aad` - Optional additional authenticated data
+///
+/// # Returns
+/// * `Ok(data)` - `[nonce(24) || ciphertext || tag(16)]`
+/// * `Err(CryptoError)` - If encryption fails
+///
+/// # Security
+/// With 96-bit random nonces the collision bound limits a key to roughly 2^32
+/// messages. A 192-bit nonce makes random nonces safe for practically
+/// unlimited messages, so this suits long-lived keys such as the STK.
+#[cfg(feature = "std")]
+pub fn encrypt_xchacha(
+    key: &[u8; 32],
+    plaintext: &[u8],
+    aad: Option<&[u8]>,
+) -> CryptoResult<Vec<u8>> {
+    encrypt_xchacha_with_rng(&mut SecureRng::new(), key, plaintext, aad)
+}
 
-    // Prepare payload
+/// [`encrypt_xchacha`] with the nonce drawn from `rng`
+pub fn encrypt_xchacha_with_rng<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    key: &[u8; 32],
+    plaintext: &[u8],
+    aad: Option<&[u8]>,
+) -> CryptoResult<Vec<u8>> {
+    let mut nonce_bytes = [0u8; XCHACHA_NONCE_SIZE];
+    rng.fill_bytes(&mut nonce_bytes);
+
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
     let payload = Payload {
-        msg: &ciphertext_with_tag,
+        msg: plaintext,
         aad: aad.unwrap_or(&[]),
     };
 
-    // Decrypt
-    let plaintext = cipher.decrypt(nonce_obj, payload)
-        .map_err(|_| CryptoError::AuthenticationFailed)?;
+    let ciphertext_with_tag = cipher.encrypt(XNonce::from_slice(&nonce_bytes), payload)
+        .map_err(|e| CryptoError::EncryptionFailed(format!("XChaCha20-Poly1305 encryption failed: {}", e)))?;
 
-    Ok(plaintext)
+    let mut data = Vec::with_capacity(XCHACHA_NONCE_SIZE + ciphertext_with_tag.len());
+    data.extend_from_slice(&nonce_bytes);
+    data.extend_from_slice(&ciphertext_with_tag);
+    Ok(data)
+}
+
+/// Decrypt data produced by [`encrypt_xchacha`]
+///
+/// # Returns
+/// * `Ok(plaintext)` - Decrypted data
+/// * `Err(CryptoError)` - If the input is too short or authentication fails
+pub fn decrypt_xchacha(
+    key: &[u8; 32],
+    data: &[u8],
+    aad: Option<&[u8]>,
+) -> CryptoResult<Vec<u8>> {
+    if data.len() < XCHACHA_NONCE_SIZE + 16 {
+        return Err(CryptoError::InvalidInput(
+            format!("XChaCha20-Poly1305 data too short: {} bytes, minimum {}", data.len(), XCHACHA_NONCE_SIZE + 16)
+        ));
+    }
+
+    let (nonce_bytes, ciphertext_with_tag) = data.split_at(XCHACHA_NONCE_SIZE);
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let payload = Payload {
+        msg: ciphertext_with_tag,
+        aad: aad.unwrap_or(&[]),
+    };
+
+    cipher.decrypt(XNonce::from_slice(nonce_bytes), payload)
+        .map_err(|_| CryptoError::AuthenticationFailed)
 }
 
 #[cfg(test)]
@@ -245,4 +445,90 @@ mod tests {
 
         assert_eq!(plaintext, decrypted.as_slice());
     }
+
+    #[test]
+    fn test_detached_interoperates_with_combined() {
+        let key = [0x42; 32];
+        let nonce = [0x07; 12];
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let payload = Payload { msg: b"detached".as_slice(), aad: b"aad" };
+        let combined = cipher.encrypt(Nonce::from_slice(&nonce), payload).unwrap();
+
+        let (ciphertext, tag) = encrypt_detached(&key, &nonce, b"aad", b"detached").unwrap();
+        assert_eq!([ciphertext.as_slice(), tag.as_slice()].concat(), combined);
+
+        let (ciphertext, tag) = combined.split_at(combined.len() - 16);
+        let tag: [u8; 16] = tag.try_into().unwrap();
+        assert_eq!(decrypt_detached(&key, &nonce, b"aad", ciphertext, &tag).unwrap(), b"detached");
+    }
+
+    #[test]
+    fn test_detached_wrong_tag_fails() {
+        let key = [0x42; 32];
+        let nonce = [0x07; 12];
+        let (ciphertext, mut tag) = encrypt_detached(&key, &nonce, b"", b"payload").unwrap();
+
+        assert!(decrypt_detached(&key, &nonce, b"other", &ciphertext, &tag).is_err());
+        tag[15] ^= 1;
+        assert!(matches!(
+            decrypt_detached(&key, &nonce, b"", &ciphertext, &tag),
+            Err(CryptoError::AuthenticationFailed)
+        ));
+    }
+
+    #[test]
+    fn test_single_use_roundtrip() {
+        let key = [0x24u8; 32];
+        let data = seal_single_use(&key, b"once", Some(b"aad")).unwrap();
+        assert_eq!(data.len(), SINGLE_USE_OVERHEAD + 4);
+        assert_eq!(open_single_use(&key, &data, Some(b"aad")).unwrap(), b"once");
+
+        assert!(open_single_use(&key, &data, None).is_err());
+        assert!(open_single_use(&[0x25u8; 32], &data, Some(b"aad")).is_err());
+        assert!(matches!(
+            open_single_use(&key, &data[..SINGLE_USE_OVERHEAD - 1], Some(b"aad")),
+            Err(CryptoError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_committing_roundtrip_and_wrong_key() {
+        let key = [0x42; 32];
+        let data = encrypt_committing(&key, 3, b"committed", Some(b"aad")).unwrap();
+
+        assert_eq!(decrypt_committing(&key, &data, Some(b"aad")).unwrap(), b"committed");
+        assert!(decrypt_committing(&[0x43; 32], &data, Some(b"aad")).is_err());
+        assert!(decrypt_committing(&key, &data[..KEY_COMMITMENT_SIZE + 27], Some(b"aad")).is_err());
+    }
+
+    #[test]
+    fn test_xchacha_roundtrip() {
+        let key = [0x42; 32];
+        let data = encrypt_xchacha(&key, b"long-lived archive", Some(b"aad")).unwrap();
+        assert_eq!(data.len(), XCHACHA_NONCE_SIZE + 18 + 16);
+        assert_eq!(decrypt_xchacha(&key, &data, Some(b"aad")).unwrap(), b"long-lived archive");
+
+        assert!(decrypt_xchacha(&key, &data, Some(b"other")).is_err());
+        assert!(decrypt_xchacha(&[0x43; 32], &data, Some(b"aad")).is_err());
+        assert_ne!(data, encrypt_xchacha(&key, b"long-lived archive", Some(b"aad")).unwrap());
+    }
+
+    #[test]
+    fn test_xchacha_rejects_12_byte_nonce() {
+        let key = [0x42; 32];
+        let (ciphertext, tag, nonce) = encrypt_chacha20poly1305(&key, 0, b"", None).unwrap();
+
+        // [nonce(12) || tag(16)] is shorter than the XChaCha minimum
+        let mut short = nonce.to_vec();
+        short.extend_from_slice(&ciphertext);
+        short.extend_from_slice(&tag);
+        assert!(matches!(decrypt_xchacha(&key, &short, None), Err(CryptoError::InvalidInput(_))));
+
+        // A 12-byte-nonce frame that happens to be long enough must still fail auth
+        let (ciphertext, tag, nonce) = encrypt_chacha20poly1305(&key, 0, b"twelve-byte nonce", None).unwrap();
+        let mut framed = nonce.to_vec();
+        framed.extend_from_slice(&ciphertext);
+        framed.extend_from_slice(&tag);
+        assert!(decrypt_xchacha(&key, &framed, None).is_err());
+    }
 }
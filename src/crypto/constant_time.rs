@@ -3,7 +3,9 @@
 // Constant-time memory operations to prevent timing side-channel attacks.
 // All operations in this module execute in time independent of input values.
 
-use subtle::{Choice, ConstantTimeEq};
+use crate::crypto::{CryptoError, CryptoResult};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use alloc::format;
 
 /// Constant-time memory operations for side-channel resistance.
 ///
@@ -109,6 +111,57 @@ impl ConstantTimeMemory {
             dst[i] = src[i];
         }
     }
+
+    /// Select between two byte slices in constant time.
+    ///
+    /// Writes `a` into `out` when `choice` is set and `b` otherwise, without
+    /// branching on `choice`.
+    ///
+    /// # Arguments
+    ///
+    /// * `choice` - Selector; `Choice::from(1)` picks `a`
+    /// * `a` - Bytes copied when `choice` is set
+    /// * `b` - Bytes copied when `choice` is not set
+    /// * `out` - Destination, same length as `a` and `b`
+    ///
+    /// # Errors
+    ///
+    /// `CryptoError::InvalidInput` if the three lengths differ. Lengths are
+    /// treated as public; only `choice` and the contents are protected.
+    ///
+    /// # Security
+    ///
+    /// - Every byte of both inputs is read and every byte of `out` written
+    /// - Uses `subtle::ConditionallySelectable`, which hides `choice` from
+    ///   the optimizer
+    /// - Timing independence should be checked dudect-style on the target
+    ///   (time many calls with `choice` fixed vs. random and apply Welch's
+    ///   t-test); unit tests only check correctness
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use b4ae::crypto::constant_time::ConstantTimeMemory;
+    /// use subtle::Choice;
+    ///
+    /// let mut out = [0u8; 4];
+    /// ConstantTimeMemory::ct_select(Choice::from(1), &[1; 4], &[2; 4], &mut out).unwrap();
+    /// assert_eq!(out, [1; 4]);
+    /// ```
+    pub fn ct_select(choice: Choice, a: &[u8], b: &[u8], out: &mut [u8]) -> CryptoResult<()> {
+        if a.len() != b.len() || a.len() != out.len() {
+            return Err(CryptoError::InvalidInput(format!(
+                "ct_select length mismatch: a={}, b={}, out={}",
+                a.len(), b.len(), out.len()
+            )));
+        }
+
+        for ((dst, x), y) in out.iter_mut().zip(a).zip(b) {
+            // conditional_select returns its second argument when choice is set
+            *dst = u8::conditional_select(y, x, choice);
+        }
+        Ok(())
+    }
 }
 
 /// Cache-timing resistant operations for side-channel resistance.
@@ -342,6 +395,29 @@ impl ConstantTimeArithmetic {
         // Use constant-time equality check from subtle crate
         x.ct_eq(&0)
     }
+
+    /// Select between two u64 values in constant time.
+    ///
+    /// Returns `a` when `choice` is set and `b` otherwise, without branching
+    /// on `choice`.
+    ///
+    /// # Security
+    ///
+    /// - No secret-dependent branching
+    /// - Uses `subtle::ConditionallySelectable`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use b4ae::crypto::constant_time::ConstantTimeArithmetic;
+    /// use subtle::Choice;
+    ///
+    /// assert_eq!(ConstantTimeArithmetic::ct_select_u64(Choice::from(1), 7, 9), 7);
+    /// assert_eq!(ConstantTimeArithmetic::ct_select_u64(Choice::from(0), 7, 9), 9);
+    /// ```
+    pub fn ct_select_u64(choice: Choice, a: u64, b: u64) -> u64 {
+        u64::conditional_select(&b, &a, choice)
+    }
 }
 
 #[cfg(test)]
@@ -472,6 +548,38 @@ mod tests {
         assert_eq!(backup, secret_key, "Secret key copy should be exact");
     }
 
+    #[test]
+    fn test_ct_select_both_branches() {
+        let a = [0xAA; 32];
+        let b = [0x55; 32];
+        let mut out = [0u8; 32];
+
+        ConstantTimeMemory::ct_select(Choice::from(1), &a, &b, &mut out).unwrap();
+        assert_eq!(out, a);
+
+        ConstantTimeMemory::ct_select(Choice::from(0), &a, &b, &mut out).unwrap();
+        assert_eq!(out, b);
+    }
+
+    #[test]
+    fn test_ct_select_empty() {
+        let mut out = [0u8; 0];
+        assert!(ConstantTimeMemory::ct_select(Choice::from(1), &[], &[], &mut out).is_ok());
+    }
+
+    #[test]
+    fn test_ct_select_length_mismatch() {
+        let mut out = [0u8; 4];
+        assert!(matches!(
+            ConstantTimeMemory::ct_select(Choice::from(1), &[1; 4], &[2; 3], &mut out),
+            Err(CryptoError::InvalidInput(_))
+        ));
+
+        let mut short = [0u8; 3];
+        assert!(ConstantTimeMemory::ct_select(Choice::from(0), &[1; 4], &[2; 4], &mut short).is_err());
+        assert_eq!(short, [0u8; 3], "Output must be untouched on error");
+    }
+
     #[test]
     fn test_ct_memcmp_all_zeros() {
         let a = [0u8; 32];
@@ -808,6 +916,13 @@ mod tests {
         assert!(bool::from(is_zero), "Product with zero should be zero");
     }
 
+    #[test]
+    fn test_ct_select_u64_both_branches() {
+        assert_eq!(ConstantTimeArithmetic::ct_select_u64(Choice::from(1), 42, u64::MAX), 42);
+        assert_eq!(ConstantTimeArithmetic::ct_select_u64(Choice::from(0), 42, u64::MAX), u64::MAX);
+        assert_eq!(ConstantTimeArithmetic::ct_select_u64(Choice::from(1), 0, 0), 0);
+    }
+
     #[test]
     fn test_ct_add_commutative() {
         // Test commutativity: a + b = b + a
@@ -1,4 +1,4 @@
-// B4AE Dilithium5 Implementation
+// B4AE Dilithium Implementation (Dilithium5 default, 2/3 via DilithiumVariant)
 // Post-Quantum Digital Signature Scheme
 
 use crate::crypto::{CryptoError, CryptoResult};
@@ -88,14 +88,22 @@ impl DilithiumPublicKey {
 }
 
 impl DilithiumSecretKey {
-    /// Size in bytes.
+    /// Size in bytes (nominal Dilithium5).
     pub const SIZE: usize = 4864;
 
+    /// Size in bytes of keys produced by the active backend
+    /// (ML-DSA-87 secret keys are 4896 bytes).
+    #[cfg(feature = "pqcrypto-mldsa")]
+    pub const BACKEND_SIZE: usize = 4896;
+    /// Size in bytes of keys produced by the active backend.
+    #[cfg(not(feature = "pqcrypto-mldsa"))]
+    pub const BACKEND_SIZE: usize = Self::SIZE;
+
     /// Parse from raw bytes.
     pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
-        if bytes.len() != Self::SIZE {
+        if bytes.len() != Self::BACKEND_SIZE {
             return Err(CryptoError::InvalidKeySize(
-                format!("Expected {} bytes, got {}", Self::SIZE, bytes.len())
+                format!("Expected {} bytes, got {}", Self::BACKEND_SIZE, bytes.len())
             ));
         }
         
@@ -188,6 +196,7 @@ pub struct DilithiumKeyPair {
 
 /// Generate Dilithium5 key pair
 pub fn keypair() -> CryptoResult<DilithiumKeyPair> {
+    super::self_test::ensure_operational()?;
     #[cfg(feature = "liboqs")]
     {
         use oqs::sig::{Sig, Algorithm};
@@ -230,6 +239,7 @@ pub fn keypair() -> CryptoResult<DilithiumKeyPair> {
 
 /// Sign a message with Dilithium5
 pub fn sign(secret_key: &DilithiumSecretKey, message: &[u8]) -> CryptoResult<DilithiumSignature> {
+    super::self_test::ensure_operational()?;
     #[cfg(feature = "liboqs")]
     {
         use oqs::sig::{Sig, Algorithm, SecretKey};
@@ -270,6 +280,7 @@ pub fn verify(
     message: &[u8],
     signature: &DilithiumSignature,
 ) -> CryptoResult<bool> {
+    super::self_test::ensure_operational()?;
     #[cfg(feature = "liboqs")]
     {
         use oqs::sig::{Sig, Algorithm, PublicKey, Signature};
@@ -303,6 +314,359 @@ pub fn verify(
     ))
 }
 
+/// Verify an ML-DSA-87 signature made with a FIPS 204 context string
+///
+/// [`verify`] uses the empty context; this is for the NIST vectors run by
+/// [`self_test`](super::self_test), which carry one.
+#[cfg(all(not(feature = "liboqs"), feature = "pqcrypto-mldsa"))]
+pub(crate) fn verify_with_context(
+    public_key: &DilithiumPublicKey,
+    message: &[u8],
+    context: &[u8],
+    signature: &DilithiumSignature,
+) -> CryptoResult<bool> {
+    super::self_test::ensure_operational()?;
+    Ok(mldsa87::verify_detached_signature_ctx(&signature.inner, message, context, &public_key.inner).is_ok())
+}
+
+/// Dilithium/ML-DSA parameter set
+///
+/// The fixed-size types above are Dilithium5; the `*_with` functions accept
+/// any variant and carry it alongside the key material. Sizes follow the
+/// active backend (ML-DSA with `pqcrypto-mldsa`, round-3 Dilithium otherwise).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DilithiumVariant {
+    /// Dilithium2 / ML-DSA-44 (NIST Level 2)
+    Dilithium2,
+    /// Dilithium3 / ML-DSA-65 (NIST Level 3)
+    Dilithium3,
+    /// Dilithium5 / ML-DSA-87 (NIST Level 5)
+    #[default]
+    Dilithium5,
+}
+
+impl DilithiumVariant {
+    /// Public key size in bytes.
+    pub const fn public_key_size(self) -> usize {
+        match self {
+            DilithiumVariant::Dilithium2 => 1312,
+            DilithiumVariant::Dilithium3 => 1952,
+            DilithiumVariant::Dilithium5 => 2592,
+        }
+    }
+
+    /// Secret key size in bytes.
+    #[cfg(feature = "pqcrypto-mldsa")]
+    pub const fn secret_key_size(self) -> usize {
+        match self {
+            DilithiumVariant::Dilithium2 => 2560,
+            DilithiumVariant::Dilithium3 => 4032,
+            DilithiumVariant::Dilithium5 => 4896,
+        }
+    }
+
+    /// Secret key size in bytes.
+    #[cfg(not(feature = "pqcrypto-mldsa"))]
+    pub const fn secret_key_size(self) -> usize {
+        match self {
+            DilithiumVariant::Dilithium2 => 2528,
+            DilithiumVariant::Dilithium3 => 4000,
+            DilithiumVariant::Dilithium5 => 4864,
+        }
+    }
+
+    /// Detached signature size in bytes.
+    #[cfg(feature = "pqcrypto-mldsa")]
+    pub const fn signature_size(self) -> usize {
+        match self {
+            DilithiumVariant::Dilithium2 => 2420,
+            DilithiumVariant::Dilithium3 => 3309,
+            DilithiumVariant::Dilithium5 => 4627,
+        }
+    }
+
+    /// Detached signature size in bytes.
+    #[cfg(not(feature = "pqcrypto-mldsa"))]
+    pub const fn signature_size(self) -> usize {
+        match self {
+            DilithiumVariant::Dilithium2 => 2420,
+            DilithiumVariant::Dilithium3 => 3293,
+            DilithiumVariant::Dilithium5 => 4595,
+        }
+    }
+
+    #[cfg(feature = "liboqs")]
+    fn oqs_algorithm(self) -> oqs::sig::Algorithm {
+        match self {
+            DilithiumVariant::Dilithium2 => oqs::sig::Algorithm::Dilithium2,
+            DilithiumVariant::Dilithium3 => oqs::sig::Algorithm::Dilithium3,
+            DilithiumVariant::Dilithium5 => oqs::sig::Algorithm::Dilithium5,
+        }
+    }
+}
+
+/// Public key for a specific Dilithium variant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantPublicKey {
+    variant: DilithiumVariant,
+    bytes: Vec<u8>,
+}
+
+/// Secret key for a specific Dilithium variant (zeroized on drop)
+pub struct VariantSecretKey {
+    variant: DilithiumVariant,
+    bytes: zeroize::Zeroizing<Vec<u8>>,
+}
+
+/// Detached signature for a specific Dilithium variant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantSignature {
+    variant: DilithiumVariant,
+    bytes: Vec<u8>,
+}
+
+/// Key pair for a specific Dilithium variant
+pub struct VariantKeyPair {
+    /// Public key for verification.
+    pub public_key: VariantPublicKey,
+    /// Secret key for signing.
+    pub secret_key: VariantSecretKey,
+}
+
+fn check_variant_size(what: &str, expected: usize, bytes: &[u8]) -> CryptoResult<()> {
+    if bytes.len() != expected {
+        return Err(CryptoError::InvalidKeySize(
+            format!("{}: expected {} bytes, got {}", what, expected, bytes.len())
+        ));
+    }
+    Ok(())
+}
+
+impl VariantPublicKey {
+    /// Parse from raw bytes for the given variant.
+    pub fn from_bytes(variant: DilithiumVariant, bytes: &[u8]) -> CryptoResult<Self> {
+        check_variant_size("Dilithium public key", variant.public_key_size(), bytes)?;
+        Ok(VariantPublicKey { variant, bytes: bytes.to_vec() })
+    }
+
+    /// Parameter set of this key.
+    pub fn variant(&self) -> DilithiumVariant {
+        self.variant
+    }
+
+    /// Serialisasi ke bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl VariantSecretKey {
+    /// Parse from raw bytes for the given variant.
+    pub fn from_bytes(variant: DilithiumVariant, bytes: &[u8]) -> CryptoResult<Self> {
+        check_variant_size("Dilithium secret key", variant.secret_key_size(), bytes)?;
+        Ok(VariantSecretKey { variant, bytes: zeroize::Zeroizing::new(bytes.to_vec()) })
+    }
+
+    /// Parameter set of this key.
+    pub fn variant(&self) -> DilithiumVariant {
+        self.variant
+    }
+
+    /// Serialisasi ke bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl VariantSignature {
+    /// Parse from raw bytes for the given variant.
+    pub fn from_bytes(variant: DilithiumVariant, bytes: &[u8]) -> CryptoResult<Self> {
+        if bytes.len() != variant.signature_size() {
+            return Err(CryptoError::InvalidInput(format!(
+                "Dilithium signature: expected {} bytes, got {}",
+                variant.signature_size(),
+                bytes.len()
+            )));
+        }
+        Ok(VariantSignature { variant, bytes: bytes.to_vec() })
+    }
+
+    /// Parameter set that produced this signature.
+    pub fn variant(&self) -> DilithiumVariant {
+        self.variant
+    }
+
+    /// Serialisasi ke bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Debug for VariantSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VariantSecretKey({:?}, [REDACTED])", self.variant)
+    }
+}
+
+/// Run `$body` with `$sig` bound to the pqcrypto module for `$variant`
+#[cfg(all(not(feature = "liboqs"), feature = "pqcrypto-mldsa"))]
+macro_rules! with_sig_module {
+    ($variant:expr, $sig:ident => $body:block) => {
+        match $variant {
+            DilithiumVariant::Dilithium2 => { use pqcrypto_mldsa::mldsa44 as $sig; $body }
+            DilithiumVariant::Dilithium3 => { use pqcrypto_mldsa::mldsa65 as $sig; $body }
+            DilithiumVariant::Dilithium5 => { use pqcrypto_mldsa::mldsa87 as $sig; $body }
+        }
+    };
+}
+
+/// Run `$body` with `$sig` bound to the pqcrypto module for `$variant`
+#[cfg(all(not(feature = "liboqs"), any(feature = "pqcrypto-dilithium", feature = "pqcrypto-alt"), not(feature = "pqcrypto-mldsa")))]
+macro_rules! with_sig_module {
+    ($variant:expr, $sig:ident => $body:block) => {
+        match $variant {
+            DilithiumVariant::Dilithium2 => { use pqcrypto_dilithium::dilithium2 as $sig; $body }
+            DilithiumVariant::Dilithium3 => { use pqcrypto_dilithium::dilithium3 as $sig; $body }
+            DilithiumVariant::Dilithium5 => { use pqcrypto_dilithium::dilithium5 as $sig; $body }
+        }
+    };
+}
+
+/// Generate a key pair for the given Dilithium variant
+pub fn keypair_with(variant: DilithiumVariant) -> CryptoResult<VariantKeyPair> {
+    super::self_test::ensure_operational()?;
+    #[cfg(feature = "liboqs")]
+    {
+        use oqs::sig::Sig;
+
+        let sig = Sig::new(variant.oqs_algorithm())
+            .map_err(|e| CryptoError::KeyGenerationFailed(e.to_string()))?;
+
+        let (pk, sk) = sig.keypair()
+            .map_err(|e| CryptoError::KeyGenerationFailed(e.to_string()))?;
+
+        Ok(VariantKeyPair {
+            public_key: VariantPublicKey::from_bytes(variant, pk.as_ref())?,
+            secret_key: VariantSecretKey::from_bytes(variant, sk.as_ref())?,
+        })
+    }
+
+    #[cfg(all(not(feature = "liboqs"), any(feature = "pqcrypto-mldsa", feature = "pqcrypto-dilithium", feature = "pqcrypto-alt")))]
+    {
+        use pqcrypto_traits::sign::{PublicKey, SecretKey};
+
+        with_sig_module!(variant, sig => {
+            let (pk, sk) = sig::keypair();
+            Ok(VariantKeyPair {
+                public_key: VariantPublicKey::from_bytes(variant, pk.as_bytes())?,
+                secret_key: VariantSecretKey::from_bytes(variant, sk.as_bytes())?,
+            })
+        })
+    }
+
+    #[cfg(not(any(feature = "liboqs", feature = "pqcrypto-mldsa", feature = "pqcrypto-dilithium", feature = "pqcrypto-alt")))]
+    {
+        let _ = variant;
+        Err(CryptoError::KeyGenerationFailed(
+            "Tidak ada implementasi DSA yang tersedia".to_string()
+        ))
+    }
+}
+
+/// Sign a message with a secret key of any Dilithium variant
+pub fn sign_with(secret_key: &VariantSecretKey, message: &[u8]) -> CryptoResult<VariantSignature> {
+    super::self_test::ensure_operational()?;
+    let variant = secret_key.variant;
+
+    #[cfg(feature = "liboqs")]
+    {
+        use oqs::sig::Sig;
+
+        let sig = Sig::new(variant.oqs_algorithm())
+            .map_err(|e| CryptoError::SignatureFailed(e.to_string()))?;
+
+        let sk = sig.secret_key_from_bytes(secret_key.as_bytes())
+            .ok_or_else(|| CryptoError::SignatureFailed("Invalid Dilithium secret key".to_string()))?;
+
+        let signature = sig.sign(message, sk)
+            .map_err(|e| CryptoError::SignatureFailed(e.to_string()))?;
+
+        VariantSignature::from_bytes(variant, signature.as_ref())
+    }
+
+    #[cfg(all(not(feature = "liboqs"), any(feature = "pqcrypto-mldsa", feature = "pqcrypto-dilithium", feature = "pqcrypto-alt")))]
+    {
+        use pqcrypto_traits::sign::{DetachedSignature, SecretKey};
+
+        with_sig_module!(variant, sig => {
+            let sk = sig::SecretKey::from_bytes(secret_key.as_bytes())
+                .map_err(|_| CryptoError::InvalidKeySize("Invalid Dilithium secret key".to_string()))?;
+            let signature = sig::detached_sign(message, &sk);
+            VariantSignature::from_bytes(variant, signature.as_bytes())
+        })
+    }
+
+    #[cfg(not(any(feature = "liboqs", feature = "pqcrypto-mldsa", feature = "pqcrypto-dilithium", feature = "pqcrypto-alt")))]
+    {
+        let _ = (variant, message);
+        Err(CryptoError::SignatureFailed(
+            "Tidak ada implementasi DSA yang tersedia".to_string()
+        ))
+    }
+}
+
+/// Verify a signature of any Dilithium variant
+///
+/// Returns `Ok(false)` when the signature was produced by a different
+/// variant than the public key.
+pub fn verify_with(
+    public_key: &VariantPublicKey,
+    message: &[u8],
+    signature: &VariantSignature,
+) -> CryptoResult<bool> {
+    super::self_test::ensure_operational()?;
+    let variant = public_key.variant;
+    if signature.variant != variant {
+        return Ok(false);
+    }
+
+    #[cfg(feature = "liboqs")]
+    {
+        use oqs::sig::Sig;
+
+        let sig = Sig::new(variant.oqs_algorithm())
+            .map_err(|e| CryptoError::VerificationFailed(e.to_string()))?;
+
+        let pk = sig.public_key_from_bytes(public_key.as_bytes())
+            .ok_or_else(|| CryptoError::VerificationFailed("Invalid Dilithium public key".to_string()))?;
+        let signature = sig.signature_from_bytes(signature.as_bytes())
+            .ok_or_else(|| CryptoError::VerificationFailed("Invalid Dilithium signature".to_string()))?;
+
+        Ok(sig.verify(message, signature, pk).is_ok())
+    }
+
+    #[cfg(all(not(feature = "liboqs"), any(feature = "pqcrypto-mldsa", feature = "pqcrypto-dilithium", feature = "pqcrypto-alt")))]
+    {
+        use pqcrypto_traits::sign::{DetachedSignature, PublicKey};
+
+        with_sig_module!(variant, sig => {
+            let pk = sig::PublicKey::from_bytes(public_key.as_bytes())
+                .map_err(|_| CryptoError::InvalidKeySize("Invalid Dilithium public key".to_string()))?;
+            let signature = sig::DetachedSignature::from_bytes(signature.as_bytes())
+                .map_err(|_| CryptoError::InvalidInput("Invalid Dilithium signature".to_string()))?;
+            Ok(sig::verify_detached_signature(&signature, message, &pk).is_ok())
+        })
+    }
+
+    #[cfg(not(any(feature = "liboqs", feature = "pqcrypto-mldsa", feature = "pqcrypto-dilithium", feature = "pqcrypto-alt")))]
+    {
+        let _ = message;
+        Err(CryptoError::VerificationFailed(
+            "Tidak ada implementasi DSA yang tersedia".to_string()
+        ))
+    }
+}
+
 impl fmt::Debug for DilithiumPublicKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         #[cfg(any(feature = "pqcrypto-mldsa", feature = "pqcrypto-dilithium", feature = "pqcrypto-alt"))]
@@ -356,6 +720,16 @@ mod tests {
         assert_eq!(DilithiumSignature::SIZE, 4627); // pqcrypto-dilithium5 detached signature
     }
 
+    #[test]
+    fn test_secret_key_from_bytes_roundtrip() {
+        let keypair = keypair().expect("Failed to generate keypair");
+        let bytes = keypair.secret_key.as_bytes();
+        assert_eq!(bytes.len(), DilithiumSecretKey::BACKEND_SIZE);
+
+        let restored = DilithiumSecretKey::from_bytes(bytes).expect("Failed to parse secret key");
+        assert_eq!(restored.as_bytes(), bytes);
+    }
+
     #[test]
     #[cfg(feature = "liboqs")]
     fn test_dilithium_keypair() {
@@ -387,4 +761,33 @@ mod tests {
         
         assert!(!invalid);
     }
+
+    #[test]
+    fn test_variant_roundtrip() {
+        let message = b"B4AE Mode B transcript";
+        for variant in [DilithiumVariant::Dilithium2, DilithiumVariant::Dilithium3, DilithiumVariant::Dilithium5] {
+            let keypair = keypair_with(variant).expect("Failed to generate keypair");
+            assert_eq!(keypair.public_key.as_bytes().len(), variant.public_key_size());
+            assert_eq!(keypair.secret_key.as_bytes().len(), variant.secret_key_size());
+
+            let signature = sign_with(&keypair.secret_key, message).expect("Failed to sign");
+            assert_eq!(signature.as_bytes().len(), variant.signature_size());
+            assert!(verify_with(&keypair.public_key, message, &signature).unwrap());
+            assert!(!verify_with(&keypair.public_key, b"other message", &signature).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_variant_mismatch_fails() {
+        let kp3 = keypair_with(DilithiumVariant::Dilithium3).unwrap();
+        let kp5 = keypair_with(DilithiumVariant::Dilithium5).unwrap();
+        let message = b"signed with Dilithium3";
+
+        let sig3 = sign_with(&kp3.secret_key, message).unwrap();
+        assert!(!verify_with(&kp5.public_key, message, &sig3).unwrap());
+
+        // Re-labelling the raw bytes as another variant is rejected on size
+        assert!(VariantSignature::from_bytes(DilithiumVariant::Dilithium5, sig3.as_bytes()).is_err());
+        assert!(VariantPublicKey::from_bytes(DilithiumVariant::Dilithium2, kp3.public_key.as_bytes()).is_err());
+    }
 }
@@ -21,6 +21,18 @@ pub struct MessageKey {
     pub counter: u64,
 }
 
+/// Which skipped message key to drop when the cache is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheEvictionPolicy {
+    /// Evict the least recently inserted or accessed key
+    #[default]
+    Lru,
+    /// Evict the key with the lowest message counter
+    LowestCounter,
+    /// Evict the key that was cached first
+    Fifo,
+}
+
 /// Chain Key Ratchet
 ///
 /// Manages symmetric key chain ratcheting for per-message key derivation.
@@ -29,6 +41,10 @@ pub struct ChainKeyRatchet {
     message_counter: u64,
     key_cache: HashMap<u64, MessageKey>,
     cache_size_limit: usize,
+    eviction_policy: CacheEvictionPolicy,
+    /// Insertion or last-access tick of each cached key (by counter)
+    cache_stamps: HashMap<u64, u64>,
+    next_stamp: u64,
 }
 
 impl ChainKeyRatchet {
@@ -42,6 +58,9 @@ impl ChainKeyRatchet {
             message_counter: 0,
             key_cache: HashMap::new(),
             cache_size_limit: super::DEFAULT_CACHE_SIZE,
+            eviction_policy: CacheEvictionPolicy::default(),
+            cache_stamps: HashMap::new(),
+            next_stamp: 0,
         }
     }
 
@@ -52,9 +71,23 @@ impl ChainKeyRatchet {
             message_counter: 0,
             key_cache: HashMap::new(),
             cache_size_limit: cache_size,
+            eviction_policy: CacheEvictionPolicy::default(),
+            cache_stamps: HashMap::new(),
+            next_stamp: 0,
         }
     }
 
+    /// Set the eviction policy used when the skipped-key cache is full
+    pub fn with_eviction_policy(mut self, policy: CacheEvictionPolicy) -> Self {
+        self.eviction_policy = policy;
+        self
+    }
+
+    /// Current cache eviction policy
+    pub fn eviction_policy(&self) -> CacheEvictionPolicy {
+        self.eviction_policy
+    }
+
     /// Derive next message key and advance chain
     ///
     /// Derives a unique message key for the current counter, then advances
@@ -105,7 +138,11 @@ impl ChainKeyRatchet {
 
     /// Get message key for specific counter (out-of-order delivery)
     ///
-    /// If the counter is in the cache, returns the cached key.
+    /// If the counter is in the cache, returns a copy of the cached key; it
+    /// stays cached (and under [`CacheEvictionPolicy::Lru`] becomes the most
+    /// recently used) until [`consume_message_key`](Self::consume_message_key)
+    /// is called once its message authenticates, so a forged message cannot
+    /// burn a skipped key.
     /// If the counter is ahead, derives and caches all intermediate keys up to MAX_SKIP.
     ///
     /// # Arguments
@@ -117,7 +154,11 @@ impl ChainKeyRatchet {
     /// * `Err(CryptoError)` - If counter skip exceeds MAX_SKIP or derivation fails
     pub fn get_message_key(&mut self, counter: u64) -> CryptoResult<Option<MessageKey>> {
         // Check if key is in cache
-        if let Some(key) = self.key_cache.remove(&counter) {
+        if let Some(key) = self.key_cache.get(&counter).cloned() {
+            if self.eviction_policy == CacheEvictionPolicy::Lru {
+                let stamp = self.next_stamp();
+                self.cache_stamps.insert(counter, stamp);
+            }
             return Ok(Some(key));
         }
 
@@ -145,22 +186,84 @@ impl ChainKeyRatchet {
         Ok(Some(key))
     }
 
+    /// Skip the chain up to `until` and drain every skipped key
+    ///
+    /// Used before a DH ratchet step resets the chain, so messages of the old
+    /// chain that are still in flight can be decrypted afterwards. The skip is
+    /// bounded by MAX_SKIP and the result by the cache size, as with
+    /// [`get_message_key`](Self::get_message_key).
+    ///
+    /// # Returns
+    /// * `Ok(Vec<MessageKey>)` - Skipped keys ordered by counter
+    /// * `Err(CryptoError)` - If the skip exceeds MAX_SKIP or derivation fails
+    pub fn take_skipped_keys(&mut self, until: u64) -> CryptoResult<Vec<MessageKey>> {
+        let skip = until.saturating_sub(self.message_counter);
+        if skip > MAX_SKIP {
+            return Err(CryptoError::InvalidInput(
+                format!("Counter skip too large - potential DoS (skip: {}, max: {})", skip, MAX_SKIP)
+            ));
+        }
+
+        while self.message_counter < until {
+            let key = self.next_message_key()?;
+            self.cache_key(key);
+        }
+
+        self.cache_stamps.clear();
+        let mut keys: Vec<MessageKey> = self.key_cache.drain().map(|(_, key)| key).collect();
+        keys.sort_by_key(|key| key.counter);
+        Ok(keys)
+    }
+
+    /// Drop and zeroize the cached key for `counter`, if any
+    ///
+    /// Called once the message for `counter` authenticated, so the key cannot
+    /// be used again.
+    pub fn consume_message_key(&mut self, counter: u64) {
+        if let Some(mut key) = self.key_cache.remove(&counter) {
+            key.encryption_key.zeroize();
+            key.auth_key.zeroize();
+        }
+        self.cache_stamps.remove(&counter);
+    }
+
     /// Cache a message key for out-of-order delivery
     fn cache_key(&mut self, key: MessageKey) {
         // Enforce cache size limit
         if self.key_cache.len() >= self.cache_size_limit {
-            // Remove oldest key (lowest counter)
-            if let Some(&oldest_counter) = self.key_cache.keys().min() {
-                if let Some(mut old_key) = self.key_cache.remove(&oldest_counter) {
+            if let Some(victim) = self.eviction_candidate() {
+                if let Some(mut old_key) = self.key_cache.remove(&victim) {
                     old_key.encryption_key.zeroize();
                     old_key.auth_key.zeroize();
                 }
+                self.cache_stamps.remove(&victim);
             }
         }
 
+        let stamp = self.next_stamp();
+        self.cache_stamps.insert(key.counter, stamp);
         self.key_cache.insert(key.counter, key);
     }
 
+    /// Counter of the cached key the eviction policy drops next
+    fn eviction_candidate(&self) -> Option<u64> {
+        match self.eviction_policy {
+            CacheEvictionPolicy::LowestCounter => self.key_cache.keys().min().copied(),
+            // Fifo stamps are only set on insertion, Lru stamps also on access
+            CacheEvictionPolicy::Lru | CacheEvictionPolicy::Fifo => self
+                .cache_stamps
+                .iter()
+                .min_by_key(|&(_, &stamp)| stamp)
+                .map(|(&counter, _)| counter),
+        }
+    }
+
+    fn next_stamp(&mut self) -> u64 {
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        stamp
+    }
+
     /// Reset chain with new key (after DH ratchet)
     ///
     /// Securely zeroizes the old chain key and all cached keys,
@@ -177,6 +280,7 @@ impl ChainKeyRatchet {
             key.encryption_key.zeroize();
             key.auth_key.zeroize();
         }
+        self.cache_stamps.clear();
 
         // Reset with new chain key
         self.chain_key = new_chain_key;
@@ -201,6 +305,7 @@ impl ChainKeyRatchet {
                 key.encryption_key.zeroize();
                 key.auth_key.zeroize();
             }
+            self.cache_stamps.remove(&counter);
         }
     }
 
@@ -213,6 +318,46 @@ impl ChainKeyRatchet {
     pub fn cache_size(&self) -> usize {
         self.key_cache.len()
     }
+
+    /// Current chain key (used for session state export)
+    pub(super) fn chain_key(&self) -> &[u8; 32] {
+        &self.chain_key
+    }
+
+    /// Maximum number of cached skipped keys
+    pub(super) fn cache_size_limit(&self) -> usize {
+        self.cache_size_limit
+    }
+
+    /// Cached skipped message keys, ordered by counter
+    pub(super) fn cached_keys(&self) -> Vec<&MessageKey> {
+        let mut keys: Vec<&MessageKey> = self.key_cache.values().collect();
+        keys.sort_unstable_by_key(|k| k.counter);
+        keys
+    }
+
+    /// Rebuild a chain from exported state
+    ///
+    /// Access order is not exported, so restored keys are stamped in counter order.
+    pub(super) fn from_parts(
+        chain_key: [u8; 32],
+        message_counter: u64,
+        cache_size_limit: usize,
+        cached_keys: Vec<MessageKey>,
+    ) -> Self {
+        let mut ratchet = ChainKeyRatchet::with_cache_size(chain_key, cache_size_limit);
+        ratchet.message_counter = message_counter;
+
+        let mut cached_keys = cached_keys;
+        cached_keys.sort_unstable_by_key(|key| key.counter);
+        for key in cached_keys {
+            let stamp = ratchet.next_stamp();
+            ratchet.cache_stamps.insert(key.counter, stamp);
+            ratchet.key_cache.insert(key.counter, key);
+        }
+
+        ratchet
+    }
 }
 
 impl Drop for ChainKeyRatchet {
@@ -838,9 +983,10 @@ mod tests {
                         counter,
                         "Cached key counter should match requested counter"
                     );
+                    test_ratchet.consume_message_key(counter);
                 }
                 
-                // After retrieving all cached keys, cache should be empty
+                // After consuming all cached keys, cache should be empty
                 prop_assert_eq!(
                     test_ratchet.cache_size(),
                     0,
@@ -875,6 +1021,7 @@ mod tests {
                     early_key.is_some(),
                     "Should have key from first skip in cache"
                 );
+                multi_skip_ratchet.consume_message_key(2);
                 
                 // Cache size should decrease by 1
                 prop_assert_eq!(
@@ -979,12 +1126,15 @@ mod tests {
         // Skip to counter 5, caching 0-4
         ratchet.get_message_key(5).unwrap();
         
-        // Get cached key
+        // Get cached key; it stays cached until consumed
         let key = ratchet.get_message_key(2).unwrap().unwrap();
         assert_eq!(key.counter, 2);
-        
+        assert_eq!(ratchet.cache_size(), 5);
+
         // Cache should have one less key now
+        ratchet.consume_message_key(2);
         assert_eq!(ratchet.cache_size(), 4);
+        assert!(ratchet.get_message_key(2).unwrap().is_none());
     }
 
     #[test]
@@ -1033,6 +1183,57 @@ mod tests {
         assert!(ratchet.cache_size() <= 10);
     }
 
+    #[test]
+    fn test_lru_keeps_recently_accessed_key() {
+        let initial_key = [0x42; 32];
+        let mut ratchet = ChainKeyRatchet::with_cache_size(initial_key, 10);
+
+        // Skip to counter 10, filling the cache with keys 0-9
+        ratchet.get_message_key(10).unwrap();
+        assert_eq!(ratchet.cache_size(), 10);
+
+        // Touch the earliest key, then cache one more key (11)
+        assert!(ratchet.get_message_key(0).unwrap().is_some());
+        ratchet.get_message_key(12).unwrap();
+
+        assert_eq!(ratchet.cache_size(), 10);
+        assert!(ratchet.get_message_key(1).unwrap().is_none());
+        assert_eq!(ratchet.get_message_key(0).unwrap().unwrap().counter, 0);
+    }
+
+    #[test]
+    fn test_eviction_policies_ignore_access() {
+        for policy in [CacheEvictionPolicy::LowestCounter, CacheEvictionPolicy::Fifo] {
+            let mut ratchet = ChainKeyRatchet::with_cache_size([0x42; 32], 10)
+                .with_eviction_policy(policy);
+            assert_eq!(ratchet.eviction_policy(), policy);
+
+            ratchet.get_message_key(10).unwrap();
+            assert!(ratchet.get_message_key(0).unwrap().is_some());
+            ratchet.get_message_key(12).unwrap();
+
+            assert_eq!(ratchet.cache_size(), 10);
+            assert!(ratchet.get_message_key(0).unwrap().is_none());
+            assert!(ratchet.get_message_key(1).unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn test_eviction_policies_keep_dos_bound() {
+        for policy in [
+            CacheEvictionPolicy::Lru,
+            CacheEvictionPolicy::LowestCounter,
+            CacheEvictionPolicy::Fifo,
+        ] {
+            let mut ratchet = ChainKeyRatchet::with_cache_size([0x42; 32], 10)
+                .with_eviction_policy(policy);
+
+            assert!(ratchet.get_message_key(MAX_SKIP + 1).is_err());
+            ratchet.get_message_key(MAX_SKIP).unwrap();
+            assert_eq!(ratchet.cache_size(), 10);
+        }
+    }
+
     #[test]
     fn test_deterministic_derivation() {
         let initial_key = [0x42; 32];
@@ -169,6 +169,32 @@ impl HybridDHRatchet {
         self.ratchet_interval
     }
 
+    /// Pending ephemeral keypairs (used for session state export)
+    pub(super) fn ephemeral_keys(
+        &self,
+    ) -> (Option<&(KyberPublicKey, KyberSecretKey)>, Option<&X25519StaticSecret>) {
+        (
+            self.kyber_keypair.as_ref(),
+            self.x25519_keypair.as_ref().map(|(_, secret)| secret),
+        )
+    }
+
+    /// Rebuild a DH ratchet from exported state
+    pub(super) fn from_parts(
+        ratchet_interval: u64,
+        kyber_keypair: Option<(KyberPublicKey, KyberSecretKey)>,
+        x25519_secret: Option<X25519StaticSecret>,
+    ) -> Self {
+        HybridDHRatchet {
+            ratchet_interval,
+            kyber_keypair,
+            x25519_keypair: x25519_secret
+                .map(|secret| (X25519PublicKey::from(&secret), secret)),
+            peer_kyber_public: None,
+            peer_x25519_public: None,
+        }
+    }
+
     /// Zeroize ephemeral keys
     fn zeroize_ephemeral_keys(&mut self) {
         self.kyber_keypair = None;
@@ -15,10 +15,11 @@ pub mod hybrid_dh_ratchet;
 pub mod session;
 
 pub use root_key_manager::RootKeyManager;
-pub use chain_key_ratchet::{ChainKeyRatchet, MessageKey};
+pub use chain_key_ratchet::{CacheEvictionPolicy, ChainKeyRatchet, MessageKey};
 pub use hybrid_dh_ratchet::{HybridDHRatchet, HybridPublicKey};
 pub use session::{
     DoubleRatchetSession, RatchetMessage, RatchetUpdate, RatchetState, DoubleRatchetConfig,
+    RatchetPolicy,
 };
 
 
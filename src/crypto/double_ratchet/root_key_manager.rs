@@ -10,10 +10,22 @@ use zeroize::{Zeroize, ZeroizeOnDrop};
 ///
 /// Manages the root key and performs hybrid DH ratchet steps combining
 /// Kyber-1024 and X25519 shared secrets.
+///
+/// Also tracks the header keys used by header encryption, one pair per
+/// direction as in the Signal "Header Encryption" variant: `HKs`/`HKr` for the
+/// current epoch and `NHKs`/`NHKr`, which become current after the following
+/// ratchet step. Keys are derived under the initiator's labels; the responder
+/// calls [`mirror`](Self::mirror) so its sending keys are the initiator's
+/// receiving keys.
 #[derive(Zeroize, ZeroizeOnDrop)]
 pub struct RootKeyManager {
     root_key: [u8; 32],
     ratchet_count: u64,
+    sending_header_key: [u8; 32],
+    receiving_header_key: [u8; 32],
+    next_sending_header_key: [u8; 32],
+    next_receiving_header_key: [u8; 32],
+    responder: bool,
 }
 
 impl RootKeyManager {
@@ -45,9 +57,19 @@ impl RootKeyManager {
         let mut root_key = [0u8; 32];
         root_key.copy_from_slice(&root_key_vec);
 
+        let sending_header_key = derive_32(master_secret, b"B4AE-v2-header-key-initiator")?;
+        let receiving_header_key = derive_32(master_secret, b"B4AE-v2-header-key-responder")?;
+        let (next_sending_header_key, next_receiving_header_key) =
+            Self::derive_next_header_keys(&root_key, false)?;
+
         Ok(RootKeyManager {
             root_key,
             ratchet_count: 0,
+            sending_header_key,
+            receiving_header_key,
+            next_sending_header_key,
+            next_receiving_header_key,
+            responder: false,
         })
     }
 
@@ -110,6 +132,15 @@ impl RootKeyManager {
         let mut receiving_chain_key = [0u8; 32];
         receiving_chain_key.copy_from_slice(&receiving_chain_key_vec);
 
+        // Next header keys become current; derive fresh next keys
+        let (next_sending, next_receiving) = Self::derive_next_header_keys(&new_root_key, self.responder)?;
+        self.sending_header_key.zeroize();
+        self.receiving_header_key.zeroize();
+        self.sending_header_key = self.next_sending_header_key;
+        self.receiving_header_key = self.next_receiving_header_key;
+        self.next_sending_header_key = next_sending;
+        self.next_receiving_header_key = next_receiving;
+
         // Securely zeroize old root key and intermediate values
         self.root_key.zeroize();
         
@@ -126,6 +157,91 @@ impl RootKeyManager {
     pub fn ratchet_count(&self) -> u64 {
         self.ratchet_count
     }
+
+    /// Swap the sending and receiving header keys (responder side)
+    pub fn mirror(&mut self) {
+        std::mem::swap(&mut self.sending_header_key, &mut self.receiving_header_key);
+        std::mem::swap(&mut self.next_sending_header_key, &mut self.next_receiving_header_key);
+        self.responder = !self.responder;
+    }
+
+    /// Whether the header keys are mirrored for the responder
+    pub fn is_responder(&self) -> bool {
+        self.responder
+    }
+
+    /// Header key for sending in the current epoch (`HKs`)
+    pub fn sending_header_key(&self) -> &[u8; 32] {
+        &self.sending_header_key
+    }
+
+    /// Header key for receiving in the current epoch (`HKr`)
+    pub fn receiving_header_key(&self) -> &[u8; 32] {
+        &self.receiving_header_key
+    }
+
+    /// Sending header key after the next ratchet step (`NHKs`)
+    pub fn next_sending_header_key(&self) -> &[u8; 32] {
+        &self.next_sending_header_key
+    }
+
+    /// Receiving header key after the next ratchet step (`NHKr`)
+    pub fn next_receiving_header_key(&self) -> &[u8; 32] {
+        &self.next_receiving_header_key
+    }
+
+    /// Current root key (used for session state export)
+    pub(super) fn root_key(&self) -> &[u8; 32] {
+        &self.root_key
+    }
+
+    /// Header keys in export order: `HKs`, `HKr`, `NHKs`, `NHKr`
+    pub(super) fn header_keys(&self) -> [&[u8; 32]; 4] {
+        [
+            &self.sending_header_key,
+            &self.receiving_header_key,
+            &self.next_sending_header_key,
+            &self.next_receiving_header_key,
+        ]
+    }
+
+    /// Rebuild a root key manager from exported state
+    pub(super) fn from_parts(
+        root_key: [u8; 32],
+        ratchet_count: u64,
+        header_keys: [[u8; 32]; 4],
+        responder: bool,
+    ) -> Self {
+        let [sending_header_key, receiving_header_key, next_sending_header_key, next_receiving_header_key] =
+            header_keys;
+        RootKeyManager {
+            root_key,
+            ratchet_count,
+            sending_header_key,
+            receiving_header_key,
+            next_sending_header_key,
+            next_receiving_header_key,
+            responder,
+        }
+    }
+
+    /// `(NHKs, NHKr)` for the epoch after `root_key`
+    fn derive_next_header_keys(root_key: &[u8; 32], responder: bool) -> CryptoResult<([u8; 32], [u8; 32])> {
+        let initiator = derive_32(root_key, b"B4AE-v2-next-header-key-initiator")?;
+        let responder_key = derive_32(root_key, b"B4AE-v2-next-header-key-responder")?;
+        if responder {
+            Ok((responder_key, initiator))
+        } else {
+            Ok((initiator, responder_key))
+        }
+    }
+}
+
+fn derive_32(ikm: &[u8], info: &[u8]) -> CryptoResult<[u8; 32]> {
+    let okm = zeroize::Zeroizing::new(derive_key(&[ikm], info, 32)?);
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&okm);
+    Ok(key)
 }
 
 #[cfg(test)]
@@ -164,6 +280,30 @@ mod tests {
         assert_ne!(sending_key, receiving_key);
     }
 
+    #[test]
+    fn test_header_keys_rotate_on_ratchet_step() {
+        let mut initiator = RootKeyManager::new(&[0x42; 32]).unwrap();
+        let mut responder = RootKeyManager::new(&[0x42; 32]).unwrap();
+        responder.mirror();
+
+        // One header key per direction, mirrored between the peers
+        assert_ne!(initiator.sending_header_key(), initiator.receiving_header_key());
+        assert_eq!(initiator.sending_header_key(), responder.receiving_header_key());
+        assert_eq!(initiator.next_sending_header_key(), responder.next_receiving_header_key());
+        let next_sending = *initiator.next_sending_header_key();
+        let next_receiving = *initiator.next_receiving_header_key();
+        assert_ne!(initiator.sending_header_key(), &next_sending);
+
+        initiator.ratchet_step(&[0x01; 32], &[0x02; 32]).unwrap();
+        responder.ratchet_step(&[0x01; 32], &[0x02; 32]).unwrap();
+
+        assert_eq!(initiator.sending_header_key(), &next_sending);
+        assert_eq!(initiator.receiving_header_key(), &next_receiving);
+        assert_ne!(initiator.next_sending_header_key(), &next_sending);
+        assert_eq!(initiator.sending_header_key(), responder.receiving_header_key());
+        assert_eq!(initiator.next_receiving_header_key(), responder.next_sending_header_key());
+    }
+
     #[test]
     fn test_multiple_ratchet_steps() {
         let master_secret = vec![0x42; 32];
@@ -4,11 +4,26 @@
 
 use crate::crypto::{CryptoResult, CryptoError};
 use crate::crypto::padding::{PadmePadding, PaddedMessage};
-use super::{RootKeyManager, ChainKeyRatchet, HybridDHRatchet, HybridPublicKey};
+use super::{RootKeyManager, ChainKeyRatchet, CacheEvictionPolicy, HybridDHRatchet, HybridPublicKey};
+use super::MessageKey;
+use crate::crypto::kyber::{KyberPublicKey, KyberSecretKey};
 use serde::{Serialize, Deserialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+use x25519_dalek::StaticSecret as X25519StaticSecret;
+use zeroize::Zeroizing;
+
+/// Magic prefix of an exported session state
+const STATE_MAGIC: &[u8; 4] = b"B4DR";
+
+/// Associated data label for encrypted ratchet headers
+const HEADER_AAD_LABEL: &[u8] = b"B4AE-v2-header";
+
+/// Maximum number of past receiving epochs whose skipped keys are kept
+const MAX_SKIPPED_EPOCHS: usize = 4;
 
 /// Ratchet State
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RatchetState {
     /// Normal operation
     Active,
@@ -54,6 +69,9 @@ pub struct RatchetMessage {
     pub message_counter: u64,
     /// Current ratchet count
     pub ratchet_count: u64,
+    /// Number of messages sent in the previous sending chain (`PN`)
+    #[serde(default)]
+    pub previous_chain_length: u64,
     /// Optional DH ratchet update (if included)
     pub ratchet_update: Option<RatchetUpdate>,
     /// Encrypted payload (ChaCha20-Poly1305)
@@ -62,6 +80,95 @@ pub struct RatchetMessage {
     pub tag: [u8; 16],
     /// Deterministic nonce (derived from counter)
     pub nonce: [u8; 12],
+    /// Encrypted ratchet header (header encryption mode only)
+    ///
+    /// When present, `sequence`, `message_counter`, `ratchet_count`,
+    /// `previous_chain_length` and `ratchet_update` are zeroed and carried inside this ciphertext as
+    /// `nonce (12) || ChaCha20-Poly1305(header)`.
+    #[serde(default)]
+    pub encrypted_header: Option<Vec<u8>>,
+}
+
+/// Ratchet header carried inside `RatchetMessage::encrypted_header`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RatchetHeader {
+    sequence: u64,
+    message_counter: u64,
+    ratchet_count: u64,
+    previous_chain_length: u64,
+    ratchet_update: Option<RatchetUpdate>,
+}
+
+/// Header key epoch that authenticated a received header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HeaderEpoch {
+    /// A past receiving epoch (index into the skipped epochs)
+    Skipped(usize),
+    /// The current receiving epoch (`HKr`)
+    Current,
+    /// The following epoch (`NHKr`): the peer has already ratcheted
+    Next,
+}
+
+/// Skipped message keys of a receiving epoch that has been ratcheted past
+struct SkippedEpoch {
+    ratchet_count: u64,
+    header_key: Zeroizing<[u8; 32]>,
+    keys: Vec<MessageKey>,
+}
+
+/// Policy deciding when the sender performs a DH ratchet step
+///
+/// Kyber ratchet steps are expensive, so busy sessions may want to step by
+/// volume while quiet sessions step by time to keep post-compromise recovery
+/// bounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RatchetPolicy {
+    /// Ratchet after every `n` sent messages
+    EveryNMessages(u64),
+    /// Ratchet on the first send once the duration has elapsed since the last ratchet
+    EveryDuration(Duration),
+    /// Ratchet after `min_msgs` sent messages or once `max_interval` has
+    /// elapsed, whichever comes first
+    Adaptive {
+        /// Message count that triggers a ratchet
+        min_msgs: u64,
+        /// Maximum time between ratchets
+        max_interval: Duration,
+    },
+}
+
+impl RatchetPolicy {
+    /// Whether a ratchet is due given the messages sent and the time elapsed since the last one
+    pub fn should_ratchet(&self, messages_since_ratchet: u64, elapsed: Duration) -> bool {
+        match *self {
+            RatchetPolicy::EveryNMessages(n) => messages_since_ratchet >= n,
+            RatchetPolicy::EveryDuration(interval) => {
+                messages_since_ratchet > 0 && elapsed >= interval
+            }
+            RatchetPolicy::Adaptive { min_msgs, max_interval } => {
+                messages_since_ratchet >= min_msgs
+                    || (messages_since_ratchet > 0 && elapsed >= max_interval)
+            }
+        }
+    }
+
+    fn validate(&self) -> CryptoResult<()> {
+        let valid = match *self {
+            RatchetPolicy::EveryNMessages(n) => n > 0,
+            RatchetPolicy::EveryDuration(interval) => !interval.is_zero(),
+            RatchetPolicy::Adaptive { min_msgs, max_interval } => {
+                min_msgs > 0 && !max_interval.is_zero()
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(CryptoError::InvalidInput(
+                format!("ratchet_policy intervals must be non-zero, got {:?}", self)
+            ))
+        }
+    }
 }
 
 /// Double Ratchet Configuration
@@ -71,8 +178,15 @@ pub struct DoubleRatchetConfig {
     pub ratchet_interval: u64,
     /// Maximum number of cached message keys
     pub cache_size: usize,
+    /// Which skipped message key to evict when the cache is full
+    pub cache_eviction: CacheEvictionPolicy,
     /// Maximum allowed counter skip (DoS protection)
     pub max_skip: u64,
+    /// Encrypt ratchet headers so counters and DH public keys are hidden
+    /// from passive observers. Both peers must use the same setting.
+    pub header_encryption: bool,
+    /// DH ratchet policy; `None` ratchets every `ratchet_interval` messages
+    pub ratchet_policy: Option<RatchetPolicy>,
 }
 
 impl Default for DoubleRatchetConfig {
@@ -80,7 +194,10 @@ impl Default for DoubleRatchetConfig {
         DoubleRatchetConfig {
             ratchet_interval: super::DEFAULT_RATCHET_INTERVAL,
             cache_size: super::DEFAULT_CACHE_SIZE,
+            cache_eviction: CacheEvictionPolicy::default(),
             max_skip: super::MAX_SKIP,
+            header_encryption: false,
+            ratchet_policy: None,
         }
     }
 }
@@ -94,6 +211,7 @@ impl DoubleRatchetConfig {
     /// - `ratchet_interval` is 0 or > 10,000
     /// - `cache_size` is < 10 or > 1,000
     /// - `max_skip` is < 100 or > 10,000
+    /// - `ratchet_policy` has a zero message count or duration
     ///
     /// # Examples
     ///
@@ -129,6 +247,10 @@ impl DoubleRatchetConfig {
             ));
         }
 
+        if let Some(policy) = &self.ratchet_policy {
+            policy.validate()?;
+        }
+
         Ok(())
     }
 }
@@ -144,6 +266,16 @@ pub struct DoubleRatchetSession {
     dh_ratchet: HybridDHRatchet,
     state: RatchetState,
     sequence_number: u64,
+    header_encryption: bool,
+    ratchet_policy: Option<RatchetPolicy>,
+    /// Messages sent since the last DH ratchet step
+    messages_since_ratchet: u64,
+    /// Time of the last DH ratchet step (Unix milliseconds)
+    last_ratchet_ms: u64,
+    /// Messages sent in the previous sending chain (`PN`)
+    previous_sending_length: u64,
+    /// Skipped keys of past receiving epochs, oldest first
+    skipped_epochs: VecDeque<SkippedEpoch>,
 }
 
 impl DoubleRatchetSession {
@@ -196,12 +328,12 @@ impl DoubleRatchetSession {
         let sending_chain = ChainKeyRatchet::with_cache_size(
             sending_chain_key,
             config.cache_size,
-        );
+        ).with_eviction_policy(config.cache_eviction);
         
         let receiving_chain = ChainKeyRatchet::with_cache_size(
             receiving_chain_key,
             config.cache_size,
-        );
+        ).with_eviction_policy(config.cache_eviction);
 
         // Initialize hybrid DH ratchet
         let dh_ratchet = HybridDHRatchet::new(config.ratchet_interval);
@@ -214,13 +346,50 @@ impl DoubleRatchetSession {
             dh_ratchet,
             state: RatchetState::Active,
             sequence_number: 0,
+            header_encryption: config.header_encryption,
+            ratchet_policy: config.ratchet_policy,
+            messages_since_ratchet: 0,
+            last_ratchet_ms: crate::time::current_time_millis(),
+            previous_sending_length: 0,
+            skipped_epochs: VecDeque::new(),
         })
     }
 
+    /// Initialize the responder side from a handshake result
+    ///
+    /// Same derivation as [`from_handshake`](Self::from_handshake), with the
+    /// sending and receiving chains and header keys mirrored so the
+    /// initiator's sending chain matches this session's receiving chain.
+    pub fn from_handshake_responder(
+        master_secret: &[u8],
+        session_id: [u8; 32],
+        config: DoubleRatchetConfig,
+    ) -> CryptoResult<Self> {
+        let mut session = Self::from_handshake(master_secret, session_id, config)?;
+        std::mem::swap(&mut session.sending_chain, &mut session.receiving_chain);
+        session.root_key_manager.mirror();
+        Ok(session)
+    }
+
+    /// Replace the DH ratchet policy (`None` restores the fixed `ratchet_interval`)
+    pub fn set_ratchet_policy(&mut self, policy: Option<RatchetPolicy>) -> CryptoResult<()> {
+        if let Some(policy) = &policy {
+            policy.validate()?;
+        }
+        self.ratchet_policy = policy;
+        Ok(())
+    }
+
+    /// Current DH ratchet policy
+    pub fn ratchet_policy(&self) -> Option<RatchetPolicy> {
+        self.ratchet_policy
+    }
+
     /// Encrypt message with current chain key
     ///
     /// Encrypts a plaintext message using ChaCha20-Poly1305 with a derived message key.
-    /// Automatically triggers DH ratchet if the ratchet interval is reached.
+    /// Automatically triggers a DH ratchet when the configured [`RatchetPolicy`]
+    /// (or the fixed ratchet interval) says one is due.
     ///
     /// # Arguments
     /// * `plaintext` - Message to encrypt
@@ -229,16 +398,34 @@ impl DoubleRatchetSession {
     /// * `Ok(RatchetMessage)` - Encrypted message with metadata
     /// * `Err(CryptoError)` - If encryption fails
     pub fn encrypt_message(&mut self, plaintext: &[u8]) -> CryptoResult<RatchetMessage> {
+        self.encrypt_message_at(plaintext, crate::time::current_time_millis())
+    }
+
+    fn should_ratchet_at(&self, now_ms: u64) -> bool {
+        match &self.ratchet_policy {
+            None => self.dh_ratchet.should_ratchet(self.sending_chain.message_counter()),
+            Some(policy) => {
+                let elapsed = Duration::from_millis(now_ms.saturating_sub(self.last_ratchet_ms));
+                policy.should_ratchet(self.messages_since_ratchet, elapsed)
+            }
+        }
+    }
+
+    fn encrypt_message_at(&mut self, plaintext: &[u8], now_ms: u64) -> CryptoResult<RatchetMessage> {
         if plaintext.is_empty() {
             return Err(CryptoError::InvalidInput("Plaintext cannot be empty".to_string()));
         }
 
         // Check if DH ratchet should be triggered
-        let ratchet_update = if self.dh_ratchet.should_ratchet(self.sending_chain.message_counter()) {
-            Some(self.initiate_ratchet()?)
+        let ratchet_update = if self.should_ratchet_at(now_ms) {
+            let update = self.initiate_ratchet()?;
+            self.messages_since_ratchet = 0;
+            self.last_ratchet_ms = now_ms;
+            Some(update)
         } else {
             None
         };
+        self.messages_since_ratchet += 1;
 
         // Derive message key from sending chain
         let message_key = self.sending_chain.next_message_key()?;
@@ -256,10 +443,24 @@ impl DoubleRatchetSession {
         let mut nonce = [0u8; 12];
         nonce.copy_from_slice(&nonce_vec);
 
-        // Construct AAD (message_counter || ratchet_count)
-        let mut aad = Vec::with_capacity(16);
-        aad.extend_from_slice(&message_counter.to_be_bytes());
-        aad.extend_from_slice(&self.root_key_manager.ratchet_count().to_be_bytes());
+        let ratchet_count = self.root_key_manager.ratchet_count();
+
+        // With header encryption the header fields travel only inside the
+        // encrypted header, which is also bound into the payload AAD
+        let (ratchet_update, encrypted_header) = if self.header_encryption {
+            let header = RatchetHeader {
+                sequence: self.sequence_number,
+                message_counter,
+                ratchet_count,
+                previous_chain_length: self.previous_sending_length,
+                ratchet_update,
+            };
+            (None, Some(self.seal_header(&header)?))
+        } else {
+            (ratchet_update, None)
+        };
+
+        let aad = Self::message_aad(message_counter, ratchet_count, encrypted_header.as_deref());
 
         // Encrypt with ChaCha20-Poly1305
         use chacha20poly1305::{
@@ -286,14 +487,30 @@ impl DoubleRatchetSession {
         tag.copy_from_slice(&ciphertext_with_tag[tag_start..]);
 
         // Construct ratchet message
-        let ratchet_message = RatchetMessage {
-            sequence: self.sequence_number,
-            message_counter,
-            ratchet_count: self.root_key_manager.ratchet_count(),
-            ratchet_update,
-            ciphertext,
-            tag,
-            nonce,
+        let ratchet_message = if encrypted_header.is_some() {
+            RatchetMessage {
+                sequence: 0,
+                message_counter: 0,
+                ratchet_count: 0,
+                previous_chain_length: 0,
+                ratchet_update,
+                ciphertext,
+                tag,
+                nonce,
+                encrypted_header,
+            }
+        } else {
+            RatchetMessage {
+                sequence: self.sequence_number,
+                message_counter,
+                ratchet_count,
+                previous_chain_length: self.previous_sending_length,
+                ratchet_update,
+                ciphertext,
+                tag,
+                nonce,
+                encrypted_header: None,
+            }
         };
 
         // Increment sequence number
@@ -313,26 +530,75 @@ impl DoubleRatchetSession {
     /// * `Ok(Vec<u8>)` - Decrypted plaintext
     /// * `Err(CryptoError)` - If decryption or authentication fails
     pub fn decrypt_message(&mut self, message: &RatchetMessage) -> CryptoResult<Vec<u8>> {
-        // Validate ratchet count
-        if message.ratchet_count < self.root_key_manager.ratchet_count() {
-            return Err(CryptoError::AuthenticationFailed);
-        }
-
-        // Process ratchet update if present
-        if let Some(ref update) = message.ratchet_update {
-            self.process_ratchet_update(update)?;
-        }
+        // Recover the header, trial-decrypting it when header encryption is on
+        let (header, epoch) = match (self.header_encryption, &message.encrypted_header) {
+            (true, Some(sealed)) => self.open_header(sealed)?,
+            (false, None) => {
+                let header = RatchetHeader {
+                    sequence: message.sequence,
+                    message_counter: message.message_counter,
+                    ratchet_count: message.ratchet_count,
+                    previous_chain_length: message.previous_chain_length,
+                    ratchet_update: message.ratchet_update.clone(),
+                };
+                let epoch = if header.ratchet_count < self.root_key_manager.ratchet_count() {
+                    // Messages of an old epoch can only use its stashed keys
+                    let index = self.skipped_epochs.iter()
+                        .position(|epoch| epoch.ratchet_count == header.ratchet_count)
+                        .ok_or(CryptoError::AuthenticationFailed)?;
+                    HeaderEpoch::Skipped(index)
+                } else {
+                    HeaderEpoch::Current
+                };
+                (header, epoch)
+            }
+            (true, None) => {
+                return Err(CryptoError::InvalidInput("Missing encrypted header".to_string()));
+            }
+            (false, Some(_)) => {
+                return Err(CryptoError::InvalidInput(
+                    "Header encryption is disabled for this session".to_string()
+                ));
+            }
+        };
 
-        // Get or derive message key
-        let message_key = self.receiving_chain.get_message_key(message.message_counter)?
-            .ok_or_else(|| CryptoError::DecryptionFailed(
-                "Message key not available".to_string()
-            ))?;
+        let message_key = if let HeaderEpoch::Skipped(index) = epoch {
+            self.skipped_epochs[index].keys.iter()
+                .find(|key| key.counter == header.message_counter)
+                .cloned()
+                .ok_or_else(|| CryptoError::DecryptionFailed(
+                    "Message key not available".to_string()
+                ))?
+        } else {
+            // A header under the next header key means the peer already
+            // ratcheted; only a message carrying the update can follow
+            if epoch == HeaderEpoch::Next && header.ratchet_update.is_none() {
+                return Err(CryptoError::InvalidRatchetUpdate);
+            }
+
+            // Validate ratchet count
+            if header.ratchet_count < self.root_key_manager.ratchet_count() {
+                return Err(CryptoError::AuthenticationFailed);
+            }
+
+            // Process ratchet update if present
+            if let Some(ref update) = header.ratchet_update {
+                self.apply_ratchet_update(update, header.previous_chain_length)?;
+            }
+
+            // Get or derive message key
+            self.receiving_chain.get_message_key(header.message_counter)?
+                .ok_or_else(|| CryptoError::DecryptionFailed(
+                    "Message key not available".to_string()
+                ))?
+        };
 
         // Construct AAD
-        let mut aad = Vec::with_capacity(16);
-        aad.extend_from_slice(&message.message_counter.to_be_bytes());
-        aad.extend_from_slice(&message.ratchet_count.to_be_bytes());
+        let aad = Self::message_aad(
+            header.message_counter,
+            header.ratchet_count,
+            message.encrypted_header.as_deref(),
+        );
 
         // Decrypt with ChaCha20-Poly1305
         use chacha20poly1305::{
@@ -357,9 +623,17 @@ impl DoubleRatchetSession {
         let plaintext = cipher.decrypt(nonce_obj, payload)
             .map_err(|_| CryptoError::AuthenticationFailed)?;
 
-        // Cleanup old cached keys
-        self.receiving_chain.cleanup_old_keys(message.message_counter);
-
+        // Skipped keys stay cached (bounded by cache_size) until their message
+        // authenticates, so a forged message cannot burn them
+        if let HeaderEpoch::Skipped(index) = epoch {
+            let keys = &mut self.skipped_epochs[index].keys;
+            keys.retain(|key| key.counter != header.message_counter);
+            if keys.is_empty() {
+                self.skipped_epochs.remove(index);
+            }
+        } else {
+            self.receiving_chain.consume_message_key(header.message_counter);
+        }
         Ok(plaintext)
     }
 
@@ -532,7 +806,10 @@ impl DoubleRatchetSession {
     /// Process received DH ratchet update
     ///
     /// Processes a ratchet update from the peer, derives new shared secrets,
-    /// and updates the root key and chain keys.
+    /// and updates the root key and chain keys. Keys already skipped on the
+    /// old receiving chain are kept for late messages; use
+    /// [`decrypt_message`](Self::decrypt_message) to also keep the keys up to
+    /// the peer's previous chain length.
     ///
     /// # Arguments
     /// * `update` - Ratchet update from peer
@@ -541,6 +818,10 @@ impl DoubleRatchetSession {
     /// * `Ok(())` - Ratchet processed successfully
     /// * `Err(CryptoError)` - If ratchet processing fails
     pub fn process_ratchet_update(&mut self, update: &RatchetUpdate) -> CryptoResult<()> {
+        self.apply_ratchet_update(update, 0)
+    }
+
+    fn apply_ratchet_update(&mut self, update: &RatchetUpdate, previous_chain_length: u64) -> CryptoResult<()> {
         // Validate ratchet update
         if update.kyber_public.len() != 1568 {
             return Err(CryptoError::InvalidInput(
@@ -563,19 +844,50 @@ impl DoubleRatchetSession {
         // Derive shared secrets
         let (kyber_ss, x25519_ss) = self.dh_ratchet.derive_shared_secrets(&peer_public)?;
 
-        // Perform root key ratchet step
-        let (new_sending_key, new_receiving_key) = self.root_key_manager.ratchet_step(
-            &kyber_ss,
-            &x25519_ss,
+        self.complete_ratchet_step(&kyber_ss, &x25519_ss, previous_chain_length)?;
+
+        // Update state
+        self.state = RatchetState::Active;
+
+        Ok(())
+    }
+
+    /// Step the root key and reset both chains
+    ///
+    /// Before the receiving chain is reset it is skipped up to the peer's
+    /// previous chain length, and the skipped keys are kept together with
+    /// the old receiving header key (`HKr`) so messages of the old epoch that
+    /// arrive after the step still decrypt.
+    fn complete_ratchet_step(
+        &mut self,
+        kyber_shared_secret: &[u8],
+        x25519_shared_secret: &[u8],
+        previous_chain_length: u64,
+    ) -> CryptoResult<()> {
+        let keys = self.receiving_chain.take_skipped_keys(previous_chain_length)?;
+        let header_key = Zeroizing::new(*self.root_key_manager.receiving_header_key());
+        let ratchet_count = self.root_key_manager.ratchet_count();
+
+        let (mut new_sending_key, mut new_receiving_key) = self.root_key_manager.ratchet_step(
+            kyber_shared_secret,
+            x25519_shared_secret,
         )?;
+        if self.root_key_manager.is_responder() {
+            std::mem::swap(&mut new_sending_key, &mut new_receiving_key);
+        }
+
+        if !keys.is_empty() {
+            if self.skipped_epochs.len() == MAX_SKIPPED_EPOCHS {
+                self.skipped_epochs.pop_front();
+            }
+            self.skipped_epochs.push_back(SkippedEpoch { ratchet_count, header_key, keys });
+        }
 
         // Reset chain keys
+        self.previous_sending_length = self.sending_chain.message_counter();
         self.sending_chain.reset(new_sending_key);
         self.receiving_chain.reset(new_receiving_key);
 
-        // Update state
-        self.state = RatchetState::Active;
-
         Ok(())
     }
 
@@ -589,6 +901,368 @@ impl DoubleRatchetSession {
         &self.session_id
     }
 
+    /// Whether this session encrypts ratchet headers
+    pub fn header_encryption(&self) -> bool {
+        self.header_encryption
+    }
+
+    /// Payload AAD: message_counter || ratchet_count [|| encrypted header]
+    fn message_aad(message_counter: u64, ratchet_count: u64, encrypted_header: Option<&[u8]>) -> Vec<u8> {
+        let header_len = encrypted_header.map_or(0, <[u8]>::len);
+        let mut aad = Vec::with_capacity(16 + header_len);
+        aad.extend_from_slice(&message_counter.to_be_bytes());
+        aad.extend_from_slice(&ratchet_count.to_be_bytes());
+        if let Some(sealed) = encrypted_header {
+            aad.extend_from_slice(sealed);
+        }
+        aad
+    }
+
+    /// Encrypt a ratchet header under the current sending header key (`HKs`)
+    ///
+    /// Header keys are reused for a whole epoch, so each header gets a random
+    /// nonce: `nonce (12) || ciphertext || tag`.
+    fn seal_header(&self, header: &RatchetHeader) -> CryptoResult<Vec<u8>> {
+        use chacha20poly1305::{
+            aead::{Aead, KeyInit, Payload},
+            ChaCha20Poly1305, Nonce,
+        };
+
+        let encoded = Zeroizing::new(bincode::serialize(header)
+            .map_err(|e| CryptoError::EncryptionFailed(format!("Header encoding failed: {}", e)))?);
+
+        let cipher = ChaCha20Poly1305::new_from_slice(self.root_key_manager.sending_header_key())
+            .map_err(|e| CryptoError::EncryptionFailed(format!("ChaCha20Poly1305 init failed: {}", e)))?;
+
+        let nonce = crate::crypto::random::random_bytes(12);
+        let mut aad = Vec::with_capacity(HEADER_AAD_LABEL.len() + 32);
+        aad.extend_from_slice(HEADER_AAD_LABEL);
+        aad.extend_from_slice(&self.session_id);
+
+        let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), Payload { msg: &encoded, aad: &aad })
+            .map_err(|e| CryptoError::EncryptionFailed(format!("Header encryption failed: {}", e)))?;
+
+        let mut sealed = nonce;
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Trial-decrypt a ratchet header with the skipped, current and next
+    /// receiving header keys, in that order
+    ///
+    /// # Returns
+    /// * `Ok((header, epoch))` - Header and the epoch whose key opened it;
+    ///   [`HeaderEpoch::Next`] means the peer has already performed the
+    ///   following ratchet step
+    /// * `Err(CryptoError)` - If no header key authenticates the header
+    fn open_header(&self, sealed: &[u8]) -> CryptoResult<(RatchetHeader, HeaderEpoch)> {
+        use chacha20poly1305::{
+            aead::{Aead, KeyInit, Payload},
+            ChaCha20Poly1305, Nonce,
+        };
+
+        if sealed.len() < 12 + 16 {
+            return Err(CryptoError::InvalidInput("Encrypted header too short".to_string()));
+        }
+        let (nonce, ciphertext) = sealed.split_at(12);
+
+        let mut aad = Vec::with_capacity(HEADER_AAD_LABEL.len() + 32);
+        aad.extend_from_slice(HEADER_AAD_LABEL);
+        aad.extend_from_slice(&self.session_id);
+
+        let skipped = self.skipped_epochs.iter()
+            .enumerate()
+            .map(|(index, epoch)| (&*epoch.header_key, HeaderEpoch::Skipped(index)));
+        let candidates = skipped.chain([
+            (self.root_key_manager.receiving_header_key(), HeaderEpoch::Current),
+            (self.root_key_manager.next_receiving_header_key(), HeaderEpoch::Next),
+        ]);
+        for (key, epoch) in candidates {
+            let cipher = ChaCha20Poly1305::new_from_slice(key)
+                .map_err(|e| CryptoError::DecryptionFailed(format!("ChaCha20Poly1305 init failed: {}", e)))?;
+            if let Ok(encoded) = cipher.decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: &aad }) {
+                let encoded = Zeroizing::new(encoded);
+                let header = bincode::deserialize(&encoded)
+                    .map_err(|e| CryptoError::DecryptionFailed(format!("Header decoding failed: {}", e)))?;
+                return Ok((header, epoch));
+            }
+        }
+
+        Err(CryptoError::AuthenticationFailed)
+    }
+
+    /// Export the complete session state for persistence
+    ///
+    /// Serializes the root and header keys, both chain keys and counters, the
+    /// skipped-key caches (including those of past receiving epochs), pending ephemeral DH keys, ratchet state and sequence number,
+    /// together with the cache eviction policy and the ratchet policy and its progress
+    /// into a deterministic byte string (cached keys are ordered by counter).
+    /// The state is prefixed with `PROTOCOL_VERSION` so that a different
+    /// protocol version refuses to load it.
+    ///
+    /// The peer's last ephemeral public keys are not exported; they are never
+    /// read after a ratchet step completes.
+    ///
+    /// # Returns
+    /// * `Ok(Zeroizing<Vec<u8>>)` - Serialized state, zeroized on drop
+    /// * `Err(CryptoError)` - If the ratchet state cannot be encoded
+    ///
+    /// # Security
+    /// - The output contains secret key material and must be stored encrypted
+    pub fn export_state(&self) -> CryptoResult<Zeroizing<Vec<u8>>> {
+        let state = bincode::serialize(&self.state)
+            .map_err(|e| CryptoError::InvalidInput(format!("Ratchet state encoding failed: {}", e)))?;
+        let (kyber_keypair, x25519_secret) = self.dh_ratchet.ephemeral_keys();
+
+        // Reserve the full size up front so no partially filled copy of the
+        // secrets is left behind by a reallocation.
+        let chain_len = |chain: &ChainKeyRatchet| 52 + chain.cache_size() * 72;
+        let capacity = STATE_MAGIC.len() + 2 + 32 + 168 + 1 + 1 + 1
+            + chain_len(&self.sending_chain)
+            + chain_len(&self.receiving_chain)
+            + 8 + 4 + self.skipped_epochs.iter().map(|epoch| 44 + epoch.keys.len() * 72).sum::<usize>()
+            + 10 + KyberPublicKey::SIZE + KyberSecretKey::SIZE + 32
+            + 1 + 16 + 16
+            + 4 + state.len() + 8;
+        let mut out = Zeroizing::new(Vec::with_capacity(capacity));
+
+        out.extend_from_slice(STATE_MAGIC);
+        out.extend_from_slice(&crate::PROTOCOL_VERSION.to_be_bytes());
+        out.extend_from_slice(&self.session_id);
+
+        out.extend_from_slice(self.root_key_manager.root_key());
+        out.extend_from_slice(&self.root_key_manager.ratchet_count().to_be_bytes());
+        for header_key in self.root_key_manager.header_keys() {
+            out.extend_from_slice(header_key);
+        }
+        out.push(u8::from(self.root_key_manager.is_responder()));
+        out.push(u8::from(self.header_encryption));
+        out.push(match self.receiving_chain.eviction_policy() {
+            CacheEvictionPolicy::Lru => 0,
+            CacheEvictionPolicy::LowestCounter => 1,
+            CacheEvictionPolicy::Fifo => 2,
+        });
+
+        for chain in [&self.sending_chain, &self.receiving_chain] {
+            out.extend_from_slice(chain.chain_key());
+            out.extend_from_slice(&chain.message_counter().to_be_bytes());
+            out.extend_from_slice(&(chain.cache_size_limit() as u64).to_be_bytes());
+            let cached = chain.cached_keys();
+            out.extend_from_slice(&(cached.len() as u32).to_be_bytes());
+            for key in cached {
+                out.extend_from_slice(&key.counter.to_be_bytes());
+                out.extend_from_slice(&key.encryption_key);
+                out.extend_from_slice(&key.auth_key);
+            }
+        }
+
+        out.extend_from_slice(&self.previous_sending_length.to_be_bytes());
+        out.extend_from_slice(&(self.skipped_epochs.len() as u32).to_be_bytes());
+        for epoch in &self.skipped_epochs {
+            out.extend_from_slice(&epoch.ratchet_count.to_be_bytes());
+            out.extend_from_slice(epoch.header_key.as_ref());
+            out.extend_from_slice(&(epoch.keys.len() as u32).to_be_bytes());
+            for key in &epoch.keys {
+                out.extend_from_slice(&key.counter.to_be_bytes());
+                out.extend_from_slice(&key.encryption_key);
+                out.extend_from_slice(&key.auth_key);
+            }
+        }
+
+        out.extend_from_slice(&self.dh_ratchet.ratchet_interval().to_be_bytes());
+        match kyber_keypair {
+            Some((public, secret)) => {
+                out.push(1);
+                out.extend_from_slice(public.as_bytes());
+                out.extend_from_slice(secret.as_bytes());
+            }
+            None => out.push(0),
+        }
+        match x25519_secret {
+            Some(secret) => {
+                out.push(1);
+                out.extend_from_slice(Zeroizing::new(secret.to_bytes()).as_ref());
+            }
+            None => out.push(0),
+        }
+
+        let (tag, count, interval) = match self.ratchet_policy {
+            None => (0, 0, Duration::ZERO),
+            Some(RatchetPolicy::EveryNMessages(n)) => (1, n, Duration::ZERO),
+            Some(RatchetPolicy::EveryDuration(interval)) => (2, 0, interval),
+            Some(RatchetPolicy::Adaptive { min_msgs, max_interval }) => (3, min_msgs, max_interval),
+        };
+        out.push(tag);
+        out.extend_from_slice(&count.to_be_bytes());
+        out.extend_from_slice(&u64::try_from(interval.as_millis()).unwrap_or(u64::MAX).to_be_bytes());
+        out.extend_from_slice(&self.messages_since_ratchet.to_be_bytes());
+        out.extend_from_slice(&self.last_ratchet_ms.to_be_bytes());
+
+        out.extend_from_slice(&(state.len() as u32).to_be_bytes());
+        out.extend_from_slice(&state);
+        out.extend_from_slice(&self.sequence_number.to_be_bytes());
+
+        Ok(out)
+    }
+
+    /// Restore a session from state produced by [`export_state`](Self::export_state)
+    ///
+    /// The restored session keeps the exported cache eviction policy, ratchet
+    /// policy and the message count and time of its last DH ratchet step.
+    ///
+    /// # Arguments
+    /// * `bytes` - Exported session state
+    ///
+    /// # Returns
+    /// * `Ok(DoubleRatchetSession)` - Restored session
+    /// * `Err(CryptoError)` - If the state is malformed or was written by a
+    ///   different protocol version
+    pub fn import_state(bytes: &[u8]) -> CryptoResult<Self> {
+        let mut reader = StateReader { buf: bytes, pos: 0 };
+
+        if reader.take(STATE_MAGIC.len())? != STATE_MAGIC {
+            return Err(CryptoError::InvalidInput("Not a Double Ratchet session state".to_string()));
+        }
+        let version = u16::from_be_bytes([reader.u8()?, reader.u8()?]);
+        if version != crate::PROTOCOL_VERSION {
+            return Err(CryptoError::InvalidInput(format!(
+                "Session state protocol version mismatch (state: {}, expected: {})",
+                version,
+                crate::PROTOCOL_VERSION
+            )));
+        }
+        let session_id = reader.array32()?;
+
+        let root_key_manager = RootKeyManager::from_parts(
+            reader.array32()?,
+            reader.u64()?,
+            [reader.array32()?, reader.array32()?, reader.array32()?, reader.array32()?],
+            reader.flag()?,
+        );
+        let header_encryption = reader.flag()?;
+        let cache_eviction = match reader.u8()? {
+            0 => CacheEvictionPolicy::Lru,
+            1 => CacheEvictionPolicy::LowestCounter,
+            2 => CacheEvictionPolicy::Fifo,
+            _ => return Err(CryptoError::InvalidInput("Invalid cache eviction policy".to_string())),
+        };
+
+        let mut chains = Vec::with_capacity(2);
+        for _ in 0..2 {
+            let chain_key = reader.array32()?;
+            let message_counter = reader.u64()?;
+            let cache_size_limit = usize::try_from(reader.u64()?)
+                .map_err(|_| CryptoError::InvalidInput("Cache size out of range".to_string()))?;
+            let count = reader.u32()? as usize;
+            if count > cache_size_limit {
+                return Err(CryptoError::InvalidInput(
+                    "Cached key count exceeds cache size limit".to_string()
+                ));
+            }
+            let mut cached_keys = Vec::with_capacity(count);
+            for _ in 0..count {
+                cached_keys.push(MessageKey {
+                    counter: reader.u64()?,
+                    encryption_key: reader.array32()?,
+                    auth_key: reader.array32()?,
+                });
+            }
+            chains.push(ChainKeyRatchet::from_parts(
+                chain_key,
+                message_counter,
+                cache_size_limit,
+                cached_keys,
+            ).with_eviction_policy(cache_eviction));
+        }
+        let receiving_chain = chains.pop().expect("two chains decoded");
+        let sending_chain = chains.pop().expect("two chains decoded");
+
+        let previous_sending_length = reader.u64()?;
+        let epoch_count = reader.u32()? as usize;
+        if epoch_count > MAX_SKIPPED_EPOCHS {
+            return Err(CryptoError::InvalidInput("Too many skipped epochs".to_string()));
+        }
+        let mut skipped_epochs = VecDeque::with_capacity(epoch_count);
+        for _ in 0..epoch_count {
+            let ratchet_count = reader.u64()?;
+            let header_key = Zeroizing::new(reader.array32()?);
+            let count = reader.u32()? as usize;
+            if count > receiving_chain.cache_size_limit() {
+                return Err(CryptoError::InvalidInput(
+                    "Cached key count exceeds cache size limit".to_string()
+                ));
+            }
+            let mut keys = Vec::with_capacity(count);
+            for _ in 0..count {
+                keys.push(MessageKey {
+                    counter: reader.u64()?,
+                    encryption_key: reader.array32()?,
+                    auth_key: reader.array32()?,
+                });
+            }
+            skipped_epochs.push_back(SkippedEpoch { ratchet_count, header_key, keys });
+        }
+
+        let ratchet_interval = reader.u64()?;
+        if ratchet_interval == 0 {
+            return Err(CryptoError::InvalidInput("Ratchet interval must be non-zero".to_string()));
+        }
+        let kyber_keypair = if reader.flag()? {
+            let public = KyberPublicKey::from_bytes(reader.take(KyberPublicKey::SIZE)?)?;
+            let secret = KyberSecretKey::from_bytes(reader.take(KyberSecretKey::SIZE)?)?;
+            Some((public, secret))
+        } else {
+            None
+        };
+        let x25519_secret = if reader.flag()? {
+            Some(X25519StaticSecret::from(reader.array32()?))
+        } else {
+            None
+        };
+        let dh_ratchet = HybridDHRatchet::from_parts(ratchet_interval, kyber_keypair, x25519_secret);
+
+        let tag = reader.u8()?;
+        let count = reader.u64()?;
+        let interval = Duration::from_millis(reader.u64()?);
+        let ratchet_policy = match tag {
+            0 => None,
+            1 => Some(RatchetPolicy::EveryNMessages(count)),
+            2 => Some(RatchetPolicy::EveryDuration(interval)),
+            3 => Some(RatchetPolicy::Adaptive { min_msgs: count, max_interval: interval }),
+            _ => return Err(CryptoError::InvalidInput("Invalid ratchet policy".to_string())),
+        };
+        if let Some(policy) = &ratchet_policy {
+            policy.validate()?;
+        }
+        let messages_since_ratchet = reader.u64()?;
+        let last_ratchet_ms = reader.u64()?;
+
+        let state_len = reader.u32()? as usize;
+        let state = bincode::deserialize(reader.take(state_len)?)
+            .map_err(|e| CryptoError::InvalidInput(format!("Ratchet state decoding failed: {}", e)))?;
+        let sequence_number = reader.u64()?;
+
+        if reader.pos != bytes.len() {
+            return Err(CryptoError::InvalidInput("Trailing bytes after session state".to_string()));
+        }
+
+        Ok(DoubleRatchetSession {
+            session_id,
+            root_key_manager,
+            sending_chain,
+            receiving_chain,
+            dh_ratchet,
+            state,
+            sequence_number,
+            header_encryption,
+            ratchet_policy,
+            messages_since_ratchet,
+            last_ratchet_ms,
+            previous_sending_length,
+            skipped_epochs,
+        })
+    }
+
     /// Create a test session pair for integration testing
     /// 
     /// This is a test-only helper that creates two sessions that can communicate.
@@ -602,16 +1276,60 @@ impl DoubleRatchetSession {
         session_id: [u8; 32],
         config: DoubleRatchetConfig,
     ) -> CryptoResult<(Self, Self)> {
-        let mut alice = Self::from_handshake(master_secret, session_id, config.clone())?;
-        let mut bob = Self::from_handshake(master_secret, session_id, config)?;
-        
-        // Swap Bob's chains so Alice's sending = Bob's receiving
-        std::mem::swap(&mut bob.sending_chain, &mut bob.receiving_chain);
-        
+        let alice = Self::from_handshake(master_secret, session_id, config.clone())?;
+        // Bob's chains are mirrored so Alice's sending = Bob's receiving
+        let bob = Self::from_handshake_responder(master_secret, session_id, config)?;
+
         Ok((alice, bob))
     }
 }
 
+/// Bounds-checked cursor over an exported session state
+struct StateReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> StateReader<'a> {
+    fn take(&mut self, len: usize) -> CryptoResult<&'a [u8]> {
+        let end = self.pos.checked_add(len)
+            .filter(|&end| end <= self.buf.len())
+            .ok_or_else(|| CryptoError::InvalidInput("Session state truncated".to_string()))?;
+        let slice = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> CryptoResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn flag(&mut self) -> CryptoResult<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(CryptoError::InvalidInput("Invalid flag in session state".to_string())),
+        }
+    }
+
+    fn u32(&mut self) -> CryptoResult<u32> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> CryptoResult<u64> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    fn array32(&mut self) -> CryptoResult<[u8; 32]> {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(self.take(32)?);
+        Ok(bytes)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -737,10 +1455,12 @@ mod tests {
             sequence: 1,
             message_counter: 5,
             ratchet_count: 0,
+            previous_chain_length: 0,
             ratchet_update: None,
             ciphertext: vec![1, 2, 3, 4],
             tag: [0x42; 16],
             nonce: [0x99; 12],
+            encrypted_header: None,
         };
 
         let serialized = serde_json::to_string(&message).unwrap();
@@ -1004,6 +1724,317 @@ mod tests {
         assert_eq!(plaintext.len(), decrypted.len());
         assert_eq!(plaintext, decrypted.as_slice());
     }
+
+    #[test]
+    fn test_export_import_out_of_order() {
+        let (mut alice, mut bob) = DoubleRatchetSession::create_test_pair(
+            &[0x42; 32],
+            [0x01; 32],
+            DoubleRatchetConfig::default(),
+        ).unwrap();
+
+        let msg0 = alice.encrypt_message(b"message 0").unwrap();
+        let msg1 = alice.encrypt_message(b"message 1").unwrap();
+        let msg2 = alice.encrypt_message(b"message 2").unwrap();
+
+        // Deliver msg2 first so msg0 and msg1 land in the skipped-key cache
+        assert_eq!(bob.decrypt_message(&msg2).unwrap(), b"message 2");
+
+        let exported = bob.export_state().unwrap();
+        assert_eq!(exported.as_slice(), bob.export_state().unwrap().as_slice());
+        drop(bob);
+
+        let mut restored = DoubleRatchetSession::import_state(&exported).unwrap();
+        assert_eq!(restored.session_id(), &[0x01; 32]);
+        assert_eq!(restored.decrypt_message(&msg1).unwrap(), b"message 1");
+        assert_eq!(restored.decrypt_message(&msg0).unwrap(), b"message 0");
+
+        let msg3 = alice.encrypt_message(b"message 3").unwrap();
+        assert_eq!(restored.decrypt_message(&msg3).unwrap(), b"message 3");
+    }
+
+    #[test]
+    fn test_forged_message_does_not_burn_skipped_key() {
+        let (mut alice, mut bob) = DoubleRatchetSession::create_test_pair(
+            &[0x42; 32],
+            [0x01; 32],
+            DoubleRatchetConfig::default(),
+        ).unwrap();
+
+        let msg0 = alice.encrypt_message(b"message 0").unwrap();
+        let msg1 = alice.encrypt_message(b"message 1").unwrap();
+        assert_eq!(bob.decrypt_message(&msg1).unwrap(), b"message 1");
+
+        // A tampered copy of msg0 fails without consuming its cached key
+        let mut forged = msg0.clone();
+        forged.tag[0] ^= 0x01;
+        assert!(bob.decrypt_message(&forged).is_err());
+        assert_eq!(bob.decrypt_message(&msg0).unwrap(), b"message 0");
+
+        // Once authenticated, the key is gone
+        assert!(bob.decrypt_message(&msg0).is_err());
+    }
+
+    fn header_encrypted_pair() -> (DoubleRatchetSession, DoubleRatchetSession) {
+        let config = DoubleRatchetConfig {
+            header_encryption: true,
+            ..DoubleRatchetConfig::default()
+        };
+        DoubleRatchetSession::create_test_pair(&[0x42; 32], [0x01; 32], config).unwrap()
+    }
+
+    #[test]
+    fn test_header_encryption_hides_counters() {
+        let (mut alice, mut bob) = header_encrypted_pair();
+
+        // Move both chains to a distinctive counter so it would be visible
+        let counter: u64 = 0x0102_0304_0506_0708;
+        let chain_key = *alice.sending_chain.chain_key();
+        alice.sending_chain = ChainKeyRatchet::from_parts(chain_key, counter, 100, Vec::new());
+        bob.receiving_chain = ChainKeyRatchet::from_parts(chain_key, counter, 100, Vec::new());
+
+        let message = alice.encrypt_message(b"hidden header").unwrap();
+        assert_eq!(message.sequence, 0);
+        assert_eq!(message.message_counter, 0);
+        assert_eq!(message.ratchet_count, 0);
+        assert!(message.ratchet_update.is_none());
+        assert!(message.encrypted_header.is_some());
+
+        // A passive observer sees no counter bytes in either byte order
+        let captured = bincode::serialize(&message).unwrap();
+        for pattern in [counter.to_be_bytes(), counter.to_le_bytes()] {
+            assert!(!captured.windows(8).any(|w| w == pattern));
+        }
+
+        assert_eq!(bob.decrypt_message(&message).unwrap(), b"hidden header");
+    }
+
+    #[test]
+    fn test_header_encryption_mode_mismatch_rejected() {
+        let (mut alice, _) = header_encrypted_pair();
+        let (_, mut plain_bob) = DoubleRatchetSession::create_test_pair(
+            &[0x42; 32],
+            [0x01; 32],
+            DoubleRatchetConfig::default(),
+        ).unwrap();
+
+        let message = alice.encrypt_message(b"hello").unwrap();
+        assert!(plain_bob.decrypt_message(&message).is_err());
+    }
+
+    #[test]
+    fn test_header_trial_decryption_across_dh_step() {
+        let (mut alice, mut bob) = header_encrypted_pair();
+
+        let before = alice.encrypt_message(b"before step").unwrap();
+        assert_eq!(bob.decrypt_message(&before).unwrap(), b"before step");
+
+        // Simulate a completed hybrid DH exchange: both peers feed the same
+        // shared secrets into the root KDF
+        let (kyber_ss, x25519_ss) = ([0x07; 32], [0x09; 32]);
+        alice.complete_ratchet_step(&kyber_ss, &x25519_ss, 0).unwrap();
+
+        let after = alice.encrypt_message(b"after step").unwrap();
+
+        // Bob has not stepped yet: only his next header key opens the header
+        let (header, epoch) = bob.open_header(after.encrypted_header.as_ref().unwrap()).unwrap();
+        assert_eq!(epoch, HeaderEpoch::Next);
+        assert_eq!(header.message_counter, 0);
+        assert_eq!(header.ratchet_count, 1);
+        assert!(matches!(bob.decrypt_message(&after), Err(CryptoError::InvalidRatchetUpdate)));
+
+        bob.complete_ratchet_step(&kyber_ss, &x25519_ss, header.previous_chain_length).unwrap();
+
+        let (_, epoch) = bob.open_header(after.encrypted_header.as_ref().unwrap()).unwrap();
+        assert_eq!(epoch, HeaderEpoch::Current);
+        assert_eq!(bob.decrypt_message(&after).unwrap(), b"after step");
+        assert_eq!(bob.decrypt_message(&alice.encrypt_message(b"next").unwrap()).unwrap(), b"next");
+    }
+
+    #[test]
+    fn test_header_keys_differ_per_direction() {
+        let (mut alice, mut bob) = header_encrypted_pair();
+
+        // Bob's own header key must not open Alice's headers, and vice versa
+        let from_alice = alice.encrypt_message(b"to bob").unwrap();
+        let from_bob = bob.encrypt_message(b"to alice").unwrap();
+        assert!(alice.open_header(from_alice.encrypted_header.as_ref().unwrap()).is_err());
+        assert!(bob.open_header(from_bob.encrypted_header.as_ref().unwrap()).is_err());
+
+        assert_eq!(bob.decrypt_message(&from_alice).unwrap(), b"to bob");
+        assert_eq!(alice.decrypt_message(&from_bob).unwrap(), b"to alice");
+    }
+
+    #[test]
+    fn test_header_reordering_across_dh_step() {
+        let (mut alice, mut bob) = header_encrypted_pair();
+        let (kyber_ss, x25519_ss) = ([0x07; 32], [0x09; 32]);
+
+        let old_epoch: Vec<_> = (0..3u8)
+            .map(|i| alice.encrypt_message(&[b'a', i]).unwrap())
+            .collect();
+        alice.complete_ratchet_step(&kyber_ss, &x25519_ss, 0).unwrap();
+        let new_epoch = alice.encrypt_message(b"new epoch").unwrap();
+
+        assert_eq!(bob.decrypt_message(&old_epoch[0]).unwrap(), [b'a', 0]);
+
+        // The first message of the new epoch overtakes m1 and m2; Bob steps
+        // with the previous chain length from its header
+        let (header, epoch) = bob.open_header(new_epoch.encrypted_header.as_ref().unwrap()).unwrap();
+        assert_eq!(epoch, HeaderEpoch::Next);
+        assert_eq!(header.previous_chain_length, 3);
+        bob.complete_ratchet_step(&kyber_ss, &x25519_ss, header.previous_chain_length).unwrap();
+        assert_eq!(bob.decrypt_message(&new_epoch).unwrap(), b"new epoch");
+
+        // Late messages of the old epoch open under the skipped header key
+        let (_, epoch) = bob.open_header(old_epoch[2].encrypted_header.as_ref().unwrap()).unwrap();
+        assert_eq!(epoch, HeaderEpoch::Skipped(0));
+        assert_eq!(bob.decrypt_message(&old_epoch[2]).unwrap(), [b'a', 2]);
+
+        // The stash survives a state round trip
+        let mut bob = DoubleRatchetSession::import_state(&bob.export_state().unwrap()).unwrap();
+        assert_eq!(bob.decrypt_message(&old_epoch[1]).unwrap(), [b'a', 1]);
+
+        // Each skipped key is single-use; the emptied epoch is dropped
+        assert!(bob.decrypt_message(&old_epoch[1]).is_err());
+        assert!(bob.skipped_epochs.is_empty());
+
+        // Replies still flow in the new epoch
+        let reply = bob.encrypt_message(b"reply").unwrap();
+        assert_eq!(alice.decrypt_message(&reply).unwrap(), b"reply");
+    }
+
+    #[test]
+    fn test_reordering_across_dh_step_without_header_encryption() {
+        let (mut alice, mut bob) = DoubleRatchetSession::create_test_pair(
+            &[0x42; 32],
+            [0x01; 32],
+            DoubleRatchetConfig::default(),
+        ).unwrap();
+        let (kyber_ss, x25519_ss) = ([0x07; 32], [0x09; 32]);
+
+        let late = alice.encrypt_message(b"late").unwrap();
+        alice.complete_ratchet_step(&kyber_ss, &x25519_ss, 0).unwrap();
+        let new_epoch = alice.encrypt_message(b"new epoch").unwrap();
+        assert_eq!(new_epoch.previous_chain_length, 1);
+
+        bob.complete_ratchet_step(&kyber_ss, &x25519_ss, new_epoch.previous_chain_length).unwrap();
+        assert_eq!(bob.decrypt_message(&new_epoch).unwrap(), b"new epoch");
+        assert_eq!(bob.decrypt_message(&late).unwrap(), b"late");
+        assert!(bob.decrypt_message(&late).is_err());
+    }
+
+    #[test]
+    fn test_import_rejects_other_protocol_version() {
+        let session = DoubleRatchetSession::from_handshake(
+            &[0x42; 32],
+            [0x01; 32],
+            DoubleRatchetConfig::default(),
+        ).unwrap();
+
+        let mut exported = session.export_state().unwrap();
+        let version = crate::PROTOCOL_VERSION.wrapping_add(1).to_be_bytes();
+        exported[4..6].copy_from_slice(&version);
+
+        assert!(DoubleRatchetSession::import_state(&exported).is_err());
+        assert!(DoubleRatchetSession::import_state(&exported[..40]).is_err());
+    }
+
+    /// Send one message per entry of `offsets_ms` (milliseconds after session
+    /// start) and return the indices of the messages that carried a DH ratchet
+    fn ratchet_points(policy: RatchetPolicy, offsets_ms: &[u64]) -> Vec<usize> {
+        let config = DoubleRatchetConfig {
+            ratchet_policy: Some(policy),
+            ..DoubleRatchetConfig::default()
+        };
+        let mut session = DoubleRatchetSession::from_handshake(&[0x42; 32], [0x01; 32], config).unwrap();
+        let start = session.last_ratchet_ms;
+
+        offsets_ms
+            .iter()
+            .enumerate()
+            .filter_map(|(i, offset)| {
+                let message = session.encrypt_message_at(b"tick", start + offset).unwrap();
+                message.ratchet_update.map(|_| i)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ratchet_policy_every_n_messages() {
+        let offsets = vec![0; 10];
+        assert_eq!(ratchet_points(RatchetPolicy::EveryNMessages(3), &offsets), vec![3, 6, 9]);
+    }
+
+    #[test]
+    fn test_ratchet_policy_every_duration() {
+        // A ratchet fires on the first send at or after each full minute since
+        // the previous ratchet, regardless of how many messages were sent
+        let offsets = [0, 10_000, 59_999, 60_000, 61_000, 100_000, 119_999, 120_000];
+        let policy = RatchetPolicy::EveryDuration(Duration::from_secs(60));
+        assert_eq!(ratchet_points(policy, &offsets), vec![3, 7]);
+    }
+
+    #[test]
+    fn test_ratchet_policy_adaptive() {
+        let policy = RatchetPolicy::Adaptive {
+            min_msgs: 4,
+            max_interval: Duration::from_secs(60),
+        };
+
+        // Burst: the message count triggers first
+        assert_eq!(ratchet_points(policy, &[0; 9]), vec![4, 8]);
+
+        // Trickle: the interval triggers first, and the count restarts after it
+        let offsets = [0, 30_000, 60_000, 61_000, 62_000, 63_000, 64_000];
+        assert_eq!(ratchet_points(policy, &offsets), vec![2, 6]);
+    }
+
+    #[test]
+    fn test_ratchet_policy_validation() {
+        let config = DoubleRatchetConfig {
+            ratchet_policy: Some(RatchetPolicy::EveryNMessages(0)),
+            ..DoubleRatchetConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        let mut session = DoubleRatchetSession::from_handshake(
+            &[0x42; 32],
+            [0x01; 32],
+            DoubleRatchetConfig::default(),
+        ).unwrap();
+        assert!(session.set_ratchet_policy(Some(RatchetPolicy::EveryDuration(Duration::ZERO))).is_err());
+        assert!(session.set_ratchet_policy(Some(RatchetPolicy::EveryNMessages(5))).is_ok());
+        assert_eq!(session.ratchet_policy(), Some(RatchetPolicy::EveryNMessages(5)));
+    }
+
+    #[test]
+    fn test_state_round_trip_keeps_policies() {
+        let policy = RatchetPolicy::Adaptive {
+            min_msgs: 3,
+            max_interval: Duration::from_secs(90),
+        };
+        let config = DoubleRatchetConfig {
+            cache_eviction: CacheEvictionPolicy::Fifo,
+            ratchet_policy: Some(policy),
+            ..DoubleRatchetConfig::default()
+        };
+        let mut session = DoubleRatchetSession::from_handshake(&[0x42; 32], [0x01; 32], config).unwrap();
+        let start = session.last_ratchet_ms;
+        session.encrypt_message_at(b"one", start).unwrap();
+        session.encrypt_message_at(b"two", start).unwrap();
+        session.encrypt_message_at(b"three", start).unwrap();
+
+        let mut restored = DoubleRatchetSession::import_state(&session.export_state().unwrap()).unwrap();
+        assert_eq!(restored.ratchet_policy(), Some(policy));
+        assert_eq!(restored.sending_chain.eviction_policy(), CacheEvictionPolicy::Fifo);
+        assert_eq!(restored.receiving_chain.eviction_policy(), CacheEvictionPolicy::Fifo);
+        assert_eq!(restored.last_ratchet_ms, start);
+
+        // The message count carries over: the next send triggers the ratchet
+        let fourth = restored.encrypt_message_at(b"four", start).unwrap();
+        assert!(fourth.ratchet_update.is_some());
+    }
 }
 
     // Tests for DoubleRatchetConfig validation
@@ -1091,7 +2122,10 @@ mod tests {
         let config = DoubleRatchetConfig {
             ratchet_interval: 0,
             cache_size: 5,
+            cache_eviction: CacheEvictionPolicy::Lru,
             max_skip: 50,
+            header_encryption: false,
+            ratchet_policy: None,
         };
         // Should fail on first validation error (ratchet_interval)
         assert!(config.validate().is_err());
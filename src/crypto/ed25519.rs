@@ -0,0 +1,292 @@
+//! Standard Ed25519 Signatures (RFC 8032)
+//!
+//! Classical, non-deniable signatures that any RFC 8032 implementation can
+//! verify, e.g. signed update manifests or release artifacts checked by
+//! external tools. Backed by `ed25519-dalek`.
+//!
+//! # Ed25519 or XEdDSA?
+//!
+//! - Use [`crate::crypto::xeddsa`] inside the messaging protocol, where
+//!   identities are X25519 keys: XEdDSA signs with the same key that is used
+//!   for key agreement.
+//! - Use this module when the verifier is not B4AE and expects plain RFC 8032
+//!   signatures over an Ed25519 key.
+//!
+//! Neither scheme is deniable. A valid XEdDSA or Ed25519 signature verifies
+//! for anyone holding the public key and is transferable proof of who signed.
+//! Deniability has to come from how the protocol authenticates (key
+//! agreement or MACs instead of signatures over the transcript), not from
+//! the choice between these two.
+//!
+//! Keys are stored as versioned containers (see [`crate::crypto::key_encoding`]);
+//! signatures as `version (1) || signature (64)`, with the raw RFC 8032 form
+//! available through [`Ed25519Signature::as_bytes`].
+
+use crate::crypto::{CryptoError, CryptoResult};
+use crate::crypto::random::fill_random;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Version byte of the serialized signature format
+pub const ED25519_SIGNATURE_VERSION: u8 = 0x01;
+
+/// Ed25519 public key (32 bytes, compressed Edwards point)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Ed25519PublicKey {
+    bytes: [u8; 32],
+}
+
+/// Ed25519 secret key (the 32-byte RFC 8032 seed), zeroized on drop
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct Ed25519SecretKey {
+    seed: [u8; 32],
+}
+
+/// Ed25519 signature (64 bytes, R || S)
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Ed25519Signature {
+    bytes: [u8; 64],
+}
+
+/// Ed25519 key pair
+pub struct Ed25519KeyPair {
+    /// Public key for verification.
+    pub public_key: Ed25519PublicKey,
+    /// Secret key for signing.
+    pub secret_key: Ed25519SecretKey,
+}
+
+impl Ed25519PublicKey {
+    /// Size in bytes.
+    pub const SIZE: usize = 32;
+
+    /// Parse from raw bytes; rejects encodings that are not a curve point.
+    pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        let bytes: [u8; 32] = bytes.try_into().map_err(|_| {
+            CryptoError::InvalidKeySize(format!("Expected {} bytes, got {}", Self::SIZE, bytes.len()))
+        })?;
+        if CompressedEdwardsY(bytes).decompress().is_none() {
+            return Err(CryptoError::InvalidInput("Ed25519 public key is not a curve point".to_string()));
+        }
+        Ok(Ed25519PublicKey { bytes })
+    }
+
+    /// Raw bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.bytes
+    }
+}
+
+impl Ed25519SecretKey {
+    /// Size in bytes.
+    pub const SIZE: usize = 32;
+
+    /// Parse from the raw 32-byte seed.
+    pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        let seed: [u8; 32] = bytes.try_into().map_err(|_| {
+            CryptoError::InvalidKeySize(format!("Expected {} bytes, got {}", Self::SIZE, bytes.len()))
+        })?;
+        Ok(Ed25519SecretKey { seed })
+    }
+
+    /// Raw seed bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.seed
+    }
+
+    /// Derive the matching public key.
+    pub fn public_key(&self) -> CryptoResult<Ed25519PublicKey> {
+        Ed25519PublicKey::from_bytes(self.signing_key().verifying_key().as_bytes())
+    }
+
+    /// Expanded signing key; zeroized on drop by `ed25519-dalek`.
+    fn signing_key(&self) -> SigningKey {
+        SigningKey::from_bytes(&self.seed)
+    }
+}
+
+impl Ed25519Signature {
+    /// Size in bytes.
+    pub const SIZE: usize = 64;
+
+    /// Parse from the raw 64-byte RFC 8032 encoding.
+    pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        let bytes: [u8; 64] = bytes.try_into().map_err(|_| {
+            CryptoError::InvalidInput(format!("Expected {} byte signature, got {}", Self::SIZE, bytes.len()))
+        })?;
+        Ok(Ed25519Signature { bytes })
+    }
+
+    /// Raw RFC 8032 bytes, as expected by external verifiers.
+    pub fn as_bytes(&self) -> &[u8; 64] {
+        &self.bytes
+    }
+
+    /// Encode as `version || signature`.
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + Self::SIZE);
+        out.push(ED25519_SIGNATURE_VERSION);
+        out.extend_from_slice(&self.bytes);
+        out
+    }
+
+    /// Decode from `version || signature`.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        match bytes.split_first() {
+            Some((&ED25519_SIGNATURE_VERSION, signature)) => Self::from_bytes(signature),
+            Some((&version, _)) => Err(CryptoError::InvalidInput(format!(
+                "Unsupported Ed25519 signature version: 0x{:02x}", version
+            ))),
+            None => Err(CryptoError::InvalidInput("Empty Ed25519 signature".to_string())),
+        }
+    }
+}
+
+/// Generate an Ed25519 key pair
+pub fn keypair() -> CryptoResult<Ed25519KeyPair> {
+    let mut secret_key = Ed25519SecretKey { seed: [0u8; 32] };
+    fill_random(&mut secret_key.seed)?;
+    let public_key = secret_key.public_key()?;
+    Ok(Ed25519KeyPair { public_key, secret_key })
+}
+
+/// Sign a message (deterministic, RFC 8032)
+pub fn sign(secret_key: &Ed25519SecretKey, message: &[u8]) -> CryptoResult<Ed25519Signature> {
+    let signature = secret_key.signing_key().sign(message);
+    Ok(Ed25519Signature { bytes: signature.to_bytes() })
+}
+
+/// Verify an Ed25519 signature
+///
+/// Uses `ed25519-dalek`'s strict verification: non-canonical `S`, small-order
+/// public keys and small-order `R` are rejected, so a signature cannot be
+/// re-encoded into a second valid one.
+pub fn verify(
+    public_key: &Ed25519PublicKey,
+    message: &[u8],
+    signature: &Ed25519Signature,
+) -> CryptoResult<bool> {
+    let key = VerifyingKey::from_bytes(public_key.as_bytes())
+        .map_err(|e| CryptoError::InvalidInput(format!("Ed25519 public key rejected: {}", e)))?;
+    let signature = ed25519_dalek::Signature::from_bytes(signature.as_bytes());
+    Ok(key.verify_strict(message, &signature).is_ok())
+}
+
+impl fmt::Debug for Ed25519PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ed25519PublicKey({})", hex::encode(self.bytes))
+    }
+}
+
+impl fmt::Debug for Ed25519SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ed25519SecretKey([REDACTED])")
+    }
+}
+
+impl fmt::Debug for Ed25519Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ed25519Signature({})", hex::encode(self.bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        hex::decode(s).unwrap()
+    }
+
+    #[test]
+    fn test_rfc8032_known_answer() {
+        // RFC 8032 section 7.1, TEST 1 (empty message) and TEST 2 (one byte)
+        let vectors = [
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                "",
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+                 5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+                "72",
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+                 085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            ),
+        ];
+
+        for (secret, public, message, expected) in vectors {
+            let secret_key = Ed25519SecretKey::from_bytes(&unhex(secret)).unwrap();
+            let public_key = secret_key.public_key().unwrap();
+            assert_eq!(public_key.as_bytes().as_slice(), unhex(public).as_slice());
+
+            let message = unhex(message);
+            let signature = sign(&secret_key, &message).unwrap();
+            assert_eq!(signature.as_bytes().as_slice(), unhex(expected).as_slice());
+            assert!(verify(&public_key, &message, &signature).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_sign_verify_roundtrip() {
+        let pair = keypair().unwrap();
+        let manifest = b"b4ae-2.1.0.tar.gz sha3-256=...";
+        let signature = sign(&pair.secret_key, manifest).unwrap();
+
+        assert!(verify(&pair.public_key, manifest, &signature).unwrap());
+        assert!(!verify(&pair.public_key, b"tampered manifest", &signature).unwrap());
+
+        let other = keypair().unwrap();
+        assert!(!verify(&other.public_key, manifest, &signature).unwrap());
+
+        let mut forged = *signature.as_bytes();
+        forged[0] ^= 0x01;
+        let forged = Ed25519Signature::from_bytes(&forged).unwrap();
+        assert!(!verify(&pair.public_key, manifest, &forged).unwrap());
+
+        // S + L verifies under the group equation but is not canonical
+        const L: [u8; 32] = [
+            0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+        ];
+        let mut malleated = *signature.as_bytes();
+        let mut carry = 0u16;
+        for (s, l) in malleated[32..].iter_mut().zip(L) {
+            let sum = *s as u16 + l as u16 + carry;
+            *s = sum as u8;
+            carry = sum >> 8;
+        }
+        let malleated = Ed25519Signature::from_bytes(&malleated).unwrap();
+        assert!(!verify(&pair.public_key, manifest, &malleated).unwrap());
+    }
+
+    #[test]
+    fn test_versioned_serialization() {
+        let pair = keypair().unwrap();
+        let signature = sign(&pair.secret_key, b"payload").unwrap();
+
+        let encoded = signature.to_versioned_bytes();
+        assert_eq!(encoded.len(), 1 + Ed25519Signature::SIZE);
+        assert_eq!(Ed25519Signature::from_versioned_bytes(&encoded).unwrap(), signature);
+
+        let mut unknown = encoded.clone();
+        unknown[0] = 0x7F;
+        assert!(Ed25519Signature::from_versioned_bytes(&unknown).is_err());
+        assert!(Ed25519Signature::from_versioned_bytes(&encoded[..40]).is_err());
+        assert!(Ed25519Signature::from_versioned_bytes(&[]).is_err());
+
+        let public = Ed25519PublicKey::from_versioned_bytes(&pair.public_key.to_versioned_bytes()).unwrap();
+        assert_eq!(public, pair.public_key);
+        let secret = Ed25519SecretKey::from_versioned_bytes(&pair.secret_key.to_versioned_bytes()).unwrap();
+        assert_eq!(secret.as_bytes(), pair.secret_key.as_bytes());
+
+        // A public key container cannot be loaded as a secret key
+        assert!(Ed25519SecretKey::from_versioned_bytes(&pair.public_key.to_versioned_bytes()).is_err());
+        assert!(Ed25519PublicKey::from_bytes(&[0u8; 31]).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Safety Numbers and Key Fingerprints
+//!
+//! Out-of-band identity verification in the style of Signal safety numbers.
+//! Each party's identity key is hashed with iterated SHA3-512 into a 30-digit
+//! half; the two halves are sorted and concatenated into a 60-digit number, so
+//! both parties derive the same value regardless of who is "local".
+//!
+//! ```text
+//! k   = SHA3-512(key)                          (hybrid keys are several KB)
+//! h_0 = SHA3-512(VERSION || len(context) || context || k)
+//! h_i = SHA3-512(h_{i-1} || k)                for i in 1..=ITERATIONS
+//! half = 6 x (be_uint40(h[5j..5j+5]) mod 100000), zero-padded to 5 digits
+//! safety_number = min(half_a, half_b) || max(half_a, half_b)
+//! ```
+
+use crate::crypto::hybrid::HybridPublicKey;
+use sha3::{Digest, Sha3_256, Sha3_512};
+
+/// Fingerprint format version
+const FINGERPRINT_VERSION: [u8; 2] = [0x00, 0x01];
+
+/// Number of SHA3-512 iterations per key (slows down brute-force matching)
+pub const SAFETY_NUMBER_ITERATIONS: usize = 5200;
+
+/// Number of decimal digits in a safety number
+pub const SAFETY_NUMBER_DIGITS: usize = 60;
+
+/// Compute the 60-digit safety number for a pair of identity keys
+///
+/// The result is symmetric: `safety_number(a, b, ctx) == safety_number(b, a, ctx)`.
+///
+/// # Arguments
+/// * `local` - Local identity public key
+/// * `remote` - Remote identity public key
+/// * `context` - Application context (e.g. app or conversation identifier)
+pub fn safety_number(local: &HybridPublicKey, remote: &HybridPublicKey, context: &[u8]) -> String {
+    safety_number_from_bytes(&local.to_bytes(), &remote.to_bytes(), context)
+}
+
+/// Short hex fingerprint of a single identity key (32 hex characters)
+pub fn fingerprint(key: &HybridPublicKey) -> String {
+    fingerprint_from_bytes(&key.to_bytes())
+}
+
+/// Format a safety number into 12 groups of 5 digits for display
+pub fn format_safety_number(number: &str) -> String {
+    number
+        .as_bytes()
+        .chunks(5)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn safety_number_from_bytes(local: &[u8], remote: &[u8], context: &[u8]) -> String {
+    let local_half = displayable_half(local, context);
+    let remote_half = displayable_half(remote, context);
+
+    if local_half <= remote_half {
+        local_half + &remote_half
+    } else {
+        remote_half + &local_half
+    }
+}
+
+/// Full 32-byte fingerprint of an encoded identity key, for pinning
+///
+/// [`fingerprint`] displays the first 16 bytes of this digest.
+pub fn fingerprint_digest(key: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(b"B4AE-key-fingerprint");
+    hasher.update(FINGERPRINT_VERSION);
+    hasher.update(key);
+    hasher.finalize().into()
+}
+
+fn fingerprint_from_bytes(key: &[u8]) -> String {
+    hex::encode(&fingerprint_digest(key)[..16])
+}
+
+/// Iterated hash of one key rendered as 30 decimal digits
+fn displayable_half(key: &[u8], context: &[u8]) -> String {
+    let key_digest = Sha3_512::digest(key);
+
+    let mut hasher = Sha3_512::new();
+    hasher.update(FINGERPRINT_VERSION);
+    hasher.update((context.len() as u32).to_be_bytes());
+    hasher.update(context);
+    hasher.update(key_digest);
+    let mut hash = hasher.finalize();
+
+    for _ in 0..SAFETY_NUMBER_ITERATIONS {
+        let mut hasher = Sha3_512::new();
+        hasher.update(hash);
+        hasher.update(key_digest);
+        hash = hasher.finalize();
+    }
+
+    hash[..30]
+        .chunks(5)
+        .map(|chunk| {
+            let value = chunk.iter().fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+            format!("{:05}", value % 100_000)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hybrid;
+
+    #[test]
+    fn test_safety_number_symmetric() {
+        let alice = hybrid::keypair().unwrap();
+        let bob = hybrid::keypair().unwrap();
+
+        let from_alice = safety_number(&alice.public_key, &bob.public_key, b"b4ae-chat");
+        let from_bob = safety_number(&bob.public_key, &alice.public_key, b"b4ae-chat");
+
+        assert_eq!(from_alice, from_bob);
+        assert_eq!(from_alice.len(), SAFETY_NUMBER_DIGITS);
+        assert!(from_alice.bytes().all(|b| b.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_different_keys_or_context_differ() {
+        let alice = hybrid::keypair().unwrap();
+        let bob = hybrid::keypair().unwrap();
+        let mallory = hybrid::keypair().unwrap();
+
+        let genuine = safety_number(&alice.public_key, &bob.public_key, b"b4ae-chat");
+        let mitm = safety_number(&alice.public_key, &mallory.public_key, b"b4ae-chat");
+        let other_context = safety_number(&alice.public_key, &bob.public_key, b"other-app");
+
+        assert_ne!(genuine, mitm);
+        assert_ne!(genuine, other_context);
+        assert_ne!(fingerprint(&bob.public_key), fingerprint(&mallory.public_key));
+    }
+
+    #[test]
+    fn test_stable_known_answer() {
+        let number = safety_number_from_bytes(b"alice-identity-key", b"bob-identity-key", b"b4ae");
+        assert_eq!(number, safety_number_from_bytes(b"bob-identity-key", b"alice-identity-key", b"b4ae"));
+        assert_eq!(number, "174727162936737874865825104026548541377981805951672927974442");
+
+        let fp = fingerprint_from_bytes(b"alice-identity-key");
+        assert_eq!(fp.len(), 32);
+        assert_eq!(fp, "a88ad0beb0b113de867d95797f68d5b8");
+    }
+
+    #[test]
+    fn test_format_safety_number() {
+        let number = "0".repeat(SAFETY_NUMBER_DIGITS);
+        let formatted = format_safety_number(&number);
+        assert_eq!(formatted.split(' ').count(), 12);
+        assert!(formatted.split(' ').all(|group| group.len() == 5));
+    }
+}
@@ -4,6 +4,8 @@
 use crate::crypto::{CryptoError, CryptoResult};
 use hkdf::Hkdf;
 use sha3::Sha3_256;
+use alloc::{format, vec, vec::Vec};
+use zeroize::Zeroizing;
 
 /// Derive key using HKDF-SHA3-256
 /// 
@@ -16,8 +18,8 @@ pub fn derive_key(
     info: &[u8],
     output_length: usize,
 ) -> CryptoResult<Vec<u8>> {
-    // Concatenate all input key material
-    let mut ikm = Vec::new();
+    // Concatenate all input key material (wiped on drop)
+    let mut ikm = Zeroizing::new(Vec::new());
     for material in input_key_material {
         ikm.extend_from_slice(material);
     }
@@ -39,8 +41,8 @@ pub fn derive_key_with_salt(
     info: &[u8],
     output_length: usize,
 ) -> CryptoResult<Vec<u8>> {
-    // Concatenate all input key material
-    let mut ikm = Vec::new();
+    // Concatenate all input key material (wiped on drop)
+    let mut ikm = Zeroizing::new(Vec::new());
     for material in input_key_material {
         ikm.extend_from_slice(material);
     }
@@ -74,6 +76,32 @@ pub fn derive_multiple_keys(
     Ok(keys)
 }
 
+/// Size of a key-commitment tag in bytes
+pub const KEY_COMMITMENT_SIZE: usize = 32;
+
+/// Compute a key-commitment tag for an AEAD key
+///
+/// AES-GCM and ChaCha20-Poly1305 are not key-committing: one ciphertext can be
+/// crafted to authenticate under several keys. Prepending this tag and checking
+/// it before decryption binds a ciphertext to exactly one key.
+pub fn key_commitment(key: &[u8]) -> CryptoResult<[u8; KEY_COMMITMENT_SIZE]> {
+    let tag = derive_key(&[key], b"B4AE-key-commitment-v1", KEY_COMMITMENT_SIZE)?;
+    let mut commitment = [0u8; KEY_COMMITMENT_SIZE];
+    commitment.copy_from_slice(&tag);
+    Ok(commitment)
+}
+
+/// Verify a key-commitment tag in constant time
+pub fn verify_key_commitment(key: &[u8], commitment: &[u8]) -> CryptoResult<()> {
+    use subtle::ConstantTimeEq;
+
+    let expected = key_commitment(key)?;
+    if commitment.len() != KEY_COMMITMENT_SIZE || !bool::from(expected.ct_eq(commitment)) {
+        return Err(CryptoError::AuthenticationFailed);
+    }
+    Ok(())
+}
+
 /// B4AE-specific key derivation for protocol
 pub struct B4aeKeyDerivation {
     master_secret: Vec<u8>,
@@ -182,14 +210,14 @@ impl Drop for B4aeKeyDerivation {
     }
 }
 
-impl std::fmt::Debug for ProtocolKeys {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for ProtocolKeys {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "ProtocolKeys([REDACTED])")
     }
 }
 
-impl std::fmt::Debug for B4aeKeyDerivation {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for B4aeKeyDerivation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "B4aeKeyDerivation([REDACTED])")
     }
 }
@@ -198,6 +226,15 @@ impl std::fmt::Debug for B4aeKeyDerivation {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_key_commitment() {
+        let commitment = key_commitment(&[0x42; 32]).unwrap();
+        assert_eq!(commitment, key_commitment(&[0x42; 32]).unwrap());
+        assert!(verify_key_commitment(&[0x42; 32], &commitment).is_ok());
+        assert!(verify_key_commitment(&[0x43; 32], &commitment).is_err());
+        assert!(verify_key_commitment(&[0x42; 32], &commitment[..31]).is_err());
+    }
+
     #[test]
     fn test_derive_key() {
         let ikm = b"input key material";
@@ -253,7 +253,7 @@ impl HybridCiphertext {
 /// Generate hybrid key pair menggunakan ring crate untuk classical crypto
 pub fn keypair() -> CryptoResult<HybridKeyPair> {
     let rng = SystemRandom::new();
-    let mut csprng = rand::rngs::OsRng;
+    let mut csprng = crate::crypto::random::SecureRng::new();
     
     // Generate Kyber keypair (post-quantum)
     let kyber_keypair = kyber::keypair()?;
@@ -303,7 +303,7 @@ pub fn generate_keypair() -> CryptoResult<HybridKeyPair> {
 /// Hybrid key exchange (encapsulation)
 /// Menggunakan X25519 + Kyber untuk defense in depth
 pub fn encapsulate(public_key: &HybridPublicKey) -> CryptoResult<(Vec<u8>, HybridCiphertext)> {
-    let mut csprng = rand::rngs::OsRng;
+    let mut csprng = crate::crypto::random::SecureRng::new();
     
     // Kyber encapsulation (post-quantum)
     let (kyber_ss, kyber_ct) = kyber::encapsulate(&public_key.kyber_public)?;
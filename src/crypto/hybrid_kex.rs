@@ -31,6 +31,20 @@
 //! )
 //! ```
 //!
+//! # Transcript Hash
+//!
+//! Applications that run their own authentication on top of the hybrid KEX can
+//! bind to it through [`HybridKexCiphertext::transcript_hash`]:
+//! ```text
+//! transcript_hash = SHA3-256(
+//!     "B4AE-v2-hybrid-kex" ||
+//!     recipient_x25519_public || recipient_kyber_public ||
+//!     x25519_ephemeral || kyber_ciphertext
+//! )
+//! ```
+//! Every input is already sent in the clear, so the hash is public and safe to
+//! expose, log or sign.
+//!
 //! # Requirements
 //!
 //! - REQ-39: Hybrid key exchange combining classical and post-quantum algorithms
@@ -43,8 +57,9 @@ use crate::crypto::pq::{KyberKem, PqKem};
 use crate::crypto::kyber::{KyberPublicKey, KyberSecretKey, KyberCiphertext};
 use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey, StaticSecret as X25519StaticSecret};
 use sha2::Sha512;
+use sha3::{Digest, Sha3_256};
 use hkdf::Hkdf;
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 use std::fmt;
 
 /// X25519 public key size (32 bytes)
@@ -56,6 +71,12 @@ pub const X25519_SECRET_KEY_SIZE: usize = 32;
 /// Hybrid shared secret size (32 bytes)
 pub const HYBRID_SHARED_SECRET_SIZE: usize = 32;
 
+/// Transcript hash size (32 bytes)
+pub const TRANSCRIPT_HASH_SIZE: usize = 32;
+
+/// Protocol identifier used as HKDF salt and transcript hash prefix
+pub const HYBRID_KEX_PROTOCOL_ID: &[u8] = b"B4AE-v2-hybrid-kex";
+
 /// Hybrid public key containing both X25519 and Kyber1024 public keys
 #[derive(Clone, Debug)]
 pub struct HybridKexPublicKey {
@@ -129,6 +150,43 @@ impl HybridKexPublicKey {
     }
 }
 
+impl HybridKexSecretKey {
+    /// Serialize secret key to bytes
+    ///
+    /// Format: x25519_secret (32 bytes) || kyber_secret (3168 bytes)
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(Self::serialized_size()));
+        bytes.extend_from_slice(&self.x25519_secret);
+        bytes.extend_from_slice(self.kyber_secret.as_bytes());
+        bytes
+    }
+
+    /// Deserialize secret key from bytes
+    pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        if bytes.len() != Self::serialized_size() {
+            return Err(CryptoError::InvalidInput(
+                format!("Invalid hybrid secret key size: expected {}, got {}",
+                    Self::serialized_size(),
+                    bytes.len())
+            ));
+        }
+
+        let mut x25519_secret = [0u8; X25519_SECRET_KEY_SIZE];
+        x25519_secret.copy_from_slice(&bytes[..X25519_SECRET_KEY_SIZE]);
+        let kyber_secret = KyberSecretKey::from_bytes(&bytes[X25519_SECRET_KEY_SIZE..])?;
+
+        Ok(HybridKexSecretKey {
+            x25519_secret,
+            kyber_secret,
+        })
+    }
+
+    /// Get the total size of serialized secret key
+    pub const fn serialized_size() -> usize {
+        X25519_SECRET_KEY_SIZE + KyberSecretKey::SIZE
+    }
+}
+
 impl HybridKexCiphertext {
     /// Serialize ciphertext to bytes
     ///
@@ -166,6 +224,26 @@ impl HybridKexCiphertext {
     pub const fn serialized_size() -> usize {
         X25519_PUBLIC_KEY_SIZE + KyberCiphertext::SIZE
     }
+
+    /// Transcript hash of the exchange that produced this ciphertext
+    ///
+    /// SHA3-256 over the protocol id, the recipient's public key and this
+    /// ciphertext, in that order. Both parties hold the same inputs, so
+    /// encapsulator and decapsulator compute the same value.
+    ///
+    /// # Security
+    ///
+    /// The hash covers only public values and reveals nothing about the
+    /// shared secret; it is safe to expose to the application layer.
+    pub fn transcript_hash(&self, recipient: &HybridKexPublicKey) -> [u8; TRANSCRIPT_HASH_SIZE] {
+        let mut hasher = Sha3_256::new();
+        hasher.update(HYBRID_KEX_PROTOCOL_ID);
+        hasher.update(recipient.x25519_public);
+        hasher.update(recipient.kyber_public.as_bytes());
+        hasher.update(self.x25519_ephemeral);
+        hasher.update(self.kyber_ciphertext.as_bytes());
+        hasher.finalize().into()
+    }
 }
 
 /// Generate a hybrid keypair for key exchange
@@ -184,7 +262,7 @@ impl HybridKexCiphertext {
 /// - Kyber1024 keygen: ~0.1ms
 /// - Total: ~0.11ms
 pub fn generate_keypair() -> CryptoResult<HybridKexKeyPair> {
-    let mut csprng = rand::rngs::OsRng;
+    let mut csprng = crate::crypto::random::SecureRng::new();
 
     // Generate X25519 static secret for key exchange
     let x25519_static = X25519StaticSecret::random_from_rng(&mut csprng);
@@ -236,7 +314,7 @@ pub fn generate_keypair() -> CryptoResult<HybridKexKeyPair> {
 /// - HKDF: ~0.01ms
 /// - Total: ~0.16ms
 pub fn encapsulate(public_key: &HybridKexPublicKey) -> CryptoResult<([u8; HYBRID_SHARED_SECRET_SIZE], HybridKexCiphertext)> {
-    let mut csprng = rand::rngs::OsRng;
+    let mut csprng = crate::crypto::random::SecureRng::new();
 
     // 1. X25519 ephemeral key generation and ECDH
     let x25519_ephemeral = EphemeralSecret::random_from_rng(&mut csprng);
@@ -344,8 +422,7 @@ fn combine_shared_secrets(
     ikm.extend_from_slice(kyber_shared);
 
     // HKDF-SHA512 with domain separation
-    let salt = b"B4AE-v2-hybrid-kex";
-    let hkdf = Hkdf::<Sha512>::new(Some(salt), &ikm);
+    let hkdf = Hkdf::<Sha512>::new(Some(HYBRID_KEX_PROTOCOL_ID), &ikm);
 
     let mut output = [0u8; HYBRID_SHARED_SECRET_SIZE];
     hkdf.expand(b"", &mut output)
@@ -440,6 +517,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_secret_key_serialization() {
+        let keypair = generate_keypair().expect("Failed to generate keypair");
+        let (shared, ciphertext) = encapsulate(&keypair.public_key).expect("Failed to encapsulate");
+
+        let bytes = keypair.secret_key.to_bytes();
+        assert_eq!(bytes.len(), HybridKexSecretKey::serialized_size());
+        let restored = HybridKexSecretKey::from_bytes(&bytes)
+            .expect("Failed to deserialize secret key");
+
+        assert_eq!(decapsulate(&restored, &ciphertext).unwrap(), shared);
+        assert!(HybridKexSecretKey::from_bytes(&bytes[1..]).is_err());
+    }
+
     #[test]
     fn test_ciphertext_serialization() {
         let bob = generate_keypair().expect("Failed to generate keypair");
@@ -493,6 +584,31 @@ mod tests {
         assert_eq!(shared2, bob_shared2);
     }
 
+    #[test]
+    fn test_transcript_hash_binding() {
+        let bob = generate_keypair().expect("Failed to generate Bob's keypair");
+        let (_, ciphertext) = encapsulate(&bob.public_key).expect("Failed to encapsulate");
+
+        // Alice knows Bob's public key and the ciphertext she sent; Bob
+        // parses the ciphertext off the wire
+        let alice_hash = ciphertext.transcript_hash(&bob.public_key);
+        let received = HybridKexCiphertext::from_bytes(&ciphertext.to_bytes()).unwrap();
+        let bob_hash = received.transcript_hash(&bob.public_key);
+        assert_eq!(alice_hash, bob_hash);
+
+        // Swapping Alice's ephemeral key changes the transcript
+        let (_, other_ciphertext) = encapsulate(&bob.public_key).expect("Failed to encapsulate");
+        let mut swapped = ciphertext.clone();
+        swapped.x25519_ephemeral = other_ciphertext.x25519_ephemeral;
+        assert_ne!(swapped.transcript_hash(&bob.public_key), alice_hash);
+
+        // Swapping Bob's key changes the transcript
+        let carol = generate_keypair().expect("Failed to generate Carol's keypair");
+        let mut swapped_recipient = bob.public_key.clone();
+        swapped_recipient.x25519_public = carol.public_key.x25519_public;
+        assert_ne!(ciphertext.transcript_hash(&swapped_recipient), alice_hash);
+    }
+
     #[test]
     fn test_serialized_sizes() {
         assert_eq!(
@@ -0,0 +1,107 @@
+//! Byte-level Kyber-1024 (ML-KEM-1024) KEM for custom protocols
+//!
+//! [`encapsulate`] and [`decapsulate`] take and return raw bytes, so callers
+//! need not touch the [`kyber`] key types. Shared secrets come back as
+//! [`Zeroizing`] arrays and are wiped when dropped. Key pairs come from
+//! [`kyber::keypair`].
+//!
+//! ```
+//! use b4ae::crypto::{self, kyber};
+//!
+//! let keypair = kyber::keypair()?;
+//! let (ciphertext, sender_secret) = crypto::encapsulate(keypair.public_key.as_bytes())?;
+//! let receiver_secret = crypto::decapsulate(keypair.secret_key.as_bytes(), &ciphertext)?;
+//! assert_eq!(*sender_secret, *receiver_secret);
+//! # Ok::<(), b4ae::crypto::CryptoError>(())
+//! ```
+
+use crate::crypto::kyber::{self, KyberCiphertext, KyberPublicKey, KyberSecretKey, KyberSharedSecret};
+use crate::crypto::{CryptoError, CryptoResult};
+use zeroize::Zeroizing;
+
+/// Encapsulate a fresh shared secret to `public_key`
+///
+/// Returns `(ciphertext, shared_secret)`; send the ciphertext
+/// ([`KyberCiphertext::SIZE`] bytes) to the key owner.
+///
+/// # Errors
+///
+/// `CryptoError::InvalidKeySize` if `public_key` is not
+/// [`KyberPublicKey::SIZE`] bytes.
+pub fn encapsulate(public_key: &[u8]) -> CryptoResult<(Vec<u8>, Zeroizing<[u8; 32]>)> {
+    let public_key = KyberPublicKey::from_bytes(public_key)?;
+    let (shared_secret, ciphertext) = kyber::encapsulate(&public_key)?;
+    Ok((ciphertext.as_bytes().to_vec(), to_zeroizing(&shared_secret)?))
+}
+
+/// Recover the shared secret from `ciphertext` with `secret_key`
+///
+/// ML-KEM decapsulation rejects implicitly: a well-formed but wrong
+/// ciphertext yields an unrelated secret rather than an error, so callers
+/// must confirm the secret (e.g. by decrypting with a key derived from it).
+///
+/// # Errors
+///
+/// `CryptoError::InvalidInput` if `ciphertext` is not
+/// [`KyberCiphertext::SIZE`] bytes, `CryptoError::InvalidKeySize` if
+/// `secret_key` is not [`KyberSecretKey::SIZE`] bytes.
+pub fn decapsulate(secret_key: &[u8], ciphertext: &[u8]) -> CryptoResult<Zeroizing<[u8; 32]>> {
+    if ciphertext.len() != KyberCiphertext::SIZE {
+        return Err(CryptoError::InvalidInput(format!(
+            "Kyber ciphertext must be {} bytes, got {}",
+            KyberCiphertext::SIZE,
+            ciphertext.len()
+        )));
+    }
+    let ciphertext = KyberCiphertext::from_bytes(ciphertext)?;
+    let secret_key = KyberSecretKey::from_bytes(secret_key)?;
+    to_zeroizing(&kyber::decapsulate(&secret_key, &ciphertext)?)
+}
+
+fn to_zeroizing(shared_secret: &KyberSharedSecret) -> CryptoResult<Zeroizing<[u8; 32]>> {
+    let bytes = shared_secret.as_bytes();
+    let mut out = Zeroizing::new([0u8; KyberSharedSecret::SIZE]);
+    if bytes.len() != out.len() {
+        return Err(CryptoError::InvalidKeySize(format!(
+            "Expected {} byte shared secret, got {}",
+            KyberSharedSecret::SIZE,
+            bytes.len()
+        )));
+    }
+    out.copy_from_slice(bytes);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encapsulate_decapsulate_roundtrip() {
+        let keypair = kyber::keypair().unwrap();
+        let (ciphertext, sender) = encapsulate(keypair.public_key.as_bytes()).unwrap();
+        assert_eq!(ciphertext.len(), KyberCiphertext::SIZE);
+
+        let receiver = decapsulate(keypair.secret_key.as_bytes(), &ciphertext).unwrap();
+        assert_eq!(*sender, *receiver);
+
+        let (_, other) = encapsulate(keypair.public_key.as_bytes()).unwrap();
+        assert_ne!(*sender, *other);
+    }
+
+    #[test]
+    fn test_wrong_length_ciphertext_rejected() {
+        let keypair = kyber::keypair().unwrap();
+        let (ciphertext, _) = encapsulate(keypair.public_key.as_bytes()).unwrap();
+        let sk = keypair.secret_key.as_bytes();
+
+        for bad in [&ciphertext[..ciphertext.len() - 1], &[][..], &[0u8; KyberCiphertext::SIZE + 1][..]] {
+            assert!(matches!(decapsulate(sk, bad), Err(CryptoError::InvalidInput(_))));
+        }
+        assert!(matches!(
+            decapsulate(&sk[..100], &ciphertext),
+            Err(CryptoError::InvalidKeySize(_))
+        ));
+        assert!(matches!(encapsulate(&[0u8; 32]), Err(CryptoError::InvalidKeySize(_))));
+    }
+}
@@ -0,0 +1,366 @@
+//! Versioned Key Containers
+//!
+//! Self-describing encoding for storing post-quantum keys, so applications do
+//! not have to invent their own framing:
+//!
+//! ```text
+//! container = magic "B4KY" (4) || key_type (1) || algorithm_id (2, BE) || length (4, BE) || key
+//! ```
+//!
+//! Every field is validated on load: magic, key type, algorithm id, declared
+//! length against the algorithm's key size, and the exact buffer length.
+//! Secret-key containers are produced and parsed through [`Zeroizing`] buffers.
+
+use crate::crypto::{CryptoError, CryptoResult};
+use crate::crypto::dilithium::{DilithiumPublicKey, DilithiumSecretKey};
+use crate::crypto::ed25519::{Ed25519PublicKey, Ed25519SecretKey};
+use crate::crypto::kyber::{KyberPublicKey, KyberSecretKey, KyberVariant, VariantPublicKey, VariantSecretKey};
+use zeroize::Zeroizing;
+
+/// Container magic
+pub const KEY_CONTAINER_MAGIC: [u8; 4] = *b"B4KY";
+
+/// Header size: magic + key type + algorithm id + length
+pub const KEY_CONTAINER_HEADER_SIZE: usize = 4 + 1 + 2 + 4;
+
+/// Kind of key held in a container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum KeyType {
+    /// Public key
+    Public = 0x01,
+    /// Secret key
+    Secret = 0x02,
+}
+
+impl KeyType {
+    fn from_u8(value: u8) -> CryptoResult<Self> {
+        match value {
+            0x01 => Ok(KeyType::Public),
+            0x02 => Ok(KeyType::Secret),
+            other => Err(CryptoError::InvalidInput(format!("Unknown key type: 0x{:02x}", other))),
+        }
+    }
+}
+
+/// Algorithm identifiers stored in containers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+pub enum KeyAlgorithm {
+    /// Kyber-512 / ML-KEM-512
+    Kyber512 = 0x0101,
+    /// Kyber-768 / ML-KEM-768
+    Kyber768 = 0x0102,
+    /// Kyber-1024 / ML-KEM-1024
+    Kyber1024 = 0x0103,
+    /// Dilithium5 / ML-DSA-87
+    Dilithium5 = 0x0201,
+    /// Ed25519 (RFC 8032)
+    Ed25519 = 0x0301,
+}
+
+impl KeyAlgorithm {
+    /// Parse an algorithm id
+    pub fn from_u16(value: u16) -> CryptoResult<Self> {
+        match value {
+            0x0101 => Ok(KeyAlgorithm::Kyber512),
+            0x0102 => Ok(KeyAlgorithm::Kyber768),
+            0x0103 => Ok(KeyAlgorithm::Kyber1024),
+            0x0201 => Ok(KeyAlgorithm::Dilithium5),
+            0x0301 => Ok(KeyAlgorithm::Ed25519),
+            other => Err(CryptoError::InvalidInput(format!("Unknown key algorithm id: 0x{:04x}", other))),
+        }
+    }
+
+    /// Algorithm id for a Kyber parameter set
+    pub fn from_kyber_variant(variant: KyberVariant) -> Self {
+        match variant {
+            KyberVariant::Kyber512 => KeyAlgorithm::Kyber512,
+            KyberVariant::Kyber768 => KeyAlgorithm::Kyber768,
+            KyberVariant::Kyber1024 => KeyAlgorithm::Kyber1024,
+        }
+    }
+
+    /// Kyber parameter set, if this is a KEM algorithm
+    pub fn kyber_variant(self) -> Option<KyberVariant> {
+        match self {
+            KeyAlgorithm::Kyber512 => Some(KyberVariant::Kyber512),
+            KeyAlgorithm::Kyber768 => Some(KyberVariant::Kyber768),
+            KeyAlgorithm::Kyber1024 => Some(KyberVariant::Kyber1024),
+            KeyAlgorithm::Dilithium5 | KeyAlgorithm::Ed25519 => None,
+        }
+    }
+
+    /// Expected key length for the given key type
+    pub fn key_size(self, key_type: KeyType) -> usize {
+        match (self.kyber_variant(), key_type) {
+            (Some(variant), KeyType::Public) => variant.public_key_size(),
+            (Some(variant), KeyType::Secret) => variant.secret_key_size(),
+            (None, KeyType::Public) if self == KeyAlgorithm::Ed25519 => Ed25519PublicKey::SIZE,
+            (None, KeyType::Secret) if self == KeyAlgorithm::Ed25519 => Ed25519SecretKey::SIZE,
+            (None, KeyType::Public) => DilithiumPublicKey::SIZE,
+            (None, KeyType::Secret) => DilithiumSecretKey::BACKEND_SIZE,
+        }
+    }
+}
+
+fn encode_header(out: &mut Vec<u8>, key_type: KeyType, algorithm: KeyAlgorithm, len: usize) {
+    out.extend_from_slice(&KEY_CONTAINER_MAGIC);
+    out.push(key_type as u8);
+    out.extend_from_slice(&(algorithm as u16).to_be_bytes());
+    out.extend_from_slice(&(len as u32).to_be_bytes());
+}
+
+fn encode_public(algorithm: KeyAlgorithm, key: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(KEY_CONTAINER_HEADER_SIZE + key.len());
+    encode_header(&mut out, KeyType::Public, algorithm, key.len());
+    out.extend_from_slice(key);
+    out
+}
+
+fn encode_secret(algorithm: KeyAlgorithm, key: &[u8]) -> Zeroizing<Vec<u8>> {
+    let mut out = Zeroizing::new(Vec::with_capacity(KEY_CONTAINER_HEADER_SIZE + key.len()));
+    encode_header(&mut out, KeyType::Secret, algorithm, key.len());
+    out.extend_from_slice(key);
+    out
+}
+
+/// Parse and validate a container header, returning the algorithm and key bytes
+pub fn decode_container(bytes: &[u8], expected_type: KeyType) -> CryptoResult<(KeyAlgorithm, &[u8])> {
+    if bytes.len() < KEY_CONTAINER_HEADER_SIZE {
+        return Err(CryptoError::InvalidInput(format!(
+            "Key container too short: {} bytes, minimum {}",
+            bytes.len(), KEY_CONTAINER_HEADER_SIZE
+        )));
+    }
+    if bytes[..4] != KEY_CONTAINER_MAGIC {
+        return Err(CryptoError::InvalidInput("Invalid key container magic".to_string()));
+    }
+
+    let key_type = KeyType::from_u8(bytes[4])?;
+    if key_type != expected_type {
+        return Err(CryptoError::InvalidInput(format!(
+            "Key type mismatch: expected {:?}, got {:?}", expected_type, key_type
+        )));
+    }
+
+    let algorithm = KeyAlgorithm::from_u16(u16::from_be_bytes([bytes[5], bytes[6]]))?;
+    let declared_len = u32::from_be_bytes([bytes[7], bytes[8], bytes[9], bytes[10]]) as usize;
+    let expected_len = algorithm.key_size(key_type);
+    if declared_len != expected_len {
+        return Err(CryptoError::InvalidKeySize(format!(
+            "{:?} key length: expected {}, declared {}", algorithm, expected_len, declared_len
+        )));
+    }
+
+    let key = &bytes[KEY_CONTAINER_HEADER_SIZE..];
+    if key.len() != declared_len {
+        return Err(CryptoError::InvalidInput(format!(
+            "Key container length mismatch: declared {}, got {}", declared_len, key.len()
+        )));
+    }
+
+    Ok((algorithm, key))
+}
+
+fn decode_expected(bytes: &[u8], key_type: KeyType, expected: KeyAlgorithm) -> CryptoResult<&[u8]> {
+    let (algorithm, key) = decode_container(bytes, key_type)?;
+    if algorithm != expected {
+        return Err(CryptoError::InvalidInput(format!(
+            "Key algorithm mismatch: expected {:?}, got {:?}", expected, algorithm
+        )));
+    }
+    Ok(key)
+}
+
+fn decode_secret(bytes: &[u8], expected: KeyAlgorithm) -> CryptoResult<Zeroizing<Vec<u8>>> {
+    decode_expected(bytes, KeyType::Secret, expected).map(|key| Zeroizing::new(key.to_vec()))
+}
+
+impl KyberPublicKey {
+    /// Encode as a versioned key container.
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        encode_public(KeyAlgorithm::Kyber1024, self.as_bytes())
+    }
+
+    /// Decode from a versioned key container.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        Self::from_bytes(decode_expected(bytes, KeyType::Public, KeyAlgorithm::Kyber1024)?)
+    }
+}
+
+impl KyberSecretKey {
+    /// Encode as a versioned key container (zeroized on drop).
+    pub fn to_versioned_bytes(&self) -> Zeroizing<Vec<u8>> {
+        encode_secret(KeyAlgorithm::Kyber1024, self.as_bytes())
+    }
+
+    /// Decode from a versioned key container.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        let key = decode_secret(bytes, KeyAlgorithm::Kyber1024)?;
+        Self::from_bytes(&key)
+    }
+}
+
+impl VariantPublicKey {
+    /// Encode as a versioned key container; the algorithm id records the variant.
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        encode_public(KeyAlgorithm::from_kyber_variant(self.variant()), self.as_bytes())
+    }
+
+    /// Decode from a versioned key container of any Kyber variant.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        let (algorithm, key) = decode_container(bytes, KeyType::Public)?;
+        let variant = algorithm.kyber_variant()
+            .ok_or_else(|| CryptoError::InvalidInput(format!("{:?} is not a Kyber algorithm", algorithm)))?;
+        Self::from_bytes(variant, key)
+    }
+}
+
+impl VariantSecretKey {
+    /// Encode as a versioned key container (zeroized on drop).
+    pub fn to_versioned_bytes(&self) -> Zeroizing<Vec<u8>> {
+        encode_secret(KeyAlgorithm::from_kyber_variant(self.variant()), self.as_bytes())
+    }
+
+    /// Decode from a versioned key container of any Kyber variant.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        let (algorithm, key) = decode_container(bytes, KeyType::Secret)?;
+        let variant = algorithm.kyber_variant()
+            .ok_or_else(|| CryptoError::InvalidInput(format!("{:?} is not a Kyber algorithm", algorithm)))?;
+        let key = Zeroizing::new(key.to_vec());
+        Self::from_bytes(variant, &key)
+    }
+}
+
+impl DilithiumPublicKey {
+    /// Encode as a versioned key container.
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        encode_public(KeyAlgorithm::Dilithium5, self.as_bytes())
+    }
+
+    /// Decode from a versioned key container.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        Self::from_bytes(decode_expected(bytes, KeyType::Public, KeyAlgorithm::Dilithium5)?)
+    }
+}
+
+impl DilithiumSecretKey {
+    /// Encode as a versioned key container (zeroized on drop).
+    pub fn to_versioned_bytes(&self) -> Zeroizing<Vec<u8>> {
+        encode_secret(KeyAlgorithm::Dilithium5, self.as_bytes())
+    }
+
+    /// Decode from a versioned key container.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        let key = decode_secret(bytes, KeyAlgorithm::Dilithium5)?;
+        Self::from_bytes(&key)
+    }
+}
+
+impl Ed25519PublicKey {
+    /// Encode as a versioned key container.
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        encode_public(KeyAlgorithm::Ed25519, self.as_bytes())
+    }
+
+    /// Decode from a versioned key container.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        Self::from_bytes(decode_expected(bytes, KeyType::Public, KeyAlgorithm::Ed25519)?)
+    }
+}
+
+impl Ed25519SecretKey {
+    /// Encode as a versioned key container (zeroized on drop).
+    pub fn to_versioned_bytes(&self) -> Zeroizing<Vec<u8>> {
+        encode_secret(KeyAlgorithm::Ed25519, self.as_bytes())
+    }
+
+    /// Decode from a versioned key container.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        let key = decode_secret(bytes, KeyAlgorithm::Ed25519)?;
+        Self::from_bytes(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{dilithium, kyber};
+
+    #[test]
+    fn test_kyber_roundtrip() {
+        let keypair = kyber::keypair().unwrap();
+
+        let public = keypair.public_key.to_versioned_bytes();
+        assert_eq!(public.len(), KEY_CONTAINER_HEADER_SIZE + KyberPublicKey::SIZE);
+        let restored = KyberPublicKey::from_versioned_bytes(&public).unwrap();
+        assert_eq!(restored.as_bytes(), keypair.public_key.as_bytes());
+
+        let secret = keypair.secret_key.to_versioned_bytes();
+        let restored = KyberSecretKey::from_versioned_bytes(&secret).unwrap();
+        assert_eq!(restored.as_bytes(), keypair.secret_key.as_bytes());
+
+        // A public container is not a secret container
+        assert!(KyberSecretKey::from_versioned_bytes(&public).is_err());
+    }
+
+    #[test]
+    fn test_dilithium_and_variant_roundtrip() {
+        let keypair = dilithium::keypair().unwrap();
+        let public = keypair.public_key.to_versioned_bytes();
+        let secret = keypair.secret_key.to_versioned_bytes();
+        assert_eq!(DilithiumPublicKey::from_versioned_bytes(&public).unwrap().as_bytes(), keypair.public_key.as_bytes());
+        assert_eq!(DilithiumSecretKey::from_versioned_bytes(&secret).unwrap().as_bytes(), keypair.secret_key.as_bytes());
+
+        let kp768 = kyber::keypair_with(KyberVariant::Kyber768).unwrap();
+        let public = kp768.public_key.to_versioned_bytes();
+        let restored = VariantPublicKey::from_versioned_bytes(&public).unwrap();
+        assert_eq!(restored, kp768.public_key);
+        let restored = VariantSecretKey::from_versioned_bytes(&kp768.secret_key.to_versioned_bytes()).unwrap();
+        assert_eq!(restored.variant(), KyberVariant::Kyber768);
+        assert_eq!(restored.as_bytes(), kp768.secret_key.as_bytes());
+    }
+
+    #[test]
+    fn test_truncated_container_rejected() {
+        let keypair = kyber::keypair().unwrap();
+        let public = keypair.public_key.to_versioned_bytes();
+        let secret = keypair.secret_key.to_versioned_bytes();
+
+        assert!(KyberPublicKey::from_versioned_bytes(&public[..public.len() - 1]).is_err());
+        assert!(KyberPublicKey::from_versioned_bytes(&public[..KEY_CONTAINER_HEADER_SIZE - 1]).is_err());
+        assert!(KyberSecretKey::from_versioned_bytes(&secret[..secret.len() - 1]).is_err());
+
+        let mut extended = public.clone();
+        extended.push(0);
+        assert!(KyberPublicKey::from_versioned_bytes(&extended).is_err());
+    }
+
+    #[test]
+    fn test_wrong_algorithm_or_header_rejected() {
+        let keypair = kyber::keypair().unwrap();
+        let public = keypair.public_key.to_versioned_bytes();
+
+        // Kyber container presented as Dilithium
+        assert!(DilithiumPublicKey::from_versioned_bytes(&public).is_err());
+
+        // Unknown algorithm id
+        let mut unknown = public.clone();
+        unknown[5..7].copy_from_slice(&0x7777u16.to_be_bytes());
+        assert!(KyberPublicKey::from_versioned_bytes(&unknown).is_err());
+
+        // Algorithm id relabelled to Kyber-768 no longer matches the length
+        let mut relabelled = public.clone();
+        relabelled[5..7].copy_from_slice(&(KeyAlgorithm::Kyber768 as u16).to_be_bytes());
+        assert!(VariantPublicKey::from_versioned_bytes(&relabelled).is_err());
+
+        let mut bad_magic = public.clone();
+        bad_magic[0] ^= 0xff;
+        assert!(KyberPublicKey::from_versioned_bytes(&bad_magic).is_err());
+
+        let mut bad_type = public;
+        bad_type[4] = 0x09;
+        assert!(KyberPublicKey::from_versioned_bytes(&bad_type).is_err());
+    }
+}
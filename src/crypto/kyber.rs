@@ -1,5 +1,9 @@
-// B4AE Kyber-1024 Implementation
+// B4AE Kyber Implementation (Kyber-1024 default, 512/768 via KyberVariant)
 // Post-Quantum Key Encapsulation Mechanism
+//
+// Backend: `pqcrypto-mlkem` (FIPS 203 ML-KEM, default) or `pqcrypto-kyber`
+// (legacy round-3 Kyber). The two are not wire compatible; see
+// `crypto::pq` for the standardized ML-KEM API and the details.
 
 use crate::crypto::{CryptoError, CryptoResult};
 use std::fmt;
@@ -247,6 +251,7 @@ pub struct KyberKeyPair {
 
 /// Generate Kyber-1024 key pair
 pub fn keypair() -> CryptoResult<KyberKeyPair> {
+    super::self_test::ensure_operational()?;
     #[cfg(feature = "liboqs")]
     {
         use oqs::kem::{Kem, Algorithm};
@@ -289,6 +294,7 @@ pub fn keypair() -> CryptoResult<KyberKeyPair> {
 
 /// Encapsulate: Generate shared secret and ciphertext
 pub fn encapsulate(public_key: &KyberPublicKey) -> CryptoResult<(KyberSharedSecret, KyberCiphertext)> {
+    super::self_test::ensure_operational()?;
     #[cfg(feature = "liboqs")]
     {
         use oqs::kem::{Kem, Algorithm, PublicKey};
@@ -337,6 +343,7 @@ pub fn decapsulate(
     secret_key: &KyberSecretKey,
     ciphertext: &KyberCiphertext,
 ) -> CryptoResult<KyberSharedSecret> {
+    super::self_test::ensure_operational()?;
     #[cfg(feature = "liboqs")]
     {
         use oqs::kem::{Kem, Algorithm, SecretKey, Ciphertext};
@@ -374,6 +381,328 @@ pub fn decapsulate(
     ))
 }
 
+/// Kyber/ML-KEM parameter set
+///
+/// The fixed-size types above are Kyber-1024; the `*_with` functions accept
+/// any variant and carry it alongside the key material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum KyberVariant {
+    /// Kyber-512 / ML-KEM-512 (NIST Level 1)
+    Kyber512,
+    /// Kyber-768 / ML-KEM-768 (NIST Level 3)
+    Kyber768,
+    /// Kyber-1024 / ML-KEM-1024 (NIST Level 5)
+    #[default]
+    Kyber1024,
+}
+
+impl KyberVariant {
+    /// Public key size in bytes.
+    pub const fn public_key_size(self) -> usize {
+        match self {
+            KyberVariant::Kyber512 => 800,
+            KyberVariant::Kyber768 => 1184,
+            KyberVariant::Kyber1024 => 1568,
+        }
+    }
+
+    /// Secret key size in bytes.
+    pub const fn secret_key_size(self) -> usize {
+        match self {
+            KyberVariant::Kyber512 => 1632,
+            KyberVariant::Kyber768 => 2400,
+            KyberVariant::Kyber1024 => 3168,
+        }
+    }
+
+    /// Ciphertext size in bytes.
+    pub const fn ciphertext_size(self) -> usize {
+        match self {
+            KyberVariant::Kyber512 => 768,
+            KyberVariant::Kyber768 => 1088,
+            KyberVariant::Kyber1024 => 1568,
+        }
+    }
+
+    /// Shared secret size in bytes (identical for all variants).
+    pub const fn shared_secret_size(self) -> usize {
+        KyberSharedSecret::SIZE
+    }
+
+    #[cfg(feature = "liboqs")]
+    fn oqs_algorithm(self) -> oqs::kem::Algorithm {
+        match self {
+            KyberVariant::Kyber512 => oqs::kem::Algorithm::Kyber512,
+            KyberVariant::Kyber768 => oqs::kem::Algorithm::Kyber768,
+            KyberVariant::Kyber1024 => oqs::kem::Algorithm::Kyber1024,
+        }
+    }
+}
+
+/// Public key for a specific Kyber variant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantPublicKey {
+    variant: KyberVariant,
+    bytes: Vec<u8>,
+}
+
+/// Secret key for a specific Kyber variant (zeroized on drop)
+pub struct VariantSecretKey {
+    variant: KyberVariant,
+    bytes: zeroize::Zeroizing<Vec<u8>>,
+}
+
+/// Ciphertext for a specific Kyber variant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantCiphertext {
+    variant: KyberVariant,
+    bytes: Vec<u8>,
+}
+
+/// Key pair for a specific Kyber variant
+pub struct VariantKeyPair {
+    /// Public key for encapsulation.
+    pub public_key: VariantPublicKey,
+    /// Secret key for decapsulation.
+    pub secret_key: VariantSecretKey,
+}
+
+fn check_variant_size(what: &str, expected: usize, bytes: &[u8]) -> CryptoResult<()> {
+    if bytes.len() != expected {
+        return Err(CryptoError::InvalidKeySize(
+            format!("{}: expected {} bytes, got {}", what, expected, bytes.len())
+        ));
+    }
+    Ok(())
+}
+
+impl VariantPublicKey {
+    /// Parse dari raw bytes untuk varian tertentu.
+    pub fn from_bytes(variant: KyberVariant, bytes: &[u8]) -> CryptoResult<Self> {
+        check_variant_size("Kyber public key", variant.public_key_size(), bytes)?;
+        Ok(VariantPublicKey { variant, bytes: bytes.to_vec() })
+    }
+
+    /// Parameter set of this key.
+    pub fn variant(&self) -> KyberVariant {
+        self.variant
+    }
+
+    /// Serialisasi ke bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl VariantSecretKey {
+    /// Parse dari raw bytes untuk varian tertentu.
+    pub fn from_bytes(variant: KyberVariant, bytes: &[u8]) -> CryptoResult<Self> {
+        check_variant_size("Kyber secret key", variant.secret_key_size(), bytes)?;
+        Ok(VariantSecretKey { variant, bytes: zeroize::Zeroizing::new(bytes.to_vec()) })
+    }
+
+    /// Parameter set of this key.
+    pub fn variant(&self) -> KyberVariant {
+        self.variant
+    }
+
+    /// Serialisasi ke bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl VariantCiphertext {
+    /// Parse dari raw bytes untuk varian tertentu.
+    pub fn from_bytes(variant: KyberVariant, bytes: &[u8]) -> CryptoResult<Self> {
+        check_variant_size("Kyber ciphertext", variant.ciphertext_size(), bytes)?;
+        Ok(VariantCiphertext { variant, bytes: bytes.to_vec() })
+    }
+
+    /// Parameter set of this ciphertext.
+    pub fn variant(&self) -> KyberVariant {
+        self.variant
+    }
+
+    /// Serialisasi ke bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Debug for VariantSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VariantSecretKey({:?}, [REDACTED])", self.variant)
+    }
+}
+
+/// Run `$body` with `$kem` bound to the pqcrypto module for `$variant`
+#[cfg(all(not(feature = "liboqs"), feature = "pqcrypto-mlkem"))]
+macro_rules! with_kem_module {
+    ($variant:expr, $kem:ident => $body:block) => {
+        match $variant {
+            KyberVariant::Kyber512 => { use pqcrypto_mlkem::mlkem512 as $kem; $body }
+            KyberVariant::Kyber768 => { use pqcrypto_mlkem::mlkem768 as $kem; $body }
+            KyberVariant::Kyber1024 => { use pqcrypto_mlkem::mlkem1024 as $kem; $body }
+        }
+    };
+}
+
+/// Run `$body` with `$kem` bound to the pqcrypto module for `$variant`
+#[cfg(all(not(feature = "liboqs"), any(feature = "pqcrypto-kyber", feature = "pqcrypto-alt"), not(feature = "pqcrypto-mlkem")))]
+macro_rules! with_kem_module {
+    ($variant:expr, $kem:ident => $body:block) => {
+        match $variant {
+            KyberVariant::Kyber512 => { use pqcrypto_kyber::kyber512 as $kem; $body }
+            KyberVariant::Kyber768 => { use pqcrypto_kyber::kyber768 as $kem; $body }
+            KyberVariant::Kyber1024 => { use pqcrypto_kyber::kyber1024 as $kem; $body }
+        }
+    };
+}
+
+/// Generate a key pair for the given Kyber variant
+pub fn keypair_with(variant: KyberVariant) -> CryptoResult<VariantKeyPair> {
+    super::self_test::ensure_operational()?;
+    #[cfg(feature = "liboqs")]
+    {
+        use oqs::kem::Kem;
+
+        let kem = Kem::new(variant.oqs_algorithm())
+            .map_err(|e| CryptoError::KeyGenerationFailed(e.to_string()))?;
+
+        let (pk, sk) = kem.keypair()
+            .map_err(|e| CryptoError::KeyGenerationFailed(e.to_string()))?;
+
+        Ok(VariantKeyPair {
+            public_key: VariantPublicKey::from_bytes(variant, pk.as_ref())?,
+            secret_key: VariantSecretKey::from_bytes(variant, sk.as_ref())?,
+        })
+    }
+
+    #[cfg(all(not(feature = "liboqs"), any(feature = "pqcrypto-mlkem", feature = "pqcrypto-kyber", feature = "pqcrypto-alt")))]
+    {
+        use pqcrypto_traits::kem::{PublicKey, SecretKey};
+
+        with_kem_module!(variant, kem => {
+            let (pk, sk) = kem::keypair();
+            Ok(VariantKeyPair {
+                public_key: VariantPublicKey::from_bytes(variant, pk.as_bytes())?,
+                secret_key: VariantSecretKey::from_bytes(variant, sk.as_bytes())?,
+            })
+        })
+    }
+
+    #[cfg(not(any(feature = "liboqs", feature = "pqcrypto-mlkem", feature = "pqcrypto-kyber", feature = "pqcrypto-alt")))]
+    {
+        let _ = variant;
+        Err(CryptoError::KeyGenerationFailed(
+            "Tidak ada implementasi KEM yang tersedia. Aktifkan feature 'pqcrypto-mlkem'".to_string()
+        ))
+    }
+}
+
+/// Encapsulate to a public key of any Kyber variant
+pub fn encapsulate_with(public_key: &VariantPublicKey) -> CryptoResult<(KyberSharedSecret, VariantCiphertext)> {
+    super::self_test::ensure_operational()?;
+    let variant = public_key.variant;
+
+    #[cfg(feature = "liboqs")]
+    {
+        use oqs::kem::Kem;
+
+        let kem = Kem::new(variant.oqs_algorithm())
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+        let pk = kem.public_key_from_bytes(public_key.as_bytes())
+            .ok_or_else(|| CryptoError::EncryptionFailed("Invalid Kyber public key".to_string()))?;
+
+        let (ct, ss) = kem.encapsulate(pk)
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+        Ok((
+            KyberSharedSecret::from_bytes(ss.as_ref())?,
+            VariantCiphertext::from_bytes(variant, ct.as_ref())?,
+        ))
+    }
+
+    #[cfg(all(not(feature = "liboqs"), any(feature = "pqcrypto-mlkem", feature = "pqcrypto-kyber", feature = "pqcrypto-alt")))]
+    {
+        use pqcrypto_traits::kem::{Ciphertext, PublicKey, SharedSecret};
+
+        with_kem_module!(variant, kem => {
+            let pk = kem::PublicKey::from_bytes(public_key.as_bytes())
+                .map_err(|_| CryptoError::InvalidKeySize("Invalid Kyber public key".to_string()))?;
+            let (ss, ct) = kem::encapsulate(&pk);
+            Ok((
+                KyberSharedSecret::from_bytes(ss.as_bytes())?,
+                VariantCiphertext::from_bytes(variant, ct.as_bytes())?,
+            ))
+        })
+    }
+
+    #[cfg(not(any(feature = "liboqs", feature = "pqcrypto-mlkem", feature = "pqcrypto-kyber", feature = "pqcrypto-alt")))]
+    {
+        let _ = variant;
+        Err(CryptoError::EncryptionFailed(
+            "Tidak ada implementasi KEM yang tersedia".to_string()
+        ))
+    }
+}
+
+/// Decapsulate a ciphertext of any Kyber variant
+///
+/// Fails if the ciphertext was produced for a different variant than the key.
+pub fn decapsulate_with(
+    secret_key: &VariantSecretKey,
+    ciphertext: &VariantCiphertext,
+) -> CryptoResult<KyberSharedSecret> {
+    super::self_test::ensure_operational()?;
+    let variant = secret_key.variant;
+    if ciphertext.variant != variant {
+        return Err(CryptoError::DecryptionFailed(format!(
+            "Ciphertext variant {:?} does not match key variant {:?}",
+            ciphertext.variant, variant
+        )));
+    }
+
+    #[cfg(feature = "liboqs")]
+    {
+        use oqs::kem::Kem;
+
+        let kem = Kem::new(variant.oqs_algorithm())
+            .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+
+        let sk = kem.secret_key_from_bytes(secret_key.as_bytes())
+            .ok_or_else(|| CryptoError::DecryptionFailed("Invalid Kyber secret key".to_string()))?;
+        let ct = kem.ciphertext_from_bytes(ciphertext.as_bytes())
+            .ok_or_else(|| CryptoError::DecryptionFailed("Invalid Kyber ciphertext".to_string()))?;
+
+        let ss = kem.decapsulate(sk, ct)
+            .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+
+        KyberSharedSecret::from_bytes(ss.as_ref())
+    }
+
+    #[cfg(all(not(feature = "liboqs"), any(feature = "pqcrypto-mlkem", feature = "pqcrypto-kyber", feature = "pqcrypto-alt")))]
+    {
+        use pqcrypto_traits::kem::{Ciphertext, SecretKey, SharedSecret};
+
+        with_kem_module!(variant, kem => {
+            let sk = kem::SecretKey::from_bytes(secret_key.as_bytes())
+                .map_err(|_| CryptoError::InvalidKeySize("Invalid Kyber secret key".to_string()))?;
+            let ct = kem::Ciphertext::from_bytes(ciphertext.as_bytes())
+                .map_err(|_| CryptoError::InvalidInput("Invalid Kyber ciphertext".to_string()))?;
+            let ss = kem::decapsulate(&ct, &sk);
+            KyberSharedSecret::from_bytes(ss.as_bytes())
+        })
+    }
+
+    #[cfg(not(any(feature = "liboqs", feature = "pqcrypto-mlkem", feature = "pqcrypto-kyber", feature = "pqcrypto-alt")))]
+    Err(CryptoError::DecryptionFailed(
+        "Tidak ada implementasi KEM yang tersedia".to_string()
+    ))
+}
+
 impl fmt::Debug for KyberPublicKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         #[cfg(any(feature = "pqcrypto-mlkem", feature = "pqcrypto-kyber", feature = "pqcrypto-alt"))]
@@ -464,4 +793,32 @@ mod tests {
         
         assert_eq!(ss1.as_bytes(), ss2.as_bytes());
     }
+
+    #[test]
+    fn test_variant_roundtrip() {
+        for variant in [KyberVariant::Kyber512, KyberVariant::Kyber768, KyberVariant::Kyber1024] {
+            let keypair = keypair_with(variant).expect("Failed to generate keypair");
+            assert_eq!(keypair.public_key.as_bytes().len(), variant.public_key_size());
+            assert_eq!(keypair.secret_key.as_bytes().len(), variant.secret_key_size());
+
+            let (ss1, ct) = encapsulate_with(&keypair.public_key).expect("Failed to encapsulate");
+            assert_eq!(ct.as_bytes().len(), variant.ciphertext_size());
+
+            let ss2 = decapsulate_with(&keypair.secret_key, &ct).expect("Failed to decapsulate");
+            assert_eq!(ss1.as_bytes(), ss2.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_variant_mismatch_fails() {
+        let kp768 = keypair_with(KyberVariant::Kyber768).unwrap();
+        let kp1024 = keypair_with(KyberVariant::Kyber1024).unwrap();
+
+        let (_, ct768) = encapsulate_with(&kp768.public_key).unwrap();
+        assert!(decapsulate_with(&kp1024.secret_key, &ct768).is_err());
+
+        // Re-labelling the raw bytes as another variant is rejected on size
+        assert!(VariantCiphertext::from_bytes(KyberVariant::Kyber1024, ct768.as_bytes()).is_err());
+        assert!(VariantPublicKey::from_bytes(KyberVariant::Kyber512, kp768.public_key.as_bytes()).is_err());
+    }
 }
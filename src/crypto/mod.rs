@@ -1,44 +1,107 @@
 //! B4AE Cryptographic Core Module
 //!
 //! Implements NIST-standardized post-quantum cryptography and classical primitives.
+//!
+//! Without the `std` feature only [`aes_gcm`], [`chacha20poly1305_wrapper`],
+//! [`hkdf`] and [`constant_time`] are built (`no_std` + `alloc`); functions that
+//! need randomness are then available only as `*_with_rng` variants.
+//!
+//! The KEM ([`kyber`], ML-KEM) and the signature schemes still require `std`:
+//! their C backends (PQClean, liboqs) fetch randomness from the OS internally
+//! and cannot be given an injected RNG, so they are not part of the `no_std`
+//! build.
 
 /// Kyber KEM (NIST ML-KEM).
+#[cfg(feature = "std")]
 pub mod kyber;
+/// Byte-level Kyber-1024 encapsulate/decapsulate.
+#[cfg(feature = "std")]
+pub mod kem;
 /// Dilithium signatures (NIST ML-DSA).
+#[cfg(feature = "std")]
 pub mod dilithium;
 /// Hybrid cryptography (PQC + classical).
+#[cfg(feature = "std")]
 pub mod hybrid;
 /// Hybrid key exchange (X25519 + Kyber1024) for B4AE v2.0.
+#[cfg(feature = "std")]
 pub mod hybrid_kex;
+/// Safety numbers and key fingerprints for out-of-band verification.
+#[cfg(feature = "std")]
+pub mod fingerprint;
 /// AES-256-GCM encryption.
 pub mod aes_gcm;
+/// AES-256-GCM-SIV nonce-misuse-resistant encryption.
+#[cfg(feature = "std")]
+pub mod aes_gcm_siv;
 /// ChaCha20-Poly1305 AEAD encryption.
 pub mod chacha20poly1305_wrapper;
+/// Anonymous sealed-box encryption to a hybrid public key.
+#[cfg(feature = "std")]
+pub mod sealed_box;
+/// Multi-recipient encryption with per-recipient wrapped content keys.
+#[cfg(feature = "std")]
+pub mod multi_recipient;
+/// Sender keys for group messaging.
+#[cfg(feature = "std")]
+pub mod sender_keys;
 /// HKDF key derivation.
 pub mod hkdf;
 /// Onion routing primitives.
+#[cfg(feature = "std")]
 pub mod onion;
 /// Hardware acceleration helpers.
+#[cfg(feature = "std")]
 pub mod perf;
 /// CSPRNG and random utilities.
+#[cfg(feature = "std")]
 pub mod random;
 /// Perfect Forward Secrecy Plus.
+#[cfg(feature = "std")]
 pub mod pfs_plus;
 /// Zero-knowledge authentication.
+#[cfg(feature = "std")]
 pub mod zkauth;
 /// Hybrid Double Ratchet protocol.
+#[cfg(feature = "std")]
 pub mod double_ratchet;
 /// PADMÉ padding for message length obfuscation.
+#[cfg(feature = "std")]
 pub mod padding;
 /// XEdDSA deniable authentication.
+#[cfg(feature = "std")]
 pub mod xeddsa;
+/// Standard (non-deniable) Ed25519 signatures.
+#[cfg(feature = "std")]
+pub mod ed25519;
+/// X3DH-style asynchronous prekey bundles for offline session setup.
+#[cfg(feature = "std")]
+pub mod x3dh;
 /// Constant-time operations for side-channel resistance.
 pub mod constant_time;
 /// Post-quantum cryptography wrapper (Kyber1024 + Dilithium5).
+#[cfg(feature = "std")]
 pub mod pq;
+/// Versioned, self-describing containers for storing PQ keys.
+#[cfg(feature = "std")]
+pub mod key_encoding;
+/// BIP39 mnemonic encoding for human-transcribable key backups.
+#[cfg(feature = "std")]
+pub mod bip39;
+/// Password hashing (PHC strings) with Argon2id and scrypt.
+#[cfg(feature = "std")]
+pub mod password;
+/// Power-on known-answer self-tests.
+#[cfg(feature = "std")]
+pub mod self_test;
+
+#[cfg(feature = "std")]
+pub use kem::{decapsulate, encapsulate};
+#[cfg(feature = "std")]
+pub use self_test::{ensure_operational, self_test, self_test_with, SelfTestAlgorithm, SelfTestReport, SelfTestResult};
 
-use std::error::Error;
-use std::fmt;
+use alloc::string::String;
+use core::fmt;
 
 /// B4AE Cryptographic Error Types
 #[derive(Debug, Clone)]
@@ -71,6 +134,10 @@ pub enum CryptoError {
     InvalidPadding,
     /// Message too large for padding.
     MessageTooLarge,
+    /// Peer identity does not match the pinned fingerprint.
+    FingerprintMismatch,
+    /// A power-on self-test failed; names the failed algorithms.
+    SelfTestFailed(String),
 }
 
 impl fmt::Display for CryptoError {
@@ -90,11 +157,14 @@ impl fmt::Display for CryptoError {
             CryptoError::InvalidRatchetUpdate => write!(f, "Invalid ratchet update"),
             CryptoError::InvalidPadding => write!(f, "Invalid padding detected"),
             CryptoError::MessageTooLarge => write!(f, "Message too large for padding"),
+            CryptoError::FingerprintMismatch => write!(f, "fingerprint mismatch"),
+            CryptoError::SelfTestFailed(msg) => write!(f, "Self-test failed: {}", msg),
         }
     }
 }
 
-impl Error for CryptoError {}
+#[cfg(feature = "std")]
+impl std::error::Error for CryptoError {}
 
 /// Result type for crypto operations.
 pub type CryptoResult<T> = Result<T, CryptoError>;
@@ -119,9 +189,34 @@ impl SecurityLevel {
             SecurityLevel::Maximum => 64,   // 512 bits
         }
     }
+
+    /// Returns the Kyber parameter set for this security level.
+    ///
+    /// Matches the cipher suites: Standard uses Kyber-768, High and Maximum
+    /// use Kyber-1024.
+    #[cfg(feature = "std")]
+    pub fn kyber_variant(&self) -> kyber::KyberVariant {
+        match self {
+            SecurityLevel::Standard => kyber::KyberVariant::Kyber768,
+            SecurityLevel::High | SecurityLevel::Maximum => kyber::KyberVariant::Kyber1024,
+        }
+    }
+
+    /// Returns the Dilithium parameter set for this security level.
+    ///
+    /// Matches the cipher suites: Standard uses Dilithium3, High and Maximum
+    /// use Dilithium5.
+    #[cfg(feature = "std")]
+    pub fn dilithium_variant(&self) -> dilithium::DilithiumVariant {
+        match self {
+            SecurityLevel::Standard => dilithium::DilithiumVariant::Dilithium3,
+            SecurityLevel::High | SecurityLevel::Maximum => dilithium::DilithiumVariant::Dilithium5,
+        }
+    }
 }
 
 /// B4AE Cryptographic Configuration
+#[cfg(feature = "std")]
 #[derive(Debug, Clone)]
 pub struct CryptoConfig {
     /// Security level (Standard/High/Maximum).
@@ -134,6 +229,56 @@ pub struct CryptoConfig {
     pub quantum_resistant: bool,
 }
 
+/// Outcome of checking [`CryptoConfig::enable_hardware_acceleration`]
+/// against the host CPU.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccelerationStatus {
+    /// Whether the config asked for hardware acceleration.
+    pub requested: bool,
+    /// Features detected on this host.
+    pub features: perf::CpuFeatures,
+    /// Set when acceleration was requested but AES-GCM will run in software.
+    pub warning: Option<String>,
+}
+
+#[cfg(feature = "std")]
+impl AccelerationStatus {
+    /// True when acceleration was requested and is available.
+    pub fn active(&self) -> bool {
+        self.requested && self.features.aes_gcm_accelerated()
+    }
+}
+
+#[cfg(feature = "std")]
+impl CryptoConfig {
+    /// Kyber parameter set selected by `security_level`.
+    pub fn kyber_variant(&self) -> kyber::KyberVariant {
+        self.security_level.kyber_variant()
+    }
+
+    /// Dilithium parameter set selected by `security_level`.
+    pub fn dilithium_variant(&self) -> dilithium::DilithiumVariant {
+        self.security_level.dilithium_variant()
+    }
+
+    /// Compare the acceleration request with what the CPU actually offers.
+    pub fn acceleration_status(&self) -> AccelerationStatus {
+        Self::acceleration_status_for(self.enable_hardware_acceleration, perf::detected_features())
+    }
+
+    fn acceleration_status_for(requested: bool, features: perf::CpuFeatures) -> AccelerationStatus {
+        let warning = (requested && !features.aes_gcm_accelerated()).then(|| {
+            format!(
+                "Hardware acceleration requested but AES-GCM will run in software (detected: {})",
+                features
+            )
+        });
+        AccelerationStatus { requested, features, warning }
+    }
+}
+
+#[cfg(feature = "std")]
 impl Default for CryptoConfig {
     fn default() -> Self {
         CryptoConfig {
@@ -156,6 +301,35 @@ mod tests {
         assert_eq!(SecurityLevel::Maximum.key_size(), 64);
     }
 
+    #[test]
+    fn test_security_level_kyber_variant() {
+        assert_eq!(SecurityLevel::Standard.kyber_variant(), kyber::KyberVariant::Kyber768);
+        assert_eq!(SecurityLevel::High.kyber_variant(), kyber::KyberVariant::Kyber1024);
+        assert_eq!(SecurityLevel::Maximum.kyber_variant(), kyber::KyberVariant::Kyber1024);
+        assert_eq!(CryptoConfig::default().kyber_variant(), kyber::KyberVariant::Kyber768);
+    }
+
+    #[test]
+    fn test_security_level_dilithium_variant() {
+        assert_eq!(SecurityLevel::Standard.dilithium_variant(), dilithium::DilithiumVariant::Dilithium3);
+        assert_eq!(SecurityLevel::High.dilithium_variant(), dilithium::DilithiumVariant::Dilithium5);
+        assert_eq!(CryptoConfig::default().dilithium_variant(), dilithium::DilithiumVariant::Dilithium3);
+    }
+
+    #[test]
+    fn test_acceleration_status() {
+        let status = CryptoConfig::default().acceleration_status();
+        assert!(status.requested);
+        assert_eq!(status.warning.is_some(), !status.active());
+
+        let none = CryptoConfig::acceleration_status_for(true, perf::CpuFeatures::default());
+        assert!(!none.active());
+        assert!(none.warning.unwrap().contains("detected: none"));
+
+        let off = CryptoConfig::acceleration_status_for(false, perf::CpuFeatures::default());
+        assert!(off.warning.is_none() && !off.active());
+    }
+
     #[test]
     fn test_default_config() {
         let config = CryptoConfig::default();
@@ -0,0 +1,300 @@
+//! Multi-Recipient (Fan-Out) Encryption
+//!
+//! Encrypts a payload once under a random content key and wraps that key
+//! separately for every recipient's [`HybridPublicKey`] with the hybrid KEM
+//! (X25519 + Kyber). Each recipient opens the message with their own keypair;
+//! the payload is never re-encrypted per recipient.
+//!
+//! # Format
+//!
+//! ```text
+//! stanza_i   = kem_ciphertext_i || nonce (12) || E(wrap_key_i, content_key) || tag (16)
+//! wrap_key_i = HKDF-SHA3-256(kem_shared_secret_i, info: "B4AE-multi-recipient-v1-wrap")
+//! stanza_aad = "B4AE-multi-recipient-v1" || kem_ciphertext_i || recipient_public_i
+//!
+//! payload    = nonce (12) || E(content_key, plaintext) || tag (16), aad = "B4AE-multi-recipient-v1"
+//! ```
+//!
+//! # Recipient privacy
+//!
+//! Stanzas carry only ephemeral KEM values and are shuffled, so the message
+//! does not reveal who the other recipients are. A recipient finds its stanza
+//! by trial decryption against every stanza. [`encrypt_with_key_ids`] opts in
+//! to a short per-recipient key ID in each stanza, trading that privacy for a
+//! single decapsulation on open.
+
+use crate::crypto::chacha20poly1305_wrapper::{open_single_use, seal_single_use};
+use crate::crypto::hkdf::derive_key;
+use crate::crypto::hybrid::{self, HybridCiphertext, HybridKeyPair, HybridPublicKey};
+use crate::crypto::random::{fill_random, random_range};
+use crate::crypto::{CryptoError, CryptoResult};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use zeroize::Zeroizing;
+
+/// Domain separation label for AAD
+const MULTI_RECIPIENT_LABEL: &[u8] = b"B4AE-multi-recipient-v1";
+
+/// HKDF info for per-recipient wrap keys
+const WRAP_KEY_INFO: &[u8] = b"B4AE-multi-recipient-v1-wrap";
+
+/// Hash prefix for opt-in key IDs
+const KEY_ID_LABEL: &[u8] = b"B4AE-multi-recipient-v1-key-id";
+
+/// Content key size
+const CONTENT_KEY_SIZE: usize = 32;
+
+/// Size of an opt-in recipient key ID
+pub const KEY_ID_SIZE: usize = 8;
+
+/// Maximum number of recipients (bounds trial decryption work on open)
+pub const MAX_RECIPIENTS: usize = 1024;
+
+/// Upper bound on a serialized message (largest payload plus a full stanza list)
+const MAX_SERIALIZED_SIZE: usize = crate::MAX_MESSAGE_SIZE + MAX_RECIPIENTS * 2048;
+
+/// Content key wrapped for one recipient
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipientStanza {
+    /// Recipient key ID, only present when the sender opted in
+    pub key_id: Option<[u8; KEY_ID_SIZE]>,
+    /// Serialized hybrid KEM ciphertext
+    pub kem_ciphertext: Vec<u8>,
+    /// `nonce || encrypted content key || tag`
+    pub wrapped_key: Vec<u8>,
+}
+
+/// One payload plus a content-key stanza per recipient
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiRecipientMessage {
+    /// Per-recipient stanzas in random order
+    pub stanzas: Vec<RecipientStanza>,
+    /// `nonce || ciphertext || tag` under the content key
+    pub payload: Vec<u8>,
+}
+
+impl MultiRecipientMessage {
+    /// Serialize message to bytes
+    pub fn to_bytes(&self) -> CryptoResult<Vec<u8>> {
+        bincode::serialize(self).map_err(|e| CryptoError::InvalidInput(e.to_string()))
+    }
+
+    /// Deserialize message from bytes
+    pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        if bytes.len() > MAX_SERIALIZED_SIZE {
+            return Err(CryptoError::InvalidInput(format!(
+                "Input too large for deserialize: {} > {}",
+                bytes.len(),
+                MAX_SERIALIZED_SIZE
+            )));
+        }
+        bincode::deserialize(bytes).map_err(|e| CryptoError::InvalidInput(e.to_string()))
+    }
+}
+
+/// Encrypt `plaintext` once for all `recipients`, without key IDs
+pub fn encrypt(recipients: &[HybridPublicKey], plaintext: &[u8]) -> CryptoResult<MultiRecipientMessage> {
+    encrypt_inner(recipients, plaintext, false)
+}
+
+/// Like [`encrypt`], but tags each stanza with the recipient's key ID
+///
+/// Anyone who knows a candidate public key can then check whether it is
+/// among the recipients.
+pub fn encrypt_with_key_ids(recipients: &[HybridPublicKey], plaintext: &[u8]) -> CryptoResult<MultiRecipientMessage> {
+    encrypt_inner(recipients, plaintext, true)
+}
+
+/// Open a message with one recipient's keypair
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - Plaintext
+/// * `Err(CryptoError)` - If no stanza opens under this keypair or the payload is corrupt
+pub fn decrypt(recipient_kp: &HybridKeyPair, message: &MultiRecipientMessage) -> CryptoResult<Vec<u8>> {
+    if message.stanzas.len() > MAX_RECIPIENTS {
+        return Err(CryptoError::InvalidInput(format!(
+            "Too many recipient stanzas: {} > {}",
+            message.stanzas.len(),
+            MAX_RECIPIENTS
+        )));
+    }
+
+    let own_id = key_id(&recipient_kp.public_key);
+    let recipient_bytes = recipient_kp.public_key.to_bytes();
+
+    // Try every candidate stanza, not just until the first match, so timing
+    // does not reveal this recipient's position
+    let mut content_key = None;
+    for stanza in &message.stanzas {
+        if stanza.key_id.is_some_and(|id| id != own_id) {
+            continue;
+        }
+        if let Ok(key) = unwrap_content_key(recipient_kp, &recipient_bytes, stanza) {
+            content_key.get_or_insert(key);
+        }
+    }
+    let content_key = content_key
+        .ok_or_else(|| CryptoError::DecryptionFailed("No recipient stanza for this key".to_string()))?;
+
+    open_single_use(&content_key, &message.payload, Some(MULTI_RECIPIENT_LABEL))
+}
+
+fn encrypt_inner(
+    recipients: &[HybridPublicKey],
+    plaintext: &[u8],
+    with_key_ids: bool,
+) -> CryptoResult<MultiRecipientMessage> {
+    if recipients.is_empty() || recipients.len() > MAX_RECIPIENTS {
+        return Err(CryptoError::InvalidInput(format!(
+            "Recipient count must be 1..={}, got {}",
+            MAX_RECIPIENTS,
+            recipients.len()
+        )));
+    }
+
+    let mut content_key = Zeroizing::new([0u8; CONTENT_KEY_SIZE]);
+    fill_random(content_key.as_mut())?;
+
+    let mut stanzas = recipients
+        .iter()
+        .map(|recipient| wrap_content_key(recipient, &content_key, with_key_ids))
+        .collect::<CryptoResult<Vec<_>>>()?;
+    shuffle(&mut stanzas);
+
+    Ok(MultiRecipientMessage {
+        stanzas,
+        payload: seal_single_use(&content_key, plaintext, Some(MULTI_RECIPIENT_LABEL))?,
+    })
+}
+
+fn wrap_content_key(
+    recipient: &HybridPublicKey,
+    content_key: &[u8; CONTENT_KEY_SIZE],
+    with_key_id: bool,
+) -> CryptoResult<RecipientStanza> {
+    let (shared_secret, kem_ciphertext) = hybrid::encapsulate(recipient)?;
+    let shared_secret = Zeroizing::new(shared_secret);
+    let kem_bytes = kem_ciphertext.to_bytes();
+
+    let wrap_key = derive_wrap_key(&shared_secret)?;
+    let aad = stanza_aad(&kem_bytes, &recipient.to_bytes());
+    let wrapped_key = seal_single_use(&wrap_key, content_key, Some(&aad))?;
+
+    Ok(RecipientStanza {
+        key_id: with_key_id.then(|| key_id(recipient)),
+        kem_ciphertext: kem_bytes,
+        wrapped_key,
+    })
+}
+
+fn unwrap_content_key(
+    recipient_kp: &HybridKeyPair,
+    recipient_bytes: &[u8],
+    stanza: &RecipientStanza,
+) -> CryptoResult<Zeroizing<[u8; CONTENT_KEY_SIZE]>> {
+    let kem_ciphertext = HybridCiphertext::from_bytes(&stanza.kem_ciphertext)?;
+    let shared_secret = Zeroizing::new(hybrid::decapsulate(&recipient_kp.secret_key, &kem_ciphertext)?);
+
+    let wrap_key = derive_wrap_key(&shared_secret)?;
+    let aad = stanza_aad(&stanza.kem_ciphertext, recipient_bytes);
+    let key_bytes = Zeroizing::new(open_single_use(&wrap_key, &stanza.wrapped_key, Some(&aad))?);
+
+    if key_bytes.len() != CONTENT_KEY_SIZE {
+        return Err(CryptoError::InvalidKeySize(format!(
+            "Wrapped content key is {} bytes, expected {}",
+            key_bytes.len(),
+            CONTENT_KEY_SIZE
+        )));
+    }
+    let mut key = Zeroizing::new([0u8; CONTENT_KEY_SIZE]);
+    key.copy_from_slice(&key_bytes);
+    Ok(key)
+}
+
+fn derive_wrap_key(shared_secret: &[u8]) -> CryptoResult<Zeroizing<[u8; 32]>> {
+    let key_vec = Zeroizing::new(derive_key(&[shared_secret], WRAP_KEY_INFO, 32)?);
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&key_vec);
+    Ok(key)
+}
+
+fn stanza_aad(kem_bytes: &[u8], recipient_bytes: &[u8]) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(MULTI_RECIPIENT_LABEL.len() + kem_bytes.len() + recipient_bytes.len());
+    aad.extend_from_slice(MULTI_RECIPIENT_LABEL);
+    aad.extend_from_slice(kem_bytes);
+    aad.extend_from_slice(recipient_bytes);
+    aad
+}
+
+fn key_id(public_key: &HybridPublicKey) -> [u8; KEY_ID_SIZE] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(KEY_ID_LABEL);
+    hasher.update(public_key.to_bytes());
+    let digest = hasher.finalize();
+    let mut id = [0u8; KEY_ID_SIZE];
+    id.copy_from_slice(&digest[..KEY_ID_SIZE]);
+    id
+}
+
+
+/// Fisher-Yates shuffle so stanza order does not follow the recipient list
+fn shuffle<T>(items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        let j = random_range(i as u64 + 1) as usize;
+        items.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_recipient_decrypts() {
+        let recipients: Vec<_> = (0..3).map(|_| hybrid::generate_keypair().unwrap()).collect();
+        let public_keys: Vec<_> = recipients.iter().map(|kp| kp.public_key.clone()).collect();
+        let plaintext = b"quarterly report for the whole team";
+
+        let message = encrypt(&public_keys, plaintext).unwrap();
+        assert_eq!(message.stanzas.len(), 3);
+        assert!(message.stanzas.iter().all(|s| s.key_id.is_none()));
+
+        let restored = MultiRecipientMessage::from_bytes(&message.to_bytes().unwrap()).unwrap();
+        for recipient in &recipients {
+            assert_eq!(decrypt(recipient, &restored).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_non_recipient_fails() {
+        let bob = hybrid::generate_keypair().unwrap();
+        let carol = hybrid::generate_keypair().unwrap();
+        let eve = hybrid::generate_keypair().unwrap();
+
+        let message = encrypt(&[bob.public_key.clone(), carol.public_key.clone()], b"secret").unwrap();
+        assert!(decrypt(&eve, &message).is_err());
+
+        // Recipient public keys do not appear anywhere in the message
+        let bytes = message.to_bytes().unwrap();
+        for public_key in [&bob.public_key, &carol.public_key] {
+            assert!(!bytes.windows(32).any(|w| w == public_key.ecdh_public.as_slice()));
+        }
+
+        assert!(encrypt(&[], b"nobody").is_err());
+    }
+
+    #[test]
+    fn test_opt_in_key_ids_and_tampering() {
+        let bob = hybrid::generate_keypair().unwrap();
+        let carol = hybrid::generate_keypair().unwrap();
+
+        let mut message =
+            encrypt_with_key_ids(&[bob.public_key.clone(), carol.public_key.clone()], b"tagged").unwrap();
+        assert!(message.stanzas.iter().all(|s| s.key_id.is_some()));
+        assert_eq!(decrypt(&carol, &message).unwrap(), b"tagged");
+
+        let last = message.payload.len() - 1;
+        message.payload[last] ^= 0x01;
+        assert!(decrypt(&bob, &message).is_err());
+    }
+}
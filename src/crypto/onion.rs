@@ -2,10 +2,18 @@
 //!
 //! Layered encryption for relay paths. Each hop decrypts one layer,
 //! sees next-hop address, forwards remainder.
+//!
+//! [`wrap`] and [`peel`] build fixed-size cells: every layer on the wire is
+//! exactly [`ONION_CELL_SIZE`] bytes, so an observer cannot tell from the
+//! size how many hops a packet has left. Format:
+//! nonce(12) || masked ciphertext length(4) || ciphertext || random filler.
+//! The length is masked with a key derived from the hop key and nonce.
 
-use crate::crypto::aes_gcm::{self, AesKey};
+use crate::crypto::aes_gcm::{self, AesKey, NONCE_SIZE, TAG_SIZE};
+use crate::crypto::hkdf;
 use crate::crypto::{CryptoError, CryptoResult};
 use crate::crypto::random;
+use zeroize::Zeroize;
 
 /// Maximum next-hop ID length.
 pub const MAX_HOP_ID_LEN: usize = 256;
@@ -84,3 +92,215 @@ pub fn generate_layer_key() -> CryptoResult<[u8; 32]> {
     random::fill_random(&mut key)?;
     Ok(key)
 }
+
+/// Size of every onion cell, before and after each peel.
+pub const ONION_CELL_SIZE: usize = 4096;
+
+/// nonce || masked ciphertext length
+const CELL_HEADER_SIZE: usize = NONCE_SIZE + 4;
+const CELL_CONTEXT: &[u8] = b"B4AE-onion-cell";
+const LENGTH_MASK_INFO: &[u8] = b"B4AE-onion-length-mask";
+const LAYER_DELIVER: u8 = 0x00;
+const LAYER_FORWARD: u8 = 0x01;
+
+/// One relay on an onion path.
+#[derive(Clone)]
+pub struct HopKey {
+    /// Address other nodes use to reach this relay.
+    pub address: Vec<u8>,
+    /// Layer key shared between the sender and this relay.
+    pub key: [u8; 32],
+}
+
+impl HopKey {
+    /// Create a hop from its address and layer key.
+    pub fn new(address: impl Into<Vec<u8>>, key: [u8; 32]) -> Self {
+        HopKey { address: address.into(), key }
+    }
+}
+
+impl Drop for HopKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+/// Result of peeling one layer with [`peel`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeelResult {
+    /// Send `onion` (a full cell) on to `next_hop`.
+    Forward {
+        /// Address of the next relay.
+        next_hop: Vec<u8>,
+        /// Remaining onion, re-padded to [`ONION_CELL_SIZE`].
+        onion: Vec<u8>,
+    },
+    /// This relay is the exit; the final payload.
+    Deliver(Vec<u8>),
+}
+
+/// Build a fixed-size onion cell for `hops`, ordered entry to exit.
+///
+/// Layers are sealed from the exit backwards, so the entry hop peels first
+/// and learns only the second hop's address; the exit hop gets `payload`.
+/// Fails if the payload and per-hop overhead do not fit in one cell.
+pub fn wrap(payload: &[u8], hops: &[HopKey]) -> CryptoResult<Vec<u8>> {
+    let (exit, relays) = hops.split_last().ok_or_else(|| {
+        CryptoError::InvalidInput("Onion path needs at least one hop".to_string())
+    })?;
+
+    let mut plaintext = Vec::with_capacity(1 + payload.len());
+    plaintext.push(LAYER_DELIVER);
+    plaintext.extend_from_slice(payload);
+    let mut inner = seal_layer(&exit.key, &plaintext)?;
+
+    let mut next = exit;
+    for hop in relays.iter().rev() {
+        if next.address.is_empty() || next.address.len() > MAX_HOP_ID_LEN {
+            return Err(CryptoError::InvalidInput("Invalid next hop address length".to_string()));
+        }
+        let mut plaintext = Vec::with_capacity(3 + next.address.len() + inner.len());
+        plaintext.push(LAYER_FORWARD);
+        plaintext.extend_from_slice(&(next.address.len() as u16).to_be_bytes());
+        plaintext.extend_from_slice(&next.address);
+        plaintext.append(&mut inner);
+        inner = seal_layer(&hop.key, &plaintext)?;
+        next = hop;
+    }
+
+    pad_cell(inner)
+}
+
+/// Peel the outer layer of an onion cell with this relay's key.
+///
+/// Fails with [`CryptoError::AuthenticationFailed`] if the cell was tampered
+/// with or was not sealed for `my_key`.
+pub fn peel(layer: &[u8], my_key: &[u8; 32]) -> CryptoResult<PeelResult> {
+    if layer.len() != ONION_CELL_SIZE {
+        return Err(CryptoError::InvalidInput(format!(
+            "Onion cell must be {} bytes, got {}",
+            ONION_CELL_SIZE,
+            layer.len()
+        )));
+    }
+    let (nonce, rest) = layer.split_at(NONCE_SIZE);
+    let (masked_len, body) = rest.split_at(4);
+    let masked_len = u32::from_be_bytes([masked_len[0], masked_len[1], masked_len[2], masked_len[3]]);
+    let ct_len = (masked_len ^ length_mask(my_key, nonce)?) as usize;
+    // A wrong key unmasks to garbage; treat it like a failed tag
+    if ct_len < TAG_SIZE || ct_len > body.len() {
+        return Err(CryptoError::AuthenticationFailed);
+    }
+
+    let aes_key = AesKey::from_bytes(my_key)?;
+    let plaintext = aes_gcm::decrypt(&aes_key, nonce, &body[..ct_len], CELL_CONTEXT)?;
+    match plaintext.split_first() {
+        Some((&LAYER_DELIVER, payload)) => Ok(PeelResult::Deliver(payload.to_vec())),
+        Some((&LAYER_FORWARD, rest)) => {
+            if rest.len() < 2 {
+                return Err(CryptoError::DecryptionFailed("Truncated next hop".to_string()));
+            }
+            let len = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+            if len == 0 || len > MAX_HOP_ID_LEN || rest.len() < 2 + len {
+                return Err(CryptoError::DecryptionFailed("Invalid next hop".to_string()));
+            }
+            Ok(PeelResult::Forward {
+                next_hop: rest[2..2 + len].to_vec(),
+                onion: pad_cell(rest[2 + len..].to_vec())?,
+            })
+        }
+        _ => Err(CryptoError::DecryptionFailed("Unknown onion layer type".to_string())),
+    }
+}
+
+/// Encrypt one layer: nonce || masked length || ciphertext (unpadded).
+fn seal_layer(key: &[u8; 32], plaintext: &[u8]) -> CryptoResult<Vec<u8>> {
+    if plaintext.len() + TAG_SIZE > ONION_CELL_SIZE - CELL_HEADER_SIZE {
+        return Err(CryptoError::InvalidInput("Onion payload too large for cell".to_string()));
+    }
+    let aes_key = AesKey::from_bytes(key)?;
+    let (nonce, ct) = aes_gcm::encrypt(&aes_key, plaintext, CELL_CONTEXT)?;
+    let masked_len = ct.len() as u32 ^ length_mask(key, &nonce)?;
+
+    let mut sealed = Vec::with_capacity(CELL_HEADER_SIZE + ct.len());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&masked_len.to_be_bytes());
+    sealed.extend_from_slice(&ct);
+    Ok(sealed)
+}
+
+/// Fill a sealed layer up to the cell size with random bytes.
+fn pad_cell(mut sealed: Vec<u8>) -> CryptoResult<Vec<u8>> {
+    let start = sealed.len();
+    sealed.resize(ONION_CELL_SIZE, 0);
+    random::fill_random(&mut sealed[start..])?;
+    Ok(sealed)
+}
+
+fn length_mask(key: &[u8; 32], nonce: &[u8]) -> CryptoResult<u32> {
+    let mask = hkdf::derive_key(&[key, nonce], LENGTH_MASK_INFO, 4)?;
+    Ok(u32::from_be_bytes([mask[0], mask[1], mask[2], mask[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path() -> CryptoResult<Vec<HopKey>> {
+        Ok(vec![
+            HopKey::new(b"relay-entry".to_vec(), generate_layer_key()?),
+            HopKey::new(b"relay-middle".to_vec(), generate_layer_key()?),
+            HopKey::new(b"relay-exit".to_vec(), generate_layer_key()?),
+        ])
+    }
+
+    #[test]
+    fn test_three_hop_roundtrip() {
+        let hops = path().unwrap();
+        let cell = wrap(b"hello through three relays", &hops).unwrap();
+        assert_eq!(cell.len(), ONION_CELL_SIZE);
+
+        let mut onion = cell;
+        for (i, hop) in hops[..2].iter().enumerate() {
+            match peel(&onion, &hop.key).unwrap() {
+                PeelResult::Forward { next_hop, onion: next } => {
+                    assert_eq!(next_hop, hops[i + 1].address);
+                    assert_eq!(next.len(), ONION_CELL_SIZE);
+                    onion = next;
+                }
+                other => panic!("expected forward, got {:?}", other),
+            }
+        }
+        assert_eq!(
+            peel(&onion, &hops[2].key).unwrap(),
+            PeelResult::Deliver(b"hello through three relays".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_tampered_layer_fails_authentication() {
+        let hops = path().unwrap();
+        let cell = wrap(b"payload", &hops).unwrap();
+
+        let mut tampered = cell.clone();
+        tampered[CELL_HEADER_SIZE + 3] ^= 0x01;
+        assert!(matches!(peel(&tampered, &hops[0].key), Err(CryptoError::AuthenticationFailed)));
+
+        // Tampering with the forwarded cell is caught by the next hop
+        let PeelResult::Forward { mut onion, .. } = peel(&cell, &hops[0].key).unwrap() else {
+            panic!("expected forward");
+        };
+        onion[CELL_HEADER_SIZE] ^= 0x80;
+        assert!(matches!(peel(&onion, &hops[1].key), Err(CryptoError::AuthenticationFailed)));
+
+        // A hop cannot skip ahead with someone else's key
+        assert!(matches!(peel(&cell, &hops[1].key), Err(CryptoError::AuthenticationFailed)));
+    }
+
+    #[test]
+    fn test_wrap_rejects_oversized_payload() {
+        let hops = path().unwrap();
+        assert!(wrap(&vec![0u8; ONION_CELL_SIZE], &hops).is_err());
+        assert!(wrap(b"payload", &[]).is_err());
+    }
+}
@@ -431,6 +431,41 @@ impl PadmePadding {
     }
 }
 
+/// Length of the padded output `PadmePadding` produces for `input_len` bytes
+/// under the default configuration, without allocating
+///
+/// The default buckets double from 512 bytes to 64 KB, so the target length is
+///
+/// ```text
+/// padded_len(n) = max(512, 2^ceil(log2(n)))    for n <= 65536
+/// ```
+///
+/// Lengths above 65536 cannot be padded (`pad` returns
+/// `CryptoError::MessageTooLarge`); for those `input_len` is returned unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use b4ae::crypto::padding::{padme_padded_len, padme_overhead};
+///
+/// assert_eq!(padme_padded_len(13), 512);
+/// assert_eq!(padme_padded_len(513), 1024);
+/// assert_eq!(padme_overhead(1000), 24);
+/// ```
+pub fn padme_padded_len(input_len: usize) -> usize {
+    let config = PadmeConfig::default();
+    if input_len > config.max_bucket_size {
+        return input_len;
+    }
+    input_len.next_power_of_two().max(config.min_bucket_size)
+}
+
+/// Number of padding bytes `PadmePadding` adds to `input_len` bytes under the
+/// default configuration (`padme_padded_len(input_len) - input_len`)
+pub fn padme_overhead(input_len: usize) -> usize {
+    padme_padded_len(input_len) - input_len
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -964,6 +999,43 @@ mod tests {
         assert_eq!(padded1.padded_data, padded2.padded_data);
     }
 
+    #[test]
+    fn test_padme_padded_len_boundaries() {
+        assert_eq!(padme_padded_len(0), 512);
+        assert_eq!(padme_padded_len(512), 512);
+        assert_eq!(padme_padded_len(513), 1024);
+        assert_eq!(padme_padded_len(65536), 65536);
+        assert_eq!(padme_padded_len(65537), 65537);
+        assert_eq!(padme_overhead(65537), 0);
+    }
+
+    mod property_tests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn prop_padded_len_covers_input(n in 0usize..200_000) {
+                prop_assert!(padme_padded_len(n) >= n);
+                prop_assert_eq!(padme_padded_len(n), n + padme_overhead(n));
+            }
+
+            #[test]
+            fn prop_padded_len_monotonic(a in 0usize..200_000, b in 0usize..200_000) {
+                let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+                prop_assert!(padme_padded_len(lo) <= padme_padded_len(hi));
+            }
+
+            #[test]
+            fn prop_padded_len_matches_pad(n in 0usize..=65536) {
+                let padding = PadmePadding::new(PadmeConfig::default());
+                let padded = padding.pad(&vec![0xAB; n]).unwrap();
+                prop_assert_eq!(padme_padded_len(n), padded.padded_data.len());
+                prop_assert_eq!(padme_overhead(n), padded.padded_data.len() - n);
+            }
+        }
+    }
+
     #[test]
     fn test_unpad_with_custom_config() {
         let config = PadmeConfig {
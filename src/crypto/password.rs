@@ -0,0 +1,330 @@
+//! Password hashing and verification with PHC strings
+//!
+//! [`hash_password`] returns a self-describing [PHC string] such as
+//! `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`; [`verify_password`] reads
+//! the algorithm and cost parameters back out of it, so stored hashes keep
+//! verifying after the defaults change. Argon2id is the default; scrypt
+//! (`$scrypt$ln=..,r=..,p=..$..`) is available for interop and selected by
+//! the PHC identifier.
+//!
+//! ```
+//! use b4ae::crypto::password::{hash_password, verify_password, PasswordParams};
+//!
+//! let params = PasswordParams::argon2id(1024, 1, 1);
+//! let phc = hash_password(b"correct horse", &params)?;
+//! assert!(verify_password(b"correct horse", &phc));
+//! assert!(!verify_password(b"battery staple", &phc));
+//! # Ok::<(), b4ae::crypto::CryptoError>(())
+//! ```
+//!
+//! [PHC string]: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md
+
+use crate::crypto::random::fill_random;
+use crate::crypto::{CryptoError, CryptoResult};
+use argon2::password_hash::{Ident, Output, ParamsString, PasswordHash, Salt, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+/// PHC identifier of the Argon2id backend
+pub const ARGON2ID_ID: &str = "argon2id";
+/// PHC identifier of the scrypt backend
+pub const SCRYPT_ID: &str = "scrypt";
+
+/// Random salt length for new hashes
+const SALT_SIZE: usize = 16;
+/// Hash output length for new hashes
+const OUTPUT_SIZE: usize = 32;
+
+/// Upper bounds accepted when verifying, so a tampered PHC string cannot make
+/// `verify_password` allocate or spin unboundedly
+const MAX_MEM_KIB: u64 = 256 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 64;
+const MAX_SCRYPT_R: u32 = 32;
+/// scrypt runs its `p` ROMix passes sequentially, so `p` multiplies CPU time
+const MAX_SCRYPT_P: u32 = 4;
+
+/// Password KDF backend and its cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordParams {
+    /// Argon2id (RFC 9106), version 0x13.
+    Argon2id {
+        /// Memory cost in KiB.
+        mem_kib: u32,
+        /// Number of passes.
+        iterations: u32,
+        /// Degree of parallelism (lanes).
+        parallelism: u32,
+    },
+    /// scrypt (RFC 7914).
+    Scrypt {
+        /// log2 of the CPU/memory cost `N`.
+        log_n: u8,
+        /// Block size.
+        r: u32,
+        /// Parallelization.
+        p: u32,
+    },
+}
+
+impl PasswordParams {
+    /// Argon2id with custom parameters.
+    pub fn argon2id(mem_kib: u32, iterations: u32, parallelism: u32) -> Self {
+        PasswordParams::Argon2id { mem_kib, iterations, parallelism }
+    }
+
+    /// scrypt with custom parameters.
+    pub fn scrypt(log_n: u8, r: u32, p: u32) -> Self {
+        PasswordParams::Scrypt { log_n, r, p }
+    }
+
+    /// OWASP-recommended scrypt settings (N = 2^17, r = 8, p = 1).
+    pub fn scrypt_default() -> Self {
+        PasswordParams::scrypt(17, 8, 1)
+    }
+
+    /// PHC identifier of this backend.
+    pub fn algorithm_id(&self) -> &'static str {
+        match self {
+            PasswordParams::Argon2id { .. } => ARGON2ID_ID,
+            PasswordParams::Scrypt { .. } => SCRYPT_ID,
+        }
+    }
+
+    /// Read the backend and parameters embedded in a PHC string.
+    ///
+    /// Useful for deciding whether a stored hash should be upgraded.
+    ///
+    /// # Errors
+    ///
+    /// `CryptoError::InvalidInput` if the string is malformed, names an
+    /// unsupported algorithm, or carries out-of-range parameters.
+    pub fn from_phc(phc: &str) -> CryptoResult<Self> {
+        let hash = PasswordHash::new(phc).map_err(phc_error)?;
+        Self::from_password_hash(&hash)
+    }
+
+    fn from_password_hash(hash: &PasswordHash<'_>) -> CryptoResult<Self> {
+        let decimal = |name: &str| {
+            hash.params
+                .get_decimal(name)
+                .ok_or_else(|| CryptoError::InvalidInput(format!("PHC string is missing parameter '{}'", name)))
+        };
+
+        let params = match hash.algorithm.as_str() {
+            ARGON2ID_ID => {
+                if hash.version.is_some_and(|v| v != Version::V0x13 as u32) {
+                    return Err(CryptoError::InvalidInput("Unsupported Argon2 version".to_string()));
+                }
+                PasswordParams::argon2id(decimal("m")?, decimal("t")?, decimal("p")?)
+            }
+            SCRYPT_ID => {
+                let log_n = u8::try_from(decimal("ln")?)
+                    .map_err(|_| CryptoError::InvalidInput("scrypt ln out of range".to_string()))?;
+                PasswordParams::scrypt(log_n, decimal("r")?, decimal("p")?)
+            }
+            other => {
+                return Err(CryptoError::InvalidInput(format!("Unsupported password hash algorithm: {}", other)));
+            }
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    fn validate(&self) -> CryptoResult<()> {
+        let in_range = match *self {
+            PasswordParams::Argon2id { mem_kib, iterations, parallelism } => {
+                u64::from(mem_kib) <= MAX_MEM_KIB
+                    && (1..=MAX_ITERATIONS).contains(&iterations)
+                    && (1..=MAX_PARALLELISM).contains(&parallelism)
+            }
+            PasswordParams::Scrypt { log_n, r, p } => {
+                // V holds N blocks of 128·r bytes
+                (1..=MAX_SCRYPT_R).contains(&r)
+                    && (1..=MAX_SCRYPT_P).contains(&p)
+                    && (1..32).contains(&log_n)
+                    && (128 * u64::from(r)) << log_n <= MAX_MEM_KIB * 1024
+            }
+        };
+        if !in_range {
+            return Err(CryptoError::InvalidInput(format!("Password hash parameters out of range: {:?}", self)));
+        }
+        Ok(())
+    }
+
+    fn params_string(&self) -> CryptoResult<ParamsString> {
+        let mut params = ParamsString::new();
+        match *self {
+            PasswordParams::Argon2id { mem_kib, iterations, parallelism } => {
+                params.add_decimal("m", mem_kib).map_err(phc_error)?;
+                params.add_decimal("t", iterations).map_err(phc_error)?;
+                params.add_decimal("p", parallelism).map_err(phc_error)?;
+            }
+            PasswordParams::Scrypt { log_n, r, p } => {
+                params.add_decimal("ln", u32::from(log_n)).map_err(phc_error)?;
+                params.add_decimal("r", r).map_err(phc_error)?;
+                params.add_decimal("p", p).map_err(phc_error)?;
+            }
+        }
+        Ok(params)
+    }
+
+    fn derive(&self, password: &[u8], salt: &[u8], output: &mut [u8]) -> CryptoResult<()> {
+        match *self {
+            PasswordParams::Argon2id { mem_kib, iterations, parallelism } => {
+                let argon_params = Params::new(mem_kib, iterations, parallelism, Some(output.len()))
+                    .map_err(|e| CryptoError::InvalidInput(format!("Invalid Argon2 parameters: {}", e)))?;
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+                    .hash_password_into(password, salt, output)
+                    .map_err(|e| CryptoError::KeyGenerationFailed(format!("Password hashing failed: {}", e)))
+            }
+            PasswordParams::Scrypt { log_n, r, p } => scrypt_into(password, salt, log_n, r, p, output),
+        }
+    }
+}
+
+impl Default for PasswordParams {
+    /// OWASP-recommended Argon2id settings (19 MiB, 2 passes, 1 lane).
+    fn default() -> Self {
+        PasswordParams::argon2id(19 * 1024, 2, 1)
+    }
+}
+
+/// Hash `password` under a fresh random salt and return the PHC string
+///
+/// # Errors
+///
+/// `CryptoError::InvalidInput` if `params` is out of range.
+pub fn hash_password(password: &[u8], params: &PasswordParams) -> CryptoResult<String> {
+    params.validate()?;
+
+    let mut salt = [0u8; SALT_SIZE];
+    fill_random(&mut salt)?;
+    let salt_string = SaltString::encode_b64(&salt).map_err(phc_error)?;
+
+    let mut output = Zeroizing::new([0u8; OUTPUT_SIZE]);
+    params.derive(password, &salt, output.as_mut())?;
+
+    let hash = PasswordHash {
+        algorithm: Ident::new(params.algorithm_id()).map_err(phc_error)?,
+        version: match params {
+            PasswordParams::Argon2id { .. } => Some(Version::V0x13 as u32),
+            PasswordParams::Scrypt { .. } => None,
+        },
+        params: params.params_string()?,
+        salt: Some(salt_string.as_salt()),
+        hash: Some(Output::new(output.as_ref()).map_err(phc_error)?),
+    };
+    Ok(hash.to_string())
+}
+
+/// Check `password` against a PHC string from [`hash_password`]
+///
+/// The backend is chosen by the PHC identifier and the stored hash is
+/// compared in constant time. Malformed strings, unsupported algorithms and
+/// out-of-range parameters all verify as `false`.
+pub fn verify_password(password: &[u8], phc: &str) -> bool {
+    try_verify(password, phc).unwrap_or(false)
+}
+
+fn try_verify(password: &[u8], phc: &str) -> CryptoResult<bool> {
+    let hash = PasswordHash::new(phc).map_err(phc_error)?;
+    let params = PasswordParams::from_password_hash(&hash)?;
+    let (salt, expected) = match (hash.salt, hash.hash) {
+        (Some(salt), Some(expected)) => (salt, expected),
+        _ => return Err(CryptoError::InvalidInput("PHC string has no salt or hash".to_string())),
+    };
+
+    let mut salt_buf = [0u8; Salt::MAX_LENGTH];
+    let salt = salt.decode_b64(&mut salt_buf).map_err(phc_error)?;
+
+    let mut output = Zeroizing::new(vec![0u8; expected.len()]);
+    params.derive(password, salt, &mut output)?;
+    Ok(bool::from(output.as_slice().ct_eq(expected.as_bytes())))
+}
+
+fn phc_error(e: argon2::password_hash::Error) -> CryptoError {
+    CryptoError::InvalidInput(format!("Invalid PHC string: {}", e))
+}
+
+/// scrypt (RFC 7914) with `N = 2^log_n`
+fn scrypt_into(password: &[u8], salt: &[u8], log_n: u8, r: u32, p: u32, output: &mut [u8]) -> CryptoResult<()> {
+    let params = scrypt::Params::new(log_n, r, p, output.len())
+        .map_err(|e| CryptoError::InvalidInput(format!("Invalid scrypt parameters: {}", e)))?;
+    scrypt::scrypt(password, salt, &params, output)
+        .map_err(|e| CryptoError::KeyGenerationFailed(format!("Password hashing failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_params() -> PasswordParams {
+        PasswordParams::argon2id(256, 1, 1)
+    }
+
+    #[test]
+    fn test_hash_and_verify() {
+        for params in [test_params(), PasswordParams::scrypt(4, 8, 1)] {
+            let phc = hash_password(b"hunter2", &params).unwrap();
+            assert!(phc.starts_with(&format!("${}$", params.algorithm_id())));
+            assert_eq!(PasswordParams::from_phc(&phc).unwrap(), params);
+            assert!(verify_password(b"hunter2", &phc));
+            assert!(!verify_password(b"hunter3", &phc));
+        }
+    }
+
+    #[test]
+    fn test_scrypt_rfc7914_vector() {
+        let mut output = [0u8; 64];
+        scrypt_into(b"", b"", 4, 1, 1, &mut output).unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442\
+             fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+        );
+
+        scrypt_into(b"password", b"NaCl", 10, 8, 16, &mut output).unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+             2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+        );
+    }
+
+    #[test]
+    fn test_verify_known_phc_strings() {
+        // Produced by `argon2::PasswordHasher` with salt "somesaltsomesalt"
+        let argon2 = "$argon2id$v=19$m=256,t=1,p=1$c29tZXNhbHRzb21lc2FsdA$hn7+SzhLFWN5L2vw5todiDT80rAFnVmYwlI26PSf7pM";
+        // Same password and salt under different costs must not verify
+        assert!(!verify_password(b"password", &argon2.replace("t=1", "t=2")));
+        assert_eq!(PasswordParams::from_phc(argon2).unwrap(), test_params());
+        assert!(verify_password(b"password", argon2));
+        assert!(!verify_password(b"Password", argon2));
+    }
+
+    #[test]
+    fn test_malformed_phc_rejected() {
+        let phc = hash_password(b"password", &test_params()).unwrap();
+        let malformed = [
+            "",
+            "argon2id$v=19$m=256,t=1,p=1",
+            "$bcrypt$v=19$m=256,t=1,p=1$c29tZXNhbHQ$aGFzaGhhc2hoYXNo",
+            "$argon2id$v=19$m=256,t=1$c29tZXNhbHQ$aGFzaGhhc2hoYXNo",
+            "$argon2id$v=16$m=256,t=1,p=1$c29tZXNhbHQ$aGFzaGhhc2hoYXNo",
+            "$argon2id$v=19$m=4294967295,t=1,p=1$c29tZXNhbHQ$aGFzaGhhc2hoYXNo",
+            "$scrypt$ln=40,r=8,p=1$c29tZXNhbHQ$aGFzaGhhc2hoYXNo",
+            // Over the verify caps: 512 MiB of Argon2 memory, scrypt p = 16
+            "$argon2id$v=19$m=524288,t=1,p=1$c29tZXNhbHQ$aGFzaGhhc2hoYXNo",
+            "$scrypt$ln=4,r=8,p=16$c29tZXNhbHQ$aGFzaGhhc2hoYXNo",
+        ];
+        for phc in malformed {
+            assert!(PasswordParams::from_phc(phc).is_err(), "{}", phc);
+            assert!(!verify_password(b"password", phc), "{}", phc);
+        }
+        // Salt present but hash stripped
+        let (truncated, _) = phc.rsplit_once('$').unwrap();
+        assert!(!verify_password(b"password", truncated));
+    }
+}
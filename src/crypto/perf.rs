@@ -3,6 +3,83 @@
 //! B4AE uses hardware acceleration when available via aes-gcm and pqcrypto crates.
 //! This module provides runtime detection for diagnostics and optional fallbacks.
 
+use std::fmt;
+
+/// CPU crypto features detected at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuFeatures {
+    /// AES-NI (x86/x86_64)
+    pub aes_ni: bool,
+    /// AVX2 (x86/x86_64)
+    pub avx2: bool,
+    /// PCLMULQDQ carry-less multiply, used by GHASH (x86/x86_64)
+    pub pclmulqdq: bool,
+    /// NEON / Advanced SIMD (aarch64)
+    pub neon: bool,
+    /// ARMv8 AES instructions (aarch64)
+    pub arm_aes: bool,
+    /// ARMv8 PMULL polynomial multiply, used by GHASH (aarch64)
+    pub arm_pmull: bool,
+}
+
+impl CpuFeatures {
+    /// True when both AES rounds and GHASH run in hardware, i.e. AES-GCM
+    /// is accelerated end to end
+    pub fn aes_gcm_accelerated(&self) -> bool {
+        (self.aes_ni && self.pclmulqdq) || (self.arm_aes && self.arm_pmull)
+    }
+
+    /// Names of the detected features, in a fixed order
+    pub fn enabled(&self) -> Vec<&'static str> {
+        [
+            (self.aes_ni, "aes-ni"),
+            (self.avx2, "avx2"),
+            (self.pclmulqdq, "pclmulqdq"),
+            (self.neon, "neon"),
+            (self.arm_aes, "aes"),
+            (self.arm_pmull, "pmull"),
+        ]
+        .iter()
+        .filter(|(present, _)| *present)
+        .map(|(_, name)| *name)
+        .collect()
+    }
+}
+
+/// Comma-separated feature names, or `none`
+impl fmt::Display for CpuFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let enabled = self.enabled();
+        if enabled.is_empty() {
+            f.write_str("none")
+        } else {
+            f.write_str(&enabled.join(", "))
+        }
+    }
+}
+
+/// Detect the crypto-relevant CPU features of the host
+pub fn detected_features() -> CpuFeatures {
+    #[allow(unused_mut)]
+    let mut features = CpuFeatures::default();
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        features.aes_ni = std::arch::is_x86_feature_detected!("aes");
+        features.avx2 = std::arch::is_x86_feature_detected!("avx2");
+        features.pclmulqdq = std::arch::is_x86_feature_detected!("pclmulqdq");
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        features.neon = std::arch::is_aarch64_feature_detected!("neon");
+        features.arm_aes = std::arch::is_aarch64_feature_detected!("aes");
+        features.arm_pmull = std::arch::is_aarch64_feature_detected!("pmull");
+    }
+
+    features
+}
+
 /// Detect AES-NI (x86/x86_64). Returns true if hardware AES is available.
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 pub fn aes_ni_available() -> bool {
@@ -38,4 +115,32 @@ pub fn print_cpu_capabilities() {
     println!("B4AE CPU capabilities:");
     println!("  AES-NI / hardware AES: {}", aes_ni_available());
     println!("  AVX2: {}", avx2_available());
+    println!("  Detected features: {}", detected_features());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detection_runs_on_host() {
+        let features = detected_features();
+        assert_eq!(features, detected_features());
+        assert_eq!(features.aes_ni || features.arm_aes, aes_ni_available());
+        assert_eq!(features.avx2, avx2_available());
+        assert!(!features.to_string().is_empty());
+    }
+
+    #[test]
+    fn test_display_lists_enabled_features() {
+        assert_eq!(CpuFeatures::default().to_string(), "none");
+
+        let x86 = CpuFeatures { aes_ni: true, pclmulqdq: true, ..Default::default() };
+        assert_eq!(x86.to_string(), "aes-ni, pclmulqdq");
+        assert!(x86.aes_gcm_accelerated());
+
+        let arm = CpuFeatures { neon: true, arm_aes: true, ..Default::default() };
+        assert_eq!(arm.to_string(), "neon, aes");
+        assert!(!arm.aes_gcm_accelerated());
+    }
 }
@@ -5,7 +5,9 @@ use crate::crypto::{CryptoError, CryptoResult};
 use crate::crypto::hkdf;
 use crate::crypto::random;
 use crate::time;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use zeroize::Zeroize;
 
 /// PFS+ Key Chain for enhanced forward secrecy
 pub struct PfsKeyChain {
@@ -33,6 +35,28 @@ pub struct PfsSession {
     rotation_interval: u64,
 }
 
+/// Serialized [`PfsKeyChain`] for session export
+///
+/// The chain itself has no serde impls, so its keys cannot be dumped through
+/// an arbitrary serializer; only the session export builds one of these.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct PfsKeyChainSnapshot {
+    chain_key: [u8; 32],
+    message_counter: u64,
+    key_cache: Vec<(u64, [u8; 32])>,
+    max_cache_size: usize,
+}
+
+/// Serialized [`PfsSession`] for session export
+#[derive(Serialize, Deserialize)]
+pub(crate) struct PfsSessionSnapshot {
+    send_chain: PfsKeyChainSnapshot,
+    receive_chain: PfsKeyChainSnapshot,
+    session_id: [u8; 32],
+    last_rotation: u64,
+    rotation_interval: u64,
+}
+
 impl PfsKeyChain {
     /// Create new key chain from initial key
     pub fn new(initial_key: &[u8]) -> CryptoResult<Self> {
@@ -179,6 +203,26 @@ impl PfsKeyChain {
     pub fn cleanup_cache(&mut self, before_counter: u64) {
         self.key_cache.retain(|&counter, _| counter >= before_counter);
     }
+
+    pub(crate) fn snapshot(&self) -> PfsKeyChainSnapshot {
+        let mut key_cache = Vec::with_capacity(self.key_cache.len());
+        key_cache.extend(self.key_cache.iter().map(|(counter, key)| (*counter, *key)));
+        PfsKeyChainSnapshot {
+            chain_key: self.chain_key,
+            message_counter: self.message_counter,
+            key_cache,
+            max_cache_size: self.max_cache_size,
+        }
+    }
+
+    pub(crate) fn from_snapshot(snapshot: &PfsKeyChainSnapshot) -> Self {
+        PfsKeyChain {
+            chain_key: snapshot.chain_key,
+            message_counter: snapshot.message_counter,
+            key_cache: snapshot.key_cache.iter().copied().collect(),
+            max_cache_size: snapshot.max_cache_size,
+        }
+    }
 }
 
 impl PfsSession {
@@ -259,6 +303,26 @@ impl PfsSession {
             self.receive_chain.cleanup_cache(cleanup_before);
         }
     }
+
+    pub(crate) fn snapshot(&self) -> PfsSessionSnapshot {
+        PfsSessionSnapshot {
+            send_chain: self.send_chain.snapshot(),
+            receive_chain: self.receive_chain.snapshot(),
+            session_id: self.session_id,
+            last_rotation: self.last_rotation,
+            rotation_interval: self.rotation_interval,
+        }
+    }
+
+    pub(crate) fn from_snapshot(snapshot: &PfsSessionSnapshot) -> Self {
+        PfsSession {
+            send_chain: PfsKeyChain::from_snapshot(&snapshot.send_chain),
+            receive_chain: PfsKeyChain::from_snapshot(&snapshot.receive_chain),
+            session_id: snapshot.session_id,
+            last_rotation: snapshot.last_rotation,
+            rotation_interval: snapshot.rotation_interval,
+        }
+    }
 }
 
 /// PFS+ Manager for handling multiple sessions
@@ -341,6 +405,15 @@ impl Drop for PfsKeyChain {
     }
 }
 
+impl Drop for PfsKeyChainSnapshot {
+    fn drop(&mut self) {
+        self.chain_key.zeroize();
+        for (_, key) in self.key_cache.iter_mut() {
+            key.zeroize();
+        }
+    }
+}
+
 impl Drop for PfsSession {
     fn drop(&mut self) {
         // Zero out session ID
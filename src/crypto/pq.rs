@@ -66,6 +66,24 @@
 //! # Ok::<(), b4ae::crypto::CryptoError>(())
 //! ```
 //!
+//! # ML-KEM (FIPS 203) and legacy Kyber
+//!
+//! [`MlKem768`] and [`MlKem1024`] expose the standardized ML-KEM API with the
+//! FIPS 203 byte layouts (`ek = ByteEncode12(t̂) || ρ`,
+//! `dk = dk_PKE || ek || H(ek) || z`) and the FIPS 203 input checks on parsed
+//! keys. They are only available with the `pqcrypto-mlkem` feature (enabled
+//! by default), which also switches [`KyberKem`] and the rest of the protocol
+//! to ML-KEM. Building with `--no-default-features --features pqcrypto-kyber`
+//! selects the pre-standard Kyber round-3 implementation instead.
+//!
+//! The two are **not wire compatible**. Key and ciphertext sizes are the
+//! same, but FIPS 203 derives the shared secret differently (no hashing of the
+//! encapsulated message, no final KDF over the ciphertext) and
+//! domain-separates key generation by the rank k. A round-3 peer and an ML-KEM
+//! peer exchange well-formed bytes, decapsulation succeeds through implicit
+//! rejection, and the handshake only fails later when the derived keys
+//! disagree. All peers must be built with the same backend.
+//!
 //! # Constant-Time Operations
 //!
 //! All cryptographic operations in this module are designed to be constant-time
@@ -82,6 +100,11 @@ use crate::crypto::kyber::{self, KyberPublicKey, KyberSecretKey, KyberCiphertext
 use crate::crypto::dilithium::{self, DilithiumPublicKey, DilithiumSecretKey, DilithiumSignature};
 use std::fmt;
 
+#[cfg(feature = "pqcrypto-mlkem")]
+use crate::crypto::kyber::{KyberVariant, VariantCiphertext, VariantPublicKey, VariantSecretKey};
+#[cfg(feature = "pqcrypto-mlkem")]
+use subtle::ConstantTimeEq;
+
 // Re-export key types for convenience
 pub use crate::crypto::kyber::{KyberPublicKey as PqKemPublicKey, KyberSecretKey as PqKemSecretKey};
 pub use crate::crypto::dilithium::{DilithiumPublicKey as PqSignPublicKey, DilithiumSecretKey as PqSignSecretKey};
@@ -305,6 +328,218 @@ impl PqSignature for DilithiumSigner {
     }
 }
 
+/// ML-KEM encapsulation key (FIPS 203 `ek`): `ByteEncode12(t̂) || ρ`
+#[cfg(feature = "pqcrypto-mlkem")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MlKemEncapsulationKey(VariantPublicKey);
+
+/// ML-KEM decapsulation key (FIPS 203 `dk`): `dk_PKE || ek || H(ek) || z`
+#[cfg(feature = "pqcrypto-mlkem")]
+#[derive(Debug)]
+pub struct MlKemDecapsulationKey(VariantSecretKey);
+
+/// ML-KEM ciphertext
+#[cfg(feature = "pqcrypto-mlkem")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MlKemCiphertext(VariantCiphertext);
+
+/// ML-KEM keypair
+#[cfg(feature = "pqcrypto-mlkem")]
+#[derive(Debug)]
+pub struct MlKemKeyPair {
+    /// Encapsulation key (public)
+    pub encapsulation_key: MlKemEncapsulationKey,
+    /// Decapsulation key (secret)
+    pub decapsulation_key: MlKemDecapsulationKey,
+}
+
+/// Module rank k of a parameter set (2, 3 or 4)
+#[cfg(feature = "pqcrypto-mlkem")]
+fn mlkem_rank(variant: KyberVariant) -> usize {
+    (variant.public_key_size() - 32) / 384
+}
+
+#[cfg(feature = "pqcrypto-mlkem")]
+impl MlKemEncapsulationKey {
+    /// Parse an `ek`, applying the FIPS 203 §7.2 type and modulus checks
+    pub fn from_bytes(variant: KyberVariant, bytes: &[u8]) -> CryptoResult<Self> {
+        let key = VariantPublicKey::from_bytes(variant, bytes)?;
+        // Every 12-bit coefficient of t̂ must already be reduced mod q
+        let encoded_t = &bytes[..384 * mlkem_rank(variant)];
+        let reduced = encoded_t.chunks_exact(3).all(|b| {
+            let c0 = u16::from(b[0]) | (u16::from(b[1] & 0x0F) << 8);
+            let c1 = u16::from(b[1] >> 4) | (u16::from(b[2]) << 4);
+            c0 < 3329 && c1 < 3329
+        });
+        if !reduced {
+            return Err(CryptoError::InvalidInput("ML-KEM encapsulation key failed modulus check".to_string()));
+        }
+        Ok(MlKemEncapsulationKey(key))
+    }
+
+    /// Parameter set of this key
+    pub fn variant(&self) -> KyberVariant {
+        self.0.variant()
+    }
+
+    /// FIPS 203 byte encoding
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+#[cfg(feature = "pqcrypto-mlkem")]
+impl MlKemDecapsulationKey {
+    /// Parse a `dk`, applying the FIPS 203 §7.3 type and hash checks
+    pub fn from_bytes(variant: KyberVariant, bytes: &[u8]) -> CryptoResult<Self> {
+        use sha3::{Digest, Sha3_256};
+
+        let key = VariantSecretKey::from_bytes(variant, bytes)?;
+        let ek_start = 384 * mlkem_rank(variant);
+        let ek_end = ek_start + variant.public_key_size();
+        let hash = Sha3_256::digest(&bytes[ek_start..ek_end]);
+        if !bool::from(hash.as_slice().ct_eq(&bytes[ek_end..ek_end + 32])) {
+            return Err(CryptoError::InvalidInput("ML-KEM decapsulation key failed hash check".to_string()));
+        }
+        Ok(MlKemDecapsulationKey(key))
+    }
+
+    /// Parameter set of this key
+    pub fn variant(&self) -> KyberVariant {
+        self.0.variant()
+    }
+
+    /// FIPS 203 byte encoding
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// Encapsulation key embedded in `dk`
+    pub fn encapsulation_key(&self) -> CryptoResult<MlKemEncapsulationKey> {
+        let variant = self.variant();
+        let ek_start = 384 * mlkem_rank(variant);
+        MlKemEncapsulationKey::from_bytes(variant, &self.as_bytes()[ek_start..ek_start + variant.public_key_size()])
+    }
+}
+
+#[cfg(feature = "pqcrypto-mlkem")]
+impl MlKemCiphertext {
+    /// Parse a ciphertext (FIPS 203 §7.3 type check)
+    pub fn from_bytes(variant: KyberVariant, bytes: &[u8]) -> CryptoResult<Self> {
+        Ok(MlKemCiphertext(VariantCiphertext::from_bytes(variant, bytes)?))
+    }
+
+    /// Parameter set of this ciphertext
+    pub fn variant(&self) -> KyberVariant {
+        self.0.variant()
+    }
+
+    /// Raw bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+#[cfg(feature = "pqcrypto-mlkem")]
+fn check_mlkem_variant(expected: KyberVariant, actual: KyberVariant) -> CryptoResult<()> {
+    if expected != actual {
+        return Err(CryptoError::InvalidInput(format!(
+            "Expected {:?} key material, got {:?}", expected, actual
+        )));
+    }
+    Ok(())
+}
+
+/// Define a FIPS 203 ML-KEM wrapper for one parameter set
+#[cfg(feature = "pqcrypto-mlkem")]
+macro_rules! mlkem_kem {
+    ($(#[$doc:meta])* $name:ident, $variant:expr, $level:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct $name;
+
+        impl $name {
+            /// Parameter set
+            pub const VARIANT: KyberVariant = $variant;
+
+            /// Create a new instance
+            pub fn new() -> CryptoResult<Self> {
+                Ok($name)
+            }
+
+            /// Get the NIST security level
+            pub fn security_level(&self) -> u8 {
+                $level
+            }
+
+            /// Get encapsulation key size in bytes
+            pub fn encapsulation_key_size(&self) -> usize {
+                Self::VARIANT.public_key_size()
+            }
+
+            /// Get decapsulation key size in bytes
+            pub fn decapsulation_key_size(&self) -> usize {
+                Self::VARIANT.secret_key_size()
+            }
+
+            /// Get ciphertext size in bytes
+            pub fn ciphertext_size(&self) -> usize {
+                Self::VARIANT.ciphertext_size()
+            }
+
+            /// Get shared secret size in bytes
+            pub fn shared_secret_size(&self) -> usize {
+                Self::VARIANT.shared_secret_size()
+            }
+        }
+
+        impl PqKem for $name {
+            type PublicKey = MlKemEncapsulationKey;
+            type SecretKey = MlKemDecapsulationKey;
+            type Ciphertext = MlKemCiphertext;
+            type SharedSecret = KyberSharedSecret;
+            type KeyPair = MlKemKeyPair;
+
+            fn generate_keypair(&self) -> CryptoResult<Self::KeyPair> {
+                let kp = kyber::keypair_with(Self::VARIANT)?;
+                Ok(MlKemKeyPair {
+                    encapsulation_key: MlKemEncapsulationKey(kp.public_key),
+                    decapsulation_key: MlKemDecapsulationKey(kp.secret_key),
+                })
+            }
+
+            fn encapsulate(&self, public_key: &Self::PublicKey) -> CryptoResult<(Self::SharedSecret, Self::Ciphertext)> {
+                check_mlkem_variant(Self::VARIANT, public_key.variant())?;
+                let (ss, ct) = kyber::encapsulate_with(&public_key.0)?;
+                Ok((ss, MlKemCiphertext(ct)))
+            }
+
+            fn decapsulate(&self, secret_key: &Self::SecretKey, ciphertext: &Self::Ciphertext) -> CryptoResult<Self::SharedSecret> {
+                check_mlkem_variant(Self::VARIANT, secret_key.variant())?;
+                kyber::decapsulate_with(&secret_key.0, &ciphertext.0)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "pqcrypto-mlkem")]
+mlkem_kem!(
+    /// ML-KEM-768 (FIPS 203, NIST Level 3)
+    ///
+    /// Encapsulation key 1184 bytes, decapsulation key 2400 bytes, ciphertext
+    /// 1088 bytes, shared secret 32 bytes.
+    MlKem768, KyberVariant::Kyber768, 3
+);
+
+#[cfg(feature = "pqcrypto-mlkem")]
+mlkem_kem!(
+    /// ML-KEM-1024 (FIPS 203, NIST Level 5)
+    ///
+    /// Encapsulation key 1568 bytes, decapsulation key 3168 bytes, ciphertext
+    /// 1568 bytes, shared secret 32 bytes.
+    MlKem1024, KyberVariant::Kyber1024, 5
+);
+
 /// NIST security level information
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NistSecurityLevel {
@@ -508,4 +743,92 @@ mod tests {
         test_kem(KyberKem::new().unwrap());
         test_signature(DilithiumSigner::new().unwrap());
     }
+
+    #[cfg(feature = "pqcrypto-mlkem")]
+    #[test]
+    fn test_mlkem_roundtrip_and_sizes() {
+        let kem = MlKem768::new().unwrap();
+        assert_eq!(
+            (kem.encapsulation_key_size(), kem.decapsulation_key_size(), kem.ciphertext_size()),
+            (1184, 2400, 1088)
+        );
+        let keypair = kem.generate_keypair().unwrap();
+        let (ss1, ct) = kem.encapsulate(&keypair.encapsulation_key).unwrap();
+        let ss2 = kem.decapsulate(&keypair.decapsulation_key, &ct).unwrap();
+        assert_eq!(ss1.as_bytes(), ss2.as_bytes());
+        assert_eq!(keypair.decapsulation_key.encapsulation_key().unwrap(), keypair.encapsulation_key);
+
+        let kem = MlKem1024::new().unwrap();
+        assert_eq!(kem.security_level(), 5);
+        let keypair = kem.generate_keypair().unwrap();
+        let (ss1, ct) = kem.encapsulate(&keypair.encapsulation_key).unwrap();
+        let ss2 = kem.decapsulate(&keypair.decapsulation_key, &ct).unwrap();
+        assert_eq!(ss1.as_bytes(), ss2.as_bytes());
+
+        // ML-KEM-1024 keys are rejected by the ML-KEM-768 instance
+        assert!(MlKem768.encapsulate(&keypair.encapsulation_key).is_err());
+    }
+
+    #[cfg(feature = "pqcrypto-mlkem")]
+    #[test]
+    fn test_mlkem_input_checks() {
+        let keypair = MlKem768.generate_keypair().unwrap();
+
+        // Coefficient 0xFFF >= q fails the encapsulation key modulus check
+        let mut ek = keypair.encapsulation_key.as_bytes().to_vec();
+        ek[0] = 0xFF;
+        ek[1] |= 0x0F;
+        assert!(MlKemEncapsulationKey::from_bytes(KyberVariant::Kyber768, &ek).is_err());
+
+        // Corrupting the embedded ek breaks H(ek) in the decapsulation key
+        let mut dk = keypair.decapsulation_key.as_bytes().to_vec();
+        dk[384 * 3] ^= 0x01;
+        assert!(MlKemDecapsulationKey::from_bytes(KyberVariant::Kyber768, &dk).is_err());
+        assert!(MlKemDecapsulationKey::from_bytes(KyberVariant::Kyber768, keypair.decapsulation_key.as_bytes()).is_ok());
+
+        assert!(MlKemCiphertext::from_bytes(KyberVariant::Kyber768, &[0u8; 1568]).is_err());
+    }
+
+    /// NIST ACVP ML-KEM encapDecap vectors (decapsulation group, tgId 5 for
+    /// ML-KEM-768 and 6 for ML-KEM-1024): `ek || dk || c || K` for one valid
+    /// and one modified ciphertext, the latter decapsulating to the implicit
+    /// rejection secret
+    #[cfg(feature = "pqcrypto-mlkem")]
+    #[test]
+    fn test_mlkem_known_answer() {
+        let cases: [(KyberVariant, &[u8]); 2] = [
+            (KyberVariant::Kyber768, include_bytes!("pq_kat_ml_kem_768.bin")),
+            (KyberVariant::Kyber1024, include_bytes!("pq_kat_ml_kem_1024.bin")),
+        ];
+
+        for (variant, vectors) in cases {
+            let (ek, rest) = vectors.split_at(variant.public_key_size());
+            let (dk, rest) = rest.split_at(variant.secret_key_size());
+            let case_size = variant.ciphertext_size() + variant.shared_secret_size();
+            assert_eq!(rest.len(), 2 * case_size);
+
+            // The published keys parse with the FIPS 203 layout
+            let dk = MlKemDecapsulationKey::from_bytes(variant, dk).unwrap();
+            let ek = MlKemEncapsulationKey::from_bytes(variant, ek).unwrap();
+            assert_eq!(dk.encapsulation_key().unwrap(), ek);
+
+            let decapsulate = |ct: &MlKemCiphertext| match variant {
+                KyberVariant::Kyber768 => MlKem768.decapsulate(&dk, ct),
+                _ => MlKem1024.decapsulate(&dk, ct),
+            };
+            for case in rest.chunks_exact(case_size) {
+                let (ct, expected) = case.split_at(variant.ciphertext_size());
+                let ct = MlKemCiphertext::from_bytes(variant, ct).unwrap();
+                assert_eq!(decapsulate(&ct).unwrap().as_bytes(), expected);
+            }
+
+            // Encapsulating to the published key agrees with the published decapsulation key
+            let (ss, ct) = match variant {
+                KyberVariant::Kyber768 => MlKem768.encapsulate(&ek),
+                _ => MlKem1024.encapsulate(&ek),
+            }
+            .unwrap();
+            assert_eq!(decapsulate(&ct).unwrap().as_bytes(), ss.as_bytes());
+        }
+    }
 }
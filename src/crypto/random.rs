@@ -1,35 +1,175 @@
 // B4AE Random Number Generation
 // Cryptographically secure random number generation
+//
+// All helpers draw from the OS CSPRNG. With `cfg(test)` or the
+// `deterministic-rng` feature, `with_random_source` can temporarily replace
+// it on the current thread (e.g. with a seeded `DeterministicRandomSource`)
+// to make protocol runs reproducible.
+//
+// The `*_with_rng` variants take the generator as a parameter instead, for
+// FIPS modules, HSM-backed entropy or reproducible test vectors. Every key,
+// nonce and padding decision depends on that generator: if it is weak,
+// seeded predictably or repeats output, no other part of B4AE stays secure.
 
-use crate::crypto::CryptoResult;
+use crate::crypto::{CryptoError, CryptoResult};
 use rand::rngs::OsRng;
-use rand::RngCore;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Source of random bytes for key generation and nonces
+pub trait RandomSource: Send {
+    /// Fill `dest` with random bytes
+    fn fill_bytes(&mut self, dest: &mut [u8]);
+
+    /// Generate random u32
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Generate random u64
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+/// Operating system CSPRNG (the production default)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsRandomSource;
+
+impl RandomSource for OsRandomSource {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        OsRng.fill_bytes(dest);
+    }
+}
+
+/// Seeded ChaCha20 stream for reproducible tests and fuzz-crash replay
+///
+/// Never use for production keys: anyone holding the seed can recompute
+/// every value it produces.
+pub struct DeterministicRandomSource {
+    rng: ChaCha20Rng,
+}
+
+impl DeterministicRandomSource {
+    /// Create from a 32-byte seed
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        DeterministicRandomSource { rng: ChaCha20Rng::from_seed(seed) }
+    }
+}
+
+impl RandomSource for DeterministicRandomSource {
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+    }
+}
+
+impl std::fmt::Debug for DeterministicRandomSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DeterministicRandomSource([REDACTED])")
+    }
+}
+
+#[cfg(any(test, feature = "deterministic-rng"))]
+thread_local! {
+    static SOURCE_OVERRIDE: std::cell::RefCell<Option<Box<dyn RandomSource>>> =
+        std::cell::RefCell::new(None);
+}
+
+/// Run `f` with `source` replacing the OS CSPRNG on the current thread
+///
+/// Affects the helpers in this module and [`SecureRng`]; the previous
+/// source is restored afterwards, even if `f` panics. Only available in
+/// tests or with the `deterministic-rng` feature.
+#[cfg(any(test, feature = "deterministic-rng"))]
+pub fn with_random_source<S, F, R>(source: S, f: F) -> R
+where
+    S: RandomSource + 'static,
+    F: FnOnce() -> R,
+{
+    struct Restore(Option<Box<dyn RandomSource>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            SOURCE_OVERRIDE.with(|cell| *cell.borrow_mut() = previous);
+        }
+    }
+
+    let previous = SOURCE_OVERRIDE.with(|cell| cell.borrow_mut().replace(Box::new(source)));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Fill from the active source (override if installed, otherwise OS)
+fn fill_from_source(dest: &mut [u8]) {
+    #[cfg(any(test, feature = "deterministic-rng"))]
+    {
+        let overridden = SOURCE_OVERRIDE.with(|cell| match cell.borrow_mut().as_mut() {
+            Some(source) => {
+                source.fill_bytes(dest);
+                true
+            }
+            None => false,
+        });
+        if overridden {
+            return;
+        }
+    }
+
+    OsRng.fill_bytes(dest);
+}
 
 /// Generate cryptographically secure random bytes
 pub fn random_bytes(length: usize) -> Vec<u8> {
     let mut bytes = vec![0u8; length];
-    OsRng.fill_bytes(&mut bytes);
+    fill_from_source(&mut bytes);
+    bytes
+}
+
+/// Generate random bytes from a caller-supplied CSPRNG
+pub fn random_bytes_with_rng<R: RngCore + CryptoRng>(rng: &mut R, length: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; length];
+    rng.fill_bytes(&mut bytes);
     bytes
 }
 
 /// Generate random bytes into existing buffer
 pub fn fill_random(buffer: &mut [u8]) -> CryptoResult<()> {
-    OsRng.fill_bytes(buffer);
+    fill_from_source(buffer);
     Ok(())
 }
 
+/// Fill `buffer` from a caller-supplied CSPRNG, reporting generator failure
+pub fn fill_random_with_rng<R: RngCore + CryptoRng>(rng: &mut R, buffer: &mut [u8]) -> CryptoResult<()> {
+    rng.try_fill_bytes(buffer)
+        .map_err(|e| CryptoError::KeyGenerationFailed(format!("RNG failure: {}", e)))
+}
+
 /// Generate random u32
 pub fn random_u32() -> u32 {
-    OsRng.next_u32()
+    let mut bytes = [0u8; 4];
+    fill_from_source(&mut bytes);
+    u32::from_le_bytes(bytes)
 }
 
 /// Generate random u64
 pub fn random_u64() -> u64 {
-    OsRng.next_u64()
+    let mut bytes = [0u8; 8];
+    fill_from_source(&mut bytes);
+    u64::from_le_bytes(bytes)
 }
 
 /// Generate random value in range [0, max)
 pub fn random_range(max: u64) -> u64 {
+    random_range_with_rng(&mut SecureRng::new(), max)
+}
+
+/// Generate random value in range [0, max) from a caller-supplied CSPRNG
+pub fn random_range_with_rng<R: RngCore + CryptoRng>(rng: &mut R, max: u64) -> u64 {
     if max == 0 {
         return 0;
     }
@@ -37,7 +177,7 @@ pub fn random_range(max: u64) -> u64 {
     // Use rejection sampling to avoid modulo bias
     let range = u64::MAX - (u64::MAX % max);
     loop {
-        let value = random_u64();
+        let value = rng.next_u64();
         if value < range {
             return value % max;
         }
@@ -45,36 +185,37 @@ pub fn random_range(max: u64) -> u64 {
 }
 
 /// Secure random number generator wrapper
+///
+/// Usable wherever an `RngCore + CryptoRng` is required (e.g. X25519 key
+/// generation); draws from the same source as [`random_bytes`].
 pub struct SecureRng {
-    rng: OsRng,
+    _private: (),
 }
 
 impl SecureRng {
     /// Create new secure RNG
     pub fn new() -> Self {
-        SecureRng { rng: OsRng }
+        SecureRng { _private: () }
     }
 
     /// Generate random bytes
     pub fn generate_bytes(&mut self, length: usize) -> Vec<u8> {
-        let mut bytes = vec![0u8; length];
-        self.rng.fill_bytes(&mut bytes);
-        bytes
+        random_bytes(length)
     }
 
     /// Fill buffer with random bytes
     pub fn fill_bytes(&mut self, buffer: &mut [u8]) {
-        self.rng.fill_bytes(buffer);
+        fill_from_source(buffer);
     }
 
     /// Generate random u32
     pub fn next_u32(&mut self) -> u32 {
-        self.rng.next_u32()
+        random_u32()
     }
 
     /// Generate random u64
     pub fn next_u64(&mut self) -> u64 {
-        self.rng.next_u64()
+        random_u64()
     }
 }
 
@@ -86,19 +227,20 @@ impl Default for SecureRng {
 
 impl RngCore for SecureRng {
     fn next_u32(&mut self) -> u32 {
-        self.rng.next_u32()
+        random_u32()
     }
 
     fn next_u64(&mut self) -> u64 {
-        self.rng.next_u64()
+        random_u64()
     }
 
     fn fill_bytes(&mut self, dest: &mut [u8]) {
-        self.rng.fill_bytes(dest)
+        fill_from_source(dest)
     }
 
     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
-        self.rng.try_fill_bytes(dest)
+        fill_from_source(dest);
+        Ok(())
     }
 }
 
@@ -225,4 +367,69 @@ mod tests {
             sequences.insert(seq);
         }
     }
+
+    #[test]
+    fn test_deterministic_source_reproducible() {
+        let mut a = DeterministicRandomSource::from_seed([7u8; 32]);
+        let mut b = DeterministicRandomSource::from_seed([7u8; 32]);
+        let mut c = DeterministicRandomSource::from_seed([8u8; 32]);
+
+        let (mut out_a, mut out_b, mut out_c) = ([0u8; 64], [0u8; 64], [0u8; 64]);
+        a.fill_bytes(&mut out_a);
+        b.fill_bytes(&mut out_b);
+        c.fill_bytes(&mut out_c);
+
+        assert_eq!(out_a, out_b);
+        assert_ne!(out_a, out_c);
+        assert_eq!(a.next_u64(), b.next_u64());
+    }
+
+    #[test]
+    fn test_with_rng_reproducible() {
+        let mut a = ChaCha20Rng::from_seed([3u8; 32]);
+        let mut b = ChaCha20Rng::from_seed([3u8; 32]);
+
+        assert_eq!(random_bytes_with_rng(&mut a, 48), random_bytes_with_rng(&mut b, 48));
+        assert_eq!(random_range_with_rng(&mut a, 1000), random_range_with_rng(&mut b, 1000));
+
+        let (mut out_a, mut out_b) = ([0u8; 16], [0u8; 16]);
+        fill_random_with_rng(&mut a, &mut out_a).unwrap();
+        fill_random_with_rng(&mut b, &mut out_b).unwrap();
+        assert_eq!(out_a, out_b);
+
+        let mut other = ChaCha20Rng::from_seed([4u8; 32]);
+        assert_ne!(random_bytes_with_rng(&mut other, 48), random_bytes_with_rng(&mut a, 48));
+
+        // The AEAD wrappers draw keys and nonces through the same parameter
+        use crate::crypto::{aes_gcm, chacha20poly1305_wrapper as chacha};
+        let seal = |seed| {
+            let mut rng = ChaCha20Rng::from_seed(seed);
+            let key = aes_gcm::AesKey::generate_with_rng(&mut rng);
+            let (nonce, ciphertext) = aes_gcm::encrypt_with_rng(&mut rng, &key, b"payload", b"aad").unwrap();
+            let key: [u8; 32] = key.as_bytes().try_into().unwrap();
+            let xchacha = chacha::encrypt_xchacha_with_rng(&mut rng, &key, b"record", None).unwrap();
+            (key, nonce, ciphertext, xchacha)
+        };
+        let (key, nonce, ciphertext, xchacha) = seal([9u8; 32]);
+        assert_eq!(seal([9u8; 32]), (key, nonce.clone(), ciphertext.clone(), xchacha.clone()));
+        assert_ne!(seal([10u8; 32]).1, nonce);
+
+        let aes_key = aes_gcm::AesKey::from_bytes(&key).unwrap();
+        assert_eq!(aes_gcm::decrypt(&aes_key, &nonce, &ciphertext, b"aad").unwrap(), b"payload");
+        assert_eq!(chacha::decrypt_xchacha(&key, &xchacha, None).unwrap(), b"record");
+    }
+
+    #[test]
+    fn test_with_random_source_overrides_and_restores() {
+        let run = || {
+            with_random_source(DeterministicRandomSource::from_seed([1u8; 32]), || {
+                (random_bytes(32), random_range(1000), SecureRng::new().next_u64())
+            })
+        };
+
+        assert_eq!(run(), run());
+
+        // OS source is back once the closure returns
+        assert_ne!(random_bytes(32), random_bytes(32));
+    }
 }
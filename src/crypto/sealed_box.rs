@@ -0,0 +1,136 @@
+//! Sealed Box: Anonymous Encryption to a Hybrid Public Key
+//!
+//! Encrypts a blob to a recipient's hybrid (X25519 + Kyber1024) public key
+//! without any sender keys. Every seal runs a fresh [`hybrid_kex`]
+//! encapsulation, so the output carries only ephemeral public values and the
+//! sender cannot be identified from it.
+//!
+//! # Format
+//!
+//! ```text
+//! sealed = x25519_ephemeral (32) || kyber_ciphertext (1568) || nonce (12) || ciphertext || tag (16)
+//!
+//! key = HKDF-SHA3-256(hybrid_shared_secret, info: "B4AE-sealed-box-v1")
+//! aad = "B4AE-sealed-box-v1" || kex_ciphertext || recipient_public
+//! ```
+//!
+//! Binding the recipient public key into the AAD prevents a sealed box from
+//! being re-targeted to a different recipient. A sealed box is not
+//! authenticated to any sender; use signatures if origin matters.
+
+use crate::crypto::{CryptoError, CryptoResult};
+use crate::crypto::chacha20poly1305_wrapper::{open_single_use, seal_single_use, SINGLE_USE_OVERHEAD};
+use crate::crypto::hkdf::derive_key;
+use crate::crypto::hybrid_kex::{self, HybridKexCiphertext, HybridKexKeyPair, HybridKexPublicKey};
+use zeroize::Zeroizing;
+
+/// Domain separation label for key derivation and AAD
+const SEALED_BOX_LABEL: &[u8] = b"B4AE-sealed-box-v1";
+
+/// Size overhead added by [`seal`]
+pub const SEALED_BOX_OVERHEAD: usize = HybridKexCiphertext::serialized_size() + SINGLE_USE_OVERHEAD;
+
+/// Encrypt `plaintext` anonymously to `recipient_pub`
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - Sealed box (`plaintext.len() + SEALED_BOX_OVERHEAD` bytes)
+/// * `Err(CryptoError)` - If encapsulation or encryption fails
+pub fn seal(recipient_pub: &HybridKexPublicKey, plaintext: &[u8]) -> CryptoResult<Vec<u8>> {
+    let (shared_secret, kex_ciphertext) = hybrid_kex::encapsulate(recipient_pub)?;
+    let shared_secret = Zeroizing::new(shared_secret);
+    let kex_bytes = kex_ciphertext.to_bytes();
+
+    let key = derive_box_key(shared_secret.as_ref())?;
+    let aad = box_aad(&kex_bytes, recipient_pub);
+
+    let mut sealed = Vec::with_capacity(plaintext.len() + SEALED_BOX_OVERHEAD);
+    sealed.extend_from_slice(&kex_bytes);
+    sealed.extend_from_slice(&seal_single_use(&key, plaintext, Some(&aad))?);
+    Ok(sealed)
+}
+
+/// Decrypt a sealed box with the recipient's keypair
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - Plaintext
+/// * `Err(CryptoError)` - If the box is malformed or not sealed to this keypair
+pub fn open(recipient_kp: &HybridKexKeyPair, sealed: &[u8]) -> CryptoResult<Vec<u8>> {
+    if sealed.len() < SEALED_BOX_OVERHEAD {
+        return Err(CryptoError::InvalidInput(
+            format!("Sealed box too short: {} bytes, minimum {}", sealed.len(), SEALED_BOX_OVERHEAD)
+        ));
+    }
+
+    let (kex_bytes, rest) = sealed.split_at(HybridKexCiphertext::serialized_size());
+
+    let kex_ciphertext = HybridKexCiphertext::from_bytes(kex_bytes)?;
+    let shared_secret = Zeroizing::new(hybrid_kex::decapsulate(&recipient_kp.secret_key, &kex_ciphertext)?);
+
+    let key = derive_box_key(shared_secret.as_ref())?;
+    let aad = box_aad(kex_bytes, &recipient_kp.public_key);
+    open_single_use(&key, rest, Some(&aad))
+}
+
+fn derive_box_key(shared_secret: &[u8]) -> CryptoResult<Zeroizing<[u8; 32]>> {
+    let key_vec = Zeroizing::new(derive_key(&[shared_secret], SEALED_BOX_LABEL, 32)?);
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&key_vec);
+    Ok(key)
+}
+
+fn box_aad(kex_bytes: &[u8], recipient_pub: &HybridKexPublicKey) -> Vec<u8> {
+    let recipient_bytes = recipient_pub.to_bytes();
+    let mut aad = Vec::with_capacity(SEALED_BOX_LABEL.len() + kex_bytes.len() + recipient_bytes.len());
+    aad.extend_from_slice(SEALED_BOX_LABEL);
+    aad.extend_from_slice(kex_bytes);
+    aad.extend_from_slice(&recipient_bytes);
+    aad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let bob = hybrid_kex::generate_keypair().unwrap();
+        let plaintext = b"anonymous tip for bob";
+
+        let sealed = seal(&bob.public_key, plaintext).unwrap();
+        assert_eq!(sealed.len(), plaintext.len() + SEALED_BOX_OVERHEAD);
+        assert_eq!(open(&bob, &sealed).unwrap(), plaintext);
+
+        let empty = seal(&bob.public_key, b"").unwrap();
+        assert!(open(&bob, &empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_wrong_recipient_fails() {
+        let bob = hybrid_kex::generate_keypair().unwrap();
+        let eve = hybrid_kex::generate_keypair().unwrap();
+
+        let sealed = seal(&bob.public_key, b"for bob only").unwrap();
+        assert!(open(&eve, &sealed).is_err());
+    }
+
+    #[test]
+    fn test_seals_are_randomized() {
+        let bob = hybrid_kex::generate_keypair().unwrap();
+
+        let first = seal(&bob.public_key, b"same message").unwrap();
+        let second = seal(&bob.public_key, b"same message").unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_tampered_or_truncated_box_rejected() {
+        let bob = hybrid_kex::generate_keypair().unwrap();
+        let mut sealed = seal(&bob.public_key, b"integrity").unwrap();
+
+        assert!(open(&bob, &sealed[..SEALED_BOX_OVERHEAD - 1]).is_err());
+
+        let last = sealed.len() - 1;
+        sealed[last] ^= 0x01;
+        assert!(open(&bob, &sealed).is_err());
+    }
+}
@@ -0,0 +1,374 @@
+//! Power-on self-test of the cryptographic primitives
+//!
+//! [`self_test`] runs a known-answer test (KAT) per algorithm against the
+//! vectors embedded below, plus pairwise consistency checks on freshly
+//! generated keys, and reports pass/fail for each. Regulated deployments call
+//! [`self_test_with`] with `fips_mode = true` before first use: any failure
+//! is then an error and puts the module into an error state for the rest of
+//! the process, in which Kyber and Dilithium key generation, encapsulation,
+//! decapsulation, signing and verification fail with
+//! [`CryptoError::SelfTestFailed`] (see [`ensure_operational`]).
+//!
+//! | algorithm          | vector                                             |
+//! |--------------------|----------------------------------------------------|
+//! | AES-256-GCM        | GCM specification, test case 14                    |
+//! | ChaCha20-Poly1305  | RFC 8439 §2.8.2                                    |
+//! | HKDF-SHA3-256      | RFC 5869 test case 1 inputs, SHA3-256 output       |
+//! | SHA3-256           | FIPS 202 `"abc"`                                   |
+//! | Kyber1024          | NIST ACVP ML-KEM encapDecap, tgId 6 tcId 98 and 96 |
+//! | Dilithium5         | NIST ACVP ML-DSA sigVer, tgId 5 tcId 73            |
+//!
+//! The ACVP vectors (FIPS 203 decapsulation, valid and implicitly rejected
+//! ciphertext; FIPS 204 external/pure verification with a context string) go
+//! through the same public API as production code. They are for the default
+//! ML-KEM-1024 / ML-DSA-87 backends (`pqcrypto-mlkem`, `pqcrypto-mldsa`); the
+//! round-3 Kyber/Dilithium backends are not FIPS algorithms and fail them.
+
+use crate::crypto::{aes_gcm, chacha20poly1305_wrapper, dilithium, hkdf, kyber};
+use crate::crypto::{CryptoError, CryptoResult};
+use sha3::{Digest, Sha3_256};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Algorithm checked by [`self_test`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelfTestAlgorithm {
+    /// AES-256-GCM known-answer test
+    AesGcm,
+    /// ChaCha20-Poly1305 known-answer test
+    ChaCha20Poly1305,
+    /// HKDF-SHA3-256 known-answer test
+    Hkdf,
+    /// SHA3-256 known-answer test
+    Sha3,
+    /// Kyber1024 (ML-KEM-1024) known-answer test
+    Kyber,
+    /// Dilithium5 (ML-DSA-87) known-answer test
+    Dilithium,
+    /// Keygen/sign/verify and keygen/encaps/decaps on fresh keys
+    PairwiseConsistency,
+}
+
+impl fmt::Display for SelfTestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SelfTestAlgorithm::AesGcm => "AES-256-GCM",
+            SelfTestAlgorithm::ChaCha20Poly1305 => "ChaCha20-Poly1305",
+            SelfTestAlgorithm::Hkdf => "HKDF-SHA3-256",
+            SelfTestAlgorithm::Sha3 => "SHA3-256",
+            SelfTestAlgorithm::Kyber => "Kyber1024",
+            SelfTestAlgorithm::Dilithium => "Dilithium5",
+            SelfTestAlgorithm::PairwiseConsistency => "pairwise consistency",
+        })
+    }
+}
+
+/// Outcome of one check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestResult {
+    /// Algorithm checked
+    pub algorithm: SelfTestAlgorithm,
+    /// Whether the output matched the expected answer
+    pub passed: bool,
+    /// Why the check failed
+    pub error: Option<String>,
+}
+
+/// Per-algorithm results of [`self_test`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// One entry per [`SelfTestAlgorithm`], in run order
+    pub results: Vec<SelfTestResult>,
+}
+
+impl SelfTestReport {
+    /// True when every check passed
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+
+    /// Algorithms whose check failed
+    pub fn failures(&self) -> Vec<SelfTestAlgorithm> {
+        self.results.iter().filter(|r| !r.passed).map(|r| r.algorithm).collect()
+    }
+
+    /// Result for one algorithm
+    pub fn result(&self, algorithm: SelfTestAlgorithm) -> Option<&SelfTestResult> {
+        self.results.iter().find(|r| r.algorithm == algorithm)
+    }
+}
+
+/// Run every self-test and report the results
+///
+/// Failures are only reported; use [`self_test_with`] in FIPS mode to turn
+/// them into an error.
+pub fn self_test() -> CryptoResult<SelfTestReport> {
+    self_test_with(false)
+}
+
+/// Run every self-test; with `fips_mode`, fail with
+/// [`CryptoError::SelfTestFailed`] naming the failed algorithms
+pub fn self_test_with(fips_mode: bool) -> CryptoResult<SelfTestReport> {
+    let result = run(&VECTORS, fips_mode);
+    if fips_mode {
+        MODULE_STATE.record(result.is_ok());
+    }
+    result
+}
+
+/// Fail with [`CryptoError::SelfTestFailed`] once a FIPS-mode
+/// [`self_test_with`] has failed in this process
+///
+/// Called by the Kyber and Dilithium entry points; the error state lasts
+/// until the process restarts.
+pub fn ensure_operational() -> CryptoResult<()> {
+    MODULE_STATE.check()
+}
+
+static MODULE_STATE: ModuleState = ModuleState::new();
+
+/// Outcome of the FIPS-mode self-tests run so far; a failure is sticky
+struct ModuleState(AtomicU8);
+
+const STATE_UNTESTED: u8 = 0;
+const STATE_OPERATIONAL: u8 = 1;
+const STATE_ERROR: u8 = 2;
+
+impl ModuleState {
+    const fn new() -> Self {
+        ModuleState(AtomicU8::new(STATE_UNTESTED))
+    }
+
+    fn record(&self, passed: bool) {
+        self.0.fetch_max(if passed { STATE_OPERATIONAL } else { STATE_ERROR }, Ordering::SeqCst);
+    }
+
+    fn check(&self) -> CryptoResult<()> {
+        if self.0.load(Ordering::SeqCst) == STATE_ERROR {
+            return Err(CryptoError::SelfTestFailed("module is in the error state".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Expected answers; the tests swap in tampered copies
+struct KatVectors<'a> {
+    /// `ciphertext || tag`
+    aes_gcm: &'a str,
+    /// `ciphertext || tag`
+    chacha20poly1305: &'a str,
+    hkdf: &'a str,
+    sha3: &'a str,
+    /// `dk || (c || K)` for a valid and a modified ciphertext
+    kyber: &'a [u8],
+    /// `pk || signature || context length (1 byte) || context || message`
+    #[cfg_attr(not(all(feature = "pqcrypto-mldsa", not(feature = "liboqs"))), allow(dead_code))]
+    dilithium: &'a [u8],
+}
+
+const VECTORS: KatVectors<'static> = KatVectors {
+    aes_gcm: "cea7403d4d606b6e074ec5d3baf39d18d0d1c8a799996bf0265b98b5d48ab919",
+    chacha20poly1305: "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6\
+        3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36\
+        92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc\
+        3ff4def08e4b7a9de576d26586cec64b61161ae10b594f09e26a7e902ecbd0600691",
+    hkdf: "0c5160501d65021deaf2c14f5abce04c5bd2635abceeba61c2edb6e8ed72674900557728f2c9f2c4c179",
+    sha3: "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+    kyber: include_bytes!("self_test_ml_kem_1024.bin"),
+    dilithium: include_bytes!("self_test_ml_dsa_87.bin"),
+};
+
+const CHACHA_KAT_PLAINTEXT: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one \
+    tip for the future, sunscreen would be it.";
+const PAIRWISE_MESSAGE: &[u8] = b"B4AE pairwise consistency";
+
+type KatCheck = fn(&KatVectors<'_>) -> CryptoResult<()>;
+
+fn run(vectors: &KatVectors<'_>, fips_mode: bool) -> CryptoResult<SelfTestReport> {
+    let checks: [(SelfTestAlgorithm, KatCheck); 7] = [
+        (SelfTestAlgorithm::AesGcm, aes_gcm_kat),
+        (SelfTestAlgorithm::ChaCha20Poly1305, chacha20poly1305_kat),
+        (SelfTestAlgorithm::Hkdf, hkdf_kat),
+        (SelfTestAlgorithm::Sha3, sha3_kat),
+        (SelfTestAlgorithm::Kyber, kyber_kat),
+        (SelfTestAlgorithm::Dilithium, dilithium_kat),
+        (SelfTestAlgorithm::PairwiseConsistency, |_| pairwise_consistency()),
+    ];
+    let results = checks
+        .iter()
+        .map(|(algorithm, check)| {
+            let error = check(vectors).err().map(|e| e.to_string());
+            SelfTestResult { algorithm: *algorithm, passed: error.is_none(), error }
+        })
+        .collect();
+    let report = SelfTestReport { results };
+
+    if fips_mode && !report.passed() {
+        let failed: Vec<String> = report.failures().iter().map(ToString::to_string).collect();
+        return Err(CryptoError::SelfTestFailed(failed.join(", ")));
+    }
+    Ok(report)
+}
+
+fn expect(actual: &[u8], expected_hex: &str) -> CryptoResult<()> {
+    let expected = hex::decode(expected_hex)
+        .map_err(|e| CryptoError::InvalidInput(format!("bad KAT vector: {}", e)))?;
+    if actual == expected.as_slice() {
+        Ok(())
+    } else {
+        Err(CryptoError::InvalidInput("output does not match the known answer".to_string()))
+    }
+}
+
+fn aes_gcm_kat(vectors: &KatVectors<'_>) -> CryptoResult<()> {
+    let key = aes_gcm::AesKey::from_bytes(&[0u8; 32])?;
+    let nonce = [0u8; aes_gcm::NONCE_SIZE];
+    let (ciphertext, tag) = aes_gcm::encrypt_detached(&key, &nonce, &[], &[0u8; 16])?;
+    expect(&[ciphertext.as_slice(), &tag].concat(), vectors.aes_gcm)?;
+    let plaintext = aes_gcm::decrypt_detached(&key, &nonce, &[], &ciphertext, &tag)?;
+    expect(&plaintext, "00000000000000000000000000000000")
+}
+
+fn chacha20poly1305_kat(vectors: &KatVectors<'_>) -> CryptoResult<()> {
+    let key: [u8; 32] = core::array::from_fn(|i| 0x80 + i as u8);
+    let nonce = [0x07, 0x00, 0x00, 0x00, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47];
+    let aad = [0x50, 0x51, 0x52, 0x53, 0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7];
+    let (ciphertext, tag) =
+        chacha20poly1305_wrapper::encrypt_detached(&key, &nonce, &aad, CHACHA_KAT_PLAINTEXT)?;
+    expect(&[ciphertext.as_slice(), &tag].concat(), vectors.chacha20poly1305)?;
+    let plaintext = chacha20poly1305_wrapper::decrypt_detached(&key, &nonce, &aad, &ciphertext, &tag)?;
+    if plaintext != CHACHA_KAT_PLAINTEXT {
+        return Err(CryptoError::DecryptionFailed("round trip does not match the plaintext".to_string()));
+    }
+    Ok(())
+}
+
+fn hkdf_kat(vectors: &KatVectors<'_>) -> CryptoResult<()> {
+    let salt: [u8; 13] = core::array::from_fn(|i| i as u8);
+    let info: [u8; 10] = core::array::from_fn(|i| 0xf0 + i as u8);
+    let okm = hkdf::derive_key_with_salt(&salt, &[&[0x0b; 22]], &info, 42)?;
+    expect(&okm, vectors.hkdf)
+}
+
+fn sha3_kat(vectors: &KatVectors<'_>) -> CryptoResult<()> {
+    expect(&Sha3_256::digest(b"abc"), vectors.sha3)
+}
+
+fn kyber_kat(vectors: &KatVectors<'_>) -> CryptoResult<()> {
+    let variant = kyber::KyberVariant::Kyber1024;
+    let ciphertext_size = variant.ciphertext_size();
+    let (dk, cases) = vectors
+        .kyber
+        .split_at_checked(variant.secret_key_size())
+        .filter(|(_, cases)| cases.len() == 2 * (ciphertext_size + 32))
+        .ok_or_else(|| CryptoError::InvalidInput("bad KAT vector: wrong length".to_string()))?;
+    let secret_key = kyber::KyberSecretKey::from_bytes(dk)?;
+    for case in cases.chunks(ciphertext_size + 32) {
+        let (ciphertext, expected) = case.split_at(ciphertext_size);
+        let shared = kyber::decapsulate(&secret_key, &kyber::KyberCiphertext::from_bytes(ciphertext)?)?;
+        if shared.as_bytes() != expected {
+            return Err(CryptoError::DecryptionFailed("decapsulation does not match the known answer".to_string()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(feature = "pqcrypto-mldsa", not(feature = "liboqs")))]
+fn dilithium_kat(vectors: &KatVectors<'_>) -> CryptoResult<()> {
+    let variant = dilithium::DilithiumVariant::Dilithium5;
+    let truncated = || CryptoError::InvalidInput("bad KAT vector: truncated".to_string());
+    let (pk, rest) = vectors.dilithium.split_at_checked(variant.public_key_size()).ok_or_else(truncated)?;
+    let (sig, rest) = rest.split_at_checked(variant.signature_size()).ok_or_else(truncated)?;
+    let (context_len, rest) = rest.split_first().ok_or_else(truncated)?;
+    let (context, message) = rest.split_at_checked(usize::from(*context_len)).ok_or_else(truncated)?;
+    let public_key = dilithium::DilithiumPublicKey::from_bytes(pk)?;
+    let signature = dilithium::DilithiumSignature::from_bytes(sig)?;
+    if !dilithium::verify_with_context(&public_key, message, context, &signature)? {
+        return Err(CryptoError::VerificationFailed("known-answer signature rejected".to_string()));
+    }
+    if dilithium::verify_with_context(&public_key, PAIRWISE_MESSAGE, context, &signature)? {
+        return Err(CryptoError::VerificationFailed("signature accepted for the wrong message".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(not(all(feature = "pqcrypto-mldsa", not(feature = "liboqs"))))]
+fn dilithium_kat(_vectors: &KatVectors<'_>) -> CryptoResult<()> {
+    Err(CryptoError::InvalidInput("no known-answer vector for this signature backend".to_string()))
+}
+
+fn pairwise_consistency() -> CryptoResult<()> {
+    let signer = dilithium::keypair()?;
+    let signature = dilithium::sign(&signer.secret_key, PAIRWISE_MESSAGE)?;
+    if !dilithium::verify(&signer.public_key, PAIRWISE_MESSAGE, &signature)? {
+        return Err(CryptoError::VerificationFailed("fresh Dilithium5 signature rejected".to_string()));
+    }
+
+    let kem = kyber::keypair()?;
+    let (shared, ciphertext) = kyber::encapsulate(&kem.public_key)?;
+    if kyber::decapsulate(&kem.secret_key, &ciphertext)?.as_bytes() != shared.as_bytes() {
+        return Err(CryptoError::DecryptionFailed("fresh Kyber1024 shared secrets differ".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_passes() {
+        let report = self_test_with(true).unwrap();
+        assert!(report.passed(), "{:?}", report);
+        assert_eq!(report.results.len(), 7);
+        assert!(report.result(SelfTestAlgorithm::PairwiseConsistency).unwrap().passed);
+    }
+
+    #[test]
+    fn test_tampered_vector_names_failing_algorithm() {
+        let tampered = KatVectors {
+            chacha20poly1305: "e31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6\
+                3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36\
+                92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc\
+                3ff4def08e4b7a9de576d26586cec64b61161ae10b594f09e26a7e902ecbd0600691",
+            ..VECTORS
+        };
+        let report = run(&tampered, false).unwrap();
+        assert_eq!(report.failures(), vec![SelfTestAlgorithm::ChaCha20Poly1305]);
+        assert!(report.result(SelfTestAlgorithm::ChaCha20Poly1305).unwrap().error.is_some());
+
+        // FIPS mode refuses to proceed
+        match run(&tampered, true) {
+            Err(CryptoError::SelfTestFailed(msg)) => assert_eq!(msg, "ChaCha20-Poly1305"),
+            other => panic!("expected SelfTestFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tampered_pq_vectors_fail() {
+        let mut signature = VECTORS.dilithium.to_vec();
+        let index = dilithium::DilithiumVariant::Dilithium5.public_key_size() + 100;
+        signature[index] ^= 0x01;
+        // The implicit-rejection answer, i.e. the second case
+        let mut kem = VECTORS.kyber.to_vec();
+        let last = kem.len() - 1;
+        kem[last] ^= 0x01;
+        let tampered = KatVectors { kyber: &kem, dilithium: &signature, ..VECTORS };
+        let report = run(&tampered, false).unwrap();
+        assert_eq!(report.failures(), vec![SelfTestAlgorithm::Kyber, SelfTestAlgorithm::Dilithium]);
+    }
+
+    #[test]
+    fn test_failed_fips_self_test_is_sticky() {
+        let state = ModuleState::new();
+        assert!(state.check().is_ok());
+        state.record(true);
+        assert!(state.check().is_ok());
+
+        state.record(false);
+        assert!(matches!(state.check(), Err(CryptoError::SelfTestFailed(_))));
+        // A later passing run does not clear the error state
+        state.record(true);
+        assert!(state.check().is_err());
+    }
+}
@@ -0,0 +1,600 @@
+//! Sender Keys: Group Messaging with One Encryption per Message
+//!
+//! Each group member owns a symmetric sending chain. The chain key is handed
+//! to every other member once, in a pairwise [`sealed_box`] signed with the
+//! sender's identity key, and every message
+//! is then encrypted a single time for the whole group. The chain ratchets
+//! forward per message, so a compromised chain key does not reveal earlier
+//! messages.
+//!
+//! # Key schedule
+//!
+//! ```text
+//! message_key   = HKDF-SHA3-256(chain_key, info: "B4AE-sender-keys-v1-message")
+//! next_chain    = HKDF-SHA3-256(chain_key, info: "B4AE-sender-keys-v1-chain")
+//! aad           = "B4AE-sender-keys-v1" || sender_id || epoch || iteration
+//! dist_sig      = HybridSign(identity_sk, "B4AE-sender-keys-v1-distribution"
+//!                            || recipient_pub || distribution)
+//! ```
+//!
+//! A sealed box alone is anonymous: anyone holding a member's public key,
+//! including a removed member, could seal a distribution that claims another
+//! member's ID. [`SenderKeyState::process_distribution`] therefore verifies
+//! the signature against the identity key the caller has on record for the
+//! claimed `sender_id` before installing the chain.
+//!
+//! # Membership changes
+//!
+//! A new member receives the current chain state of every sender, which is
+//! already ratcheted past earlier messages. When a member leaves, every
+//! remaining member calls [`SenderKeyState::remove_member`], which starts a
+//! fresh chain under a new epoch, and redistributes it to the remaining
+//! members; the departed member's copy of the old chain is useless for new
+//! messages.
+//!
+//! Messages are authenticated to the group, not to an individual sender:
+//! anyone holding a chain key could forge messages on that chain. Sign
+//! messages if per-sender origin matters.
+
+use crate::crypto::chacha20poly1305_wrapper::{open_single_use, seal_single_use, SINGLE_USE_OVERHEAD};
+use crate::crypto::double_ratchet::MAX_SKIP;
+use crate::crypto::hkdf::derive_key;
+use crate::crypto::hybrid::{self, HybridKeyPair, HybridPublicKey, HybridSignature};
+use crate::crypto::hybrid_kex::{HybridKexKeyPair, HybridKexPublicKey};
+use crate::crypto::random::fill_random;
+use crate::crypto::sealed_box;
+use crate::crypto::{CryptoError, CryptoResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Domain separation label for AAD
+const SENDER_KEYS_LABEL: &[u8] = b"B4AE-sender-keys-v1";
+
+/// HKDF info for per-message keys
+const MESSAGE_KEY_INFO: &[u8] = b"B4AE-sender-keys-v1-message";
+
+/// HKDF info for the next chain key
+const CHAIN_KEY_INFO: &[u8] = b"B4AE-sender-keys-v1-chain";
+
+/// Domain separation label for distribution signatures
+const DISTRIBUTION_SIG_LABEL: &[u8] = b"B4AE-sender-keys-v1-distribution";
+
+/// Maximum member ID length
+pub const MAX_MEMBER_ID_LEN: usize = 256;
+
+/// Upper bound on a serialized message or distribution
+const MAX_SERIALIZED_SIZE: usize = crate::MAX_MESSAGE_SIZE + 1024;
+
+/// A sender's chain state, as distributed to other members
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SenderKeyDistribution {
+    /// Member that owns the chain
+    pub sender_id: Vec<u8>,
+    /// Chain generation; bumped on every rekey
+    pub epoch: u64,
+    /// Index of the next message on the chain
+    pub iteration: u64,
+    /// Chain key at `iteration`
+    pub chain_key: [u8; 32],
+}
+
+impl Drop for SenderKeyDistribution {
+    fn drop(&mut self) {
+        self.chain_key.zeroize();
+    }
+}
+
+/// Serialized distribution plus the sender's signature, as sealed on the wire
+#[derive(Serialize, Deserialize)]
+struct SignedDistribution {
+    distribution: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl Drop for SignedDistribution {
+    fn drop(&mut self) {
+        self.distribution.zeroize();
+    }
+}
+
+/// A group message encrypted on the sender's chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SenderKeyMessage {
+    /// Member that sent the message
+    pub sender_id: Vec<u8>,
+    /// Sender chain epoch
+    pub epoch: u64,
+    /// Position on the sender chain
+    pub iteration: u64,
+    /// `nonce || ciphertext || tag`
+    pub ciphertext: Vec<u8>,
+}
+
+impl SenderKeyMessage {
+    /// Serialize message to bytes
+    pub fn to_bytes(&self) -> CryptoResult<Vec<u8>> {
+        bincode::serialize(self).map_err(|e| CryptoError::InvalidInput(e.to_string()))
+    }
+
+    /// Deserialize message from bytes
+    pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        bounded_deserialize(bytes)
+    }
+}
+
+/// Symmetric chain ratcheted once per message
+#[derive(Clone)]
+struct Chain {
+    epoch: u64,
+    iteration: u64,
+    chain_key: Zeroizing<[u8; 32]>,
+}
+
+impl Chain {
+    fn random(epoch: u64) -> CryptoResult<Self> {
+        let mut chain_key = Zeroizing::new([0u8; 32]);
+        fill_random(chain_key.as_mut())?;
+        Ok(Chain { epoch, iteration: 0, chain_key })
+    }
+
+    /// Return the key for the current iteration and step the chain forward
+    fn next_message_key(&mut self) -> CryptoResult<(u64, Zeroizing<[u8; 32]>)> {
+        let message_key = derive_32(&self.chain_key, MESSAGE_KEY_INFO)?;
+        self.chain_key = derive_32(&self.chain_key, CHAIN_KEY_INFO)?;
+        let iteration = self.iteration;
+        self.iteration += 1;
+        Ok((iteration, message_key))
+    }
+}
+
+/// Another member's chain plus keys for messages that arrived out of order
+struct ReceivingChain {
+    chain: Chain,
+    skipped: HashMap<u64, Zeroizing<[u8; 32]>>,
+}
+
+/// One member's view of a sender-keys group
+///
+/// Holds this member's own sending chain and a receiving chain for every
+/// other member whose distribution has been processed.
+pub struct SenderKeyState {
+    member_id: Vec<u8>,
+    own: Chain,
+    peers: HashMap<Vec<u8>, ReceivingChain>,
+}
+
+impl SenderKeyState {
+    /// Create state with a fresh sending chain for `member_id`
+    pub fn new(member_id: impl Into<Vec<u8>>) -> CryptoResult<Self> {
+        let member_id = member_id.into();
+        if member_id.is_empty() || member_id.len() > MAX_MEMBER_ID_LEN {
+            return Err(CryptoError::InvalidInput("Invalid member ID length".to_string()));
+        }
+        Ok(SenderKeyState {
+            member_id,
+            own: Chain::random(0)?,
+            peers: HashMap::new(),
+        })
+    }
+
+    /// This member's ID
+    pub fn member_id(&self) -> &[u8] {
+        &self.member_id
+    }
+
+    /// Current epoch of the sending chain
+    pub fn epoch(&self) -> u64 {
+        self.own.epoch
+    }
+
+    /// Members whose chains this state can decrypt
+    pub fn known_senders(&self) -> impl Iterator<Item = &[u8]> {
+        self.peers.keys().map(Vec::as_slice)
+    }
+
+    /// Current sending chain state, to be sealed to other members
+    pub fn distribution(&self) -> SenderKeyDistribution {
+        SenderKeyDistribution {
+            sender_id: self.member_id.clone(),
+            epoch: self.own.epoch,
+            iteration: self.own.iteration,
+            chain_key: *self.own.chain_key,
+        }
+    }
+
+    /// Sign the current sending chain state with `identity` and seal it to
+    /// one member's public key
+    pub fn seal_distribution(
+        &self,
+        recipient_pub: &HybridKexPublicKey,
+        identity: &HybridKeyPair,
+    ) -> CryptoResult<Vec<u8>> {
+        let distribution = Zeroizing::new(
+            bincode::serialize(&self.distribution()).map_err(|e| CryptoError::InvalidInput(e.to_string()))?,
+        );
+        let signed_message = Zeroizing::new(distribution_signed_message(recipient_pub, &distribution));
+        let signature = hybrid::sign(&identity.secret_key, &signed_message)?;
+        let signed = SignedDistribution {
+            distribution: distribution.to_vec(),
+            signature: signature.to_bytes(),
+        };
+        let bytes = Zeroizing::new(
+            bincode::serialize(&signed).map_err(|e| CryptoError::InvalidInput(e.to_string()))?,
+        );
+        sealed_box::seal(recipient_pub, &bytes)
+    }
+
+    /// Open a sealed distribution, verify its signature and install the
+    /// sender's chain
+    ///
+    /// `identity_for` maps the claimed `sender_id` to that member's identity
+    /// public key; an unknown sender or a bad signature is rejected with
+    /// `CryptoError::VerificationFailed` and nothing is installed.
+    ///
+    /// A distribution older than the chain already installed (earlier epoch,
+    /// or earlier iteration in the same epoch) is rejected, so a replayed
+    /// distribution cannot roll a chain back.
+    pub fn process_distribution<F>(
+        &mut self,
+        sealed: &[u8],
+        my_keypair: &HybridKexKeyPair,
+        identity_for: F,
+    ) -> CryptoResult<()>
+    where
+        F: FnOnce(&[u8]) -> Option<HybridPublicKey>,
+    {
+        let bytes = Zeroizing::new(sealed_box::open(my_keypair, sealed)?);
+        let signed: SignedDistribution = bounded_deserialize(&bytes)?;
+        let distribution: SenderKeyDistribution = bounded_deserialize(&signed.distribution)?;
+
+        let identity = identity_for(&distribution.sender_id).ok_or_else(|| {
+            CryptoError::VerificationFailed("Unknown sender for distribution".to_string())
+        })?;
+        let signature = HybridSignature::from_bytes(&signed.signature)?;
+        let signed_message = Zeroizing::new(distribution_signed_message(&my_keypair.public_key, &signed.distribution));
+        if !hybrid::verify(&identity, &signed_message, &signature)? {
+            return Err(CryptoError::VerificationFailed("Invalid distribution signature".to_string()));
+        }
+        self.install(&distribution)
+    }
+
+    /// Install a sender's chain from an already-authenticated distribution
+    ///
+    /// Only for distributions delivered over a channel that already
+    /// authenticates the sender, such as an established pairwise session;
+    /// sealed distributions go through [`Self::process_distribution`].
+    ///
+    /// A distribution at the chain's current (epoch, iteration) is a
+    /// redelivery and changes nothing, so replaying it cannot wipe the keys
+    /// kept for out-of-order messages.
+    pub fn install(&mut self, distribution: &SenderKeyDistribution) -> CryptoResult<()> {
+        if distribution.sender_id == self.member_id {
+            return Err(CryptoError::InvalidInput("Distribution for own chain".to_string()));
+        }
+        if distribution.sender_id.is_empty() || distribution.sender_id.len() > MAX_MEMBER_ID_LEN {
+            return Err(CryptoError::InvalidInput("Invalid member ID length".to_string()));
+        }
+        if let Some(existing) = self.peers.get(&distribution.sender_id) {
+            let current = (existing.chain.epoch, existing.chain.iteration);
+            match (distribution.epoch, distribution.iteration).cmp(&current) {
+                std::cmp::Ordering::Less => {
+                    return Err(CryptoError::InvalidInput("Stale sender key distribution".to_string()));
+                }
+                std::cmp::Ordering::Equal => return Ok(()),
+                std::cmp::Ordering::Greater => {}
+            }
+        }
+        self.peers.insert(distribution.sender_id.clone(), ReceivingChain {
+            chain: Chain {
+                epoch: distribution.epoch,
+                iteration: distribution.iteration,
+                chain_key: Zeroizing::new(distribution.chain_key),
+            },
+            skipped: HashMap::new(),
+        });
+        Ok(())
+    }
+
+    /// Encrypt `plaintext` once for the whole group
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> CryptoResult<SenderKeyMessage> {
+        let epoch = self.own.epoch;
+        let (iteration, message_key) = self.own.next_message_key()?;
+        let aad = message_aad(&self.member_id, epoch, iteration);
+        let ciphertext = seal_single_use(&message_key, plaintext, Some(&aad))?;
+        Ok(SenderKeyMessage {
+            sender_id: self.member_id.clone(),
+            epoch,
+            iteration,
+            ciphertext,
+        })
+    }
+
+    /// Decrypt a message from another member
+    ///
+    /// Messages may arrive out of order; up to [`MAX_SKIP`] message keys are
+    /// kept for a chain. Each message decrypts at most once, and a message
+    /// that fails authentication leaves the chain unchanged.
+    pub fn decrypt(&mut self, message: &SenderKeyMessage) -> CryptoResult<Vec<u8>> {
+        if message.ciphertext.len() < SINGLE_USE_OVERHEAD {
+            return Err(CryptoError::InvalidInput("Sender key message too short".to_string()));
+        }
+        let peer = self.peers.get_mut(&message.sender_id).ok_or_else(|| {
+            CryptoError::InvalidInput("No sender key for member".to_string())
+        })?;
+        if message.epoch != peer.chain.epoch {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+
+        let aad = message_aad(&message.sender_id, message.epoch, message.iteration);
+
+        if message.iteration < peer.chain.iteration {
+            let message_key = peer.skipped.get(&message.iteration).ok_or(CryptoError::AuthenticationFailed)?;
+            let plaintext = open_single_use(message_key, &message.ciphertext, Some(&aad))?;
+            peer.skipped.remove(&message.iteration);
+            return Ok(plaintext);
+        }
+
+        let skip = message.iteration - peer.chain.iteration;
+        if skip > MAX_SKIP || peer.skipped.len() as u64 + skip > MAX_SKIP {
+            return Err(CryptoError::InvalidInput(format!(
+                "Too many skipped sender key messages: {}",
+                skip
+            )));
+        }
+
+        // Advance a copy so a forged message cannot move the chain
+        let mut chain = peer.chain.clone();
+        let mut skipped = Vec::new();
+        let message_key = loop {
+            let (iteration, key) = chain.next_message_key()?;
+            if iteration == message.iteration {
+                break key;
+            }
+            skipped.push((iteration, key));
+        };
+        let plaintext = open_single_use(&message_key, &message.ciphertext, Some(&aad))?;
+
+        peer.chain = chain;
+        peer.skipped.extend(skipped);
+        Ok(plaintext)
+    }
+
+    /// Replace the sending chain with a fresh one under the next epoch
+    ///
+    /// The new chain must be redistributed to every current member.
+    pub fn rekey(&mut self) -> CryptoResult<()> {
+        self.own = Chain::random(self.own.epoch + 1)?;
+        Ok(())
+    }
+
+    /// Forget a departed member's chain and rekey the sending chain
+    pub fn remove_member(&mut self, member_id: &[u8]) -> CryptoResult<()> {
+        self.peers.remove(member_id);
+        self.rekey()
+    }
+}
+
+fn derive_32(chain_key: &[u8; 32], info: &[u8]) -> CryptoResult<Zeroizing<[u8; 32]>> {
+    let derived = Zeroizing::new(derive_key(&[chain_key], info, 32)?);
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&derived);
+    Ok(key)
+}
+
+fn message_aad(sender_id: &[u8], epoch: u64, iteration: u64) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(SENDER_KEYS_LABEL.len() + sender_id.len() + 16);
+    aad.extend_from_slice(SENDER_KEYS_LABEL);
+    aad.extend_from_slice(sender_id);
+    aad.extend_from_slice(&epoch.to_be_bytes());
+    aad.extend_from_slice(&iteration.to_be_bytes());
+    aad
+}
+
+fn distribution_signed_message(recipient_pub: &HybridKexPublicKey, distribution: &[u8]) -> Vec<u8> {
+    let recipient = recipient_pub.to_bytes();
+    let mut message = Vec::with_capacity(DISTRIBUTION_SIG_LABEL.len() + recipient.len() + distribution.len());
+    message.extend_from_slice(DISTRIBUTION_SIG_LABEL);
+    message.extend_from_slice(&recipient);
+    message.extend_from_slice(distribution);
+    message
+}
+
+fn bounded_deserialize<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> CryptoResult<T> {
+    if bytes.len() > MAX_SERIALIZED_SIZE {
+        return Err(CryptoError::InvalidInput(format!(
+            "Input too large for deserialize: {} > {}",
+            bytes.len(),
+            MAX_SERIALIZED_SIZE
+        )));
+    }
+    bincode::deserialize(bytes).map_err(|e| CryptoError::InvalidInput(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hybrid_kex;
+
+    struct Member {
+        state: SenderKeyState,
+        keypair: HybridKexKeyPair,
+        identity: HybridKeyPair,
+    }
+
+    fn member(name: &str) -> Member {
+        Member {
+            state: SenderKeyState::new(name.as_bytes()).unwrap(),
+            keypair: hybrid_kex::generate_keypair().unwrap(),
+            identity: hybrid::generate_keypair().unwrap(),
+        }
+    }
+
+    /// Identity directory as each member would keep it
+    fn directory(group: &[Member]) -> HashMap<Vec<u8>, HybridPublicKey> {
+        group.iter().map(|m| (m.state.member_id().to_vec(), m.identity.public_key.clone())).collect()
+    }
+
+    /// Every member seals its current chain to every other member
+    fn distribute_all(group: &mut [Member]) {
+        let directory = directory(group);
+        for i in 0..group.len() {
+            for j in 0..group.len() {
+                if i != j {
+                    let sealed = group[i]
+                        .state
+                        .seal_distribution(&group[j].keypair.public_key, &group[i].identity)
+                        .unwrap();
+                    let Member { state, keypair, .. } = &mut group[j];
+                    state.process_distribution(&sealed, keypair, |id| directory.get(id).cloned()).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_three_member_group() {
+        let mut group = vec![member("alice"), member("bob"), member("carol")];
+        distribute_all(&mut group);
+
+        for round in 0..3 {
+            for sender in 0..group.len() {
+                let text = format!("round {} from {}", round, sender);
+                let message = group[sender].state.encrypt(text.as_bytes()).unwrap();
+                let wire = SenderKeyMessage::from_bytes(&message.to_bytes().unwrap()).unwrap();
+                for receiver in (0..group.len()).filter(|&r| r != sender) {
+                    assert_eq!(group[receiver].state.decrypt(&wire).unwrap(), text.as_bytes());
+                }
+            }
+        }
+
+        // Out of order within a chain, and each message only once
+        let first = group[0].state.encrypt(b"first").unwrap();
+        let second = group[0].state.encrypt(b"second").unwrap();
+        assert_eq!(group[1].state.decrypt(&second).unwrap(), b"second");
+        assert_eq!(group[1].state.decrypt(&first).unwrap(), b"first");
+        assert!(group[1].state.decrypt(&first).is_err());
+
+        // Tampering fails authentication
+        let mut tampered = group[2].state.encrypt(b"hi").unwrap();
+        tampered.ciphertext[14] ^= 0x01;
+        assert!(matches!(group[0].state.decrypt(&tampered), Err(CryptoError::AuthenticationFailed)));
+    }
+
+    #[test]
+    fn test_replayed_distribution_keeps_skipped_keys() {
+        let mut group = vec![member("alice"), member("bob")];
+        distribute_all(&mut group);
+        let directory = directory(&group);
+
+        let first = group[0].state.encrypt(b"first").unwrap();
+        let second = group[0].state.encrypt(b"second").unwrap();
+        let third = group[0].state.encrypt(b"third").unwrap();
+        let sealed = group[0]
+            .state
+            .seal_distribution(&group[1].keypair.public_key, &group[0].identity)
+            .unwrap();
+
+        // Bob skips ahead, then receives the distribution at the same point twice
+        assert_eq!(group[1].state.decrypt(&third).unwrap(), b"third");
+        for _ in 0..2 {
+            let Member { state, keypair, .. } = &mut group[1];
+            state.process_distribution(&sealed, keypair, |id| directory.get(id).cloned()).unwrap();
+        }
+
+        assert_eq!(group[1].state.decrypt(&first).unwrap(), b"first");
+        assert_eq!(group[1].state.decrypt(&second).unwrap(), b"second");
+    }
+
+    #[test]
+    fn test_member_join_gets_current_chain() {
+        let mut group = vec![member("alice"), member("bob")];
+        distribute_all(&mut group);
+        let before_join = group[0].state.encrypt(b"before dave").unwrap();
+        assert_eq!(group[1].state.decrypt(&before_join).unwrap(), b"before dave");
+
+        group.push(member("dave"));
+        distribute_all(&mut group);
+        assert_eq!(group[2].state.known_senders().count(), 2);
+
+        // Dave reads new traffic and has his own chain, but not earlier messages
+        let after_join = group[0].state.encrypt(b"welcome dave").unwrap();
+        assert_eq!(group[2].state.decrypt(&after_join).unwrap(), b"welcome dave");
+        assert!(group[2].state.decrypt(&before_join).is_err());
+
+        let from_dave = group[2].state.encrypt(b"hi all").unwrap();
+        assert_eq!(group[0].state.decrypt(&from_dave).unwrap(), b"hi all");
+        assert_eq!(group[1].state.decrypt(&from_dave).unwrap(), b"hi all");
+    }
+
+    #[test]
+    fn test_member_leave_rekeys_group() {
+        let mut group = vec![member("alice"), member("bob"), member("carol")];
+        distribute_all(&mut group);
+        let message = group[0].state.encrypt(b"carol can read").unwrap();
+        assert_eq!(group[2].state.decrypt(&message).unwrap(), b"carol can read");
+
+        let carol = group.pop().unwrap();
+        let mut carol_state = carol.state;
+        for remaining in group.iter_mut() {
+            remaining.state.remove_member(b"carol").unwrap();
+            assert_eq!(remaining.state.epoch(), 1);
+        }
+        distribute_all(&mut group);
+
+        let message = group[0].state.encrypt(b"carol is gone").unwrap();
+        assert_eq!(group[1].state.decrypt(&message).unwrap(), b"carol is gone");
+        assert!(carol_state.decrypt(&message).is_err());
+
+        // Messages from the departed member are no longer accepted
+        let from_carol = carol_state.encrypt(b"still here?").unwrap();
+        assert!(group[0].state.decrypt(&from_carol).is_err());
+
+        // A replayed pre-rekey distribution cannot roll the chain back
+        let mut stale = group[0].state.distribution();
+        stale.epoch = 0;
+        assert!(group[1].state.install(&stale).is_err());
+    }
+
+    #[test]
+    fn test_forged_distribution_rejected() {
+        let mut group = vec![member("alice"), member("bob"), member("carol")];
+        distribute_all(&mut group);
+        let directory = directory(&group);
+
+        // Carol (or a removed member) seals a chain she controls under Alice's ID
+        let mut forged_state = SenderKeyState::new(b"alice".to_vec()).unwrap();
+        forged_state.rekey().unwrap();
+        let forged = forged_state
+            .seal_distribution(&group[1].keypair.public_key, &group[2].identity)
+            .unwrap();
+        let Member { state, keypair, .. } = &mut group[1];
+        let alice_epoch_before = state.peers[b"alice".as_slice()].chain.epoch;
+        assert!(matches!(
+            state.process_distribution(&forged, keypair, |id| directory.get(id).cloned()),
+            Err(CryptoError::VerificationFailed(_))
+        ));
+        assert_eq!(state.peers[b"alice".as_slice()].chain.epoch, alice_epoch_before);
+
+        // A forged message on the attacker's chain does not decrypt as Alice
+        let forged_message = forged_state.encrypt(b"from alice, honest").unwrap();
+        assert!(group[1].state.decrypt(&forged_message).is_err());
+
+        // Unknown senders are rejected too
+        let outsider = member("mallory");
+        let sealed = outsider
+            .state
+            .seal_distribution(&group[1].keypair.public_key, &outsider.identity)
+            .unwrap();
+        let Member { state, keypair, .. } = &mut group[1];
+        assert!(state.process_distribution(&sealed, keypair, |id| directory.get(id).cloned()).is_err());
+
+        // A genuine distribution sealed to Bob does not verify for Carol
+        let to_bob = group[0]
+            .state
+            .seal_distribution(&group[1].keypair.public_key, &group[0].identity)
+            .unwrap();
+        let Member { state, keypair, .. } = &mut group[2];
+        assert!(state.process_distribution(&to_bob, keypair, |id| directory.get(id).cloned()).is_err());
+    }
+}
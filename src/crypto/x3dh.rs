@@ -0,0 +1,493 @@
+//! X3DH-style Asynchronous Prekey Bundles (X25519 + Kyber1024)
+//!
+//! Lets an initiator establish a Double Ratchet session with a recipient who
+//! is offline. The recipient publishes a [`PrekeyBundle`] in advance; the
+//! initiator verifies it, performs the hybrid key agreement and sends an
+//! [`InitialMessage`] that the recipient processes when it comes online.
+//!
+//! # Key Agreement
+//!
+//! ```text
+//! DH1 = X25519(IK_A, SPK_B)
+//! DH2 = X25519(EK_A, IK_B)
+//! DH3 = X25519(EK_A, SPK_B)
+//! DH4 = X25519(EK_A, OPK_B)          (only if a one-time prekey was used)
+//! SS  = Kyber1024.Encaps(SPK_B.kyber)
+//!
+//! SK  = HKDF-SHA3-256(0xFF^32 || DH1 || DH2 || DH3 [|| DH4] || SS,
+//!                     info: "B4AE-X3DH-hybrid")
+//! ```
+//!
+//! The signed prekey (X25519 and Kyber1024 halves) is signed with the
+//! recipient's Dilithium5 identity key. `SK` is then expanded into the master
+//! secret and session ID consumed by
+//! [`DoubleRatchetSession`](crate::crypto::double_ratchet::DoubleRatchetSession).
+//!
+//! # One-Time Prekeys
+//!
+//! Each one-time prekey is consumed by the first initial message that uses
+//! it; its secret is deleted so replaying the initial message fails. When no
+//! one-time prekeys are left, bundles are issued without one (DH4 omitted).
+//!
+//! Trust in the identity key itself (safety numbers, key transparency) is
+//! outside the scope of this module.
+
+use crate::crypto::{CryptoError, CryptoResult};
+use crate::crypto::dilithium::{self, DilithiumKeyPair, DilithiumPublicKey, DilithiumSignature};
+use crate::crypto::double_ratchet::{DoubleRatchetConfig, DoubleRatchetSession};
+use crate::crypto::hkdf::derive_key;
+use crate::crypto::kyber::{self, KyberCiphertext, KyberPublicKey, KyberSecretKey};
+use std::collections::BTreeMap;
+use x25519_dalek::{PublicKey as X25519PublicKey, StaticSecret as X25519StaticSecret};
+use zeroize::Zeroizing;
+
+/// Domain separation prefix for signed prekey signatures
+const SIGNED_PREKEY_CONTEXT: &[u8] = b"B4AE-X3DH-signed-prekey-v1";
+
+/// HKDF info string for the X3DH shared key
+const X3DH_INFO: &[u8] = b"B4AE-X3DH-hybrid";
+
+/// Public identity key: X25519 for key agreement, Dilithium5 for signing
+#[derive(Clone)]
+pub struct IdentityPublicKey {
+    /// X25519 identity public key
+    pub x25519_public: [u8; 32],
+    /// Dilithium5 public key used to sign prekeys
+    pub signing_key: DilithiumPublicKey,
+}
+
+impl IdentityPublicKey {
+    /// Serialize as `x25519_public || signing_key`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(32 + DilithiumPublicKey::SIZE);
+        bytes.extend_from_slice(&self.x25519_public);
+        bytes.extend_from_slice(self.signing_key.as_bytes());
+        bytes
+    }
+}
+
+/// Long-term identity keypair
+pub struct IdentityKeyPair {
+    x25519_secret: X25519StaticSecret,
+    signing: DilithiumKeyPair,
+    public: IdentityPublicKey,
+}
+
+impl IdentityKeyPair {
+    /// Generate a fresh identity keypair
+    pub fn generate() -> CryptoResult<Self> {
+        let x25519_secret = X25519StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let signing = dilithium::keypair()?;
+        let public = IdentityPublicKey {
+            x25519_public: *X25519PublicKey::from(&x25519_secret).as_bytes(),
+            signing_key: signing.public_key.clone(),
+        };
+
+        Ok(IdentityKeyPair {
+            x25519_secret,
+            signing,
+            public,
+        })
+    }
+
+    /// Public half of the identity
+    pub fn public_key(&self) -> &IdentityPublicKey {
+        &self.public
+    }
+}
+
+/// Medium-term signed prekey (public part)
+#[derive(Clone)]
+pub struct SignedPrekey {
+    /// Prekey identifier
+    pub id: u32,
+    /// X25519 prekey
+    pub x25519_public: [u8; 32],
+    /// Kyber1024 prekey
+    pub kyber_public: KyberPublicKey,
+}
+
+impl SignedPrekey {
+    /// Bytes covered by the signature, bound to the owner's X25519 identity key
+    fn signed_bytes(&self, identity: &IdentityPublicKey) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SIGNED_PREKEY_CONTEXT.len() + 68 + KyberPublicKey::SIZE);
+        bytes.extend_from_slice(SIGNED_PREKEY_CONTEXT);
+        bytes.extend_from_slice(&identity.x25519_public);
+        bytes.extend_from_slice(&self.id.to_be_bytes());
+        bytes.extend_from_slice(&self.x25519_public);
+        bytes.extend_from_slice(self.kyber_public.as_bytes());
+        bytes
+    }
+}
+
+/// Single-use prekey (public part)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OneTimePrekey {
+    /// Prekey identifier
+    pub id: u32,
+    /// X25519 public key
+    pub x25519_public: [u8; 32],
+}
+
+/// Prekey bundle published by a recipient for offline session setup
+#[derive(Clone)]
+pub struct PrekeyBundle {
+    /// Recipient identity key
+    pub identity_key: IdentityPublicKey,
+    /// Signed prekey
+    pub signed_prekey: SignedPrekey,
+    /// Dilithium5 signature over the signed prekey
+    pub signed_prekey_sig: DilithiumSignature,
+    /// Optional one-time prekey
+    pub one_time_prekey: Option<OneTimePrekey>,
+}
+
+impl PrekeyBundle {
+    /// Verify the signed prekey signature against the bundle's identity key
+    ///
+    /// # Returns
+    /// * `Ok(())` - Signature is valid
+    /// * `Err(CryptoError::VerificationFailed)` - Signature does not match
+    pub fn verify(&self) -> CryptoResult<()> {
+        let message = self.signed_prekey.signed_bytes(&self.identity_key);
+        if dilithium::verify(&self.identity_key.signing_key, &message, &self.signed_prekey_sig)? {
+            Ok(())
+        } else {
+            Err(CryptoError::VerificationFailed(
+                "Signed prekey signature is invalid".to_string()
+            ))
+        }
+    }
+}
+
+/// First message from initiator to recipient
+#[derive(Clone)]
+pub struct InitialMessage {
+    /// Initiator identity key
+    pub identity_key: IdentityPublicKey,
+    /// Initiator ephemeral X25519 key
+    pub ephemeral_key: [u8; 32],
+    /// Kyber1024 ciphertext for the signed prekey
+    pub kyber_ciphertext: KyberCiphertext,
+    /// Signed prekey the initiator used
+    pub signed_prekey_id: u32,
+    /// One-time prekey the initiator used, if any
+    pub one_time_prekey_id: Option<u32>,
+}
+
+/// Result of a completed X3DH key agreement
+pub struct X3dhOutput {
+    master_secret: Zeroizing<[u8; 32]>,
+    session_id: [u8; 32],
+    associated_data: Vec<u8>,
+}
+
+impl X3dhOutput {
+    /// Master secret for `DoubleRatchetSession::from_handshake`
+    pub fn master_secret(&self) -> &[u8; 32] {
+        &self.master_secret
+    }
+
+    /// Session ID shared by both parties
+    pub fn session_id(&self) -> &[u8; 32] {
+        &self.session_id
+    }
+
+    /// Associated data `IK_A || IK_B` for authenticating the first message
+    pub fn associated_data(&self) -> &[u8] {
+        &self.associated_data
+    }
+
+    /// Start the initiator's Double Ratchet session
+    pub fn initiator_session(&self, config: DoubleRatchetConfig) -> CryptoResult<DoubleRatchetSession> {
+        DoubleRatchetSession::from_handshake(&*self.master_secret, self.session_id, config)
+    }
+
+    /// Start the recipient's Double Ratchet session
+    pub fn responder_session(&self, config: DoubleRatchetConfig) -> CryptoResult<DoubleRatchetSession> {
+        DoubleRatchetSession::from_handshake_responder(&*self.master_secret, self.session_id, config)
+    }
+}
+
+/// Recipient-side prekey storage
+///
+/// Holds the identity, the current signed prekey and the unconsumed one-time
+/// prekey secrets. Secrets never leave the store.
+pub struct PrekeyStore {
+    identity: IdentityKeyPair,
+    signed_prekey: SignedPrekey,
+    signed_prekey_sig: DilithiumSignature,
+    signed_prekey_x25519: X25519StaticSecret,
+    signed_prekey_kyber: KyberSecretKey,
+    one_time_prekeys: BTreeMap<u32, X25519StaticSecret>,
+    next_one_time_id: u32,
+}
+
+impl PrekeyStore {
+    /// Create a store with a fresh signed prekey and `one_time_count` one-time prekeys
+    pub fn generate(identity: IdentityKeyPair, one_time_count: usize) -> CryptoResult<Self> {
+        let signed_prekey_x25519 = X25519StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let kyber_keypair = kyber::keypair()?;
+
+        let signed_prekey = SignedPrekey {
+            id: 1,
+            x25519_public: *X25519PublicKey::from(&signed_prekey_x25519).as_bytes(),
+            kyber_public: kyber_keypair.public_key,
+        };
+        let signed_prekey_sig = dilithium::sign(
+            &identity.signing.secret_key,
+            &signed_prekey.signed_bytes(&identity.public),
+        )?;
+
+        let mut store = PrekeyStore {
+            identity,
+            signed_prekey,
+            signed_prekey_sig,
+            signed_prekey_x25519,
+            signed_prekey_kyber: kyber_keypair.secret_key,
+            one_time_prekeys: BTreeMap::new(),
+            next_one_time_id: 1,
+        };
+        store.replenish(one_time_count);
+
+        Ok(store)
+    }
+
+    /// Public identity of the store owner
+    pub fn identity_key(&self) -> &IdentityPublicKey {
+        self.identity.public_key()
+    }
+
+    /// Generate `count` additional one-time prekeys and return their public parts
+    pub fn replenish(&mut self, count: usize) -> Vec<OneTimePrekey> {
+        (0..count)
+            .map(|_| {
+                let id = self.next_one_time_id;
+                self.next_one_time_id = self.next_one_time_id.wrapping_add(1);
+
+                let secret = X25519StaticSecret::random_from_rng(rand::rngs::OsRng);
+                let prekey = OneTimePrekey {
+                    id,
+                    x25519_public: *X25519PublicKey::from(&secret).as_bytes(),
+                };
+                self.one_time_prekeys.insert(id, secret);
+                prekey
+            })
+            .collect()
+    }
+
+    /// Number of unconsumed one-time prekeys
+    pub fn one_time_prekeys_remaining(&self) -> usize {
+        self.one_time_prekeys.len()
+    }
+
+    /// Build a bundle offering the oldest unconsumed one-time prekey
+    pub fn bundle(&self) -> PrekeyBundle {
+        let one_time_prekey = self.one_time_prekeys.iter().next().map(|(&id, secret)| OneTimePrekey {
+            id,
+            x25519_public: *X25519PublicKey::from(secret).as_bytes(),
+        });
+
+        PrekeyBundle {
+            identity_key: self.identity.public.clone(),
+            signed_prekey: self.signed_prekey.clone(),
+            signed_prekey_sig: self.signed_prekey_sig.clone(),
+            one_time_prekey,
+        }
+    }
+
+    /// Process an initial message and consume its one-time prekey
+    ///
+    /// # Returns
+    /// * `Ok(X3dhOutput)` - Shared secret matching the initiator's
+    /// * `Err(CryptoError)` - If the prekeys are unknown or already consumed
+    pub fn respond(&mut self, message: &InitialMessage) -> CryptoResult<X3dhOutput> {
+        if message.signed_prekey_id != self.signed_prekey.id {
+            return Err(CryptoError::InvalidInput(
+                format!("Unknown signed prekey {}", message.signed_prekey_id)
+            ));
+        }
+
+        let one_time_secret = match message.one_time_prekey_id {
+            Some(id) => Some(self.one_time_prekeys.remove(&id).ok_or_else(|| {
+                CryptoError::InvalidInput(format!("One-time prekey {} unknown or already used", id))
+            })?),
+            None => None,
+        };
+
+        let initiator_identity = X25519PublicKey::from(message.identity_key.x25519_public);
+        let ephemeral = X25519PublicKey::from(message.ephemeral_key);
+
+        let dh1 = self.signed_prekey_x25519.diffie_hellman(&initiator_identity);
+        let dh2 = self.identity.x25519_secret.diffie_hellman(&ephemeral);
+        let dh3 = self.signed_prekey_x25519.diffie_hellman(&ephemeral);
+        let dh4 = one_time_secret.map(|secret| secret.diffie_hellman(&ephemeral));
+        let kyber_ss = kyber::decapsulate(&self.signed_prekey_kyber, &message.kyber_ciphertext)?;
+
+        derive_output(
+            &[dh1.as_bytes(), dh2.as_bytes(), dh3.as_bytes()],
+            dh4.as_ref().map(|dh| dh.as_bytes()),
+            kyber_ss.as_bytes(),
+            &message.identity_key,
+            &self.identity.public,
+        )
+    }
+}
+
+/// Initiator side: verify a bundle and derive the shared secret
+///
+/// # Arguments
+/// * `identity` - Initiator identity keypair
+/// * `bundle` - Recipient's prekey bundle
+///
+/// # Returns
+/// * `Ok((X3dhOutput, InitialMessage))` - Shared secret and the message to send
+/// * `Err(CryptoError)` - If bundle verification or key agreement fails
+pub fn initiate(
+    identity: &IdentityKeyPair,
+    bundle: &PrekeyBundle,
+) -> CryptoResult<(X3dhOutput, InitialMessage)> {
+    bundle.verify()?;
+
+    let ephemeral = X25519StaticSecret::random_from_rng(rand::rngs::OsRng);
+    let ephemeral_public = X25519PublicKey::from(&ephemeral);
+
+    let recipient_identity = X25519PublicKey::from(bundle.identity_key.x25519_public);
+    let signed_prekey = X25519PublicKey::from(bundle.signed_prekey.x25519_public);
+
+    let dh1 = identity.x25519_secret.diffie_hellman(&signed_prekey);
+    let dh2 = ephemeral.diffie_hellman(&recipient_identity);
+    let dh3 = ephemeral.diffie_hellman(&signed_prekey);
+    let dh4 = bundle.one_time_prekey.as_ref()
+        .map(|prekey| ephemeral.diffie_hellman(&X25519PublicKey::from(prekey.x25519_public)));
+    let (kyber_ss, kyber_ciphertext) = kyber::encapsulate(&bundle.signed_prekey.kyber_public)?;
+
+    let output = derive_output(
+        &[dh1.as_bytes(), dh2.as_bytes(), dh3.as_bytes()],
+        dh4.as_ref().map(|dh| dh.as_bytes()),
+        kyber_ss.as_bytes(),
+        &identity.public,
+        &bundle.identity_key,
+    )?;
+
+    let message = InitialMessage {
+        identity_key: identity.public.clone(),
+        ephemeral_key: *ephemeral_public.as_bytes(),
+        kyber_ciphertext,
+        signed_prekey_id: bundle.signed_prekey.id,
+        one_time_prekey_id: bundle.one_time_prekey.as_ref().map(|prekey| prekey.id),
+    };
+
+    Ok((output, message))
+}
+
+/// Combine the DH outputs and Kyber secret into the session secrets
+fn derive_output(
+    dh: &[&[u8; 32]; 3],
+    dh4: Option<&[u8; 32]>,
+    kyber_ss: &[u8],
+    initiator: &IdentityPublicKey,
+    recipient: &IdentityPublicKey,
+) -> CryptoResult<X3dhOutput> {
+    // 0xFF prefix separates X3DH from other uses of the X25519 keys
+    let mut ikm = Zeroizing::new(Vec::with_capacity(32 * 5 + kyber_ss.len()));
+    ikm.extend_from_slice(&[0xFF; 32]);
+    for secret in dh {
+        ikm.extend_from_slice(*secret);
+    }
+    if let Some(secret) = dh4 {
+        ikm.extend_from_slice(secret);
+    }
+    ikm.extend_from_slice(kyber_ss);
+
+    let shared_key = Zeroizing::new(derive_key(&[&ikm], X3DH_INFO, 32)?);
+
+    let mut associated_data = initiator.to_bytes();
+    associated_data.extend_from_slice(&recipient.to_bytes());
+
+    let master_secret_vec = Zeroizing::new(derive_key(&[&shared_key], b"B4AE-X3DH-master-secret", 32)?);
+    let mut master_secret = Zeroizing::new([0u8; 32]);
+    master_secret.copy_from_slice(&master_secret_vec);
+
+    let session_id_vec = derive_key(&[&shared_key, &associated_data], b"B4AE-X3DH-session-id", 32)?;
+    let mut session_id = [0u8; 32];
+    session_id.copy_from_slice(&session_id_vec);
+
+    Ok(X3dhOutput {
+        master_secret,
+        session_id,
+        associated_data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_verifies() {
+        let store = PrekeyStore::generate(IdentityKeyPair::generate().unwrap(), 2).unwrap();
+        assert!(store.bundle().verify().is_ok());
+    }
+
+    #[test]
+    fn test_tampered_bundle_rejected() {
+        let alice = IdentityKeyPair::generate().unwrap();
+        let store = PrekeyStore::generate(IdentityKeyPair::generate().unwrap(), 1).unwrap();
+
+        // Swapped signed prekey
+        let mut bundle = store.bundle();
+        bundle.signed_prekey.x25519_public[0] ^= 0x01;
+        assert!(matches!(bundle.verify(), Err(CryptoError::VerificationFailed(_))));
+        assert!(initiate(&alice, &bundle).is_err());
+
+        // Signature from a different identity
+        let mut bundle = store.bundle();
+        bundle.identity_key.signing_key = IdentityKeyPair::generate().unwrap().public_key().signing_key.clone();
+        assert!(bundle.verify().is_err());
+    }
+
+    #[test]
+    fn test_offline_handshake_into_ratchet() {
+        let alice = IdentityKeyPair::generate().unwrap();
+        let mut bob = PrekeyStore::generate(IdentityKeyPair::generate().unwrap(), 1).unwrap();
+
+        // Bob is offline: Alice only has his published bundle
+        let bundle = bob.bundle();
+        assert!(bundle.one_time_prekey.is_some());
+        let (alice_out, initial) = initiate(&alice, &bundle).unwrap();
+
+        let mut alice_session = alice_out.initiator_session(DoubleRatchetConfig::default()).unwrap();
+        let first = alice_session.encrypt_message(b"hello while offline").unwrap();
+
+        // Bob comes online and processes the initial message
+        let bob_out = bob.respond(&initial).unwrap();
+        assert_eq!(alice_out.master_secret(), bob_out.master_secret());
+        assert_eq!(alice_out.session_id(), bob_out.session_id());
+        assert_eq!(alice_out.associated_data(), bob_out.associated_data());
+        assert_eq!(bob.one_time_prekeys_remaining(), 0);
+
+        let mut bob_session = bob_out.responder_session(DoubleRatchetConfig::default()).unwrap();
+        assert_eq!(bob_session.decrypt_message(&first).unwrap(), b"hello while offline");
+
+        let reply = bob_session.encrypt_message(b"back online").unwrap();
+        assert_eq!(alice_session.decrypt_message(&reply).unwrap(), b"back online");
+    }
+
+    #[test]
+    fn test_one_time_prekey_consumed_once() {
+        let alice = IdentityKeyPair::generate().unwrap();
+        let mut bob = PrekeyStore::generate(IdentityKeyPair::generate().unwrap(), 1).unwrap();
+
+        let (_, initial) = initiate(&alice, &bob.bundle()).unwrap();
+        assert!(bob.respond(&initial).is_ok());
+        assert!(bob.respond(&initial).is_err());
+
+        // Exhausted store still issues bundles, without a one-time prekey
+        let bundle = bob.bundle();
+        assert!(bundle.one_time_prekey.is_none());
+        let (alice_out, initial) = initiate(&alice, &bundle).unwrap();
+        let bob_out = bob.respond(&initial).unwrap();
+        assert_eq!(alice_out.master_secret(), bob_out.master_secret());
+    }
+}
@@ -8,6 +8,9 @@
 //! - X25519 keys for key agreement
 //! - SHA-512 for challenge computation
 //! - Constant-time operations for side-channel resistance
+//!
+//! For transferable, externally verifiable signatures use
+//! [`crate::crypto::ed25519`] instead.
 
 use crate::crypto::{CryptoError, CryptoResult};
 use crate::crypto::dilithium::{DilithiumPublicKey, DilithiumSecretKey, DilithiumSignature};
@@ -17,7 +20,7 @@ use curve25519_dalek::{
     scalar::Scalar,
     traits::Identity,
 };
-use rand::{rngs::OsRng, RngCore};
+use crate::crypto::random::SecureRng;
 use sha2::{Digest, Sha512};
 use x25519_dalek::{PublicKey, StaticSecret};
 use zeroize::{Zeroize, ZeroizeOnDrop};
@@ -64,7 +67,20 @@ impl XEdDSAKeyPair {
     /// ```
     pub fn generate() -> CryptoResult<Self> {
         // Generate X25519 secret key from secure RNG
-        let secret = StaticSecret::random_from_rng(OsRng);
+        let secret = StaticSecret::random_from_rng(SecureRng::new());
+        Self::from_secret_bytes(&secret.to_bytes())
+    }
+
+    /// Rebuild a keypair from a stored X25519 secret key.
+    ///
+    /// The public and verification keys are recomputed, so only the 32
+    /// secret bytes need to be persisted.
+    ///
+    /// # Returns
+    /// - `Ok(XEdDSAKeyPair)` on success
+    /// - `Err(CryptoError::KeyGenerationFailed)` if the secret yields an invalid public key
+    pub fn from_secret_bytes(secret_bytes: &[u8; 32]) -> CryptoResult<Self> {
+        let secret = StaticSecret::from(*secret_bytes);
         let public = PublicKey::from(&secret);
 
         // Extract raw bytes
@@ -185,40 +201,74 @@ impl XEdDSAKeyPair {
     /// let signature = keypair.sign(message).unwrap();
     /// ```
     pub fn sign(&self, message: &[u8]) -> CryptoResult<XEdDSASignature> {
+        // Generate random nonce (32 bytes)
+        let mut nonce_bytes = [0u8; 32];
+        SecureRng::new().fill_bytes(&mut nonce_bytes);
+        let nonce = Scalar::from_bytes_mod_order(nonce_bytes);
+        nonce_bytes.zeroize();
+
+        self.sign_with_nonce(message, nonce)
+    }
+
+    /// Sign a message using XEdDSA with a deterministic nonce.
+    ///
+    /// The nonce is `SHA-512(secret_key || message || domain)` reduced to a
+    /// scalar (RFC 6979 in spirit), so signing needs no RNG and a weak RNG
+    /// cannot leak the key. Signatures verify with [`verify`](Self::verify)
+    /// exactly like randomized ones and remain equally deniable. The same
+    /// message always yields the same signature; prefer [`sign`](Self::sign)
+    /// unless RNG quality is a concern.
+    ///
+    /// # Example
+    /// ```
+    /// use b4ae::crypto::xeddsa::XEdDSAKeyPair;
+    ///
+    /// let keypair = XEdDSAKeyPair::generate().unwrap();
+    /// let signature = keypair.sign_deterministic(b"Hello, World!").unwrap();
+    /// assert_eq!(signature, keypair.sign_deterministic(b"Hello, World!").unwrap());
+    /// ```
+    pub fn sign_deterministic(&self, message: &[u8]) -> CryptoResult<XEdDSASignature> {
+        let mut hasher = Sha512::new();
+        hasher.update(self.secret_key);
+        hasher.update(message);
+        hasher.update(b"XEdDSA-deterministic-nonce");
+        let mut nonce_hash: [u8; 64] = hasher.finalize().into();
+        let nonce = Scalar::from_bytes_mod_order_wide(&nonce_hash);
+        nonce_hash.zeroize();
+
+        self.sign_with_nonce(message, nonce)
+    }
+
+    /// Produce the signature `(r, s)` for a given nonce; zeroizes the nonce.
+    fn sign_with_nonce(&self, message: &[u8], mut nonce: Scalar) -> CryptoResult<XEdDSASignature> {
         // Step 1: Derive signing key from X25519 secret using SHA-512
         let mut signing_key = self.derive_signing_key();
 
-        // Step 2: Generate random nonce (32 bytes)
-        let mut nonce_bytes = [0u8; 32];
-        OsRng.fill_bytes(&mut nonce_bytes);
-        let mut nonce = Scalar::from_bytes_mod_order(nonce_bytes);
-
-        // Step 3: Compute commitment r = nonce * G (constant-time scalar multiplication)
+        // Step 2: Compute commitment r = nonce * G (constant-time scalar multiplication)
         let r_point = &nonce * ED25519_BASEPOINT_TABLE;
 
-        // Step 4: Encode r_point to bytes (compressed Edwards point)
+        // Step 3: Encode r_point to bytes (compressed Edwards point)
         let r = r_point.compress().to_bytes();
 
-        // Step 5: Compute challenge c = SHA-512(r || verification_key || message)
+        // Step 4: Compute challenge c = SHA-512(r || verification_key || message)
         let mut hasher = Sha512::new();
         hasher.update(&r);
         hasher.update(&self.verification_key);
         hasher.update(message);
         let challenge_hash = hasher.finalize();
 
-        // Step 6: Compute c = challenge mod curve_order
+        // Step 5: Compute c = challenge mod curve_order
         let c = Scalar::from_bytes_mod_order_wide(&challenge_hash.into());
 
-        // Step 7: Compute response s = (nonce + c * signing_key) mod curve_order (constant-time)
+        // Step 6: Compute response s = (nonce + c * signing_key) mod curve_order (constant-time)
         let s_scalar = nonce + (c * signing_key);
         let s = s_scalar.to_bytes();
 
-        // Step 8: Zeroize sensitive data
+        // Step 7: Zeroize sensitive data
         nonce.zeroize();
         signing_key.zeroize();
-        nonce_bytes.zeroize();
 
-        // Step 9: Return signature
+        // Step 8: Return signature
         Ok(XEdDSASignature { r, s })
     }
 
@@ -227,6 +277,9 @@ impl XEdDSAKeyPair {
     /// Verifies that a signature is valid for the given message and verification key.
     /// Uses constant-time operations to prevent timing attacks.
     ///
+    /// The check is cofactored, `[8](s·G − R − c·A) = 0`, the same equation as
+    /// [`verify_batch`](Self::verify_batch), so the two agree on every signature.
+    ///
     /// # Arguments
     /// - `verification_key` - The Ed25519 verification key (32 bytes compressed Edwards point)
     /// - `message` - The message that was signed
@@ -299,7 +352,7 @@ impl XEdDSAKeyPair {
         // Step 5: Compute c = challenge mod curve_order
         let c = Scalar::from_bytes_mod_order_wide(&challenge_hash.into());
 
-        // Step 6: Verify equation s*G = r + c*A (constant-time)
+        // Step 6: Verify equation [8](s*G - (r + c*A)) = 0 (constant-time)
         // where A is the verification key point
         // Left side: s*G
         let left_side = &s_scalar * ED25519_BASEPOINT_TABLE;
@@ -307,12 +360,9 @@ impl XEdDSAKeyPair {
         // Right side: r + c*A
         let right_side = r_point + (c * a_point);
 
-        // Step 7: Constant-time point comparison
-        // Use constant-time equality check on compressed points
-        let left_compressed = left_side.compress();
-        let right_compressed = right_side.compress();
-        
-        let equation_valid = left_compressed.as_bytes().ct_eq(right_compressed.as_bytes());
+        // Step 7: Constant-time cofactored comparison, matching verify_batch
+        let difference = (left_side - right_side).mul_by_cofactor();
+        let equation_valid = difference.ct_eq(&EdwardsPoint::identity());
 
         // Step 8: Combine all validity checks using constant-time AND
         // All components must be valid: r_valid AND s_valid AND a_valid AND equation_valid
@@ -321,6 +371,89 @@ impl XEdDSAKeyPair {
         Ok(final_valid.into())
     }
 
+    /// Verify many XEdDSA signatures at once.
+    ///
+    /// Checks all well-formed items with a single random linear combination
+    /// `[8](Σ z_i·s_i·G − Σ z_i·R_i − Σ z_i·c_i·A_i) = 0` using 128-bit random
+    /// weights `z_i`. If the combined check fails, each item is re-verified with
+    /// [`verify`](Self::verify) so the result pinpoints the bad signatures.
+    ///
+    /// # Arguments
+    /// - `items` - `(verification_key, message, signature)` tuples
+    ///
+    /// # Returns
+    /// - `Ok(Vec<bool>)` with one validity flag per item, in input order
+    ///
+    /// # Security
+    /// - All inputs are public, so the combined check uses variable-time
+    ///   multiscalar multiplication; the per-item fallback is constant-time
+    /// - The combined check is cofactored like [`verify`](Self::verify), so a
+    ///   signature gets the same verdict in a batch as on its own
+    pub fn verify_batch(
+        items: &[(&[u8; 32], &[u8], &XEdDSASignature)],
+    ) -> CryptoResult<Vec<bool>> {
+        use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+        use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+        use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+
+        let mut results = vec![false; items.len()];
+        let mut batch_indices = Vec::with_capacity(items.len());
+        let mut scalars = Vec::with_capacity(2 * items.len() + 1);
+        let mut points = Vec::with_capacity(2 * items.len() + 1);
+        let mut basepoint_scalar = Scalar::ZERO;
+        let mut rng = SecureRng::new();
+
+        for (index, (verification_key, message, signature)) in items.iter().enumerate() {
+            // Malformed items can never verify; leave them out of the batch
+            let r_point = CompressedEdwardsY(signature.r).decompress();
+            let a_point = CompressedEdwardsY(**verification_key).decompress();
+            let s_scalar = Option::<Scalar>::from(Scalar::from_canonical_bytes(signature.s));
+            let (r_point, a_point, s_scalar) = match (r_point, a_point, s_scalar) {
+                (Some(r), Some(a), Some(s)) => (r, a, s),
+                _ => continue,
+            };
+
+            let mut hasher = Sha512::new();
+            hasher.update(signature.r);
+            hasher.update(verification_key);
+            hasher.update(message);
+            let c = Scalar::from_bytes_mod_order_wide(&hasher.finalize().into());
+
+            let mut z_bytes = [0u8; 32];
+            rng.fill_bytes(&mut z_bytes[..16]);
+            let z = Scalar::from_bytes_mod_order(z_bytes);
+
+            basepoint_scalar += z * s_scalar;
+            scalars.push(-z);
+            points.push(r_point);
+            scalars.push(-(z * c));
+            points.push(a_point);
+            batch_indices.push(index);
+        }
+
+        if batch_indices.is_empty() {
+            return Ok(results);
+        }
+
+        scalars.push(basepoint_scalar);
+        points.push(ED25519_BASEPOINT_POINT);
+
+        let combined = EdwardsPoint::vartime_multiscalar_mul(&scalars, &points);
+        if combined.mul_by_cofactor().is_identity() {
+            for index in batch_indices {
+                results[index] = true;
+            }
+            return Ok(results);
+        }
+
+        // Batch failed: find out which items are bad
+        for index in batch_indices {
+            let (verification_key, message, signature) = items[index];
+            results[index] = Self::verify(verification_key, message, signature)?;
+        }
+        Ok(results)
+    }
+
     /// Get the Ed25519 verification key.
     ///
     /// This is the compressed Edwards point of signing_key * G,
@@ -803,6 +936,90 @@ mod tests {
         assert!(!valid, "Invalid signature should return false");
     }
 
+    #[test]
+    fn test_sign_deterministic() {
+        let keypair = XEdDSAKeyPair::generate().expect("Failed to generate keypair");
+        let other = XEdDSAKeyPair::generate().expect("Failed to generate keypair");
+
+        let sig1 = keypair.sign_deterministic(b"message one").unwrap();
+        let sig2 = keypair.sign_deterministic(b"message one").unwrap();
+        let sig3 = keypair.sign_deterministic(b"message two").unwrap();
+
+        assert_eq!(sig1, sig2, "Same message should give identical signatures");
+        assert_ne!(sig1, sig3, "Different messages should give different signatures");
+        assert_ne!(sig1, other.sign_deterministic(b"message one").unwrap());
+        assert_ne!(sig1, keypair.sign(b"message one").unwrap());
+
+        let vk = keypair.verification_key();
+        assert!(XEdDSAKeyPair::verify(vk, b"message one", &sig1).unwrap());
+        assert!(XEdDSAKeyPair::verify(vk, b"message two", &sig3).unwrap());
+        assert!(!XEdDSAKeyPair::verify(vk, b"message two", &sig1).unwrap());
+    }
+
+    #[test]
+    fn test_verify_batch_all_valid() {
+        let keypairs: Vec<_> = (0..8).map(|_| XEdDSAKeyPair::generate().unwrap()).collect();
+        let messages: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 16 + i as usize]).collect();
+        let signatures: Vec<_> = keypairs.iter().zip(&messages).map(|(kp, m)| kp.sign(m).unwrap()).collect();
+
+        let items: Vec<_> = keypairs.iter().zip(&messages).zip(&signatures)
+            .map(|((kp, m), sig)| (kp.verification_key(), m.as_slice(), sig))
+            .collect();
+
+        assert_eq!(XEdDSAKeyPair::verify_batch(&items).unwrap(), vec![true; 8]);
+        assert!(XEdDSAKeyPair::verify_batch(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_verify_batch_flags_corrupted_index() {
+        let keypairs: Vec<_> = (0..6).map(|_| XEdDSAKeyPair::generate().unwrap()).collect();
+        let message = b"queued message";
+        let mut signatures: Vec<_> = keypairs.iter().map(|kp| kp.sign(message).unwrap()).collect();
+        signatures[3].s[0] ^= 0x01;
+
+        let items: Vec<_> = keypairs.iter().zip(&signatures)
+            .map(|(kp, sig)| (kp.verification_key(), &message[..], sig))
+            .collect();
+
+        let expected: Vec<bool> = (0..6).map(|i| i != 3).collect();
+        assert_eq!(XEdDSAKeyPair::verify_batch(&items).unwrap(), expected);
+
+        // Wrong message for one item is pinpointed as well
+        let mut items = items;
+        items[5].1 = b"other message";
+        let expected: Vec<bool> = (0..6).map(|i| i != 3 && i != 5).collect();
+        assert_eq!(XEdDSAKeyPair::verify_batch(&items).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_verify_and_verify_batch_agree_on_small_order_component() {
+        use curve25519_dalek::constants::EIGHT_TORSION;
+
+        // A key holder can add a torsion point to R and still satisfy the
+        // cofactored equation; both verifiers must give the same answer
+        let mut rng = SecureRng::new();
+        let mut wide = [0u8; 64];
+        rng.fill_bytes(&mut wide);
+        let a = Scalar::from_bytes_mod_order_wide(&wide);
+        rng.fill_bytes(&mut wide);
+        let r = Scalar::from_bytes_mod_order_wide(&wide);
+        let verification_key = (&a * ED25519_BASEPOINT_TABLE).compress().to_bytes();
+        let message = b"torsion";
+
+        let r_point = (&r * ED25519_BASEPOINT_TABLE + EIGHT_TORSION[1]).compress().to_bytes();
+        let mut hasher = Sha512::new();
+        hasher.update(r_point);
+        hasher.update(verification_key);
+        hasher.update(message);
+        let c = Scalar::from_bytes_mod_order_wide(&hasher.finalize().into());
+        let signature = XEdDSASignature { r: r_point, s: (r + c * a).to_bytes() };
+
+        let single = XEdDSAKeyPair::verify(&verification_key, message, &signature).unwrap();
+        let batch = XEdDSAKeyPair::verify_batch(&[(&verification_key, &message[..], &signature)]).unwrap();
+        assert!(single);
+        assert_eq!(batch, vec![single]);
+    }
+
     #[test]
     fn test_hybrid_keypair_generation() {
         let keypair = DeniableHybridKeyPair::generate().expect("Failed to generate hybrid keypair");
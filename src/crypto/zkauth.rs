@@ -1,5 +1,6 @@
 // B4AE Zero-Knowledge Authentication Implementation
-// Allows authentication without revealing identity
+// Allows authentication without revealing identity, plus a password-
+// authenticated key exchange (SPAKE2+ over Ristretto255) for password logins
 
 use crate::crypto::{CryptoError, CryptoResult};
 use crate::crypto::aes_gcm::{self, AesKey};
@@ -7,8 +8,14 @@ use crate::crypto::hkdf;
 use crate::time;
 use crate::crypto::random;
 use crate::crypto::dilithium::{self, DilithiumKeyPair, DilithiumSignature};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
 use sha3::{Sha3_256, Digest};
 use std::collections::HashMap;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
 
 /// Extension type for ZK challenge in handshake
 pub const EXTENSION_TYPE_ZK_CHALLENGE: u16 = 0x0100;
@@ -490,6 +497,399 @@ impl std::fmt::Debug for ZkProof {
     }
 }
 
+/// Argon2id cost parameters for PAKE password stretching.
+///
+/// Client and server must agree on these: they are part of what turns a
+/// password into the registration record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PakeParams {
+    /// Memory cost in KiB.
+    pub memory_kib: u32,
+    /// Number of passes.
+    pub iterations: u32,
+}
+
+impl PakeParams {
+    /// Create custom parameters.
+    pub fn new(memory_kib: u32, iterations: u32) -> Self {
+        PakeParams { memory_kib, iterations }
+    }
+}
+
+impl Default for PakeParams {
+    /// OWASP-recommended Argon2id settings (19 MiB, 2 passes).
+    fn default() -> Self {
+        PakeParams { memory_kib: 19 * 1024, iterations: 2 }
+    }
+}
+
+/// Length of the random per-registration PAKE salt.
+pub const PAKE_SALT_SIZE: usize = 16;
+
+/// Server-side PAKE record: `(salt, w0, L = w1·G)`.
+///
+/// Not password-equivalent: logging in as the client requires `w1`, which the
+/// server never sees. A stolen record still allows an offline guessing attack,
+/// but each guess costs one Argon2id evaluation and the random salt drawn at
+/// registration rules out precomputation, even for a known identity.
+#[derive(Clone)]
+pub struct PakeRegistration {
+    identity: Vec<u8>,
+    salt: [u8; PAKE_SALT_SIZE],
+    w0: [u8; 32],
+    verifier: [u8; 32],
+}
+
+/// First PAKE message (client → server): `X = x·G + w0·M`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PakeClientMessage {
+    /// Client share (compressed Ristretto point).
+    pub share: [u8; 32],
+}
+
+/// Second PAKE message (server → client): `Y = y·G + w0·N` and key confirmation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PakeServerMessage {
+    /// Server share (compressed Ristretto point).
+    pub share: [u8; 32],
+    /// Server key confirmation.
+    pub confirmation: [u8; 32],
+}
+
+/// Third PAKE message (client → server): client key confirmation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PakeClientConfirm {
+    /// Client key confirmation.
+    pub confirmation: [u8; 32],
+}
+
+/// Client state between [`pake_client_start`] and [`pake_client_finish`].
+pub struct PakeClientState {
+    identity: Vec<u8>,
+    w0: Scalar,
+    w1: Scalar,
+    x: Scalar,
+    share: [u8; 32],
+}
+
+/// Server state between [`pake_server_respond`] and [`pake_server_finish`].
+pub struct PakeServerState {
+    expected_confirmation: [u8; 32],
+    session_key: PakeSessionKey,
+}
+
+/// Shared PAKE session key (zeroized on drop).
+///
+/// Bind it into a B4AE handshake via `HandshakeConfig::pake_key`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PakeSessionKey([u8; 32]);
+
+impl PakeSessionKey {
+    /// Key bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl PakeRegistration {
+    /// Identity this record belongs to.
+    pub fn identity(&self) -> &[u8] {
+        &self.identity
+    }
+
+    /// Salt the client needs for [`pake_client_start`].
+    ///
+    /// The server sends it before the first PAKE message. To avoid revealing
+    /// which identities are registered, answer unknown identities with a
+    /// stable fake salt (e.g. an HMAC of the identity under a server key).
+    pub fn salt(&self) -> &[u8; PAKE_SALT_SIZE] {
+        &self.salt
+    }
+
+    /// Serialize for storage:
+    /// `identity_len (u16) || identity || salt (16) || w0 (32) || L (32)`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(2 + self.identity.len() + PAKE_SALT_SIZE + 64);
+        bytes.extend_from_slice(&(self.identity.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.identity);
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&self.w0);
+        bytes.extend_from_slice(&self.verifier);
+        bytes
+    }
+
+    /// Deserialize a stored record.
+    pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        if bytes.len() < 2 {
+            return Err(CryptoError::InvalidInput("PAKE registration too short".to_string()));
+        }
+        let identity_len = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+        if bytes.len() != 2 + identity_len + PAKE_SALT_SIZE + 64 {
+            return Err(CryptoError::InvalidInput("Invalid PAKE registration length".to_string()));
+        }
+        let (identity, rest) = bytes[2..].split_at(identity_len);
+        let (salt_bytes, rest) = rest.split_at(PAKE_SALT_SIZE);
+        let mut salt = [0u8; PAKE_SALT_SIZE];
+        salt.copy_from_slice(salt_bytes);
+        let mut w0 = [0u8; 32];
+        w0.copy_from_slice(&rest[..32]);
+        let mut verifier = [0u8; 32];
+        verifier.copy_from_slice(&rest[32..]);
+
+        if Option::<Scalar>::from(Scalar::from_canonical_bytes(w0)).is_none() {
+            return Err(CryptoError::InvalidInput("Invalid PAKE w0".to_string()));
+        }
+        decode_pake_point(&verifier)?;
+        Ok(PakeRegistration { identity: identity.to_vec(), salt, w0, verifier })
+    }
+}
+
+/// Register a password: run by the client, the record is sent to the server once.
+///
+/// Draws a fresh random salt, stored in the record.
+pub fn pake_register(identity: &[u8], password: &[u8], params: &PakeParams) -> CryptoResult<PakeRegistration> {
+    let mut salt = [0u8; PAKE_SALT_SIZE];
+    random::fill_random(&mut salt)?;
+    let (mut w0, mut w1) = pake_password_scalars(identity, password, &salt, params)?;
+    let verifier = (&w1 * RISTRETTO_BASEPOINT_TABLE).compress().to_bytes();
+    let registration = PakeRegistration {
+        identity: identity.to_vec(),
+        salt,
+        w0: w0.to_bytes(),
+        verifier,
+    };
+    w0.zeroize();
+    w1.zeroize();
+    Ok(registration)
+}
+
+/// Start a PAKE login with the salt the server sent from the record
+/// ([`PakeRegistration::salt`]).
+pub fn pake_client_start(
+    identity: &[u8],
+    password: &[u8],
+    salt: &[u8; PAKE_SALT_SIZE],
+    params: &PakeParams,
+) -> CryptoResult<(PakeClientState, PakeClientMessage)> {
+    let (w0, w1) = pake_password_scalars(identity, password, salt, params)?;
+    let x = random_scalar();
+    let (m, _) = pake_generators();
+
+    let share = (&x * RISTRETTO_BASEPOINT_TABLE + w0 * m).compress().to_bytes();
+
+    Ok((
+        PakeClientState { identity: identity.to_vec(), w0, w1, x, share },
+        PakeClientMessage { share },
+    ))
+}
+
+/// Answer a PAKE login using the stored registration record.
+pub fn pake_server_respond(
+    registration: &PakeRegistration,
+    client_msg: &PakeClientMessage,
+) -> CryptoResult<(PakeServerState, PakeServerMessage)> {
+    let x_point = decode_pake_point(&client_msg.share)?;
+    let verifier = decode_pake_point(&registration.verifier)?;
+    let mut w0 = Option::<Scalar>::from(Scalar::from_canonical_bytes(registration.w0))
+        .ok_or_else(|| CryptoError::InvalidInput("Invalid PAKE w0".to_string()))?;
+    let (m, n) = pake_generators();
+
+    let mut y = random_scalar();
+    let share = (&y * RISTRETTO_BASEPOINT_TABLE + w0 * n).compress().to_bytes();
+
+    let z = (y * (x_point - w0 * m)).compress().to_bytes();
+    let v = (y * verifier).compress().to_bytes();
+
+    let keys = pake_key_schedule(&registration.identity, &client_msg.share, &share, &z, &v, &w0)?;
+    y.zeroize();
+    w0.zeroize();
+
+    Ok((
+        PakeServerState {
+            expected_confirmation: keys.client_confirmation,
+            session_key: keys.session_key,
+        },
+        PakeServerMessage { share, confirmation: keys.server_confirmation },
+    ))
+}
+
+/// Finish the PAKE on the client: verifies the server and yields the session key.
+///
+/// Fails with `AuthenticationFailed` if the password (or server record) does
+/// not match; the error carries no information about the password.
+pub fn pake_client_finish(
+    mut state: PakeClientState,
+    server_msg: &PakeServerMessage,
+) -> CryptoResult<(PakeSessionKey, PakeClientConfirm)> {
+    let y_point = decode_pake_point(&server_msg.share)?;
+    let (_, n) = pake_generators();
+
+    let unblinded = y_point - state.w0 * n;
+    let z = (state.x * unblinded).compress().to_bytes();
+    let v = (state.w1 * unblinded).compress().to_bytes();
+
+    let keys = pake_key_schedule(&state.identity, &state.share, &server_msg.share, &z, &v, &state.w0)?;
+    state.x.zeroize();
+    state.w0.zeroize();
+    state.w1.zeroize();
+
+    if !bool::from(keys.server_confirmation.ct_eq(&server_msg.confirmation)) {
+        return Err(CryptoError::AuthenticationFailed);
+    }
+
+    Ok((keys.session_key, PakeClientConfirm { confirmation: keys.client_confirmation }))
+}
+
+/// Finish the PAKE on the server: verifies the client and yields the session key.
+pub fn pake_server_finish(state: PakeServerState, confirm: &PakeClientConfirm) -> CryptoResult<PakeSessionKey> {
+    if !bool::from(state.expected_confirmation.ct_eq(&confirm.confirmation)) {
+        return Err(CryptoError::AuthenticationFailed);
+    }
+    Ok(state.session_key)
+}
+
+struct PakeKeys {
+    session_key: PakeSessionKey,
+    client_confirmation: [u8; 32],
+    server_confirmation: [u8; 32],
+}
+
+/// Nothing-up-my-sleeve generators M and N (hash-to-group, unknown discrete logs)
+fn pake_generators() -> (RistrettoPoint, RistrettoPoint) {
+    let hash_to_point = |label: &[u8]| {
+        let digest: [u8; 64] = sha2::Sha512::digest(label).into();
+        RistrettoPoint::from_uniform_bytes(&digest)
+    };
+    (hash_to_point(b"B4AE-PAKE-v1-M"), hash_to_point(b"B4AE-PAKE-v1-N"))
+}
+
+/// Stretch the password into `(w0, w1)` with Argon2id
+fn pake_password_scalars(
+    identity: &[u8],
+    password: &[u8],
+    salt: &[u8; PAKE_SALT_SIZE],
+    params: &PakeParams,
+) -> CryptoResult<(Scalar, Scalar)> {
+    use argon2::{Algorithm, Argon2, Params, Version};
+
+    let argon_params = Params::new(params.memory_kib, params.iterations, 1, Some(128))
+        .map_err(|e| CryptoError::InvalidInput(format!("Invalid PAKE parameters: {}", e)))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params);
+
+    let mut salt_hasher = Sha3_256::new();
+    salt_hasher.update(b"B4AE-PAKE-v1-salt");
+    salt_hasher.update(salt);
+    salt_hasher.update(identity);
+    let salt = salt_hasher.finalize();
+
+    let mut output = zeroize::Zeroizing::new([0u8; 128]);
+    argon2.hash_password_into(password, &salt, output.as_mut())
+        .map_err(|e| CryptoError::KeyGenerationFailed(format!("Password hashing failed: {}", e)))?;
+
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&output[..64]);
+    let w0 = Scalar::from_bytes_mod_order_wide(&wide);
+    wide.copy_from_slice(&output[64..]);
+    let w1 = Scalar::from_bytes_mod_order_wide(&wide);
+    wide.zeroize();
+
+    Ok((w0, w1))
+}
+
+fn random_scalar() -> Scalar {
+    let mut wide = [0u8; 64];
+    random::SecureRng::new().fill_bytes(&mut wide);
+    let scalar = Scalar::from_bytes_mod_order_wide(&wide);
+    wide.zeroize();
+    scalar
+}
+
+fn decode_pake_point(bytes: &[u8; 32]) -> CryptoResult<RistrettoPoint> {
+    let point = CompressedRistretto(*bytes).decompress()
+        .ok_or_else(|| CryptoError::InvalidInput("Invalid PAKE point".to_string()))?;
+    if point.is_identity() {
+        return Err(CryptoError::InvalidInput("PAKE point is the identity".to_string()));
+    }
+    Ok(point)
+}
+
+/// Derive session key and confirmations from the length-prefixed transcript
+fn pake_key_schedule(
+    identity: &[u8],
+    client_share: &[u8; 32],
+    server_share: &[u8; 32],
+    z: &[u8; 32],
+    v: &[u8; 32],
+    w0: &Scalar,
+) -> CryptoResult<PakeKeys> {
+    let (m, n) = pake_generators();
+    let w0_bytes = zeroize::Zeroizing::new(w0.to_bytes());
+
+    let mut transcript = zeroize::Zeroizing::new(Vec::with_capacity(256 + identity.len()));
+    for part in [
+        &b"B4AE-PAKE-v1"[..],
+        identity,
+        m.compress().as_bytes(),
+        n.compress().as_bytes(),
+        client_share,
+        server_share,
+        z,
+        v,
+        &w0_bytes[..],
+    ] {
+        transcript.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        transcript.extend_from_slice(part);
+    }
+
+    let main_key = zeroize::Zeroizing::new(hkdf::derive_key(&[&transcript], b"B4AE-PAKE-v1-main", 32)?);
+    let to_array = |bytes: Vec<u8>| {
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&bytes);
+        out
+    };
+
+    Ok(PakeKeys {
+        session_key: PakeSessionKey(to_array(hkdf::derive_key(&[&main_key], b"B4AE-PAKE-v1-session-key", 32)?)),
+        client_confirmation: to_array(hkdf::derive_key(&[&main_key, server_share], b"B4AE-PAKE-v1-client-confirm", 32)?),
+        server_confirmation: to_array(hkdf::derive_key(&[&main_key, client_share], b"B4AE-PAKE-v1-server-confirm", 32)?),
+    })
+}
+
+impl Drop for PakeRegistration {
+    fn drop(&mut self) {
+        self.w0.zeroize();
+    }
+}
+
+impl Drop for PakeClientState {
+    fn drop(&mut self) {
+        self.x.zeroize();
+        self.w0.zeroize();
+        self.w1.zeroize();
+    }
+}
+
+impl Drop for PakeSessionKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl std::fmt::Debug for PakeRegistration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PakeRegistration")
+            .field("identity", &String::from_utf8_lossy(&self.identity))
+            .field("verifier", &hex::encode(&self.verifier[..8]))
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for PakeSessionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PakeSessionKey([REDACTED])")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -587,4 +987,76 @@ mod tests {
         assert_eq!(decrypted.as_slice(), value);
         assert_eq!(String::from_utf8(decrypted).unwrap(), "legacy_value");
     }
-}
\ No newline at end of file
+
+    fn test_pake_params() -> PakeParams {
+        PakeParams::new(64, 1)
+    }
+
+    #[test]
+    fn test_pake_correct_password() {
+        let params = test_pake_params();
+        let registration = pake_register(b"alice", b"correct horse", &params).unwrap();
+        let registration = PakeRegistration::from_bytes(&registration.to_bytes()).unwrap();
+
+        let (client_state, client_msg) = pake_client_start(b"alice", b"correct horse", registration.salt(), &params).unwrap();
+        let (server_state, server_msg) = pake_server_respond(&registration, &client_msg).unwrap();
+        let (client_key, confirm) = pake_client_finish(client_state, &server_msg).unwrap();
+        let server_key = pake_server_finish(server_state, &confirm).unwrap();
+
+        assert_eq!(client_key.as_bytes(), server_key.as_bytes());
+    }
+
+    #[test]
+    fn test_pake_wrong_password_fails() {
+        let params = test_pake_params();
+        let registration = pake_register(b"alice", b"correct horse", &params).unwrap();
+
+        let (client_state, client_msg) = pake_client_start(b"alice", b"battery staple", registration.salt(), &params).unwrap();
+        let (server_state, server_msg) = pake_server_respond(&registration, &client_msg).unwrap();
+
+        let result = pake_client_finish(client_state, &server_msg);
+        assert!(matches!(result, Err(CryptoError::AuthenticationFailed)));
+
+        // A client that ignores the failure cannot produce a valid confirmation
+        let forged = PakeClientConfirm { confirmation: [0u8; 32] };
+        assert!(matches!(pake_server_finish(server_state, &forged), Err(CryptoError::AuthenticationFailed)));
+
+        // The stored record does not contain the password
+        let record = registration.to_bytes();
+        assert!(!record.windows(b"correct horse".len()).any(|w| w == b"correct horse"));
+    }
+
+    #[test]
+    fn test_pake_identity_bound_and_randomized() {
+        let params = test_pake_params();
+        let registration = pake_register(b"alice", b"pw", &params).unwrap();
+
+        // Same password under another identity does not match alice's record
+        let (client_state, client_msg) = pake_client_start(b"mallory", b"pw", registration.salt(), &params).unwrap();
+        let (_, server_msg) = pake_server_respond(&registration, &client_msg).unwrap();
+        assert!(pake_client_finish(client_state, &server_msg).is_err());
+
+        let (_, first) = pake_client_start(b"alice", b"pw", registration.salt(), &params).unwrap();
+        let (_, second) = pake_client_start(b"alice", b"pw", registration.salt(), &params).unwrap();
+        assert_ne!(first, second);
+
+        assert!(pake_server_respond(&registration, &PakeClientMessage { share: [0u8; 32] }).is_err());
+    }
+
+    #[test]
+    fn test_pake_salt_is_random_per_registration() {
+        let params = test_pake_params();
+        let first = pake_register(b"alice", b"pw", &params).unwrap();
+        let second = pake_register(b"alice", b"pw", &params).unwrap();
+
+        // Same identity and password, unrelated records
+        assert_ne!(first.salt(), second.salt());
+        assert_ne!(first.to_bytes()[2 + 5 + PAKE_SALT_SIZE..], second.to_bytes()[2 + 5 + PAKE_SALT_SIZE..]);
+        assert_eq!(PakeRegistration::from_bytes(&first.to_bytes()).unwrap().salt(), first.salt());
+
+        // Logging in against one record with the other's salt fails
+        let (client_state, client_msg) = pake_client_start(b"alice", b"pw", second.salt(), &params).unwrap();
+        let (_, server_msg) = pake_server_respond(&first, &client_msg).unwrap();
+        assert!(matches!(pake_client_finish(client_state, &server_msg), Err(CryptoError::AuthenticationFailed)));
+    }
+}
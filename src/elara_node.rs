@@ -265,7 +265,7 @@ impl B4aeElaraNode {
                 if let Some(layer_key) = self.client.onion_layer_key(&peer_id)? {
                     let path = [(Vec::new(), layer_key)]; // empty next_hop = final destination
                     let layer = onion::onion_encrypt(&path, &enc_bytes)
-                        .map_err(|e| B4aeError::CryptoError(e.to_string()))?;
+                        .map_err(B4aeError::CryptoError)?;
                     B4aeWireMessage::OnionWrapped(layer.as_bytes().to_vec())
                 } else {
                     B4aeWireMessage::EncryptedMessage(enc_bytes)
@@ -304,7 +304,7 @@ impl B4aeElaraNode {
                         None => continue, // no session or onion disabled
                     };
                     let (_, payload) = onion::onion_decrypt_layer(&layer_key, onion_bytes)
-                        .map_err(|e| B4aeError::CryptoError(e.to_string()))?;
+                        .map_err(B4aeError::CryptoError)?;
                     payload
                 }
                 _ => continue,
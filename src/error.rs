@@ -1,13 +1,27 @@
 // B4AE Error Types
 
+use crate::crypto::CryptoError;
 use std::fmt;
 use std::error::Error;
 
 /// B4AE Error Type
+///
+/// Cryptographic failures keep the underlying [`CryptoError`], reachable by
+/// matching on `B4aeError::CryptoError` or through [`Error::source`]:
+///
+/// ```rust
+/// use b4ae::crypto::CryptoError;
+/// use b4ae::error::B4aeError;
+/// use std::error::Error;
+///
+/// let err = B4aeError::from(CryptoError::AuthenticationFailed);
+/// let cause = err.source().and_then(|e| e.downcast_ref::<CryptoError>());
+/// assert!(matches!(cause, Some(CryptoError::AuthenticationFailed)));
+/// ```
 #[derive(Debug, Clone)]
 pub enum B4aeError {
     /// Cryptographic operation failed
-    CryptoError(String),
+    CryptoError(CryptoError),
     
     /// Protocol error
     ProtocolError(String),
@@ -40,7 +54,7 @@ pub enum B4aeError {
 impl fmt::Display for B4aeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            B4aeError::CryptoError(msg) => write!(f, "Cryptographic error: {}", msg),
+            B4aeError::CryptoError(err) => write!(f, "Cryptographic error: {}", err),
             B4aeError::ProtocolError(msg) => write!(f, "Protocol error: {}", msg),
             B4aeError::NetworkError(msg) => write!(f, "Network error: {}", msg),
             B4aeError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
@@ -54,12 +68,23 @@ impl fmt::Display for B4aeError {
     }
 }
 
-impl Error for B4aeError {}
+impl Error for B4aeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            B4aeError::CryptoError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 /// Convert CryptoError to B4aeError
-impl From<crate::crypto::CryptoError> for B4aeError {
-    fn from(err: crate::crypto::CryptoError) -> Self {
-        B4aeError::CryptoError(err.to_string())
+impl From<CryptoError> for B4aeError {
+    fn from(err: CryptoError) -> Self {
+        match err {
+            // A pinned identity mismatch is a protocol-level rejection of the peer
+            CryptoError::FingerprintMismatch => B4aeError::ProtocolError("fingerprint mismatch".to_string()),
+            err => B4aeError::CryptoError(err),
+        }
     }
 }
 
@@ -75,8 +100,8 @@ mod tests {
 
     #[test]
     fn test_error_display() {
-        let err = B4aeError::CryptoError("test error".to_string());
-        assert_eq!(err.to_string(), "Cryptographic error: test error");
+        let err = B4aeError::CryptoError(CryptoError::InvalidInput("test error".to_string()));
+        assert_eq!(err.to_string(), "Cryptographic error: Invalid input: test error");
     }
 
     #[test]
@@ -85,8 +110,41 @@ mod tests {
         let b4ae_err: B4aeError = crypto_err.into();
         
         match b4ae_err {
-            B4aeError::CryptoError(_) => (),
+            B4aeError::CryptoError(CryptoError::EncryptionFailed(msg)) => assert_eq!(msg, "test"),
             _ => panic!("Wrong error type"),
         }
     }
+
+    #[test]
+    fn test_question_mark_conversion() {
+        fn decrypt_step() -> B4aeResult<()> {
+            Err(CryptoError::InvalidPadding)?
+        }
+
+        let err = decrypt_step().unwrap_err();
+        assert!(matches!(err, B4aeError::CryptoError(CryptoError::InvalidPadding)));
+        assert!(B4aeError::ConfigError("x".to_string()).source().is_none());
+    }
+
+    #[test]
+    fn test_decryption_failure_source() {
+        use crate::client::B4aeClient;
+        use crate::protocol::SecurityProfile;
+
+        let mut alice = B4aeClient::new(SecurityProfile::Standard).unwrap();
+        let mut bob = B4aeClient::new(SecurityProfile::Standard).unwrap();
+        let init = alice.initiate_handshake(b"bob").unwrap();
+        let response = bob.respond_to_handshake(b"alice", init).unwrap();
+        let complete = alice.process_response(b"bob", response).unwrap();
+        bob.complete_handshake(b"alice", complete).unwrap();
+        alice.finalize_initiator(b"bob").unwrap();
+
+        let mut encrypted = alice.encrypt_message(b"bob", b"hello").unwrap().pop().unwrap();
+        let last = encrypted.payload.len() - 1;
+        encrypted.payload[last] ^= 0x01;
+
+        let err = bob.decrypt_message(b"alice", &encrypted).unwrap_err();
+        let cause = err.source().and_then(|e| e.downcast_ref::<CryptoError>());
+        assert!(matches!(cause, Some(CryptoError::AuthenticationFailed)), "{:?}", err);
+    }
 }
@@ -0,0 +1,291 @@
+//! Async HSM backend
+//!
+//! Network-bound HSMs and cloud KMS services should not block the async
+//! runtime. [`AsyncHsmBackend`] mirrors [`HsmBackend`] with async methods;
+//! the adapters bridge the two:
+//!
+//! - [`SyncHsmAdapter`] runs a sync backend on tokio's blocking pool so it
+//!   satisfies `AsyncHsmBackend`.
+//! - [`BlockingHsm`] drives an async backend with `block_on` so it can be
+//!   used where a sync `HsmBackend` is expected.
+//! - [`TimeoutHsm`] bounds every call and reports expiry as a `B4aeError`.
+
+use super::HsmBackend;
+use crate::error::{B4aeError, B4aeResult};
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Async HSM backend abstraction.
+///
+/// Same contract as [`HsmBackend`]; implementations must be `Send + Sync`
+/// and return `Send` futures so calls can move across runtime threads.
+#[async_trait]
+pub trait AsyncHsmBackend: Send + Sync {
+    /// Generate keypair dalam HSM
+    async fn generate_keypair(&self, _key_id: &str) -> B4aeResult<Vec<u8>> {
+        Err(B4aeError::ProtocolError(
+            "HSM not configured".to_string(),
+        ))
+    }
+
+    /// Sign data dengan key di HSM
+    async fn sign(&self, _key_id: &str, _data: &[u8]) -> B4aeResult<Vec<u8>> {
+        Err(B4aeError::ProtocolError(
+            "HSM not configured".to_string(),
+        ))
+    }
+
+    /// Verify signature
+    async fn verify(&self, _key_id: &str, _data: &[u8], _signature: &[u8]) -> B4aeResult<bool> {
+        Err(B4aeError::ProtocolError(
+            "HSM not configured".to_string(),
+        ))
+    }
+
+    /// Cek apakah HSM tersedia
+    async fn is_available(&self) -> bool {
+        false
+    }
+}
+
+/// Runs a sync [`HsmBackend`] on tokio's blocking thread pool
+///
+/// Each call moves to `spawn_blocking`, so slow PKCS#11 round-trips do not
+/// stall the runtime's worker threads. Requires a tokio runtime.
+pub struct SyncHsmAdapter<B: HsmBackend + 'static> {
+    inner: Arc<B>,
+}
+
+impl<B: HsmBackend + 'static> SyncHsmAdapter<B> {
+    /// Wrap a sync backend
+    pub fn new(inner: B) -> Self {
+        Self::from_arc(Arc::new(inner))
+    }
+
+    /// Wrap a sync backend that is shared elsewhere
+    pub fn from_arc(inner: Arc<B>) -> Self {
+        Self { inner }
+    }
+
+    async fn run<T, F>(&self, f: F) -> B4aeResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&B) -> B4aeResult<T> + Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || f(&inner))
+            .await
+            .map_err(|e| B4aeError::InternalError(format!("HSM worker failed: {}", e)))?
+    }
+}
+
+#[async_trait]
+impl<B: HsmBackend + 'static> AsyncHsmBackend for SyncHsmAdapter<B> {
+    async fn generate_keypair(&self, key_id: &str) -> B4aeResult<Vec<u8>> {
+        let key_id = key_id.to_string();
+        self.run(move |hsm| hsm.generate_keypair(&key_id)).await
+    }
+
+    async fn sign(&self, key_id: &str, data: &[u8]) -> B4aeResult<Vec<u8>> {
+        let (key_id, data) = (key_id.to_string(), data.to_vec());
+        self.run(move |hsm| hsm.sign(&key_id, &data)).await
+    }
+
+    async fn verify(&self, key_id: &str, data: &[u8], signature: &[u8]) -> B4aeResult<bool> {
+        let (key_id, data, signature) = (key_id.to_string(), data.to_vec(), signature.to_vec());
+        self.run(move |hsm| hsm.verify(&key_id, &data, &signature)).await
+    }
+
+    async fn is_available(&self) -> bool {
+        self.run(|hsm| Ok(hsm.is_available())).await.unwrap_or(false)
+    }
+}
+
+/// Drives an [`AsyncHsmBackend`] with `block_on` to satisfy [`HsmBackend`]
+///
+/// Owns a single-threaded runtime. Calling it from inside another tokio
+/// runtime panics; async callers should use the async backend directly.
+pub struct BlockingHsm<A: AsyncHsmBackend> {
+    inner: A,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl<A: AsyncHsmBackend> BlockingHsm<A> {
+    /// Wrap an async backend with its own runtime
+    pub fn new(inner: A) -> B4aeResult<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| B4aeError::InternalError(format!("HSM runtime: {}", e)))?;
+        Ok(Self { inner, runtime })
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+impl<A: AsyncHsmBackend> HsmBackend for BlockingHsm<A> {
+    fn generate_keypair(&self, key_id: &str) -> B4aeResult<Vec<u8>> {
+        self.block_on(self.inner.generate_keypair(key_id))
+    }
+
+    fn sign(&self, key_id: &str, data: &[u8]) -> B4aeResult<Vec<u8>> {
+        self.block_on(self.inner.sign(key_id, data))
+    }
+
+    fn verify(&self, key_id: &str, data: &[u8], signature: &[u8]) -> B4aeResult<bool> {
+        self.block_on(self.inner.verify(key_id, data, signature))
+    }
+
+    fn is_available(&self) -> bool {
+        self.block_on(self.inner.is_available())
+    }
+}
+
+/// Bounds every call of an [`AsyncHsmBackend`]
+///
+/// A call that exceeds the timeout is dropped and fails with
+/// `B4aeError::NetworkError` naming the operation.
+pub struct TimeoutHsm<A: AsyncHsmBackend> {
+    inner: A,
+    timeout: Duration,
+}
+
+impl<A: AsyncHsmBackend> TimeoutHsm<A> {
+    /// Wrap `inner`, failing calls that take longer than `timeout`
+    pub fn new(inner: A, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+
+    async fn bounded<T>(&self, operation: &str, future: impl Future<Output = B4aeResult<T>> + Send) -> B4aeResult<T> {
+        tokio::time::timeout(self.timeout, future).await.map_err(|_| {
+            B4aeError::NetworkError(format!("HSM {} timed out after {:?}", operation, self.timeout))
+        })?
+    }
+}
+
+#[async_trait]
+impl<A: AsyncHsmBackend> AsyncHsmBackend for TimeoutHsm<A> {
+    async fn generate_keypair(&self, key_id: &str) -> B4aeResult<Vec<u8>> {
+        self.bounded("generate_keypair", self.inner.generate_keypair(key_id)).await
+    }
+
+    async fn sign(&self, key_id: &str, data: &[u8]) -> B4aeResult<Vec<u8>> {
+        self.bounded("sign", self.inner.sign(key_id, data)).await
+    }
+
+    async fn verify(&self, key_id: &str, data: &[u8], signature: &[u8]) -> B4aeResult<bool> {
+        self.bounded("verify", self.inner.verify(key_id, data, signature)).await
+    }
+
+    /// Reports unavailable if the probe itself times out
+    async fn is_available(&self) -> bool {
+        tokio::time::timeout(self.timeout, self.inner.is_available())
+            .await
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha3::{Digest, Sha3_256};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Mock remote HSM: "signs" with a keyed hash after a simulated network delay
+    struct MockRemoteHsm {
+        latency: Duration,
+        keys: Mutex<HashMap<String, [u8; 32]>>,
+    }
+
+    impl MockRemoteHsm {
+        fn new(latency: Duration) -> Self {
+            Self { latency, keys: Mutex::new(HashMap::new()) }
+        }
+
+        fn tag(key: &[u8; 32], data: &[u8]) -> Vec<u8> {
+            let mut hasher = Sha3_256::new();
+            hasher.update(key);
+            hasher.update(data);
+            hasher.finalize().to_vec()
+        }
+
+        fn key(&self, key_id: &str) -> B4aeResult<[u8; 32]> {
+            self.keys
+                .lock()
+                .unwrap()
+                .get(key_id)
+                .copied()
+                .ok_or_else(|| B4aeError::ProtocolError(format!("Key not found: {}", key_id)))
+        }
+    }
+
+    #[async_trait]
+    impl AsyncHsmBackend for MockRemoteHsm {
+        async fn generate_keypair(&self, key_id: &str) -> B4aeResult<Vec<u8>> {
+            tokio::time::sleep(self.latency).await;
+            let key: [u8; 32] = Sha3_256::digest(key_id.as_bytes()).into();
+            self.keys.lock().unwrap().insert(key_id.to_string(), key);
+            Ok(key.to_vec())
+        }
+
+        async fn sign(&self, key_id: &str, data: &[u8]) -> B4aeResult<Vec<u8>> {
+            tokio::time::sleep(self.latency).await;
+            Ok(Self::tag(&self.key(key_id)?, data))
+        }
+
+        async fn verify(&self, key_id: &str, data: &[u8], signature: &[u8]) -> B4aeResult<bool> {
+            tokio::time::sleep(self.latency).await;
+            Ok(Self::tag(&self.key(key_id)?, data) == signature)
+        }
+
+        async fn is_available(&self) -> bool {
+            true
+        }
+    }
+
+    fn assert_send_sync<T: Send + Sync + ?Sized>() {}
+
+    #[tokio::test]
+    async fn test_async_sign_verify_roundtrip() {
+        assert_send_sync::<dyn AsyncHsmBackend>();
+
+        let hsm = TimeoutHsm::new(MockRemoteHsm::new(Duration::from_millis(1)), Duration::from_secs(5));
+        assert!(hsm.is_available().await);
+
+        hsm.generate_keypair("kms-key").await.unwrap();
+        let signature = hsm.sign("kms-key", b"payload").await.unwrap();
+        assert!(hsm.verify("kms-key", b"payload", &signature).await.unwrap());
+        assert!(!hsm.verify("kms-key", b"other", &signature).await.unwrap());
+        assert!(matches!(hsm.sign("missing", b"payload").await, Err(B4aeError::ProtocolError(_))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout_surfaces_as_error() {
+        let hsm = TimeoutHsm::new(MockRemoteHsm::new(Duration::from_secs(30)), Duration::from_secs(2));
+        match hsm.sign("kms-key", b"payload").await {
+            Err(B4aeError::NetworkError(msg)) => assert!(msg.contains("sign timed out"), "{}", msg),
+            other => panic!("expected timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_backend_through_adapter() {
+        let hsm = SyncHsmAdapter::new(super::super::NoOpHsm::new());
+        assert!(!hsm.is_available().await);
+        assert!(hsm.sign("key", b"data").await.is_err());
+    }
+
+    #[test]
+    fn test_blocking_hsm_drives_async_backend() {
+        let hsm = BlockingHsm::new(MockRemoteHsm::new(Duration::from_millis(1))).unwrap();
+        hsm.generate_keypair("kms-key").unwrap();
+        let signature = hsm.sign("kms-key", b"payload").unwrap();
+        assert!(hsm.verify("kms-key", b"payload", &signature).unwrap());
+        assert!(hsm.is_available());
+    }
+}
@@ -0,0 +1,125 @@
+//! AWS KMS backend skeleton
+//!
+//! Signs with asymmetric KMS keys through an injected [`KmsClient`], so this
+//! crate does not depend on the AWS SDK. Wrap `aws_sdk_kms::Client` (or any
+//! compatible service) in a `KmsClient` impl and hand it to
+//! [`AwsKmsBackend::new`]. Key labels map to KMS aliases (`alias/<key_id>`).
+
+use super::async_backend::AsyncHsmBackend;
+use crate::error::{B4aeError, B4aeResult};
+use async_trait::async_trait;
+
+/// Default KMS signing algorithm (ECC_NIST_P256 keys)
+pub const DEFAULT_SIGNING_ALGORITHM: &str = "ECDSA_SHA_256";
+
+/// Minimal async KMS client surface used by [`AwsKmsBackend`]
+///
+/// Errors should already be `B4aeError`s carrying the service's message.
+#[async_trait]
+pub trait KmsClient: Send + Sync {
+    /// Create an asymmetric signing key under `alias`; returns its DER public key
+    async fn create_signing_key(&self, alias: &str) -> B4aeResult<Vec<u8>>;
+
+    /// `Sign` with a raw message (`MessageType=RAW`)
+    async fn sign(&self, key_id: &str, message: &[u8], algorithm: &str) -> B4aeResult<Vec<u8>>;
+
+    /// `Verify`; `Ok(false)` for an invalid signature
+    async fn verify(&self, key_id: &str, message: &[u8], signature: &[u8], algorithm: &str) -> B4aeResult<bool>;
+
+    /// `DescribeKey`-style reachability probe
+    async fn is_reachable(&self) -> bool;
+}
+
+/// [`AsyncHsmBackend`] over AWS KMS
+pub struct AwsKmsBackend<C: KmsClient> {
+    client: C,
+    signing_algorithm: String,
+}
+
+impl<C: KmsClient> AwsKmsBackend<C> {
+    /// Backend using [`DEFAULT_SIGNING_ALGORITHM`]
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            signing_algorithm: DEFAULT_SIGNING_ALGORITHM.to_string(),
+        }
+    }
+
+    /// Use another KMS signing algorithm (must match the key spec)
+    pub fn with_signing_algorithm(mut self, algorithm: impl Into<String>) -> Self {
+        self.signing_algorithm = algorithm.into();
+        self
+    }
+
+    fn alias(key_id: &str) -> B4aeResult<String> {
+        if key_id.is_empty() || !key_id.bytes().all(|b| b.is_ascii_alphanumeric() || b"/_-".contains(&b)) {
+            return Err(B4aeError::InvalidInput(format!("Invalid KMS key label: {:?}", key_id)));
+        }
+        Ok(format!("alias/{}", key_id))
+    }
+}
+
+#[async_trait]
+impl<C: KmsClient> AsyncHsmBackend for AwsKmsBackend<C> {
+    async fn generate_keypair(&self, key_id: &str) -> B4aeResult<Vec<u8>> {
+        self.client.create_signing_key(&Self::alias(key_id)?).await
+    }
+
+    async fn sign(&self, key_id: &str, data: &[u8]) -> B4aeResult<Vec<u8>> {
+        self.client.sign(&Self::alias(key_id)?, data, &self.signing_algorithm).await
+    }
+
+    async fn verify(&self, key_id: &str, data: &[u8], signature: &[u8]) -> B4aeResult<bool> {
+        self.client
+            .verify(&Self::alias(key_id)?, data, signature, &self.signing_algorithm)
+            .await
+    }
+
+    async fn is_available(&self) -> bool {
+        self.client.is_reachable().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingClient {
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl KmsClient for RecordingClient {
+        async fn create_signing_key(&self, alias: &str) -> B4aeResult<Vec<u8>> {
+            self.calls.lock().unwrap().push(format!("create {}", alias));
+            Ok(vec![0x30])
+        }
+
+        async fn sign(&self, key_id: &str, message: &[u8], algorithm: &str) -> B4aeResult<Vec<u8>> {
+            self.calls.lock().unwrap().push(format!("sign {} {}", key_id, algorithm));
+            Ok(message.iter().rev().copied().collect())
+        }
+
+        async fn verify(&self, _key_id: &str, message: &[u8], signature: &[u8], _algorithm: &str) -> B4aeResult<bool> {
+            Ok(message.iter().rev().copied().eq(signature.iter().copied()))
+        }
+
+        async fn is_reachable(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kms_backend_maps_labels_to_aliases() {
+        let kms = AwsKmsBackend::new(RecordingClient::default());
+        kms.generate_keypair("b4ae/identity").await.unwrap();
+        let signature = kms.sign("b4ae/identity", b"abc").await.unwrap();
+        assert!(kms.verify("b4ae/identity", b"abc", &signature).await.unwrap());
+        assert!(kms.sign("bad label", b"abc").await.is_err());
+
+        let calls = kms.client.calls.lock().unwrap().clone();
+        assert_eq!(calls, vec!["create alias/b4ae/identity", "sign alias/b4ae/identity ECDSA_SHA_256"]);
+    }
+}
@@ -0,0 +1,338 @@
+//! Windows CNG HSM backend
+//!
+//! Keys are ECDSA P-256 keys persisted in the per-user Microsoft Software Key
+//! Storage Provider, named by the `key_id` of [`HsmBackend`]. The private
+//! half never leaves the provider; only signatures and the public point are
+//! returned. Keys on a TPM or smart card provider work the same way, but
+//! this backend only opens the software provider.
+//!
+//! NCrypt reports failures as `SECURITY_STATUS` (`NTE_*`) codes;
+//! [`cng_error`] maps them to [`B4aeError`] and builds on every platform.
+//! [`CngHsm`] itself is Windows-only and uses the `windows` crate bindings.
+
+#[cfg(windows)]
+use super::HsmBackend;
+use crate::error::{B4aeError, B4aeResult};
+
+/// Maximum key name length in characters
+pub const MAX_KEY_LABEL_LEN: usize = 64;
+
+/// Signature failed to verify (`NTE_BAD_SIGNATURE`)
+pub const NTE_BAD_SIGNATURE: i32 = 0x8009_0006_u32 as i32;
+/// Malformed input (`NTE_BAD_DATA`)
+pub const NTE_BAD_DATA: i32 = 0x8009_0005_u32 as i32;
+/// Key container not found (`NTE_BAD_KEYSET`)
+pub const NTE_BAD_KEYSET: i32 = 0x8009_0016_u32 as i32;
+/// No memory (`NTE_NO_MEMORY`)
+pub const NTE_NO_MEMORY: i32 = 0x8009_000E_u32 as i32;
+/// Key name already in use (`NTE_EXISTS`)
+pub const NTE_EXISTS: i32 = 0x8009_000F_u32 as i32;
+/// Access denied (`NTE_PERM`)
+pub const NTE_PERM: i32 = 0x8009_0010_u32 as i32;
+/// Object not found (`NTE_NOT_FOUND`)
+pub const NTE_NOT_FOUND: i32 = 0x8009_0011_u32 as i32;
+/// Invalid parameter (`NTE_INVALID_PARAMETER`)
+pub const NTE_INVALID_PARAMETER: i32 = 0x8009_0027_u32 as i32;
+/// Output buffer too small (`NTE_BUFFER_TOO_SMALL`)
+pub const NTE_BUFFER_TOO_SMALL: i32 = 0x8009_0028_u32 as i32;
+/// Algorithm or operation not supported (`NTE_NOT_SUPPORTED`)
+pub const NTE_NOT_SUPPORTED: i32 = 0x8009_0029_u32 as i32;
+
+/// Map a failed CNG status to `B4aeError`, naming the failed operation
+///
+/// Bad signatures become `AuthenticationFailed`, bad arguments
+/// `InvalidInput`, out-of-memory `InternalError`; everything else is a
+/// `ProtocolError` carrying the hex status.
+pub fn cng_error(context: &str, status: i32) -> B4aeError {
+    let detail = match status {
+        NTE_BAD_SIGNATURE => return B4aeError::AuthenticationFailed,
+        NTE_BAD_DATA | NTE_INVALID_PARAMETER | NTE_BUFFER_TOO_SMALL => {
+            return B4aeError::InvalidInput(format!("CNG {}: invalid argument (0x{:08X})", context, status))
+        }
+        NTE_NO_MEMORY => {
+            return B4aeError::InternalError(format!("CNG {}: out of memory (0x{:08X})", context, status))
+        }
+        NTE_BAD_KEYSET | NTE_NOT_FOUND => "key not found",
+        NTE_EXISTS => "key label already in use",
+        NTE_PERM => "access denied",
+        NTE_NOT_SUPPORTED => "not supported by the key storage provider",
+        _ => "failed",
+    };
+    B4aeError::ProtocolError(format!("CNG {}: {} (0x{:08X})", context, detail, status))
+}
+
+/// Whether a verify status means "signature does not match" rather than a provider error
+pub fn is_bad_signature(status: i32) -> bool {
+    status == NTE_BAD_SIGNATURE
+}
+
+/// `BCRYPT_ECDSA_PUBLIC_P256_MAGIC` ("ECS1")
+#[cfg_attr(not(windows), allow(dead_code))]
+const ECDSA_PUBLIC_P256_MAGIC: u32 = 0x3153_4345;
+
+/// Convert a `BCRYPT_ECCPUBLIC_BLOB` (`magic || cbKey || X || Y`, little
+/// endian header) into an uncompressed SEC1 point (`0x04 || X || Y`)
+#[cfg_attr(not(windows), allow(dead_code))]
+fn decode_ecc_public_blob(blob: &[u8]) -> B4aeResult<Vec<u8>> {
+    let malformed = || B4aeError::ProtocolError(format!("Unexpected CNG public key blob ({} bytes)", blob.len()));
+    let (header, coordinates) = blob.split_at_checked(8).ok_or_else(malformed)?;
+    let magic = u32::from_le_bytes(header[..4].try_into().expect("4-byte slice"));
+    let key_len = u32::from_le_bytes(header[4..].try_into().expect("4-byte slice"));
+    if magic != ECDSA_PUBLIC_P256_MAGIC || key_len != 32 || coordinates.len() != 64 {
+        return Err(malformed());
+    }
+    let mut point = Vec::with_capacity(65);
+    point.push(0x04);
+    point.extend_from_slice(coordinates);
+    Ok(point)
+}
+
+/// Key names must be 1..=[`MAX_KEY_LABEL_LEN`] printable ASCII characters
+#[cfg_attr(not(windows), allow(dead_code))]
+fn validate_label(key_id: &str) -> B4aeResult<()> {
+    if key_id.is_empty() || key_id.len() > MAX_KEY_LABEL_LEN {
+        return Err(B4aeError::InvalidInput(format!(
+            "HSM key label must be 1..={} bytes, got {}",
+            MAX_KEY_LABEL_LEN,
+            key_id.len()
+        )));
+    }
+    if !key_id.bytes().all(|b| b.is_ascii_graphic() || b == b' ') {
+        return Err(B4aeError::InvalidInput(
+            "HSM key label must be printable ASCII".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub use windows_impl::CngHsm;
+
+#[cfg(windows)]
+mod windows_impl {
+    use super::*;
+    use sha2::{Digest, Sha256};
+    use windows::core::PCWSTR;
+    use windows::Win32::Security::Cryptography::{
+        NCryptCreatePersistedKey, NCryptDeleteKey, NCryptExportKey, NCryptFinalizeKey, NCryptFreeObject,
+        NCryptOpenKey, NCryptOpenStorageProvider, NCryptSignHash, NCryptVerifySignature, BCRYPT_ECCPUBLIC_BLOB,
+        CERT_KEY_SPEC, MS_KEY_STORAGE_PROVIDER, NCRYPT_ECDSA_P256_ALGORITHM, NCRYPT_FLAGS, NCRYPT_KEY_HANDLE,
+        NCRYPT_PROV_HANDLE, NCRYPT_SILENT_FLAG,
+    };
+
+    /// CNG HSM backend over the per-user software key storage provider
+    pub struct CngHsm {
+        provider: NCRYPT_PROV_HANDLE,
+    }
+
+    /// Owned key handle, freed on drop
+    struct KeyHandle(NCRYPT_KEY_HANDLE);
+
+    impl Drop for KeyHandle {
+        fn drop(&mut self) {
+            // SAFETY: the handle came from NCryptOpenKey/NCryptCreatePersistedKey and is freed once
+            let _ = unsafe { NCryptFreeObject(self.0.into()) };
+        }
+    }
+
+    fn check(context: &str, result: windows::core::Result<()>) -> B4aeResult<()> {
+        result.map_err(|e| cng_error(context, e.code().0))
+    }
+
+    /// NUL-terminated UTF-16 key name
+    fn wide(key_id: &str) -> Vec<u16> {
+        key_id.encode_utf16().chain(Some(0)).collect()
+    }
+
+    impl CngHsm {
+        /// Open the Microsoft Software Key Storage Provider
+        pub fn new() -> B4aeResult<Self> {
+            let mut provider = NCRYPT_PROV_HANDLE::default();
+            // SAFETY: out-pointer to a local; the provider name is a static wide string
+            let result = unsafe { NCryptOpenStorageProvider(&mut provider, MS_KEY_STORAGE_PROVIDER, 0) };
+            check("open key storage provider", result)?;
+            Ok(Self { provider })
+        }
+
+        /// Delete the key named `key_id` from the provider
+        pub fn delete_key(&self, key_id: &str) -> B4aeResult<()> {
+            let key = self.open_key(key_id)?;
+            // SAFETY: NCryptDeleteKey frees the handle, so it must not be freed again
+            let result = unsafe { NCryptDeleteKey(key.0, 0) };
+            if result.is_ok() {
+                std::mem::forget(key);
+            }
+            check("delete key", result)
+        }
+
+        fn open_key(&self, key_id: &str) -> B4aeResult<KeyHandle> {
+            validate_label(key_id)?;
+            let name = wide(key_id);
+            let mut key = NCRYPT_KEY_HANDLE::default();
+            // SAFETY: `name` is NUL-terminated and outlives the call
+            let result = unsafe {
+                NCryptOpenKey(self.provider, &mut key, PCWSTR(name.as_ptr()), CERT_KEY_SPEC(0), NCRYPT_SILENT_FLAG)
+            };
+            check("open key", result)?;
+            Ok(KeyHandle(key))
+        }
+
+        fn export_public_point(key: &KeyHandle) -> B4aeResult<Vec<u8>> {
+            let mut len = 0u32;
+            // SAFETY: size query without an output buffer
+            let result = unsafe {
+                NCryptExportKey(key.0, None, BCRYPT_ECCPUBLIC_BLOB, None, None, &mut len, NCRYPT_FLAGS(0))
+            };
+            check("export public key", result)?;
+            let mut blob = vec![0u8; len as usize];
+            // SAFETY: `blob` is a writable slice of the queried length
+            let result = unsafe {
+                NCryptExportKey(key.0, None, BCRYPT_ECCPUBLIC_BLOB, None, Some(&mut blob), &mut len, NCRYPT_FLAGS(0))
+            };
+            check("export public key", result)?;
+            blob.truncate(len as usize);
+            decode_ecc_public_blob(&blob)
+        }
+    }
+
+    impl Drop for CngHsm {
+        fn drop(&mut self) {
+            // SAFETY: the provider handle came from NCryptOpenStorageProvider and is freed once
+            let _ = unsafe { NCryptFreeObject(self.provider.into()) };
+        }
+    }
+
+    impl HsmBackend for CngHsm {
+        /// Creates a persisted P-256 key named `key_id` and returns its
+        /// 65-byte uncompressed public point
+        fn generate_keypair(&self, key_id: &str) -> B4aeResult<Vec<u8>> {
+            validate_label(key_id)?;
+            let name = wide(key_id);
+            let mut handle = NCRYPT_KEY_HANDLE::default();
+            // SAFETY: `name` is NUL-terminated and outlives the call; flags
+            // omit NCRYPT_OVERWRITE_KEY_FLAG so an existing key is kept
+            let result = unsafe {
+                NCryptCreatePersistedKey(
+                    self.provider,
+                    &mut handle,
+                    NCRYPT_ECDSA_P256_ALGORITHM,
+                    PCWSTR(name.as_ptr()),
+                    CERT_KEY_SPEC(0),
+                    NCRYPT_FLAGS(0),
+                )
+            };
+            check("create key", result)?;
+            let key = KeyHandle(handle);
+
+            // SAFETY: `key` is a valid, not yet finalized key handle
+            if let Err(e) = unsafe { NCryptFinalizeKey(key.0, NCRYPT_SILENT_FLAG) } {
+                // SAFETY: deleting frees the handle
+                let _ = unsafe { NCryptDeleteKey(key.0, 0) };
+                std::mem::forget(key);
+                return Err(cng_error("finalize key", e.code().0));
+            }
+            Self::export_public_point(&key)
+        }
+
+        /// Signs SHA-256(data) with ECDSA; the signature is raw `r || s` (64 bytes)
+        fn sign(&self, key_id: &str, data: &[u8]) -> B4aeResult<Vec<u8>> {
+            let key = self.open_key(key_id)?;
+            let digest = Sha256::digest(data);
+            let mut len = 0u32;
+            // SAFETY: size query without an output buffer
+            let result = unsafe { NCryptSignHash(key.0, None, &digest, None, &mut len, NCRYPT_SILENT_FLAG) };
+            check("sign", result)?;
+            let mut signature = vec![0u8; len as usize];
+            // SAFETY: `signature` is a writable slice of the queried length
+            let result =
+                unsafe { NCryptSignHash(key.0, None, &digest, Some(&mut signature), &mut len, NCRYPT_SILENT_FLAG) };
+            check("sign", result)?;
+            signature.truncate(len as usize);
+            Ok(signature)
+        }
+
+        fn verify(&self, key_id: &str, data: &[u8], signature: &[u8]) -> B4aeResult<bool> {
+            let key = self.open_key(key_id)?;
+            let digest = Sha256::digest(data);
+            // SAFETY: `digest` and `signature` are borrowed slices valid for the call
+            match unsafe { NCryptVerifySignature(key.0, None, &digest, signature, NCRYPT_SILENT_FLAG) } {
+                Ok(()) => Ok(true),
+                Err(e) if is_bad_signature(e.code().0) => Ok(false),
+                Err(e) => Err(cng_error("verify", e.code().0)),
+            }
+        }
+
+        /// True when the key storage provider is open
+        fn is_available(&self) -> bool {
+            !self.provider.is_invalid()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_mapping() {
+        assert!(matches!(cng_error("sign", NTE_BAD_SIGNATURE), B4aeError::AuthenticationFailed));
+        assert!(matches!(cng_error("sign", NTE_INVALID_PARAMETER), B4aeError::InvalidInput(_)));
+        assert!(matches!(cng_error("sign", NTE_NO_MEMORY), B4aeError::InternalError(_)));
+
+        match cng_error("open key", NTE_BAD_KEYSET) {
+            B4aeError::ProtocolError(msg) => assert_eq!(msg, "CNG open key: key not found (0x80090016)"),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(cng_error("create key", NTE_EXISTS).to_string().contains("already in use"));
+        assert!(cng_error("open key", NTE_PERM).to_string().contains("access denied"));
+        assert!(cng_error("sign", 0x8009_0030_u32 as i32).to_string().contains("failed (0x80090030)"));
+
+        assert!(is_bad_signature(NTE_BAD_SIGNATURE));
+        assert!(!is_bad_signature(NTE_BAD_KEYSET));
+    }
+
+    #[test]
+    fn test_decode_ecc_public_blob() {
+        let mut blob = ECDSA_PUBLIC_P256_MAGIC.to_le_bytes().to_vec();
+        blob.extend_from_slice(&32u32.to_le_bytes());
+        blob.extend_from_slice(&[0xab; 64]);
+
+        let point = decode_ecc_public_blob(&blob).unwrap();
+        assert_eq!(point.len(), 65);
+        assert_eq!(point[0], 0x04);
+        assert_eq!(&point[1..], &[0xab; 64][..]);
+
+        assert!(decode_ecc_public_blob(&blob[..blob.len() - 1]).is_err());
+        assert!(decode_ecc_public_blob(&blob[..4]).is_err());
+        blob[0] ^= 1;
+        assert!(decode_ecc_public_blob(&blob).is_err());
+    }
+
+    #[test]
+    fn test_label_validation() {
+        assert!(validate_label("b4ae-identity-1").is_ok());
+        assert!(validate_label("").is_err());
+        assert!(validate_label(&"k".repeat(MAX_KEY_LABEL_LEN + 1)).is_err());
+        assert!(validate_label("kunci-ñ").is_err());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_cng_sign_verify() {
+        let hsm = CngHsm::new().unwrap();
+        assert!(hsm.is_available());
+
+        let label = format!("b4ae-test-{}", std::process::id());
+        let public = hsm.generate_keypair(&label).unwrap();
+        assert_eq!(public.len(), 65);
+        assert!(hsm.generate_keypair(&label).is_err());
+
+        let signature = hsm.sign(&label, b"cng-backed message").unwrap();
+        assert_eq!(signature.len(), 64);
+        assert!(hsm.verify(&label, b"cng-backed message", &signature).unwrap());
+        assert!(!hsm.verify(&label, b"tampered message", &signature).unwrap());
+
+        hsm.delete_key(&label).unwrap();
+        assert!(hsm.sign(&label, b"data").is_err());
+    }
+}
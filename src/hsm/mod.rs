@@ -62,12 +62,27 @@ impl HsmBackend for NoOpHsm {
     }
 }
 
+/// File-backed software HSM for development and testing
+pub mod software;
+
+/// Async HSM trait and sync/async adapters
+#[cfg(feature = "async")]
+pub mod async_backend;
+
+/// AWS KMS backend skeleton (client injected by the caller)
+#[cfg(feature = "hsm-aws-kms")]
+pub mod aws_kms;
+
 #[cfg(feature = "hsm-pkcs11")]
 pub mod pkcs11;
 
 #[cfg(feature = "hsm-pkcs11")]
 pub mod pkcs11_enhanced;
 
+/// Windows CNG backend (per-user key storage provider)
+#[cfg(feature = "hsm-cng")]
+pub mod cng;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3,28 +3,97 @@
 //! Menggunakan cryptoki untuk komunikasi dengan HSM via PKCS#11.
 //! Requires: SoftHSM2, Nitrokey, atau PKCS#11 library lain.
 //!
+//! Keys are ECDSA P-256 keypairs addressed by `CKA_LABEL` (the `key_id` of
+//! [`HsmBackend`]). The private half is generated on the token as sensitive
+//! and non-extractable; only signatures and the public point leave it.
+//!
 //! Setup SoftHSM2 (Linux):
 //!   sudo apt install libsofthsm2
 //!   mkdir -p /tmp/tokens
 //!   echo "directories.tokendir = /tmp/tokens" > /tmp/softhsm2.conf
 //!   export SOFTHSM2_CONF=/tmp/softhsm2.conf
+//!   softhsm2-util --init-token --free --label b4ae --pin 1234 --so-pin 5678
+//!
+//! The token tests run only when `B4AE_PKCS11_MODULE` (module path),
+//! `B4AE_PKCS11_SLOT` and `B4AE_PKCS11_PIN` are set.
 
 use super::HsmBackend;
 use crate::error::{B4aeError, B4aeResult};
 use cryptoki::context::{CInitializeArgs, CInitializeFlags};
+use cryptoki::error::{Error as Pkcs11Error, RvError};
 use cryptoki::mechanism::Mechanism;
-use cryptoki::object::{Attribute, ObjectClass};
-use cryptoki::session::UserType;
+use cryptoki::object::{Attribute, AttributeType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::slot::Slot;
 use cryptoki::types::AuthPin;
-use std::path::Path;
-use std::sync::{Arc, RwLock};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use zeroize::Zeroizing;
+
+/// Maximum key label length in bytes
+pub const MAX_KEY_LABEL_LEN: usize = 64;
+
+/// DER-encoded OID of the P-256 curve (secp256r1), used as `CKA_EC_PARAMS`
+const P256_EC_PARAMS: [u8; 10] = [0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// Uncompressed SEC1 P-256 point length (`0x04 || x || y`)
+const P256_POINT_LEN: usize = 65;
+
+/// Connection settings for [`Pkcs11Hsm`]
+#[derive(Clone)]
+pub struct Pkcs11Config {
+    /// Path to the PKCS#11 module (e.g. `/usr/lib/softhsm/libsofthsm2.so`)
+    pub library_path: PathBuf,
+    /// Slot holding the token
+    pub slot_id: u64,
+    /// User PIN (None = tidak login, untuk token yang tidak perlu auth)
+    pub pin: Option<Zeroizing<String>>,
+}
+
+impl Pkcs11Config {
+    /// Config for slot 0 without login
+    pub fn new(library_path: impl Into<PathBuf>) -> Self {
+        Self {
+            library_path: library_path.into(),
+            slot_id: 0,
+            pin: None,
+        }
+    }
+
+    /// Use the given slot
+    pub fn with_slot(mut self, slot_id: u64) -> Self {
+        self.slot_id = slot_id;
+        self
+    }
+
+    /// Log in as user with the given PIN
+    pub fn with_pin(mut self, pin: impl Into<String>) -> Self {
+        self.pin = Some(Zeroizing::new(pin.into()));
+        self
+    }
+}
+
+impl fmt::Debug for Pkcs11Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pkcs11Config")
+            .field("library_path", &self.library_path)
+            .field("slot_id", &self.slot_id)
+            .field("pin", &self.pin.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
 
 /// PKCS#11 HSM backend
 #[cfg(feature = "hsm-pkcs11")]
 pub struct Pkcs11Hsm {
     pkcs11: Arc<RwLock<Option<cryptoki::context::Pkcs11>>>,
-    slot_id: cryptoki::slot::Slot,
+    slot_id: Slot,
     pin: Option<AuthPin>,
+    /// Sessions currently relying on the user login. Login state is shared
+    /// by all sessions of the application, so only the first logs in and
+    /// only the last logs out.
+    logins: Mutex<usize>,
 }
 
 #[cfg(feature = "hsm-pkcs11")]
@@ -34,25 +103,36 @@ impl Pkcs11Hsm {
     /// `pin`: user PIN untuk login (None = tidak login, untuk token yang tidak perlu auth).
     pub fn new(
         p11_library_path: impl AsRef<Path>,
-        slot_id: cryptoki::slot::Slot,
+        slot_id: Slot,
         pin: Option<impl Into<String>>,
     ) -> B4aeResult<Self> {
-        let pkcs11 = cryptoki::context::Pkcs11::new(p11_library_path)
-            .map_err(|e| B4aeError::ProtocolError(format!("PKCS#11 init: {}", e)))?;
+        let pkcs11 = cryptoki::context::Pkcs11::new(p11_library_path).map_err(|e| pkcs11_error("load module", e))?;
         pkcs11
             .initialize(CInitializeArgs::new(CInitializeFlags::OS_LOCKING_OK))
-            .map_err(|e| B4aeError::ProtocolError(format!("PKCS#11 initialize: {}", e)))?;
+            .map_err(|e| pkcs11_error("initialize", e))?;
         let pin = pin.map(|p| AuthPin::new(Box::from(p.into().as_str())));
         Ok(Self {
             pkcs11: Arc::new(RwLock::new(Some(pkcs11))),
             slot_id,
             pin,
+            logins: Mutex::new(0),
         })
     }
 
+    /// Buat HSM backend dari [`Pkcs11Config`]
+    pub fn from_config(config: &Pkcs11Config) -> B4aeResult<Self> {
+        let slot_id = Slot::try_from(config.slot_id).map_err(|e| pkcs11_error("slot id", e))?;
+        Self::new(&config.library_path, slot_id, config.pin.as_ref().map(|p| p.as_str()))
+    }
+
+    /// Slot this backend talks to
+    pub fn slot(&self) -> Slot {
+        self.slot_id
+    }
+
     fn with_session<T, F>(&self, f: F) -> B4aeResult<T>
     where
-        F: FnOnce(&cryptoki::session::Session) -> B4aeResult<T>,
+        F: FnOnce(&Session) -> B4aeResult<T>,
     {
         let guard = self.pkcs11.read().map_err(|e| {
             B4aeError::ProtocolError(format!("Lock error: {}", e))
@@ -62,46 +142,78 @@ impl Pkcs11Hsm {
         })?;
         let session = pkcs11
             .open_rw_session(self.slot_id)
-            .map_err(|e| B4aeError::ProtocolError(format!("Open session: {}", e)))?;
+            .map_err(|e| pkcs11_error("open session", e))?;
         if let Some(ref pin) = self.pin {
-            session
-                .login(UserType::User, Some(pin))
-                .map_err(|e| B4aeError::ProtocolError(format!("Login: {}", e)))?;
+            let mut logins = self.lock_logins()?;
+            if *logins == 0 {
+                match session.login(UserType::User, Some(pin)) {
+                    // Logged in outside this backend
+                    Ok(()) | Err(Pkcs11Error::Pkcs11(RvError::UserAlreadyLoggedIn, _)) => {}
+                    Err(e) => return Err(pkcs11_error("login", e)),
+                }
+            }
+            *logins += 1;
         }
         let result = f(&session);
-        let _ = session.logout();
+        if self.pin.is_some() {
+            let mut logins = self.lock_logins()?;
+            *logins -= 1;
+            if *logins == 0 {
+                let _ = session.logout();
+            }
+        }
         result
     }
+
+    fn lock_logins(&self) -> B4aeResult<std::sync::MutexGuard<'_, usize>> {
+        self.logins
+            .lock()
+            .map_err(|e| B4aeError::ProtocolError(format!("Lock error: {}", e)))
+    }
 }
 
 #[cfg(feature = "hsm-pkcs11")]
 impl HsmBackend for Pkcs11Hsm {
+    /// Generates a P-256 keypair labelled `key_id` and returns its 65-byte
+    /// uncompressed public point
     fn generate_keypair(&self, key_id: &str) -> B4aeResult<Vec<u8>> {
+        validate_label(key_id)?;
         self.with_session(|session| {
-            // EC P-256 keypair for signing
+            // Duplicate labels would make later lookups ambiguous
+            let existing = session
+                .find_objects(&key_template(ObjectClass::PRIVATE_KEY, key_id))
+                .map_err(|e| pkcs11_error("find key", e))?;
+            if !existing.is_empty() {
+                return Err(B4aeError::ProtocolError(format!("Key label already in use: {}", key_id)));
+            }
+
+            let label = key_id.as_bytes().to_vec();
             let pub_template = vec![
                 Attribute::Token(true),
                 Attribute::Private(false),
-                Attribute::Label(key_id.as_bytes().to_vec()),
+                Attribute::Verify(true),
+                Attribute::EcParams(P256_EC_PARAMS.to_vec()),
+                Attribute::Label(label.clone()),
+                Attribute::Id(label.clone()),
             ];
             let priv_template = vec![
                 Attribute::Token(true),
                 Attribute::Private(true),
-                Attribute::Label(key_id.as_bytes().to_vec()),
+                Attribute::Sensitive(true),
+                Attribute::Extractable(false),
+                Attribute::Sign(true),
+                Attribute::Label(label.clone()),
+                Attribute::Id(label),
             ];
             let (pub_handle, _priv_handle) = session
-                .generate_key_pair(
-                    &Mechanism::EccKeyPairGen,
-                    &pub_template,
-                    &priv_template,
-                )
-                .map_err(|e| B4aeError::ProtocolError(format!("Generate keypair: {}", e)))?;
+                .generate_key_pair(&Mechanism::EccKeyPairGen, &pub_template, &priv_template)
+                .map_err(|e| pkcs11_error("generate keypair", e))?;
             let attrs = session
-                .get_attributes(pub_handle, &[cryptoki::object::AttributeType::EcPoint])
-                .map_err(|e| B4aeError::ProtocolError(format!("Get EC point: {}", e)))?;
+                .get_attributes(pub_handle, &[AttributeType::EcPoint])
+                .map_err(|e| pkcs11_error("get EC point", e))?;
             for attr in attrs {
                 if let Attribute::EcPoint(v) = attr {
-                    return Ok(v);
+                    return decode_ec_point(&v);
                 }
             }
             Err(B4aeError::ProtocolError(
@@ -110,48 +222,263 @@ impl HsmBackend for Pkcs11Hsm {
         })
     }
 
+    /// Signs with ECDSA-SHA256; the signature is raw `r || s` (64 bytes)
     fn sign(&self, key_id: &str, data: &[u8]) -> B4aeResult<Vec<u8>> {
+        validate_label(key_id)?;
         self.with_session(|session| {
-            let template = vec![
-                Attribute::Class(ObjectClass::PRIVATE_KEY),
-                Attribute::Label(key_id.as_bytes().to_vec()),
-            ];
-            let handles = session
-                .find_objects(&template)
-                .map_err(|e| B4aeError::ProtocolError(format!("Find key: {}", e)))?;
-            let key_handle = handles.into_iter().next().ok_or_else(|| {
-                B4aeError::ProtocolError(format!("Key not found: {}", key_id))
-            })?;
+            let key_handle = find_key(session, ObjectClass::PRIVATE_KEY, key_id)?;
             session
                 .sign(&Mechanism::EcdsaSha256, key_handle, data)
-                .map_err(|e| B4aeError::ProtocolError(format!("Sign: {}", e)))
+                .map_err(|e| pkcs11_error("sign", e))
         })
     }
 
     fn verify(&self, key_id: &str, data: &[u8], signature: &[u8]) -> B4aeResult<bool> {
+        validate_label(key_id)?;
         self.with_session(|session| {
-            let template = vec![
-                Attribute::Class(ObjectClass::PUBLIC_KEY),
-                Attribute::Label(key_id.as_bytes().to_vec()),
-            ];
-            let handles = session
-                .find_objects(&template)
-                .map_err(|e| B4aeError::ProtocolError(format!("Find key: {}", e)))?;
-            let key_handle = handles.into_iter().next().ok_or_else(|| {
-                B4aeError::ProtocolError(format!("Key not found: {}", key_id))
-            })?;
+            let key_handle = find_key(session, ObjectClass::PUBLIC_KEY, key_id)?;
             match session.verify(&Mechanism::EcdsaSha256, key_handle, data, signature) {
                 Ok(()) => Ok(true),
-                Err(_) => Ok(false),
+                Err(e) if is_bad_signature(&e) => Ok(false),
+                Err(e) => Err(pkcs11_error("verify", e)),
             }
         })
     }
 
+    /// True when the module is loaded and the configured slot holds a token
     fn is_available(&self) -> bool {
-        self.pkcs11
+        let guard = self
+            .pkcs11
             .read()
-            .unwrap_or_else(std::sync::PoisonError::into_inner)
-            .as_ref()
-            .is_some()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match guard.as_ref() {
+            Some(pkcs11) => pkcs11
+                .get_slots_with_token()
+                .map(|slots| slots.contains(&self.slot_id))
+                .unwrap_or(false),
+            None => false,
+        }
+    }
+}
+
+/// Wrap a PKCS#11 failure as `ProtocolError`, naming the failed operation
+/// and hinting at the likely misconfiguration
+fn pkcs11_error(context: &str, err: Pkcs11Error) -> B4aeError {
+    let hint = match &err {
+        Pkcs11Error::LibraryLoading(_) => " (check the PKCS#11 module path)",
+        Pkcs11Error::Pkcs11(rv, _) => match rv {
+            RvError::PinIncorrect
+            | RvError::PinInvalid
+            | RvError::PinLenRange
+            | RvError::PinExpired
+            | RvError::PinLocked => " (check the configured PIN)",
+            RvError::SlotIdInvalid | RvError::TokenNotPresent | RvError::TokenNotRecognized => {
+                " (check the configured slot)"
+            }
+            RvError::UserNotLoggedIn => " (a PIN is required for this token)",
+            _ => "",
+        },
+        _ => "",
+    };
+    B4aeError::ProtocolError(format!("PKCS#11 {}: {}{}", context, err, hint))
+}
+
+/// Whether a verify failure means "signature does not match" rather than a token error
+fn is_bad_signature(err: &Pkcs11Error) -> bool {
+    matches!(
+        err,
+        Pkcs11Error::Pkcs11(RvError::SignatureInvalid | RvError::SignatureLenRange, _)
+    )
+}
+
+/// Key labels must be non-empty printable ASCII of at most [`MAX_KEY_LABEL_LEN`] bytes
+fn validate_label(key_id: &str) -> B4aeResult<()> {
+    if key_id.is_empty() || key_id.len() > MAX_KEY_LABEL_LEN {
+        return Err(B4aeError::InvalidInput(format!(
+            "HSM key label must be 1..={} bytes, got {}",
+            MAX_KEY_LABEL_LEN,
+            key_id.len()
+        )));
+    }
+    if !key_id.bytes().all(|b| b.is_ascii_graphic() || b == b' ') {
+        return Err(B4aeError::InvalidInput(
+            "HSM key label must be printable ASCII".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn key_template(class: ObjectClass, key_id: &str) -> Vec<Attribute> {
+    vec![
+        Attribute::Class(class),
+        Attribute::Label(key_id.as_bytes().to_vec()),
+    ]
+}
+
+/// Find exactly one key object of `class` labelled `key_id`
+fn find_key(session: &Session, class: ObjectClass, key_id: &str) -> B4aeResult<ObjectHandle> {
+    let handles = session
+        .find_objects(&key_template(class, key_id))
+        .map_err(|e| pkcs11_error("find key", e))?;
+    match handles.as_slice() {
+        [handle] => Ok(*handle),
+        [] => Err(B4aeError::ProtocolError(format!("Key not found: {}", key_id))),
+        _ => Err(B4aeError::ProtocolError(format!(
+            "Key label is ambiguous ({} objects): {}",
+            handles.len(),
+            key_id
+        ))),
+    }
+}
+
+/// Unwrap `CKA_EC_POINT` into an uncompressed SEC1 point
+///
+/// PKCS#11 v2.40 DER-wraps the point in an OCTET STRING; some tokens return
+/// the raw point instead, so both forms are accepted.
+fn decode_ec_point(value: &[u8]) -> B4aeResult<Vec<u8>> {
+    let point = match value {
+        [0x04, len, rest @ ..] if *len as usize == P256_POINT_LEN && rest.len() == P256_POINT_LEN => rest,
+        raw if raw.len() == P256_POINT_LEN => raw,
+        _ => {
+            return Err(B4aeError::ProtocolError(format!(
+                "Unexpected EC point encoding ({} bytes)",
+                value.len()
+            )))
+        }
+    };
+    if point[0] != 0x04 {
+        return Err(B4aeError::ProtocolError(
+            "EC point is not uncompressed".to_string(),
+        ));
+    }
+    Ok(point.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cryptoki::context::Function;
+
+    #[test]
+    fn test_error_mapping_adds_context_and_hint() {
+        let err = pkcs11_error("login", Pkcs11Error::Pkcs11(RvError::PinIncorrect, Function::Login));
+        match err {
+            B4aeError::ProtocolError(msg) => {
+                assert!(msg.starts_with("PKCS#11 login:"), "{}", msg);
+                assert!(msg.contains("check the configured PIN"), "{}", msg);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let err = pkcs11_error("open session", Pkcs11Error::Pkcs11(RvError::TokenNotPresent, Function::OpenSession));
+        assert!(err.to_string().contains("check the configured slot"));
+
+        let err = pkcs11_error("sign", Pkcs11Error::NotSupported);
+        assert!(matches!(err, B4aeError::ProtocolError(ref msg) if msg == "PKCS#11 sign: Feature not supported"));
+
+        assert!(is_bad_signature(&Pkcs11Error::Pkcs11(RvError::SignatureInvalid, Function::Verify)));
+        assert!(!is_bad_signature(&Pkcs11Error::Pkcs11(RvError::DeviceError, Function::Verify)));
+    }
+
+    #[test]
+    fn test_label_validation() {
+        assert!(validate_label("b4ae-identity-1").is_ok());
+        assert!(validate_label("device key").is_ok());
+        assert!(validate_label(&"k".repeat(MAX_KEY_LABEL_LEN)).is_ok());
+
+        assert!(matches!(validate_label(""), Err(B4aeError::InvalidInput(_))));
+        assert!(validate_label(&"k".repeat(MAX_KEY_LABEL_LEN + 1)).is_err());
+        assert!(validate_label("tab\there").is_err());
+        assert!(validate_label("kunci-ñ").is_err());
+    }
+
+    #[test]
+    fn test_decode_ec_point() {
+        let mut raw = vec![0x04];
+        raw.extend_from_slice(&[0xab; 64]);
+
+        let mut der = vec![0x04, 0x41];
+        der.extend_from_slice(&raw);
+
+        assert_eq!(decode_ec_point(&der).unwrap(), raw);
+        assert_eq!(decode_ec_point(&raw).unwrap(), raw);
+        assert!(decode_ec_point(&der[..der.len() - 1]).is_err());
+
+        let mut compressed = raw.clone();
+        compressed[0] = 0x02;
+        assert!(decode_ec_point(&compressed).is_err());
+    }
+
+    #[test]
+    fn test_config_debug_redacts_pin() {
+        let config = Pkcs11Config::new("/usr/lib/softhsm/libsofthsm2.so").with_slot(3).with_pin("1234");
+        assert_eq!(config.slot_id, 3);
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains("1234"));
+    }
+
+    #[test]
+    fn test_missing_module_reports_path_hint() {
+        let config = Pkcs11Config::new("/nonexistent/libb4ae-missing-pkcs11.so");
+        match Pkcs11Hsm::from_config(&config) {
+            Err(B4aeError::ProtocolError(msg)) => assert!(msg.contains("module path"), "{}", msg),
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("missing module must not load"),
+        }
+    }
+
+    /// Token config from the environment, or None to skip
+    fn token_config() -> Option<Pkcs11Config> {
+        let module = std::env::var("B4AE_PKCS11_MODULE").ok()?;
+        let slot = std::env::var("B4AE_PKCS11_SLOT").ok()?.parse().ok()?;
+        let pin = std::env::var("B4AE_PKCS11_PIN").ok()?;
+        Some(Pkcs11Config::new(module).with_slot(slot).with_pin(pin))
+    }
+
+    #[test]
+    fn test_token_sign_verify() {
+        let Some(config) = token_config() else {
+            return;
+        };
+        let hsm = Pkcs11Hsm::from_config(&config).unwrap();
+        assert!(hsm.is_available());
+
+        let label = format!("b4ae-test-{}", std::process::id());
+        let public = hsm.generate_keypair(&label).unwrap();
+        assert_eq!(public.len(), P256_POINT_LEN);
+        assert!(hsm.generate_keypair(&label).is_err());
+
+        let signature = hsm.sign(&label, b"hsm-backed message").unwrap();
+        assert!(hsm.verify(&label, b"hsm-backed message", &signature).unwrap());
+        assert!(!hsm.verify(&label, b"tampered message", &signature).unwrap());
+        assert!(hsm.sign("b4ae-no-such-key", b"data").is_err());
+    }
+
+    #[test]
+    fn test_token_concurrent_sessions_stay_logged_in() {
+        let Some(config) = token_config() else {
+            return;
+        };
+        let hsm = Arc::new(Pkcs11Hsm::from_config(&config).unwrap());
+        let label = format!("b4ae-test-concurrent-{}", std::process::id());
+        hsm.generate_keypair(&label).unwrap();
+
+        // One session logging out must not strip the login from the others
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let hsm = Arc::clone(&hsm);
+                let label = label.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..16 {
+                        hsm.sign(&label, b"concurrent message").unwrap();
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(*hsm.logins.lock().unwrap(), 0);
     }
 }
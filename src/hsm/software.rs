@@ -0,0 +1,453 @@
+//! File-backed software HSM
+//!
+//! Implements [`HsmBackend`] without hardware, for development and tests.
+//! Keypairs live in a JSON file; each secret key is sealed with AES-256-GCM
+//! under a key derived from a passphrase with the same Argon2id KDF as
+//! [`KeyStore`](crate::key_store::KeyStore).
+//!
+//! ```text
+//! file   = { version, kdf: { mem_kib, iterations, parallelism }, salt, verifier, keys: { label: entry } }
+//! entry  = { algorithm, public_key, nonce, sealed_secret }          (binary fields hex-encoded)
+//! aad    = "B4AE-softhsm-key" || len(label) || label || algorithm || public_key
+//! ```
+//!
+//! Secret keys are decrypted only for the duration of a `sign` call and are
+//! zeroized afterwards. This offers no protection against an attacker with
+//! access to the running process; use a real HSM in production.
+
+use super::HsmBackend;
+use crate::crypto::CryptoError;
+use crate::crypto::aes_gcm::{self, AesKey};
+use crate::crypto::dilithium::{self, DilithiumPublicKey, DilithiumSecretKey, DilithiumSignature};
+use crate::crypto::xeddsa::{XEdDSAKeyPair, XEdDSASignature};
+use crate::error::{B4aeError, B4aeResult};
+use crate::key_store::{Argon2Params, KeyStore};
+use crate::storage::temp_file::{create_temp_sibling, sync_parent_dir};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+/// File format version
+const FILE_VERSION: u32 = 1;
+
+/// Salt size for the passphrase KDF
+const SALT_SIZE: usize = 16;
+
+/// AAD label for the passphrase verifier
+const VERIFIER_AAD: &[u8] = b"B4AE-softhsm-verifier";
+
+/// AAD label for sealed secret keys
+const KEY_AAD_LABEL: &[u8] = b"B4AE-softhsm-key";
+
+/// Signature algorithm of a software HSM key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SoftHsmAlgorithm {
+    /// Dilithium5 / ML-DSA-87 (post-quantum)
+    #[default]
+    #[serde(rename = "dilithium5")]
+    Dilithium5,
+    /// XEdDSA over Curve25519; public key is the 32-byte Ed25519 verification key
+    #[serde(rename = "xeddsa")]
+    XEdDSA,
+}
+
+impl SoftHsmAlgorithm {
+    fn name(self) -> &'static str {
+        match self {
+            SoftHsmAlgorithm::Dilithium5 => "dilithium5",
+            SoftHsmAlgorithm::XEdDSA => "xeddsa",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct KdfRecord {
+    mem_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyRecord {
+    algorithm: SoftHsmAlgorithm,
+    public_key: String,
+    nonce: String,
+    sealed_secret: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HsmFile {
+    version: u32,
+    kdf: KdfRecord,
+    salt: String,
+    verifier: String,
+    keys: BTreeMap<String, KeyRecord>,
+}
+
+/// Software HSM storing passphrase-encrypted keypairs in a JSON file
+///
+/// ```rust,no_run
+/// use b4ae::hsm::HsmBackend;
+/// use b4ae::hsm::software::SoftwareHsm;
+///
+/// let hsm = SoftwareHsm::open("/var/lib/b4ae/dev-hsm.json", b"dev passphrase").unwrap();
+/// hsm.generate_keypair("signing-1").unwrap();
+/// let signature = hsm.sign("signing-1", b"message").unwrap();
+/// assert!(hsm.verify("signing-1", b"message", &signature).unwrap());
+/// ```
+pub struct SoftwareHsm {
+    path: PathBuf,
+    key: AesKey,
+    algorithm: SoftHsmAlgorithm,
+    state: Mutex<HsmFile>,
+}
+
+impl SoftwareHsm {
+    /// Open the key file at `path`, creating it with default Argon2id
+    /// parameters if it does not exist
+    pub fn open(path: impl AsRef<Path>, passphrase: &[u8]) -> B4aeResult<Self> {
+        Self::open_with_params(path, passphrase, Argon2Params::default())
+    }
+
+    /// Open the key file at `path`; `params` only apply when creating it
+    ///
+    /// Fails with `AuthenticationFailed` if the passphrase does not match an
+    /// existing file.
+    pub fn open_with_params(path: impl AsRef<Path>, passphrase: &[u8], params: Argon2Params) -> B4aeResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            Self::load(path, passphrase)
+        } else {
+            Self::create(path, passphrase, params)
+        }
+    }
+
+    /// Algorithm used by [`HsmBackend::generate_keypair`] (default Dilithium5)
+    pub fn with_algorithm(mut self, algorithm: SoftHsmAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Path of the backing key file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Labels of all stored keys, in order
+    pub fn labels(&self) -> B4aeResult<Vec<String>> {
+        Ok(self.lock()?.keys.keys().cloned().collect())
+    }
+
+    /// Public key bytes for `key_id`
+    pub fn public_key(&self, key_id: &str) -> B4aeResult<Vec<u8>> {
+        let state = self.lock()?;
+        let record = find_key(&state, key_id)?;
+        decode_hex(&record.public_key)
+    }
+
+    /// Generate a keypair with an explicit algorithm and return its public key
+    pub fn generate_keypair_with(&self, key_id: &str, algorithm: SoftHsmAlgorithm) -> B4aeResult<Vec<u8>> {
+        if key_id.is_empty() {
+            return Err(B4aeError::InvalidInput("HSM key label must not be empty".to_string()));
+        }
+        let mut state = self.lock()?;
+        if state.keys.contains_key(key_id) {
+            return Err(B4aeError::ProtocolError(format!("Key label already in use: {}", key_id)));
+        }
+
+        let (public_key, secret_key) = match algorithm {
+            SoftHsmAlgorithm::Dilithium5 => {
+                let keypair = dilithium::keypair()?;
+                (
+                    keypair.public_key.as_bytes().to_vec(),
+                    Zeroizing::new(keypair.secret_key.as_bytes().to_vec()),
+                )
+            }
+            SoftHsmAlgorithm::XEdDSA => {
+                let mut secret = Zeroizing::new([0u8; 32]);
+                crate::crypto::random::fill_random(secret.as_mut())?;
+                let keypair = XEdDSAKeyPair::from_secret_bytes(&secret)?;
+                (keypair.verification_key().to_vec(), Zeroizing::new(secret.to_vec()))
+            }
+        };
+
+        let aad = key_aad(key_id, algorithm, &public_key);
+        let (nonce, sealed) = aes_gcm::encrypt(&self.key, &secret_key, &aad)?;
+        state.keys.insert(
+            key_id.to_string(),
+            KeyRecord {
+                algorithm,
+                public_key: hex::encode(&public_key),
+                nonce: hex::encode(nonce),
+                sealed_secret: hex::encode(sealed),
+            },
+        );
+
+        if let Err(e) = write_file(&self.path, &state) {
+            state.keys.remove(key_id);
+            return Err(e);
+        }
+        Ok(public_key)
+    }
+
+    fn create(path: PathBuf, passphrase: &[u8], params: Argon2Params) -> B4aeResult<Self> {
+        let mut salt = [0u8; SALT_SIZE];
+        crate::crypto::random::fill_random(&mut salt)?;
+        let key = KeyStore::derive_key(passphrase, &salt, &params)?;
+
+        let (nonce, tag) = aes_gcm::encrypt(&key, &[], VERIFIER_AAD)?;
+        let mut verifier = nonce;
+        verifier.extend_from_slice(&tag);
+
+        let file = HsmFile {
+            version: FILE_VERSION,
+            kdf: KdfRecord {
+                mem_kib: params.mem_kib,
+                iterations: params.iterations,
+                parallelism: params.parallelism,
+            },
+            salt: hex::encode(salt),
+            verifier: hex::encode(verifier),
+            keys: BTreeMap::new(),
+        };
+        write_file(&path, &file)?;
+        Ok(Self {
+            path,
+            key,
+            algorithm: SoftHsmAlgorithm::default(),
+            state: Mutex::new(file),
+        })
+    }
+
+    fn load(path: PathBuf, passphrase: &[u8]) -> B4aeResult<Self> {
+        let json = fs::read(&path)
+            .map_err(|e| B4aeError::InternalError(format!("Read {}: {}", path.display(), e)))?;
+        let file: HsmFile = serde_json::from_slice(&json)
+            .map_err(|e| B4aeError::InvalidInput(format!("Malformed software HSM file: {}", e)))?;
+        if file.version != FILE_VERSION {
+            return Err(B4aeError::InvalidInput(format!(
+                "Unsupported software HSM file version {}",
+                file.version
+            )));
+        }
+
+        let params = Argon2Params::new(file.kdf.mem_kib, file.kdf.iterations, file.kdf.parallelism);
+        let key = KeyStore::derive_key(passphrase, &decode_hex(&file.salt)?, &params)?;
+        let verifier = decode_hex(&file.verifier)?;
+        if verifier.len() != aes_gcm::NONCE_SIZE + aes_gcm::TAG_SIZE {
+            return Err(B4aeError::InvalidInput("Malformed software HSM verifier".to_string()));
+        }
+        let (nonce, tag) = verifier.split_at(aes_gcm::NONCE_SIZE);
+        aes_gcm::decrypt(&key, nonce, tag, VERIFIER_AAD).map_err(|_| B4aeError::AuthenticationFailed)?;
+
+        Ok(Self {
+            path,
+            key,
+            algorithm: SoftHsmAlgorithm::default(),
+            state: Mutex::new(file),
+        })
+    }
+
+    fn lock(&self) -> B4aeResult<std::sync::MutexGuard<'_, HsmFile>> {
+        self.state
+            .lock()
+            .map_err(|e| B4aeError::InternalError(format!("Lock error: {}", e)))
+    }
+
+    /// Decrypt the secret key of `record` (zeroized on drop)
+    fn open_secret(&self, key_id: &str, record: &KeyRecord) -> B4aeResult<Zeroizing<Vec<u8>>> {
+        let public_key = decode_hex(&record.public_key)?;
+        let aad = key_aad(key_id, record.algorithm, &public_key);
+        let secret = aes_gcm::decrypt(
+            &self.key,
+            &decode_hex(&record.nonce)?,
+            &decode_hex(&record.sealed_secret)?,
+            &aad,
+        )
+        .map_err(|_| B4aeError::CryptoError(CryptoError::DecryptionFailed(format!("Sealed key failed authentication: {}", key_id))))?;
+        Ok(Zeroizing::new(secret))
+    }
+}
+
+impl HsmBackend for SoftwareHsm {
+    fn generate_keypair(&self, key_id: &str) -> B4aeResult<Vec<u8>> {
+        self.generate_keypair_with(key_id, self.algorithm)
+    }
+
+    fn sign(&self, key_id: &str, data: &[u8]) -> B4aeResult<Vec<u8>> {
+        let state = self.lock()?;
+        let record = find_key(&state, key_id)?;
+        let secret = self.open_secret(key_id, record)?;
+
+        match record.algorithm {
+            SoftHsmAlgorithm::Dilithium5 => {
+                let secret_key = DilithiumSecretKey::from_bytes(&secret)?;
+                Ok(dilithium::sign(&secret_key, data)?.as_bytes().to_vec())
+            }
+            SoftHsmAlgorithm::XEdDSA => {
+                let mut bytes = Zeroizing::new([0u8; 32]);
+                bytes.copy_from_slice(&secret);
+                let keypair = XEdDSAKeyPair::from_secret_bytes(&bytes)?;
+                let signature = keypair.sign(data)?;
+                let mut out = signature.r.to_vec();
+                out.extend_from_slice(&signature.s);
+                Ok(out)
+            }
+        }
+    }
+
+    fn verify(&self, key_id: &str, data: &[u8], signature: &[u8]) -> B4aeResult<bool> {
+        let state = self.lock()?;
+        let record = find_key(&state, key_id)?;
+        let public_key = decode_hex(&record.public_key)?;
+
+        match record.algorithm {
+            SoftHsmAlgorithm::Dilithium5 => {
+                let Ok(signature) = DilithiumSignature::from_bytes(signature) else {
+                    return Ok(false);
+                };
+                let public_key = DilithiumPublicKey::from_bytes(&public_key)?;
+                Ok(dilithium::verify(&public_key, data, &signature)?)
+            }
+            SoftHsmAlgorithm::XEdDSA => {
+                let verification_key = <[u8; 32]>::try_from(public_key.as_slice())
+                    .map_err(|_| B4aeError::InvalidInput("Malformed XEdDSA public key".to_string()))?;
+                if signature.len() != 64 {
+                    return Ok(false);
+                }
+                let mut sig = XEdDSASignature { r: [0u8; 32], s: [0u8; 32] };
+                sig.r.copy_from_slice(&signature[..32]);
+                sig.s.copy_from_slice(&signature[32..]);
+                Ok(XEdDSAKeyPair::verify(&verification_key, data, &sig)?)
+            }
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+}
+
+/// Shows the file path and key labels only; key material is never printed
+impl fmt::Debug for SoftwareHsm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels: Vec<String> = self.labels().unwrap_or_default();
+        f.debug_struct("SoftwareHsm")
+            .field("path", &self.path)
+            .field("algorithm", &self.algorithm)
+            .field("labels", &labels)
+            .finish_non_exhaustive()
+    }
+}
+
+fn find_key<'a>(state: &'a HsmFile, key_id: &str) -> B4aeResult<&'a KeyRecord> {
+    state
+        .keys
+        .get(key_id)
+        .ok_or_else(|| B4aeError::ProtocolError(format!("Key not found: {}", key_id)))
+}
+
+/// AAD binding a sealed secret to its label, algorithm and public key
+fn key_aad(key_id: &str, algorithm: SoftHsmAlgorithm, public_key: &[u8]) -> Vec<u8> {
+    let mut aad = KEY_AAD_LABEL.to_vec();
+    aad.extend_from_slice(&(key_id.len() as u32).to_be_bytes());
+    aad.extend_from_slice(key_id.as_bytes());
+    aad.extend_from_slice(algorithm.name().as_bytes());
+    aad.extend_from_slice(public_key);
+    aad
+}
+
+fn decode_hex(value: &str) -> B4aeResult<Vec<u8>> {
+    hex::decode(value).map_err(|e| B4aeError::InvalidInput(format!("Malformed software HSM file: {}", e)))
+}
+
+/// Write the key file via a fresh private temporary file and rename, so a
+/// crash never leaves a half-written file
+fn write_file(path: &Path, file: &HsmFile) -> B4aeResult<()> {
+    use std::io::Write;
+
+    let json = serde_json::to_vec_pretty(file)
+        .map_err(|e| B4aeError::InternalError(format!("Serialize software HSM file: {}", e)))?;
+    let (temp_path, mut temp) = create_temp_sibling(path)
+        .map_err(|e| B4aeError::InternalError(format!("Write {}: {}", path.display(), e)))?;
+
+    temp.write_all(&json)
+        .and_then(|()| temp.sync_all())
+        .and_then(|()| fs::rename(&temp_path, path))
+        .and_then(|()| sync_parent_dir(path))
+        .map_err(|e| {
+            let _ = fs::remove_file(&temp_path);
+            B4aeError::InternalError(format!("Write {}: {}", path.display(), e))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_params() -> Argon2Params {
+        Argon2Params::new(1024, 1, 1)
+    }
+
+    fn temp_hsm_path(name: &str) -> PathBuf {
+        crate::storage::temp_file::test_path("softhsm", name, "hsm.json")
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let path = temp_hsm_path("roundtrip");
+        let hsm = SoftwareHsm::open_with_params(&path, b"passphrase", test_params()).unwrap();
+        assert!(hsm.is_available());
+
+        let pq_public = hsm.generate_keypair("pq-key").unwrap();
+        let x_public = hsm.generate_keypair_with("x-key", SoftHsmAlgorithm::XEdDSA).unwrap();
+        assert_eq!(x_public.len(), 32);
+        assert_eq!(hsm.public_key("pq-key").unwrap(), pq_public);
+
+        for label in ["pq-key", "x-key"] {
+            let signature = hsm.sign(label, b"release manifest").unwrap();
+            assert!(hsm.verify(label, b"release manifest", &signature).unwrap());
+            assert!(!hsm.verify(label, b"tampered manifest", &signature).unwrap());
+            assert!(!hsm.verify(label, b"release manifest", &signature[1..]).unwrap());
+        }
+
+        // Keys persist and reload under the same passphrase
+        drop(hsm);
+        let reopened = SoftwareHsm::open(&path, b"passphrase").unwrap();
+        assert_eq!(reopened.labels().unwrap(), vec!["pq-key".to_string(), "x-key".to_string()]);
+        let signature = reopened.sign("x-key", b"after reload").unwrap();
+        assert!(reopened.verify("x-key", b"after reload", &signature).unwrap());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_label_and_wrong_passphrase() {
+        let path = temp_hsm_path("errors");
+        let hsm = SoftwareHsm::open_with_params(&path, b"passphrase", test_params())
+            .unwrap()
+            .with_algorithm(SoftHsmAlgorithm::XEdDSA);
+        hsm.generate_keypair("signing").unwrap();
+
+        assert!(matches!(hsm.sign("missing", b"data"), Err(B4aeError::ProtocolError(_))));
+        assert!(hsm.verify("missing", b"data", &[0u8; 64]).is_err());
+        assert!(hsm.generate_keypair("signing").is_err());
+
+        assert!(matches!(
+            SoftwareHsm::open(&path, b"wrong passphrase"),
+            Err(B4aeError::AuthenticationFailed)
+        ));
+
+        // Neither the file nor Debug output contains secret material
+        let debug = format!("{:?}", hsm);
+        assert!(debug.contains("signing") && !debug.contains("sealed"));
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(!json.contains("passphrase"));
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -12,6 +12,9 @@
 //! │   └── Storage Key (STK)      [encrypted storage]
 //! └── Backup Key Shards (BKS)     [N-of-M recovery]
 //! ```
+//!
+//! For escrow across more than two custodians, [`split_mik`] /
+//! [`recover_mik`] implement t-of-n Shamir secret sharing over GF(256).
 
 use crate::crypto::{CryptoError, CryptoResult};
 use crate::crypto::aes_gcm::{self, AesKey};
@@ -303,6 +306,246 @@ mod backup_keys {
     }
 }
 
+/// Shamir share format version
+const SHARE_VERSION: u8 = 0x01;
+const SHARE_SET_ID_LEN: usize = 8;
+const SHARE_MAC_LEN: usize = 32;
+/// Shared secret: MIK (32) || per-split MAC key (32)
+const SHARE_VALUE_LEN: usize = 32 + SHARE_MAC_LEN;
+/// Serialized share: version || set_id || threshold || index || value || mac
+pub const MIK_SHARE_LEN: usize = 1 + SHARE_SET_ID_LEN + 1 + 1 + SHARE_VALUE_LEN + SHARE_MAC_LEN;
+
+/// One Shamir share of a MIK (see [`split_mik`]).
+///
+/// Self-describing: carries the split it belongs to, the threshold and its
+/// x-coordinate, all covered by a MAC so corrupted or mismatched shares are
+/// detected on recovery. The MAC key is random per split and shared together
+/// with the MIK, so the tags say nothing about the MIK. Zeroized on drop.
+#[derive(Clone)]
+pub struct MikShare {
+    set_id: [u8; SHARE_SET_ID_LEN],
+    threshold: u8,
+    index: u8,
+    value: [u8; SHARE_VALUE_LEN],
+    mac: [u8; SHARE_MAC_LEN],
+}
+
+impl MikShare {
+    /// Share index (x-coordinate, 1..=255).
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Number of shares needed to recover the MIK.
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Serialize for handing to a custodian (`MIK_SHARE_LEN` bytes).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.mac_input().to_vec();
+        out.extend_from_slice(&self.mac);
+        out
+    }
+
+    /// Parse a serialized share. The MAC is checked by [`recover_mik`].
+    pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        if bytes.len() != MIK_SHARE_LEN {
+            return Err(CryptoError::InvalidInput(format!(
+                "Invalid MIK share length: expected {}, got {}",
+                MIK_SHARE_LEN,
+                bytes.len()
+            )));
+        }
+        if bytes[0] != SHARE_VERSION {
+            return Err(CryptoError::InvalidInput(format!("Unsupported MIK share version {}", bytes[0])));
+        }
+        let mut share = MikShare {
+            set_id: [0u8; SHARE_SET_ID_LEN],
+            threshold: bytes[1 + SHARE_SET_ID_LEN],
+            index: bytes[2 + SHARE_SET_ID_LEN],
+            value: [0u8; SHARE_VALUE_LEN],
+            mac: [0u8; SHARE_MAC_LEN],
+        };
+        let value_start = 3 + SHARE_SET_ID_LEN;
+        share.set_id.copy_from_slice(&bytes[1..1 + SHARE_SET_ID_LEN]);
+        share.value.copy_from_slice(&bytes[value_start..value_start + SHARE_VALUE_LEN]);
+        share.mac.copy_from_slice(&bytes[value_start + SHARE_VALUE_LEN..]);
+        if share.threshold < 2 || share.index == 0 {
+            return Err(CryptoError::InvalidInput("Invalid MIK share threshold or index".to_string()));
+        }
+        Ok(share)
+    }
+
+    fn mac_input(&self) -> zeroize::Zeroizing<Vec<u8>> {
+        let mut out = zeroize::Zeroizing::new(Vec::with_capacity(MIK_SHARE_LEN));
+        out.push(SHARE_VERSION);
+        out.extend_from_slice(&self.set_id);
+        out.push(self.threshold);
+        out.push(self.index);
+        out.extend_from_slice(&self.value);
+        out
+    }
+}
+
+impl Drop for MikShare {
+    fn drop(&mut self) {
+        self.value.zeroize();
+        self.mac.zeroize();
+    }
+}
+
+impl std::fmt::Debug for MikShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MikShare(index={}, threshold={}, [REDACTED])", self.index, self.threshold)
+    }
+}
+
+/// Split a MIK into `shares` Shamir shares, any `threshold` of which recover it.
+///
+/// Each byte of the MIK, followed by a fresh random MAC key, is the constant
+/// term of a random polynomial of degree `threshold - 1` over GF(256); share
+/// `i` holds the evaluations at `x = i`. Fewer than `threshold` shares are
+/// statistically independent of the MIK.
+pub fn split_mik(mik: &MasterIdentityKey, threshold: u8, shares: u8) -> CryptoResult<Vec<MikShare>> {
+    if threshold < 2 || shares < threshold {
+        return Err(CryptoError::InvalidInput(
+            "Shamir split requires 2 <= threshold <= shares <= 255".to_string(),
+        ));
+    }
+
+    let mut set_id = [0u8; SHARE_SET_ID_LEN];
+    random::fill_random(&mut set_id)?;
+
+    // coefficients[k] holds the degree-k coefficient for every byte position
+    let mut coefficients = vec![[0u8; SHARE_VALUE_LEN]; threshold as usize];
+    let mut mik_bytes = mik.to_bytes();
+    coefficients[0][..32].copy_from_slice(&mik_bytes);
+    mik_bytes.zeroize();
+    random::fill_random(&mut coefficients[0][32..])?;
+    for coefficient in coefficients.iter_mut().skip(1) {
+        random::fill_random(coefficient)?;
+    }
+
+    let mac_key = share_mac_key(&coefficients[0][32..]);
+    let result = (1..=shares)
+        .map(|x| {
+            let mut share = MikShare {
+                set_id,
+                threshold,
+                index: x,
+                value: [0u8; SHARE_VALUE_LEN],
+                mac: [0u8; SHARE_MAC_LEN],
+            };
+            for (position, byte) in share.value.iter_mut().enumerate() {
+                // Horner evaluation from the highest degree down
+                *byte = coefficients
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, c| gf256::mul(acc, x) ^ c[position]);
+            }
+            let tag = hmac::sign(&mac_key, &share.mac_input());
+            share.mac.copy_from_slice(tag.as_ref());
+            share
+        })
+        .collect();
+
+    for coefficient in coefficients.iter_mut() {
+        coefficient.zeroize();
+    }
+    Ok(result)
+}
+
+/// Recover a MIK from at least `threshold` shares of the same split.
+///
+/// Fails cleanly with fewer than `threshold` shares, shares from different
+/// splits, duplicate indices, or any share whose MAC does not verify against
+/// the recovered MIK (corruption or tampering).
+pub fn recover_mik(shares: &[MikShare]) -> CryptoResult<MasterIdentityKey> {
+    let first = shares
+        .first()
+        .ok_or_else(|| CryptoError::InvalidInput("No MIK shares provided".to_string()))?;
+    if shares.iter().any(|s| s.set_id != first.set_id || s.threshold != first.threshold) {
+        return Err(CryptoError::InvalidInput("MIK shares come from different splits".to_string()));
+    }
+    if shares.len() < first.threshold as usize {
+        return Err(CryptoError::InvalidInput(format!(
+            "Need {} MIK shares to recover, got {}",
+            first.threshold,
+            shares.len()
+        )));
+    }
+    for (i, a) in shares.iter().enumerate() {
+        if shares[..i].iter().any(|b| b.index == a.index) {
+            return Err(CryptoError::InvalidInput(format!("Duplicate MIK share index {}", a.index)));
+        }
+    }
+
+    // Lagrange interpolation at x = 0 over the first `threshold` shares
+    let used = &shares[..first.threshold as usize];
+    let mut secret = [0u8; SHARE_VALUE_LEN];
+    for (i, share) in used.iter().enumerate() {
+        let basis = used
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .fold(1u8, |acc, (_, other)| {
+                gf256::mul(acc, gf256::div(other.index, other.index ^ share.index))
+            });
+        for (acc, &y) in secret.iter_mut().zip(share.value.iter()) {
+            *acc ^= gf256::mul(basis, y);
+        }
+    }
+    let mac_key = share_mac_key(&secret[32..]);
+    let verified = shares.iter().try_for_each(|share| {
+        hmac::verify(&mac_key, &share.mac_input(), &share.mac).map_err(|_| {
+            CryptoError::InvalidInput(format!(
+                "MIK share {} MAC verification failed (possible corruption)",
+                share.index
+            ))
+        })
+    });
+    let mik = verified.and_then(|()| MasterIdentityKey::from_bytes(&secret[..32]));
+    secret.zeroize();
+    mik
+}
+
+fn share_mac_key(key_bytes: &[u8]) -> hmac::Key {
+    hmac::Key::new(hmac::HMAC_SHA256, key_bytes)
+}
+
+/// Constant-time GF(256) arithmetic (AES polynomial x^8 + x^4 + x^3 + x + 1).
+mod gf256 {
+    /// Carry-less multiply with reduction; no table lookups or data-dependent branches.
+    pub fn mul(mut a: u8, mut b: u8) -> u8 {
+        let mut product = 0u8;
+        for _ in 0..8 {
+            product ^= a & 0u8.wrapping_sub(b & 1);
+            let carry = 0u8.wrapping_sub(a >> 7);
+            a = (a << 1) ^ (carry & 0x1b);
+            b >>= 1;
+        }
+        product
+    }
+
+    /// Multiplicative inverse as a^254 (inv(0) = 0).
+    pub fn inv(a: u8) -> u8 {
+        let a2 = mul(a, a);
+        let a4 = mul(a2, a2);
+        let a8 = mul(a4, a4);
+        let a16 = mul(a8, a8);
+        let a32 = mul(a16, a16);
+        let a64 = mul(a32, a32);
+        let a128 = mul(a64, a64);
+        // 254 = 128 + 64 + 32 + 16 + 8 + 4 + 2
+        [a64, a32, a16, a8, a4, a2].iter().fold(a128, |acc, &p| mul(acc, p))
+    }
+
+    pub fn div(a: u8, b: u8) -> u8 {
+        mul(a, inv(b))
+    }
+}
+
 /// AAD for DMK wrap (binds ciphertext to device_id)
 const DMK_WRAP_AAD_PREFIX: &[u8] = b"B4AE-v1-DMK-wrap";
 
@@ -421,4 +664,66 @@ mod tests {
         let dmk_imported = import_dmk_for_device(&wrapped, &mik, b"device-b").unwrap();
         assert_eq!(dmk.to_bytes(), dmk_imported.to_bytes());
     }
+
+    #[test]
+    fn test_gf256_arithmetic() {
+        // FIPS-197 example: {57} x {83} = {c1}
+        assert_eq!(gf256::mul(0x57, 0x83), 0xc1);
+        for a in 1..=255u8 {
+            assert_eq!(gf256::mul(a, gf256::inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_shamir_threshold_recovery() {
+        let mik = MasterIdentityKey::generate().unwrap();
+        let shares = split_mik(&mik, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        assert!(shares.iter().all(|s| s.threshold() == 3));
+
+        // Every 3-subset recovers, via serialized shares too
+        for a in 0..5 {
+            for b in a + 1..5 {
+                for c in b + 1..5 {
+                    let subset: Vec<MikShare> = [a, c, b]
+                        .iter()
+                        .map(|&i| MikShare::from_bytes(&shares[i].to_bytes()).unwrap())
+                        .collect();
+                    assert_eq!(recover_mik(&subset).unwrap().to_bytes(), mik.to_bytes());
+                }
+            }
+        }
+        assert_eq!(recover_mik(&shares).unwrap().to_bytes(), mik.to_bytes());
+    }
+
+    #[test]
+    fn test_shamir_below_threshold_fails() {
+        let mik = MasterIdentityKey::generate().unwrap();
+        let shares = split_mik(&mik, 3, 5).unwrap();
+        assert!(recover_mik(&shares[..2]).is_err());
+        assert!(recover_mik(&[]).is_err());
+        assert!(recover_mik(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]).is_err());
+
+        // Shares from another split of the same MIK do not mix
+        let other = split_mik(&mik, 3, 5).unwrap();
+        assert!(recover_mik(&[shares[0].clone(), shares[1].clone(), other[2].clone()]).is_err());
+
+        assert!(split_mik(&mik, 1, 5).is_err());
+        assert!(split_mik(&mik, 4, 3).is_err());
+    }
+
+    #[test]
+    fn test_shamir_corrupted_share_detected() {
+        let mik = MasterIdentityKey::generate().unwrap();
+        let shares = split_mik(&mik, 2, 3).unwrap();
+
+        let mut bytes = shares[1].to_bytes();
+        bytes[20] ^= 0x01;
+        let corrupted = MikShare::from_bytes(&bytes).unwrap();
+        assert!(recover_mik(&[shares[0].clone(), corrupted.clone()]).is_err());
+        // Also detected when enough intact shares are present
+        assert!(recover_mik(&[shares[0].clone(), shares[2].clone(), corrupted]).is_err());
+
+        assert!(MikShare::from_bytes(&bytes[..MIK_SHARE_LEN - 1]).is_err());
+    }
 }
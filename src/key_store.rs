@@ -1,17 +1,109 @@
 //! B4AE Key Store
 //!
 //! Persistent storage for Master Identity Key (MIK) encrypted with passphrase.
-//! Uses HKDF + AES-256-GCM.
+//! Uses Argon2id + AES-256-GCM.
+//!
+//! Blob layout (the header is authenticated as AAD, so tampering with the
+//! stored cost parameters fails decryption):
+//!
+//! ```text
+//! version (1) || mem_kib (4) || iterations (4) || parallelism (4) || salt (16) || nonce (12) || ciphertext+tag
+//! ```
+//!
+//! Blobs written before the Argon2 format (`salt || nonce || ciphertext+tag`,
+//! HKDF-derived key) still load; storing again upgrades them.
 
+use crate::crypto::CryptoError;
 use crate::crypto::aes_gcm::{self, AesKey};
+use crate::crypto::bip39;
 use crate::crypto::hkdf;
 use crate::error::{B4aeError, B4aeResult};
 use crate::key_hierarchy::MasterIdentityKey;
 use std::collections::HashMap;
+use zeroize::Zeroizing;
+
+/// Blob format version for Argon2id-protected MIKs
+const BLOB_VERSION: u8 = 0x02;
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+const HEADER_SIZE: usize = 1 + 12 + SALT_SIZE;
+/// Serialized MIK (32 bytes) plus GCM tag
+const SEALED_MIK_SIZE: usize = 32 + 16;
+/// Length of a pre-Argon2 blob: salt || nonce || ciphertext+tag
+const LEGACY_BLOB_SIZE: usize = SALT_SIZE + NONCE_SIZE + SEALED_MIK_SIZE;
+
+/// Upper bounds accepted when loading, so a tampered header cannot make
+/// `load_mik` allocate or spin unboundedly before the tag is checked.
+/// Memory is capped at the `Sensitive` profile's 1 GiB.
+const MAX_MEM_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 64;
+
+/// Argon2id cost parameters for the passphrase KDF.
+///
+/// Stored alongside the encrypted MIK, so a blob always loads with the
+/// parameters it was created with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Params {
+    /// Memory cost in KiB.
+    pub mem_kib: u32,
+    /// Number of passes.
+    pub iterations: u32,
+    /// Degree of parallelism (lanes).
+    pub parallelism: u32,
+}
+
+impl Argon2Params {
+    /// Create custom parameters.
+    pub fn new(mem_kib: u32, iterations: u32, parallelism: u32) -> Self {
+        Argon2Params { mem_kib, iterations, parallelism }
+    }
+
+    fn validate(&self) -> B4aeResult<()> {
+        if self.mem_kib > MAX_MEM_KIB || self.iterations > MAX_ITERATIONS || self.parallelism > MAX_PARALLELISM {
+            return Err(B4aeError::InvalidInput(format!("Argon2 parameters out of range: {:?}", self)));
+        }
+        Ok(())
+    }
+
+    fn to_bytes(self) -> [u8; 12] {
+        let mut bytes = [0u8; 12];
+        bytes[0..4].copy_from_slice(&self.mem_kib.to_be_bytes());
+        bytes[4..8].copy_from_slice(&self.iterations.to_be_bytes());
+        bytes[8..12].copy_from_slice(&self.parallelism.to_be_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let word = |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Argon2Params { mem_kib: word(0), iterations: word(4), parallelism: word(8) }
+    }
+}
+
+impl Default for Argon2Params {
+    /// [`KdfProfile::Interactive`] parameters.
+    fn default() -> Self {
+        KeyStore::recommended_params_for(KdfProfile::Interactive)
+    }
+}
+
+/// Deployment profile for choosing Argon2 parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KdfProfile {
+    /// Phones and interactive unlock: 19 MiB, 2 passes, 1 lane (OWASP).
+    Interactive,
+    /// Servers with memory to spare: 64 MiB, 3 passes, 4 lanes (RFC 9106).
+    Server,
+    /// Rarely unlocked, high-value keys: 1 GiB, 4 passes, 4 lanes.
+    Sensitive,
+}
 
 /// Backend for key persistence.
 pub trait KeyStoreBackend: Send + Sync {
     /// Store a key-value pair (encrypted blob).
+    ///
+    /// Must replace any existing value atomically: a failed `put` leaves the
+    /// previous value intact (e.g. write a temp file, then rename).
     fn put(&mut self, key: &str, value: &[u8]) -> B4aeResult<()>;
     /// Retrieve value by key.
     fn get(&self, key: &str) -> B4aeResult<Option<Vec<u8>>>;
@@ -46,28 +138,79 @@ impl KeyStoreBackend for MemoryKeyStoreBackend {
 /// Key store for MIK persistence. Encrypts with passphrase-derived key.
 pub struct KeyStore {
     backend: Box<dyn KeyStoreBackend>,
+    params: Argon2Params,
 }
 
 impl KeyStore {
-    /// Create key store with the given backend.
+    /// Create key store with the given backend and interactive KDF parameters.
     pub fn new(backend: Box<dyn KeyStoreBackend>) -> Self {
-        Self { backend }
+        Self::with_params(backend, Argon2Params::default())
     }
 
-    /// Derive encryption key from passphrase.
-    fn derive_key(passphrase: &[u8], salt: &[u8]) -> B4aeResult<AesKey> {
-        let key = hkdf::derive_key_with_salt(salt, &[passphrase], b"B4AE-v1-keystore", 32)?;
+    /// Create key store that encrypts new blobs with the given KDF parameters.
+    ///
+    /// Loading always uses the parameters stored in the blob.
+    pub fn with_params(backend: Box<dyn KeyStoreBackend>, params: Argon2Params) -> Self {
+        Self { backend, params }
+    }
+
+    /// KDF parameters used when storing.
+    pub fn params(&self) -> Argon2Params {
+        self.params
+    }
+
+    /// Recommended Argon2id parameters for a deployment profile.
+    pub fn recommended_params_for(profile: KdfProfile) -> Argon2Params {
+        match profile {
+            KdfProfile::Interactive => Argon2Params::new(19 * 1024, 2, 1),
+            KdfProfile::Server => Argon2Params::new(64 * 1024, 3, 4),
+            KdfProfile::Sensitive => Argon2Params::new(1024 * 1024, 4, 4),
+        }
+    }
+
+    /// Derive encryption key from passphrase with Argon2id.
+    ///
+    /// The same passphrase, salt and parameters always give the same key; the
+    /// salt should be random, at least 16 bytes, and stored with the data.
+    pub fn derive_key(passphrase: &[u8], salt: &[u8], params: &Argon2Params) -> B4aeResult<AesKey> {
+        use argon2::{Algorithm, Argon2, Params, Version};
+
+        params.validate()?;
+        let argon_params = Params::new(params.mem_kib, params.iterations, params.parallelism, Some(32))
+            .map_err(|e| B4aeError::InvalidInput(format!("Invalid Argon2 parameters: {}", e)))?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+            .hash_password_into(passphrase, salt, key.as_mut())
+            .map_err(|e| B4aeError::CryptoError(CryptoError::KeyGenerationFailed(format!("Passphrase hashing failed: {}", e))))?;
+        Ok(AesKey::from_bytes(key.as_ref())?)
+    }
+
+    /// Derive encryption key for a pre-Argon2 blob.
+    fn derive_legacy_key(passphrase: &[u8], salt: &[u8]) -> B4aeResult<AesKey> {
+        let key = Zeroizing::new(hkdf::derive_key_with_salt(salt, &[passphrase], b"B4AE-v1-keystore", 32)?);
         Ok(AesKey::from_bytes(&key)?)
     }
 
+    /// AAD binding the header (version, parameters, salt) to the ciphertext.
+    fn blob_aad(header: &[u8]) -> Vec<u8> {
+        let mut aad = b"B4AE-MIK".to_vec();
+        aad.extend_from_slice(header);
+        aad
+    }
+
     /// Store MIK encrypted with passphrase.
     pub fn store_mik(&mut self, passphrase: &[u8], mik: &MasterIdentityKey) -> B4aeResult<()> {
-        let mut salt = [0u8; 16];
-        crate::crypto::random::fill_random(&mut salt).map_err(|e| B4aeError::CryptoError(e.to_string()))?;
-        let key = Self::derive_key(passphrase, &salt)?;
-        let plaintext = mik.to_bytes();
-        let (nonce, ciphertext) = aes_gcm::encrypt(&key, &plaintext, b"B4AE-MIK")?;
-        let mut blob = salt.to_vec();
+        let mut salt = [0u8; SALT_SIZE];
+        crate::crypto::random::fill_random(&mut salt).map_err(B4aeError::CryptoError)?;
+        let key = Self::derive_key(passphrase, &salt, &self.params)?;
+
+        let mut blob = Vec::with_capacity(HEADER_SIZE + NONCE_SIZE + SEALED_MIK_SIZE);
+        blob.push(BLOB_VERSION);
+        blob.extend_from_slice(&self.params.to_bytes());
+        blob.extend_from_slice(&salt);
+
+        let plaintext = Zeroizing::new(mik.to_bytes());
+        let (nonce, ciphertext) = aes_gcm::encrypt(&key, plaintext.as_ref(), &Self::blob_aad(&blob))?;
         blob.extend_from_slice(&nonce);
         blob.extend_from_slice(&ciphertext);
         self.backend.put("mik", &blob)
@@ -79,14 +222,243 @@ impl KeyStore {
             Some(b) => b,
             None => return Ok(None),
         };
-        if blob.len() < 16 + 12 + 32 + 16 {
-            return Err(B4aeError::CryptoError("KeyStore blob too short".to_string()));
-        }
-        let salt = &blob[0..16];
-        let nonce = &blob[16..28];
-        let ciphertext = &blob[28..];
-        let key = Self::derive_key(passphrase, salt)?;
-        let plaintext = aes_gcm::decrypt(&key, nonce, ciphertext, b"B4AE-MIK")?;
+
+        let plaintext = if blob.len() == LEGACY_BLOB_SIZE {
+            let (salt, rest) = blob.split_at(SALT_SIZE);
+            let (nonce, ciphertext) = rest.split_at(NONCE_SIZE);
+            let key = Self::derive_legacy_key(passphrase, salt)?;
+            Zeroizing::new(aes_gcm::decrypt(&key, nonce, ciphertext, b"B4AE-MIK")?)
+        } else {
+            if blob.len() < HEADER_SIZE + NONCE_SIZE + SEALED_MIK_SIZE {
+                return Err(B4aeError::CryptoError(CryptoError::InvalidInput("KeyStore blob too short".to_string())));
+            }
+            if blob[0] != BLOB_VERSION {
+                return Err(B4aeError::CryptoError(CryptoError::InvalidInput(format!("Unsupported KeyStore blob version {}", blob[0]))));
+            }
+            let (header, rest) = blob.split_at(HEADER_SIZE);
+            let (nonce, ciphertext) = rest.split_at(NONCE_SIZE);
+            let params = Argon2Params::from_bytes(&header[1..13]);
+            let key = Self::derive_key(passphrase, &header[13..], &params)?;
+            Zeroizing::new(aes_gcm::decrypt(&key, nonce, ciphertext, &Self::blob_aad(header))?)
+        };
         Ok(Some(MasterIdentityKey::from_bytes(&plaintext)?))
     }
+
+    /// KDF parameters of the stored blob, if any.
+    ///
+    /// Returns `None` for an empty store or a pre-Argon2 blob. The values are
+    /// not authenticated until [`load_mik`](Self::load_mik) succeeds.
+    pub fn stored_params(&self) -> B4aeResult<Option<Argon2Params>> {
+        Ok(self
+            .backend
+            .get("mik")?
+            .filter(|blob| blob.len() != LEGACY_BLOB_SIZE && blob.len() >= HEADER_SIZE && blob[0] == BLOB_VERSION)
+            .map(|blob| Argon2Params::from_bytes(&blob[1..13])))
+    }
+
+    /// Re-encrypt the stored MIK under a new passphrase.
+    ///
+    /// The MIK itself is unchanged, so all keys derived from it stay valid.
+    /// A fresh salt and this store's [`params`](Self::params) are used for
+    /// the new passphrase. If `old_passphrase` is wrong the stored blob is
+    /// left untouched.
+    pub fn change_passphrase(&mut self, old_passphrase: &[u8], new_passphrase: &[u8]) -> B4aeResult<()> {
+        let mik = self
+            .load_mik(old_passphrase)?
+            .ok_or_else(|| B4aeError::InvalidInput("KeyStore has no stored MIK".to_string()))?;
+        self.store_mik(new_passphrase, &mik)
+    }
+
+    /// Export the stored MIK as a 24-word BIP39 mnemonic for paper backup.
+    ///
+    /// Anyone holding the phrase holds the MIK; it is returned in a
+    /// zeroizing buffer and should never be written to disk unencrypted.
+    pub fn export_mnemonic(&self, passphrase: &[u8]) -> B4aeResult<Zeroizing<String>> {
+        let mik = self
+            .load_mik(passphrase)?
+            .ok_or_else(|| B4aeError::InvalidInput("KeyStore has no stored MIK".to_string()))?;
+        let entropy = Zeroizing::new(mik.to_bytes());
+        Ok(bip39::entropy_to_mnemonic(entropy.as_ref())?)
+    }
+
+    /// Reconstruct a MIK from a 24-word BIP39 mnemonic.
+    ///
+    /// Rejects phrases with the wrong word count, unknown words or a bad
+    /// checksum. Use [`store_mik`](Self::store_mik) to persist the result.
+    pub fn import_mnemonic(phrase: &str) -> B4aeResult<MasterIdentityKey> {
+        let entropy = bip39::mnemonic_to_entropy(phrase)?;
+        if entropy.len() != 32 {
+            return Err(B4aeError::InvalidInput(
+                format!("MIK mnemonic must have 24 words, got {}", phrase.split_whitespace().count())
+            ));
+        }
+        Ok(MasterIdentityKey::from_bytes(&entropy)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap parameters so tests stay fast
+    fn test_params() -> Argon2Params {
+        Argon2Params::new(1024, 1, 1)
+    }
+
+    fn store_with_mik(passphrase: &[u8]) -> (KeyStore, MasterIdentityKey) {
+        let mut store = KeyStore::with_params(Box::new(MemoryKeyStoreBackend::new()), test_params());
+        let mik = MasterIdentityKey::generate().unwrap();
+        store.store_mik(passphrase, &mik).unwrap();
+        (store, mik)
+    }
+
+    #[test]
+    fn test_change_passphrase() {
+        let (mut store, mik) = store_with_mik(b"old passphrase");
+        let old_blob = store.backend.get("mik").unwrap().unwrap();
+
+        store.change_passphrase(b"old passphrase", b"new passphrase").unwrap();
+
+        let new_blob = store.backend.get("mik").unwrap().unwrap();
+        assert_ne!(old_blob[13..HEADER_SIZE], new_blob[13..HEADER_SIZE], "salt must be fresh");
+        assert!(store.load_mik(b"old passphrase").is_err());
+        let loaded = store.load_mik(b"new passphrase").unwrap().unwrap();
+        assert_eq!(loaded.to_bytes(), mik.to_bytes());
+    }
+
+    #[test]
+    fn test_change_passphrase_wrong_old_passphrase() {
+        let (mut store, _) = store_with_mik(b"old passphrase");
+        let before = store.backend.get("mik").unwrap().unwrap();
+
+        assert!(store.change_passphrase(b"wrong passphrase", b"new passphrase").is_err());
+
+        assert_eq!(store.backend.get("mik").unwrap().unwrap(), before);
+        assert!(store.load_mik(b"old passphrase").unwrap().is_some());
+
+        let mut empty = KeyStore::new(Box::new(MemoryKeyStoreBackend::new()));
+        assert!(empty.change_passphrase(b"old passphrase", b"new passphrase").is_err());
+    }
+
+    #[test]
+    fn test_change_passphrase_keeps_derived_keys() {
+        let (mut store, mik) = store_with_mik(b"old passphrase");
+        let dmk_before = mik.derive_dmk(b"device-1").unwrap().to_bytes();
+
+        store.change_passphrase(b"old passphrase", b"new passphrase").unwrap();
+
+        let loaded = store.load_mik(b"new passphrase").unwrap().unwrap();
+        assert_eq!(loaded.derive_dmk(b"device-1").unwrap().to_bytes(), dmk_before);
+    }
+
+    #[test]
+    fn test_custom_params_roundtrip() {
+        let params = Argon2Params::new(2048, 3, 2);
+        let mut store = KeyStore::with_params(Box::new(MemoryKeyStoreBackend::new()), params);
+        let mik = MasterIdentityKey::generate().unwrap();
+        store.store_mik(b"passphrase", &mik).unwrap();
+        assert_eq!(store.stored_params().unwrap(), Some(params));
+
+        // A store configured with different parameters still loads the blob
+        // with the parameters recorded in it
+        let blob = store.backend.get("mik").unwrap().unwrap();
+        let mut backend = MemoryKeyStoreBackend::new();
+        backend.put("mik", &blob).unwrap();
+        let reader = KeyStore::with_params(Box::new(backend), test_params());
+        let loaded = reader.load_mik(b"passphrase").unwrap().unwrap();
+        assert_eq!(loaded.to_bytes(), mik.to_bytes());
+    }
+
+    #[test]
+    fn test_tampered_params_rejected() {
+        let (mut store, _) = store_with_mik(b"passphrase");
+        let mut blob = store.backend.get("mik").unwrap().unwrap();
+
+        // iterations field: bytes 5..9
+        blob[8] ^= 0x03;
+        store.backend.put("mik", &blob).unwrap();
+        assert!(store.load_mik(b"passphrase").is_err());
+
+        // Absurd costs are refused before running the KDF
+        blob[5] = 0xff;
+        store.backend.put("mik", &blob).unwrap();
+        assert!(matches!(store.load_mik(b"passphrase"), Err(B4aeError::InvalidInput(_))));
+
+        // mem_kib field: bytes 1..5
+        let mut blob = store_with_mik(b"passphrase").0.backend.get("mik").unwrap().unwrap();
+        blob[1..5].copy_from_slice(&(MAX_MEM_KIB + 1).to_be_bytes());
+        store.backend.put("mik", &blob).unwrap();
+        assert!(matches!(store.load_mik(b"passphrase"), Err(B4aeError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_legacy_blob_loads_and_upgrades() {
+        let mik = MasterIdentityKey::generate().unwrap();
+        let salt = [9u8; SALT_SIZE];
+        let key = KeyStore::derive_legacy_key(b"passphrase", &salt).unwrap();
+        let (nonce, ciphertext) = aes_gcm::encrypt(&key, &mik.to_bytes(), b"B4AE-MIK").unwrap();
+        let mut legacy = salt.to_vec();
+        legacy.extend_from_slice(&nonce);
+        legacy.extend_from_slice(&ciphertext);
+        assert_eq!(legacy.len(), LEGACY_BLOB_SIZE);
+
+        let mut backend = MemoryKeyStoreBackend::new();
+        backend.put("mik", &legacy).unwrap();
+        let mut store = KeyStore::with_params(Box::new(backend), test_params());
+        assert_eq!(store.stored_params().unwrap(), None);
+        assert_eq!(store.load_mik(b"passphrase").unwrap().unwrap().to_bytes(), mik.to_bytes());
+
+        store.change_passphrase(b"passphrase", b"passphrase").unwrap();
+        assert_eq!(store.stored_params().unwrap(), Some(test_params()));
+        assert_eq!(store.load_mik(b"passphrase").unwrap().unwrap().to_bytes(), mik.to_bytes());
+    }
+
+    #[test]
+    fn test_recommended_params() {
+        let interactive = KeyStore::recommended_params_for(KdfProfile::Interactive);
+        let server = KeyStore::recommended_params_for(KdfProfile::Server);
+        let sensitive = KeyStore::recommended_params_for(KdfProfile::Sensitive);
+        assert_eq!(Argon2Params::default(), interactive);
+        assert!(interactive.mem_kib < server.mem_kib && server.mem_kib < sensitive.mem_kib);
+        for params in [interactive, server, sensitive] {
+            assert!(params.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_mnemonic_roundtrip() {
+        let (store, mik) = store_with_mik(b"passphrase");
+
+        let phrase = store.export_mnemonic(b"passphrase").unwrap();
+        assert_eq!(phrase.split(' ').count(), 24);
+        assert!(store.export_mnemonic(b"wrong").is_err());
+
+        let restored = KeyStore::import_mnemonic(&phrase).unwrap();
+        assert_eq!(restored.to_bytes(), mik.to_bytes());
+        assert_eq!(
+            restored.derive_dmk(b"device-1").unwrap().to_bytes(),
+            mik.derive_dmk(b"device-1").unwrap().to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_mnemonic_invalid_rejected() {
+        let (store, _) = store_with_mik(b"passphrase");
+        let phrase = store.export_mnemonic(b"passphrase").unwrap();
+        let words: Vec<&str> = phrase.split(' ').collect();
+
+        // The low bit of the last word's index is the final checksum bit, so
+        // flipping it leaves the entropy intact and always breaks the checksum
+        let wordlist = bip39::wordlist();
+        let last = wordlist.iter().position(|w| *w == words[23]).unwrap();
+        let mut tampered = words.clone();
+        tampered[23] = wordlist[last ^ 1];
+        assert!(KeyStore::import_mnemonic(&tampered.join(" ")).is_err());
+
+        // Wrong word counts: 23 words, and a valid 12-word phrase
+        assert!(KeyStore::import_mnemonic(&words[..23].join(" ")).is_err());
+        assert!(KeyStore::import_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        ).is_err());
+    }
 }
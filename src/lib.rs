@@ -7,50 +7,73 @@
 #![warn(missing_docs)]
 #![warn(rust_2018_idioms)]
 #![allow(unexpected_cfgs)] // liboqs (PQC) feature — bukan elara; elara-transport sudah dari crates.io
+// Tanpa fitur `std` hanya inti kripto (AEAD, HKDF, constant-time) yang dikompilasi
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 /// Cryptographic primitives (Kyber, Dilithium, hybrid, AES-GCM, HKDF, PFS+, ZKAuth).
 pub mod crypto;
 /// Protocol layer (handshake, message, session).
+#[cfg(feature = "std")]
 pub mod protocol;
 /// Metadata protection (padding, timing, obfuscation).
+#[cfg(feature = "std")]
 pub mod metadata;
 /// Safe time utilities (panic-free).
+#[cfg(feature = "std")]
 pub mod time;
 /// Error types for B4AE.
+#[cfg(feature = "std")]
 pub mod error;
 /// High-level client API.
+#[cfg(feature = "std")]
 pub mod client;
 /// High-level client API — v2.0 protocol (mode negotiation, cookie challenge, traffic scheduler).
 #[cfg(feature = "v2_protocol")]
 pub mod client_v2;
 /// Re-exports for common usage.
+#[cfg(feature = "std")]
 pub mod prelude;
 /// Audit logging for compliance.
+#[cfg(feature = "std")]
 pub mod audit;
 /// Key hierarchy (MIK, DMK, STK, BKS).
+#[cfg(feature = "std")]
 pub mod key_hierarchy;
 /// Key persistence (passphrase-protected MIK storage).
+#[cfg(feature = "std")]
 pub mod key_store;
 /// Encrypted storage using STK.
+#[cfg(feature = "std")]
 pub mod storage;
 /// Performance monitoring and profiling.
+#[cfg(feature = "std")]
 pub mod performance;
+/// Tracing spans for handshakes and message operations (feature `otel`).
+#[cfg(feature = "std")]
+pub mod telemetry;
 
 #[cfg(feature = "hsm")]
 pub mod hsm;
 
-#[cfg(feature = "elara-transport")]
+/// Transport layer: packet chunking and optional ELARA/proxy adapters.
+#[cfg(feature = "std")]
 pub mod transport;
 
-#[cfg(feature = "elara-transport")]
+#[cfg(all(feature = "std", feature = "elara-transport"))]
 pub mod elara_node;
 
 // Re-export commonly used types
+#[cfg(feature = "std")]
 pub use error::{B4aeError, B4aeResult};
+#[cfg(feature = "std")]
 pub use crypto::{CryptoConfig, SecurityLevel};
+#[cfg(feature = "std")]
 pub use client::{B4aeClient, B4aeConfig};
 #[cfg(feature = "v2_protocol")]
 pub use client_v2::B4aeClientV2;
+#[cfg(feature = "std")]
 pub use protocol::SecurityProfile;
 
 /// B4AE crate version (Cargo.toml)
@@ -70,6 +93,7 @@ pub const PROTOCOL_FULL_NAME: &str = "Beyond For All Encryption";
 pub const MAX_MESSAGE_SIZE: usize = 1 << 20; // 1 MiB
 
 /// Security-hardened core module with panic-free production paths
+#[cfg(feature = "std")]
 pub mod security;
 
 #[cfg(test)]
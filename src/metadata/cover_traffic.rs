@@ -3,10 +3,18 @@
 //! Generates dummy messages at a configurable rate to obfuscate real traffic patterns.
 //! Dummy messages are indistinguishable from real messages when encrypted and are marked
 //! internally to prevent application processing.
+//!
+//! With the `tokio` feature, [`CoverTrafficDriver`] emits a constant-rate
+//! stream that interleaves real messages with PADME-padded dummies.
 
 use std::time::Instant;
 use rand::{Rng, thread_rng};
 
+#[cfg(feature = "tokio")]
+use crate::crypto::{CryptoError, CryptoResult};
+#[cfg(feature = "tokio")]
+use crate::metadata::{padding, MetadataProtectionConfig};
+
 /// Cover traffic generator that creates dummy messages to hide real traffic patterns.
 ///
 /// The generator uses a probabilistic approach to determine when to send dummy messages,
@@ -192,6 +200,151 @@ impl CoverTrafficGenerator {
     }
 }
 
+/// Frame marker for real messages emitted by [`CoverTrafficDriver`]
+#[cfg(feature = "tokio")]
+const FRAME_REAL: u8 = 0x00;
+/// Frame marker for dummy messages emitted by [`CoverTrafficDriver`]
+#[cfg(feature = "tokio")]
+const FRAME_DUMMY: u8 = 0xFF;
+
+/// Task handle of a spawned [`CoverTrafficDriver`]
+#[cfg(feature = "tokio")]
+pub type CoverTrafficTask = tokio::task::JoinHandle<CryptoResult<()>>;
+
+/// Async constant-rate cover traffic driver.
+///
+/// Every `1 / target_rate_msgs_per_sec` seconds the driver emits exactly one
+/// frame: the next queued real message if one is waiting, otherwise a dummy.
+/// Frames are `[marker][payload]` padded with PADME, and dummies reuse the
+/// length of the last real message, so real and dummy frames land in the same
+/// bucket. Encrypt frames before sending; use [`CoverTrafficDriver::decode`]
+/// after decryption to drop dummies.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn run() {
+/// use b4ae::metadata::MetadataProtectionConfig;
+/// use b4ae::metadata::cover_traffic::CoverTrafficDriver;
+///
+/// let driver = CoverTrafficDriver::new(MetadataProtectionConfig::high_security()).unwrap();
+/// let (real_tx, mut frames, _task) = driver.spawn(64);
+/// real_tx.send(b"hello".to_vec()).await.unwrap();
+/// while let Some(frame) = frames.recv().await {
+///     // encrypt and send `frame`
+/// }
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+pub struct CoverTrafficDriver {
+    period: std::time::Duration,
+    last_real_len: usize,
+}
+
+#[cfg(feature = "tokio")]
+impl CoverTrafficDriver {
+    /// Create a driver from a config with `constant_rate_mode` enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CryptoError::InvalidInput` if the config is invalid,
+    /// constant-rate mode is disabled, or the rate gives no representable
+    /// non-zero period.
+    pub fn new(config: MetadataProtectionConfig) -> CryptoResult<Self> {
+        config.validate()?;
+        if !config.constant_rate_mode {
+            return Err(CryptoError::InvalidInput(
+                "CoverTrafficDriver requires constant_rate_mode".to_string(),
+            ));
+        }
+        let period = std::time::Duration::try_from_secs_f64(1.0 / config.target_rate_msgs_per_sec)
+            .ok()
+            .filter(|period| !period.is_zero())
+            .ok_or_else(|| {
+                CryptoError::InvalidInput(format!(
+                    "target_rate_msgs_per_sec out of range: {}",
+                    config.target_rate_msgs_per_sec
+                ))
+            })?;
+        Ok(Self {
+            period,
+            last_real_len: 0,
+        })
+    }
+
+    /// Spawn the driver on the current tokio runtime.
+    ///
+    /// Returns the sender for real messages, the receiver of padded frames and
+    /// the task handle. The task stops with `Ok(())` when the frame receiver is
+    /// dropped; dropping the real-message sender keeps dummies flowing.
+    ///
+    /// A real message too large for the largest PADME bucket (64 KiB including
+    /// the frame marker and length header) is never dropped silently: the task
+    /// stops with that error and closes the frame channel.
+    pub fn spawn(
+        self,
+        capacity: usize,
+    ) -> (
+        tokio::sync::mpsc::Sender<Vec<u8>>,
+        tokio::sync::mpsc::Receiver<Vec<u8>>,
+        CoverTrafficTask,
+    ) {
+        let (real_tx, real_rx) = tokio::sync::mpsc::channel(capacity);
+        let (out_tx, out_rx) = tokio::sync::mpsc::channel(capacity);
+        let handle = tokio::spawn(self.run(real_rx, out_tx));
+        (real_tx, out_rx, handle)
+    }
+
+    async fn run(
+        mut self,
+        mut real_rx: tokio::sync::mpsc::Receiver<Vec<u8>>,
+        out_tx: tokio::sync::mpsc::Sender<Vec<u8>>,
+    ) -> CryptoResult<()> {
+        let start = tokio::time::Instant::now() + self.period;
+        let mut ticker = tokio::time::interval_at(start, self.period);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let frame = match real_rx.try_recv() {
+                Ok(message) => {
+                    let frame = Self::frame(FRAME_REAL, &message)?;
+                    self.last_real_len = message.len();
+                    frame
+                }
+                Err(_) => {
+                    let mut dummy = vec![0u8; self.last_real_len];
+                    thread_rng().fill(&mut dummy[..]);
+                    Self::frame(FRAME_DUMMY, &dummy)?
+                }
+            };
+            if out_tx.send(frame).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn frame(marker: u8, payload: &[u8]) -> CryptoResult<Vec<u8>> {
+        let mut framed = Vec::with_capacity(payload.len() + 1);
+        framed.push(marker);
+        framed.extend_from_slice(payload);
+        padding::apply_padme_padding(&framed, 0)
+            .map_err(|e| CryptoError::InvalidInput(e.to_string()))
+    }
+
+    /// Decode a frame emitted by the driver.
+    ///
+    /// Returns `Ok(None)` for dummy frames and `Ok(Some(message))` for real ones.
+    pub fn decode(frame: &[u8]) -> CryptoResult<Option<Vec<u8>>> {
+        let framed = padding::remove_padme_padding(frame)
+            .map_err(|e| CryptoError::InvalidInput(e.to_string()))?;
+        match framed.split_first() {
+            Some((&FRAME_REAL, message)) => Ok(Some(message.to_vec())),
+            Some((&FRAME_DUMMY, _)) => Ok(None),
+            _ => Err(CryptoError::InvalidInput("Invalid cover traffic frame".to_string())),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -369,3 +522,72 @@ mod tests {
                 high_rate_count, low_rate_count);
     }
 }
+
+#[cfg(all(test, feature = "tokio"))]
+mod driver_tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn constant_rate(rate: f64) -> MetadataProtectionConfig {
+        MetadataProtectionConfig {
+            constant_rate_mode: true,
+            target_rate_msgs_per_sec: rate,
+            ..MetadataProtectionConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_driver_requires_constant_rate() {
+        assert!(CoverTrafficDriver::new(MetadataProtectionConfig::default()).is_err());
+        assert!(CoverTrafficDriver::new(constant_rate(0.0)).is_err());
+    }
+
+    #[test]
+    fn test_driver_rejects_out_of_range_rate() {
+        for rate in [f64::NAN, f64::INFINITY, 1e-300, 1e300] {
+            assert!(matches!(
+                CoverTrafficDriver::new(constant_rate(rate)),
+                Err(CryptoError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_driver_emits_target_rate() {
+        let driver = CoverTrafficDriver::new(constant_rate(10.0)).unwrap();
+        let (real_tx, mut frames, handle) = driver.spawn(16);
+        real_tx.send(b"real message".to_vec()).await.unwrap();
+
+        // One simulated second (+ half a period to avoid the boundary tick)
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(1050);
+        let mut emitted = Vec::new();
+        while let Ok(Some(frame)) = tokio::time::timeout_at(deadline, frames.recv()).await {
+            emitted.push(frame);
+        }
+        assert_eq!(emitted.len(), 10);
+
+        // Real and dummy frames share one PADME bucket
+        assert!(emitted.iter().all(|f| f.len() == emitted[0].len()));
+
+        let decoded: Vec<_> = emitted
+            .iter()
+            .map(|f| CoverTrafficDriver::decode(f).unwrap())
+            .collect();
+        assert_eq!(decoded[0].as_deref(), Some(&b"real message"[..]));
+        assert!(decoded[1..].iter().all(|d| d.is_none()));
+
+        drop(frames);
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_driver_reports_oversized_message() {
+        let driver = CoverTrafficDriver::new(constant_rate(10.0)).unwrap();
+        let (real_tx, mut frames, handle) = driver.spawn(16);
+        real_tx.send(vec![0x42; 64 * 1024]).await.unwrap();
+
+        // The message is not silently skipped: the driver stops with an error
+        assert!(frames.recv().await.is_none());
+        assert!(matches!(handle.await.unwrap(), Err(CryptoError::InvalidInput(_))));
+    }
+}
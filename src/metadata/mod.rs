@@ -2,7 +2,7 @@
 //!
 //! Padding, timing obfuscation, dummy traffic for traffic analysis resistance.
 
-/// PKCS#7 and random padding.
+/// PKCS#7, PADME and random padding.
 pub mod padding;
 /// Timing delay strategies.
 pub mod timing;
@@ -15,7 +15,8 @@ pub mod protector;
 
 use crate::error::{B4aeError, B4aeResult};
 use crate::crypto::{CryptoError, CryptoResult};
-use crate::protocol::ProtocolConfig;
+use crate::protocol::{PaddingScheme, ProtocolConfig};
+use hmac::{Hmac, Mac};
 use sha3::{Sha3_256, Digest};
 use subtle::ConstantTimeEq;
 
@@ -56,18 +57,31 @@ impl ProtectionLevel {
     }
 }
 
+/// Construction used for the 32-byte padding authentication tag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaddingMac {
+    /// Legacy `SHA3-256(key || message)` prefix MAC, kept for interoperability
+    /// with peers that predate [`PaddingMac::HmacSha3`].
+    #[default]
+    Sha3Prefix,
+    /// HMAC-SHA3-256 keyed by the metadata key.
+    HmacSha3,
+}
+
 /// Metadata protection manager
 pub struct MetadataProtection {
     config: ProtocolConfig,
     level: ProtectionLevel,
     /// Optional metadata key from session (for padding authentication)
     metadata_key: Option<Vec<u8>>,
+    /// Tag construction used when `metadata_key` is set
+    padding_mac: PaddingMac,
 }
 
 impl MetadataProtection {
     /// Create new metadata protection manager
     pub fn new(config: ProtocolConfig, level: ProtectionLevel) -> Self {
-        MetadataProtection { config, level, metadata_key: None }
+        MetadataProtection { config, level, metadata_key: None, padding_mac: PaddingMac::default() }
     }
 
     /// Create with session metadata key (for authenticated padding)
@@ -76,18 +90,33 @@ impl MetadataProtection {
         self
     }
 
+    /// Select the padding tag construction (both peers must agree)
+    pub fn with_padding_mac(mut self, mac: PaddingMac) -> Self {
+        self.padding_mac = mac;
+        self
+    }
+
     /// Apply metadata protection to message
     pub fn protect_message(&self, message: &[u8]) -> B4aeResult<Vec<u8>> {
         let mut protected = message.to_vec();
 
         // Apply padding
         if self.level.padding_enabled() {
-            protected = padding::apply_padding(&protected, self.config.padding_block_size)?;
+            protected = match self.config.padding_scheme {
+                PaddingScheme::Block => {
+                    padding::apply_padding(&protected, self.config.padding_block_size)?
+                }
+                PaddingScheme::Padme => {
+                    // Reserve room for the tag so the final size is a PADME bucket
+                    let reserved = if self.metadata_key.is_some() { PADDING_TAG_SIZE } else { 0 };
+                    padding::apply_padme_padding(&protected, reserved)?
+                }
+            };
         }
 
         // Append MAC when metadata_key available (authenticates padding)
         if let Some(ref key) = self.metadata_key {
-            let tag = compute_padding_tag(key, &protected);
+            let tag = compute_padding_tag(self.padding_mac, key, &protected);
             protected.extend_from_slice(&tag);
         }
 
@@ -100,22 +129,25 @@ impl MetadataProtection {
 
         // Verify and strip MAC when metadata_key available
         if let Some(ref key) = self.metadata_key {
-            if message.len() < 32 {
-                return Err(B4aeError::CryptoError("Message too short for metadata tag".to_string()));
+            if message.len() < PADDING_TAG_SIZE {
+                return Err(B4aeError::CryptoError(CryptoError::InvalidInput("Message too short for metadata tag".to_string())));
             }
-            let tag_len = 32;
+            let tag_len = PADDING_TAG_SIZE;
             let (payload, tag) = message.split_at(message.len() - tag_len);
-            let expected = compute_padding_tag(key, payload);
-            let tag_arr: [u8; 32] = tag.try_into().map_err(|_| B4aeError::CryptoError("Tag size mismatch".to_string()))?;
+            let expected = compute_padding_tag(self.padding_mac, key, payload);
+            let tag_arr: [u8; 32] = tag.try_into().map_err(|_| B4aeError::CryptoError(CryptoError::InvalidInput("Tag size mismatch".to_string())))?;
             if bool::from(tag_arr.ct_eq(&expected)) == false {
-                return Err(B4aeError::CryptoError("Metadata protection tag verification failed".to_string()));
+                return Err(B4aeError::CryptoError(CryptoError::AuthenticationFailed));
             }
             message = payload.to_vec();
         }
 
         // Remove padding
         if self.level.padding_enabled() {
-            message = padding::remove_padding(&message)?;
+            message = match self.config.padding_scheme {
+                PaddingScheme::Block => padding::remove_padding(&message)?,
+                PaddingScheme::Padme => padding::remove_padme_padding(&message)?,
+            };
         }
 
         Ok(message)
@@ -124,7 +156,29 @@ impl MetadataProtection {
     /// Get timing delay for obfuscation
     pub fn get_timing_delay_ms(&self) -> u64 {
         if self.level.timing_enabled() {
-            timing::calculate_delay(0, self.config.max_timing_delay_ms)
+            timing::calculate_delay_with(
+                self.config.timing_distribution,
+                self.config.max_timing_delay_ms,
+            )
+        } else {
+            0
+        }
+    }
+
+    /// Get timing delay for a message of `size` bytes
+    ///
+    /// Like [`get_timing_delay_ms`](Self::get_timing_delay_ms), but the window
+    /// widens by `timing_delay_per_kib_ms` per KiB so transmission time does
+    /// not reveal size (see [`timing::size_delay_window_ms`]).
+    pub fn get_timing_delay_for_size_ms(&self, size: usize) -> u64 {
+        if self.level.timing_enabled() {
+            let window = timing::size_delay_window_ms(
+                size,
+                self.config.max_timing_delay_ms,
+                self.config.timing_delay_per_kib_ms,
+                self.config.max_size_timing_delay_ms,
+            );
+            timing::calculate_delay_with(self.config.timing_distribution, window)
         } else {
             0
         }
@@ -141,13 +195,27 @@ impl MetadataProtection {
     }
 }
 
+/// Size of the padding authentication tag appended by `protect_message`
+const PADDING_TAG_SIZE: usize = 32;
+
 /// Compute 32-byte MAC for padded message (padding authentication)
-fn compute_padding_tag(key: &[u8], message: &[u8]) -> [u8; 32] {
-    let mut hasher = Sha3_256::new();
-    hasher.update(key);
-    hasher.update(message);
+fn compute_padding_tag(mac: PaddingMac, key: &[u8], message: &[u8]) -> [u8; 32] {
     let mut out = [0u8; 32];
-    out.copy_from_slice(&hasher.finalize());
+    match mac {
+        PaddingMac::Sha3Prefix => {
+            let mut hasher = Sha3_256::new();
+            hasher.update(key);
+            hasher.update(message);
+            out.copy_from_slice(&hasher.finalize());
+        }
+        PaddingMac::HmacSha3 => {
+            // HMAC accepts keys of any length
+            let mut hmac = <Hmac<Sha3_256> as Mac>::new_from_slice(key)
+                .expect("HMAC accepts any key length");
+            hmac.update(message);
+            out.copy_from_slice(&hmac.finalize().into_bytes());
+        }
+    }
     out
 }
 
@@ -220,7 +288,7 @@ impl MetadataProtectionConfig {
     /// Returns `CryptoError::InvalidInput` if:
     /// - `cover_traffic_rate` is not in the range [0.0, 1.0]
     /// - `timing_delay_min_ms` > `timing_delay_max_ms`
-    /// - `target_rate_msgs_per_sec` ≤ 0.0 when `constant_rate_mode` is enabled
+    /// - `target_rate_msgs_per_sec` is ≤ 0.0 or not finite when `constant_rate_mode` is enabled
     ///
     /// # Examples
     ///
@@ -258,10 +326,11 @@ impl MetadataProtectionConfig {
         }
 
         // Validate target rate when constant-rate mode is enabled
-        if self.constant_rate_mode && self.target_rate_msgs_per_sec <= 0.0 {
+        let rate = self.target_rate_msgs_per_sec;
+        if self.constant_rate_mode && !(rate > 0.0 && rate.is_finite()) {
             return Err(CryptoError::InvalidInput(
                 format!(
-                    "target_rate_msgs_per_sec must be > 0.0 when constant_rate_mode is enabled, got {}",
+                    "target_rate_msgs_per_sec must be finite and > 0.0 when constant_rate_mode is enabled, got {}",
                     self.target_rate_msgs_per_sec
                 )
             ));
@@ -379,6 +448,109 @@ mod tests {
         assert!(protection.unprotect_message(&tampered).is_err());
     }
 
+    #[test]
+    fn test_hmac_padding_mac_detects_tampering() {
+        let key = [0x42u8; 32];
+        let protection = MetadataProtection::new(ProtocolConfig::default(), ProtectionLevel::Standard)
+            .with_metadata_key(&key)
+            .with_padding_mac(PaddingMac::HmacSha3);
+
+        let message = b"Hello, B4AE with HMAC!";
+        let protected = protection.protect_message(message).unwrap();
+        assert_eq!(protection.unprotect_message(&protected).unwrap(), message);
+
+        for i in [0, protected.len() / 2, protected.len() - 1] {
+            let mut tampered = protected.clone();
+            tampered[i] ^= 0x01;
+            assert!(protection.unprotect_message(&tampered).is_err());
+        }
+
+        // Tag from one construction must not verify under the other
+        let legacy = MetadataProtection::new(ProtocolConfig::default(), ProtectionLevel::Standard)
+            .with_metadata_key(&key);
+        assert!(legacy.unprotect_message(&protected).is_err());
+    }
+
+    #[test]
+    fn test_hmac_padding_mac_differs_from_legacy() {
+        let key = [0x42u8; 32];
+        let message = b"padded payload";
+        let legacy = compute_padding_tag(PaddingMac::Sha3Prefix, &key, message);
+        let hmac = compute_padding_tag(PaddingMac::HmacSha3, &key, message);
+        assert_ne!(legacy, hmac);
+        assert_eq!(hmac.len(), PADDING_TAG_SIZE);
+        assert_eq!(hmac, compute_padding_tag(PaddingMac::HmacSha3, &key, message));
+    }
+
+    #[test]
+    fn test_timing_delay_uses_distribution() {
+        let config = ProtocolConfig {
+            max_timing_delay_ms: 300,
+            timing_distribution: timing::DelayDistribution::Poisson { mean: 10_000.0 },
+            ..ProtocolConfig::default()
+        };
+        let protection = MetadataProtection::new(config.clone(), ProtectionLevel::Standard);
+        assert_eq!(protection.get_timing_delay_ms(), 300);
+
+        let protection = MetadataProtection::new(config, ProtectionLevel::Basic);
+        assert_eq!(protection.get_timing_delay_ms(), 0);
+    }
+
+    #[test]
+    fn test_timing_delay_for_size_scales_window() {
+        let config = ProtocolConfig {
+            max_timing_delay_ms: 300,
+            timing_delay_per_kib_ms: 100,
+            max_size_timing_delay_ms: 5_000,
+            timing_distribution: timing::DelayDistribution::Poisson { mean: 1_000_000.0 },
+            ..ProtocolConfig::default()
+        };
+        // The saturating distribution lands on the window's upper bound
+        let protection = MetadataProtection::new(config, ProtectionLevel::Standard);
+        assert_eq!(protection.get_timing_delay_for_size_ms(0), 300);
+        assert_eq!(protection.get_timing_delay_for_size_ms(4096), 700);
+        assert_eq!(protection.get_timing_delay_for_size_ms(usize::MAX), 5_000);
+
+        let protection = MetadataProtection::new(ProtocolConfig::default(), ProtectionLevel::Basic);
+        assert_eq!(protection.get_timing_delay_for_size_ms(4096), 0);
+    }
+
+    #[test]
+    fn test_padme_scheme_shares_bucket() {
+        let config = ProtocolConfig {
+            padding_scheme: PaddingScheme::Padme,
+            ..ProtocolConfig::default()
+        };
+        let key = [0x42u8; 32];
+        let protection = MetadataProtection::new(config, ProtectionLevel::Standard)
+            .with_metadata_key(&key);
+
+        let mut sizes = Vec::new();
+        for len in [1000usize, 1024, 1100] {
+            let message: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let protected = protection.protect_message(&message).unwrap();
+            sizes.push(protected.len());
+            assert_eq!(protection.unprotect_message(&protected).unwrap(), message);
+        }
+        assert_eq!(sizes, vec![2048, 2048, 2048]);
+    }
+
+    #[test]
+    fn test_padme_scheme_mac_covers_length() {
+        let config = ProtocolConfig {
+            padding_scheme: PaddingScheme::Padme,
+            ..ProtocolConfig::default()
+        };
+        let key = [0x42u8; 32];
+        let protection = MetadataProtection::new(config, ProtectionLevel::Basic)
+            .with_metadata_key(&key);
+
+        let protected = protection.protect_message(b"short").unwrap();
+        let mut tampered = protected.clone();
+        tampered[3] ^= 0x01; // length header
+        assert!(protection.unprotect_message(&tampered).is_err());
+    }
+
     // Tests for MetadataProtectionConfig
 
     #[test]
@@ -494,6 +666,18 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_config_invalid_target_rate_non_finite_with_constant_rate_mode() {
+        for rate in [f64::NAN, f64::INFINITY] {
+            let config = MetadataProtectionConfig {
+                constant_rate_mode: true,
+                target_rate_msgs_per_sec: rate,
+                ..Default::default()
+            };
+            assert!(config.validate().is_err());
+        }
+    }
+
     #[test]
     fn test_config_valid_target_rate_zero_without_constant_rate_mode() {
         let config = MetadataProtectionConfig {
@@ -1,5 +1,5 @@
 // B4AE Traffic Obfuscation Implementation
-// Dummy traffic generation and pattern obfuscation
+// Dummy traffic generation, pattern obfuscation and traffic morphing
 
 use crate::crypto::random::{fill_random, random_range};
 use crate::error::{B4aeError, B4aeResult};
@@ -98,7 +98,7 @@ impl DummyTrafficGenerator {
     fn generate_noise(&self, size: usize) -> B4aeResult<Vec<u8>> {
         let mut dummy = vec![0u8; size];
         fill_random(&mut dummy)
-            .map_err(|e| B4aeError::CryptoError(format!("Random generation failed: {}", e)))?;
+            .map_err(B4aeError::CryptoError)?;
         Ok(dummy)
     }
 
@@ -110,7 +110,7 @@ impl DummyTrafficGenerator {
         // Add header-like structure (16 bytes)
         let mut header = vec![0u8; 16.min(size)];
         fill_random(&mut header)
-            .map_err(|e| B4aeError::CryptoError(format!("Random generation failed: {}", e)))?;
+            .map_err(B4aeError::CryptoError)?;
         dummy.extend_from_slice(&header);
 
         if size > 16 {
@@ -120,7 +120,7 @@ impl DummyTrafficGenerator {
             
             // Mix random data with some patterns
             fill_random(&mut payload)
-                .map_err(|e| B4aeError::CryptoError(format!("Random generation failed: {}", e)))?;
+                .map_err(B4aeError::CryptoError)?;
             
             // Add some zero blocks to mimic encrypted padding
             if payload_size > 64 {
@@ -139,7 +139,7 @@ impl DummyTrafficGenerator {
         // Cover traffic looks like encrypted messages
         let mut dummy = vec![0u8; size];
         fill_random(&mut dummy)
-            .map_err(|e| B4aeError::CryptoError(format!("Random generation failed: {}", e)))?;
+            .map_err(B4aeError::CryptoError)?;
 
         let timestamp = time::current_time_secs();
         
@@ -271,6 +271,136 @@ impl Default for TrafficPattern {
     }
 }
 
+/// Common TLS 1.3 application-data record sizes on the wire (size, weight):
+/// small control records, MTU-bounded records and full 16 KiB records.
+const TLS13_RECORD_SIZES: &[(usize, u32)] = &[(122, 2), (1369, 5), (16406, 3)];
+
+/// Record marker inside the morphed stream; anything else ends the stream
+const MORPH_RECORD: u8 = 0x01;
+/// Per-record header: marker + u32 BE length
+const MORPH_HEADER_SIZE: usize = 5;
+
+/// Largest chunk size a [`MorphProfile`] may emit; each chunk is encrypted
+/// as one message, so this is [`crate::MAX_MESSAGE_SIZE`]
+pub const MAX_MORPH_CHUNK_SIZE: usize = crate::MAX_MESSAGE_SIZE;
+
+/// Target chunk size distribution for [`TrafficMorpher`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MorphProfile {
+    /// TLS 1.3 application-data record sizes
+    Tls13,
+    /// User-supplied histogram of (chunk size, weight); sizes must be
+    /// 1..=[`MAX_MORPH_CHUNK_SIZE`]
+    Histogram(Vec<(usize, u32)>),
+}
+
+impl MorphProfile {
+    fn buckets(&self) -> B4aeResult<&[(usize, u32)]> {
+        let buckets = match self {
+            MorphProfile::Tls13 => TLS13_RECORD_SIZES,
+            MorphProfile::Histogram(h) => h.as_slice(),
+        };
+        if buckets.iter().any(|&(size, _)| size == 0) {
+            return Err(B4aeError::InvalidInput("Morph profile contains zero-sized chunk".to_string()));
+        }
+        if let Some(&(size, _)) = buckets.iter().find(|&&(size, _)| size > MAX_MORPH_CHUNK_SIZE) {
+            return Err(B4aeError::InvalidInput(format!(
+                "Morph profile chunk size {} exceeds {}",
+                size, MAX_MORPH_CHUNK_SIZE
+            )));
+        }
+        if buckets.iter().all(|&(_, weight)| weight == 0) {
+            return Err(B4aeError::InvalidInput("Morph profile has no weighted chunk sizes".to_string()));
+        }
+        Ok(buckets)
+    }
+
+    /// Chunk sizes this profile can emit
+    pub fn sizes(&self) -> Vec<usize> {
+        self.buckets()
+            .map(|b| b.iter().filter(|&&(_, w)| w > 0).map(|&(s, _)| s).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Weighted pick from `buckets`, restricted to sizes >= `min` when any exist
+fn sample_chunk_size(buckets: &[(usize, u32)], min: usize) -> usize {
+    let fits = buckets.iter().any(|&(s, w)| w > 0 && s >= min);
+    let candidates = buckets.iter().filter(|&&(s, w)| w > 0 && (!fits || s >= min));
+    let total: u64 = candidates.clone().map(|&(_, w)| w as u64).sum();
+    let mut pick = random_range(total);
+    for &(size, weight) in candidates {
+        if pick < weight as u64 {
+            return size;
+        }
+        pick -= weight as u64;
+    }
+    unreachable!("weighted pick within total")
+}
+
+/// Traffic morpher reshaping payloads to a target chunk size distribution.
+///
+/// Payloads are serialized as `[0x01][len u32 BE][payload]` records into one
+/// stream, which is cut into chunks sampled from the profile; the tail is
+/// zero-padded. Large messages are split across chunks and small ones are
+/// coalesced. Encrypt each chunk before sending so the record headers stay
+/// inside the authenticated payload.
+pub struct TrafficMorpher;
+
+impl TrafficMorpher {
+    /// Morph `payloads` into chunks whose sizes all belong to `profile`
+    pub fn morph(payloads: &[Vec<u8>], profile: &MorphProfile) -> B4aeResult<Vec<Vec<u8>>> {
+        let buckets = profile.buckets()?;
+
+        let total: usize = payloads.iter().map(|p| MORPH_HEADER_SIZE + p.len()).sum();
+        let mut stream = Vec::with_capacity(total);
+        for payload in payloads {
+            let len = u32::try_from(payload.len())
+                .map_err(|_| B4aeError::InvalidInput("Payload too large to morph".to_string()))?;
+            stream.push(MORPH_RECORD);
+            stream.extend_from_slice(&len.to_be_bytes());
+            stream.extend_from_slice(payload);
+        }
+
+        let mut chunks = Vec::new();
+        let mut offset = 0;
+        while offset < stream.len() {
+            let remaining = stream.len() - offset;
+            let size = sample_chunk_size(buckets, remaining);
+            let take = size.min(remaining);
+            let mut chunk = Vec::with_capacity(size);
+            chunk.extend_from_slice(&stream[offset..offset + take]);
+            chunk.resize(size, 0);
+            chunks.push(chunk);
+            offset += take;
+        }
+        Ok(chunks)
+    }
+
+    /// Reconstruct the original payload boundaries from morphed chunks
+    pub fn demorph(chunks: &[Vec<u8>]) -> B4aeResult<Vec<Vec<u8>>> {
+        let stream: Vec<u8> = chunks.concat();
+        let mut payloads = Vec::new();
+        let mut offset = 0;
+        while offset < stream.len() && stream[offset] == MORPH_RECORD {
+            let header = stream
+                .get(offset + 1..offset + MORPH_HEADER_SIZE)
+                .ok_or_else(|| B4aeError::ProtocolError("Truncated morph record header".to_string()))?;
+            let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+            let start = offset + MORPH_HEADER_SIZE;
+            let payload = stream
+                .get(start..start + len)
+                .ok_or_else(|| B4aeError::ProtocolError("Truncated morph record".to_string()))?;
+            payloads.push(payload.to_vec());
+            offset = start + len;
+        }
+        if stream[offset..].iter().any(|&b| b != 0) {
+            return Err(B4aeError::ProtocolError("Invalid morph padding".to_string()));
+        }
+        Ok(payloads)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -373,4 +503,47 @@ mod tests {
             assert!(!generator.should_generate());
         }
     }
+
+    #[test]
+    fn test_morph_bursty_input_matches_profile() {
+        // Burst of tiny messages followed by a large one and an empty one
+        let mut payloads: Vec<Vec<u8>> = (0..20).map(|i| vec![i as u8; 10 + i]).collect();
+        payloads.push((0..40_000).map(|i| (i % 251) as u8).collect());
+        payloads.push(Vec::new());
+
+        for profile in [
+            MorphProfile::Tls13,
+            MorphProfile::Histogram(vec![(256, 1), (1024, 1)]),
+        ] {
+            let sizes = profile.sizes();
+            let chunks = TrafficMorpher::morph(&payloads, &profile).unwrap();
+            assert!(chunks.iter().all(|c| sizes.contains(&c.len())));
+            assert_eq!(TrafficMorpher::demorph(&chunks).unwrap(), payloads);
+        }
+    }
+
+    #[test]
+    fn test_morph_invalid_profile() {
+        let payloads = vec![b"hello".to_vec()];
+        assert!(TrafficMorpher::morph(&payloads, &MorphProfile::Histogram(vec![])).is_err());
+        assert!(TrafficMorpher::morph(&payloads, &MorphProfile::Histogram(vec![(0, 1)])).is_err());
+        assert!(TrafficMorpher::morph(&payloads, &MorphProfile::Histogram(vec![(512, 0)])).is_err());
+        for size in [MAX_MORPH_CHUNK_SIZE + 1, usize::MAX] {
+            assert!(matches!(
+                TrafficMorpher::morph(&payloads, &MorphProfile::Histogram(vec![(512, 1), (size, 1)])),
+                Err(B4aeError::InvalidInput(_))
+            ));
+        }
+        let largest = MorphProfile::Histogram(vec![(MAX_MORPH_CHUNK_SIZE, 1)]);
+        assert_eq!(TrafficMorpher::morph(&payloads, &largest).unwrap()[0].len(), MAX_MORPH_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_demorph_rejects_truncation() {
+        let payloads = vec![vec![0xAB; 3000]];
+        let profile = MorphProfile::Histogram(vec![(1000, 1)]);
+        let chunks = TrafficMorpher::morph(&payloads, &profile).unwrap();
+        assert_eq!(chunks.len(), 4);
+        assert!(TrafficMorpher::demorph(&chunks[..2]).is_err());
+    }
 }
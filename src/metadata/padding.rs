@@ -1,8 +1,12 @@
 // B4AE Traffic Padding Implementation
-// PKCS#7-style padding with configurable block sizes
+// PKCS#7-style padding with configurable block sizes, and PADME buckets
 
+use crate::crypto::padding::{PadmeConfig, PadmePadding};
 use crate::error::{B4aeError, B4aeResult};
 
+/// Size of the big-endian length header in PADME-padded messages
+pub const PADME_HEADER_SIZE: usize = 4;
+
 /// Apply PKCS#7-style padding to message
 /// 
 /// Pads the message to the nearest multiple of block_size.
@@ -114,7 +118,7 @@ pub fn apply_random_padding(message: &[u8], min_size: usize, max_size: usize) ->
 
     let mut padding_bytes = vec![0u8; padding_needed];
     crate::crypto::random::fill_random(&mut padding_bytes)
-        .map_err(|e| B4aeError::CryptoError(format!("Random generation failed: {}", e)))?;
+        .map_err(B4aeError::CryptoError)?;
     padded.extend_from_slice(&padding_bytes);
 
     padded.extend_from_slice(&(padding_needed as u16).to_be_bytes());
@@ -138,6 +142,49 @@ pub fn remove_random_padding(padded: &[u8]) -> B4aeResult<Vec<u8>> {
     Ok(padded[..message_len].to_vec())
 }
 
+/// Apply PADME bucket padding.
+///
+/// Format: [original_len u32 BE][message][padding bytes]. The bucket is chosen
+/// so that the padded output plus `reserved` trailing bytes (e.g. a MAC tag)
+/// is exactly a PADME bucket size, so the observable length is the bucket.
+/// Padding bytes follow the `crypto::padding` convention (`padding_len % 256`).
+pub fn apply_padme_padding(message: &[u8], reserved: usize) -> B4aeResult<Vec<u8>> {
+    let padme = PadmePadding::new(PadmeConfig::default());
+    let needed = message.len() + PADME_HEADER_SIZE + reserved;
+    let bucket = padme
+        .find_bucket(needed)
+        .ok_or_else(|| B4aeError::InvalidInput("Message too large for PADME padding".to_string()))?;
+    let padded_len = bucket - reserved;
+    let padding_len = padded_len - PADME_HEADER_SIZE - message.len();
+
+    let mut padded = Vec::with_capacity(padded_len);
+    padded.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    padded.extend_from_slice(message);
+    padded.resize(padded_len, (padding_len % 256) as u8);
+    Ok(padded)
+}
+
+/// Remove PADME bucket padding produced by `apply_padme_padding`.
+pub fn remove_padme_padding(padded: &[u8]) -> B4aeResult<Vec<u8>> {
+    if padded.len() < PADME_HEADER_SIZE {
+        return Err(B4aeError::InvalidInput("PADME message too short".to_string()));
+    }
+    let (header, body) = padded.split_at(PADME_HEADER_SIZE);
+    let message_len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    if message_len > body.len() {
+        return Err(B4aeError::InvalidInput("Invalid PADME length".to_string()));
+    }
+    let padding_len = body.len() - message_len;
+    let padding_byte = (padding_len % 256) as u8;
+    let valid = body[message_len..]
+        .iter()
+        .fold(0u8, |acc, &b| acc | (b ^ padding_byte));
+    if valid != 0 {
+        return Err(B4aeError::InvalidInput("Invalid PADME padding bytes".to_string()));
+    }
+    Ok(body[..message_len].to_vec())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,4 +260,31 @@ mod tests {
         let unpadded = remove_padding(&padded).unwrap();
         assert_eq!(&unpadded, &msg);
     }
+
+    #[test]
+    fn test_padme_padding_roundtrip() {
+        let message = b"Hello, PADME!";
+        let padded = apply_padme_padding(message, 0).unwrap();
+        assert_eq!(padded.len(), 512);
+        assert_eq!(remove_padme_padding(&padded).unwrap(), message);
+
+        // Reserved bytes are subtracted so padded + reserved == bucket
+        let padded = apply_padme_padding(message, 32).unwrap();
+        assert_eq!(padded.len() + 32, 512);
+        assert_eq!(remove_padme_padding(&padded).unwrap(), message);
+    }
+
+    #[test]
+    fn test_padme_padding_invalid() {
+        let mut padded = apply_padme_padding(b"abc", 0).unwrap();
+        let last = padded.len() - 1;
+        padded[last] ^= 1;
+        assert!(remove_padme_padding(&padded).is_err());
+
+        let mut padded = apply_padme_padding(b"abc", 0).unwrap();
+        padded[..4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(remove_padme_padding(&padded).is_err());
+
+        assert!(apply_padme_padding(&vec![0u8; 65536], 0).is_err());
+    }
 }
@@ -1,8 +1,13 @@
 // B4AE Timing Obfuscation Implementation
 // Random delays to prevent timing analysis attacks
 
-use crate::crypto::random::random_range;
-use std::time::Duration;
+use crate::crypto::random::{fill_random, random_range, random_u64};
+use crate::crypto::{CryptoError, CryptoResult};
+use crate::metadata::MetadataProtectionConfig;
+use crate::time::{Clock, SystemClock};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Timing obfuscator for adding random delays to messages.
 ///
@@ -156,6 +161,26 @@ pub fn calculate_delay(min_ms: u64, max_ms: u64) -> u64 {
     min_ms + random_range(range)
 }
 
+/// Upper bound of the delay window for a message of `size` bytes
+///
+/// The window grows by `per_kib_ms` for every started KiB on top of
+/// `base_max_ms`, so large messages (which take longer on the wire) get a
+/// proportionally wider window. Clamped to `ceiling_ms`.
+pub fn size_delay_window_ms(size: usize, base_max_ms: u64, per_kib_ms: u64, ceiling_ms: u64) -> u64 {
+    let kib = (size as u64).div_ceil(1024);
+    base_max_ms
+        .saturating_add(kib.saturating_mul(per_kib_ms))
+        .min(ceiling_ms)
+}
+
+/// Calculate random delay scaled with message size
+///
+/// Uniform in [0, window) where the window is
+/// [`size_delay_window_ms`]`(size, base_max_ms, per_kib_ms, ceiling_ms)`.
+pub fn calculate_delay_for_size(size: usize, base_max_ms: u64, per_kib_ms: u64, ceiling_ms: u64) -> u64 {
+    calculate_delay(0, size_delay_window_ms(size, base_max_ms, per_kib_ms, ceiling_ms))
+}
+
 /// Calculate delay with exponential distribution
 /// 
 /// Provides more natural-looking delays that mimic human behavior.
@@ -197,6 +222,75 @@ pub fn calculate_normal_delay(mean_ms: u64, std_dev_ms: u64, max_ms: u64) -> u64
     delay_ms.min(max_ms)
 }
 
+/// Delay distribution for `calculate_delay_with`
+///
+/// Uniform delays are easy to fingerprint; exponential and Poisson delays look
+/// more like real network and user traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DelayDistribution {
+    /// Uniform delay in [0, max)
+    #[default]
+    Uniform,
+    /// Exponential delay with rate `lambda` per millisecond (mean = 1/lambda ms)
+    Exponential {
+        /// Rate parameter (1/ms), must be > 0
+        lambda: f64,
+    },
+    /// Poisson-distributed delay with the given mean in milliseconds
+    Poisson {
+        /// Mean delay in milliseconds, must be > 0
+        mean: f64,
+    },
+}
+
+/// Uniform random f64 in [0, 1) with 53 bits of precision
+fn random_unit() -> f64 {
+    (random_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Sample Poisson(mean) using Knuth's method, stopping once `cap` is exceeded.
+///
+/// Large means are split into chunks (sum of Poissons is Poisson) so that
+/// `exp(-mean)` never underflows.
+fn sample_poisson(mean: f64, cap: u64) -> u64 {
+    const CHUNK: f64 = 30.0;
+    let mut remaining = mean;
+    let mut count = 0u64;
+    while remaining > 0.0 && count <= cap {
+        let step = remaining.min(CHUNK);
+        remaining -= step;
+        let limit = (-step).exp();
+        let mut p = random_unit();
+        while p > limit && count <= cap {
+            count += 1;
+            p *= random_unit();
+        }
+    }
+    count
+}
+
+/// Calculate delay sampled from `dist`, clamped to `max_ms`.
+///
+/// Uses the crate CSPRNG. Invalid parameters (lambda or mean <= 0) yield 0.
+pub fn calculate_delay_with(dist: DelayDistribution, max_ms: u64) -> u64 {
+    match dist {
+        DelayDistribution::Uniform => calculate_delay(0, max_ms),
+        DelayDistribution::Exponential { lambda } => {
+            if lambda <= 0.0 || lambda.is_nan() {
+                return 0;
+            }
+            let delay = -(1.0 - random_unit()).ln() / lambda;
+            (delay as u64).min(max_ms)
+        }
+        DelayDistribution::Poisson { mean } => {
+            if mean <= 0.0 || mean.is_nan() {
+                return 0;
+            }
+            sample_poisson(mean, max_ms).min(max_ms)
+        }
+    }
+}
+
 /// Timing strategy for different scenarios
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TimingStrategy {
@@ -381,6 +475,189 @@ impl AdaptiveTiming {
     }
 }
 
+/// Bound on real messages waiting in a [`TokenBucketScheduler`]
+pub const MAX_SCHEDULED_MESSAGES: usize = 1024;
+
+/// Length of cover frames sent before the first real message
+pub const DEFAULT_COVER_FRAME_LEN: usize = 256;
+
+/// Frame released by [`TokenBucketScheduler`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Payload {
+    /// Message passed to [`TokenBucketScheduler::submit`]
+    Real(Vec<u8>),
+    /// Random cover frame, the length of the last real message (or the
+    /// initial cover length before the first one)
+    Dummy(Vec<u8>),
+}
+
+impl Payload {
+    /// Whether this is a cover frame
+    pub fn is_dummy(&self) -> bool {
+        matches!(self, Payload::Dummy(_))
+    }
+
+    /// Payload bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Payload::Real(bytes) | Payload::Dummy(bytes) => bytes,
+        }
+    }
+}
+
+/// Constant-rate sender for `constant_rate_mode`
+///
+/// A token bucket of capacity one refilled every
+/// `1 / target_rate_msgs_per_sec`: at most one message leaves per tick, ticks
+/// lie on a fixed grid, and bursts wait in a FIFO queue. With cover traffic
+/// (`cover_traffic_rate > 0`) a tick that finds the queue empty releases a
+/// [`Payload::Dummy`], so the wire sees one frame per tick whatever the
+/// application does. Ticks the caller misses are skipped, not bunched up.
+/// At most [`MAX_SCHEDULED_MESSAGES`] wait at once; further submissions are
+/// rejected rather than queued without bound.
+///
+/// Unlike [`CoverTrafficDriver`](crate::metadata::cover_traffic::CoverTrafficDriver)
+/// this does no framing or padding; the caller owns both.
+pub struct TokenBucketScheduler {
+    period: Duration,
+    cover_traffic: bool,
+    queue: VecDeque<Vec<u8>>,
+    next_tick: Instant,
+    last_real_len: usize,
+    clock: Arc<dyn Clock>,
+}
+
+impl TokenBucketScheduler {
+    /// Create a scheduler from a config with `constant_rate_mode` enabled.
+    ///
+    /// The first tick is one period after creation.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CryptoError::InvalidInput` if the config is invalid,
+    /// constant-rate mode is disabled, or the target rate has no
+    /// representable non-zero period.
+    pub fn new(config: MetadataProtectionConfig) -> CryptoResult<Self> {
+        config.validate()?;
+        if !config.constant_rate_mode {
+            return Err(CryptoError::InvalidInput(
+                "TokenBucketScheduler requires constant_rate_mode".to_string(),
+            ));
+        }
+        let period = Duration::try_from_secs_f64(1.0 / config.target_rate_msgs_per_sec)
+            .ok()
+            .filter(|period| !period.is_zero())
+            .ok_or_else(|| {
+                CryptoError::InvalidInput(format!(
+                    "target_rate_msgs_per_sec out of range: {}",
+                    config.target_rate_msgs_per_sec
+                ))
+            })?;
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        Ok(Self {
+            period,
+            cover_traffic: config.cover_traffic_rate > 0.0,
+            queue: VecDeque::new(),
+            next_tick: clock.now_monotonic() + period,
+            last_real_len: DEFAULT_COVER_FRAME_LEN,
+            clock,
+        })
+    }
+
+    /// Takes ticks from `clock`; the first tick is one period from its present time
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.next_tick = clock.now_monotonic() + self.period;
+        self.clock = clock;
+        self
+    }
+
+    /// Length of cover frames sent before the first real message
+    /// (default [`DEFAULT_COVER_FRAME_LEN`])
+    pub fn with_initial_cover_len(mut self, len: usize) -> Self {
+        self.last_real_len = len;
+        self
+    }
+
+    /// Queue a real message for the next free tick
+    ///
+    /// # Errors
+    ///
+    /// Returns `CryptoError::InvalidInput` if [`MAX_SCHEDULED_MESSAGES`]
+    /// are already waiting.
+    pub fn submit(&mut self, payload: Vec<u8>) -> CryptoResult<()> {
+        if self.queue.len() >= MAX_SCHEDULED_MESSAGES {
+            return Err(CryptoError::InvalidInput(format!(
+                "Scheduler queue full ({} messages)",
+                MAX_SCHEDULED_MESSAGES
+            )));
+        }
+        self.queue.push_back(payload);
+        Ok(())
+    }
+
+    /// Number of real messages waiting
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Interval between releases
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Instant of the next tick
+    pub fn next_tick(&self) -> Instant {
+        self.next_tick
+    }
+
+    /// Release the frame for a due tick, if any
+    ///
+    /// Returns `None` when no tick is due, or when the due tick finds the
+    /// queue empty and cover traffic is off (the tick is consumed either way).
+    ///
+    /// # Errors
+    ///
+    /// Returns the RNG error if a cover frame cannot be generated; the tick
+    /// is not consumed, so the next call retries it.
+    pub fn try_next(&mut self) -> CryptoResult<Option<Payload>> {
+        let now = self.clock.now_monotonic();
+        if now < self.next_tick {
+            return Ok(None);
+        }
+        let payload = match self.queue.front() {
+            Some(_) => None,
+            None if self.cover_traffic => {
+                let mut dummy = vec![0u8; self.last_real_len];
+                fill_random(&mut dummy)?;
+                Some(Payload::Dummy(dummy))
+            }
+            None => None,
+        };
+
+        let missed = now.duration_since(self.next_tick).as_nanos() / self.period.as_nanos();
+        let skip = u32::try_from(missed.saturating_add(1)).unwrap_or(u32::MAX);
+        self.next_tick += self.period.saturating_mul(skip);
+
+        Ok(payload.or_else(|| {
+            let message = self.queue.pop_front()?;
+            self.last_real_len = message.len();
+            Some(Payload::Real(message))
+        }))
+    }
+
+    /// Wait for the next tick that releases a frame
+    #[cfg(feature = "tokio")]
+    pub async fn next(&mut self) -> CryptoResult<Payload> {
+        loop {
+            let wait = self.next_tick.saturating_duration_since(self.clock.now_monotonic());
+            tokio::time::sleep(wait).await;
+            if let Some(payload) = self.try_next()? {
+                return Ok(payload);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -396,6 +673,52 @@ mod tests {
         }
     }
 
+    fn empirical_mean(dist: DelayDistribution, max: u64, samples: usize) -> f64 {
+        let mut sum = 0u64;
+        for _ in 0..samples {
+            let delay = calculate_delay_with(dist, max);
+            assert!(delay <= max);
+            sum += delay;
+        }
+        sum as f64 / samples as f64
+    }
+
+    #[test]
+    fn test_delay_with_uniform_mean() {
+        let mean = empirical_mean(DelayDistribution::Uniform, 1000, 20_000);
+        assert!((mean - 500.0).abs() < 15.0, "uniform mean {}", mean);
+    }
+
+    #[test]
+    fn test_delay_with_exponential_mean() {
+        // lambda = 0.01/ms => mean 100ms (truncation to whole ms lowers it by ~0.5)
+        let dist = DelayDistribution::Exponential { lambda: 0.01 };
+        let mean = empirical_mean(dist, 100_000, 20_000);
+        assert!((mean - 100.0).abs() < 5.0, "exponential mean {}", mean);
+    }
+
+    #[test]
+    fn test_delay_with_poisson_mean() {
+        let dist = DelayDistribution::Poisson { mean: 50.0 };
+        let mean = empirical_mean(dist, 10_000, 20_000);
+        assert!((mean - 50.0).abs() < 1.0, "poisson mean {}", mean);
+
+        // Large mean exercises chunked sampling
+        let dist = DelayDistribution::Poisson { mean: 400.0 };
+        let mean = empirical_mean(dist, 10_000, 5_000);
+        assert!((mean - 400.0).abs() < 4.0, "poisson mean {}", mean);
+    }
+
+    #[test]
+    fn test_delay_with_clamps_and_invalid() {
+        let dist = DelayDistribution::Poisson { mean: 5000.0 };
+        for _ in 0..10 {
+            assert_eq!(calculate_delay_with(dist, 100), 100);
+        }
+        assert_eq!(calculate_delay_with(DelayDistribution::Exponential { lambda: 0.0 }, 100), 0);
+        assert_eq!(calculate_delay_with(DelayDistribution::Poisson { mean: -1.0 }, 100), 0);
+    }
+
     #[test]
     fn test_exponential_delay() {
         let lambda = 0.002;
@@ -463,6 +786,156 @@ mod tests {
         assert!(delay <= config.max_delay_ms);
     }
 
+    #[test]
+    fn test_size_delay_window_grows_with_size() {
+        assert_eq!(size_delay_window_ms(0, 100, 10, 30_000), 100);
+        assert_eq!(size_delay_window_ms(1, 100, 10, 30_000), 110);
+        assert_eq!(size_delay_window_ms(1024, 100, 10, 30_000), 110);
+        assert_eq!(size_delay_window_ms(1025, 100, 10, 30_000), 120);
+        assert_eq!(size_delay_window_ms(64 * 1024, 100, 10, 30_000), 740);
+        assert_eq!(size_delay_window_ms(usize::MAX, u64::MAX, u64::MAX, 30_000), 30_000);
+        assert_eq!(size_delay_window_ms(64 * 1024, 100, 10, 500), 500);
+    }
+
+    #[test]
+    fn test_delay_for_size_distribution() {
+        let max_observed = |size: usize| {
+            (0..2000)
+                .map(|_| {
+                    let delay = calculate_delay_for_size(size, 100, 10, 30_000);
+                    assert!(delay <= size_delay_window_ms(size, 100, 10, 30_000));
+                    delay
+                })
+                .max()
+                .unwrap()
+        };
+
+        // Small messages stay within the base window; large ones exceed it
+        assert!(max_observed(512) <= 110);
+        assert!(max_observed(64 * 1024) > 110);
+
+        for _ in 0..1000 {
+            assert!(calculate_delay_for_size(16 * 1024 * 1024, 100, 10, 1_000) <= 1_000);
+        }
+    }
+
+    fn constant_rate(rate: f64, cover_traffic_rate: f64) -> MetadataProtectionConfig {
+        MetadataProtectionConfig {
+            constant_rate_mode: true,
+            target_rate_msgs_per_sec: rate,
+            cover_traffic_rate,
+            ..MetadataProtectionConfig::default()
+        }
+    }
+
+    /// Step `clock` in 1ms increments, recording when frames are released
+    fn run_for(
+        scheduler: &mut TokenBucketScheduler,
+        clock: &crate::time::MockClock,
+        millis: u64,
+    ) -> Vec<(u64, Payload)> {
+        let mut released = Vec::new();
+        for t in 1..=millis {
+            clock.advance(Duration::from_millis(1));
+            if let Some(payload) = scheduler.try_next().unwrap() {
+                released.push((t, payload));
+            }
+        }
+        released
+    }
+
+    #[test]
+    fn test_token_bucket_requires_constant_rate() {
+        assert!(TokenBucketScheduler::new(MetadataProtectionConfig::default()).is_err());
+        assert!(TokenBucketScheduler::new(constant_rate(0.0, 0.0)).is_err());
+        // 1 / rate must be a representable, non-zero period
+        assert!(TokenBucketScheduler::new(constant_rate(1e-300, 0.0)).is_err());
+        assert!(TokenBucketScheduler::new(constant_rate(1e300, 0.0)).is_err());
+    }
+
+    #[test]
+    fn test_token_bucket_queue_is_bounded() {
+        let mut scheduler = TokenBucketScheduler::new(constant_rate(10.0, 0.0)).unwrap();
+        for _ in 0..MAX_SCHEDULED_MESSAGES {
+            scheduler.submit(vec![0; 8]).unwrap();
+        }
+        assert!(scheduler.submit(vec![0; 8]).is_err());
+        assert_eq!(scheduler.queued(), MAX_SCHEDULED_MESSAGES);
+    }
+
+    #[test]
+    fn test_token_bucket_cover_before_first_message() {
+        let clock = Arc::new(crate::time::MockClock::new(1_000));
+        let mut scheduler = TokenBucketScheduler::new(constant_rate(10.0, 1.0))
+            .unwrap()
+            .with_clock(clock.clone());
+        let released = run_for(&mut scheduler, &clock, 100);
+        assert_eq!(released[0].1.as_bytes().len(), DEFAULT_COVER_FRAME_LEN);
+
+        let mut scheduler = scheduler.with_clock(clock.clone()).with_initial_cover_len(64);
+        let released = run_for(&mut scheduler, &clock, 100);
+        assert!(released[0].1.is_dummy());
+        assert_eq!(released[0].1.as_bytes().len(), 64);
+    }
+
+    #[test]
+    fn test_token_bucket_constant_interval_under_bursts() {
+        let clock = Arc::new(crate::time::MockClock::new(1_000));
+        let mut scheduler = TokenBucketScheduler::new(constant_rate(10.0, 0.0))
+            .unwrap()
+            .with_clock(clock.clone());
+
+        // Burst of five, then a pause, then another burst of three
+        for i in 0..5u8 {
+            scheduler.submit(vec![i; 8]).unwrap();
+        }
+        let mut released = run_for(&mut scheduler, &clock, 700);
+        for i in 5..8u8 {
+            scheduler.submit(vec![i; 8]).unwrap();
+        }
+        released.extend(run_for(&mut scheduler, &clock, 500).into_iter().map(|(t, p)| (t + 700, p)));
+
+        let times: Vec<u64> = released.iter().map(|(t, _)| *t).collect();
+        assert_eq!(times, vec![100, 200, 300, 400, 500, 800, 900, 1000]);
+        let payloads: Vec<u8> = released.iter().map(|(_, p)| p.as_bytes()[0]).collect();
+        assert_eq!(payloads, (0..8).collect::<Vec<_>>());
+        assert!(released.iter().all(|(_, p)| !p.is_dummy()));
+        assert_eq!(scheduler.queued(), 0);
+    }
+
+    #[test]
+    fn test_token_bucket_cover_traffic_fills_ticks() {
+        let clock = Arc::new(crate::time::MockClock::new(1_000));
+        let mut scheduler = TokenBucketScheduler::new(constant_rate(20.0, 0.5))
+            .unwrap()
+            .with_clock(clock.clone());
+        scheduler.submit(b"real".to_vec()).unwrap();
+
+        let released = run_for(&mut scheduler, &clock, 500);
+        let times: Vec<u64> = released.iter().map(|(t, _)| *t).collect();
+        assert_eq!(times, (1..=10).map(|k| k * 50).collect::<Vec<_>>());
+        assert_eq!(released[0].1, Payload::Real(b"real".to_vec()));
+        assert!(released[1..].iter().all(|(_, p)| p.is_dummy() && p.as_bytes().len() == 4));
+
+        // A late caller gets one frame, then the grid resumes
+        clock.advance(Duration::from_millis(175));
+        assert!(scheduler.try_next().unwrap().is_some());
+        assert!(scheduler.try_next().unwrap().is_none());
+        assert_eq!(scheduler.next_tick() - clock.now_monotonic(), Duration::from_millis(25));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_token_bucket_async_next() {
+        let mut scheduler = TokenBucketScheduler::new(constant_rate(100.0, 1.0)).unwrap();
+        scheduler.submit(b"first".to_vec()).unwrap();
+        scheduler.submit(b"second".to_vec()).unwrap();
+
+        assert_eq!(scheduler.next().await.unwrap(), Payload::Real(b"first".to_vec()));
+        assert_eq!(scheduler.next().await.unwrap(), Payload::Real(b"second".to_vec()));
+        assert!(scheduler.next().await.unwrap().is_dummy());
+    }
+
     // Tests for TimingObfuscator
 
     #[test]
@@ -3,9 +3,11 @@
 //! Real-time performance monitoring for production deployments
 
 use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant, SystemTime};
 use serde::{Serialize, Deserialize};
+use crate::telemetry::otel_span;
 
 /// Performance metrics for B4AE operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,10 +32,54 @@ pub struct PerformanceMetrics {
     pub error_rate: f64,
     /// Memory usage in bytes (if available)
     pub memory_usage_bytes: Option<u64>,
+    /// Latency distribution (reports saved before histograms load empty)
+    #[serde(default)]
+    pub latency_histogram: LatencyHistogram,
     /// Last updated timestamp
     pub last_updated: SystemTime,
 }
 
+/// Upper bounds of the latency histogram buckets, in microseconds
+///
+/// Spans 50μs (symmetric crypto) to 10s (handshakes over slow links).
+pub const LATENCY_BUCKETS_US: [u64; 17] = [
+    50, 100, 250, 500,
+    1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000,
+    1_000_000, 2_500_000, 5_000_000, 10_000_000,
+];
+
+/// Fixed-bucket latency histogram over [`LATENCY_BUCKETS_US`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyHistogram {
+    /// Per-bucket (non-cumulative) counts; one extra slot for `+Inf`
+    pub counts: Vec<u64>,
+}
+
+impl LatencyHistogram {
+    /// Record one observation
+    pub fn observe(&mut self, duration_us: u64) {
+        if self.counts.len() != LATENCY_BUCKETS_US.len() + 1 {
+            self.counts.resize(LATENCY_BUCKETS_US.len() + 1, 0);
+        }
+        let bucket = LATENCY_BUCKETS_US
+            .iter()
+            .position(|&bound| duration_us <= bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+        self.counts[bucket] += 1;
+    }
+
+    /// Cumulative counts per bucket bound, ending with `+Inf`
+    pub fn cumulative(&self) -> Vec<u64> {
+        let mut total = 0;
+        (0..=LATENCY_BUCKETS_US.len())
+            .map(|i| {
+                total += self.counts.get(i).copied().unwrap_or(0);
+                total
+            })
+            .collect()
+    }
+}
+
 impl PerformanceMetrics {
     /// Create new metrics for an operation
     pub fn new(operation: String) -> Self {
@@ -48,6 +94,7 @@ impl PerformanceMetrics {
             error_count: 0,
             error_rate: 0.0,
             memory_usage_bytes: None,
+            latency_histogram: LatencyHistogram::default(),
             last_updated: SystemTime::now(),
         }
     }
@@ -60,6 +107,7 @@ impl PerformanceMetrics {
         self.avg_time_us = self.total_time_us / self.count;
         self.min_time_us = self.min_time_us.min(duration_us);
         self.max_time_us = self.max_time_us.max(duration_us);
+        self.latency_histogram.observe(duration_us);
         
         // Calculate ops per second based on recent activity
         if let Ok(elapsed) = self.last_updated.elapsed() {
@@ -137,10 +185,13 @@ impl PerformanceMonitor {
     }
 
     /// Record operation timing
+    ///
+    /// With feature `otel`, `f` also runs inside a `b4ae.operation` span.
     pub fn record_operation<F, R>(&self, operation: &str, f: F) -> R
     where
         F: FnOnce() -> R,
     {
+        otel_span!("b4ae.operation", operation);
         if !self.is_enabled() {
             return f();
         }
@@ -162,6 +213,7 @@ impl PerformanceMonitor {
     where
         F: FnOnce() -> Result<R, Box<dyn std::error::Error>>,
     {
+        otel_span!("b4ae.operation", operation);
         if !self.is_enabled() {
             return f();
         }
@@ -211,6 +263,59 @@ impl PerformanceMonitor {
         }
     }
 
+    /// Render all metrics in the Prometheus text exposition format (0.0.4)
+    ///
+    /// Series are labelled by operation name, so encrypt/decrypt counts and
+    /// handshake durations appear as `operation="encrypt"` etc.:
+    ///
+    /// - `b4ae_operations_total` / `b4ae_operation_errors_total` (counters)
+    /// - `b4ae_operation_duration_seconds` (histogram)
+    /// - `b4ae_uptime_seconds` (gauge)
+    pub fn to_prometheus(&self) -> String {
+        let metrics = self.get_all_metrics();
+        let mut operations: Vec<&PerformanceMetrics> = metrics.values().collect();
+        operations.sort_by(|a, b| a.operation.cmp(&b.operation));
+
+        let mut out = String::new();
+        out.push_str("# HELP b4ae_operations_total Completed operations, including failed ones.\n");
+        out.push_str("# TYPE b4ae_operations_total counter\n");
+        for m in &operations {
+            let _ = writeln!(out, "b4ae_operations_total{{operation=\"{}\"}} {}", escape_label(&m.operation), m.count);
+        }
+
+        out.push_str("# HELP b4ae_operation_errors_total Failed operations.\n");
+        out.push_str("# TYPE b4ae_operation_errors_total counter\n");
+        for m in &operations {
+            let _ = writeln!(out, "b4ae_operation_errors_total{{operation=\"{}\"}} {}", escape_label(&m.operation), m.error_count);
+        }
+
+        out.push_str("# HELP b4ae_operation_duration_seconds Operation latency.\n");
+        out.push_str("# TYPE b4ae_operation_duration_seconds histogram\n");
+        for m in &operations {
+            let label = escape_label(&m.operation);
+            let cumulative = m.latency_histogram.cumulative();
+            for (bound, count) in LATENCY_BUCKETS_US.iter().zip(&cumulative) {
+                let _ = writeln!(
+                    out,
+                    "b4ae_operation_duration_seconds_bucket{{operation=\"{}\",le=\"{}\"}} {}",
+                    label,
+                    *bound as f64 / 1_000_000.0,
+                    count
+                );
+            }
+            // Reports loaded from before histograms existed have no buckets;
+            // +Inf must still equal _count
+            let _ = writeln!(out, "b4ae_operation_duration_seconds_bucket{{operation=\"{}\",le=\"+Inf\"}} {}", label, m.count);
+            let _ = writeln!(out, "b4ae_operation_duration_seconds_sum{{operation=\"{}\"}} {}", label, m.total_time_us as f64 / 1_000_000.0);
+            let _ = writeln!(out, "b4ae_operation_duration_seconds_count{{operation=\"{}\"}} {}", label, m.count);
+        }
+
+        out.push_str("# HELP b4ae_uptime_seconds Time since the monitor was created or reset.\n");
+        out.push_str("# TYPE b4ae_uptime_seconds gauge\n");
+        let _ = writeln!(out, "b4ae_uptime_seconds {}", self.uptime().as_secs_f64());
+        out
+    }
+
     /// Reset all metrics
     pub fn reset(&mut self) {
         self.metrics.write().unwrap().clear();
@@ -224,6 +329,11 @@ impl Default for PerformanceMonitor {
     }
 }
 
+/// Escape a Prometheus label value (backslash, double quote, newline)
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
 /// Comprehensive performance report
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PerformanceReport {
@@ -354,6 +464,49 @@ pub mod profiling {
     }
 }
 
+/// Prometheus scrape endpoint for axum-based services
+#[cfg(feature = "metrics-http")]
+pub mod http {
+    use super::PerformanceMonitor;
+    use axum::extract::State;
+    use axum::http::header;
+    use axum::response::IntoResponse;
+    use axum::routing::get;
+    use axum::Router;
+
+    /// Content type of the Prometheus text exposition format
+    pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+    /// Handler rendering [`PerformanceMonitor::to_prometheus`]
+    pub async fn metrics_handler(State(monitor): State<PerformanceMonitor>) -> impl IntoResponse {
+        ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], monitor.to_prometheus())
+    }
+
+    /// Router serving `GET /metrics`; merge it into the API router
+    pub fn metrics_router(monitor: PerformanceMonitor) -> Router {
+        Router::new()
+            .route("/metrics", get(metrics_handler))
+            .with_state(monitor)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_metrics_handler_serves_exposition() {
+            let monitor = PerformanceMonitor::new();
+            monitor.record_operation("encrypt", || ());
+
+            let response = metrics_handler(State(monitor)).await.into_response();
+            assert_eq!(response.headers()[header::CONTENT_TYPE], PROMETHEUS_CONTENT_TYPE);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert!(body.contains("b4ae_operations_total{operation=\"encrypt\"} 1"));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -406,4 +559,43 @@ mod tests {
         assert_eq!(report.total_errors, 0);
         assert!(report.operations.contains_key("report_test"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_prometheus_exposition() {
+        let monitor = PerformanceMonitor::new();
+        for _ in 0..3 {
+            monitor.record_operation("encrypt", || ());
+        }
+        monitor.record_operation("decrypt", || ());
+        let _: Result<(), Box<dyn std::error::Error>> =
+            monitor.record_operation_result("handshake", || Err("peer timeout".into()));
+
+        let text = monitor.to_prometheus();
+        assert!(text.contains("# TYPE b4ae_operations_total counter"));
+        assert!(text.contains("# TYPE b4ae_operation_duration_seconds histogram"));
+        assert!(text.contains("b4ae_operations_total{operation=\"encrypt\"} 3"));
+        assert!(text.contains("b4ae_operations_total{operation=\"decrypt\"} 1"));
+        assert!(text.contains("b4ae_operation_errors_total{operation=\"handshake\"} 1"));
+        assert!(text.contains("b4ae_uptime_seconds "));
+
+        // Buckets: one per bound plus +Inf, cumulative, +Inf == _count
+        let buckets: Vec<(String, u64)> = text
+            .lines()
+            .filter(|l| l.starts_with("b4ae_operation_duration_seconds_bucket{operation=\"encrypt\""))
+            .map(|l| {
+                let le = l.split("le=\"").nth(1).unwrap().split('"').next().unwrap().to_string();
+                (le, l.rsplit(' ').next().unwrap().parse().unwrap())
+            })
+            .collect();
+        assert_eq!(buckets.len(), LATENCY_BUCKETS_US.len() + 1);
+        assert_eq!(buckets.last().unwrap(), &("+Inf".to_string(), 3));
+        assert!(buckets.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(buckets[..buckets.len() - 1].iter().all(|(le, _)| le.parse::<f64>().is_ok()));
+        assert!(text.contains("b4ae_operation_duration_seconds_count{operation=\"encrypt\"} 3"));
+    }
+
+    #[test]
+    fn test_prometheus_label_escaping() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -3,17 +3,24 @@
 //! Three-way handshake with quantum-resistant key exchange.
 
 use crate::crypto::{CryptoError, CryptoResult};
+use crate::crypto::aes_gcm::{self, AesKey};
 use crate::crypto::hybrid::{HybridCiphertext};
 use crate::crypto::xeddsa::{DeniableHybridKeyPair, DeniableHybridPublicKey, DeniableHybridSignature, verify_deniable_hybrid};
+use crate::crypto::fingerprint;
+use crate::crypto::kyber::KyberKeyPair;
 use crate::crypto::hkdf;
 use crate::crypto::random;
 use crate::crypto::zkauth::{self, ZkChallenge, ZkProof, EXTENSION_TYPE_ZK_CHALLENGE, EXTENSION_TYPE_ZK_PROOF};
 use crate::protocol::PROTOCOL_VERSION;
+use crate::protocol::session::{ResumptionPsk, ResumptionTicket};
+#[cfg(feature = "v2_protocol")]
+use crate::protocol::v2::replay_protection::ReplayCache;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use crate::time;
 use subtle::ConstantTimeEq;
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 /// Handshake state machine (matches TLA+/Coq spec: Initiation, WaitingResponse, etc.)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -120,12 +127,19 @@ pub struct HandshakeConfig {
     pub zk_identity: Option<Arc<zkauth::ZkIdentity>>,
     /// Optional ZK verifier for responder (verifies initiator's proof)
     pub zk_verifier: Option<Arc<Mutex<zkauth::ZkVerifier>>>,
+    /// Optional PAKE session key mixed into the confirmation and master secret.
+    /// Both sides must set the same key or the handshake fails.
+    pub pake_key: Option<Arc<zkauth::PakeSessionKey>>,
     /// Optional HSM backend for signing (when available, ECDSA part uses HSM)
     #[cfg(feature = "hsm")]
     pub hsm: Option<Arc<dyn crate::hsm::HsmBackend>>,
     /// HSM key ID when hsm is configured
     #[cfg(feature = "hsm")]
     pub hsm_key_id: Option<String>,
+    /// Pinned peer identity (see [`identity_fingerprint`]); the handshake
+    /// fails with `CryptoError::FingerprintMismatch` if the peer signs with a
+    /// different identity key. `None` accepts any correctly signed key.
+    pub expected_peer_fingerprint: Option<[u8; 32]>,
 }
 
 impl std::fmt::Debug for HandshakeConfig {
@@ -137,6 +151,8 @@ impl std::fmt::Debug for HandshakeConfig {
             .field("extensions", &self.extensions)
             .field("zk_identity", &self.zk_identity.as_ref().map(|_| "Some"))
             .field("zk_verifier", &self.zk_verifier.as_ref().map(|_| "Some"))
+            .field("pake_key", &self.pake_key.as_ref().map(|_| "Some"))
+            .field("expected_peer_fingerprint", &self.expected_peer_fingerprint.map(hex::encode))
             .finish()
     }
 }
@@ -161,10 +177,12 @@ impl Default for HandshakeConfig {
             extensions: Vec::new(),
             zk_identity: None,
             zk_verifier: None,
+            pake_key: None,
             #[cfg(feature = "hsm")]
             hsm: None,
             #[cfg(feature = "hsm")]
             hsm_key_id: None,
+            expected_peer_fingerprint: None,
         }
     }
 }
@@ -209,12 +227,17 @@ pub struct HandshakeResult {
     pub peer_public_key: DeniableHybridPublicKey,
     /// Session ID (32 bytes).
     pub session_id: [u8; 32],
+    /// Algorithms the responder selected.
+    pub selected_algorithms: Vec<AlgorithmId>,
 }
 
 /// Handshake initiator (client)
 pub struct HandshakeInitiator {
     config: HandshakeConfig,
-    local_keypair: DeniableHybridKeyPair,
+    /// Long-term signing identity, shared across handshakes
+    local_keypair: Arc<DeniableHybridKeyPair>,
+    /// Per-handshake KEM key pair, so past sessions stay secret if the identity leaks
+    kem_keypair: KyberKeyPair,
     state: HandshakeState,
     client_random: [u8; 32],
     server_random: Option<[u8; 32]>,
@@ -223,12 +246,17 @@ pub struct HandshakeInitiator {
     start_time: u64,
     /// ZK challenge from responder (when using ZK auth)
     pending_zk_challenge: Option<ZkChallenge>,
+    /// Algorithms selected in the response (empty until it arrives)
+    selected_algorithms: Vec<AlgorithmId>,
 }
 
 /// Handshake responder (server)
 pub struct HandshakeResponder {
     config: HandshakeConfig,
-    local_keypair: DeniableHybridKeyPair,
+    /// Long-term signing identity, shared across handshakes
+    local_keypair: Arc<DeniableHybridKeyPair>,
+    /// Per-handshake KEM key pair, so past sessions stay secret if the identity leaks
+    kem_keypair: KyberKeyPair,
     state: HandshakeState,
     server_random: [u8; 32],
     client_random: Option<[u8; 32]>,
@@ -237,12 +265,22 @@ pub struct HandshakeResponder {
     start_time: u64,
     /// ZK challenge ID (when ZK verifier is used)
     pending_zk_challenge_id: Option<[u8; 16]>,
+    /// Algorithms selected in our response (empty until it is sent)
+    selected_algorithms: Vec<AlgorithmId>,
 }
 
 impl HandshakeInitiator {
     /// Create new handshake initiator.
     pub fn new(config: HandshakeConfig) -> CryptoResult<Self> {
-        let local_keypair = DeniableHybridKeyPair::generate()?;
+        Self::with_identity(config, Arc::new(DeniableHybridKeyPair::generate()?))
+    }
+
+    /// Create a handshake initiator that signs with a long-term identity key pair.
+    ///
+    /// Only the identity's signing keys are used; the KEM key pair is fresh
+    /// for every handshake.
+    pub fn with_identity(config: HandshakeConfig, local_keypair: Arc<DeniableHybridKeyPair>) -> CryptoResult<Self> {
+        let kem_keypair = crate::crypto::kyber::keypair()?;
         let mut client_random = [0u8; 32];
         random::fill_random(&mut client_random)?;
         
@@ -251,6 +289,7 @@ impl HandshakeInitiator {
         Ok(HandshakeInitiator {
             config,
             local_keypair,
+            kem_keypair,
             state: HandshakeState::Initiation,
             client_random,
             server_random: None,
@@ -258,6 +297,7 @@ impl HandshakeInitiator {
             peer_public_key: None,
             start_time,
             pending_zk_challenge: None,
+            selected_algorithms: Vec::new(),
         })
     }
 
@@ -268,7 +308,7 @@ impl HandshakeInitiator {
         }
 
         // Serialize public key - include all components
-        let public_key = self.local_keypair.public_key();
+        let public_key = self.local_public_key();
         let hybrid_public_key = serialize_deniable_public_key(&public_key);
 
         // Create message to sign
@@ -331,19 +371,21 @@ impl HandshakeInitiator {
         if !is_valid {
             return Err(CryptoError::VerificationFailed("Response signature verification failed".to_string()));
         }
+        check_pinned_fingerprint(&self.config, &peer_public_key)?;
 
         // Deserialize ciphertext manually
         let ciphertext = deserialize_ciphertext(&response.encrypted_shared_secret)?;
 
         // Decapsulate using Kyber secret key
         let shared_secret = crate::crypto::kyber::decapsulate(
-            self.local_keypair.kyber_secret_key(),
+            &self.kem_keypair.secret_key,
             &ciphertext.kyber_ciphertext
         )?;
 
         self.server_random = Some(response.server_random);
         self.shared_secret = Some(shared_secret.as_bytes().to_vec());
         self.peer_public_key = Some(peer_public_key);
+        self.selected_algorithms = response.selected_algorithms;
 
         // Extract ZK challenge if present and we have zk_identity
         if self.config.zk_identity.is_some() {
@@ -419,6 +461,7 @@ impl HandshakeInitiator {
             session_keys,
             peer_public_key,
             session_id,
+            selected_algorithms: self.selected_algorithms.clone(),
         })
     }
 
@@ -436,7 +479,7 @@ impl HandshakeInitiator {
 
         // Use hkdf::derive_key with correct API
         let confirmation = hkdf::derive_key(
-            &[shared_secret],
+            &bound_key_material(&self.config, shared_secret),
             b"handshake-confirmation",
             32
         )?;
@@ -451,7 +494,7 @@ impl HandshakeInitiator {
         let mut salt = Vec::with_capacity(64);
         salt.extend_from_slice(&self.client_random);
         salt.extend_from_slice(server_random);
-        hkdf::derive_key_with_salt(&salt, &[shared_secret], b"B4AE-v1-master-secret", 32)
+        hkdf::derive_key_with_salt(&salt, &bound_key_material(&self.config, shared_secret), b"B4AE-v1-master-secret", 32)
     }
 
     /// Derive session keys from master_secret per spec (B4AE-v1-encryption-key, etc.)
@@ -486,16 +529,42 @@ impl HandshakeInitiator {
         self.state
     }
 
+    /// Algorithms selected for this handshake (empty before the response).
+    pub fn selected_algorithms(&self) -> &[AlgorithmId] {
+        &self.selected_algorithms
+    }
+
+    /// This side's public key for this handshake; peers pin its [`identity_fingerprint`].
+    pub fn local_public_key(&self) -> DeniableHybridPublicKey {
+        DeniableHybridPublicKey {
+            kyber_public: self.kem_keypair.public_key.clone(),
+            ..self.local_keypair.public_key()
+        }
+    }
+
     /// Whether handshake has timed out.
     pub fn is_timed_out(&self) -> bool {
         let current_time = time::current_time_millis();
         current_time - self.start_time > self.config.timeout_ms
     }
+
+    /// Milliseconds since this side started the handshake.
+    pub fn elapsed_ms(&self) -> u64 {
+        time::current_time_millis().saturating_sub(self.start_time)
+    }
 }
 impl HandshakeResponder {
     /// Create new handshake responder.
     pub fn new(config: HandshakeConfig) -> CryptoResult<Self> {
-        let local_keypair = DeniableHybridKeyPair::generate()?;
+        Self::with_identity(config, Arc::new(DeniableHybridKeyPair::generate()?))
+    }
+
+    /// Create a handshake responder that signs with a long-term identity key pair.
+    ///
+    /// Only the identity's signing keys are used; the KEM key pair is fresh
+    /// for every handshake.
+    pub fn with_identity(config: HandshakeConfig, local_keypair: Arc<DeniableHybridKeyPair>) -> CryptoResult<Self> {
+        let kem_keypair = crate::crypto::kyber::keypair()?;
         let mut server_random = [0u8; 32];
         random::fill_random(&mut server_random)?;
 
@@ -504,6 +573,7 @@ impl HandshakeResponder {
         Ok(HandshakeResponder {
             config,
             local_keypair,
+            kem_keypair,
             state: HandshakeState::Initiation,
             server_random,
             client_random: None,
@@ -511,6 +581,7 @@ impl HandshakeResponder {
             peer_public_key: None,
             start_time,
             pending_zk_challenge_id: None,
+            selected_algorithms: Vec::new(),
         })
     }
 
@@ -549,6 +620,7 @@ impl HandshakeResponder {
         if !is_valid {
             return Err(CryptoError::VerificationFailed("Init signature verification failed".to_string()));
         }
+        check_pinned_fingerprint(&self.config, &peer_public_key)?;
 
         // Encapsulate - returns (shared_secret, ciphertext)
         let (shared_secret, ciphertext) = crate::crypto::kyber::encapsulate(&peer_public_key.kyber_public)?;
@@ -557,7 +629,7 @@ impl HandshakeResponder {
         let encrypted_shared_secret = serialize_ciphertext(&ciphertext);
 
         // Serialize our public key - include all components
-        let public_key = self.local_keypair.public_key();
+        let public_key = self.local_public_key();
         let hybrid_public_key = serialize_deniable_public_key(&public_key);
 
         // Create message to sign
@@ -589,6 +661,7 @@ impl HandshakeResponder {
         self.client_random = Some(init.client_random);
         self.shared_secret = Some(shared_secret.as_bytes().to_vec());
         self.peer_public_key = Some(peer_public_key);
+        self.selected_algorithms = selected_algorithms.clone();
         self.state = HandshakeState::WaitingComplete;
 
         Ok(HandshakeResponse {
@@ -683,6 +756,7 @@ impl HandshakeResponder {
             session_keys,
             peer_public_key,
             session_id,
+            selected_algorithms: self.selected_algorithms.clone(),
         })
     }
 
@@ -700,7 +774,7 @@ impl HandshakeResponder {
 
         // Use hkdf::derive_key with correct API
         let confirmation = hkdf::derive_key(
-            &[shared_secret],
+            &bound_key_material(&self.config, shared_secret),
             b"handshake-confirmation",
             32
         )?;
@@ -715,7 +789,7 @@ impl HandshakeResponder {
         let mut salt = Vec::with_capacity(64);
         salt.extend_from_slice(client_random);
         salt.extend_from_slice(&self.server_random);
-        hkdf::derive_key_with_salt(&salt, &[shared_secret], b"B4AE-v1-master-secret", 32)
+        hkdf::derive_key_with_salt(&salt, &bound_key_material(&self.config, shared_secret), b"B4AE-v1-master-secret", 32)
     }
 
     /// Derive session keys from master_secret per spec (B4AE-v1-encryption-key, etc.)
@@ -750,15 +824,350 @@ impl HandshakeResponder {
         self.state
     }
 
+    /// Algorithms selected for this handshake (empty before the response).
+    pub fn selected_algorithms(&self) -> &[AlgorithmId] {
+        &self.selected_algorithms
+    }
+
+    /// This side's public key for this handshake; peers pin its [`identity_fingerprint`].
+    pub fn local_public_key(&self) -> DeniableHybridPublicKey {
+        DeniableHybridPublicKey {
+            kyber_public: self.kem_keypair.public_key.clone(),
+            ..self.local_keypair.public_key()
+        }
+    }
+
     /// Whether handshake has timed out.
     pub fn is_timed_out(&self) -> bool {
         let current_time = time::current_time_millis();
         current_time - self.start_time > self.config.timeout_ms
     }
+
+    /// Milliseconds since this side started the handshake.
+    pub fn elapsed_ms(&self) -> u64 {
+        time::current_time_millis().saturating_sub(self.start_time)
+    }
 }
 
 // Helper functions for manual serialization/deserialization
 
+/// Resume Init message (client → server): abbreviated handshake from a ticket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeInit {
+    /// Protocol version.
+    pub protocol_version: u16,
+    /// Ticket ID ([`ResumptionTicket::id`]).
+    pub ticket_id: [u8; 32],
+    /// Fresh client randomness (32 bytes).
+    pub client_random: [u8; 32],
+    /// Proof of PSK possession over the ticket ID and client random.
+    pub binder: [u8; 32],
+    /// 0-RTT payload from [`ResumptionInitiator::send_early_data`].
+    #[serde(default)]
+    pub early_data: Option<EarlyData>,
+}
+
+/// 0-RTT payload sent with a [`ResumeInit`]
+///
+/// Encrypted with AES-256-GCM under a key derived from the resumption PSK
+/// and the client random; `sent_at` is authenticated and bounds how long a
+/// captured packet can be replayed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarlyData {
+    /// Send time (Unix seconds).
+    pub sent_at: u64,
+    /// AES-GCM nonce (12 bytes).
+    pub nonce: Vec<u8>,
+    /// Ciphertext with tag.
+    pub ciphertext: Vec<u8>,
+}
+
+/// Resume Response message (server → client).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeResponse {
+    /// Protocol version.
+    pub protocol_version: u16,
+    /// Fresh server randomness (32 bytes).
+    pub server_random: [u8; 32],
+    /// Confirmation that the server derived the same resumed keys.
+    pub confirmation: [u8; 32],
+    /// Whether the server accepted the init's early data; if not, the
+    /// client must resend it over the resumed session.
+    #[serde(default)]
+    pub early_data_accepted: bool,
+}
+
+/// Session resumption, initiator side
+///
+/// Skips the KEM and signatures: the resumed master secret is derived from
+/// the ticket's PSK bound to fresh client and server randoms, so resumed
+/// sessions get new keys and a new session ID. Peer authentication carries
+/// over from the handshake that issued the ticket.
+pub struct ResumptionInitiator {
+    ticket: ResumptionTicket,
+    psk: ResumptionPsk,
+    client_random: [u8; 32],
+}
+
+impl ResumptionInitiator {
+    /// Start resuming with a ticket from [`Session::resumption_ticket`](crate::protocol::session::Session::resumption_ticket).
+    pub fn resume(ticket: ResumptionTicket, psk: ResumptionPsk) -> CryptoResult<(Self, ResumeInit)> {
+        if ticket.is_expired() {
+            return Err(CryptoError::InvalidInput("Resumption ticket expired".to_string()));
+        }
+
+        let mut client_random = [0u8; 32];
+        random::fill_random(&mut client_random)?;
+
+        let init = ResumeInit {
+            protocol_version: PROTOCOL_VERSION,
+            ticket_id: ticket.id,
+            client_random,
+            binder: resumption_binder(&psk, &ticket.id, &client_random)?,
+            early_data: None,
+        };
+        Ok((ResumptionInitiator { ticket, psk, client_random }, init))
+    }
+
+    /// Attach a 0-RTT payload to `init`, before the server has answered.
+    ///
+    /// # Replay
+    ///
+    /// Early data is not forward secret and is replayable: anyone who
+    /// captures `init` can resend it, e.g. to another server that holds the
+    /// same ticket. Servers only accept it when opted in with a replay cache
+    /// ([`TicketStore::with_early_data`]), which still admits one copy per
+    /// cache, so only send idempotent requests. Check
+    /// [`ResumeResponse::early_data_accepted`] and resend rejected data over
+    /// the resumed session; servers built without the `v2_protocol` feature
+    /// never accept it.
+    pub fn send_early_data(&self, init: &mut ResumeInit, plaintext: &[u8]) -> CryptoResult<()> {
+        if init.ticket_id != self.ticket.id || init.client_random != self.client_random {
+            return Err(CryptoError::InvalidInput("ResumeInit was not created by this initiator".to_string()));
+        }
+
+        let sent_at = time::current_time_secs();
+        let key = early_data_key(&self.psk, &self.ticket.id, &self.client_random)?;
+        let aad = early_data_aad(&self.ticket.id, &self.client_random, sent_at);
+        let (nonce, ciphertext) = aes_gcm::encrypt(&key, plaintext, &aad)?;
+        init.early_data = Some(EarlyData { sent_at, nonce, ciphertext });
+        Ok(())
+    }
+
+    /// Verify the server's confirmation and derive the resumed session.
+    pub fn process_response(self, response: ResumeResponse) -> CryptoResult<HandshakeResult> {
+        if response.protocol_version != PROTOCOL_VERSION {
+            return Err(CryptoError::InvalidInput("Protocol version mismatch".to_string()));
+        }
+
+        let result = derive_resumed_session(&self.psk, &self.ticket, &self.client_random, &response.server_random)?;
+        let expected = resumption_confirmation(&result.master_secret)?;
+        if !bool::from(expected.ct_eq(&response.confirmation)) {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+        Ok(result)
+    }
+}
+
+/// Resumption tickets issued to peers, responder side
+///
+/// Each ticket is accepted at most once and only before it expires, so a
+/// captured `ResumeInit` cannot be replayed. A ticket is consumed only when
+/// its binder verifies, and a resumption with a valid binder always
+/// completes; an attacker who only saw the ticket ID, or who tampers with
+/// the early data, cannot burn it. Early data that fails to decrypt is
+/// declined with [`ResumeResponse::early_data_accepted`] `= false`.
+///
+/// Early data is ignored unless enabled with [`with_early_data`](Self::with_early_data),
+/// which needs the `v2_protocol` feature for its replay cache; servers built
+/// without it always decline 0-RTT.
+#[derive(Default)]
+pub struct TicketStore {
+    tickets: HashMap<[u8; 32], (ResumptionTicket, ResumptionPsk)>,
+    #[cfg(feature = "v2_protocol")]
+    early_data_replay: Option<Arc<ReplayCache>>,
+    early_data: HashMap<[u8; 32], Zeroizing<Vec<u8>>>,
+}
+
+impl TicketStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a ticket from [`Session::issue_resumption_ticket`](crate::protocol::session::Session::issue_resumption_ticket).
+    pub fn insert(&mut self, ticket: ResumptionTicket, psk: ResumptionPsk) {
+        self.tickets.insert(ticket.id, (ticket, psk));
+    }
+
+    /// Number of stored tickets.
+    pub fn len(&self) -> usize {
+        self.tickets.len()
+    }
+
+    /// Whether no tickets are stored.
+    pub fn is_empty(&self) -> bool {
+        self.tickets.is_empty()
+    }
+
+    /// Drop expired tickets.
+    pub fn remove_expired(&mut self) {
+        self.tickets.retain(|_, (ticket, _)| !ticket.is_expired());
+    }
+
+    /// Opt in to 0-RTT early data.
+    ///
+    /// Accepted early data is recorded in `replay_cache`, and data already
+    /// in it or sent outside its window is rejected. Share one cache across
+    /// every server that can accept the same tickets; anything that bypasses
+    /// it can be replayed. See [`ResumptionInitiator::send_early_data`].
+    #[cfg(feature = "v2_protocol")]
+    pub fn with_early_data(mut self, replay_cache: Arc<ReplayCache>) -> Self {
+        self.early_data_replay = Some(replay_cache);
+        self
+    }
+
+    /// Take the early data accepted with the resumed session `session_id`.
+    pub fn take_early_data(&mut self, session_id: &[u8; 32]) -> Option<Zeroizing<Vec<u8>>> {
+        self.early_data.remove(session_id)
+    }
+
+    /// Accept a resumption: consumes the ticket and returns the response for
+    /// the initiator together with the resumed session.
+    pub fn accept(&mut self, init: &ResumeInit) -> CryptoResult<(ResumeResponse, HandshakeResult)> {
+        if init.protocol_version != PROTOCOL_VERSION {
+            return Err(CryptoError::InvalidInput("Protocol version mismatch".to_string()));
+        }
+
+        let (ticket, psk) = self.tickets.get(&init.ticket_id)
+            .ok_or_else(|| CryptoError::InvalidInput("Unknown or already used resumption ticket".to_string()))?;
+        if ticket.is_expired() {
+            self.tickets.remove(&init.ticket_id);
+            return Err(CryptoError::InvalidInput("Resumption ticket expired".to_string()));
+        }
+        let expected = resumption_binder(psk, &init.ticket_id, &init.client_random)?;
+        if !bool::from(expected.ct_eq(&init.binder)) {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+        let (ticket, psk) = self.tickets.remove(&init.ticket_id).expect("ticket looked up above");
+
+        let mut server_random = [0u8; 32];
+        random::fill_random(&mut server_random)?;
+
+        let result = derive_resumed_session(&psk, &ticket, &init.client_random, &server_random)?;
+        let early_data_accepted = match &init.early_data {
+            Some(early_data) => self.accept_early_data(&psk, init, early_data, &result.session_id)?,
+            None => false,
+        };
+        let response = ResumeResponse {
+            protocol_version: PROTOCOL_VERSION,
+            server_random,
+            confirmation: resumption_confirmation(&result.master_secret)?,
+            early_data_accepted,
+        };
+        Ok((response, result))
+    }
+
+    /// Decrypt early data whose binder has been verified and record it in
+    /// the replay cache; `false` if disabled, undecryptable, replayed or
+    /// outside the window.
+    #[cfg(feature = "v2_protocol")]
+    fn accept_early_data(
+        &mut self,
+        psk: &ResumptionPsk,
+        init: &ResumeInit,
+        early_data: &EarlyData,
+        session_id: &[u8; 32],
+    ) -> CryptoResult<bool> {
+        let Some(replay_cache) = &self.early_data_replay else {
+            return Ok(false);
+        };
+
+        let key = early_data_key(psk, &init.ticket_id, &init.client_random)?;
+        let aad = early_data_aad(&init.ticket_id, &init.client_random, early_data.sent_at);
+        // The binder does not cover early data, so tampering here must not
+        // fail the resumption: fall back to 1-RTT and let the client resend
+        let Ok(plaintext) = aes_gcm::decrypt(&key, &early_data.nonce, &early_data.ciphertext, &aad) else {
+            return Ok(false);
+        };
+        let plaintext = Zeroizing::new(plaintext);
+        if replay_cache.check_and_insert(&init.binder, early_data.sent_at).is_err() {
+            return Ok(false);
+        }
+        self.early_data.insert(*session_id, plaintext);
+        Ok(true)
+    }
+
+    #[cfg(not(feature = "v2_protocol"))]
+    fn accept_early_data(
+        &mut self,
+        _psk: &ResumptionPsk,
+        _init: &ResumeInit,
+        _early_data: &EarlyData,
+        _session_id: &[u8; 32],
+    ) -> CryptoResult<bool> {
+        Ok(false)
+    }
+}
+
+fn resumption_binder(psk: &ResumptionPsk, ticket_id: &[u8; 32], client_random: &[u8; 32]) -> CryptoResult<[u8; 32]> {
+    let binder = hkdf::derive_key(&[psk.as_bytes(), ticket_id, client_random], b"B4AE-v1-resumption-binder", 32)?;
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&binder);
+    Ok(result)
+}
+
+fn early_data_key(psk: &ResumptionPsk, ticket_id: &[u8; 32], client_random: &[u8; 32]) -> CryptoResult<AesKey> {
+    let key = Zeroizing::new(hkdf::derive_key(&[psk.as_bytes(), ticket_id, client_random], b"B4AE-v1-early-data-key", 32)?);
+    AesKey::from_bytes(&key)
+}
+
+fn early_data_aad(ticket_id: &[u8; 32], client_random: &[u8; 32], sent_at: u64) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(72);
+    aad.extend_from_slice(ticket_id);
+    aad.extend_from_slice(client_random);
+    aad.extend_from_slice(&sent_at.to_be_bytes());
+    aad
+}
+
+fn resumption_confirmation(master_secret: &[u8]) -> CryptoResult<[u8; 32]> {
+    let confirmation = hkdf::derive_key(&[master_secret], b"B4AE-v1-resumption-confirmation", 32)?;
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&confirmation);
+    Ok(result)
+}
+
+/// Resumed master secret, session keys and session ID:
+/// master_secret = HKDF(ikm=psk, salt=client_random||server_random, info="B4AE-v1-resumption-master-secret")
+fn derive_resumed_session(
+    psk: &ResumptionPsk,
+    ticket: &ResumptionTicket,
+    client_random: &[u8; 32],
+    server_random: &[u8; 32],
+) -> CryptoResult<HandshakeResult> {
+    let mut randoms = Vec::with_capacity(64);
+    randoms.extend_from_slice(client_random);
+    randoms.extend_from_slice(server_random);
+
+    let master_secret = hkdf::derive_key_with_salt(&randoms, &[psk.as_bytes()], b"B4AE-v1-resumption-master-secret", 32)?;
+    let keys = hkdf::B4aeKeyDerivation::new(master_secret.clone()).derive_all_keys()?;
+    let session_id = hkdf::derive_key(&[&randoms, &ticket.id], b"resumption-session-id", 32)?;
+
+    let mut id = [0u8; 32];
+    id.copy_from_slice(&session_id);
+    Ok(HandshakeResult {
+        master_secret,
+        session_keys: SessionKeys {
+            encryption_key: keys.encryption_key.clone(),
+            authentication_key: keys.authentication_key.clone(),
+            metadata_key: keys.metadata_key.clone(),
+        },
+        peer_public_key: ticket.peer_public_key.clone(),
+        session_id: id,
+        selected_algorithms: ticket.selected_algorithms.clone(),
+    })
+}
+
 fn serialize_ciphertext(ciphertext: &crate::crypto::kyber::KyberCiphertext) -> Vec<u8> {
     ciphertext.as_bytes().to_vec()
 }
@@ -824,7 +1233,31 @@ fn deserialize_deniable_signature(bytes: &[u8]) -> CryptoResult<DeniableHybridSi
     })
 }
 
-fn serialize_deniable_public_key(public_key: &DeniableHybridPublicKey) -> Vec<u8> {
+/// Identity fingerprint of a handshake public key, for
+/// [`HandshakeConfig::expected_peer_fingerprint`]
+///
+/// Covers the long-term signing keys (X25519/XEdDSA and Dilithium) only, not
+/// the per-handshake Kyber key, so it is stable across handshakes with the
+/// same identity.
+pub fn identity_fingerprint(public_key: &DeniableHybridPublicKey) -> [u8; 32] {
+    let mut identity = Vec::with_capacity(64 + public_key.dilithium_public.as_bytes().len());
+    identity.extend_from_slice(&public_key.x25519_public);
+    identity.extend_from_slice(&public_key.xeddsa_verification_key);
+    identity.extend_from_slice(public_key.dilithium_public.as_bytes());
+    fingerprint::fingerprint_digest(&identity)
+}
+
+/// Reject the peer's identity if it does not match the pin (constant time)
+fn check_pinned_fingerprint(config: &HandshakeConfig, peer_public_key: &DeniableHybridPublicKey) -> CryptoResult<()> {
+    match &config.expected_peer_fingerprint {
+        Some(expected) if !bool::from(identity_fingerprint(peer_public_key).ct_eq(expected)) => {
+            Err(CryptoError::FingerprintMismatch)
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn serialize_deniable_public_key(public_key: &DeniableHybridPublicKey) -> Vec<u8> {
     let mut bytes = Vec::new();
     
     // X25519 public key (32 bytes)
@@ -842,7 +1275,7 @@ fn serialize_deniable_public_key(public_key: &DeniableHybridPublicKey) -> Vec<u8
     bytes
 }
 
-fn deserialize_deniable_public_key(bytes: &[u8]) -> CryptoResult<DeniableHybridPublicKey> {
+pub(crate) fn deserialize_deniable_public_key(bytes: &[u8]) -> CryptoResult<DeniableHybridPublicKey> {
     use crate::crypto::dilithium::DilithiumPublicKey;
     use crate::crypto::kyber::KyberPublicKey;
     
@@ -885,9 +1318,21 @@ fn deserialize_deniable_public_key(bytes: &[u8]) -> CryptoResult<DeniableHybridP
     })
 }
 
+/// Key material for confirmation and master secret: the KEM shared secret,
+/// followed by the PAKE session key when one is configured.
+fn bound_key_material<'a>(config: &'a HandshakeConfig, shared_secret: &'a [u8]) -> Vec<&'a [u8]> {
+    let mut material = vec![shared_secret];
+    if let Some(pake_key) = &config.pake_key {
+        material.push(pake_key.as_bytes());
+    }
+    material
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::message::{Message, MessageContent};
+    use crate::protocol::session::{NewSessionTicket, Session, DEFAULT_TICKET_LIFETIME};
 
     #[test]
     fn test_handshake_flow() -> CryptoResult<()> {
@@ -922,6 +1367,161 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_deterministic_source_reproduces_handshake() -> CryptoResult<()> {
+        use crate::crypto::random::{with_random_source, DeterministicRandomSource};
+
+        let run = |seed: [u8; 32]| {
+            with_random_source(DeterministicRandomSource::from_seed(seed), || {
+                HandshakeInitiator::new(HandshakeConfig::default())?.generate_init()
+            })
+        };
+
+        // The whole init must match except the bytes the PQ backends draw
+        // from their own RNG: the Dilithium/Kyber public keys after the two
+        // classical keys, and the XEdDSA `s` and Dilithium signature after
+        // the XEdDSA commitment `r` (which depends only on the nonce).
+        const CLASSICAL_KEYS_LEN: usize = 64;
+        const XEDDSA_R_LEN: usize = 32;
+        let (first, second, other) = (run([9u8; 32])?, run([9u8; 32])?, run([10u8; 32])?);
+        assert_eq!(first.protocol_version, second.protocol_version);
+        assert_eq!(first.client_random, second.client_random);
+        assert_eq!(first.supported_algorithms, second.supported_algorithms);
+        assert_eq!(format!("{:?}", first.extensions), format!("{:?}", second.extensions));
+        assert_eq!(first.hybrid_public_key.len(), second.hybrid_public_key.len());
+        assert_eq!(first.hybrid_public_key[..CLASSICAL_KEYS_LEN], second.hybrid_public_key[..CLASSICAL_KEYS_LEN]);
+        assert_eq!(first.signature.len(), second.signature.len());
+        assert_eq!(first.signature[..XEDDSA_R_LEN], second.signature[..XEDDSA_R_LEN]);
+
+        assert_ne!(first.client_random, other.client_random);
+        assert_ne!(first.hybrid_public_key[..CLASSICAL_KEYS_LEN], other.hybrid_public_key[..CLASSICAL_KEYS_LEN]);
+        assert_ne!(first.signature[..XEDDSA_R_LEN], other.signature[..XEDDSA_R_LEN]);
+        Ok(())
+    }
+
+    fn run_pake(password: &[u8]) -> CryptoResult<(zkauth::PakeSessionKey, zkauth::PakeSessionKey)> {
+        let params = zkauth::PakeParams::new(64, 1);
+        let registration = zkauth::pake_register(b"alice", b"hunter2", &params)?;
+        let (client_state, client_msg) = zkauth::pake_client_start(b"alice", password, registration.salt(), &params)?;
+        let (server_state, server_msg) = zkauth::pake_server_respond(&registration, &client_msg)?;
+        let (client_key, confirm) = zkauth::pake_client_finish(client_state, &server_msg)?;
+        let server_key = zkauth::pake_server_finish(server_state, &confirm)?;
+        Ok((client_key, server_key))
+    }
+
+    fn run_handshake(
+        initiator_config: HandshakeConfig,
+        responder_config: HandshakeConfig,
+    ) -> CryptoResult<(HandshakeResult, HandshakeResult)> {
+        let mut initiator = HandshakeInitiator::new(initiator_config)?;
+        let mut responder = HandshakeResponder::new(responder_config)?;
+        let response = responder.process_init(initiator.generate_init()?)?;
+        initiator.process_response(response)?;
+        responder.process_complete(initiator.generate_complete()?)?;
+        Ok((initiator.finalize()?, responder.finalize()?))
+    }
+
+    #[test]
+    fn test_handshake_binds_pake_key() -> CryptoResult<()> {
+        let (client_key, server_key) = run_pake(b"hunter2")?;
+        let (client_key, server_key) = (Arc::new(client_key), Arc::new(server_key));
+
+        let initiator_config = HandshakeConfig { pake_key: Some(client_key), ..HandshakeConfig::default() };
+        let responder_config = HandshakeConfig { pake_key: Some(server_key), ..HandshakeConfig::default() };
+
+        let (initiator_result, responder_result) = run_handshake(initiator_config.clone(), responder_config)?;
+        assert_eq!(initiator_result.master_secret, responder_result.master_secret);
+
+        // A responder without the PAKE key cannot complete the handshake
+        assert!(run_handshake(initiator_config, HandshakeConfig::default()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pinned_fingerprint_match() -> CryptoResult<()> {
+        let responder_keypair = DeniableHybridKeyPair::generate()?;
+        let initiator_keypair = DeniableHybridKeyPair::generate()?;
+        let initiator_config = HandshakeConfig {
+            expected_peer_fingerprint: Some(identity_fingerprint(&responder_keypair.public_key())),
+            ..HandshakeConfig::default()
+        };
+        let responder_config = HandshakeConfig {
+            expected_peer_fingerprint: Some(identity_fingerprint(&initiator_keypair.public_key())),
+            ..HandshakeConfig::default()
+        };
+
+        let mut initiator = HandshakeInitiator::with_identity(initiator_config, Arc::new(initiator_keypair))?;
+        let mut responder = HandshakeResponder::with_identity(responder_config, Arc::new(responder_keypair))?;
+        let response = responder.process_init(initiator.generate_init()?)?;
+        initiator.process_response(response)?;
+        responder.process_complete(initiator.generate_complete()?)?;
+
+        let (initiator_result, responder_result) = (initiator.finalize()?, responder.finalize()?);
+        assert_eq!(initiator_result.master_secret, responder_result.master_secret);
+        assert_eq!(
+            identity_fingerprint(&initiator_result.peer_public_key),
+            identity_fingerprint(&responder.local_public_key())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pinned_identity_survives_fresh_kem_keys() -> CryptoResult<()> {
+        let identity = Arc::new(DeniableHybridKeyPair::generate()?);
+        let pinned = HandshakeConfig {
+            expected_peer_fingerprint: Some(identity_fingerprint(&identity.public_key())),
+            ..HandshakeConfig::default()
+        };
+
+        let mut kem_keys = Vec::new();
+        for _ in 0..2 {
+            let mut initiator = HandshakeInitiator::new(pinned.clone())?;
+            let mut responder = HandshakeResponder::with_identity(HandshakeConfig::default(), Arc::clone(&identity))?;
+            let response = responder.process_init(initiator.generate_init()?)?;
+            initiator.process_response(response)?;
+            responder.process_complete(initiator.generate_complete()?)?;
+            kem_keys.push(responder.local_public_key().kyber_public.as_bytes().to_vec());
+        }
+        // Same pinned identity, but a new KEM key for every handshake
+        assert_ne!(kem_keys[0], kem_keys[1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pinned_fingerprint_mismatch() -> CryptoResult<()> {
+        let wrong_pin = Some(identity_fingerprint(&DeniableHybridKeyPair::generate()?.public_key()));
+
+        // Initiator pins a key the responder does not have
+        let pinned = HandshakeConfig { expected_peer_fingerprint: wrong_pin, ..HandshakeConfig::default() };
+        let mut initiator = HandshakeInitiator::new(pinned.clone())?;
+        let mut responder = HandshakeResponder::new(HandshakeConfig::default())?;
+        let response = responder.process_init(initiator.generate_init()?)?;
+        assert!(matches!(initiator.process_response(response), Err(CryptoError::FingerprintMismatch)));
+        assert_eq!(initiator.state(), HandshakeState::WaitingResponse);
+
+        // Responder pins a key the initiator does not have
+        let mut initiator = HandshakeInitiator::new(HandshakeConfig::default())?;
+        let mut responder = HandshakeResponder::new(pinned)?;
+        let err = responder.process_init(initiator.generate_init()?).unwrap_err();
+        assert!(matches!(err, CryptoError::FingerprintMismatch));
+
+        // Surfaced to applications as a protocol error
+        match crate::error::B4aeError::from(err) {
+            crate::error::B4aeError::ProtocolError(msg) => assert_eq!(msg, "fingerprint mismatch"),
+            other => panic!("expected protocol error, got {:?}", other),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_pin_skips_fingerprint_check() -> CryptoResult<()> {
+        assert!(HandshakeConfig::default().expected_peer_fingerprint.is_none());
+        let (initiator_result, responder_result) =
+            run_handshake(HandshakeConfig::default(), HandshakeConfig::default())?;
+        assert_eq!(initiator_result.master_secret, responder_result.master_secret);
+        Ok(())
+    }
+
     #[test]
     fn test_handshake_timeout() -> CryptoResult<()> {
         let mut config = HandshakeConfig::default();
@@ -944,4 +1544,194 @@ mod tests {
 
         Ok(())
     }
+
+    /// Established sessions for both sides of a full handshake
+    fn established_sessions() -> CryptoResult<(Session, Session)> {
+        let (client, server) = run_handshake(HandshakeConfig::default(), HandshakeConfig::default())?;
+        Ok((
+            Session::from_handshake(client, b"server".to_vec(), None)?,
+            Session::from_handshake(server, b"client".to_vec(), None)?,
+        ))
+    }
+
+    #[test]
+    fn test_resume_session() -> CryptoResult<()> {
+        let (client, server) = established_sessions()?;
+        let (server_ticket, server_psk, announcement) = server.issue_resumption_ticket(DEFAULT_TICKET_LIFETIME)?;
+        let (client_ticket, client_psk) = client.resumption_ticket(&announcement)?;
+        assert_eq!(client_ticket.id, server_ticket.id);
+
+        // Every issue draws a fresh nonce, so tickets are unlinkable
+        let (reissued, _, _) = server.issue_resumption_ticket(DEFAULT_TICKET_LIFETIME)?;
+        assert_ne!(reissued.id, server_ticket.id);
+
+        let mut store = TicketStore::new();
+        store.insert(server_ticket, server_psk);
+
+        let (resumer, init) = ResumptionInitiator::resume(client_ticket, client_psk)?;
+        let (response, server_result) = store.accept(&init)?;
+        let client_result = resumer.process_response(response)?;
+        assert!(store.is_empty());
+
+        // Fresh keys and session ID, same peers
+        assert_eq!(client_result.session_id, server_result.session_id);
+        assert_ne!(&client_result.session_id, client.session_id());
+        assert_eq!(client_result.session_keys.encryption_key, server_result.session_keys.encryption_key);
+        assert_eq!(
+            identity_fingerprint(&server_result.peer_public_key),
+            identity_fingerprint(server.peer_public_key())
+        );
+
+        let mut client = Session::from_handshake(client_result, b"server".to_vec(), None)?;
+        let mut server = Session::from_handshake(server_result, b"client".to_vec(), None)?;
+        let sealed = client.send(&Message::text("after the blip".to_string())).map_err(|e| CryptoError::InvalidInput(e.to_string()))?;
+        assert!(matches!(server.receive(&sealed)?.content, MessageContent::Text(text) if text == "after the blip"));
+
+        // The resumed session issues its own ticket
+        assert_ne!(server.issue_resumption_ticket(DEFAULT_TICKET_LIFETIME)?.0.id, init.ticket_id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_rejects_expired_ticket() -> CryptoResult<()> {
+        let (client, server) = established_sessions()?;
+        let (server_ticket, server_psk, announcement) = server.issue_resumption_ticket(std::time::Duration::ZERO)?;
+        assert!(server_ticket.is_expired());
+        let (client_ticket, client_psk) = client.resumption_ticket(&announcement)?;
+        assert!(client_ticket.is_expired());
+        assert!(ResumptionInitiator::resume(client_ticket, client_psk).is_err());
+
+        // A client with a skewed clock still cannot resume past the server's expiry
+        let mut store = TicketStore::new();
+        store.insert(server_ticket, server_psk);
+        let skewed = NewSessionTicket { lifetime_secs: DEFAULT_TICKET_LIFETIME.as_secs(), ..announcement };
+        let (fresh_ticket, fresh_psk) = client.resumption_ticket(&skewed)?;
+        let (_, init) = ResumptionInitiator::resume(fresh_ticket, fresh_psk)?;
+        assert!(matches!(store.accept(&init), Err(CryptoError::InvalidInput(msg)) if msg.contains("expired")));
+        assert!(store.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_rejects_replayed_ticket() -> CryptoResult<()> {
+        let (client, server) = established_sessions()?;
+        let mut store = TicketStore::new();
+        let (server_ticket, server_psk, announcement) = server.issue_resumption_ticket(DEFAULT_TICKET_LIFETIME)?;
+        store.insert(server_ticket, server_psk);
+
+        let (client_ticket, client_psk) = client.resumption_ticket(&announcement)?;
+        let (_, init) = ResumptionInitiator::resume(client_ticket, client_psk)?;
+
+        // A forged binder is rejected without consuming the ticket
+        let mut forged = init.clone();
+        forged.binder[0] ^= 1;
+        assert!(matches!(store.accept(&forged), Err(CryptoError::AuthenticationFailed)));
+        assert_eq!(store.len(), 1);
+
+        store.accept(&init)?;
+        assert!(matches!(store.accept(&init), Err(CryptoError::InvalidInput(msg)) if msg.contains("already used")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_early_data_ignored_without_opt_in() -> CryptoResult<()> {
+        let (client, server) = established_sessions()?;
+        let mut store = TicketStore::new();
+        let (server_ticket, server_psk, announcement) = server.issue_resumption_ticket(DEFAULT_TICKET_LIFETIME)?;
+        store.insert(server_ticket, server_psk);
+
+        let (client_ticket, client_psk) = client.resumption_ticket(&announcement)?;
+        let (resumer, mut init) = ResumptionInitiator::resume(client_ticket, client_psk)?;
+        resumer.send_early_data(&mut init, b"GET /inbox")?;
+
+        let (response, result) = store.accept(&init)?;
+        assert!(!response.early_data_accepted);
+        assert!(store.take_early_data(&result.session_id).is_none());
+        resumer.process_response(response)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "v2_protocol")]
+    #[test]
+    fn test_early_data_accepted() -> CryptoResult<()> {
+        use crate::protocol::v2::replay_protection::ReplayCache;
+
+        let (client, server) = established_sessions()?;
+        let mut store = TicketStore::new().with_early_data(Arc::new(ReplayCache::new()));
+        let (server_ticket, server_psk, announcement) = server.issue_resumption_ticket(DEFAULT_TICKET_LIFETIME)?;
+        store.insert(server_ticket, server_psk);
+
+        let (client_ticket, client_psk) = client.resumption_ticket(&announcement)?;
+        let (resumer, mut init) = ResumptionInitiator::resume(client_ticket, client_psk)?;
+        resumer.send_early_data(&mut init, b"GET /inbox")?;
+        assert!(!init.early_data.as_ref().unwrap().ciphertext.windows(10).any(|w| w == b"GET /inbox"));
+
+        let (response, result) = store.accept(&init)?;
+        assert!(response.early_data_accepted);
+        assert_eq!(store.take_early_data(&result.session_id).unwrap().as_slice(), b"GET /inbox");
+        assert!(store.take_early_data(&result.session_id).is_none());
+        assert_eq!(resumer.process_response(response)?.session_id, result.session_id);
+        Ok(())
+    }
+
+    #[cfg(feature = "v2_protocol")]
+    #[test]
+    fn test_replayed_early_data_rejected() -> CryptoResult<()> {
+        use crate::protocol::v2::replay_protection::ReplayCache;
+
+        // Two servers that both hold the ticket and share a replay cache
+        let (client, server) = established_sessions()?;
+        let replay_cache = Arc::new(ReplayCache::new());
+        let mut first = TicketStore::new().with_early_data(Arc::clone(&replay_cache));
+        let mut second = TicketStore::new().with_early_data(Arc::clone(&replay_cache));
+        let (ticket, psk, announcement) = server.issue_resumption_ticket(DEFAULT_TICKET_LIFETIME)?;
+        first.insert(ticket, psk);
+        // The second server loads the same issued ticket, e.g. from shared storage
+        let (ticket, psk) = server.resumption_ticket(&announcement)?;
+        second.insert(ticket, psk);
+
+        let (client_ticket, client_psk) = client.resumption_ticket(&announcement)?;
+        let (resumer, mut init) = ResumptionInitiator::resume(client_ticket, client_psk)?;
+        resumer.send_early_data(&mut init, b"transfer 10")?;
+
+        let (response, result) = first.accept(&init)?;
+        assert!(response.early_data_accepted);
+        assert!(first.take_early_data(&result.session_id).is_some());
+
+        let (replayed, result) = second.accept(&init)?;
+        assert!(!replayed.early_data_accepted);
+        assert!(second.take_early_data(&result.session_id).is_none());
+        assert_eq!(replay_cache.len(), 1);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "v2_protocol")]
+    #[test]
+    fn test_tampered_early_data_does_not_burn_ticket() -> CryptoResult<()> {
+        use crate::protocol::v2::replay_protection::ReplayCache;
+
+        let (client, server) = established_sessions()?;
+        let replay_cache = Arc::new(ReplayCache::new());
+        let mut store = TicketStore::new().with_early_data(Arc::clone(&replay_cache));
+        let (ticket, psk, announcement) = server.issue_resumption_ticket(DEFAULT_TICKET_LIFETIME)?;
+        store.insert(ticket, psk);
+
+        let (client_ticket, client_psk) = client.resumption_ticket(&announcement)?;
+        let (resumer, mut init) = ResumptionInitiator::resume(client_ticket, client_psk)?;
+        resumer.send_early_data(&mut init, b"transfer 10")?;
+
+        // An on-path attacker flips one byte of the early data
+        let mut tampered = init.clone();
+        tampered.early_data.as_mut().unwrap().ciphertext[0] ^= 1;
+
+        // The resumption still completes, without 0-RTT, and the client
+        // resends over the resumed session
+        let (response, result) = store.accept(&tampered)?;
+        assert!(!response.early_data_accepted);
+        assert!(store.take_early_data(&result.session_id).is_none());
+        assert!(replay_cache.is_empty());
+        assert_eq!(resumer.process_response(response)?.session_id, result.session_id);
+        Ok(())
+    }
 }
@@ -4,11 +4,12 @@
 
 use crate::crypto::{CryptoError, CryptoResult};
 use crate::crypto::aes_gcm::{self, AesKey};
-use crate::crypto::pfs_plus::PfsSession;
+use crate::crypto::pfs_plus::{PfsSession, PfsSessionSnapshot};
 use crate::protocol::MessageType;
 use crate::time;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
+use std::io::Read;
 
 /// Message priority levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -83,12 +84,14 @@ pub struct Message {
 pub mod flags {
     /// Payload is encrypted
     pub const ENCRYPTED: u8 = 0b00000001;
-    /// Payload is compressed
+    /// Payload is compressed before encryption (codec id in the first plaintext byte)
     pub const COMPRESSED: u8 = 0b00000010;
     /// Dummy traffic (discard by recipient)
     pub const DUMMY_TRAFFIC: u8 = 0b00000100;
     /// Requires acknowledgment
     pub const REQUIRES_ACK: u8 = 0b00001000;
+    /// Encrypted under an odd key rotation epoch (selects old or new keys during rotation)
+    pub const KEY_EPOCH: u8 = 0b00010000;
 }
 
 impl Message {
@@ -167,6 +170,71 @@ impl Message {
     }
 }
 
+/// Compression applied to the serialized message before encryption
+///
+/// The choice is per message and travels in the `COMPRESSED` flag, which is
+/// bound into the AEAD associated data. A compressed plaintext starts with a
+/// one-byte codec id ([`Compression::codec_id`]), so codecs can be added
+/// without changing the frame layout. Decompression is capped at
+/// `MAX_MESSAGE_SIZE` to reject decompression bombs.
+///
+/// # Security
+///
+/// Compressing before encrypting makes the ciphertext length depend on the
+/// plaintext content. If attacker-influenced data is compressed together with
+/// a secret (CRIME/BREACH style), observing message sizes can reveal the
+/// secret. Compression is therefore off by default; only enable it for
+/// payloads that do not mix secrets with attacker-controlled input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Compression {
+    /// Send the serialized message as is
+    #[default]
+    None,
+    /// DEFLATE (RFC 1951) with `level` 0 (store) to 9 (best)
+    Deflate {
+        /// Compression level (0-9)
+        level: u32,
+    },
+    /// Zstandard (RFC 8878) with `level` from `zstd::compression_level_range()`
+    Zstd {
+        /// Compression level (negative for fast modes, up to 22)
+        level: i32,
+    },
+}
+
+/// Codec id of DEFLATE in a compressed plaintext
+const CODEC_DEFLATE: u8 = 1;
+/// Codec id of Zstandard in a compressed plaintext
+const CODEC_ZSTD: u8 = 2;
+
+impl Compression {
+    /// Validate the compression parameters
+    pub fn validate(&self) -> CryptoResult<()> {
+        match *self {
+            Compression::Deflate { level } if level > 9 => Err(CryptoError::InvalidInput(
+                format!("Deflate level must be between 0 and 9, got {}", level)
+            )),
+            Compression::Zstd { level } if !zstd::compression_level_range().contains(&level) => {
+                Err(CryptoError::InvalidInput(format!(
+                    "Zstd level must be in {:?}, got {}",
+                    zstd::compression_level_range(),
+                    level
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Wire id written before the compressed bytes (`None` for no compression)
+    pub fn codec_id(&self) -> Option<u8> {
+        match self {
+            Compression::None => None,
+            Compression::Deflate { .. } => Some(CODEC_DEFLATE),
+            Compression::Zstd { .. } => Some(CODEC_ZSTD),
+        }
+    }
+}
+
 /// Maximum number of sequences to track for replay protection
 const REPLAY_WINDOW_SIZE: usize = 4096;
 
@@ -180,6 +248,15 @@ pub struct MessageCrypto {
     received_sequences: BTreeSet<u64>,
 }
 
+/// Serialized [`MessageCrypto`] for session export; see
+/// [`PfsKeyChainSnapshot`](crate::crypto::pfs_plus::PfsKeyChainSnapshot)
+#[derive(Serialize, Deserialize)]
+pub(crate) struct MessageCryptoSnapshot {
+    pfs_session: PfsSessionSnapshot,
+    sequence: u64,
+    received_sequences: BTreeSet<u64>,
+}
+
 impl MessageCrypto {
     /// Create new message crypto
     pub fn new(pfs_session: PfsSession) -> Self {
@@ -190,8 +267,48 @@ impl MessageCrypto {
         }
     }
 
+    pub(crate) fn snapshot(&self) -> MessageCryptoSnapshot {
+        MessageCryptoSnapshot {
+            pfs_session: self.pfs_session.snapshot(),
+            sequence: self.sequence,
+            received_sequences: self.received_sequences.clone(),
+        }
+    }
+
+    pub(crate) fn from_snapshot(snapshot: MessageCryptoSnapshot) -> Self {
+        MessageCrypto {
+            pfs_session: PfsSession::from_snapshot(&snapshot.pfs_session),
+            sequence: snapshot.sequence,
+            received_sequences: snapshot.received_sequences,
+        }
+    }
+
     /// Encrypt message
     pub fn encrypt(&mut self, message: &Message) -> CryptoResult<EncryptedMessage> {
+        self.seal(MessageType::DataMessage, message, Compression::None)
+    }
+
+    /// Encrypt message, compressing it first (see [`Compression`] for the risks)
+    pub fn encrypt_compressed(&mut self, message: &Message, compression: Compression) -> CryptoResult<EncryptedMessage> {
+        self.seal(MessageType::DataMessage, message, compression)
+    }
+
+    /// Encrypt message as the given message type
+    ///
+    /// Types other than `DataMessage` are bound into the AEAD associated data,
+    /// so a control message cannot be relabelled as data or vice versa.
+    pub fn encrypt_as(&mut self, message_type: MessageType, message: &Message) -> CryptoResult<EncryptedMessage> {
+        self.seal(message_type, message, Compression::None)
+    }
+
+    fn seal(
+        &mut self,
+        message_type: MessageType,
+        message: &Message,
+        compression: Compression,
+    ) -> CryptoResult<EncryptedMessage> {
+        compression.validate()?;
+
         // Check if message is expired
         if message.is_expired() {
             return Err(CryptoError::InvalidInput("Message expired".to_string()));
@@ -207,20 +324,32 @@ impl MessageCrypto {
             )));
         }
 
+        let mut message_flags = flags::ENCRYPTED;
+        let plaintext = match compression.codec_id() {
+            None => plaintext,
+            Some(codec) => {
+                message_flags |= flags::COMPRESSED;
+                let mut framed = vec![codec];
+                framed.extend_from_slice(&compress(&plaintext, compression)?);
+                framed
+            }
+        };
+
         // Get next encryption key from PFS+
         let message_key = self.pfs_session.next_send_key()?;
         let aes_key = AesKey::from_bytes(&message_key)?;
 
         // Encrypt with AES-256-GCM
-        let (nonce, ciphertext) = aes_gcm::encrypt(&aes_key, &plaintext, b"")?;
+        let aad = message_aad(message_type as u8, message_flags);
+        let (nonce, ciphertext) = aes_gcm::encrypt(&aes_key, &plaintext, &aad)?;
 
         let timestamp = time::current_time_secs();
 
         // Create encrypted message
         let encrypted = EncryptedMessage {
             version: crate::PROTOCOL_VERSION,
-            message_type: MessageType::DataMessage as u8,
-            flags: flags::ENCRYPTED,
+            message_type: message_type as u8,
+            flags: message_flags,
             sequence: self.sequence,
             timestamp,
             payload: ciphertext,
@@ -272,7 +401,13 @@ impl MessageCrypto {
         let aes_key = AesKey::from_bytes(&message_key)?;
 
         // Decrypt with AES-256-GCM
-        let plaintext = aes_gcm::decrypt(&aes_key, &encrypted.nonce, &encrypted.payload, b"")?;
+        let aad = message_aad(encrypted.message_type, encrypted.flags);
+        let plaintext = aes_gcm::decrypt(&aes_key, &encrypted.nonce, &encrypted.payload, &aad)?;
+        let plaintext = if encrypted.flags & flags::COMPRESSED != 0 {
+            decompress_bounded(&plaintext, crate::MAX_MESSAGE_SIZE)?
+        } else {
+            plaintext
+        };
 
         // If dummy traffic, return Dummy message (recipient discards)
         if encrypted.flags & flags::DUMMY_TRAFFIC != 0 {
@@ -312,6 +447,66 @@ impl MessageCrypto {
     }
 }
 
+/// Associated data binding the message type and compression flag
+///
+/// Empty for uncompressed data messages, which keeps them wire compatible.
+fn message_aad(message_type: u8, message_flags: u8) -> Vec<u8> {
+    let compressed = message_flags & flags::COMPRESSED;
+    if message_type == MessageType::DataMessage as u8 && compressed == 0 {
+        Vec::new()
+    } else {
+        vec![message_type, compressed]
+    }
+}
+
+fn compress(data: &[u8], compression: Compression) -> CryptoResult<Vec<u8>> {
+    let mut out = Vec::new();
+    let result = match compression {
+        Compression::None => {
+            out.extend_from_slice(data);
+            Ok(out.len())
+        }
+        Compression::Deflate { level } => {
+            flate2::read::DeflateEncoder::new(data, flate2::Compression::new(level)).read_to_end(&mut out)
+        }
+        Compression::Zstd { level } => {
+            zstd::stream::read::Encoder::new(data, level).and_then(|mut e| e.read_to_end(&mut out))
+        }
+    };
+    result.map_err(|e| CryptoError::EncryptionFailed(format!("Compression failed: {}", e)))?;
+    Ok(out)
+}
+
+/// Decompress a `[codec id || compressed]` plaintext, refusing to produce
+/// more than `max_len` bytes
+fn decompress_bounded(data: &[u8], max_len: usize) -> CryptoResult<Vec<u8>> {
+    let (&codec, body) = data
+        .split_first()
+        .ok_or_else(|| CryptoError::DecryptionFailed("Missing compression codec id".to_string()))?;
+    let limit = max_len as u64 + 1;
+    let mut out = Vec::new();
+    let result = match codec {
+        CODEC_DEFLATE => flate2::read::DeflateDecoder::new(body).take(limit).read_to_end(&mut out),
+        CODEC_ZSTD => zstd::stream::read::Decoder::new(body)
+            .and_then(|d| d.take(limit).read_to_end(&mut out)),
+        _ => {
+            return Err(CryptoError::DecryptionFailed(format!(
+                "Unknown compression codec id: {}",
+                codec
+            )))
+        }
+    };
+    result.map_err(|e| CryptoError::DecryptionFailed(format!("Decompression failed: {}", e)))?;
+
+    if out.len() > max_len {
+        return Err(CryptoError::InvalidInput(format!(
+            "Decompressed message exceeds {} bytes",
+            max_len
+        )));
+    }
+    Ok(out)
+}
+
 /// Message builder for fluent API
 pub struct MessageBuilder {
     message: Message,
@@ -427,6 +622,92 @@ mod tests {
         }
     }
 
+    fn crypto_pair() -> (MessageCrypto, MessageCrypto) {
+        let key = [0x42; 32];
+        let sender = MessageCrypto::new(PfsSession::new(&key, &key, [0x44; 32]).unwrap());
+        let receiver = MessageCrypto::new(PfsSession::new(&key, &key, [0x44; 32]).unwrap());
+        (sender, receiver)
+    }
+
+    #[test]
+    fn test_compressed_roundtrip() {
+        let (mut sender, mut receiver) = crypto_pair();
+        let text = "the quick brown fox jumps over the lazy dog. ".repeat(50);
+        let message = Message::text(text.clone());
+
+        let plain = sender.encrypt(&message).unwrap();
+        let deflated = sender.encrypt_compressed(&message, Compression::Deflate { level: 6 }).unwrap();
+        let zstd = sender.encrypt_compressed(&message, Compression::Zstd { level: 3 }).unwrap();
+        assert_eq!(plain.flags & flags::COMPRESSED, 0);
+        for compressed in [&deflated, &zstd] {
+            assert_eq!(compressed.flags & flags::COMPRESSED, flags::COMPRESSED);
+            assert!(compressed.payload.len() < plain.payload.len() / 4);
+        }
+
+        for encrypted in [&plain, &deflated, &zstd] {
+            match receiver.decrypt(encrypted).unwrap().content {
+                MessageContent::Text(ref t) => assert_eq!(t, &text),
+                _ => panic!("Wrong content type"),
+            }
+        }
+
+        // The compression flag is authenticated
+        let mut stripped = sender.encrypt_compressed(&message, Compression::Deflate { level: 1 }).unwrap();
+        stripped.flags &= !flags::COMPRESSED;
+        assert!(receiver.decrypt(&stripped).is_err());
+
+        assert!(sender.encrypt_compressed(&message, Compression::Deflate { level: 10 }).is_err());
+        assert!(sender.encrypt_compressed(&message, Compression::Zstd { level: 23 }).is_err());
+    }
+
+    #[test]
+    fn test_codec_id_selects_decompressor() {
+        let data = b"codec framing codec framing codec framing".repeat(8);
+        for compression in [Compression::Deflate { level: 6 }, Compression::Zstd { level: 3 }] {
+            let mut framed = vec![compression.codec_id().unwrap()];
+            framed.extend_from_slice(&compress(&data, compression).unwrap());
+            assert_eq!(decompress_bounded(&framed, crate::MAX_MESSAGE_SIZE).unwrap(), data);
+
+            // The same bytes under the other codec id do not decode
+            framed[0] = if framed[0] == CODEC_DEFLATE { CODEC_ZSTD } else { CODEC_DEFLATE };
+            assert!(decompress_bounded(&framed, crate::MAX_MESSAGE_SIZE).is_err());
+        }
+
+        assert!(decompress_bounded(&[], crate::MAX_MESSAGE_SIZE).is_err());
+        assert!(decompress_bounded(&[0xff, 0, 0], crate::MAX_MESSAGE_SIZE).is_err());
+    }
+
+    #[test]
+    fn test_decompression_bomb_rejected() {
+        let zeros = vec![0u8; crate::MAX_MESSAGE_SIZE + 1];
+        for compression in [Compression::Deflate { level: 9 }, Compression::Zstd { level: 19 }] {
+            let mut framed = vec![compression.codec_id().unwrap()];
+            framed.extend_from_slice(&compress(&zeros, compression).unwrap());
+            assert!(framed.len() < 4096);
+            assert!(decompress_bounded(&framed, crate::MAX_MESSAGE_SIZE).is_err());
+        }
+        let mut bomb = vec![CODEC_DEFLATE];
+        bomb.extend_from_slice(&compress(&zeros, Compression::Deflate { level: 9 }).unwrap());
+
+        // A peer holding the session key cannot get the bomb inflated either
+        let (mut sender, mut receiver) = crypto_pair();
+        let message_key = sender.pfs_session.next_send_key().unwrap();
+        let message_flags = flags::ENCRYPTED | flags::COMPRESSED;
+        let aad = message_aad(MessageType::DataMessage as u8, message_flags);
+        let (nonce, payload) = aes_gcm::encrypt(&AesKey::from_bytes(&message_key).unwrap(), &bomb, &aad).unwrap();
+
+        let encrypted = EncryptedMessage {
+            version: crate::PROTOCOL_VERSION,
+            message_type: MessageType::DataMessage as u8,
+            flags: message_flags,
+            sequence: 0,
+            timestamp: time::current_time_secs(),
+            payload,
+            nonce,
+        };
+        assert!(matches!(receiver.decrypt(&encrypted), Err(CryptoError::InvalidInput(_))));
+    }
+
     #[test]
     #[cfg(feature = "liboqs")]
     fn test_message_encryption() {
@@ -17,6 +17,7 @@ pub mod session;
 pub mod v2;
 
 use crate::error::{B4aeError, B4aeResult};
+use crate::metadata::timing::DelayDistribution;
 use serde::{Deserialize, Serialize};
 
 /// Wire protocol version (Protocol Specification v1.0). Re-exported from crate root.
@@ -38,6 +39,8 @@ pub enum MessageType {
     KeyRotation = 0x20,
     /// Acknowledgment
     Ack = 0x30,
+    /// Authenticated end of session
+    Close = 0x40,
     /// Error message
     Error = 0xFF,
 }
@@ -52,6 +55,7 @@ impl MessageType {
             0x10 => Ok(MessageType::DataMessage),
             0x20 => Ok(MessageType::KeyRotation),
             0x30 => Ok(MessageType::Ack),
+            0x40 => Ok(MessageType::Close),
             0xFF => Ok(MessageType::Error),
             _ => Err(B4aeError::ProtocolError(format!("Unknown message type: {}", value))),
         }
@@ -72,6 +76,16 @@ pub struct AnonymizationConfig {
     pub use_tor: bool,
 }
 
+/// Traffic padding scheme used by metadata protection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaddingScheme {
+    /// PKCS#7-style padding to a multiple of `padding_block_size`.
+    #[default]
+    Block,
+    /// PADME exponential buckets (512B..64KB); the protected size lands on a bucket.
+    Padme,
+}
+
 /// Protocol configuration
 #[derive(Debug, Clone)]
 pub struct ProtocolConfig {
@@ -79,10 +93,18 @@ pub struct ProtocolConfig {
     pub metadata_protection: bool,
     /// Traffic padding block size (bytes)
     pub padding_block_size: usize,
+    /// Traffic padding scheme (fixed block or PADME buckets)
+    pub padding_scheme: PaddingScheme,
     /// Enable timing obfuscation
     pub timing_obfuscation: bool,
     /// Maximum timing delay (milliseconds)
     pub max_timing_delay_ms: u64,
+    /// Distribution of timing delays (clamped to `max_timing_delay_ms`)
+    pub timing_distribution: DelayDistribution,
+    /// Extra delay window per KiB of message when the size is known (milliseconds)
+    pub timing_delay_per_kib_ms: u64,
+    /// Ceiling of the size-scaled delay window (milliseconds)
+    pub max_size_timing_delay_ms: u64,
     /// Enable dummy traffic
     pub dummy_traffic: bool,
     /// Dummy traffic percentage (0-100)
@@ -96,8 +118,12 @@ impl Default for ProtocolConfig {
         ProtocolConfig {
             metadata_protection: true,
             padding_block_size: 4096,
+            padding_scheme: PaddingScheme::Block,
             timing_obfuscation: true,
             max_timing_delay_ms: 2000,
+            timing_distribution: DelayDistribution::Uniform,
+            timing_delay_per_kib_ms: 10,
+            max_size_timing_delay_ms: 30_000,
             dummy_traffic: false,
             dummy_traffic_percent: 10,
             anonymization: AnonymizationConfig::default(),
@@ -123,8 +149,12 @@ impl SecurityProfile {
             SecurityProfile::Standard => ProtocolConfig {
                 metadata_protection: true,
                 padding_block_size: 4096,
+                padding_scheme: PaddingScheme::Block,
                 timing_obfuscation: true,
                 max_timing_delay_ms: 2000,
+                timing_distribution: DelayDistribution::Uniform,
+                timing_delay_per_kib_ms: 10,
+            max_size_timing_delay_ms: 30_000,
                 dummy_traffic: false,
                 dummy_traffic_percent: 10,
                 anonymization: AnonymizationConfig::default(),
@@ -132,8 +162,12 @@ impl SecurityProfile {
             SecurityProfile::High => ProtocolConfig {
                 metadata_protection: true,
                 padding_block_size: 16384,
+                padding_scheme: PaddingScheme::Block,
                 timing_obfuscation: true,
                 max_timing_delay_ms: 5000,
+                timing_distribution: DelayDistribution::Uniform,
+                timing_delay_per_kib_ms: 20,
+                max_size_timing_delay_ms: 30_000,
                 dummy_traffic: true,
                 dummy_traffic_percent: 20,
                 anonymization: AnonymizationConfig::default(),
@@ -141,8 +175,12 @@ impl SecurityProfile {
             SecurityProfile::Maximum => ProtocolConfig {
                 metadata_protection: true,
                 padding_block_size: 65536,
+                padding_scheme: PaddingScheme::Block,
                 timing_obfuscation: true,
                 max_timing_delay_ms: 10000,
+                timing_distribution: DelayDistribution::Uniform,
+                timing_delay_per_kib_ms: 50,
+                max_size_timing_delay_ms: 30_000,
                 dummy_traffic: true,
                 dummy_traffic_percent: 30,
                 anonymization: AnonymizationConfig::default(),
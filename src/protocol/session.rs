@@ -6,17 +6,23 @@ use crate::crypto::{CryptoError, CryptoResult};
 use crate::crypto::pfs_plus::{PfsSession, PfsManager};
 use crate::crypto::xeddsa::DeniableHybridPublicKey;
 use crate::crypto::hkdf;
-use crate::protocol::message::{Message, MessageCrypto, EncryptedMessage};
-use crate::protocol::handshake::{HandshakeResult, SessionKeys};
-use crate::protocol::message::flags;
-use crate::error::B4aeResult;
-use std::collections::HashMap;
+use crate::crypto::random;
+use crate::protocol::message::{Compression, Message, MessageCrypto, MessageCryptoSnapshot, EncryptedMessage};
+use crate::protocol::handshake::{AlgorithmId, HandshakeResult, SessionKeys};
+use crate::protocol::message::{flags, MessageContent};
+use crate::protocol::MessageType;
+use crate::error::{B4aeError, B4aeResult};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use crate::time;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 use tracing::{info, warn};
+use zeroize::{Zeroize, Zeroizing};
 
 /// Session state
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SessionState {
     /// Session is being established
     Establishing,
@@ -31,7 +37,7 @@ pub enum SessionState {
 }
 
 /// Session information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionInfo {
     /// Session ID
     pub session_id: [u8; 32],
@@ -75,12 +81,61 @@ pub struct Session {
     last_rotation_time: u64,
     /// Optional audit sink for key rotation events
     audit_sink: Option<Arc<dyn AuditSink>>,
+    /// Negotiated automatic rotation policy (replaces `rotation_policy` when set)
+    negotiated_rotation: Option<RotationPolicy>,
+    /// Rotation we initiated and the peer has not acknowledged yet
+    pending_rotation: Option<PendingRotation>,
+    /// Rotation request waiting to be handed to the transport
+    outgoing_rotation: Option<KeyRotationMessage>,
+    /// Pre-rotation keys kept until the peer sends under the new keys or
+    /// the grace window ends
+    previous_crypto: Option<PreviousKeys>,
+    /// Compression applied to outgoing data messages
+    compression: Compression,
+    /// Sent data messages awaiting an ACK: sequence -> send timestamp
+    unacked: BTreeMap<u64, u64>,
+    /// Secret for deriving resumption tickets (empty once closed)
+    resumption_secret: Zeroizing<Vec<u8>>,
+    /// Algorithms selected in the handshake
+    selected_algorithms: Vec<AlgorithmId>,
+}
+
+/// Keys derived for a rotation that is not installed yet
+struct PendingRotation {
+    sequence: u64,
+    session_keys: SessionKeys,
+    message_crypto: MessageCrypto,
+    /// Request sent to the peer, kept for retransmission
+    request: KeyRotationMessage,
+    /// Unix time the request was last handed out
+    sent_at: u64,
 }
 
+/// Keys of the previous epoch, kept to decrypt messages still in flight
+struct PreviousKeys {
+    message_crypto: MessageCrypto,
+    /// Unix time after which they are dropped
+    expires_at: u64,
+}
+
+/// How long the previous keys stay usable after a negotiated rotation
+pub const ROTATION_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// How long a rotation request may go unacknowledged before
+/// [`Session::take_rotation_request`] hands it out again
+pub const ROTATION_RETRANSMIT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Maximum number of sent data messages tracked while awaiting an ACK
+///
+/// When full, the message that has waited longest is dropped from the
+/// tracker, so a peer that never acknowledges cannot grow it without bound.
+pub const MAX_UNACKED: usize = 4096;
+
 /// Key rotation message untuk komunikasi dengan peer
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyRotationMessage {
-    /// New encryption key (derived)
+    /// New encryption key (derived); negotiated rotations carry a key
+    /// confirmation value here instead of the key itself
     pub new_key_material: Vec<u8>,
     /// Rotation sequence number
     pub rotation_sequence: u64,
@@ -89,7 +144,7 @@ pub struct KeyRotationMessage {
 }
 
 /// Key rotation policy
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyRotationPolicy {
     /// Rotate after this many seconds
     pub time_based: Option<u64>,
@@ -109,6 +164,197 @@ impl Default for KeyRotationPolicy {
     }
 }
 
+/// Reason code carried by a CLOSE message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CloseReason {
+    /// Normal end of session
+    Normal = 0x00,
+    /// Peer is going away (shutdown, app closed)
+    GoingAway = 0x01,
+    /// Peer detected a protocol violation
+    ProtocolViolation = 0x02,
+    /// Session is idle for too long
+    Timeout = 0x03,
+}
+
+impl CloseReason {
+    /// Parse reason code from wire byte
+    pub fn from_u8(value: u8) -> B4aeResult<Self> {
+        match value {
+            0x00 => Ok(CloseReason::Normal),
+            0x01 => Ok(CloseReason::GoingAway),
+            0x02 => Ok(CloseReason::ProtocolViolation),
+            0x03 => Ok(CloseReason::Timeout),
+            _ => Err(B4aeError::ProtocolError(format!("Unknown close reason: {}", value))),
+        }
+    }
+}
+
+/// Result of processing an incoming frame with [`Session::process`]
+#[derive(Debug)]
+pub enum SessionEvent {
+    /// Decrypted data message
+    Message(Message),
+    /// Peer closed the session; the session keys have been zeroized
+    Closed(CloseReason),
+    /// Peer acknowledged the data message with this sequence number
+    Acked(u64),
+}
+
+/// Acknowledgment of a negotiated key rotation
+#[derive(Debug, Clone)]
+pub struct KeyRotationAck {
+    /// Rotation sequence number being acknowledged
+    pub rotation_sequence: u64,
+    /// Proof that the peer derived the same rotated keys
+    pub confirmation: Vec<u8>,
+}
+
+/// Thresholds for negotiated automatic key rotation
+///
+/// When any threshold is crossed on send, the session queues a
+/// [`KeyRotationMessage`] (see [`Session::take_rotation_request`]) and keeps
+/// sending under the current keys until the peer's [`KeyRotationAck`] arrives.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationPolicy {
+    /// Rotate after this many payload bytes sent
+    pub max_bytes: u64,
+    /// Rotate after this many messages sent
+    pub max_messages: u64,
+    /// Rotate once the keys are this old
+    pub max_age: Duration,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        RotationPolicy {
+            max_bytes: 1_000_000_000, // 1GB
+            max_messages: 10_000,
+            max_age: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Magic prefix of [`Session::export_state`] output (distinct from the
+/// `storage::stream` archive magic `B4SS`)
+const SESSION_STATE_MAGIC: &[u8; 4] = b"B4SN";
+
+/// Encryption, authentication and metadata keys of a [`SessionSnapshot`]
+type SnapshotKeys = [Zeroizing<Vec<u8>>; 3];
+
+/// Serialized [`Session`] fields
+#[derive(Serialize, Deserialize)]
+struct SessionSnapshot {
+    session_id: [u8; 32],
+    peer_public_key: Vec<u8>,
+    session_keys: SnapshotKeys,
+    message_crypto: MessageCryptoSnapshot,
+    state: SessionState,
+    info: SessionInfo,
+    rotation_policy: KeyRotationPolicy,
+    rotation_count: u64,
+    last_rotation_time: u64,
+    negotiated_rotation: Option<RotationPolicy>,
+    pending_rotation: Option<(u64, SnapshotKeys, MessageCryptoSnapshot, KeyRotationMessage)>,
+    outgoing_rotation: Option<KeyRotationMessage>,
+    previous_crypto: Option<(MessageCryptoSnapshot, u64)>,
+    compression: Compression,
+    unacked: BTreeMap<u64, u64>,
+    resumption_secret: Zeroizing<Vec<u8>>,
+    selected_algorithms: Vec<AlgorithmId>,
+}
+
+fn snapshot_keys(keys: &SessionKeys) -> SnapshotKeys {
+    [
+        Zeroizing::new(keys.encryption_key.clone()),
+        Zeroizing::new(keys.authentication_key.clone()),
+        Zeroizing::new(keys.metadata_key.clone()),
+    ]
+}
+
+fn restore_keys([encryption_key, authentication_key, metadata_key]: SnapshotKeys) -> SessionKeys {
+    SessionKeys {
+        encryption_key: encryption_key.to_vec(),
+        authentication_key: authentication_key.to_vec(),
+        metadata_key: metadata_key.to_vec(),
+    }
+}
+
+/// Default lifetime of a resumption ticket
+pub const DEFAULT_TICKET_LIFETIME: Duration = Duration::from_secs(3600);
+
+/// Announcement of a freshly issued resumption ticket, responder → initiator
+///
+/// Sent over the established session. The nonce is random per issue, so
+/// every ticket has its own ID and PSK and tickets cannot be linked to each
+/// other; only a holder of the session's resumption secret can derive them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewSessionTicket {
+    /// Random per-issue nonce mixed into the ticket ID and PSK
+    pub nonce: [u8; 32],
+    /// Ticket lifetime in seconds, counted from issue (receipt on the initiator)
+    pub lifetime_secs: u64,
+}
+
+/// Ticket for resuming a session without the KEM/signature round-trip
+///
+/// The responder issues it with [`Session::issue_resumption_ticket`] and the
+/// initiator derives the same ticket from the [`NewSessionTicket`] with
+/// [`Session::resumption_ticket`]. The initiator presents it with
+/// [`ResumptionInitiator::resume`](crate::protocol::handshake::ResumptionInitiator::resume);
+/// the responder keeps it in a
+/// [`TicketStore`](crate::protocol::handshake::TicketStore), which accepts it once.
+#[derive(Debug, Clone)]
+pub struct ResumptionTicket {
+    /// Ticket ID, sent in clear in `ResumeInit`
+    pub id: [u8; 32],
+    /// Expiry (Unix seconds)
+    pub expires_at: u64,
+    /// Peer identity authenticated by the original handshake
+    pub peer_public_key: DeniableHybridPublicKey,
+    /// Algorithms selected by the original handshake, kept on resumption
+    pub selected_algorithms: Vec<AlgorithmId>,
+}
+
+impl ResumptionTicket {
+    /// Whether the ticket can no longer be used
+    pub fn is_expired(&self) -> bool {
+        time::current_time_secs() >= self.expires_at
+    }
+}
+
+/// Resumption pre-shared key; stays on each peer, only its binder is sent
+pub struct ResumptionPsk([u8; 32]);
+
+impl ResumptionPsk {
+    pub(crate) fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for ResumptionPsk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ResumptionPsk([REDACTED])")
+    }
+}
+
+impl Drop for ResumptionPsk {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Session configuration
+#[derive(Debug, Clone, Default)]
+pub struct SessionConfig {
+    /// Negotiated automatic key rotation; `None` keeps the unilateral
+    /// [`KeyRotationPolicy`] behaviour
+    pub rotation: Option<RotationPolicy>,
+    /// Compression for outgoing data messages (opt-in, see [`Compression`])
+    pub compression: Compression,
+}
+
 impl Session {
     /// Create new session from handshake result
     pub fn from_handshake(
@@ -116,6 +362,18 @@ impl Session {
         peer_id: Vec<u8>,
         audit_sink: Option<Arc<dyn AuditSink>>,
     ) -> CryptoResult<Self> {
+        Self::from_handshake_with_config(handshake_result, peer_id, audit_sink, SessionConfig::default())
+    }
+
+    /// Create new session from handshake result with explicit configuration
+    pub fn from_handshake_with_config(
+        handshake_result: HandshakeResult,
+        peer_id: Vec<u8>,
+        audit_sink: Option<Arc<dyn AuditSink>>,
+        config: SessionConfig,
+    ) -> CryptoResult<Self> {
+        config.compression.validate()?;
+
         // Create PFS+ session
         let pfs_session = PfsSession::new(
             &handshake_result.session_keys.encryption_key,
@@ -126,6 +384,12 @@ impl Session {
         // Create message crypto
         let message_crypto = MessageCrypto::new(pfs_session);
 
+        let resumption_secret = Zeroizing::new(hkdf::derive_key(
+            &[&handshake_result.master_secret],
+            b"B4AE-v1-resumption-secret",
+            32,
+        )?);
+
         let now = time::current_time_secs();
 
         let info = SessionInfo {
@@ -151,6 +415,165 @@ impl Session {
             rotation_count: 0,
             last_rotation_time: now,
             audit_sink,
+            negotiated_rotation: config.rotation,
+            pending_rotation: None,
+            outgoing_rotation: None,
+            previous_crypto: None,
+            compression: config.compression,
+            unacked: BTreeMap::new(),
+            resumption_secret,
+            selected_algorithms: handshake_result.selected_algorithms,
+        })
+    }
+
+    /// Issue a resumption ticket for the peer (responder side)
+    ///
+    /// Store the ticket and PSK in a
+    /// [`TicketStore`](crate::protocol::handshake::TicketStore) and send the
+    /// [`NewSessionTicket`] to the peer. Each call draws a fresh nonce, so
+    /// re-issuing yields an unrelated ticket. The ticket expires `lifetime`
+    /// from now.
+    pub fn issue_resumption_ticket(
+        &self,
+        lifetime: Duration,
+    ) -> CryptoResult<(ResumptionTicket, ResumptionPsk, NewSessionTicket)> {
+        let mut nonce = [0u8; 32];
+        random::fill_random(&mut nonce)?;
+        let announcement = NewSessionTicket { nonce, lifetime_secs: lifetime.as_secs() };
+        let (ticket, psk) = self.resumption_ticket(&announcement)?;
+        Ok((ticket, psk, announcement))
+    }
+
+    /// Resumption ticket and PSK announced by the peer (initiator side)
+    ///
+    /// Derives the ticket the peer issued with
+    /// [`issue_resumption_ticket`](Self::issue_resumption_ticket), for
+    /// [`ResumptionInitiator::resume`](crate::protocol::handshake::ResumptionInitiator::resume).
+    /// The ticket expires `lifetime_secs` after this call.
+    pub fn resumption_ticket(&self, announcement: &NewSessionTicket) -> CryptoResult<(ResumptionTicket, ResumptionPsk)> {
+        if self.state != SessionState::Active {
+            return Err(CryptoError::InvalidInput("Session not active".to_string()));
+        }
+
+        let derive = |info: &[u8]| -> CryptoResult<[u8; 32]> {
+            let okm = Zeroizing::new(hkdf::derive_key(&[&self.resumption_secret, &announcement.nonce], info, 32)?);
+            let mut out = [0u8; 32];
+            out.copy_from_slice(&okm);
+            Ok(out)
+        };
+
+        let ticket = ResumptionTicket {
+            id: derive(b"B4AE-v1-resumption-ticket-id")?,
+            expires_at: time::current_time_secs().saturating_add(announcement.lifetime_secs),
+            peer_public_key: self.peer_public_key.clone(),
+            selected_algorithms: self.selected_algorithms.clone(),
+        };
+        Ok((ticket, ResumptionPsk(derive(b"B4AE-v1-resumption-psk")?)))
+    }
+
+    /// Export the complete session state for backup or device migration
+    ///
+    /// Covers the session keys, PFS+ chains and counters, replay window,
+    /// rotation state, unacknowledged sends and statistics, so a restored
+    /// session keeps decrypting messages that were in flight. The state is
+    /// prefixed with `PROTOCOL_VERSION` so that a different protocol version
+    /// refuses to load it. The audit sink is not exported.
+    ///
+    /// # Security
+    /// - The output contains secret key material and must be stored encrypted
+    ///   (see [`B4aeClient::export_sessions`](crate::client::B4aeClient::export_sessions))
+    /// - Keep using only one copy: two live copies would reuse message keys
+    pub fn export_state(&self) -> CryptoResult<Zeroizing<Vec<u8>>> {
+        let snapshot = SessionSnapshot {
+            session_id: self.session_id,
+            peer_public_key: crate::protocol::handshake::serialize_deniable_public_key(&self.peer_public_key),
+            session_keys: snapshot_keys(&self.session_keys),
+            message_crypto: self.message_crypto.snapshot(),
+            state: self.state,
+            info: self.info.clone(),
+            rotation_policy: self.rotation_policy.clone(),
+            rotation_count: self.rotation_count,
+            last_rotation_time: self.last_rotation_time,
+            negotiated_rotation: self.negotiated_rotation.clone(),
+            pending_rotation: self.pending_rotation.as_ref()
+                .map(|p| (p.sequence, snapshot_keys(&p.session_keys), p.message_crypto.snapshot(), p.request.clone())),
+            outgoing_rotation: self.outgoing_rotation.clone(),
+            previous_crypto: self.previous_crypto.as_ref().map(|p| (p.message_crypto.snapshot(), p.expires_at)),
+            compression: self.compression,
+            unacked: self.unacked.clone(),
+            resumption_secret: self.resumption_secret.clone(),
+            selected_algorithms: self.selected_algorithms.clone(),
+        };
+
+        // Reserve the full size up front so no partially filled copy of the
+        // secrets is left behind by a reallocation.
+        let encoding_error = |e: bincode::Error| CryptoError::InvalidInput(format!("Session state encoding failed: {}", e));
+        let size = bincode::serialized_size(&snapshot).map_err(encoding_error)? as usize;
+        let mut out = Zeroizing::new(Vec::with_capacity(SESSION_STATE_MAGIC.len() + 2 + size));
+        out.extend_from_slice(SESSION_STATE_MAGIC);
+        out.extend_from_slice(&crate::PROTOCOL_VERSION.to_be_bytes());
+        bincode::serialize_into(&mut *out, &snapshot).map_err(encoding_error)?;
+        Ok(out)
+    }
+
+    /// Restore a session from state produced by [`export_state`](Self::export_state)
+    ///
+    /// # Returns
+    /// * `Ok(Session)` - Restored session, reporting to `audit_sink`
+    /// * `Err(CryptoError)` - If the state is malformed or was written by a
+    ///   different protocol version
+    pub fn import_state(bytes: &[u8], audit_sink: Option<Arc<dyn AuditSink>>) -> CryptoResult<Self> {
+        let body = bytes.strip_prefix(SESSION_STATE_MAGIC.as_slice())
+            .ok_or_else(|| CryptoError::InvalidInput("Not a session state".to_string()))?;
+        if body.len() < 2 {
+            return Err(CryptoError::InvalidInput("Truncated session state".to_string()));
+        }
+        let version = u16::from_be_bytes([body[0], body[1]]);
+        if version != crate::PROTOCOL_VERSION {
+            return Err(CryptoError::InvalidInput(format!(
+                "Session state protocol version mismatch (state: {}, expected: {})",
+                version,
+                crate::PROTOCOL_VERSION
+            )));
+        }
+
+        let snapshot: SessionSnapshot = bincode::deserialize(&body[2..])
+            .map_err(|e| CryptoError::InvalidInput(format!("Session state decoding failed: {}", e)))?;
+        if snapshot.info.session_id != snapshot.session_id {
+            return Err(CryptoError::InvalidInput("Session state has inconsistent session IDs".to_string()));
+        }
+        snapshot.compression.validate()?;
+
+        Ok(Session {
+            session_id: snapshot.session_id,
+            peer_public_key: crate::protocol::handshake::deserialize_deniable_public_key(&snapshot.peer_public_key)?,
+            session_keys: restore_keys(snapshot.session_keys),
+            message_crypto: MessageCrypto::from_snapshot(snapshot.message_crypto),
+            state: snapshot.state,
+            info: snapshot.info,
+            rotation_policy: snapshot.rotation_policy,
+            rotation_count: snapshot.rotation_count,
+            last_rotation_time: snapshot.last_rotation_time,
+            audit_sink,
+            negotiated_rotation: snapshot.negotiated_rotation,
+            // Restored requests are due for retransmission straight away
+            pending_rotation: snapshot.pending_rotation.map(|(sequence, keys, message_crypto, request)| PendingRotation {
+                sequence,
+                session_keys: restore_keys(keys),
+                message_crypto: MessageCrypto::from_snapshot(message_crypto),
+                request,
+                sent_at: 0,
+            }),
+            outgoing_rotation: snapshot.outgoing_rotation,
+            previous_crypto: snapshot.previous_crypto
+                .map(|(message_crypto, expires_at)| PreviousKeys {
+                    message_crypto: MessageCrypto::from_snapshot(message_crypto),
+                    expires_at,
+                }),
+            compression: snapshot.compression,
+            unacked: snapshot.unacked,
+            resumption_secret: snapshot.resumption_secret,
+            selected_algorithms: snapshot.selected_algorithms,
         })
     }
 
@@ -159,18 +582,26 @@ impl Session {
         if self.state != SessionState::Active {
             return Err(CryptoError::InvalidInput("Session not active".to_string()));
         }
+        self.expire_previous_keys();
 
         // Encrypt message
-        let encrypted = self.message_crypto.encrypt(message)?;
+        let mut encrypted = self.message_crypto.encrypt_compressed(message, self.compression)?;
+        encrypted.flags |= self.epoch_flag();
 
         // Update statistics
         self.info.messages_sent += 1;
         self.info.bytes_sent += encrypted.payload.len() as u64;
         self.update_activity();
+        self.track_unacked(encrypted.sequence);
 
         // Check if rotation needed and perform automatic rotation
         if self.needs_rotation() {
-            if let Err(e) = self.perform_key_rotation() {
+            let result = if self.negotiated_rotation.is_some() {
+                self.begin_key_rotation().map(|_| ())
+            } else {
+                self.perform_key_rotation().map(|_| ())
+            };
+            if let Err(e) = result {
                 warn!("Key rotation failed: {}", e);
             }
         }
@@ -183,15 +614,63 @@ impl Session {
         if self.state != SessionState::Active {
             return Err(CryptoError::InvalidInput("Session not active".to_string()));
         }
+        self.expire_previous_keys();
 
         let mut encrypted = self.message_crypto.encrypt(message)?;
-        encrypted.flags |= flags::DUMMY_TRAFFIC;
+        encrypted.flags |= flags::DUMMY_TRAFFIC | self.epoch_flag();
+        self.info.messages_sent += 1;
+        self.info.bytes_sent += encrypted.payload.len() as u64;
+        self.update_activity();
+        Ok(encrypted)
+    }
+
+    /// Acknowledge the peer's data message with sequence number `counter`
+    ///
+    /// The ACK is encrypted and authenticated like a data message, with its
+    /// type bound into the associated data. ACKs are not tracked themselves.
+    pub fn send_ack(&mut self, counter: u64) -> CryptoResult<EncryptedMessage> {
+        if self.state != SessionState::Active {
+            return Err(CryptoError::InvalidInput("Session not active".to_string()));
+        }
+        self.expire_previous_keys();
+
+        let message = Message::binary(counter.to_be_bytes().to_vec());
+        let mut encrypted = self.message_crypto.encrypt_as(MessageType::Ack, &message)?;
+        encrypted.flags |= self.epoch_flag();
         self.info.messages_sent += 1;
         self.info.bytes_sent += encrypted.payload.len() as u64;
         self.update_activity();
         Ok(encrypted)
     }
 
+    /// Sequence numbers of sent data messages the peer has not acknowledged,
+    /// in ascending order
+    ///
+    /// Sequence numbers restart when the keys rotate; a message still
+    /// unacknowledged under the previous keys is superseded by the first new
+    /// message that reuses its number.
+    pub fn unacked_counters(&self) -> Vec<u64> {
+        self.unacked.keys().copied().collect()
+    }
+
+    /// Timestamp (seconds) at which an unacknowledged message was sent
+    pub fn unacked_since(&self, counter: u64) -> Option<u64> {
+        self.unacked.get(&counter).copied()
+    }
+
+    /// Record a sent data message, evicting the oldest beyond [`MAX_UNACKED`]
+    fn track_unacked(&mut self, sequence: u64) {
+        if self.unacked.len() >= MAX_UNACKED && !self.unacked.contains_key(&sequence) {
+            let oldest = self.unacked.iter()
+                .min_by_key(|&(&counter, &sent_at)| (sent_at, counter))
+                .map(|(&counter, _)| counter);
+            if let Some(oldest) = oldest {
+                self.unacked.remove(&oldest);
+            }
+        }
+        self.unacked.insert(sequence, self.info.last_activity);
+    }
+
     /// Perform key rotation - derives new session keys
     pub fn perform_key_rotation(&mut self) -> CryptoResult<KeyRotationMessage> {
         info!("Performing key rotation #{}", self.rotation_count + 1);
@@ -199,55 +678,261 @@ impl Session {
         let now = time::current_time_secs();
         
         // Generate new key material using HKDF from current keys
-        let rotation_context = format!("B4AE-v1-key-rotation-{}", self.rotation_count + 1);
-        
-        // Derive new encryption key
-        let new_encryption_key = hkdf::derive_key(
-            &[&self.session_keys.encryption_key, &self.rotation_count.to_be_bytes()],
-            rotation_context.as_bytes(),
-            32
-        )?;
-        
-        // Derive new authentication key
-        let new_auth_key = hkdf::derive_key(
-            &[&self.session_keys.authentication_key, &self.rotation_count.to_be_bytes()],
-            rotation_context.as_bytes(),
-            32
-        )?;
-        
-        // Derive new metadata key
-        let new_metadata_key = hkdf::derive_key(
-            &[&self.session_keys.metadata_key, &self.rotation_count.to_be_bytes()],
-            rotation_context.as_bytes(),
-            32
-        )?;
+        let new_keys = self.derive_rotated_keys()?;
+        let new_encryption_key = new_keys.encryption_key.clone();
         
         // Create new PFS+ session with rotated keys
         let new_pfs_session = PfsSession::new(
-            &new_encryption_key,
-            &new_encryption_key,
+            &new_keys.encryption_key,
+            &new_keys.encryption_key,
             self.session_id,
         )?;
         
-        // Update session keys
-        self.session_keys = SessionKeys {
-            encryption_key: new_encryption_key.clone(),
-            authentication_key: new_auth_key,
-            metadata_key: new_metadata_key,
-        };
+        // Update session keys and message crypto
+        self.install_rotated_keys(new_keys, MessageCrypto::new(new_pfs_session), now);
         
-        // Update message crypto
-        self.message_crypto = MessageCrypto::new(new_pfs_session);
+        info!("Key rotation #{} completed successfully", self.rotation_count);
         
+        Ok(KeyRotationMessage {
+            new_key_material: new_encryption_key,
+            rotation_sequence: self.rotation_count,
+            timestamp: now,
+        })
+    }
+
+    /// Start a negotiated key rotation
+    ///
+    /// Derives the next keys but keeps sending under the current ones until
+    /// [`complete_key_rotation`](Self::complete_key_rotation) verifies the
+    /// peer's acknowledgment. The returned request is also queued for
+    /// [`take_rotation_request`](Self::take_rotation_request).
+    ///
+    /// The rotation runs in two phases so neither side sends under keys the
+    /// other cannot read yet:
+    ///
+    /// ```text
+    /// initiator                                   responder
+    /// begin_key_rotation()   -- KeyRotation -->   accept_peer_rotation()
+    ///   (sends old keys)        commitment          checks commitment,
+    ///                                               installs new keys
+    /// complete_key_rotation() <-- Ack ---------     (sends new keys)
+    ///   checks confirmation,     confirmation
+    ///   installs new keys
+    /// ```
+    ///
+    /// The request carries a commitment to the rotated keys rather than the
+    /// keys; a responder that derives different keys rejects it and keeps the
+    /// old ones. Each side keeps its previous keys for
+    /// [`ROTATION_GRACE_PERIOD`] after installing, to decrypt messages the
+    /// peer sent under them before the switch.
+    ///
+    /// Either message may be lost. An unacknowledged request is handed out
+    /// again by [`take_rotation_request`](Self::take_rotation_request) every
+    /// [`ROTATION_RETRANSMIT_INTERVAL`]; a responder that already accepted it
+    /// answers a retransmission with the same acknowledgment. A message from
+    /// the peer under the new keys also completes the rotation, since the
+    /// peer only sends under them after accepting.
+    pub fn begin_key_rotation(&mut self) -> CryptoResult<KeyRotationMessage> {
+        if let Some(pending) = &self.pending_rotation {
+            return Err(CryptoError::InvalidInput(format!(
+                "Key rotation #{} already pending",
+                pending.sequence
+            )));
+        }
+
+        let sequence = self.rotation_count + 1;
+        info!("Requesting key rotation #{}", sequence);
+
+        let session_keys = self.derive_rotated_keys()?;
+        let pfs_session = PfsSession::new(
+            &session_keys.encryption_key,
+            &session_keys.encryption_key,
+            self.session_id,
+        )?;
+        let request = KeyRotationMessage {
+            new_key_material: rotation_confirmation(&session_keys, sequence, "request")?,
+            rotation_sequence: sequence,
+            timestamp: time::current_time_secs(),
+        };
+
+        self.pending_rotation = Some(PendingRotation {
+            sequence,
+            session_keys,
+            message_crypto: MessageCrypto::new(pfs_session),
+            request: request.clone(),
+            sent_at: request.timestamp,
+        });
+        self.outgoing_rotation = Some(request.clone());
+        Ok(request)
+    }
+
+    /// Take the queued rotation request, if automatic rotation produced one
+    /// or the pending request has gone unacknowledged for
+    /// [`ROTATION_RETRANSMIT_INTERVAL`]
+    pub fn take_rotation_request(&mut self) -> Option<KeyRotationMessage> {
+        if let Some(request) = self.outgoing_rotation.take() {
+            return Some(request);
+        }
+        let now = time::current_time_secs();
+        let pending = self.pending_rotation.as_mut()?;
+        if now.saturating_sub(pending.sent_at) < ROTATION_RETRANSMIT_INTERVAL.as_secs() {
+            return None;
+        }
+        pending.sent_at = now;
+        Some(pending.request.clone())
+    }
+
+    /// Whether a rotation we initiated is waiting for the peer's acknowledgment
+    pub fn rotation_pending(&self) -> bool {
+        self.pending_rotation.is_some()
+    }
+
+    /// Accept a negotiated rotation request from the peer
+    ///
+    /// Installs the rotated keys for sending and keeps the previous keys to
+    /// decrypt messages the peer sent before it saw the acknowledgment. They
+    /// are dropped once a message under the new keys arrives.
+    pub fn accept_peer_rotation(&mut self, request: &KeyRotationMessage) -> CryptoResult<KeyRotationAck> {
+        if self.rotation_count > 0 && request.rotation_sequence == self.rotation_count {
+            // Retransmission of the request we installed: our ack was lost
+            let expected = rotation_confirmation(&self.session_keys, self.rotation_count, "request")?;
+            if !bool::from(expected.ct_eq(&request.new_key_material)) {
+                return Err(CryptoError::AuthenticationFailed);
+            }
+            return Ok(KeyRotationAck {
+                rotation_sequence: self.rotation_count,
+                confirmation: rotation_confirmation(&self.session_keys, self.rotation_count, "ack")?,
+            });
+        }
+
+        let sequence = self.rotation_count + 1;
+        if request.rotation_sequence != sequence {
+            return Err(CryptoError::InvalidInput(format!(
+                "Unexpected rotation sequence: expected {}, got {}",
+                sequence, request.rotation_sequence
+            )));
+        }
+
+        let session_keys = self.derive_rotated_keys()?;
+        let expected = rotation_confirmation(&session_keys, sequence, "request")?;
+        if !bool::from(expected.ct_eq(&request.new_key_material)) {
+            return Err(CryptoError::AuthenticationFailed);
+        }
+        let confirmation = rotation_confirmation(&session_keys, sequence, "ack")?;
+
+        // Both sides requested the same rotation: the derived keys are equal
+        self.pending_rotation = None;
+        self.outgoing_rotation = None;
+
+        let pfs_session = PfsSession::new(
+            &session_keys.encryption_key,
+            &session_keys.encryption_key,
+            self.session_id,
+        )?;
+        let old_crypto = self.install_rotated_keys(
+            session_keys,
+            MessageCrypto::new(pfs_session),
+            time::current_time_secs(),
+        );
+        self.keep_previous_keys(old_crypto);
+
+        Ok(KeyRotationAck {
+            rotation_sequence: sequence,
+            confirmation,
+        })
+    }
+
+    /// Finish a rotation started with [`begin_key_rotation`](Self::begin_key_rotation)
+    ///
+    /// The old keys are dropped only after the acknowledgment verifies.
+    pub fn complete_key_rotation(&mut self, ack: &KeyRotationAck) -> CryptoResult<()> {
+        let pending = match self.pending_rotation.take() {
+            Some(pending) => pending,
+            // Already completed through a crossing request from the peer
+            None if ack.rotation_sequence == self.rotation_count => return Ok(()),
+            None => {
+                return Err(CryptoError::InvalidInput("No key rotation pending".to_string()));
+            }
+        };
+
+        let expected = rotation_confirmation(&pending.session_keys, pending.sequence, "ack")?;
+        if ack.rotation_sequence != pending.sequence
+            || !bool::from(expected.ct_eq(&ack.confirmation))
+        {
+            self.pending_rotation = Some(pending);
+            return Err(CryptoError::AuthenticationFailed);
+        }
+
+        self.install_pending_rotation(pending);
+        info!("Key rotation #{} acknowledged by peer", self.rotation_count);
+        Ok(())
+    }
+
+    /// Switch to the keys of a rotation the peer has accepted
+    fn install_pending_rotation(&mut self, pending: PendingRotation) {
+        self.outgoing_rotation = None;
+        let old_crypto = self.install_rotated_keys(pending.session_keys, pending.message_crypto, time::current_time_secs());
+        // The peer may have sent under the old keys before it saw our request
+        self.keep_previous_keys(old_crypto);
+    }
+
+    /// Keep `message_crypto` for decrypting for [`ROTATION_GRACE_PERIOD`]
+    fn keep_previous_keys(&mut self, message_crypto: MessageCrypto) {
+        self.previous_crypto = Some(PreviousKeys {
+            message_crypto,
+            expires_at: time::current_time_secs().saturating_add(ROTATION_GRACE_PERIOD.as_secs()),
+        });
+    }
+
+    /// Drop the previous epoch's keys once their grace period has passed
+    ///
+    /// Called on every send and receive; [`SessionManager::cleanup_inactive`]
+    /// also calls it, so idle sessions do not hold old keys indefinitely.
+    pub fn expire_previous_keys(&mut self) {
+        if self.previous_crypto.as_ref().is_some_and(|p| time::current_time_secs() > p.expires_at) {
+            self.previous_crypto = None;
+        }
+    }
+
+    /// Derive the keys for rotation `rotation_count + 1` from the current keys
+    fn derive_rotated_keys(&self) -> CryptoResult<SessionKeys> {
+        let rotation_context = format!("B4AE-v1-key-rotation-{}", self.rotation_count + 1);
+        let derive = |key: &[u8]| {
+            hkdf::derive_key(
+                &[key, &self.rotation_count.to_be_bytes()],
+                rotation_context.as_bytes(),
+                32
+            )
+        };
+
+        Ok(SessionKeys {
+            encryption_key: derive(&self.session_keys.encryption_key)?,
+            authentication_key: derive(&self.session_keys.authentication_key)?,
+            metadata_key: derive(&self.session_keys.metadata_key)?,
+        })
+    }
+
+    /// Switch to rotated keys and reset the rotation counters
+    ///
+    /// Returns the message crypto of the previous keys.
+    fn install_rotated_keys(
+        &mut self,
+        session_keys: SessionKeys,
+        message_crypto: MessageCrypto,
+        now: u64,
+    ) -> MessageCrypto {
+        self.session_keys = session_keys;
+        let old_crypto = std::mem::replace(&mut self.message_crypto, message_crypto);
+
         // Update rotation tracking
         self.rotation_count += 1;
         self.last_rotation_time = now;
-        
+
         // Reset counters for next rotation cycle
         self.info.messages_sent = 0;
         self.info.bytes_sent = 0;
         self.info.established_at = now;
-        
+
         if let Some(sink) = &self.audit_sink {
             sink.log(AuditEntry::new(
                 AuditEvent::KeyRotation {
@@ -256,14 +941,17 @@ impl Session {
                 None,
             ));
         }
-        
-        info!("Key rotation #{} completed successfully", self.rotation_count);
-        
-        Ok(KeyRotationMessage {
-            new_key_material: new_encryption_key,
-            rotation_sequence: self.rotation_count,
-            timestamp: now,
-        })
+
+        old_crypto
+    }
+
+    /// Flag marking which key epoch a message was encrypted under
+    fn epoch_flag(&self) -> u8 {
+        if self.rotation_count % 2 == 1 {
+            flags::KEY_EPOCH
+        } else {
+            0
+        }
     }
 
     /// Apply key rotation received from peer
@@ -280,39 +968,17 @@ impl Session {
         }
         
         // Generate matching keys using same derivation
-        let rotation_context = format!("B4AE-v1-key-rotation-{}", rotation_msg.rotation_sequence);
-        
-        let new_encryption_key = hkdf::derive_key(
-            &[&self.session_keys.encryption_key, &self.rotation_count.to_be_bytes()],
-            rotation_context.as_bytes(),
-            32
-        )?;
-        
-        let new_auth_key = hkdf::derive_key(
-            &[&self.session_keys.authentication_key, &self.rotation_count.to_be_bytes()],
-            rotation_context.as_bytes(),
-            32
-        )?;
-        
-        let new_metadata_key = hkdf::derive_key(
-            &[&self.session_keys.metadata_key, &self.rotation_count.to_be_bytes()],
-            rotation_context.as_bytes(),
-            32
-        )?;
+        let new_keys = self.derive_rotated_keys()?;
         
         // Create new PFS+ session
         let new_pfs_session = PfsSession::new(
-            &new_encryption_key,
-            &new_encryption_key,
+            &new_keys.encryption_key,
+            &new_keys.encryption_key,
             self.session_id,
         )?;
         
         // Update session
-        self.session_keys = SessionKeys {
-            encryption_key: new_encryption_key,
-            authentication_key: new_auth_key,
-            metadata_key: new_metadata_key,
-        };
+        self.session_keys = new_keys;
         self.message_crypto = MessageCrypto::new(new_pfs_session);
         self.rotation_count = rotation_msg.rotation_sequence;
         self.last_rotation_time = rotation_msg.timestamp;
@@ -322,6 +988,13 @@ impl Session {
         Ok(())
     }
 
+    /// Set compression for outgoing data messages (dummy traffic is never compressed)
+    pub fn set_compression(&mut self, compression: Compression) -> CryptoResult<()> {
+        compression.validate()?;
+        self.compression = compression;
+        Ok(())
+    }
+
     /// Get rotation count
     pub fn rotation_count(&self) -> u64 {
         self.rotation_count
@@ -333,14 +1006,101 @@ impl Session {
         now.saturating_sub(self.last_rotation_time)
     }
 
-    /// Receive and decrypt message
+    /// Process an incoming frame, dispatching on its message type
+    ///
+    /// A CLOSE is only honoured if it decrypts under the session keys of the
+    /// epoch it is marked with (so a close also works mid-rotation); an
+    /// unauthenticated CLOSE returns an error and leaves the session active.
+    /// Data received after either side closed is rejected with
+    /// `B4aeError::ProtocolError`. An ACK must authenticate as well and name
+    /// a sequence number that is still unacknowledged.
+    pub fn process(&mut self, encrypted: &EncryptedMessage) -> B4aeResult<SessionEvent> {
+        if self.state == SessionState::Closed {
+            return Err(B4aeError::ProtocolError("Session closed".to_string()));
+        }
+
+        if encrypted.message_type == MessageType::Ack as u8 {
+            return self.process_ack(encrypted);
+        }
+        if encrypted.message_type != MessageType::Close as u8 {
+            return Ok(SessionEvent::Message(self.decrypt_frame(encrypted)?));
+        }
+
+        if self.state != SessionState::Active {
+            return Err(B4aeError::ProtocolError("Session not active".to_string()));
+        }
+        let message = self.decrypt_frame(encrypted)
+            .map_err(|_| B4aeError::AuthenticationFailed)?;
+        let reason = match &message.content {
+            MessageContent::Binary(code) if code.len() == 1 => CloseReason::from_u8(code[0])?,
+            _ => return Err(B4aeError::ProtocolError("Malformed CLOSE".to_string())),
+        };
+
+        info!("Session closed by peer ({:?})", reason);
+        self.wipe_keys()?;
+        Ok(SessionEvent::Closed(reason))
+    }
+
+    /// Verify an ACK and remove the acknowledged message from the tracker
+    fn process_ack(&mut self, encrypted: &EncryptedMessage) -> B4aeResult<SessionEvent> {
+        let message = self.decrypt_frame(encrypted).map_err(|_| B4aeError::AuthenticationFailed)?;
+        let counter = match &message.content {
+            MessageContent::Binary(bytes) => <[u8; 8]>::try_from(bytes.as_slice())
+                .map(u64::from_be_bytes)
+                .map_err(|_| B4aeError::ProtocolError("Malformed ACK".to_string()))?,
+            _ => return Err(B4aeError::ProtocolError("Malformed ACK".to_string())),
+        };
+
+        if self.unacked.remove(&counter).is_none() {
+            return Err(B4aeError::ProtocolError(format!(
+                "ACK for unknown message counter: {}",
+                counter
+            )));
+        }
+        Ok(SessionEvent::Acked(counter))
+    }
+
+    /// Receive and decrypt a data message
+    ///
+    /// ACK and CLOSE frames are rejected; they only take effect through
+    /// [`process`](Self::process).
     pub fn receive(&mut self, encrypted: &EncryptedMessage) -> CryptoResult<Message> {
+        if encrypted.message_type == MessageType::Ack as u8
+            || encrypted.message_type == MessageType::Close as u8
+        {
+            return Err(CryptoError::InvalidInput(
+                "Control frame must be handled by Session::process".to_string(),
+            ));
+        }
+        self.decrypt_frame(encrypted)
+    }
+
+    /// Decrypt any frame under the key epoch it is marked with
+    fn decrypt_frame(&mut self, encrypted: &EncryptedMessage) -> CryptoResult<Message> {
         if self.state != SessionState::Active {
             return Err(CryptoError::InvalidInput("Session not active".to_string()));
         }
 
-        // Decrypt message
-        let message = self.message_crypto.decrypt(encrypted)?;
+        // Decrypt under the key epoch the sender marked. The previous keys
+        // stay until their grace period ends, even after the peer switched,
+        // so messages reordered across the rotation still decrypt.
+        self.expire_previous_keys();
+        let current_epoch = encrypted.flags & flags::KEY_EPOCH == self.epoch_flag();
+        let message = match (&mut self.pending_rotation, &mut self.previous_crypto) {
+            // The peer accepted our rotation and already sends under the new
+            // keys, which stands in for an acknowledgment that was lost
+            (Some(pending), _) if !current_epoch => {
+                let message = pending.message_crypto.decrypt(encrypted)?;
+                if let Some(pending) = self.pending_rotation.take() {
+                    self.install_pending_rotation(pending);
+                    info!("Key rotation #{} confirmed by peer traffic", self.rotation_count);
+                }
+                message
+            }
+            // Sent before the peer switched keys
+            (None, Some(previous)) if !current_epoch => previous.message_crypto.decrypt(encrypted)?,
+            _ => self.message_crypto.decrypt(encrypted)?,
+        };
 
         // Update statistics
         self.info.messages_received += 1;
@@ -354,6 +1114,14 @@ impl Session {
     pub fn needs_rotation(&self) -> bool {
         let now = time::current_time_secs();
 
+        if let Some(policy) = &self.negotiated_rotation {
+            let age = Duration::from_secs(now.saturating_sub(self.last_rotation_time));
+            return self.pending_rotation.is_none()
+                && (self.info.bytes_sent >= policy.max_bytes
+                    || self.info.messages_sent >= policy.max_messages
+                    || age >= policy.max_age);
+        }
+
         // Check time-based rotation (saturating_sub for clock skew safety)
         if let Some(time_limit) = self.rotation_policy.time_based {
             if now.saturating_sub(self.info.established_at) > time_limit {
@@ -398,6 +1166,11 @@ impl Session {
         &self.session_id
     }
 
+    /// Algorithms selected in the handshake that created the session
+    pub fn selected_algorithms(&self) -> &[AlgorithmId] {
+        &self.selected_algorithms
+    }
+
     /// Get peer public key
     pub fn peer_public_key(&self) -> &DeniableHybridPublicKey {
         &self.peer_public_key
@@ -414,8 +1187,40 @@ impl Session {
     }
 
     /// Close session
-    pub fn close(&mut self) {
+    ///
+    /// Returns an authenticated CLOSE for the peer carrying `reason`, then
+    /// zeroizes the session keys. Closing a session that is not active only
+    /// marks it closed and returns `B4aeError::ProtocolError`.
+    pub fn close(&mut self, reason: CloseReason) -> B4aeResult<EncryptedMessage> {
+        if self.state != SessionState::Active {
+            self.state = SessionState::Closed;
+            return Err(B4aeError::ProtocolError("Session not active".to_string()));
+        }
+
+        let message = Message::binary(vec![reason as u8]);
+        let mut encrypted = self.message_crypto.encrypt_as(MessageType::Close, &message)?;
+        encrypted.flags |= self.epoch_flag();
+
+        self.wipe_keys()?;
+        Ok(encrypted)
+    }
+
+    /// Mark the session closed and drop all key material (zeroized on drop)
+    fn wipe_keys(&mut self) -> CryptoResult<()> {
         self.state = SessionState::Closed;
+        self.info.state = SessionState::Closed;
+        self.session_keys = SessionKeys {
+            encryption_key: Vec::new(),
+            authentication_key: Vec::new(),
+            metadata_key: Vec::new(),
+        };
+        self.message_crypto = MessageCrypto::new(PfsSession::new(&[0u8; 32], &[0u8; 32], self.session_id)?);
+        self.pending_rotation = None;
+        self.outgoing_rotation = None;
+        self.previous_crypto = None;
+        self.unacked.clear();
+        self.resumption_secret = Zeroizing::new(Vec::new());
+        Ok(())
     }
 
     /// Update last activity timestamp
@@ -424,6 +1229,12 @@ impl Session {
     }
 }
 
+/// Confirmation value proving knowledge of rotated keys
+fn rotation_confirmation(keys: &SessionKeys, sequence: u64, role: &str) -> CryptoResult<Vec<u8>> {
+    let context = format!("B4AE-v1-key-rotation-{}-{}", role, sequence);
+    hkdf::derive_key(&[&keys.authentication_key], context.as_bytes(), 32)
+}
+
 /// Session manager for handling multiple sessions
 pub struct SessionManager {
     /// Active sessions
@@ -471,7 +1282,7 @@ impl SessionManager {
             .collect()
     }
 
-    /// Cleanup inactive sessions
+    /// Cleanup inactive sessions and expired previous-epoch keys
     pub fn cleanup_inactive(&mut self) {
         let now = time::current_time_secs();
 
@@ -479,6 +1290,9 @@ impl SessionManager {
             let inactive_time = now.saturating_sub(session.info.last_activity);
             inactive_time < self.session_timeout
         });
+        for session in self.sessions.values_mut() {
+            session.expire_previous_keys();
+        }
     }
 
     /// Check all sessions for rotation needs
@@ -546,6 +1360,7 @@ mod tests {
             session_keys,
             peer_public_key: create_test_public_key(),
             session_id: [0x46; 32],
+            selected_algorithms: vec![AlgorithmId::Kyber1024, AlgorithmId::Aes256Gcm],
         }
     }
 
@@ -600,6 +1415,414 @@ mod tests {
         assert!(session.needs_rotation());
     }
 
+    fn negotiated_pair(policy: RotationPolicy) -> (Session, Session) {
+        let config = SessionConfig { rotation: Some(policy), ..SessionConfig::default() };
+        let alice = Session::from_handshake_with_config(
+            create_test_handshake_result(), vec![0x47; 32], None, config.clone(),
+        ).unwrap();
+        let bob = Session::from_handshake_with_config(
+            create_test_handshake_result(), vec![0x48; 32], None, config,
+        ).unwrap();
+        (alice, bob)
+    }
+
+    fn byte_policy(max_bytes: u64) -> RotationPolicy {
+        RotationPolicy {
+            max_bytes,
+            max_messages: u64::MAX,
+            max_age: Duration::from_secs(3600),
+        }
+    }
+
+    fn text_of(message: &Message) -> &str {
+        match &message.content {
+            crate::protocol::message::MessageContent::Text(text) => text,
+            other => panic!("unexpected content: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_byte_threshold_triggers_negotiated_rotation() {
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(200));
+
+        let mut sent = Vec::new();
+        while alice.take_rotation_request().is_none() {
+            assert!(sent.len() < 100, "byte threshold never triggered");
+            sent.push(alice.send(&Message::text("x".repeat(40))).unwrap());
+        }
+        assert!(alice.info().bytes_sent >= 200);
+        assert!(alice.rotation_pending());
+
+        // Old keys stay in use until the peer acknowledges
+        assert_eq!(alice.rotation_count(), 0);
+        assert!(alice.take_rotation_request().is_none());
+        for encrypted in &sent {
+            bob.receive(encrypted).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_decryption_continues_across_rotation() {
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(200));
+
+        let mut request = None;
+        while request.is_none() {
+            let encrypted = alice.send(&Message::text("x".repeat(40))).unwrap();
+            bob.receive(&encrypted).unwrap();
+            request = alice.take_rotation_request();
+        }
+        let request = request.unwrap();
+
+        // In flight while Bob processes the request
+        let before_ack = alice.send(&Message::text("old keys")).unwrap();
+
+        let ack = bob.accept_peer_rotation(&request).unwrap();
+        assert_eq!(bob.rotation_count(), 1);
+
+        // Bob already sends under the new keys; reading it completes Alice's
+        // rotation before the ack lands
+        let from_bob = bob.send(&Message::text("new keys from bob")).unwrap();
+        assert_eq!(text_of(&alice.receive(&from_bob).unwrap()), "new keys from bob");
+
+        // Bob still reads Alice's pre-ack message with the previous keys
+        assert_eq!(text_of(&bob.receive(&before_ack).unwrap()), "old keys");
+
+        alice.complete_key_rotation(&ack).unwrap();
+        assert_eq!(alice.rotation_count(), 1);
+        assert!(!alice.rotation_pending());
+
+        let after = alice.send(&Message::text("new keys from alice")).unwrap();
+        assert_eq!(text_of(&bob.receive(&after).unwrap()), "new keys from alice");
+        // Kept until the grace period ends, not dropped on the first new-key message
+        assert!(bob.previous_crypto.is_some());
+    }
+
+    #[test]
+    fn test_reordered_messages_decrypt_across_rotation() {
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(u64::MAX));
+
+        let request = alice.begin_key_rotation().unwrap();
+        let old_first = bob.send(&Message::text("old one")).unwrap();
+        let old_second = bob.send(&Message::text("old two")).unwrap();
+        let ack = bob.accept_peer_rotation(&request).unwrap();
+        alice.complete_key_rotation(&ack).unwrap();
+        let new_message = bob.send(&Message::text("new")).unwrap();
+
+        // A new-epoch message overtakes both old-epoch messages
+        assert_eq!(text_of(&alice.receive(&old_first).unwrap()), "old one");
+        assert_eq!(text_of(&alice.receive(&new_message).unwrap()), "new");
+        assert_eq!(text_of(&alice.receive(&old_second).unwrap()), "old two");
+
+        // Sending also expires the previous keys once their grace period ends
+        alice.previous_crypto.as_mut().unwrap().expires_at = time::current_time_secs() - 1;
+        alice.send(&Message::text("tick")).unwrap();
+        assert!(alice.previous_crypto.is_none());
+    }
+
+    #[test]
+    fn test_forged_rotation_ack_keeps_old_keys() {
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(u64::MAX));
+
+        let request = alice.begin_key_rotation().unwrap();
+        let mut ack = bob.accept_peer_rotation(&request).unwrap();
+        ack.confirmation[0] ^= 0x01;
+
+        assert!(alice.complete_key_rotation(&ack).is_err());
+        assert!(alice.rotation_pending());
+        assert_eq!(alice.rotation_count(), 0);
+
+        let mut forged = request.clone();
+        forged.new_key_material[0] ^= 0x01;
+        let (_, mut carol) = negotiated_pair(byte_policy(u64::MAX));
+        assert!(carol.accept_peer_rotation(&forged).is_err());
+        assert_eq!(carol.rotation_count(), 0);
+    }
+
+    #[test]
+    fn test_clean_negotiated_rekey() {
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(u64::MAX));
+
+        let request = alice.begin_key_rotation().unwrap();
+        assert!(alice.begin_key_rotation().is_err());
+        let ack = bob.accept_peer_rotation(&request).unwrap();
+        alice.complete_key_rotation(&ack).unwrap();
+        assert_eq!((alice.rotation_count(), bob.rotation_count()), (1, 1));
+        assert_eq!(alice.session_keys.encryption_key, bob.session_keys.encryption_key);
+
+        let to_bob = alice.send(&Message::text("after rekey")).unwrap();
+        assert_eq!(text_of(&bob.receive(&to_bob).unwrap()), "after rekey");
+        let to_alice = bob.send(&Message::text("reply")).unwrap();
+        assert_eq!(text_of(&alice.receive(&to_alice).unwrap()), "reply");
+        assert!(alice.previous_crypto.is_some() && bob.previous_crypto.is_some());
+
+        // A replayed request or ack does not rotate again; the request is
+        // acknowledged again in case the first ack was lost
+        let again = bob.accept_peer_rotation(&request).unwrap();
+        assert_eq!(again.confirmation, ack.confirmation);
+        alice.complete_key_rotation(&ack).unwrap();
+        assert_eq!((alice.rotation_count(), bob.rotation_count()), (1, 1));
+    }
+
+    #[test]
+    fn test_lost_rotation_request_is_retransmitted() {
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(u64::MAX));
+
+        let lost = alice.begin_key_rotation().unwrap();
+        assert!(alice.take_rotation_request().is_some());
+        assert!(alice.take_rotation_request().is_none());
+
+        // Still pending after the retransmit interval: the request comes back
+        alice.pending_rotation.as_mut().unwrap().sent_at -= ROTATION_RETRANSMIT_INTERVAL.as_secs();
+        let request = alice.take_rotation_request().unwrap();
+        assert_eq!(request.new_key_material, lost.new_key_material);
+        assert!(alice.take_rotation_request().is_none());
+
+        let ack = bob.accept_peer_rotation(&request).unwrap();
+        alice.complete_key_rotation(&ack).unwrap();
+        assert_eq!((alice.rotation_count(), bob.rotation_count()), (1, 1));
+    }
+
+    #[test]
+    fn test_lost_rotation_ack_recovers() {
+        // Retransmitted request: Bob acknowledges again without rotating twice
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(u64::MAX));
+        let request = alice.begin_key_rotation().unwrap();
+        let _lost = bob.accept_peer_rotation(&request).unwrap();
+        let ack = bob.accept_peer_rotation(&request).unwrap();
+        assert_eq!(bob.rotation_count(), 1);
+        alice.complete_key_rotation(&ack).unwrap();
+        assert_eq!(alice.rotation_count(), 1);
+        assert!(alice.begin_key_rotation().is_ok());
+
+        // Traffic under the new keys completes the rotation without the ack
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(u64::MAX));
+        let request = alice.begin_key_rotation().unwrap();
+        let _lost = bob.accept_peer_rotation(&request).unwrap();
+        let from_bob = bob.send(&Message::text("new keys")).unwrap();
+        assert_eq!(text_of(&alice.receive(&from_bob).unwrap()), "new keys");
+        assert!(!alice.rotation_pending());
+        assert_eq!(alice.rotation_count(), 1);
+        let to_bob = alice.send(&Message::text("reply")).unwrap();
+        assert_eq!(text_of(&bob.receive(&to_bob).unwrap()), "reply");
+    }
+
+    #[test]
+    fn test_in_flight_messages_decrypt_during_grace_window() {
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(u64::MAX));
+
+        let request = alice.begin_key_rotation().unwrap();
+        // Sent by Bob just before he processes the request
+        let before_rekey = bob.send(&Message::text("just before")).unwrap();
+        let late = bob.send(&Message::text("too late")).unwrap();
+        let ack = bob.accept_peer_rotation(&request).unwrap();
+        alice.complete_key_rotation(&ack).unwrap();
+
+        assert_eq!(text_of(&alice.receive(&before_rekey).unwrap()), "just before");
+
+        // Past the grace window the previous keys are gone
+        alice.previous_crypto.as_mut().unwrap().expires_at = time::current_time_secs() - 1;
+        assert!(alice.receive(&late).is_err());
+        assert!(alice.previous_crypto.is_none());
+
+        let current = bob.send(&Message::text("new keys")).unwrap();
+        assert_eq!(text_of(&alice.receive(&current).unwrap()), "new keys");
+    }
+
+    #[test]
+    fn test_rekey_with_mismatched_commitment_rejected() {
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(u64::MAX));
+
+        // Commitment to keys Bob does not share
+        let mut other = create_test_handshake_result();
+        other.session_keys.authentication_key = vec![0x99; 32];
+        let mut mallory = Session::from_handshake(other, vec![0x49; 32], None).unwrap();
+        let mismatched = mallory.begin_key_rotation().unwrap();
+        assert!(matches!(bob.accept_peer_rotation(&mismatched), Err(CryptoError::AuthenticationFailed)));
+        assert_eq!(bob.rotation_count(), 0);
+        assert!(bob.previous_crypto.is_none());
+
+        // Bob keeps the old keys, so the session still works both ways
+        let to_bob = alice.send(&Message::text("still old keys")).unwrap();
+        assert_eq!(text_of(&bob.receive(&to_bob).unwrap()), "still old keys");
+        let genuine = alice.begin_key_rotation().unwrap();
+        let ack = bob.accept_peer_rotation(&genuine).unwrap();
+        alice.complete_key_rotation(&ack).unwrap();
+        assert_eq!(alice.rotation_count(), 1);
+    }
+
+    fn plain_pair() -> (Session, Session) {
+        let alice = Session::from_handshake(create_test_handshake_result(), vec![0x47; 32], None).unwrap();
+        let bob = Session::from_handshake(create_test_handshake_result(), vec![0x48; 32], None).unwrap();
+        (alice, bob)
+    }
+
+    #[test]
+    fn test_clean_close() {
+        let (mut alice, mut bob) = plain_pair();
+
+        let hello = alice.send(&Message::text("hello")).unwrap();
+        assert!(matches!(bob.process(&hello).unwrap(), SessionEvent::Message(_)));
+
+        let close = alice.close(CloseReason::GoingAway).unwrap();
+        assert_eq!(close.message_type, MessageType::Close as u8);
+        assert!(!alice.is_active());
+        assert!(alice.metadata_key().is_empty());
+
+        match bob.process(&close).unwrap() {
+            SessionEvent::Closed(reason) => assert_eq!(reason, CloseReason::GoingAway),
+            other => panic!("expected close, got {:?}", other),
+        }
+        assert!(!bob.is_active());
+        assert!(bob.session_keys.encryption_key.is_empty());
+        assert!(bob.session_keys.authentication_key.is_empty());
+        assert!(bob.metadata_key().is_empty());
+    }
+
+    #[test]
+    fn test_data_after_close_rejected() {
+        let (mut alice, mut bob) = plain_pair();
+
+        let late = alice.send(&Message::text("sent before close")).unwrap();
+        let close = alice.close(CloseReason::Normal).unwrap();
+        bob.process(&close).unwrap();
+
+        assert!(matches!(bob.process(&late), Err(B4aeError::ProtocolError(_))));
+        assert!(matches!(alice.close(CloseReason::Normal), Err(B4aeError::ProtocolError(_))));
+        assert!(alice.send(&Message::text("after close")).is_err());
+    }
+
+    #[test]
+    fn test_unauthenticated_close_ignored() {
+        let (mut alice, mut bob) = plain_pair();
+
+        // A data message relabelled as CLOSE fails authentication
+        let mut relabelled = alice.send(&Message::binary(vec![0x00])).unwrap();
+        relabelled.message_type = MessageType::Close as u8;
+        assert!(bob.process(&relabelled).is_err());
+
+        // So does a CLOSE from a different session
+        let (mut mallory, _) = negotiated_pair(byte_policy(u64::MAX));
+        mallory.session_keys.encryption_key = vec![0x99; 32];
+        mallory.message_crypto = MessageCrypto::new(
+            PfsSession::new(&[0x99; 32], &[0x99; 32], [0x46; 32]).unwrap(),
+        );
+        let forged = mallory.close(CloseReason::Normal).unwrap();
+        assert!(bob.process(&forged).is_err());
+
+        assert!(bob.is_active());
+        let hello = alice.send(&Message::text("still open")).unwrap();
+        match bob.process(&hello).unwrap() {
+            SessionEvent::Message(message) => assert_eq!(text_of(&message), "still open"),
+            other => panic!("expected message, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_import_state_rejects_stream_archive() {
+        assert_ne!(SESSION_STATE_MAGIC, &crate::storage::stream::STREAM_MAGIC);
+
+        let mut archive = crate::storage::stream::STREAM_MAGIC.to_vec();
+        archive.extend_from_slice(&crate::PROTOCOL_VERSION.to_be_bytes());
+        assert!(matches!(
+            Session::import_state(&archive, None),
+            Err(CryptoError::InvalidInput(ref msg)) if msg == "Not a session state"
+        ));
+    }
+
+    #[test]
+    fn test_close_during_rotation() {
+        // Bob switched to the new keys but Alice's rotation is still pending
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(u64::MAX));
+        let request = alice.begin_key_rotation().unwrap();
+        let _ack = bob.accept_peer_rotation(&request).unwrap();
+        let close = bob.close(CloseReason::GoingAway).unwrap();
+        assert!(matches!(alice.process(&close).unwrap(), SessionEvent::Closed(CloseReason::GoingAway)));
+        assert!(!alice.is_active());
+
+        // Alice closes under the old keys while her rotation is pending; Bob
+        // already switched and accepts it within his grace window
+        let (mut alice, mut bob) = negotiated_pair(byte_policy(u64::MAX));
+        let request = alice.begin_key_rotation().unwrap();
+        let _ack = bob.accept_peer_rotation(&request).unwrap();
+        let close = alice.close(CloseReason::Normal).unwrap();
+        assert!(matches!(bob.process(&close).unwrap(), SessionEvent::Closed(CloseReason::Normal)));
+        assert!(!bob.is_active());
+    }
+
+    #[test]
+    fn test_ack_clears_only_acknowledged_message() {
+        let (mut alice, mut bob) = plain_pair();
+
+        let sent: Vec<_> = ["one", "two", "three"]
+            .iter()
+            .map(|text| alice.send(&Message::text(*text)).unwrap())
+            .collect();
+        assert_eq!(alice.unacked_counters().len(), 3);
+        for encrypted in &sent {
+            bob.process(encrypted).unwrap();
+        }
+
+        let middle = sent[1].sequence;
+        let ack = bob.send_ack(middle).unwrap();
+        assert_eq!(ack.message_type, MessageType::Ack as u8);
+        match alice.process(&ack).unwrap() {
+            SessionEvent::Acked(counter) => assert_eq!(counter, middle),
+            other => panic!("expected ack, got {:?}", other),
+        }
+
+        assert_eq!(alice.unacked_counters(), vec![sent[0].sequence, sent[2].sequence]);
+        assert!(alice.unacked_since(middle).is_none());
+        assert!(alice.unacked_since(sent[0].sequence).is_some());
+    }
+
+    #[test]
+    fn test_forged_ack_rejected() {
+        let (mut alice, mut bob) = plain_pair();
+        let sent = alice.send(&Message::text("hello")).unwrap();
+        bob.process(&sent).unwrap();
+
+        // Authenticated, but for a counter Alice never sent
+        let unknown = bob.send_ack(sent.sequence + 7).unwrap();
+        assert!(matches!(alice.process(&unknown), Err(B4aeError::ProtocolError(_))));
+
+        // A data message relabelled as ACK fails authentication
+        let mut relabelled = bob.send(&Message::binary(sent.sequence.to_be_bytes().to_vec())).unwrap();
+        relabelled.message_type = MessageType::Ack as u8;
+        assert!(matches!(alice.process(&relabelled), Err(B4aeError::AuthenticationFailed)));
+
+        // A tampered ACK fails authentication
+        let mut tampered = bob.send_ack(sent.sequence).unwrap();
+        tampered.payload[0] ^= 0x01;
+        assert!(matches!(alice.process(&tampered), Err(B4aeError::AuthenticationFailed)));
+
+        assert_eq!(alice.unacked_counters(), vec![sent.sequence]);
+    }
+
+    #[test]
+    fn test_receive_rejects_ack_frames() {
+        let (mut alice, mut bob) = plain_pair();
+        let sent = alice.send(&Message::text("hello")).unwrap();
+        bob.process(&sent).unwrap();
+
+        // An ACK passed to receive is neither surfaced as data nor applied
+        let ack = bob.send_ack(sent.sequence).unwrap();
+        assert!(alice.receive(&ack).is_err());
+        assert_eq!(alice.unacked_counters(), vec![sent.sequence]);
+        assert!(matches!(alice.process(&ack).unwrap(), SessionEvent::Acked(_)));
+    }
+
+    #[test]
+    fn test_unacked_is_bounded() {
+        let (mut alice, _) = plain_pair();
+        let mut first = None;
+        for _ in 0..MAX_UNACKED + 10 {
+            let sent = alice.send(&Message::text("unacknowledged")).unwrap();
+            first.get_or_insert(sent.sequence);
+        }
+        assert_eq!(alice.unacked_counters().len(), MAX_UNACKED);
+        assert!(alice.unacked_since(first.unwrap()).is_none());
+    }
+
     #[test]
     fn test_session_cleanup() {
         let mut manager = SessionManager::new();
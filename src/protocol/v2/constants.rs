@@ -85,6 +85,15 @@ pub const BLOOM_FILTER_SIZE: usize = 1_000_000;
 /// **Requirement**: REQ-4 (Replay Protection)
 pub const BLOOM_FILTER_FALSE_POSITIVE_RATE: f64 = 0.001;
 
+/// Maximum number of message IDs held by the replay cache
+///
+/// Bounds memory under flood (32 bytes per ID plus set overhead). When full,
+/// the oldest time bucket is dropped and its timestamps are rejected as
+/// outside the window, so no replay is let through.
+///
+/// **Requirement**: REQ-4 (Replay Protection)
+pub const REPLAY_CACHE_CAPACITY: usize = 100_000;
+
 /// Maximum message size in bytes (1 MiB)
 ///
 /// Limits memory usage and prevents DoS attacks via oversized messages.
@@ -172,6 +181,13 @@ pub const MODE_BINDING_SIZE: usize = 32;
 /// exhaustion from incomplete handshakes.
 pub const HANDSHAKE_TIMEOUT_SECONDS: u64 = 60;
 
+/// Tolerated clock skew between peers in seconds
+///
+/// Handshake message timestamps may lie this far in the future, and this
+/// much beyond [`HANDSHAKE_TIMEOUT_SECONDS`] in the past, before they are
+/// rejected.
+pub const MAX_CLOCK_SKEW_SECONDS: u64 = 300;
+
 /// Performance target: Cookie generation time (milliseconds)
 ///
 /// Target: ~0.02ms for HMAC-SHA256 computation
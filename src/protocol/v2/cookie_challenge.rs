@@ -47,9 +47,11 @@
 
 use sha2::{Digest, Sha256};
 use subtle::ConstantTimeEq;
+use std::sync::Arc;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use crate::protocol::v2::constants::{COOKIE_SIZE, COOKIE_TIMEOUT_SECONDS};
+use crate::protocol::v2::constants::{COOKIE_SIZE, COOKIE_TIMEOUT_SECONDS, MAX_CLOCK_SKEW_SECONDS};
+use crate::time::{Clock, SystemClock};
 
 /// Error type for cookie challenge operations
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -57,7 +59,7 @@ pub enum CookieChallengeError {
     /// Cookie verification failed (invalid HMAC)
     InvalidCookie,
     
-    /// Timestamp is expired (older than COOKIE_TIMEOUT_SECONDS)
+    /// Timestamp is expired (older than the cookie lifetime)
     ExpiredTimestamp,
     
     /// Timestamp is too far in the future (possible clock skew)
@@ -74,7 +76,7 @@ impl std::fmt::Display for CookieChallengeError {
                 write!(f, "Cookie verification failed: invalid HMAC")
             }
             CookieChallengeError::ExpiredTimestamp => {
-                write!(f, "Cookie expired: timestamp older than {} seconds", COOKIE_TIMEOUT_SECONDS)
+                write!(f, "Cookie expired: timestamp older than the cookie lifetime")
             }
             CookieChallengeError::FutureTimestamp => {
                 write!(f, "Cookie timestamp is too far in the future (possible clock skew)")
@@ -99,6 +101,8 @@ impl std::error::Error for CookieChallengeError {}
 /// The server can maintain multiple secrets for graceful rotation:
 /// - Current secret for new cookies
 /// - Previous secret(s) for verification during rotation window
+///
+/// See [`CookieSecretRing`] for a ready-made current/previous pair.
 #[derive(Clone, Zeroize, ZeroizeOnDrop)]
 pub struct ServerSecret([u8; 32]);
 
@@ -252,7 +256,7 @@ pub fn generate_cookie(
 ///
 /// ## Verification Steps
 ///
-/// 1. Check timestamp freshness (current_time - timestamp ≤ 30 seconds)
+/// 1. Check timestamp freshness (current_time - timestamp ≤ COOKIE_TIMEOUT_SECONDS)
 /// 2. Recompute expected cookie using same inputs
 /// 3. Compare using constant-time comparison
 ///
@@ -273,10 +277,7 @@ pub fn generate_cookie(
 ///
 /// let server_secret = ServerSecret::generate();
 /// let client_ip = "192.168.1.100";
-/// let timestamp = std::time::SystemTime::now()
-///     .duration_since(std::time::UNIX_EPOCH)
-///     .unwrap()
-///     .as_secs();
+/// let timestamp = b4ae::time::current_time_secs();
 /// let client_random = [0u8; 32];
 ///
 /// // Generate cookie
@@ -299,6 +300,30 @@ pub fn verify_cookie(
     client_ip: &str,
     timestamp: u64,
     client_random: &[u8],
+) -> Result<(), CookieChallengeError> {
+    verify_cookie_with_clock(
+        cookie,
+        server_secret,
+        client_ip,
+        timestamp,
+        client_random,
+        &SystemClock,
+        COOKIE_TIMEOUT_SECONDS,
+    )
+}
+
+/// [`verify_cookie`] against an injected clock and cookie lifetime
+///
+/// Cookies older than `lifetime_secs`, or more than
+/// [`MAX_CLOCK_SKEW_SECONDS`] in the future, are rejected.
+pub fn verify_cookie_with_clock(
+    cookie: &[u8],
+    server_secret: &ServerSecret,
+    client_ip: &str,
+    timestamp: u64,
+    client_random: &[u8],
+    clock: &dyn Clock,
+    lifetime_secs: u64,
 ) -> Result<(), CookieChallengeError> {
     // Validate cookie size
     if cookie.len() != COOKIE_SIZE {
@@ -306,18 +331,15 @@ pub fn verify_cookie(
     }
 
     // Check timestamp freshness
-    let current_time = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map_err(|_| CookieChallengeError::InvalidInput("System time error".to_string()))?
-        .as_secs();
+    let current_time = clock.now_unix_secs();
 
-    // Check if timestamp is expired (older than COOKIE_TIMEOUT_SECONDS)
-    if current_time > timestamp && (current_time - timestamp) > COOKIE_TIMEOUT_SECONDS {
+    // Check if timestamp is expired (older than the cookie lifetime)
+    if current_time > timestamp && (current_time - timestamp) > lifetime_secs {
         return Err(CookieChallengeError::ExpiredTimestamp);
     }
 
-    // Check if timestamp is too far in the future (allow 5 minute clock skew)
-    if timestamp > current_time && (timestamp - current_time) > 300 {
+    // Check if timestamp is too far in the future (tolerated clock skew)
+    if timestamp > current_time && (timestamp - current_time) > MAX_CLOCK_SKEW_SECONDS {
         return Err(CookieChallengeError::FutureTimestamp);
     }
 
@@ -334,6 +356,133 @@ pub fn verify_cookie(
     }
 }
 
+/// Current and previous server secrets for graceful rotation
+///
+/// New cookies are always issued under the current secret; verification
+/// accepts either secret, so cookies issued just before [`rotate`] stay
+/// valid for the rest of their lifetime instead of forcing every in-flight
+/// client to restart. A cookie survives exactly one rotation: after two,
+/// its secret has been retired and zeroized.
+///
+/// Rotate at intervals well above [`COOKIE_TIMEOUT_SECONDS`] (e.g. every
+/// 24 hours); rotating faster than the cookie lifetime would cut cookies short.
+///
+/// ## Example
+///
+/// ```rust
+/// use b4ae::protocol::v2::cookie_challenge::CookieSecretRing;
+///
+/// let mut ring = CookieSecretRing::new();
+/// let now = b4ae::time::current_time_secs();
+/// let client_random = [0u8; 32];
+///
+/// let cookie = ring.generate_cookie("192.168.1.100", now, &client_random).unwrap();
+/// ring.rotate();
+/// assert!(ring.verify_cookie(&cookie, "192.168.1.100", now, &client_random).is_ok());
+/// ```
+///
+/// [`rotate`]: CookieSecretRing::rotate
+pub struct CookieSecretRing {
+    current: ServerSecret,
+    previous: Option<ServerSecret>,
+    clock: Arc<dyn Clock>,
+    lifetime_secs: u64,
+}
+
+impl CookieSecretRing {
+    /// Creates a ring with a freshly generated current secret
+    pub fn new() -> Self {
+        Self::with_secret(ServerSecret::generate())
+    }
+
+    /// Creates a ring with the given current secret and no previous secret
+    pub fn with_secret(current: ServerSecret) -> Self {
+        CookieSecretRing {
+            current,
+            previous: None,
+            clock: Arc::new(SystemClock),
+            lifetime_secs: COOKIE_TIMEOUT_SECONDS,
+        }
+    }
+
+    /// Checks cookie freshness against `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Accepts cookies up to `lifetime_secs` old (default
+    /// [`COOKIE_TIMEOUT_SECONDS`])
+    pub fn with_lifetime(mut self, lifetime_secs: u64) -> Self {
+        self.lifetime_secs = lifetime_secs;
+        self
+    }
+
+    /// Generates a cookie under the current secret
+    ///
+    /// See [`generate_cookie`] for the cookie construction.
+    pub fn generate_cookie(
+        &self,
+        client_ip: &str,
+        timestamp: u64,
+        client_random: &[u8],
+    ) -> Result<Vec<u8>, CookieChallengeError> {
+        generate_cookie(&self.current, client_ip, timestamp, client_random)
+    }
+
+    /// Verifies a cookie against the current and previous secrets
+    ///
+    /// Both secrets are always checked so timing does not reveal which one
+    /// issued the cookie. Timestamp freshness is enforced as in
+    /// [`verify_cookie_with_clock`], with the ring's clock and lifetime.
+    pub fn verify_cookie(
+        &self,
+        cookie: &[u8],
+        client_ip: &str,
+        timestamp: u64,
+        client_random: &[u8],
+    ) -> Result<(), CookieChallengeError> {
+        let clock = self.clock.as_ref();
+        let current = verify_cookie_with_clock(
+            cookie, &self.current, client_ip, timestamp, client_random, clock, self.lifetime_secs,
+        );
+        let previous = match &self.previous {
+            Some(previous) => verify_cookie_with_clock(
+                cookie, previous, client_ip, timestamp, client_random, clock, self.lifetime_secs,
+            ),
+            None => Err(CookieChallengeError::InvalidCookie),
+        };
+
+        match (current, previous) {
+            (Ok(()), _) | (_, Ok(())) => Ok(()),
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    /// Installs a fresh random current secret
+    ///
+    /// The old current secret becomes the previous one; the old previous
+    /// secret is zeroized, invalidating every cookie it issued.
+    pub fn rotate(&mut self) {
+        self.rotate_to(ServerSecret::generate());
+    }
+
+    /// Installs `secret` as the current secret (e.g. one shared across a
+    /// server cluster), retiring secrets as in [`rotate`](Self::rotate)
+    pub fn rotate_to(&mut self, secret: ServerSecret) {
+        let retired = std::mem::replace(&mut self.current, secret);
+        if let Some(mut oldest) = self.previous.replace(retired) {
+            oldest.zeroize();
+        }
+    }
+}
+
+impl Default for CookieSecretRing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -377,10 +526,7 @@ mod tests {
     fn test_verify_cookie_success() {
         let server_secret = ServerSecret::generate();
         let client_ip = "192.168.1.100";
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let timestamp = crate::time::current_time_secs();
         let client_random = [0u8; 32];
 
         // Generate cookie
@@ -396,10 +542,7 @@ mod tests {
     fn test_verify_cookie_invalid_cookie() {
         let server_secret = ServerSecret::generate();
         let client_ip = "192.168.1.100";
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let timestamp = crate::time::current_time_secs();
         let client_random = [0u8; 32];
 
         // Generate valid cookie
@@ -418,10 +561,7 @@ mod tests {
     fn test_verify_cookie_expired() {
         let server_secret = ServerSecret::generate();
         let client_ip = "192.168.1.100";
-        let current_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let current_time = crate::time::current_time_secs();
         let timestamp = current_time - COOKIE_TIMEOUT_SECONDS - 1; // Expired
         let client_random = [0u8; 32];
 
@@ -438,10 +578,7 @@ mod tests {
     fn test_verify_cookie_future_timestamp() {
         let server_secret = ServerSecret::generate();
         let client_ip = "192.168.1.100";
-        let current_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let current_time = crate::time::current_time_secs();
         let timestamp = current_time + 400; // Too far in future (> 5 min)
         let client_random = [0u8; 32];
 
@@ -459,10 +596,7 @@ mod tests {
         let server_secret1 = ServerSecret::generate();
         let server_secret2 = ServerSecret::generate();
         let client_ip = "192.168.1.100";
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let timestamp = crate::time::current_time_secs();
         let client_random = [0u8; 32];
 
         // Generate cookie with secret1
@@ -479,10 +613,7 @@ mod tests {
         let server_secret = ServerSecret::generate();
         let client_ip1 = "192.168.1.100";
         let client_ip2 = "192.168.1.101";
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let timestamp = crate::time::current_time_secs();
         let client_random = [0u8; 32];
 
         // Generate cookie with ip1
@@ -498,10 +629,7 @@ mod tests {
     fn test_verify_cookie_wrong_random() {
         let server_secret = ServerSecret::generate();
         let client_ip = "192.168.1.100";
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let timestamp = crate::time::current_time_secs();
         let client_random1 = [0u8; 32];
         let client_random2 = [1u8; 32];
 
@@ -568,10 +696,7 @@ mod tests {
     fn test_ipv6_address() {
         let server_secret = ServerSecret::generate();
         let client_ip = "2001:0db8:85a3:0000:0000:8a2e:0370:7334";
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let timestamp = crate::time::current_time_secs();
         let client_random = [0u8; 32];
 
         // Generate and verify cookie with IPv6 address
@@ -581,4 +706,111 @@ mod tests {
         verify_cookie(&cookie, &server_secret, client_ip, timestamp, &client_random)
             .expect("Cookie verification failed");
     }
+
+    #[test]
+    fn test_secret_ring_accepts_cookie_across_one_rotation() {
+        let mut ring = CookieSecretRing::new();
+        let client_ip = "192.168.1.100";
+        let timestamp = crate::time::current_time_secs();
+        let client_random = [7u8; 32];
+
+        let before = ring.generate_cookie(client_ip, timestamp, &client_random)
+            .expect("Failed to generate cookie");
+        ring.rotate();
+        let after = ring.generate_cookie(client_ip, timestamp, &client_random)
+            .expect("Failed to generate cookie");
+
+        // New cookies use the new secret; both verify
+        assert_ne!(before, after);
+        ring.verify_cookie(&before, client_ip, timestamp, &client_random)
+            .expect("Cookie from previous secret rejected");
+        ring.verify_cookie(&after, client_ip, timestamp, &client_random)
+            .expect("Cookie from current secret rejected");
+
+        // Binding to client IP is unchanged
+        assert_eq!(
+            ring.verify_cookie(&before, "10.0.0.1", timestamp, &client_random),
+            Err(CookieChallengeError::InvalidCookie)
+        );
+    }
+
+    #[test]
+    fn test_secret_ring_rejects_cookie_after_two_rotations() {
+        let mut ring = CookieSecretRing::with_secret(ServerSecret::new([1u8; 32]));
+        let client_ip = "192.168.1.100";
+        let timestamp = crate::time::current_time_secs();
+        let client_random = [7u8; 32];
+
+        let cookie = ring.generate_cookie(client_ip, timestamp, &client_random)
+            .expect("Failed to generate cookie");
+
+        ring.rotate_to(ServerSecret::new([2u8; 32]));
+        assert!(ring.verify_cookie(&cookie, client_ip, timestamp, &client_random).is_ok());
+
+        ring.rotate_to(ServerSecret::new([3u8; 32]));
+        assert_eq!(
+            ring.verify_cookie(&cookie, client_ip, timestamp, &client_random),
+            Err(CookieChallengeError::InvalidCookie)
+        );
+
+        // Expiry still applies to cookies from either secret
+        let old = timestamp - COOKIE_TIMEOUT_SECONDS - 1;
+        let stale = ring.generate_cookie(client_ip, old, &client_random)
+            .expect("Failed to generate cookie");
+        assert_eq!(
+            ring.verify_cookie(&stale, client_ip, old, &client_random),
+            Err(CookieChallengeError::ExpiredTimestamp)
+        );
+    }
+
+    #[test]
+    fn test_verify_cookie_with_mock_clock() {
+        use crate::time::MockClock;
+        use std::time::Duration;
+
+        let server_secret = ServerSecret::generate();
+        let client_ip = "192.168.1.100";
+        let client_random = [0u8; 32];
+        let clock = MockClock::new(1_000_000);
+        let timestamp = 1_000_000;
+        let cookie = generate_cookie(&server_secret, client_ip, timestamp, &client_random)
+            .expect("Failed to generate cookie");
+        let verify = |clock: &MockClock, lifetime| {
+            verify_cookie_with_clock(&cookie, &server_secret, client_ip, timestamp, &client_random, clock, lifetime)
+        };
+
+        assert!(verify(&clock, 10).is_ok());
+        clock.advance(Duration::from_secs(10));
+        assert!(verify(&clock, 10).is_ok());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(verify(&clock, 10), Err(CookieChallengeError::ExpiredTimestamp));
+        assert!(verify(&clock, 60).is_ok());
+
+        // Cookie from a peer whose clock runs ahead
+        clock.set_unix_secs(timestamp - MAX_CLOCK_SKEW_SECONDS);
+        assert!(verify(&clock, 10).is_ok());
+        clock.set_unix_secs(timestamp - MAX_CLOCK_SKEW_SECONDS - 1);
+        assert_eq!(verify(&clock, 10), Err(CookieChallengeError::FutureTimestamp));
+    }
+
+    #[test]
+    fn test_secret_ring_uses_its_clock_and_lifetime() {
+        use crate::time::MockClock;
+        use std::time::Duration;
+
+        let clock = Arc::new(MockClock::new(1_000_000));
+        let ring = CookieSecretRing::new().with_clock(clock.clone()).with_lifetime(5);
+        let client_ip = "192.168.1.100";
+        let client_random = [7u8; 32];
+        let cookie = ring.generate_cookie(client_ip, 1_000_000, &client_random)
+            .expect("Failed to generate cookie");
+
+        clock.advance(Duration::from_secs(5));
+        assert!(ring.verify_cookie(&cookie, client_ip, 1_000_000, &client_random).is_ok());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(
+            ring.verify_cookie(&cookie, client_ip, 1_000_000, &client_random),
+            Err(CookieChallengeError::ExpiredTimestamp)
+        );
+    }
 }
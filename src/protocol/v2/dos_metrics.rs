@@ -14,6 +14,8 @@
 //!
 //! ### Replay Protection Metrics
 //! - `replay_detections`: Number of replay attacks detected
+//! - `replay_window_rejections`: Messages rejected for a timestamp outside the replay window
+//! - `replay_cache_evictions`: Replay cache buckets evicted early because the cache was full
 //!
 //! ### Handshake Metrics
 //! - `handshake_attempts`: Total handshake attempts (ClientHello received)
@@ -95,6 +97,12 @@ pub struct DosMetrics {
     /// Number of replay attacks detected
     replay_detections: AtomicU64,
     
+    /// Number of messages rejected for a timestamp outside the replay window
+    replay_window_rejections: AtomicU64,
+    
+    /// Number of replay cache buckets evicted because the cache was full
+    replay_cache_evictions: AtomicU64,
+    
     /// Total handshake attempts (ClientHello received)
     handshake_attempts: AtomicU64,
     
@@ -111,6 +119,8 @@ impl DosMetrics {
             cookie_verifications_failed: AtomicU64::new(0),
             cookie_expired_rejections: AtomicU64::new(0),
             replay_detections: AtomicU64::new(0),
+            replay_window_rejections: AtomicU64::new(0),
+            replay_cache_evictions: AtomicU64::new(0),
             handshake_attempts: AtomicU64::new(0),
             handshake_completions: AtomicU64::new(0),
         }
@@ -151,6 +161,20 @@ impl DosMetrics {
         self.replay_detections.fetch_add(1, Ordering::Relaxed);
     }
     
+    /// Increments the replay window rejections counter
+    ///
+    /// Call this when a message timestamp falls outside the replay window.
+    pub fn increment_replay_window_rejections(&self) {
+        self.replay_window_rejections.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// Increments the replay cache evictions counter
+    ///
+    /// Call this when the replay cache drops a bucket early to stay within capacity.
+    pub fn increment_replay_cache_evictions(&self) {
+        self.replay_cache_evictions.fetch_add(1, Ordering::Relaxed);
+    }
+    
     /// Increments the handshake attempts counter
     ///
     /// Call this when a ClientHello is received (before cookie verification).
@@ -190,6 +214,16 @@ impl DosMetrics {
         self.replay_detections.load(Ordering::Relaxed)
     }
     
+    /// Returns the current value of replay window rejections
+    pub fn replay_window_rejections(&self) -> u64 {
+        self.replay_window_rejections.load(Ordering::Relaxed)
+    }
+    
+    /// Returns the current value of replay cache evictions
+    pub fn replay_cache_evictions(&self) -> u64 {
+        self.replay_cache_evictions.load(Ordering::Relaxed)
+    }
+    
     /// Returns the current value of handshake attempts
     pub fn handshake_attempts(&self) -> u64 {
         self.handshake_attempts.load(Ordering::Relaxed)
@@ -309,6 +343,8 @@ impl DosMetrics {
             cookie_verifications_failed: self.cookie_verifications_failed(),
             cookie_expired_rejections: self.cookie_expired_rejections(),
             replay_detections: self.replay_detections(),
+            replay_window_rejections: self.replay_window_rejections(),
+            replay_cache_evictions: self.replay_cache_evictions(),
             handshake_attempts: self.handshake_attempts(),
             handshake_completions: self.handshake_completions(),
             cookie_success_rate: self.cookie_success_rate(),
@@ -340,6 +376,8 @@ impl DosMetrics {
         self.cookie_verifications_failed.store(0, Ordering::Relaxed);
         self.cookie_expired_rejections.store(0, Ordering::Relaxed);
         self.replay_detections.store(0, Ordering::Relaxed);
+        self.replay_window_rejections.store(0, Ordering::Relaxed);
+        self.replay_cache_evictions.store(0, Ordering::Relaxed);
         self.handshake_attempts.store(0, Ordering::Relaxed);
         self.handshake_completions.store(0, Ordering::Relaxed);
     }
@@ -360,6 +398,8 @@ impl Clone for DosMetrics {
             cookie_verifications_failed: AtomicU64::new(self.cookie_verifications_failed()),
             cookie_expired_rejections: AtomicU64::new(self.cookie_expired_rejections()),
             replay_detections: AtomicU64::new(self.replay_detections()),
+            replay_window_rejections: AtomicU64::new(self.replay_window_rejections()),
+            replay_cache_evictions: AtomicU64::new(self.replay_cache_evictions()),
             handshake_attempts: AtomicU64::new(self.handshake_attempts()),
             handshake_completions: AtomicU64::new(self.handshake_completions()),
         }
@@ -400,6 +440,12 @@ pub struct DosMetricsSnapshot {
     /// Number of replay attacks detected
     pub replay_detections: u64,
     
+    /// Number of messages rejected for a timestamp outside the replay window
+    pub replay_window_rejections: u64,
+    
+    /// Number of replay cache buckets evicted because the cache was full
+    pub replay_cache_evictions: u64,
+    
     /// Total handshake attempts (ClientHello received)
     pub handshake_attempts: u64,
     
@@ -29,7 +29,10 @@
 //! - REQ-38: Downgrade Protection
 
 use sha3::{Digest, Sha3_256};
+use crate::crypto::dilithium::{self, VariantPublicKey, VariantSecretKey, VariantSignature};
+use crate::crypto::{CryptoError, CryptoResult};
 use crate::protocol::v2::types::{AuthenticationMode, ModeBinding};
+use crate::security::hardened_core::CipherSuite;
 
 /// Domain separation string for mode binding derivation
 const MODE_BINDING_DOMAIN: &[u8] = b"B4AE-v2-mode-binding";
@@ -232,6 +235,43 @@ pub fn build_handshake_transcript(
     transcript
 }
 
+/// Signs a Mode B handshake transcript under the negotiated cipher suite
+///
+/// The secret key must belong to the Dilithium parameter set of `suite`
+/// (see [`CipherSuite::dilithium_variant`]); a key of another variant is
+/// rejected rather than silently signing with a different scheme.
+pub fn sign_mode_b_transcript(
+    suite: CipherSuite,
+    secret_key: &VariantSecretKey,
+    transcript: &[u8],
+) -> CryptoResult<VariantSignature> {
+    if secret_key.variant() != suite.dilithium_variant() {
+        return Err(CryptoError::InvalidInput(format!(
+            "Mode B under {:?} requires {:?}, got {:?} key",
+            suite,
+            suite.dilithium_variant(),
+            secret_key.variant()
+        )));
+    }
+    dilithium::sign_with(secret_key, transcript)
+}
+
+/// Verifies a Mode B transcript signature under the negotiated cipher suite
+///
+/// Returns `Ok(false)` if the key or signature belongs to a different
+/// Dilithium parameter set than `suite` selects.
+pub fn verify_mode_b_transcript(
+    suite: CipherSuite,
+    public_key: &VariantPublicKey,
+    transcript: &[u8],
+    signature: &VariantSignature,
+) -> CryptoResult<bool> {
+    if public_key.variant() != suite.dilithium_variant() {
+        return Ok(false);
+    }
+    dilithium::verify_with(public_key, transcript, signature)
+}
+
 /// Verifies mode binding consistency across handshake messages
 ///
 /// This function checks that the mode_binding in a handshake message matches
@@ -457,4 +497,25 @@ mod tests {
         };
         assert!(error2.to_string().contains("downgrade attack detected"));
     }
+
+    #[test]
+    fn test_mode_b_signature_follows_cipher_suite() {
+        use crate::crypto::dilithium::{keypair_with, DilithiumVariant};
+
+        let binding = derive_mode_binding(&[1u8; 32], &[2u8; 32], AuthenticationMode::ModeB);
+        let transcript = build_handshake_transcript(&[0u8; 32], &binding, &[3u8; 32], &[4u8; 64], 1);
+
+        let standard = keypair_with(DilithiumVariant::Dilithium3).unwrap();
+        let signature = sign_mode_b_transcript(CipherSuite::Standard, &standard.secret_key, &transcript).unwrap();
+        assert_eq!(signature.variant(), DilithiumVariant::Dilithium3);
+        assert!(verify_mode_b_transcript(CipherSuite::Standard, &standard.public_key, &transcript, &signature).unwrap());
+
+        // The same signature does not verify once the suite says Dilithium5
+        assert!(!verify_mode_b_transcript(CipherSuite::High, &standard.public_key, &transcript, &signature).unwrap());
+        let high = keypair_with(DilithiumVariant::Dilithium5).unwrap();
+        assert!(!verify_mode_b_transcript(CipherSuite::High, &high.public_key, &transcript, &signature).unwrap());
+
+        // A key of the wrong parameter set cannot sign for the suite
+        assert!(sign_mode_b_transcript(CipherSuite::High, &standard.secret_key, &transcript).is_err());
+    }
 }
@@ -40,6 +40,14 @@
 //!   |    [4. Proceed with handshake]     |
 //! ```
 //!
+//! ## Replay Cache
+//!
+//! [`ReplayCache`] is an exact (no false positives) alternative for message
+//! IDs carrying a timestamp. IDs are kept in time buckets covering the
+//! acceptance window; whole buckets expire once they fall out of the window,
+//! and a capacity bound evicts the oldest bucket early while raising the
+//! accepted timestamp floor so evicted IDs cannot be replayed.
+//!
 //! ## Requirements
 //!
 //! - REQ-4: Replay Protection for Cookie Challenge
@@ -47,12 +55,19 @@
 //! - REQ-23: Memory Usage Requirements
 
 use bloomfilter::Bloom;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use crate::protocol::v2::constants::{
     BLOOM_FILTER_SIZE, BLOOM_FILTER_FALSE_POSITIVE_RATE, COOKIE_TIMEOUT_SECONDS,
+    REPLAY_CACHE_CAPACITY,
 };
+use crate::protocol::v2::dos_metrics::SharedDosMetrics;
+use crate::time::{Clock, SystemClock};
+
+/// Number of time buckets the replay window is split into
+const REPLAY_CACHE_BUCKETS: u64 = 16;
 
 /// Error type for replay protection operations
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -118,6 +133,9 @@ pub struct ReplayProtection {
     
     /// Expiry window duration (30 seconds)
     expiry_window: Duration,
+
+    /// Source of monotonic time for rotation
+    clock: Arc<dyn Clock>,
 }
 
 impl ReplayProtection {
@@ -145,6 +163,7 @@ impl ReplayProtection {
             bloom: Arc::new(Mutex::new(bloom)),
             created_at: Arc::new(Mutex::new(Instant::now())),
             expiry_window: Duration::from_secs(COOKIE_TIMEOUT_SECONDS),
+            clock: Arc::new(SystemClock),
         }
     }
     
@@ -175,8 +194,18 @@ impl ReplayProtection {
             bloom: Arc::new(Mutex::new(bloom)),
             created_at: Arc::new(Mutex::new(Instant::now())),
             expiry_window: Duration::from_secs(expiry_seconds),
+            clock: Arc::new(SystemClock),
         }
     }
+
+    /// Uses `clock` to decide when the filter rotates
+    ///
+    /// The current filter's age restarts from the clock's present time.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        *self.created_at.lock().unwrap() = clock.now_monotonic();
+        self.clock = clock;
+        self
+    }
     
     /// Checks if client_random was seen recently and inserts it if not
     ///
@@ -254,7 +283,7 @@ impl ReplayProtection {
     /// This method is thread-safe and can be called concurrently.
     fn rotate_if_expired(&self) {
         let mut created_at = self.created_at.lock().unwrap();
-        let elapsed = created_at.elapsed();
+        let elapsed = self.clock.now_monotonic().saturating_duration_since(*created_at);
         
         if elapsed >= self.expiry_window {
             // Filter has expired, rotate it
@@ -264,7 +293,7 @@ impl ReplayProtection {
             bloom.clear();
             
             // Update creation timestamp
-            *created_at = Instant::now();
+            *created_at = self.clock.now_monotonic();
         }
     }
     
@@ -298,7 +327,7 @@ impl ReplayProtection {
     /// ```
     pub fn time_until_rotation(&self) -> Duration {
         let created_at = self.created_at.lock().unwrap();
-        let elapsed = created_at.elapsed();
+        let elapsed = self.clock.now_monotonic().saturating_duration_since(*created_at);
         
         if elapsed >= self.expiry_window {
             Duration::from_secs(0)
@@ -307,6 +336,16 @@ impl ReplayProtection {
         }
     }
     
+    /// Window after which the filter rotates
+    pub fn expiry_window(&self) -> Duration {
+        self.expiry_window
+    }
+
+    /// Clock this filter rotates by
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
     /// Manually clears the Bloom filter
     ///
     /// This is useful for testing or when you want to reset the replay
@@ -325,7 +364,7 @@ impl ReplayProtection {
         bloom.clear();
         
         let mut created_at = self.created_at.lock().unwrap();
-        *created_at = Instant::now();
+        *created_at = self.clock.now_monotonic();
     }
 }
 
@@ -342,10 +381,205 @@ impl Clone for ReplayProtection {
             bloom: Arc::clone(&self.bloom),
             created_at: Arc::clone(&self.created_at),
             expiry_window: self.expiry_window,
+            clock: Arc::clone(&self.clock),
         }
     }
 }
 
+/// Error type for [`ReplayCache`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// Message ID already seen within the window
+    Duplicate,
+    /// Timestamp outside the acceptance window (too old, too far in the
+    /// future, or older than buckets evicted under flood)
+    OutsideWindow,
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Duplicate => write!(f, "Replay detected: message ID seen recently"),
+            ReplayError::OutsideWindow => write!(f, "Message timestamp outside replay window"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Replay cache configuration
+#[derive(Debug, Clone)]
+pub struct ReplayCacheConfig {
+    /// Acceptance window: timestamps more than this far from now are rejected
+    pub window: Duration,
+    /// Maximum number of message IDs held at once
+    pub capacity: usize,
+}
+
+impl Default for ReplayCacheConfig {
+    fn default() -> Self {
+        ReplayCacheConfig {
+            window: Duration::from_secs(COOKIE_TIMEOUT_SECONDS),
+            capacity: REPLAY_CACHE_CAPACITY,
+        }
+    }
+}
+
+struct ReplayCacheState {
+    /// Seen IDs keyed by bucket index (`timestamp / bucket_width`)
+    buckets: BTreeMap<u64, HashSet<[u8; 32]>>,
+    /// Number of IDs across all buckets
+    len: usize,
+    /// Timestamps below this were evicted early and are rejected
+    floor: u64,
+}
+
+/// Exact replay cache with a sliding time window and bounded memory
+///
+/// ## Example
+///
+/// ```rust
+/// use b4ae::protocol::v2::replay_protection::{ReplayCache, ReplayError};
+///
+/// let cache = ReplayCache::new();
+/// let now = b4ae::time::current_time_secs();
+///
+/// assert!(cache.check_and_insert(&[7u8; 32], now).is_ok());
+/// assert_eq!(cache.check_and_insert(&[7u8; 32], now), Err(ReplayError::Duplicate));
+/// assert_eq!(cache.check_and_insert(&[8u8; 32], now - 3600), Err(ReplayError::OutsideWindow));
+/// ```
+pub struct ReplayCache {
+    state: Mutex<ReplayCacheState>,
+    window_secs: u64,
+    bucket_width: u64,
+    capacity: usize,
+    metrics: Option<SharedDosMetrics>,
+    clock: Arc<dyn Clock>,
+}
+
+impl ReplayCache {
+    /// Creates a replay cache with the default window (30 s) and capacity
+    pub fn new() -> Self {
+        Self::with_config(ReplayCacheConfig::default())
+    }
+
+    /// Creates a replay cache with custom window and capacity
+    pub fn with_config(config: ReplayCacheConfig) -> Self {
+        let window_secs = config.window.as_secs().max(1);
+        ReplayCache {
+            state: Mutex::new(ReplayCacheState {
+                buckets: BTreeMap::new(),
+                len: 0,
+                floor: 0,
+            }),
+            window_secs,
+            bucket_width: (window_secs / REPLAY_CACHE_BUCKETS).max(1),
+            capacity: config.capacity.max(1),
+            metrics: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Reports duplicates, window rejections and evictions to `metrics`
+    pub fn with_metrics(mut self, metrics: SharedDosMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Takes the current time for [`check_and_insert`](Self::check_and_insert) from `clock`
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Checks `msg_id` against the cache and records it if new
+    ///
+    /// `timestamp` is the message's Unix timestamp in seconds; it must lie
+    /// within the window around the current time. An ID seen within the
+    /// window is a duplicate whatever timestamp it is replayed with.
+    pub fn check_and_insert(&self, msg_id: &[u8; 32], timestamp: u64) -> Result<(), ReplayError> {
+        self.check_and_insert_at(msg_id, timestamp, self.clock.now_unix_secs())
+    }
+
+    /// [`check_and_insert`](Self::check_and_insert) with an explicit current time
+    pub fn check_and_insert_at(&self, msg_id: &[u8; 32], timestamp: u64, now: u64) -> Result<(), ReplayError> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Expire buckets that lie entirely before the window
+        let oldest = now.saturating_sub(self.window_secs);
+        while let Some((&index, ids)) = state.buckets.iter().next() {
+            if (index + 1) * self.bucket_width > oldest {
+                break;
+            }
+            let count = ids.len();
+            state.buckets.remove(&index);
+            state.len -= count;
+        }
+
+        if timestamp < oldest || timestamp < state.floor || timestamp > now.saturating_add(self.window_secs) {
+            self.record(|m| m.increment_replay_window_rejections());
+            return Err(ReplayError::OutsideWindow);
+        }
+
+        // Look in every bucket: a replay may carry a different timestamp
+        if state.buckets.values().any(|ids| ids.contains(msg_id)) {
+            self.record(|m| m.increment_replay_detections());
+            return Err(ReplayError::Duplicate);
+        }
+
+        // Stay within capacity by evicting the oldest bucket and rejecting
+        // its timestamps from now on
+        while state.len >= self.capacity {
+            let Some((&evicted, _)) = state.buckets.iter().next() else { break };
+            let count = state.buckets.remove(&evicted).map_or(0, |ids| ids.len());
+            state.len -= count;
+            state.floor = (evicted + 1) * self.bucket_width;
+            self.record(|m| m.increment_replay_cache_evictions());
+        }
+        if timestamp < state.floor {
+            self.record(|m| m.increment_replay_window_rejections());
+            return Err(ReplayError::OutsideWindow);
+        }
+
+        let index = timestamp / self.bucket_width;
+        state.buckets.entry(index).or_default().insert(*msg_id);
+        state.len += 1;
+        Ok(())
+    }
+
+    /// Number of message IDs currently held
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).len
+    }
+
+    /// Whether the cache holds no message IDs
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Maximum number of message IDs held at once
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Acceptance window
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+
+    fn record(&self, update: impl FnOnce(&SharedDosMetrics)) {
+        if let Some(metrics) = &self.metrics {
+            update(metrics);
+        }
+    }
+}
+
+impl Default for ReplayCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -437,6 +671,22 @@ mod tests {
         assert!(rp.check_and_insert(&client_random).is_ok());
     }
 
+    #[test]
+    fn test_rotation_with_mock_clock() {
+        let clock = Arc::new(crate::time::MockClock::new(1_000_000));
+        let rp = ReplayProtection::with_config(1000, 0.001, 30).with_clock(clock.clone());
+        let client_random = [5u8; 32];
+
+        assert!(rp.check_and_insert(&client_random).is_ok());
+        clock.advance(Duration::from_secs(29));
+        assert_eq!(rp.time_until_rotation(), Duration::from_secs(1));
+        assert!(rp.check_and_insert(&client_random).is_err());
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(rp.time_until_rotation(), Duration::ZERO);
+        assert!(rp.check_and_insert(&client_random).is_ok());
+    }
+
     #[test]
     fn test_time_until_rotation() {
         let rp = ReplayProtection::new();
@@ -567,4 +817,88 @@ mod tests {
         let err2 = ReplayProtectionError::InvalidInput("test".to_string());
         assert!(err2.to_string().contains("Invalid input"));
     }
+
+    #[test]
+    fn test_replay_cache_rejects_duplicates() {
+        let metrics = Arc::new(crate::protocol::v2::dos_metrics::DosMetrics::new());
+        let cache = ReplayCache::new().with_metrics(Arc::clone(&metrics));
+        let now = 1_000_000;
+
+        assert!(cache.check_and_insert_at(&[1u8; 32], now, now).is_ok());
+        assert!(cache.check_and_insert_at(&[2u8; 32], now, now).is_ok());
+        assert_eq!(cache.check_and_insert_at(&[1u8; 32], now, now + 5), Err(ReplayError::Duplicate));
+        assert_eq!(metrics.replay_detections(), 1);
+
+        // Replays with a different in-window timestamp are caught as well
+        assert_eq!(cache.check_and_insert_at(&[1u8; 32], now + 20, now + 5), Err(ReplayError::Duplicate));
+        assert_eq!(cache.check_and_insert_at(&[2u8; 32], now - 20, now), Err(ReplayError::Duplicate));
+        assert_eq!(metrics.replay_detections(), 3);
+
+        // Too old or too far in the future
+        assert_eq!(cache.check_and_insert_at(&[3u8; 32], now - 31, now), Err(ReplayError::OutsideWindow));
+        assert_eq!(cache.check_and_insert_at(&[3u8; 32], now + 31, now), Err(ReplayError::OutsideWindow));
+        assert_eq!(metrics.replay_window_rejections(), 2);
+    }
+
+    #[test]
+    fn test_replay_cache_expiry_allows_reuse() {
+        let cache = ReplayCache::with_config(ReplayCacheConfig {
+            window: Duration::from_secs(60),
+            capacity: 1_000,
+        });
+        let start = 1_000_000;
+
+        assert!(cache.check_and_insert_at(&[9u8; 32], start, start).is_ok());
+        assert_eq!(cache.check_and_insert_at(&[9u8; 32], start, start + 59), Err(ReplayError::Duplicate));
+
+        // Once the window (plus one bucket) has passed the old entry is gone:
+        // the ID is fresh with a new timestamp, and replaying the old
+        // timestamp is out of window
+        let later = start + 64;
+        assert_eq!(cache.check_and_insert_at(&[9u8; 32], start, later), Err(ReplayError::OutsideWindow));
+        assert!(cache.check_and_insert_at(&[9u8; 32], later, later).is_ok());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_replay_cache_window_follows_clock() {
+        let clock = Arc::new(crate::time::MockClock::new(1_000_000));
+        let cache = ReplayCache::new().with_clock(clock.clone());
+        let timestamp = 1_000_020;
+
+        assert_eq!(cache.check_and_insert(&[4u8; 32], timestamp + 20), Err(ReplayError::OutsideWindow));
+        assert!(cache.check_and_insert(&[4u8; 32], timestamp).is_ok());
+
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(cache.check_and_insert(&[5u8; 32], timestamp), Err(ReplayError::OutsideWindow));
+    }
+
+    #[test]
+    fn test_replay_cache_bounded_under_flood() {
+        let metrics = Arc::new(crate::protocol::v2::dos_metrics::DosMetrics::new());
+        let cache = ReplayCache::with_config(ReplayCacheConfig {
+            window: Duration::from_secs(32),
+            capacity: 100,
+        })
+        .with_metrics(Arc::clone(&metrics));
+        let start = 1_000_000;
+
+        // 10,000 distinct IDs spread over the window
+        let mut first_id = [0u8; 32];
+        for i in 0u32..10_000 {
+            let mut id = [0u8; 32];
+            id[..4].copy_from_slice(&i.to_be_bytes());
+            if i == 0 {
+                first_id = id;
+            }
+            let now = start + u64::from(i) / 400;
+            let _ = cache.check_and_insert_at(&id, now, now);
+            assert!(cache.len() <= cache.capacity());
+        }
+        assert!(metrics.replay_cache_evictions() > 0);
+
+        // An evicted ID still cannot be replayed with its original timestamp
+        let now = start + 24;
+        assert_eq!(cache.check_and_insert_at(&first_id, start, now), Err(ReplayError::OutsideWindow));
+    }
 }
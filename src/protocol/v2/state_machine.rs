@@ -18,6 +18,10 @@
 //! INIT -> MODE_NEGOTIATION -> COOKIE_CHALLENGE -> HANDSHAKE -> ESTABLISHED -> TERMINATED
 //! ```
 //!
+//! Every valid (role, direction, state, message) step is listed once in
+//! [`TRANSITIONS`]; the machine enforces that table and
+//! [`StateMachine::to_dot`] renders it for review against the formal model.
+//!
 //! ## Security Properties
 //!
 //! - Enforces correct message ordering
@@ -83,6 +87,16 @@ pub enum ProtocolState {
 }
 
 impl ProtocolState {
+    /// All protocol states, in handshake order
+    pub const ALL: [ProtocolState; 6] = [
+        ProtocolState::Init,
+        ProtocolState::ModeNegotiation,
+        ProtocolState::CookieChallenge,
+        ProtocolState::Handshake,
+        ProtocolState::Established,
+        ProtocolState::Terminated,
+    ];
+
     /// Returns true if this is a terminal state (no further transitions allowed)
     pub fn is_terminal(&self) -> bool {
         matches!(self, ProtocolState::Terminated)
@@ -192,6 +206,100 @@ impl fmt::Display for Role {
     }
 }
 
+/// Whether a transition is triggered by sending or receiving a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransitionDirection {
+    /// Triggered by sending the message
+    Send,
+
+    /// Triggered by receiving the message
+    Receive,
+}
+
+impl fmt::Display for TransitionDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransitionDirection::Send => write!(f, "send"),
+            TransitionDirection::Receive => write!(f, "recv"),
+        }
+    }
+}
+
+/// One valid event-driven step of the protocol state machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transition {
+    /// Role taking the step
+    pub role: Role,
+
+    /// Whether the message is sent or received
+    pub direction: TransitionDirection,
+
+    /// State the step is valid in
+    pub from: ProtocolState,
+
+    /// Message that triggers the step
+    pub message_type: MessageType,
+
+    /// State after the step
+    pub to: ProtocolState,
+}
+
+const fn transition(
+    role: Role,
+    direction: TransitionDirection,
+    from: ProtocolState,
+    message_type: MessageType,
+    to: ProtocolState,
+) -> Transition {
+    Transition { role, direction, from, message_type, to }
+}
+
+/// Every valid transition, for both roles
+///
+/// This is the table [`StateMachine`] enforces; any (role, direction,
+/// state, message) combination not listed is rejected.
+pub const TRANSITIONS: &[Transition] = {
+    use MessageType as M;
+    use ProtocolState as S;
+    use Role::{Client, Server};
+    use TransitionDirection::{Receive, Send};
+
+    &[
+        // INIT state
+        transition(Client, Send, S::Init, M::ModeNegotiation, S::ModeNegotiation),
+        transition(Server, Receive, S::Init, M::ModeNegotiation, S::ModeNegotiation),
+
+        // MODE_NEGOTIATION state
+        transition(Server, Send, S::ModeNegotiation, M::ModeSelection, S::CookieChallenge),
+        transition(Client, Receive, S::ModeNegotiation, M::ModeSelection, S::CookieChallenge),
+        transition(Client, Send, S::ModeNegotiation, M::ClientHello, S::CookieChallenge),
+
+        // COOKIE_CHALLENGE state
+        transition(Server, Send, S::CookieChallenge, M::CookieChallenge, S::CookieChallenge),
+        transition(Client, Receive, S::CookieChallenge, M::CookieChallenge, S::CookieChallenge),
+        transition(Client, Send, S::CookieChallenge, M::ClientHelloWithCookie, S::Handshake),
+        transition(Server, Receive, S::CookieChallenge, M::ClientHelloWithCookie, S::Handshake),
+
+        // HANDSHAKE state
+        transition(Client, Send, S::Handshake, M::HandshakeInit, S::Handshake),
+        transition(Server, Receive, S::Handshake, M::HandshakeInit, S::Handshake),
+        transition(Server, Send, S::Handshake, M::HandshakeResponse, S::Handshake),
+        transition(Client, Receive, S::Handshake, M::HandshakeResponse, S::Handshake),
+        transition(Client, Send, S::Handshake, M::HandshakeComplete, S::Established),
+        transition(Server, Receive, S::Handshake, M::HandshakeComplete, S::Established),
+
+        // ESTABLISHED state (both roles, both directions)
+        transition(Client, Send, S::Established, M::EncryptedMessage, S::Established),
+        transition(Client, Receive, S::Established, M::EncryptedMessage, S::Established),
+        transition(Server, Send, S::Established, M::EncryptedMessage, S::Established),
+        transition(Server, Receive, S::Established, M::EncryptedMessage, S::Established),
+        transition(Client, Send, S::Established, M::Terminate, S::Terminated),
+        transition(Client, Receive, S::Established, M::Terminate, S::Terminated),
+        transition(Server, Send, S::Established, M::Terminate, S::Terminated),
+        transition(Server, Receive, S::Established, M::Terminate, S::Terminated),
+    ]
+};
+
 /// State machine error
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StateMachineError {
@@ -330,73 +438,12 @@ impl StateMachine {
 
     /// Validates that a message can be received in the current state
     pub fn validate_receive(&self, message_type: MessageType) -> Result<(), StateMachineError> {
-        if self.state.is_terminal() {
-            return Err(StateMachineError::SessionTerminated);
-        }
-
-        match (self.role, self.state, message_type) {
-            // INIT state
-            (Role::Server, ProtocolState::Init, MessageType::ModeNegotiation) => Ok(()),
-            
-            // MODE_NEGOTIATION state
-            (Role::Client, ProtocolState::ModeNegotiation, MessageType::ModeSelection) => Ok(()),
-            
-            // COOKIE_CHALLENGE state
-            (Role::Client, ProtocolState::CookieChallenge, MessageType::CookieChallenge) => Ok(()),
-            (Role::Server, ProtocolState::CookieChallenge, MessageType::ClientHelloWithCookie) => Ok(()),
-            
-            // HANDSHAKE state
-            (Role::Server, ProtocolState::Handshake, MessageType::HandshakeInit) => Ok(()),
-            (Role::Client, ProtocolState::Handshake, MessageType::HandshakeResponse) => Ok(()),
-            (Role::Server, ProtocolState::Handshake, MessageType::HandshakeComplete) => Ok(()),
-            
-            // ESTABLISHED state
-            (_, ProtocolState::Established, MessageType::EncryptedMessage) => Ok(()),
-            (_, ProtocolState::Established, MessageType::Terminate) => Ok(()),
-            
-            // Invalid combinations
-            _ => Err(StateMachineError::InvalidStateForMessage {
-                current_state: self.state,
-                message_type,
-                role: self.role,
-            }),
-        }
+        self.compute_next_state_on_receive(message_type).map(|_| ())
     }
 
     /// Validates that a message can be sent in the current state
     pub fn validate_send(&self, message_type: MessageType) -> Result<(), StateMachineError> {
-        if self.state.is_terminal() {
-            return Err(StateMachineError::SessionTerminated);
-        }
-
-        match (self.role, self.state, message_type) {
-            // INIT state
-            (Role::Client, ProtocolState::Init, MessageType::ModeNegotiation) => Ok(()),
-            
-            // MODE_NEGOTIATION state
-            (Role::Server, ProtocolState::ModeNegotiation, MessageType::ModeSelection) => Ok(()),
-            (Role::Client, ProtocolState::ModeNegotiation, MessageType::ClientHello) => Ok(()),
-            
-            // COOKIE_CHALLENGE state
-            (Role::Server, ProtocolState::CookieChallenge, MessageType::CookieChallenge) => Ok(()),
-            (Role::Client, ProtocolState::CookieChallenge, MessageType::ClientHelloWithCookie) => Ok(()),
-            
-            // HANDSHAKE state
-            (Role::Client, ProtocolState::Handshake, MessageType::HandshakeInit) => Ok(()),
-            (Role::Server, ProtocolState::Handshake, MessageType::HandshakeResponse) => Ok(()),
-            (Role::Client, ProtocolState::Handshake, MessageType::HandshakeComplete) => Ok(()),
-            
-            // ESTABLISHED state
-            (_, ProtocolState::Established, MessageType::EncryptedMessage) => Ok(()),
-            (_, ProtocolState::Established, MessageType::Terminate) => Ok(()),
-            
-            // Invalid combinations
-            _ => Err(StateMachineError::InvalidStateForSend {
-                current_state: self.state,
-                message_type,
-                role: self.role,
-            }),
-        }
+        self.compute_next_state_on_send(message_type).map(|_| ())
     }
 
     /// Handles receiving a message and transitions state if valid
@@ -421,119 +468,81 @@ impl StateMachine {
 
     /// Computes the next state after receiving a message
     fn compute_next_state_on_receive(&self, message_type: MessageType) -> Result<ProtocolState, StateMachineError> {
-        match (self.role, self.state, message_type) {
-            // Server receives ModeNegotiation in INIT -> MODE_NEGOTIATION
-            (Role::Server, ProtocolState::Init, MessageType::ModeNegotiation) => {
-                Ok(ProtocolState::ModeNegotiation)
-            }
-            
-            // Client receives ModeSelection in MODE_NEGOTIATION -> COOKIE_CHALLENGE
-            (Role::Client, ProtocolState::ModeNegotiation, MessageType::ModeSelection) => {
-                Ok(ProtocolState::CookieChallenge)
-            }
-            
-            // Client receives CookieChallenge in COOKIE_CHALLENGE -> stay in COOKIE_CHALLENGE
-            (Role::Client, ProtocolState::CookieChallenge, MessageType::CookieChallenge) => {
-                Ok(ProtocolState::CookieChallenge)
-            }
-            
-            // Server receives ClientHelloWithCookie in COOKIE_CHALLENGE -> HANDSHAKE
-            (Role::Server, ProtocolState::CookieChallenge, MessageType::ClientHelloWithCookie) => {
-                Ok(ProtocolState::Handshake)
-            }
-            
-            // Server receives HandshakeInit in HANDSHAKE -> stay in HANDSHAKE
-            (Role::Server, ProtocolState::Handshake, MessageType::HandshakeInit) => {
-                Ok(ProtocolState::Handshake)
-            }
-            
-            // Client receives HandshakeResponse in HANDSHAKE -> stay in HANDSHAKE
-            (Role::Client, ProtocolState::Handshake, MessageType::HandshakeResponse) => {
-                Ok(ProtocolState::Handshake)
-            }
-            
-            // Server receives HandshakeComplete in HANDSHAKE -> ESTABLISHED
-            (Role::Server, ProtocolState::Handshake, MessageType::HandshakeComplete) => {
-                Ok(ProtocolState::Established)
-            }
-            
-            // Encrypted messages in ESTABLISHED -> stay in ESTABLISHED
-            (_, ProtocolState::Established, MessageType::EncryptedMessage) => {
-                Ok(ProtocolState::Established)
-            }
-            
-            // Terminate in ESTABLISHED -> TERMINATED
-            (_, ProtocolState::Established, MessageType::Terminate) => {
-                Ok(ProtocolState::Terminated)
-            }
-            
-            _ => Err(StateMachineError::InvalidStateForMessage {
+        if self.state.is_terminal() {
+            return Err(StateMachineError::SessionTerminated);
+        }
+
+        self.lookup(TransitionDirection::Receive, message_type)
+            .ok_or(StateMachineError::InvalidStateForMessage {
                 current_state: self.state,
                 message_type,
                 role: self.role,
-            }),
-        }
+            })
     }
 
     /// Computes the next state after sending a message
     fn compute_next_state_on_send(&self, message_type: MessageType) -> Result<ProtocolState, StateMachineError> {
-        match (self.role, self.state, message_type) {
-            // Client sends ModeNegotiation in INIT -> MODE_NEGOTIATION
-            (Role::Client, ProtocolState::Init, MessageType::ModeNegotiation) => {
-                Ok(ProtocolState::ModeNegotiation)
-            }
-            
-            // Server sends ModeSelection in MODE_NEGOTIATION -> COOKIE_CHALLENGE
-            (Role::Server, ProtocolState::ModeNegotiation, MessageType::ModeSelection) => {
-                Ok(ProtocolState::CookieChallenge)
-            }
-            
-            // Client sends ClientHello in MODE_NEGOTIATION -> COOKIE_CHALLENGE
-            (Role::Client, ProtocolState::ModeNegotiation, MessageType::ClientHello) => {
-                Ok(ProtocolState::CookieChallenge)
-            }
-            
-            // Server sends CookieChallenge in COOKIE_CHALLENGE -> stay in COOKIE_CHALLENGE
-            (Role::Server, ProtocolState::CookieChallenge, MessageType::CookieChallenge) => {
-                Ok(ProtocolState::CookieChallenge)
-            }
-            
-            // Client sends ClientHelloWithCookie in COOKIE_CHALLENGE -> HANDSHAKE
-            (Role::Client, ProtocolState::CookieChallenge, MessageType::ClientHelloWithCookie) => {
-                Ok(ProtocolState::Handshake)
-            }
-            
-            // Client sends HandshakeInit in HANDSHAKE -> stay in HANDSHAKE
-            (Role::Client, ProtocolState::Handshake, MessageType::HandshakeInit) => {
-                Ok(ProtocolState::Handshake)
-            }
-            
-            // Server sends HandshakeResponse in HANDSHAKE -> stay in HANDSHAKE
-            (Role::Server, ProtocolState::Handshake, MessageType::HandshakeResponse) => {
-                Ok(ProtocolState::Handshake)
-            }
-            
-            // Client sends HandshakeComplete in HANDSHAKE -> ESTABLISHED
-            (Role::Client, ProtocolState::Handshake, MessageType::HandshakeComplete) => {
-                Ok(ProtocolState::Established)
-            }
-            
-            // Encrypted messages in ESTABLISHED -> stay in ESTABLISHED
-            (_, ProtocolState::Established, MessageType::EncryptedMessage) => {
-                Ok(ProtocolState::Established)
-            }
-            
-            // Terminate in ESTABLISHED -> TERMINATED
-            (_, ProtocolState::Established, MessageType::Terminate) => {
-                Ok(ProtocolState::Terminated)
-            }
-            
-            _ => Err(StateMachineError::InvalidStateForSend {
+        if self.state.is_terminal() {
+            return Err(StateMachineError::SessionTerminated);
+        }
+
+        self.lookup(TransitionDirection::Send, message_type)
+            .ok_or(StateMachineError::InvalidStateForSend {
                 current_state: self.state,
                 message_type,
                 role: self.role,
-            }),
+            })
+    }
+
+    /// Finds the target state in [`TRANSITIONS`] for the current role and state
+    fn lookup(&self, direction: TransitionDirection, message_type: MessageType) -> Option<ProtocolState> {
+        TRANSITIONS
+            .iter()
+            .find(|t| {
+                t.role == self.role
+                    && t.direction == direction
+                    && t.from == self.state
+                    && t.message_type == message_type
+            })
+            .map(|t| t.to)
+    }
+
+    /// Renders this role's state machine as a Graphviz `digraph`
+    ///
+    /// Nodes are every [`ProtocolState`] (terminal states drawn as double
+    /// circles, the current state in bold); edges are the role's entries in
+    /// [`TRANSITIONS`], labeled `send <Message>` or `recv <Message>`.
+    ///
+    /// ```rust
+    /// use b4ae::protocol::v2::state_machine::{Role, StateMachine};
+    ///
+    /// let dot = StateMachine::new(Role::Client).to_dot();
+    /// assert!(dot.starts_with("digraph"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        use std::fmt::Write;
+
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph b4ae_v2_{} {{", self.role.to_string().to_lowercase());
+        let _ = writeln!(dot, "    rankdir=LR;");
+        let _ = writeln!(dot, "    label=\"B4AE v2 protocol state machine ({})\";", self.role);
+
+        for state in ProtocolState::ALL {
+            let shape = if state.is_terminal() { "doublecircle" } else { "circle" };
+            let style = if state == self.state { ", style=bold" } else { "" };
+            let _ = writeln!(dot, "    \"{}\" [shape={}{}];", state, shape, style);
+        }
+
+        for t in TRANSITIONS.iter().filter(|t| t.role == self.role) {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{} {}\"];",
+                t.from, t.to, t.direction, t.message_type
+            );
         }
+
+        dot.push_str("}\n");
+        dot
     }
 
     /// Transitions to a new state
@@ -903,4 +912,61 @@ mod tests {
         assert_eq!(Role::Client.to_string(), "Client");
         assert_eq!(Role::Server.to_string(), "Server");
     }
+
+    #[test]
+    fn test_transition_table_matches_forward_transitions() {
+        // Every table entry must also pass the forward-transition check
+        for t in TRANSITIONS {
+            let machine = StateMachine { state: t.from, role: t.role, transition_count: 0 };
+            assert!(machine.is_valid_transition(t.to), "{:?}", t);
+        }
+    }
+
+    #[test]
+    fn test_to_dot_contains_all_states_and_transitions() {
+        for role in [Role::Client, Role::Server] {
+            let dot = StateMachine::new(role).to_dot();
+            let body = dot
+                .trim()
+                .strip_prefix("digraph ")
+                .and_then(|rest| rest.split_once('{'))
+                .and_then(|(_, rest)| rest.strip_suffix('}'))
+                .expect("not a digraph");
+
+            let mut nodes = Vec::new();
+            let mut edges = Vec::new();
+            for line in body.lines().map(str::trim).filter(|l| l.starts_with('"')) {
+                let statement = line.strip_suffix(';').expect("statement must end with ';'");
+                let (head, attrs) = statement.split_once(" [").expect("missing attributes");
+                assert!(attrs.ends_with(']'));
+                let names: Vec<&str> = head.split(" -> ").map(|n| n.trim_matches('"')).collect();
+                match names.as_slice() {
+                    [node] => nodes.push(node.to_string()),
+                    [from, to] => edges.push((from.to_string(), to.to_string())),
+                    _ => panic!("unexpected statement: {}", line),
+                }
+            }
+
+            // Every state is a node, including the terminal one
+            for state in ProtocolState::ALL {
+                // Exhaustive match: a new state must be added to ALL
+                match state {
+                    ProtocolState::Init
+                    | ProtocolState::ModeNegotiation
+                    | ProtocolState::CookieChallenge
+                    | ProtocolState::Handshake
+                    | ProtocolState::Established
+                    | ProtocolState::Terminated => {}
+                }
+                assert!(nodes.contains(&state.to_string()), "missing node {}", state);
+            }
+            assert!(dot.contains("\"TERMINATED\" [shape=doublecircle"));
+            assert!(dot.contains("\"INIT\" [shape=circle, style=bold]"));
+
+            // One edge per table entry for this role, between known nodes
+            let expected = TRANSITIONS.iter().filter(|t| t.role == role).count();
+            assert_eq!(edges.len(), expected);
+            assert!(edges.iter().all(|(from, to)| nodes.contains(from) && nodes.contains(to)));
+        }
+    }
 }
@@ -13,6 +13,15 @@
 //! - **Constant-Rate Output**: Messages are sent at a fixed rate (e.g., 100 msg/s)
 //! - **Global Dummy Generation**: Dummy messages fill gaps to maintain constant rate
 //! - **Cross-Session Mixing**: No per-session burst patterns visible to observers
+//! - **Pluggable Policy**: Release times come from a [`SchedulingPolicy`]
+//!   ([`ConstantRatePolicy`] by default, [`PoissonPolicy`] for randomized
+//!   release), so alternative shaping can be tried without forking
+//!
+//! Each release slot emits exactly one frame: the head of the queue, or a
+//! [`Release::Cover`] request when the queue is empty. Slot times are fixed
+//! before the frame is chosen and the queue is FIFO regardless of the dummy
+//! flag, so an observer sees the same timing whether a slot carried real
+//! or dummy traffic.
 //!
 //! ## Security Properties
 //!
@@ -39,8 +48,167 @@
 //! - REQ-23: Memory Usage Requirements
 
 use crate::protocol::v2::{SessionId, DEFAULT_TARGET_RATE, MAX_QUEUE_DEPTH, MAX_QUEUE_MEMORY};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use std::collections::VecDeque;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Source of release times for the [`GlobalTrafficScheduler`]
+///
+/// The scheduler asks the policy for the next release slot after every
+/// release. Policies may adapt to the queue backlog (e.g. adaptive padding),
+/// but they only see counts, never which message is at the head, and the
+/// scheduler emits one frame per slot whatever the queue holds.
+///
+/// ## Example
+///
+/// ```rust
+/// use b4ae::protocol::v2::SchedulingPolicy;
+/// use std::time::{Duration, Instant};
+///
+/// /// Releases every 50 ms, or every 10 ms while a backlog builds up
+/// #[derive(Debug)]
+/// struct BurstyPolicy;
+///
+/// impl SchedulingPolicy for BurstyPolicy {
+///     fn next_release(&mut self, now: Instant, queued_real: usize, queued_dummy: usize) -> Option<Instant> {
+///         let backlog = queued_real + queued_dummy;
+///         let delay = if backlog > 100 { 10 } else { 50 };
+///         Some(now + Duration::from_millis(delay))
+///     }
+/// }
+/// ```
+pub trait SchedulingPolicy: Send + std::fmt::Debug {
+    /// Returns the next release slot, or `None` to stay idle
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current time (the previous release, or when polling starts)
+    /// * `queued_real` - Real messages waiting in the unified queue
+    /// * `queued_dummy` - Dummy messages waiting in the unified queue
+    fn next_release(&mut self, now: Instant, queued_real: usize, queued_dummy: usize) -> Option<Instant>;
+
+    /// Adjusts the policy's average rate in messages per second
+    ///
+    /// Called by [`GlobalTrafficScheduler::set_target_rate`]. Policies
+    /// without a rate parameter ignore it.
+    fn set_target_rate(&mut self, _rate: f64) {}
+}
+
+/// Fixed-interval release at the target rate
+///
+/// Slots are spaced exactly `1 / rate` apart. If the scheduler falls behind,
+/// the next slot is `now` rather than a burst of overdue slots.
+#[derive(Debug, Clone)]
+pub struct ConstantRatePolicy {
+    interval: Duration,
+    last_slot: Option<Instant>,
+}
+
+impl ConstantRatePolicy {
+    /// Creates a constant-rate policy
+    ///
+    /// # Panics
+    ///
+    /// Panics if rate is not positive and finite.
+    pub fn new(rate: f64) -> Self {
+        Self {
+            interval: rate_interval(rate),
+            last_slot: None,
+        }
+    }
+
+    /// Returns the interval between release slots
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+impl SchedulingPolicy for ConstantRatePolicy {
+    fn next_release(&mut self, now: Instant, _queued_real: usize, _queued_dummy: usize) -> Option<Instant> {
+        let slot = match self.last_slot {
+            Some(last) => (last + self.interval).max(now),
+            None => now + self.interval,
+        };
+        self.last_slot = Some(slot);
+        Some(slot)
+    }
+
+    fn set_target_rate(&mut self, rate: f64) {
+        self.interval = rate_interval(rate);
+    }
+}
+
+/// Poisson-process release at the target rate
+///
+/// Gaps between slots are drawn from an exponential distribution with mean
+/// `1 / rate`, so release times carry no periodic signature. Gaps come from
+/// a ChaCha20 stream seeded from the OS RNG; use [`PoissonPolicy::with_seed`]
+/// only for reproducible experiments.
+#[derive(Debug, Clone)]
+pub struct PoissonPolicy {
+    rate: f64,
+    rng: ChaCha20Rng,
+}
+
+impl PoissonPolicy {
+    /// Creates a Poisson policy with the given average rate
+    ///
+    /// # Panics
+    ///
+    /// Panics if rate is not positive and finite.
+    pub fn new(rate: f64) -> Self {
+        Self::from_rng(rate, ChaCha20Rng::from_entropy())
+    }
+
+    /// Creates a Poisson policy with a fixed seed (reproducible gaps)
+    pub fn with_seed(rate: f64, seed: [u8; 32]) -> Self {
+        Self::from_rng(rate, ChaCha20Rng::from_seed(seed))
+    }
+
+    fn from_rng(rate: f64, rng: ChaCha20Rng) -> Self {
+        assert_valid_rate(rate);
+        Self { rate, rng }
+    }
+
+    /// Returns the average rate in messages per second
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+impl SchedulingPolicy for PoissonPolicy {
+    fn next_release(&mut self, now: Instant, _queued_real: usize, _queued_dummy: usize) -> Option<Instant> {
+        // Inverse-CDF sampling; 1 - u lies in (0, 1] so ln is finite
+        let u: f64 = self.rng.gen();
+        let gap = -(1.0 - u).ln() / self.rate;
+        Some(now + Duration::from_secs_f64(gap))
+    }
+
+    fn set_target_rate(&mut self, rate: f64) {
+        assert_valid_rate(rate);
+        self.rate = rate;
+    }
+}
+
+fn assert_valid_rate(rate: f64) {
+    assert!(rate > 0.0 && rate.is_finite(), "Target rate must be positive and finite");
+}
+
+fn rate_interval(rate: f64) -> Duration {
+    assert_valid_rate(rate);
+    Duration::from_secs_f64(1.0 / rate)
+}
+
+/// Frame to emit at a release slot
+#[derive(Debug, Clone)]
+pub enum Release {
+    /// Head of the unified queue (real or dummy)
+    Message(ScheduledMessage),
+    /// Queue was empty: the caller must send a freshly generated dummy
+    /// message now to keep the slot occupied
+    Cover,
+}
 
 /// Global traffic scheduler managing all outbound traffic
 ///
@@ -102,6 +270,15 @@ pub struct GlobalTrafficScheduler {
     /// When this limit is reached, new messages are rejected with
     /// "Memory limit exceeded" error to prevent unbounded memory growth.
     max_queue_memory: usize,
+
+    /// Policy deciding release slot times
+    policy: Box<dyn SchedulingPolicy>,
+
+    /// Next release slot, fetched from the policy on demand
+    next_slot: Option<Instant>,
+
+    /// Number of dummy messages currently in the queue
+    queued_dummy: usize,
 }
 
 impl GlobalTrafficScheduler {
@@ -121,6 +298,26 @@ impl GlobalTrafficScheduler {
     /// let scheduler = GlobalTrafficScheduler::new(1000.0);
     /// ```
     pub fn new(target_rate: f64) -> Self {
+        Self::with_policy(target_rate, ConstantRatePolicy::new(target_rate))
+    }
+
+    /// Creates a scheduler driven by a custom [`SchedulingPolicy`]
+    ///
+    /// # Arguments
+    ///
+    /// * `target_rate` - Nominal rate in messages per second (reported by
+    ///   [`target_rate`](Self::target_rate))
+    /// * `policy` - Policy deciding release slot times
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use b4ae::protocol::v2::{GlobalTrafficScheduler, PoissonPolicy};
+    ///
+    /// let scheduler = GlobalTrafficScheduler::with_policy(100.0, PoissonPolicy::new(100.0));
+    /// assert_eq!(scheduler.target_rate(), 100.0);
+    /// ```
+    pub fn with_policy(target_rate: f64, policy: impl SchedulingPolicy + 'static) -> Self {
         Self {
             unified_queue: VecDeque::new(),
             target_rate,
@@ -128,9 +325,20 @@ impl GlobalTrafficScheduler {
             statistics: TrafficStatistics::new(),
             max_queue_depth: MAX_QUEUE_DEPTH,
             max_queue_memory: MAX_QUEUE_MEMORY,
+            policy: Box::new(policy),
+            next_slot: None,
+            queued_dummy: 0,
         }
     }
 
+    /// Replaces the scheduling policy
+    ///
+    /// Any pending release slot is discarded; the new policy picks the next one.
+    pub fn set_policy(&mut self, policy: impl SchedulingPolicy + 'static) {
+        self.policy = Box::new(policy);
+        self.next_slot = None;
+    }
+
     /// Creates a new global traffic scheduler with default configuration
     ///
     /// Uses the default target rate of 100 messages per second.
@@ -161,8 +369,9 @@ impl GlobalTrafficScheduler {
     ///
     /// Panics if rate is not positive and finite.
     pub fn set_target_rate(&mut self, rate: f64) {
-        assert!(rate > 0.0 && rate.is_finite(), "Target rate must be positive and finite");
+        assert_valid_rate(rate);
         self.target_rate = rate;
+        self.policy.set_target_rate(rate);
     }
 
     /// Returns a reference to the current traffic statistics
@@ -276,6 +485,7 @@ impl GlobalTrafficScheduler {
         self.statistics.current_queue_depth = self.unified_queue.len() + 1;
 
         if is_dummy {
+            self.queued_dummy += 1;
             self.statistics.dummy_messages_sent += 1;
         } else {
             self.statistics.real_messages_sent += 1;
@@ -292,6 +502,9 @@ impl GlobalTrafficScheduler {
     /// Updates statistics on dequeue.
     pub fn dequeue_message(&mut self) -> Option<ScheduledMessage> {
         if let Some(msg) = self.unified_queue.pop_front() {
+            if msg.is_dummy {
+                self.queued_dummy -= 1;
+            }
             let size = msg.size();
             self.statistics.current_queue_memory =
                 self.statistics.current_queue_memory.saturating_sub(size);
@@ -301,6 +514,43 @@ impl GlobalTrafficScheduler {
             None
         }
     }
+
+    /// Returns the next release slot, asking the policy if none is pending
+    ///
+    /// Returns `None` if the policy is idle.
+    pub fn next_release(&mut self, now: Instant) -> Option<Instant> {
+        if self.next_slot.is_none() {
+            let queued_real = self.unified_queue.len() - self.queued_dummy;
+            self.next_slot = self.policy.next_release(now, queued_real, self.queued_dummy);
+        }
+        self.next_slot
+    }
+
+    /// Releases one frame if the current slot has been reached
+    ///
+    /// Returns `None` before the slot. At or after it, returns the head of
+    /// the queue, or [`Release::Cover`] if the queue is empty, and asks the
+    /// policy for the following slot.
+    ///
+    /// ## Requirements
+    ///
+    /// - REQ-5: Global Unified Traffic Scheduler
+    /// - REQ-20: Cross-Session Indistinguishability
+    pub fn poll_release(&mut self, now: Instant) -> Option<Release> {
+        let slot = self.next_release(now)?;
+        if now < slot {
+            return None;
+        }
+
+        let release = match self.dequeue_message() {
+            Some(msg) => Release::Message(msg),
+            None => Release::Cover,
+        };
+        self.last_send_time = now;
+        self.next_slot = None;
+        self.next_release(slot.max(now));
+        Some(release)
+    }
 }
 
 /// Scheduled message in the unified queue
@@ -625,4 +875,108 @@ mod tests {
         assert_eq!(stats.real_messages_sent, 0);
         assert_eq!(stats.dummy_messages_sent, 0);
     }
+
+    /// Polls `scheduler` every millisecond of a synthetic clock, returning
+    /// each release offset (ms) and whether the slot was a cover request
+    fn drive(scheduler: &mut GlobalTrafficScheduler, start: Instant, millis: u64) -> Vec<(u64, bool)> {
+        (0..=millis)
+            .filter_map(|ms| {
+                scheduler
+                    .poll_release(start + Duration::from_millis(ms))
+                    .map(|release| (ms, matches!(release, Release::Cover)))
+            })
+            .collect()
+    }
+
+    fn fill(scheduler: &mut GlobalTrafficScheduler, count: usize, is_dummy: bool) {
+        for _ in 0..count {
+            scheduler
+                .schedule_message(SessionId::new([0u8; 32]), vec![0u8; 64], is_dummy)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_constant_rate_policy_release_pattern() {
+        let start = Instant::now();
+        let mut scheduler = GlobalTrafficScheduler::new(10.0);
+        assert_eq!(scheduler.next_release(start), Some(start + Duration::from_millis(100)));
+        fill(&mut scheduler, 3, false);
+
+        let releases = drive(&mut scheduler, start, 1000);
+        let times: Vec<u64> = releases.iter().map(|&(ms, _)| ms).collect();
+        assert_eq!(times, (1..=10).map(|i| i * 100).collect::<Vec<_>>());
+
+        // Queued messages go first, then empty slots request cover traffic
+        let covers: Vec<bool> = releases.iter().map(|&(_, cover)| cover).collect();
+        assert_eq!(&covers[..3], &[false, false, false]);
+        assert!(covers[3..].iter().all(|&cover| cover));
+        assert!(scheduler.is_queue_empty());
+    }
+
+    #[test]
+    fn test_constant_rate_policy_no_catch_up_burst() {
+        let start = Instant::now();
+        let mut policy = ConstantRatePolicy::new(10.0);
+
+        let first = policy.next_release(start, 0, 0).unwrap();
+        assert_eq!(first, start + Duration::from_millis(100));
+
+        // Polled late: next slot is now, not a burst of missed slots
+        let late = start + Duration::from_millis(550);
+        assert_eq!(policy.next_release(late, 0, 0), Some(late));
+        assert_eq!(policy.next_release(late, 0, 0), Some(late + Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_poisson_policy_release_pattern() {
+        let start = Instant::now();
+        let rate = 100.0;
+        let mut policy = PoissonPolicy::with_seed(rate, [7u8; 32]);
+
+        let mut now = start;
+        let mut gaps = Vec::new();
+        for _ in 0..5000 {
+            let next = policy.next_release(now, 0, 0).unwrap();
+            assert!(next >= now);
+            gaps.push((next - now).as_secs_f64());
+            now = next;
+        }
+
+        // Mean gap 1/rate; exponential gaps are irregular (CV close to 1)
+        let mean = gaps.iter().sum::<f64>() / gaps.len() as f64;
+        let variance = gaps.iter().map(|g| (g - mean).powi(2)).sum::<f64>() / gaps.len() as f64;
+        assert!((mean - 1.0 / rate).abs() < 0.1 / rate, "mean gap {}", mean);
+        assert!((variance.sqrt() / mean - 1.0).abs() < 0.1);
+
+        // Same seed gives the same gaps
+        let mut replay = PoissonPolicy::with_seed(rate, [7u8; 32]);
+        let first_gap = replay.next_release(start, 0, 0).unwrap() - start;
+        assert_eq!(first_gap.as_secs_f64(), gaps[0]);
+    }
+
+    #[test]
+    fn test_release_timing_independent_of_dummy_flag() {
+        let start = Instant::now();
+        let policies: [fn() -> GlobalTrafficScheduler; 2] = [
+            || GlobalTrafficScheduler::new(50.0),
+            || GlobalTrafficScheduler::with_policy(50.0, PoissonPolicy::with_seed(50.0, [3u8; 32])),
+        ];
+
+        for make in policies {
+            let mut real = make();
+            let mut dummy = make();
+            let mut empty = make();
+            fill(&mut real, 20, false);
+            fill(&mut dummy, 20, true);
+
+            // Slot times are identical whether the queue holds real
+            // messages, dummies, or nothing at all
+            let timing = |releases: Vec<(u64, bool)>| releases.into_iter().map(|(ms, _)| ms).collect::<Vec<_>>();
+            let real_times = timing(drive(&mut real, start, 2000));
+            assert!(!real_times.is_empty());
+            assert_eq!(real_times, timing(drive(&mut dummy, start, 2000)));
+            assert_eq!(real_times, timing(drive(&mut empty, start, 2000)));
+        }
+    }
 }
@@ -8,8 +8,9 @@
 //! B4AE v2.0 separates authentication into distinct modes with clear security properties:
 //!
 //! - **Mode A (Deniable)**: XEdDSA-only signatures providing deniable authentication
-//! - **Mode B (Post-Quantum)**: Dilithium5-only signatures providing non-repudiable
-//!   post-quantum authentication
+//! - **Mode B (Post-Quantum)**: Dilithium-only signatures providing non-repudiable
+//!   post-quantum authentication (Dilithium5, or Dilithium3 under the `Standard`
+//!   cipher suite; see [`AuthenticationMode::signature_scheme_for`])
 //! - **Mode C (Future)**: Research placeholder for post-quantum deniable authentication
 //!
 //! ## Session Binding
@@ -21,6 +22,10 @@
 //!
 //! This prevents key transplant attacks and ensures session isolation.
 
+use crate::crypto::dilithium::DilithiumVariant;
+use crate::protocol::v2::constants::{HANDSHAKE_TIMEOUT_SECONDS, MAX_CLOCK_SKEW_SECONDS};
+use crate::security::hardened_core::CipherSuite;
+use crate::time::{Clock, SystemClock};
 use serde::{Deserialize, Serialize};
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
@@ -163,6 +168,17 @@ impl AuthenticationMode {
         }
     }
 
+    /// Returns the signature scheme for this mode under a negotiated cipher suite
+    ///
+    /// Mode B signs with the Dilithium parameter set of the suite (Dilithium3
+    /// for `Standard`, Dilithium5 otherwise); other modes ignore the suite.
+    pub fn signature_scheme_for(&self, suite: CipherSuite) -> SignatureScheme {
+        match (self, suite.dilithium_variant()) {
+            (AuthenticationMode::ModeB, DilithiumVariant::Dilithium3) => SignatureScheme::Dilithium3,
+            _ => self.signature_scheme(),
+        }
+    }
+
     /// Returns the expected handshake latency for this mode in milliseconds
     ///
     /// This is an approximate value based on signature verification times.
@@ -274,11 +290,25 @@ pub enum SignatureScheme {
     
     /// Dilithium5 signature scheme (post-quantum, non-repudiable)
     Dilithium5,
+
+    /// Dilithium3 signature scheme (Mode B under the `Standard` cipher suite)
+    Dilithium3,
     
     /// Future signature scheme (research placeholder)
     Future,
 }
 
+impl SignatureScheme {
+    /// Dilithium parameter set used by this scheme, if any
+    pub fn dilithium_variant(&self) -> Option<DilithiumVariant> {
+        match self {
+            SignatureScheme::Dilithium5 => Some(DilithiumVariant::Dilithium5),
+            SignatureScheme::Dilithium3 => Some(DilithiumVariant::Dilithium3),
+            SignatureScheme::XEdDSA | SignatureScheme::Future => None,
+        }
+    }
+}
+
 /// Session identifier uniquely identifying a protocol session
 ///
 /// The session ID is derived using HKDF-SHA512 from:
@@ -498,7 +528,8 @@ pub struct HandshakeInit {
     /// Mode-specific signature over transcript
     ///
     /// - Mode A: XEdDSA signature (64 bytes)
-    /// - Mode B: Dilithium5 signature (~4595 bytes)
+    /// - Mode B: Dilithium signature under the cipher suite's parameter set
+    ///   (Dilithium5 ~4595 bytes, Dilithium3 ~3293 bytes)
     pub signature: Vec<u8>,
     
     /// Timestamp for replay protection
@@ -506,6 +537,12 @@ pub struct HandshakeInit {
     
     /// Mode binding value to prevent downgrade attacks
     pub mode_binding: ModeBinding,
+
+    /// Echo of the server's cookie challenge
+    ///
+    /// Verified (and its `client_random` run through replay protection)
+    /// before any expensive cryptography on the server side.
+    pub cookie: ClientHelloWithCookie,
 }
 
 impl HandshakeInit {
@@ -514,8 +551,13 @@ impl HandshakeInit {
     /// Checks that:
     /// - Signature is non-empty
     /// - Ephemeral keys are valid sizes
-    /// - Timestamp is reasonable
+    /// - Timestamp is neither too far in the future nor expired
     pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with_clock(&SystemClock)
+    }
+
+    /// [`validate`](Self::validate) against an injected clock
+    pub fn validate_with_clock(&self, clock: &dyn Clock) -> Result<(), ValidationError> {
         if self.signature.is_empty() {
             return Err(ValidationError::EmptySignature);
         }
@@ -524,14 +566,7 @@ impl HandshakeInit {
             return Err(ValidationError::InvalidKyberKey);
         }
         
-        // Timestamp should not be too far in the future (allow 5 minute clock skew)
-        let now = crate::time::current_time_secs();
-        
-        if self.timestamp > now + 300 {
-            return Err(ValidationError::FutureTimestamp);
-        }
-        
-        Ok(())
+        validate_timestamp(self.timestamp, clock)
     }
 }
 
@@ -558,7 +593,8 @@ pub struct HandshakeResponse {
     /// Mode-specific signature over transcript
     ///
     /// - Mode A: XEdDSA signature (64 bytes)
-    /// - Mode B: Dilithium5 signature (~4595 bytes)
+    /// - Mode B: Dilithium signature under the cipher suite's parameter set
+    ///   (Dilithium5 ~4595 bytes, Dilithium3 ~3293 bytes)
     pub signature: Vec<u8>,
     
     /// Timestamp for replay protection
@@ -574,8 +610,13 @@ impl HandshakeResponse {
     /// Checks that:
     /// - Signature is non-empty
     /// - Ephemeral keys are valid sizes
-    /// - Timestamp is reasonable
+    /// - Timestamp is neither too far in the future nor expired
     pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with_clock(&SystemClock)
+    }
+
+    /// [`validate`](Self::validate) against an injected clock
+    pub fn validate_with_clock(&self, clock: &dyn Clock) -> Result<(), ValidationError> {
         if self.signature.is_empty() {
             return Err(ValidationError::EmptySignature);
         }
@@ -584,14 +625,7 @@ impl HandshakeResponse {
             return Err(ValidationError::InvalidKyberKey);
         }
         
-        // Timestamp should not be too far in the future (allow 5 minute clock skew)
-        let now = crate::time::current_time_secs();
-        
-        if self.timestamp > now + 300 {
-            return Err(ValidationError::FutureTimestamp);
-        }
-        
-        Ok(())
+        validate_timestamp(self.timestamp, clock)
     }
 }
 
@@ -627,23 +661,34 @@ impl HandshakeComplete {
     ///
     /// Checks that:
     /// - Signature is non-empty
-    /// - Timestamp is reasonable
+    /// - Timestamp is neither too far in the future nor expired
     pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with_clock(&SystemClock)
+    }
+
+    /// [`validate`](Self::validate) against an injected clock
+    pub fn validate_with_clock(&self, clock: &dyn Clock) -> Result<(), ValidationError> {
         if self.signature.is_empty() {
             return Err(ValidationError::EmptySignature);
         }
         
-        // Timestamp should not be too far in the future (allow 5 minute clock skew)
-        let now = crate::time::current_time_secs();
-        
-        if self.timestamp > now + 300 {
-            return Err(ValidationError::FutureTimestamp);
-        }
-        
-        Ok(())
+        validate_timestamp(self.timestamp, clock)
     }
 }
 
+/// Rejects timestamps beyond the allowed clock skew in the future, or older
+/// than the handshake timeout plus that skew
+fn validate_timestamp(timestamp: u64, clock: &dyn Clock) -> Result<(), ValidationError> {
+    let now = clock.now_unix_secs();
+    if timestamp > now.saturating_add(MAX_CLOCK_SKEW_SECONDS) {
+        return Err(ValidationError::FutureTimestamp);
+    }
+    if now.saturating_sub(timestamp) > HANDSHAKE_TIMEOUT_SECONDS + MAX_CLOCK_SKEW_SECONDS {
+        return Err(ValidationError::ExpiredTimestamp);
+    }
+    Ok(())
+}
+
 /// Validation error for message structures
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ValidationError {
@@ -778,6 +823,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_signature_scheme_for_cipher_suite() {
+        let mode_b = AuthenticationMode::ModeB;
+        assert_eq!(mode_b.signature_scheme_for(CipherSuite::Standard), SignatureScheme::Dilithium3);
+        assert_eq!(mode_b.signature_scheme_for(CipherSuite::High), SignatureScheme::Dilithium5);
+        assert_eq!(mode_b.signature_scheme_for(CipherSuite::Maximum), SignatureScheme::Dilithium5);
+        assert_eq!(
+            AuthenticationMode::ModeA.signature_scheme_for(CipherSuite::Standard),
+            SignatureScheme::XEdDSA
+        );
+        assert_eq!(SignatureScheme::Dilithium3.dilithium_variant(), Some(DilithiumVariant::Dilithium3));
+        assert_eq!(SignatureScheme::XEdDSA.dilithium_variant(), None);
+    }
+
     #[test]
     fn test_expected_handshake_latency() {
         // Mode A should be fast
@@ -982,17 +1041,20 @@ mod tests {
     #[test]
     fn test_handshake_init_validation() {
         let mode_binding = ModeBinding::new([8u8; 32]);
+        let cookie = ClientHelloWithCookie {
+            client_random: [16u8; 32],
+            cookie: [17u8; 32],
+            timestamp: crate::time::current_time_secs(),
+        };
         
         // Valid handshake init
         let valid_msg = HandshakeInit {
             ephemeral_x25519: [9u8; 32],
             ephemeral_kyber: vec![10u8; 1568], // Kyber1024 public key size
             signature: vec![11u8; 64], // XEdDSA signature size
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: crate::time::current_time_secs(),
             mode_binding: mode_binding.clone(),
+            cookie: cookie.clone(),
         };
         assert!(valid_msg.validate().is_ok());
 
@@ -1001,11 +1063,9 @@ mod tests {
             ephemeral_x25519: [9u8; 32],
             ephemeral_kyber: vec![10u8; 1568],
             signature: vec![],
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: crate::time::current_time_secs(),
             mode_binding: mode_binding.clone(),
+            cookie: cookie.clone(),
         };
         assert_eq!(invalid_sig.validate(), Err(ValidationError::EmptySignature));
 
@@ -1014,11 +1074,9 @@ mod tests {
             ephemeral_x25519: [9u8; 32],
             ephemeral_kyber: vec![],
             signature: vec![11u8; 64],
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: crate::time::current_time_secs(),
             mode_binding: mode_binding.clone(),
+            cookie: cookie.clone(),
         };
         assert_eq!(invalid_kyber.validate(), Err(ValidationError::InvalidKyberKey));
 
@@ -1027,11 +1085,9 @@ mod tests {
             ephemeral_x25519: [9u8; 32],
             ephemeral_kyber: vec![10u8; 1568],
             signature: vec![11u8; 64],
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() + 1000, // 1000 seconds in future
+            timestamp: crate::time::current_time_secs() + 1000, // 1000 seconds in future
             mode_binding,
+            cookie,
         };
         assert_eq!(future_timestamp.validate(), Err(ValidationError::FutureTimestamp));
     }
@@ -1044,6 +1100,11 @@ mod tests {
             signature: vec![14u8; 64],
             timestamp: 1111111111,
             mode_binding: ModeBinding::new([15u8; 32]),
+            cookie: ClientHelloWithCookie {
+                client_random: [16u8; 32],
+                cookie: [17u8; 32],
+                timestamp: 1111111110,
+            },
         };
 
         // Test serialization roundtrip
@@ -1055,6 +1116,7 @@ mod tests {
         assert_eq!(deserialized.signature, vec![14u8; 64]);
         assert_eq!(deserialized.timestamp, 1111111111);
         assert_eq!(deserialized.mode_binding.as_bytes(), &[15u8; 32]);
+        assert_eq!(deserialized.cookie.cookie, [17u8; 32]);
     }
 
     #[test]
@@ -1066,10 +1128,7 @@ mod tests {
             ephemeral_x25519: [17u8; 32],
             ephemeral_kyber: vec![18u8; 1568], // Kyber1024 ciphertext size
             signature: vec![19u8; 4595], // Dilithium5 signature size
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: crate::time::current_time_secs(),
             mode_binding: mode_binding.clone(),
         };
         assert!(valid_msg.validate().is_ok());
@@ -1079,10 +1138,7 @@ mod tests {
             ephemeral_x25519: [17u8; 32],
             ephemeral_kyber: vec![18u8; 1568],
             signature: vec![],
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: crate::time::current_time_secs(),
             mode_binding: mode_binding.clone(),
         };
         assert_eq!(invalid_sig.validate(), Err(ValidationError::EmptySignature));
@@ -1092,10 +1148,7 @@ mod tests {
             ephemeral_x25519: [17u8; 32],
             ephemeral_kyber: vec![],
             signature: vec![19u8; 4595],
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: crate::time::current_time_secs(),
             mode_binding: mode_binding.clone(),
         };
         assert_eq!(invalid_kyber.validate(), Err(ValidationError::InvalidKyberKey));
@@ -1105,10 +1158,7 @@ mod tests {
             ephemeral_x25519: [17u8; 32],
             ephemeral_kyber: vec![18u8; 1568],
             signature: vec![19u8; 4595],
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() + 1000,
+            timestamp: crate::time::current_time_secs() + 1000,
             mode_binding,
         };
         assert_eq!(future_timestamp.validate(), Err(ValidationError::FutureTimestamp));
@@ -1142,10 +1192,7 @@ mod tests {
         // Valid handshake complete
         let valid_msg = HandshakeComplete {
             signature: vec![25u8; 64],
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: crate::time::current_time_secs(),
             mode_binding: mode_binding.clone(),
         };
         assert!(valid_msg.validate().is_ok());
@@ -1153,10 +1200,7 @@ mod tests {
         // Empty signature should fail
         let invalid_sig = HandshakeComplete {
             signature: vec![],
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp: crate::time::current_time_secs(),
             mode_binding: mode_binding.clone(),
         };
         assert_eq!(invalid_sig.validate(), Err(ValidationError::EmptySignature));
@@ -1164,10 +1208,7 @@ mod tests {
         // Future timestamp should fail
         let future_timestamp = HandshakeComplete {
             signature: vec![25u8; 64],
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() + 1000,
+            timestamp: crate::time::current_time_secs() + 1000,
             mode_binding,
         };
         assert_eq!(future_timestamp.validate(), Err(ValidationError::FutureTimestamp));
@@ -1190,6 +1231,34 @@ mod tests {
         assert_eq!(deserialized.mode_binding.as_bytes(), &[27u8; 32]);
     }
 
+    #[test]
+    fn test_handshake_validation_with_mock_clock() {
+        use crate::time::MockClock;
+        use std::time::Duration;
+
+        let clock = MockClock::new(1_000_000);
+        let msg = HandshakeComplete {
+            signature: vec![25u8; 64],
+            timestamp: 1_000_000 + MAX_CLOCK_SKEW_SECONDS + 10,
+            mode_binding: ModeBinding::new([24u8; 32]),
+        };
+
+        // Too far ahead until the clock catches up
+        assert_eq!(msg.validate_with_clock(&clock), Err(ValidationError::FutureTimestamp));
+        clock.advance(Duration::from_secs(10));
+        assert!(msg.validate_with_clock(&clock).is_ok());
+
+        // Still valid at the edge of the expiry window, expired just past it
+        clock.advance(Duration::from_secs(MAX_CLOCK_SKEW_SECONDS + HANDSHAKE_TIMEOUT_SECONDS + MAX_CLOCK_SKEW_SECONDS));
+        assert!(msg.validate_with_clock(&clock).is_ok());
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(msg.validate_with_clock(&clock), Err(ValidationError::ExpiredTimestamp));
+
+        // A wall-clock jump back clears the expiry
+        clock.set_unix_secs(msg.timestamp);
+        assert!(msg.validate_with_clock(&clock).is_ok());
+    }
+
     #[test]
     fn test_validation_error_display() {
         let err = ValidationError::EmptySignature;
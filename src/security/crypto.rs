@@ -4,7 +4,7 @@
 //! bounds checking and constant-time execution for sensitive operations.
 
 use crate::security::hardened_core::{
-    SecurityResult, SecurityError, constant_time_eq_security,
+    SecurityResult, SecurityError, SecretBuffer, constant_time_eq_security,
     checked_add_security, checked_sub_security
 };
 use zeroize::Zeroizing;
@@ -69,6 +69,28 @@ impl SecurityKey {
         Self::new(data.to_vec(), key_type)
     }
     
+    /// Parse kunci ber-prefix panjang u16 dari SecretBuffer
+    ///
+    /// Bytes kunci hanya disalin ke `Zeroizing`, tidak pernah ke `Vec` biasa.
+    pub fn parse_security(buffer: &mut SecretBuffer, key_type: KeyType) -> SecurityResult<Self> {
+        let bytes = buffer.read_len_prefixed_u16("key_length", MAX_KEY_SIZE)?;
+        if bytes.is_empty() {
+            return Err(SecurityError::InvalidKey {
+                expected: 1,
+                actual: 0,
+            });
+        }
+        
+        let mut data = Zeroizing::new(Vec::with_capacity(bytes.len()));
+        data.extend_from_slice(bytes);
+        Ok(SecurityKey { data, key_type })
+    }
+    
+    /// Serialisasi kunci (prefix panjang u16) ke SecretBuffer
+    pub fn serialize_security(&self, buffer: &mut SecretBuffer) -> SecurityResult<()> {
+        buffer.write_len_prefixed_u16(&self.data)
+    }
+    
     /// Kembalikan data kunci sebagai slice bytes
     pub fn as_slice(&self) -> &[u8] {
         &self.data
@@ -366,6 +388,27 @@ mod tests {
         assert!(large_key.is_err());
     }
     
+    #[test]
+    fn test_security_key_secret_buffer_roundtrip() {
+        let key = SecurityKey::new(vec![0x5A; 32], KeyType::Static).expect("Key creation should succeed");
+        let mut buffer = SecretBuffer::new(2 + 32).expect("Buffer creation should succeed");
+        key.serialize_security(&mut buffer).expect("Serialize should succeed");
+        
+        buffer.set_position(0).expect("Set position should succeed");
+        let parsed = SecurityKey::parse_security(&mut buffer, KeyType::Static).expect("Parse should succeed");
+        assert_eq!(parsed.as_slice(), key.as_slice());
+        assert_eq!(parsed.key_type(), KeyType::Static);
+        
+        // Empty and oversized keys are rejected
+        let mut buffer = SecretBuffer::from_slice(&[0x00, 0x00]).expect("Buffer creation should succeed");
+        assert!(SecurityKey::parse_security(&mut buffer, KeyType::Static).is_err());
+        let mut buffer = SecretBuffer::from_slice(&[0x00, 0xFF]).expect("Buffer creation should succeed");
+        assert!(matches!(
+            SecurityKey::parse_security(&mut buffer, KeyType::Static),
+            Err(SecurityError::ResourceExhaustionProtection { .. })
+        ));
+    }
+    
     #[test]
     fn test_hkdf_validation() {
         // Valid parameters
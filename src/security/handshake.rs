@@ -4,10 +4,14 @@
 //! comprehensive bounds checking and zero-trust input validation.
 
 use crate::security::{
-    SecurityResult, SecurityError, SecurityBuffer,
+    SecurityResult, SecurityError, SecurityBuffer, SecretBuffer,
     ProtocolVersion, CipherSuite,
     HandshakeState
 };
+use crate::security::hardened_core::{self, constant_time_eq_security, VersionRange};
+use hmac::{Hmac, Mac};
+use sha3::Sha3_256;
+use zeroize::Zeroizing;
 
 /// Maximum sizes for security validation
 const MAX_ECDH_SIZE: usize = 256;
@@ -16,27 +20,27 @@ const MAX_ECDSA_SIZE: usize = 128;
 const DILITHIUM_SIGNATURE_SIZE: usize = 4595;
 const MAX_EXTENSIONS_SIZE: usize = 4096;
 
+/// Extension type: sender's supported version range (`min || max`)
+pub const EXTENSION_SUPPORTED_VERSIONS: u16 = 0x0001;
+/// Extension type: responder's selected version
+pub const EXTENSION_SELECTED_VERSION: u16 = 0x0002;
+/// Extension type: HMAC-SHA3-256 binding of the version negotiation
+pub const EXTENSION_VERSION_BINDING: u16 = 0x0003;
+/// Minimum size of the handshake secret that keys the version binding
+pub const MIN_BINDING_KEY_SIZE: usize = 32;
+
 /// Security-hardened hybrid ciphertext parsing
+///
+/// Parsers read from a [`SecretBuffer`], which wipes the raw message on drop;
+/// serializers write to a growable [`SecurityBuffer`].
 pub struct SecurityHybridParser;
 
 impl SecurityHybridParser {
     /// Parse hybrid ciphertext with comprehensive bounds checking
-    pub fn parse_ciphertext(buffer: &mut SecurityBuffer) -> SecurityResult<SecurityHybridCiphertext> {
-        // Read ECDH ephemeral public key length (4 bytes)
-        let ecdh_len = buffer.read_u32_be()? as usize;
-        
-        // Validate ECDH length
+    pub fn parse_ciphertext(buffer: &mut SecretBuffer) -> SecurityResult<SecurityHybridCiphertext> {
+        // Read ECDH ephemeral public key (4-byte length prefix)
         const MAX_ECDH_SIZE: usize = 256;
-        if ecdh_len > MAX_ECDH_SIZE {
-            return Err(SecurityError::ResourceExhaustionProtection {
-                resource: "ecdh_length".to_string(),
-                limit: MAX_ECDH_SIZE,
-                requested: ecdh_len,
-            });
-        }
-        
-        // Read ECDH ephemeral public key
-        let ecdh_ephemeral_public = buffer.read_exact(ecdh_len)?.to_vec();
+        let ecdh_ephemeral_public = buffer.read_len_prefixed_u32("ecdh_length", MAX_ECDH_SIZE)?.to_vec();
         
         // Read Kyber ciphertext (fixed size)
         const KYBER_CIPHERTEXT_SIZE: usize = 1568; // Kyber-1024 ciphertext size
@@ -49,22 +53,10 @@ impl SecurityHybridParser {
     }
     
     /// Parse hybrid signature with comprehensive bounds checking
-    pub fn parse_signature(buffer: &mut SecurityBuffer) -> SecurityResult<SecurityHybridSignature> {
-        // Read ECDSA signature length (4 bytes)
-        let ecdsa_len = buffer.read_u32_be()? as usize;
-        
-        // Validate ECDSA length
+    pub fn parse_signature(buffer: &mut SecretBuffer) -> SecurityResult<SecurityHybridSignature> {
+        // Read ECDSA signature (4-byte length prefix)
         const MAX_ECDSA_SIZE: usize = 128;
-        if ecdsa_len > MAX_ECDSA_SIZE {
-            return Err(SecurityError::ResourceExhaustionProtection {
-                resource: "ecdsa_length".to_string(),
-                limit: MAX_ECDSA_SIZE,
-                requested: ecdsa_len,
-            });
-        }
-        
-        // Read ECDSA signature
-        let ecdsa_signature = buffer.read_exact(ecdsa_len)?.to_vec();
+        let ecdsa_signature = buffer.read_len_prefixed_u32("ecdsa_length", MAX_ECDSA_SIZE)?.to_vec();
         
         // Read Dilithium signature (fixed size)
         const DILITHIUM_SIGNATURE_SIZE: usize = 4595; // Dilithium5 signature size
@@ -95,8 +87,7 @@ impl SecurityHybridParser {
         }
         
         // Write ECDH length and data
-        buffer.write_u32_be(ciphertext.ecdh_ephemeral_public.len() as u32)?;
-        buffer.write_slice(&ciphertext.ecdh_ephemeral_public)?;
+        buffer.write_len_prefixed_u32(&ciphertext.ecdh_ephemeral_public)?;
         
         // Write Kyber ciphertext
         buffer.write_slice(&ciphertext.kyber_ciphertext)?;
@@ -123,8 +114,7 @@ impl SecurityHybridParser {
         }
         
         // Write ECDSA length and data
-        buffer.write_u32_be(signature.ecdsa_signature.len() as u32)?;
-        buffer.write_slice(&signature.ecdsa_signature)?;
+        buffer.write_len_prefixed_u32(&signature.ecdsa_signature)?;
         
         // Write Dilithium signature
         buffer.write_slice(&signature.dilithium_signature)?;
@@ -156,7 +146,7 @@ pub struct SecurityHandshakeMessageParser;
 
 impl SecurityHandshakeMessageParser {
     /// Parse handshake init message
-    pub fn parse_init(buffer: &mut SecurityBuffer) -> SecurityResult<SecurityHandshakeInit> {
+    pub fn parse_init(buffer: &mut SecretBuffer) -> SecurityResult<SecurityHandshakeInit> {
         // Parse protocol version
         let version_bytes = buffer.read_exact(2)?;
         let version = ProtocolVersion::from_bytes([version_bytes[0], version_bytes[1]])?;
@@ -171,25 +161,9 @@ impl SecurityHandshakeMessageParser {
         // Parse timestamp
         let timestamp = buffer.read_i64_be()?;
         
-        // Parse extensions length
-        let extensions_len = buffer.read_u16_be()? as usize;
-        
-        // Validate extensions length
+        // Parse extensions (2-byte length prefix)
         const MAX_EXTENSIONS_SIZE: usize = 4096;
-        if extensions_len > MAX_EXTENSIONS_SIZE {
-            return Err(SecurityError::ResourceExhaustionProtection {
-                resource: "extensions_length".to_string(),
-                limit: MAX_EXTENSIONS_SIZE,
-                requested: extensions_len,
-            });
-        }
-        
-        // Parse extensions
-        let extensions = if extensions_len > 0 {
-            buffer.read_exact(extensions_len)?.to_vec()
-        } else {
-            Vec::new()
-        };
+        let extensions = buffer.read_len_prefixed_u16("extensions_length", MAX_EXTENSIONS_SIZE)?.to_vec();
         
         Ok(SecurityHandshakeInit {
             version,
@@ -201,7 +175,7 @@ impl SecurityHandshakeMessageParser {
     }
     
     /// Parse handshake response message
-    pub fn parse_response(buffer: &mut SecurityBuffer) -> SecurityResult<SecurityHandshakeResponse> {
+    pub fn parse_response(buffer: &mut SecretBuffer) -> SecurityResult<SecurityHandshakeResponse> {
         // Parse protocol version
         let version_bytes = buffer.read_exact(2)?;
         let version = ProtocolVersion::from_bytes([version_bytes[0], version_bytes[1]])?;
@@ -219,24 +193,8 @@ impl SecurityHandshakeMessageParser {
         // Parse timestamp
         let timestamp = buffer.read_i64_be()?;
         
-        // Parse extensions length
-        let extensions_len = buffer.read_u16_be()? as usize;
-        
-        // Validate extensions length
-        if extensions_len > MAX_EXTENSIONS_SIZE {
-            return Err(SecurityError::ResourceExhaustionProtection {
-                resource: "extensions_length".to_string(),
-                limit: MAX_EXTENSIONS_SIZE,
-                requested: extensions_len,
-            });
-        }
-        
-        // Parse extensions
-        let extensions = if extensions_len > 0 {
-            buffer.read_exact(extensions_len)?.to_vec()
-        } else {
-            Vec::new()
-        };
+        // Parse extensions (2-byte length prefix)
+        let extensions = buffer.read_len_prefixed_u16("extensions_length", MAX_EXTENSIONS_SIZE)?.to_vec();
         
         Ok(SecurityHandshakeResponse {
             version,
@@ -249,7 +207,7 @@ impl SecurityHandshakeMessageParser {
     }
     
     /// Parse handshake complete message
-    pub fn parse_complete(buffer: &mut SecurityBuffer) -> SecurityResult<SecurityHandshakeComplete> {
+    pub fn parse_complete(buffer: &mut SecretBuffer) -> SecurityResult<SecurityHandshakeComplete> {
         // Parse protocol version
         let version_bytes = buffer.read_exact(2)?;
         let version = ProtocolVersion::from_bytes([version_bytes[0], version_bytes[1]])?;
@@ -260,24 +218,8 @@ impl SecurityHandshakeMessageParser {
         // Parse timestamp
         let timestamp = buffer.read_i64_be()?;
         
-        // Parse extensions length
-        let extensions_len = buffer.read_u16_be()? as usize;
-        
-        // Validate extensions length
-        if extensions_len > MAX_EXTENSIONS_SIZE {
-            return Err(SecurityError::ResourceExhaustionProtection {
-                resource: "extensions_length".to_string(),
-                limit: MAX_EXTENSIONS_SIZE,
-                requested: extensions_len,
-            });
-        }
-        
-        // Parse extensions
-        let extensions = if extensions_len > 0 {
-            buffer.read_exact(extensions_len)?.to_vec()
-        } else {
-            Vec::new()
-        };
+        // Parse extensions (2-byte length prefix)
+        let extensions = buffer.read_len_prefixed_u16("extensions_length", MAX_EXTENSIONS_SIZE)?.to_vec();
         
         Ok(SecurityHandshakeComplete {
             version,
@@ -286,6 +228,44 @@ impl SecurityHandshakeMessageParser {
             extensions,
         })
     }
+    
+    /// Serialize handshake init message (inverse of [`parse_init`](Self::parse_init))
+    pub fn serialize_init(message: &SecurityHandshakeInit, buffer: &mut SecurityBuffer) -> SecurityResult<()> {
+        buffer.write_slice(&message.version.to_bytes())?;
+        buffer.write_u8(message.cipher_suite.to_u8())?;
+        SecurityHybridParser::serialize_ciphertext(&message.ephemeral_keys, buffer)?;
+        buffer.write_u64_be(message.timestamp)?;
+        Self::serialize_extensions(&message.extensions, buffer)
+    }
+    
+    /// Serialize handshake response message (inverse of [`parse_response`](Self::parse_response))
+    pub fn serialize_response(message: &SecurityHandshakeResponse, buffer: &mut SecurityBuffer) -> SecurityResult<()> {
+        buffer.write_slice(&message.version.to_bytes())?;
+        buffer.write_u8(message.cipher_suite.to_u8())?;
+        SecurityHybridParser::serialize_ciphertext(&message.ephemeral_keys, buffer)?;
+        SecurityHybridParser::serialize_signature(&message.signature, buffer)?;
+        buffer.write_u64_be(message.timestamp)?;
+        Self::serialize_extensions(&message.extensions, buffer)
+    }
+    
+    /// Serialize handshake complete message (inverse of [`parse_complete`](Self::parse_complete))
+    pub fn serialize_complete(message: &SecurityHandshakeComplete, buffer: &mut SecurityBuffer) -> SecurityResult<()> {
+        buffer.write_slice(&message.version.to_bytes())?;
+        SecurityHybridParser::serialize_signature(&message.signature, buffer)?;
+        buffer.write_u64_be(message.timestamp)?;
+        Self::serialize_extensions(&message.extensions, buffer)
+    }
+    
+    fn serialize_extensions(extensions: &[u8], buffer: &mut SecurityBuffer) -> SecurityResult<()> {
+        if extensions.len() > MAX_EXTENSIONS_SIZE {
+            return Err(SecurityError::ResourceExhaustionProtection {
+                resource: "extensions_size".to_string(),
+                limit: MAX_EXTENSIONS_SIZE,
+                requested: extensions.len(),
+            });
+        }
+        buffer.write_len_prefixed_u16(extensions)
+    }
 }
 
 /// Security-hardened handshake init message
@@ -333,10 +313,177 @@ pub struct SecurityHandshakeComplete {
     pub extensions: Vec<u8>,
 }
 
+/// Protocol version negotiation carried in handshake extensions
+///
+/// Extensions are encoded as `type (2) || length (2) || data`. The initiator
+/// offers its [`VersionRange`]; the responder answers with its own range, the
+/// selected version and an HMAC-SHA3-256 binding over
+/// `offered range || responder range || selected version`, keyed with secret
+/// material both ends derive from the handshake (at least
+/// [`MIN_BINDING_KEY_SIZE`] bytes). The initiator recomputes the binding from
+/// the range it actually sent, so a MITM that rewrites the offer to force an
+/// older version is detected in [`confirm`](Self::confirm); without the
+/// handshake secret it cannot recompute the binding to match.
+///
+/// An initiator that sends no supported-versions extension is treated as
+/// speaking only V1_0. A response without the negotiation extensions is only
+/// accepted when the initiator offered nothing newer than V1_0, so stripping
+/// them cannot force a downgrade.
+pub struct VersionNegotiation;
+
+impl VersionNegotiation {
+    /// Extensions for a HandshakeInit advertising `local`
+    pub fn offer(local: VersionRange) -> Vec<u8> {
+        let mut extensions = Vec::with_capacity(4 + VersionRange::BYTES);
+        push_extension(&mut extensions, EXTENSION_SUPPORTED_VERSIONS, &local.to_bytes());
+        extensions
+    }
+
+    /// Select the version for `init` and build the response extensions
+    pub fn respond(
+        local: VersionRange,
+        init: &SecurityHandshakeInit,
+        binding_key: &[u8],
+    ) -> SecurityResult<(hardened_core::ProtocolVersion, Vec<u8>)> {
+        let offered = Self::offered_range(init)?;
+        let selected = local.negotiate(offered)?;
+        Ok((selected, Self::response_extensions(local, offered, selected, binding_key)?))
+    }
+
+    /// Range offered by `init`; V1_0 only when it carries no offer
+    fn offered_range(init: &SecurityHandshakeInit) -> SecurityResult<VersionRange> {
+        match find_extension(&init.extensions, EXTENSION_SUPPORTED_VERSIONS)? {
+            Some(data) => VersionRange::from_bytes(data),
+            None => Ok(VersionRange::legacy()),
+        }
+    }
+
+    /// Response extensions announcing `local` and `selected` for `offered`
+    fn response_extensions(
+        local: VersionRange,
+        offered: VersionRange,
+        selected: hardened_core::ProtocolVersion,
+        binding_key: &[u8],
+    ) -> SecurityResult<Vec<u8>> {
+        let binding = Self::binding(binding_key, offered, local, selected)?;
+        let mut extensions = Vec::with_capacity(3 * 4 + VersionRange::BYTES + 2 + 32);
+        push_extension(&mut extensions, EXTENSION_SUPPORTED_VERSIONS, &local.to_bytes());
+        push_extension(&mut extensions, EXTENSION_SELECTED_VERSION, &selected.to_bytes());
+        push_extension(&mut extensions, EXTENSION_VERSION_BINDING, &binding);
+        Ok(extensions)
+    }
+
+    /// Check the responder's selection against the range this side offered
+    pub fn confirm(
+        offered: VersionRange,
+        response: &SecurityHandshakeResponse,
+        binding_key: &[u8],
+    ) -> SecurityResult<hardened_core::ProtocolVersion> {
+        let Some(data) = find_extension(&response.extensions, EXTENSION_SUPPORTED_VERSIONS)? else {
+            // Legacy responder: no binding to check, so only acceptable when
+            // nothing newer than V1_0 was on offer
+            if offered.max > VersionRange::legacy().max {
+                return Err(SecurityError::ProtocolDowngrade {
+                    expected: offered.max,
+                    actual: hardened_core::ProtocolVersion::V1_0.as_u16(),
+                });
+            }
+            return VersionRange::legacy().negotiate(offered);
+        };
+        let responder = VersionRange::from_bytes(data)?;
+        let expected = offered.negotiate(responder)?;
+
+        let selected = match find_extension(&response.extensions, EXTENSION_SELECTED_VERSION)? {
+            Some([a, b]) => hardened_core::ProtocolVersion::from_bytes([*a, *b])?,
+            Some(data) => return Err(SecurityError::InvalidLength { expected: 2, actual: data.len() }),
+            None => return Err(missing_extension(EXTENSION_SELECTED_VERSION)),
+        };
+        let binding = find_extension(&response.extensions, EXTENSION_VERSION_BINDING)?
+            .ok_or_else(|| missing_extension(EXTENSION_VERSION_BINDING))?;
+
+        let bound = constant_time_eq_security(binding, &Self::binding(binding_key, offered, responder, selected)?)?;
+        if selected != expected || !bound {
+            return Err(SecurityError::ProtocolDowngrade {
+                expected: expected.as_u16(),
+                actual: selected.as_u16(),
+            });
+        }
+        Ok(selected)
+    }
+
+    /// HMAC-SHA3-256 under `binding_key` over `offered || responder || selected`
+    pub fn binding(
+        binding_key: &[u8],
+        offered: VersionRange,
+        responder: VersionRange,
+        selected: hardened_core::ProtocolVersion,
+    ) -> SecurityResult<[u8; 32]> {
+        if binding_key.len() < MIN_BINDING_KEY_SIZE {
+            return Err(SecurityError::InvalidKey {
+                expected: MIN_BINDING_KEY_SIZE,
+                actual: binding_key.len(),
+            });
+        }
+        let mut mac = <Hmac<Sha3_256> as Mac>::new_from_slice(binding_key)
+            .expect("HMAC accepts any key length");
+        mac.update(b"B4AE-version-binding");
+        mac.update(&offered.to_bytes());
+        mac.update(&responder.to_bytes());
+        mac.update(&selected.to_bytes());
+        Ok(mac.finalize().into_bytes().into())
+    }
+}
+
+fn missing_extension(ext_type: u16) -> SecurityError {
+    SecurityError::SecurityInvariantViolation {
+        invariant: "Version-range response carries all negotiation extensions".to_string(),
+        details: format!("missing extension 0x{:04x}", ext_type),
+    }
+}
+
+fn push_extension(extensions: &mut Vec<u8>, ext_type: u16, data: &[u8]) {
+    extensions.extend_from_slice(&ext_type.to_be_bytes());
+    extensions.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    extensions.extend_from_slice(data);
+}
+
+/// Find the first extension of `ext_type` - explicit error on truncation
+fn find_extension(mut extensions: &[u8], ext_type: u16) -> SecurityResult<Option<&[u8]>> {
+    while !extensions.is_empty() {
+        let [t0, t1, l0, l1, rest @ ..] = extensions else {
+            return Err(SecurityError::BufferTooSmall { required: 4, available: extensions.len() });
+        };
+        let len = u16::from_be_bytes([*l0, *l1]) as usize;
+        let data = rest
+            .get(..len)
+            .ok_or(SecurityError::BufferTooSmall { required: len, available: rest.len() })?;
+        if u16::from_be_bytes([*t0, *t1]) == ext_type {
+            return Ok(Some(data));
+        }
+        extensions = &rest[len..];
+    }
+    Ok(None)
+}
+
 /// Handshake state machine implementation
+///
+/// Sees the whole exchange in order (an initiator feeds it the init it sent)
+/// and runs [`VersionNegotiation`] along the way: the init's offer is answered
+/// in [`process_init`](Self::process_init) and the response is confirmed
+/// against it in [`process_response`](Self::process_response). Later
+/// messages must carry the negotiated version.
+///
+/// The handshake secret keying the version binding is supplied with
+/// [`set_binding_key`](Self::set_binding_key) once it is derived: by the
+/// responder before [`version_extensions`](Self::version_extensions), by the
+/// initiator before `process_response`.
 pub struct SecurityHandshakeStateMachine {
     state: HandshakeState,
     max_message_size: usize,
+    local_versions: VersionRange,
+    offered_versions: Option<VersionRange>,
+    negotiated: Option<hardened_core::ProtocolVersion>,
+    binding_key: Option<Zeroizing<Vec<u8>>>,
 }
 
 impl SecurityHandshakeStateMachine {
@@ -357,9 +504,31 @@ impl SecurityHandshakeStateMachine {
         Ok(SecurityHandshakeStateMachine {
             state: HandshakeState::Init,
             max_message_size,
+            local_versions: VersionRange::supported(),
+            offered_versions: None,
+            negotiated: None,
+            binding_key: None,
         })
     }
     
+    /// Advertise `range` instead of [`VersionRange::supported`]
+    pub fn with_local_versions(mut self, range: VersionRange) -> Self {
+        self.local_versions = range;
+        self
+    }
+    
+    /// Key the version binding with secret material derived from the handshake
+    pub fn set_binding_key(&mut self, key: &[u8]) -> SecurityResult<()> {
+        if key.len() < MIN_BINDING_KEY_SIZE {
+            return Err(SecurityError::InvalidKey {
+                expected: MIN_BINDING_KEY_SIZE,
+                actual: key.len(),
+            });
+        }
+        self.binding_key = Some(Zeroizing::new(key.to_vec()));
+        Ok(())
+    }
+    
     /// Proses pesan HandshakeInit dan transisi ke state WaitingResponse
     pub fn process_init(&mut self, data: &[u8]) -> SecurityResult<SecurityHandshakeInit> {
         // Validate state transition
@@ -375,12 +544,15 @@ impl SecurityHandshakeStateMachine {
         }
         
         // Parse message
-        let mut buffer = SecurityBuffer::new(data.len())?;
-        buffer.write_slice(data)?;
-        buffer.set_position(0)?;
+        let mut buffer = SecretBuffer::from_slice(data)?;
         
         let init = SecurityHandshakeMessageParser::parse_init(&mut buffer)?;
         
+        // Answer the version offer; a responder sends the matching extensions
+        let offered = VersionNegotiation::offered_range(&init)?;
+        self.negotiated = Some(self.local_versions.negotiate(offered)?);
+        self.offered_versions = Some(offered);
+        
         // Update state
         self.state = HandshakeState::WaitingResponse;
         
@@ -402,12 +574,16 @@ impl SecurityHandshakeStateMachine {
         }
         
         // Parse message
-        let mut buffer = SecurityBuffer::new(data.len())?;
-        buffer.write_slice(data)?;
-        buffer.set_position(0)?;
+        let mut buffer = SecretBuffer::from_slice(data)?;
         
         let response = SecurityHandshakeMessageParser::parse_response(&mut buffer)?;
         
+        // Confirm the selection against the offer seen in process_init
+        let offered = self.offered_versions.ok_or_else(|| self.out_of_order(HandshakeState::WaitingComplete))?;
+        let negotiated = VersionNegotiation::confirm(offered, &response, self.binding_key()?)?;
+        self.check_version(response.version)?;
+        self.negotiated = Some(negotiated);
+        
         // Update state
         self.state = HandshakeState::WaitingComplete;
         
@@ -429,11 +605,10 @@ impl SecurityHandshakeStateMachine {
         }
         
         // Parse message
-        let mut buffer = SecurityBuffer::new(data.len())?;
-        buffer.write_slice(data)?;
-        buffer.set_position(0)?;
+        let mut buffer = SecretBuffer::from_slice(data)?;
         
         let complete = SecurityHandshakeMessageParser::parse_complete(&mut buffer)?;
+        self.check_version(complete.version)?;
         
         // Update state
         self.state = HandshakeState::Completed;
@@ -446,6 +621,53 @@ impl SecurityHandshakeStateMachine {
         self.state
     }
     
+    /// Version extensions for the HandshakeResponse answering the last init
+    pub fn version_extensions(&self) -> SecurityResult<Vec<u8>> {
+        let (Some(offered), Some(selected)) = (self.offered_versions, self.negotiated) else {
+            return Err(self.out_of_order(HandshakeState::WaitingResponse));
+        };
+        VersionNegotiation::response_extensions(self.local_versions, offered, selected, self.binding_key()?)
+    }
+    
+    /// Version negotiated so far (selected after init, confirmed after response)
+    pub fn negotiated_version(&self) -> Option<hardened_core::ProtocolVersion> {
+        self.negotiated
+    }
+    
+    /// Validate a message header against the negotiated version
+    pub fn validate_header(&self, header: &hardened_core::SecurityMessageHeader) -> SecurityResult<()> {
+        let negotiated = self.negotiated.ok_or_else(|| self.out_of_order(HandshakeState::Completed))?;
+        header.validate_negotiated(negotiated)
+    }
+    
+    /// Handshake secret set with [`set_binding_key`](Self::set_binding_key)
+    fn binding_key(&self) -> SecurityResult<&[u8]> {
+        self.binding_key.as_deref().map(Vec::as_slice).ok_or_else(|| SecurityError::SecurityInvariantViolation {
+            invariant: "Version binding is keyed with the handshake secret".to_string(),
+            details: "binding key not set".to_string(),
+        })
+    }
+    
+    /// Error for a message that needs an earlier step first
+    fn out_of_order(&self, to: HandshakeState) -> SecurityError {
+        SecurityError::InvalidStateTransition {
+            from: format!("{:?}", self.state),
+            to: format!("{:?}", to),
+        }
+    }
+    
+    /// Reject a handshake message whose version differs from the negotiated one
+    fn check_version(&self, version: ProtocolVersion) -> SecurityResult<()> {
+        match self.negotiated {
+            Some(negotiated) if negotiated.to_bytes() == version.to_bytes() => Ok(()),
+            Some(negotiated) => Err(SecurityError::ProtocolDowngrade {
+                expected: negotiated.as_u16(),
+                actual: u16::from_be_bytes(version.to_bytes()),
+            }),
+            None => Err(self.out_of_order(HandshakeState::Completed)),
+        }
+    }
+    
     /// Transisi ke state baru dengan validasi eksplisit
     pub fn transition_state(&mut self, new_state: HandshakeState) -> SecurityResult<()> {
         self.state.can_transition_to(new_state)?;
@@ -457,6 +679,9 @@ impl SecurityHandshakeStateMachine {
     pub fn reset(&mut self) -> SecurityResult<()> {
         self.state.can_transition_to(HandshakeState::Init)?;
         self.state = HandshakeState::Init;
+        self.offered_versions = None;
+        self.negotiated = None;
+        self.binding_key = None;
         Ok(())
     }
 }
@@ -465,9 +690,14 @@ impl SecurityHandshakeStateMachine {
 mod tests {
     use super::*;
     
+    /// Serialized bytes as the parsers receive them
+    fn secret(buffer: &SecurityBuffer) -> SecretBuffer {
+        SecretBuffer::from_slice(buffer.as_slice()).expect("Buffer creation should succeed")
+    }
+    
     #[test]
     fn test_hybrid_ciphertext_parsing() {
-        let mut buffer = SecurityBuffer::new(2000).expect("Buffer creation should succeed");
+        let mut buffer = SecretBuffer::new(2000).expect("Buffer creation should succeed");
         
         // Create test ciphertext
         let ecdh_public = vec![0u8; 32];
@@ -492,7 +722,7 @@ mod tests {
     
     #[test]
     fn test_hybrid_signature_parsing() {
-        let mut buffer = SecurityBuffer::new(5000).expect("Buffer creation should succeed");
+        let mut buffer = SecretBuffer::new(5000).expect("Buffer creation should succeed");
         
         // Create test signature
         let ecdsa_signature = vec![0u8; 64];
@@ -544,9 +774,60 @@ mod tests {
         assert_eq!(sm.current_state(), HandshakeState::WaitingResponse);
     }
     
+    #[test]
+    fn test_handshake_message_serialization_roundtrip() {
+        let ephemeral_keys = SecurityHybridCiphertext {
+            ecdh_ephemeral_public: vec![1u8; 32],
+            kyber_ciphertext: vec![2u8; KYBER_CIPHERTEXT_SIZE],
+        };
+        let signature = SecurityHybridSignature {
+            ecdsa_signature: vec![3u8; 64],
+            dilithium_signature: vec![4u8; DILITHIUM_SIGNATURE_SIZE],
+        };
+        
+        let init = SecurityHandshakeInit {
+            version: ProtocolVersion::V1_0,
+            cipher_suite: CipherSuite::HybridKyber1024X25519,
+            ephemeral_keys: ephemeral_keys.clone(),
+            timestamp: 1234567890,
+            extensions: vec![5u8; 10],
+        };
+        let mut buffer = SecurityBuffer::new(2000).expect("Buffer creation should succeed");
+        SecurityHandshakeMessageParser::serialize_init(&init, &mut buffer).expect("Serialize should succeed");
+        assert_eq!(SecurityHandshakeMessageParser::parse_init(&mut secret(&buffer)).expect("Parse should succeed"), init);
+        
+        let response = SecurityHandshakeResponse {
+            version: ProtocolVersion::V1_0,
+            cipher_suite: CipherSuite::HybridKyber1024X25519,
+            ephemeral_keys,
+            signature: signature.clone(),
+            timestamp: 1234567891,
+            extensions: Vec::new(),
+        };
+        let mut buffer = SecurityBuffer::new(8000).expect("Buffer creation should succeed");
+        SecurityHandshakeMessageParser::serialize_response(&response, &mut buffer).expect("Serialize should succeed");
+        assert_eq!(SecurityHandshakeMessageParser::parse_response(&mut secret(&buffer)).expect("Parse should succeed"), response);
+        
+        let complete = SecurityHandshakeComplete {
+            version: ProtocolVersion::V1_0,
+            signature,
+            timestamp: 1234567892,
+            extensions: vec![6u8; 3],
+        };
+        let mut buffer = SecurityBuffer::new(5000).expect("Buffer creation should succeed");
+        SecurityHandshakeMessageParser::serialize_complete(&complete, &mut buffer).expect("Serialize should succeed");
+        assert_eq!(SecurityHandshakeMessageParser::parse_complete(&mut secret(&buffer)).expect("Parse should succeed"), complete);
+        
+        // Oversized extensions are refused rather than truncated
+        let mut oversized = init;
+        oversized.extensions = vec![0u8; MAX_EXTENSIONS_SIZE + 1];
+        let mut buffer = SecurityBuffer::new(8000).expect("Buffer creation should succeed");
+        assert!(SecurityHandshakeMessageParser::serialize_init(&oversized, &mut buffer).is_err());
+    }
+    
     #[test]
     fn test_bounds_checking() {
-        let mut buffer = SecurityBuffer::new(100).expect("Buffer creation should succeed");
+        let mut buffer = SecretBuffer::new(100).expect("Buffer creation should succeed");
         
         // Write insufficient data for ECDH length
         buffer.write_u8(0x42).expect("Write should succeed");
@@ -568,4 +849,176 @@ mod tests {
         let result = sm.process_init(&oversized_data);
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+    
+    /// Handshake secret shared by the honest initiator and responder
+    const BINDING_KEY: [u8; MIN_BINDING_KEY_SIZE] = [0x5a; MIN_BINDING_KEY_SIZE];
+    
+    fn init_with(extensions: Vec<u8>) -> SecurityHandshakeInit {
+        SecurityHandshakeInit {
+            version: ProtocolVersion::V1_0,
+            cipher_suite: CipherSuite::HybridKyber1024X25519,
+            ephemeral_keys: SecurityHybridCiphertext {
+                ecdh_ephemeral_public: vec![1u8; 32],
+                kyber_ciphertext: vec![2u8; KYBER_CIPHERTEXT_SIZE],
+            },
+            timestamp: 1234567890,
+            extensions,
+        }
+    }
+    
+    fn response_with(extensions: Vec<u8>) -> SecurityHandshakeResponse {
+        let init = init_with(Vec::new());
+        SecurityHandshakeResponse {
+            version: ProtocolVersion::V1_0,
+            cipher_suite: init.cipher_suite,
+            ephemeral_keys: init.ephemeral_keys,
+            signature: SecurityHybridSignature {
+                ecdsa_signature: vec![3u8; 64],
+                dilithium_signature: vec![4u8; DILITHIUM_SIGNATURE_SIZE],
+            },
+            timestamp: 1234567891,
+            extensions,
+        }
+    }
+    
+    #[test]
+    fn test_version_negotiation() {
+        // Initiator from a future build that also speaks V1_0
+        let offered = VersionRange::new(0x0100, 0x0200).expect("Range should be valid");
+        let init = init_with(VersionNegotiation::offer(offered));
+        
+        let (selected, extensions) = VersionNegotiation::respond(VersionRange::supported(), &init, &BINDING_KEY)
+            .expect("Responder should negotiate");
+        assert_eq!(selected, hardened_core::ProtocolVersion::V1_0);
+        
+        // Extensions survive serialization
+        let mut buffer = SecurityBuffer::new(8000).expect("Buffer creation should succeed");
+        SecurityHandshakeMessageParser::serialize_response(&response_with(extensions), &mut buffer)
+            .expect("Serialize should succeed");
+        let response = SecurityHandshakeMessageParser::parse_response(&mut secret(&buffer)).expect("Parse should succeed");
+        assert_eq!(VersionNegotiation::confirm(offered, &response, &BINDING_KEY).expect("Initiator should confirm"), selected);
+        
+        // Legacy peers without the extension speak V1_0
+        let (legacy, _) = VersionNegotiation::respond(VersionRange::supported(), &init_with(Vec::new()), &BINDING_KEY)
+            .expect("Legacy initiator should negotiate");
+        assert_eq!(legacy, hardened_core::ProtocolVersion::V1_0);
+        assert_eq!(
+            VersionNegotiation::confirm(VersionRange::legacy(), &response_with(Vec::new()), &BINDING_KEY).expect("Legacy responder"),
+            hardened_core::ProtocolVersion::V1_0
+        );
+        
+        // Stripping the extensions from a response to a wider offer is a downgrade
+        let result = VersionNegotiation::confirm(offered, &response_with(Vec::new()), &BINDING_KEY);
+        assert!(matches!(result, Err(SecurityError::ProtocolDowngrade { expected: 0x0200, actual: 0x0100 })));
+    }
+    
+    #[test]
+    fn test_version_negotiation_no_overlap() {
+        let init = init_with(VersionNegotiation::offer(VersionRange::new(0x0200, 0x0300).unwrap()));
+        let result = VersionNegotiation::respond(VersionRange::supported(), &init, &BINDING_KEY);
+        assert!(matches!(result, Err(SecurityError::NoCommonProtocolVersion { .. })));
+        
+        // Truncated extensions are rejected, not read past
+        let init = init_with(vec![0x00, 0x01, 0x00, 0x04, 0x01]);
+        assert!(VersionNegotiation::respond(VersionRange::supported(), &init, &BINDING_KEY).is_err());
+    }
+    
+    #[test]
+    fn test_version_downgrade_detected() {
+        let offered = VersionRange::new(0x0100, 0x0200).unwrap();
+        let mut init = init_with(VersionNegotiation::offer(offered));
+        
+        // MITM strips the newer versions from the offer
+        init.extensions = VersionNegotiation::offer(VersionRange::legacy());
+        let (_, extensions) = VersionNegotiation::respond(VersionRange::supported(), &init, &BINDING_KEY).unwrap();
+        
+        let result = VersionNegotiation::confirm(offered, &response_with(extensions), &BINDING_KEY);
+        assert!(matches!(result, Err(SecurityError::ProtocolDowngrade { .. })));
+    }
+    
+    fn serialize_init(init: &SecurityHandshakeInit) -> Vec<u8> {
+        let mut buffer = SecurityBuffer::new(2000).expect("Buffer creation should succeed");
+        SecurityHandshakeMessageParser::serialize_init(init, &mut buffer).expect("Serialize should succeed");
+        buffer.as_slice().to_vec()
+    }
+    
+    fn serialize_response(response: &SecurityHandshakeResponse) -> Vec<u8> {
+        let mut buffer = SecurityBuffer::new(8000).expect("Buffer creation should succeed");
+        SecurityHandshakeMessageParser::serialize_response(response, &mut buffer).expect("Serialize should succeed");
+        buffer.as_slice().to_vec()
+    }
+    
+    #[test]
+    fn test_state_machine_negotiates_version() {
+        let offered = VersionRange::new(0x0100, 0x0200).unwrap();
+        let init = serialize_init(&init_with(VersionNegotiation::offer(offered)));
+        
+        // Responder answers the offer while processing the init
+        let mut responder = SecurityHandshakeStateMachine::new(16384).unwrap();
+        responder.process_init(&init).expect("Responder should negotiate");
+        assert_eq!(responder.negotiated_version(), Some(hardened_core::ProtocolVersion::V1_0));
+        assert!(responder.version_extensions().is_err(), "binding needs the handshake secret");
+        responder.set_binding_key(&BINDING_KEY).unwrap();
+        let response = serialize_response(&response_with(responder.version_extensions().unwrap()));
+        
+        // Initiator confirms it against the offer it sent
+        let mut initiator = SecurityHandshakeStateMachine::new(16384).unwrap().with_local_versions(offered);
+        initiator.process_init(&init).expect("Initiator should record its offer");
+        assert!(initiator.process_response(&response).is_err(), "binding needs the handshake secret");
+        initiator.set_binding_key(&BINDING_KEY).unwrap();
+        initiator.process_response(&response).expect("Initiator should confirm");
+        assert_eq!(initiator.negotiated_version(), Some(hardened_core::ProtocolVersion::V1_0));
+        
+        assert!(initiator.set_binding_key(&BINDING_KEY[..MIN_BINDING_KEY_SIZE - 1]).is_err());
+    }
+    
+    #[test]
+    fn test_state_machine_detects_downgrade() {
+        let offered = VersionRange::new(0x0100, 0x0200).unwrap();
+        let sent = serialize_init(&init_with(VersionNegotiation::offer(offered)));
+        
+        // MITM narrows the offer on the way to the responder
+        let tampered = serialize_init(&init_with(VersionNegotiation::offer(VersionRange::legacy())));
+        let mut responder = SecurityHandshakeStateMachine::new(16384).unwrap();
+        responder.process_init(&tampered).unwrap();
+        responder.set_binding_key(&BINDING_KEY).unwrap();
+        let response = serialize_response(&response_with(responder.version_extensions().unwrap()));
+        
+        let mut initiator = SecurityHandshakeStateMachine::new(16384).unwrap().with_local_versions(offered);
+        initiator.process_init(&sent).unwrap();
+        initiator.set_binding_key(&BINDING_KEY).unwrap();
+        let result = initiator.process_response(&response);
+        assert!(matches!(result, Err(SecurityError::ProtocolDowngrade { .. })));
+        assert_eq!(initiator.current_state(), HandshakeState::WaitingResponse);
+        
+        // Stripping the negotiation extensions is caught as well
+        let stripped = serialize_response(&response_with(Vec::new()));
+        assert!(matches!(initiator.process_response(&stripped), Err(SecurityError::ProtocolDowngrade { .. })));
+    }
+    
+    #[test]
+    fn test_recomputed_binding_rejected() {
+        // Only V1_0 exists today, so model a future initiator whose offer a
+        // MITM narrows to force the responder onto V1_0
+        let offered = VersionRange::new(0x0100, 0x0200).unwrap();
+        let responder_range = VersionRange::new(0x0100, 0x0200).unwrap();
+        let init = init_with(VersionNegotiation::offer(VersionRange::legacy()));
+        let (selected, _) = VersionNegotiation::respond(responder_range, &init, &BINDING_KEY).unwrap();
+        assert_eq!(selected, hardened_core::ProtocolVersion::V1_0);
+        
+        // The MITM rewrites the response to look consistent with the real
+        // offer: it advertises a V1_0-only responder and recomputes the
+        // binding, but only under a key of its own
+        let attacker_key = [0xa7; MIN_BINDING_KEY_SIZE];
+        let forged = VersionNegotiation::response_extensions(VersionRange::legacy(), offered, selected, &attacker_key)
+            .unwrap();
+        let result = VersionNegotiation::confirm(offered, &response_with(forged), &BINDING_KEY);
+        assert!(matches!(result, Err(SecurityError::ProtocolDowngrade { .. })));
+        
+        // The identical rewrite under the real handshake secret would pass,
+        // so the key is what stops it
+        let keyed = VersionNegotiation::response_extensions(VersionRange::legacy(), offered, selected, &BINDING_KEY)
+            .unwrap();
+        assert_eq!(VersionNegotiation::confirm(offered, &response_with(keyed), &BINDING_KEY).unwrap(), selected);
+    }
+}
@@ -10,8 +10,9 @@
 
 use std::convert::TryFrom;
 use std::num::TryFromIntError;
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 use subtle::ConstantTimeEq;
+use crate::crypto::dilithium::DilithiumVariant;
 
 /// Security-hardened error types - no panic propagation
 #[derive(Debug, Clone, PartialEq)]
@@ -39,6 +40,24 @@ pub enum SecurityError {
         /// Versi aktual yang diterima
         actual: u16,
     },
+    /// Peers advertised protocol version ranges with no version in common
+    NoCommonProtocolVersion {
+        /// Versi minimum lokal
+        local_min: u16,
+        /// Versi maksimum lokal
+        local_max: u16,
+        /// Versi minimum peer
+        peer_min: u16,
+        /// Versi maksimum peer
+        peer_max: u16,
+    },
+    /// Version negotiation was tampered with
+    ProtocolDowngrade {
+        /// Versi yang seharusnya dipilih
+        expected: u16,
+        /// Versi yang dipilih peer
+        actual: u16,
+    },
     /// Invalid message type
     InvalidMessageType(u8),
     /// Invalid cipher suite
@@ -394,6 +413,16 @@ impl std::fmt::Display for SecurityError {
             SecurityError::InvalidProtocolVersion { expected, actual } => {
                 write!(f, "Invalid protocol version: expected {}, actual {}", expected, actual)
             }
+            SecurityError::NoCommonProtocolVersion { local_min, local_max, peer_min, peer_max } => {
+                write!(
+                    f,
+                    "No common protocol version: local 0x{:04x}-0x{:04x}, peer 0x{:04x}-0x{:04x}",
+                    local_min, local_max, peer_min, peer_max
+                )
+            }
+            SecurityError::ProtocolDowngrade { expected, actual } => {
+                write!(f, "Protocol downgrade detected: expected 0x{:04x}, actual 0x{:04x}", expected, actual)
+            }
             SecurityError::InvalidMessageType(t) => {
                 write!(f, "Invalid message type: {}", t)
             }
@@ -600,6 +629,10 @@ pub enum ProtocolVersion {
 impl ProtocolVersion {
     /// Versi protokol yang sedang digunakan
     pub const CURRENT: Self = Self::V1_0;
+    /// Versi tertua yang masih didukung
+    pub const MIN_SUPPORTED: Self = Self::V1_0;
+    /// Semua versi yang didukung, dari yang tertua
+    pub const SUPPORTED: &'static [Self] = &[Self::V1_0];
     /// Ukuran representasi bytes versi protokol
     pub const BYTES: usize = 2;
     
@@ -627,6 +660,89 @@ impl ProtocolVersion {
             ProtocolVersion::V1_0 => 0x0100,
         }
     }
+
+    /// Convert from u16 - unknown (including newer) versions are an error
+    pub fn from_u16(value: u16) -> SecurityResult<Self> {
+        Self::from_bytes(value.to_be_bytes())
+    }
+}
+
+/// Range of protocol versions a peer speaks, advertised during the handshake
+///
+/// Versions are compared as `u16` (`major << 8 | minor`), so a peer may
+/// advertise versions newer than this build knows; [`negotiate`](Self::negotiate)
+/// only ever selects one of [`ProtocolVersion::SUPPORTED`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VersionRange {
+    /// Versi minimum yang didukung
+    pub min: u16,
+    /// Versi maksimum yang didukung
+    pub max: u16,
+}
+
+impl VersionRange {
+    /// Ukuran representasi bytes (`min || max`)
+    pub const BYTES: usize = 4;
+
+    /// Range `min..=max` - explicit error if empty
+    pub fn new(min: u16, max: u16) -> SecurityResult<Self> {
+        if min > max {
+            return Err(SecurityError::SecurityInvariantViolation {
+                invariant: "Version range min <= max".to_string(),
+                details: format!("min=0x{:04x}, max=0x{:04x}", min, max),
+            });
+        }
+        Ok(VersionRange { min, max })
+    }
+
+    /// Versions this build speaks
+    pub fn supported() -> Self {
+        VersionRange {
+            min: ProtocolVersion::MIN_SUPPORTED.as_u16(),
+            max: ProtocolVersion::CURRENT.as_u16(),
+        }
+    }
+
+    /// Range of a peer that does not advertise one (speaks only V1_0)
+    pub fn legacy() -> Self {
+        let v1_0 = ProtocolVersion::V1_0.as_u16();
+        VersionRange { min: v1_0, max: v1_0 }
+    }
+
+    /// Whether `version` lies in the range
+    pub fn contains(self, version: u16) -> bool {
+        (self.min..=self.max).contains(&version)
+    }
+
+    /// Convert to bytes (`min || max`, big-endian)
+    pub fn to_bytes(self) -> [u8; 4] {
+        let [a, b] = self.min.to_be_bytes();
+        let [c, d] = self.max.to_be_bytes();
+        [a, b, c, d]
+    }
+
+    /// Convert from bytes - explicit length and ordering checks
+    pub fn from_bytes(bytes: &[u8]) -> SecurityResult<Self> {
+        match bytes {
+            [a, b, c, d] => Self::new(u16::from_be_bytes([*a, *b]), u16::from_be_bytes([*c, *d])),
+            _ => Err(SecurityError::InvalidLength { expected: Self::BYTES, actual: bytes.len() }),
+        }
+    }
+
+    /// Highest supported version in both ranges
+    pub fn negotiate(self, peer: VersionRange) -> SecurityResult<ProtocolVersion> {
+        ProtocolVersion::SUPPORTED
+            .iter()
+            .rev()
+            .copied()
+            .find(|v| self.contains(v.as_u16()) && peer.contains(v.as_u16()))
+            .ok_or(SecurityError::NoCommonProtocolVersion {
+                local_min: self.min,
+                local_max: self.max,
+                peer_min: peer.min,
+                peer_max: peer.max,
+            })
+    }
 }
 
 /// Deterministic message type - explicit enum, no implicit values
@@ -698,6 +814,14 @@ impl CipherSuite {
             CipherSuite::Maximum => SecurityLevel::Maximum,
         }
     }
+
+    /// Get Dilithium parameter set - explicit mapping
+    pub fn dilithium_variant(self) -> DilithiumVariant {
+        match self {
+            CipherSuite::Standard => DilithiumVariant::Dilithium3,
+            CipherSuite::High | CipherSuite::Maximum => DilithiumVariant::Dilithium5,
+        }
+    }
 }
 
 /// Security levels - explicit enum
@@ -844,19 +968,7 @@ impl SecurityBuffer {
     
     /// Read exact N bytes - explicit bounds checking, no panic
     pub fn read_exact(&mut self, n: usize) -> SecurityResult<&[u8]> {
-        if !self.can_read(n) {
-            return Err(SecurityError::BufferTooSmall {
-                required: n,
-                available: self.remaining(),
-            });
-        }
-        
-        let start = self.read_pos;
-        let end = start.checked_add(n).ok_or(SecurityError::ArithmeticOverflowProtection {
-            operation: "read_exact".to_string(),
-            values: format!("start={}, n={}", start, n),
-        })?;
-        
+        let (start, end) = read_range(&self.data, self.read_pos, n)?;
         self.read_pos = end;
         Ok(&self.data[start..end])
     }
@@ -888,6 +1000,27 @@ impl SecurityBuffer {
         ]))
     }
     
+    /// Read u16 length prefix and that many bytes - explicit bounds checking, no panic
+    ///
+    /// The declared length must not exceed `max` nor the remaining bytes;
+    /// `resource` names the field in the resulting
+    /// [`ResourceExhaustionProtection`](SecurityError::ResourceExhaustionProtection).
+    /// On error the read position is left unchanged.
+    pub fn read_len_prefixed_u16(&mut self, resource: &str, max: usize) -> SecurityResult<&[u8]> {
+        let (start, end) = len_prefixed_range(&self.data, self.read_pos, 2, resource, max)?;
+        self.read_pos = end;
+        Ok(&self.data[start..end])
+    }
+    
+    /// Read u32 length prefix and that many bytes - explicit bounds checking, no panic
+    ///
+    /// Same contract as [`read_len_prefixed_u16`](Self::read_len_prefixed_u16).
+    pub fn read_len_prefixed_u32(&mut self, resource: &str, max: usize) -> SecurityResult<&[u8]> {
+        let (start, end) = len_prefixed_range(&self.data, self.read_pos, 4, resource, max)?;
+        self.read_pos = end;
+        Ok(&self.data[start..end])
+    }
+    
     /// Write u8 - explicit bounds checking, no panic
     pub fn write_u8(&mut self, value: u8) -> SecurityResult<()> {
         self.data.push(value);
@@ -940,6 +1073,39 @@ impl SecurityBuffer {
         Ok(())
     }
     
+    /// Write u16 length prefix followed by `field` - explicit bounds checking, no panic
+    ///
+    /// Nothing is written if `field` is longer than `u16::MAX` or the buffer
+    /// limit would be exceeded.
+    pub fn write_len_prefixed_u16(&mut self, field: &[u8]) -> SecurityResult<()> {
+        let len = u16::try_from(field.len()).map_err(SecurityError::IntegerOverflow)?;
+        self.ensure_writable(checked_add_security(2, field.len())?)?;
+        self.write_u16_be(len)?;
+        self.write_slice(field)
+    }
+    
+    /// Write u32 length prefix followed by `field` - explicit bounds checking, no panic
+    ///
+    /// Same contract as [`write_len_prefixed_u16`](Self::write_len_prefixed_u16).
+    pub fn write_len_prefixed_u32(&mut self, field: &[u8]) -> SecurityResult<()> {
+        let len = u32::try_from(field.len()).map_err(SecurityError::IntegerOverflow)?;
+        self.ensure_writable(checked_add_security(4, field.len())?)?;
+        self.write_u32_be(len)?;
+        self.write_slice(field)
+    }
+    
+    /// Check that `additional` bytes fit within MAX_BUFFER_SIZE
+    fn ensure_writable(&self, additional: usize) -> SecurityResult<()> {
+        let new_len = checked_add_security(self.data.len(), additional)?;
+        if new_len > MAX_BUFFER_SIZE {
+            return Err(SecurityError::BufferOverflowProtection {
+                size: new_len,
+                capacity: MAX_BUFFER_SIZE,
+            });
+        }
+        Ok(())
+    }
+    
     /// Get current position - no panic
     pub fn position(&self) -> usize {
         self.read_pos
@@ -997,6 +1163,286 @@ impl SecurityBuffer {
     }
 }
 
+/// Locate `n` bytes at `pos` without consuming them
+fn read_range(data: &[u8], pos: usize, n: usize) -> SecurityResult<(usize, usize)> {
+    let available = data.len().saturating_sub(pos);
+    if available < n {
+        return Err(SecurityError::BufferTooSmall {
+            required: n,
+            available,
+        });
+    }
+    
+    let end = pos.checked_add(n).ok_or(SecurityError::ArithmeticOverflowProtection {
+        operation: "read_exact".to_string(),
+        values: format!("start={}, n={}", pos, n),
+    })?;
+    Ok((pos, end))
+}
+
+/// Locate a length-prefixed field at `pos` without consuming it
+fn len_prefixed_range(
+    data: &[u8],
+    pos: usize,
+    prefix_size: usize,
+    resource: &str,
+    max: usize,
+) -> SecurityResult<(usize, usize)> {
+    let (prefix_start, start) = read_range(data, pos, prefix_size)?;
+    let len = data[prefix_start..start]
+        .iter()
+        .fold(0usize, |acc, &byte| (acc << 8) | byte as usize);
+    
+    if len > max {
+        return Err(SecurityError::ResourceExhaustionProtection {
+            resource: resource.to_string(),
+            limit: max,
+            requested: len,
+        });
+    }
+    
+    let end = checked_add_security(start, len)?;
+    if end > data.len() {
+        return Err(SecurityError::BufferTooSmall {
+            required: len,
+            available: data.len() - start,
+        });
+    }
+    
+    Ok((start, end))
+}
+
+/// Zeroizing buffer for key material - same bounds-checked API as [`SecurityBuffer`]
+///
+/// The contents are wiped on drop and on [`clear`](Self::clear). The capacity
+/// is fixed at construction: a write that would exceed it fails instead of
+/// reallocating, so no unzeroized copy of the secret is left on the heap.
+pub struct SecretBuffer {
+    data: Zeroizing<Vec<u8>>,
+    read_pos: usize,
+}
+
+impl SecretBuffer {
+    /// Create new buffer - explicit capacity validation
+    pub fn new(capacity: usize) -> SecurityResult<Self> {
+        if capacity > MAX_BUFFER_SIZE {
+            return Err(SecurityError::BufferOverflowProtection {
+                size: capacity,
+                capacity: MAX_BUFFER_SIZE,
+            });
+        }
+        
+        Ok(SecretBuffer {
+            data: Zeroizing::new(Vec::with_capacity(capacity)),
+            read_pos: 0,
+        })
+    }
+    
+    /// Create from slice - explicit validation; capacity equals `slice.len()`
+    pub fn from_slice(slice: &[u8]) -> SecurityResult<Self> {
+        let mut buffer = Self::new(slice.len())?;
+        buffer.write_slice(slice)?;
+        Ok(buffer)
+    }
+    
+    /// Get remaining bytes - bounds checked, no panic
+    pub fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.read_pos)
+    }
+    
+    /// Check if can read N bytes - bounds checked, no panic
+    pub fn can_read(&self, n: usize) -> bool {
+        self.remaining() >= n
+    }
+    
+    /// Read exact N bytes - explicit bounds checking, no panic
+    pub fn read_exact(&mut self, n: usize) -> SecurityResult<&[u8]> {
+        let (start, end) = read_range(&self.data, self.read_pos, n)?;
+        self.read_pos = end;
+        Ok(&self.data[start..end])
+    }
+    
+    /// Read u8 - explicit bounds checking, no panic
+    pub fn read_u8(&mut self) -> SecurityResult<u8> {
+        let bytes = self.read_exact(1)?;
+        Ok(bytes[0])
+    }
+    
+    /// Read u16 (big-endian) - explicit bounds checking, no panic
+    pub fn read_u16_be(&mut self) -> SecurityResult<u16> {
+        let bytes = self.read_exact(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+    
+    /// Read u32 (big-endian) - explicit bounds checking, no panic
+    pub fn read_u32_be(&mut self) -> SecurityResult<u32> {
+        let bytes = self.read_exact(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+    
+    /// Read i64 (big-endian) - explicit bounds checking, no panic
+    pub fn read_i64_be(&mut self) -> SecurityResult<i64> {
+        let bytes = self.read_exact(8)?;
+        Ok(i64::from_be_bytes([
+            bytes[0], bytes[1], bytes[2], bytes[3],
+            bytes[4], bytes[5], bytes[6], bytes[7],
+        ]))
+    }
+    
+    /// Read u16 length prefix and that many bytes - explicit bounds checking, no panic
+    ///
+    /// Same contract as [`SecurityBuffer::read_len_prefixed_u16`].
+    pub fn read_len_prefixed_u16(&mut self, resource: &str, max: usize) -> SecurityResult<&[u8]> {
+        let (start, end) = len_prefixed_range(&self.data, self.read_pos, 2, resource, max)?;
+        self.read_pos = end;
+        Ok(&self.data[start..end])
+    }
+    
+    /// Read u32 length prefix and that many bytes - explicit bounds checking, no panic
+    ///
+    /// Same contract as [`SecurityBuffer::read_len_prefixed_u32`].
+    pub fn read_len_prefixed_u32(&mut self, resource: &str, max: usize) -> SecurityResult<&[u8]> {
+        let (start, end) = len_prefixed_range(&self.data, self.read_pos, 4, resource, max)?;
+        self.read_pos = end;
+        Ok(&self.data[start..end])
+    }
+    
+    /// Write u8 - explicit bounds checking, no panic
+    pub fn write_u8(&mut self, value: u8) -> SecurityResult<()> {
+        self.write_slice(&[value])
+    }
+    
+    /// Write u16 (big-endian) - explicit bounds checking, no panic
+    pub fn write_u16_be(&mut self, value: u16) -> SecurityResult<()> {
+        self.write_slice(&value.to_be_bytes())
+    }
+    
+    /// Write u32 (big-endian) - explicit bounds checking, no panic
+    pub fn write_u32_be(&mut self, value: u32) -> SecurityResult<()> {
+        self.write_slice(&value.to_be_bytes())
+    }
+    
+    /// Write u64 (big-endian) - explicit bounds checking, no panic
+    pub fn write_u64_be(&mut self, value: u64) -> SecurityResult<()> {
+        self.write_slice(&value.to_be_bytes())
+    }
+    
+    /// Write i64 (big-endian) - explicit bounds checking, no panic
+    pub fn write_i64_be(&mut self, value: i64) -> SecurityResult<()> {
+        self.write_slice(&value.to_be_bytes())
+    }
+    
+    /// Write slice - explicit bounds checking against the fixed capacity, no panic
+    pub fn write_slice(&mut self, slice: &[u8]) -> SecurityResult<()> {
+        self.ensure_writable(slice.len())?;
+        self.data.extend_from_slice(slice);
+        Ok(())
+    }
+    
+    /// Write u16 length prefix followed by `field` - explicit bounds checking, no panic
+    ///
+    /// Nothing is written if `field` is longer than `u16::MAX` or does not fit.
+    pub fn write_len_prefixed_u16(&mut self, field: &[u8]) -> SecurityResult<()> {
+        let len = u16::try_from(field.len()).map_err(SecurityError::IntegerOverflow)?;
+        self.ensure_writable(checked_add_security(2, field.len())?)?;
+        self.write_u16_be(len)?;
+        self.write_slice(field)
+    }
+    
+    /// Write u32 length prefix followed by `field` - explicit bounds checking, no panic
+    ///
+    /// Same contract as [`write_len_prefixed_u16`](Self::write_len_prefixed_u16).
+    pub fn write_len_prefixed_u32(&mut self, field: &[u8]) -> SecurityResult<()> {
+        let len = u32::try_from(field.len()).map_err(SecurityError::IntegerOverflow)?;
+        self.ensure_writable(checked_add_security(4, field.len())?)?;
+        self.write_u32_be(len)?;
+        self.write_slice(field)
+    }
+    
+    /// Check that `additional` bytes fit without reallocating
+    fn ensure_writable(&self, additional: usize) -> SecurityResult<()> {
+        let new_len = checked_add_security(self.data.len(), additional)?;
+        if new_len > self.data.capacity() {
+            return Err(SecurityError::BufferOverflowProtection {
+                size: new_len,
+                capacity: self.data.capacity(),
+            });
+        }
+        Ok(())
+    }
+    
+    /// Get current position - no panic
+    pub fn position(&self) -> usize {
+        self.read_pos
+    }
+    
+    /// Set position - explicit bounds checking, no panic
+    pub fn set_position(&mut self, pos: usize) -> SecurityResult<()> {
+        if pos > self.data.len() {
+            return Err(SecurityError::BufferOverflowProtection {
+                size: pos,
+                capacity: self.data.len(),
+            });
+        }
+        
+        self.read_pos = pos;
+        Ok(())
+    }
+    
+    /// Get data - no panic
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    
+    /// Get length - no panic
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    
+    /// Check if empty - no panic
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    
+    /// Get capacity - no panic
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+    
+    /// Get as slice - no panic
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+    
+    /// Clear buffer - zeroizes the contents, keeps the capacity
+    pub fn clear(&mut self) -> SecurityResult<()> {
+        self.zeroize();
+        Ok(())
+    }
+    
+    /// Clear buffer - zeroize sensitive data
+    pub fn zeroize(&mut self) {
+        // Vec::zeroize wipes the whole allocation, including spare capacity
+        self.data.zeroize();
+        self.read_pos = 0;
+    }
+}
+
+impl std::fmt::Debug for SecretBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretBuffer")
+            .field("len", &self.data.len())
+            .field("read_pos", &self.read_pos)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for SecretBuffer {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
 /// Maximum buffer size to prevent DoS attacks
 const MAX_BUFFER_SIZE: usize = 64 * 1024 * 1024; // 64MB
 
@@ -1705,11 +2151,11 @@ impl SecurityMessageHeader {
     
     /// Validate header invariants - comprehensive security checks
     pub fn validate_security(&self) -> SecurityResult<()> {
-        // Validate protocol version is current
-        if self.protocol_version != ProtocolVersion::CURRENT {
+        // Validate protocol version is one this build speaks
+        if !ProtocolVersion::SUPPORTED.contains(&self.protocol_version) {
             return Err(SecurityError::SecurityInvariantViolation {
-                invariant: "Protocol version must be current".to_string(),
-                details: format!("expected {:?}, actual {:?}", ProtocolVersion::CURRENT, self.protocol_version),
+                invariant: "Protocol version must be supported".to_string(),
+                details: format!("supported {:?}, actual {:?}", ProtocolVersion::SUPPORTED, self.protocol_version),
             });
         }
         
@@ -1751,6 +2197,21 @@ impl SecurityMessageHeader {
     }
 }
 
+impl SecurityMessageHeader {
+    /// Validate header invariants and that the header carries the version
+    /// negotiated for the session
+    pub fn validate_negotiated(&self, negotiated: ProtocolVersion) -> SecurityResult<()> {
+        self.validate_security()?;
+        if self.protocol_version != negotiated {
+            return Err(SecurityError::ProtocolDowngrade {
+                expected: negotiated.as_u16(),
+                actual: self.protocol_version.as_u16(),
+            });
+        }
+        Ok(())
+    }
+}
+
 /// Get current timestamp - security-hardened
 fn current_timestamp() -> i64 {
     // Use std::time for deterministic timestamp
@@ -1777,6 +2238,47 @@ const MAX_TIMESTAMP_DRIFT: i64 = 5 * 60; // 5 minutes in seconds
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+    
+    /// Address of the allocation whose contents should be checked when freed
+    static WATCHED_ALLOCATION: AtomicUsize = AtomicUsize::new(0);
+    /// 0 = not freed yet, 1 = all zero when freed, 2 = non-zero bytes when freed
+    static WATCHED_OUTCOME: AtomicU8 = AtomicU8::new(0);
+    
+    /// Test allocator: inspects the watched allocation right before it is freed
+    struct WipeCheckingAllocator;
+    
+    unsafe impl GlobalAlloc for WipeCheckingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            System.alloc(layout)
+        }
+        
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            let watched = WATCHED_ALLOCATION
+                .compare_exchange(ptr as usize, 0, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok();
+            if watched {
+                // Still allocated here, so the whole block is readable
+                let contents = std::slice::from_raw_parts(ptr, layout.size());
+                let outcome = if contents.iter().all(|&byte| byte == 0) { 1 } else { 2 };
+                WATCHED_OUTCOME.store(outcome, Ordering::SeqCst);
+            }
+            System.dealloc(ptr, layout)
+        }
+    }
+    
+    #[global_allocator]
+    static ALLOCATOR: WipeCheckingAllocator = WipeCheckingAllocator;
+    
+    /// Drop `value` and report how the heap block at `ptr` looked when it was freed
+    fn contents_when_freed<T>(ptr: *const u8, value: T) -> u8 {
+        WATCHED_OUTCOME.store(0, Ordering::SeqCst);
+        WATCHED_ALLOCATION.store(ptr as usize, Ordering::SeqCst);
+        drop(value);
+        WATCHED_ALLOCATION.store(0, Ordering::SeqCst);
+        WATCHED_OUTCOME.load(Ordering::SeqCst)
+    }
     
     #[test]
     fn test_protocol_version_bytes() {
@@ -1873,6 +2375,134 @@ mod tests {
         assert!(result.is_err());
     }
     
+    #[test]
+    fn test_len_prefixed_roundtrip() {
+        let mut buffer = SecurityBuffer::new(64).expect("Buffer creation should succeed");
+        buffer.write_len_prefixed_u16(b"extension").expect("Write should succeed");
+        buffer.write_len_prefixed_u32(b"signature").expect("Write should succeed");
+        buffer.write_len_prefixed_u16(&[]).expect("Write should succeed");
+        assert_eq!(buffer.len(), 2 + 9 + 4 + 9 + 2);
+        
+        assert_eq!(buffer.read_len_prefixed_u16("test_field", 16).expect("Read should succeed"), b"extension");
+        assert_eq!(buffer.read_len_prefixed_u32("test_field", 16).expect("Read should succeed"), b"signature");
+        
+        // Zero-length field is valid even with a zero maximum
+        assert!(buffer.read_len_prefixed_u16("test_field", 0).expect("Read should succeed").is_empty());
+        assert_eq!(buffer.remaining(), 0);
+        
+        // No field at all: the prefix itself is missing
+        assert!(matches!(buffer.read_len_prefixed_u16("test_field", 16), Err(SecurityError::BufferTooSmall { required: 2, .. })));
+    }
+    
+    #[test]
+    fn test_len_prefixed_rejects_bad_lengths() {
+        // Declared length exceeds the remaining bytes
+        let mut buffer = SecurityBuffer::from_slice(&[0x00, 0x10, 1, 2, 3]).expect("Buffer creation should succeed");
+        assert!(matches!(
+            buffer.read_len_prefixed_u16("test_field", 1024),
+            Err(SecurityError::BufferTooSmall { required: 16, available: 3 })
+        ));
+        assert_eq!(buffer.position(), 0);
+        
+        // Declared length exceeds the caller's maximum
+        let mut buffer = SecurityBuffer::from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]).expect("Buffer creation should succeed");
+        assert!(matches!(
+            buffer.read_len_prefixed_u32("signature_length", 4096),
+            Err(SecurityError::ResourceExhaustionProtection { ref resource, limit: 4096, .. }) if resource == "signature_length"
+        ));
+        assert_eq!(buffer.position(), 0);
+        
+        // Field too long for its prefix writes nothing
+        let mut buffer = SecurityBuffer::new(0).expect("Buffer creation should succeed");
+        assert!(buffer.write_len_prefixed_u16(&vec![0u8; u16::MAX as usize + 1]).is_err());
+        assert!(buffer.is_empty());
+    }
+    
+    #[test]
+    fn test_secret_buffer_api_parity() {
+        macro_rules! write_fields {
+            ($buffer:expr) => {{
+                $buffer.write_u8(0x42).expect("Write u8 should succeed");
+                $buffer.write_u16_be(0x1234).expect("Write u16 should succeed");
+                $buffer.write_u32_be(0xDEAD_BEEF).expect("Write u32 should succeed");
+                $buffer.write_u64_be(7).expect("Write u64 should succeed");
+                $buffer.write_i64_be(-7).expect("Write i64 should succeed");
+                $buffer.write_len_prefixed_u16(b"key").expect("Write should succeed");
+                $buffer.write_len_prefixed_u32(b"secret").expect("Write should succeed");
+            }};
+        }
+        
+        // Same calls produce the same bytes as SecurityBuffer
+        let mut secret = SecretBuffer::new(64).expect("Buffer creation should succeed");
+        let mut plain = SecurityBuffer::new(64).expect("Buffer creation should succeed");
+        write_fields!(secret);
+        write_fields!(plain);
+        assert_eq!(secret.as_slice(), plain.as_slice());
+        
+        assert_eq!(secret.read_u8().expect("Read u8 should succeed"), 0x42);
+        assert_eq!(secret.read_u16_be().expect("Read u16 should succeed"), 0x1234);
+        assert_eq!(secret.read_u32_be().expect("Read u32 should succeed"), 0xDEAD_BEEF);
+        assert_eq!(secret.read_exact(8).expect("Read should succeed"), &7u64.to_be_bytes());
+        assert_eq!(secret.read_i64_be().expect("Read i64 should succeed"), -7);
+        assert_eq!(secret.read_len_prefixed_u16("test_field", 16).expect("Read should succeed"), b"key");
+        assert!(matches!(
+            secret.read_len_prefixed_u32("test_field", 4),
+            Err(SecurityError::ResourceExhaustionProtection { limit: 4, .. })
+        ));
+        assert_eq!(secret.read_len_prefixed_u32("test_field", 16).expect("Read should succeed"), b"secret");
+        assert_eq!(secret.remaining(), 0);
+        assert!(secret.read_exact(1).is_err());
+        
+        secret.set_position(1).expect("Set position should succeed");
+        assert_eq!(secret.read_u16_be().expect("Read u16 should succeed"), 0x1234);
+        assert!(secret.set_position(secret.len() + 1).is_err());
+        
+        secret.clear().expect("Clear should succeed");
+        assert!(secret.is_empty());
+        assert_eq!(secret.position(), 0);
+    }
+    
+    #[test]
+    fn test_secret_buffer_capacity_is_fixed() {
+        assert!(SecretBuffer::new(MAX_BUFFER_SIZE + 1).is_err());
+        
+        let mut buffer = SecretBuffer::new(4).expect("Buffer creation should succeed");
+        buffer.write_u32_be(1).expect("Write should succeed");
+        let capacity = buffer.capacity();
+        assert!(matches!(
+            buffer.write_u8(0),
+            Err(SecurityError::BufferOverflowProtection { size: 5, .. })
+        ));
+        assert!(buffer.write_len_prefixed_u16(&[]).is_err());
+        assert_eq!(buffer.len(), 4);
+        assert_eq!(buffer.capacity(), capacity);
+        
+        let buffer = SecretBuffer::from_slice(&[0x11; 32]).expect("Buffer creation should succeed");
+        assert_eq!(buffer.data(), &[0x11; 32]);
+        assert_eq!(format!("{:?}", buffer), "SecretBuffer { len: 32, read_pos: 0, .. }");
+    }
+    
+    #[test]
+    fn test_secret_buffer_zeroizes_on_drop() {
+        // Full capacity, partially read: every byte of the block must be wiped
+        let mut buffer = SecretBuffer::new(32).expect("Buffer creation should succeed");
+        buffer.write_slice(&[0xA5; 32]).expect("Write should succeed");
+        buffer.read_exact(16).expect("Read should succeed");
+        let ptr = buffer.as_slice().as_ptr();
+        assert_eq!(contents_when_freed(ptr, buffer), 1);
+        
+        // Spare capacity is wiped too
+        let mut buffer = SecretBuffer::new(64).expect("Buffer creation should succeed");
+        buffer.write_slice(&[0x5A; 8]).expect("Write should succeed");
+        let ptr = buffer.as_slice().as_ptr();
+        assert_eq!(contents_when_freed(ptr, buffer), 1);
+        
+        // The allocator hook does see secrets left behind by a plain Vec
+        let plain = vec![0xA5u8; 32];
+        let ptr = plain.as_ptr();
+        assert_eq!(contents_when_freed(ptr, plain), 2);
+    }
+    
     #[test]
     fn test_integer_conversion_overflow_protection() {
         let large_value = usize::MAX;
@@ -1977,4 +2607,68 @@ mod tests {
         let result = parsed.validate_security();
         assert!(result.is_err());
     }
+    
+    #[test]
+    fn test_version_range_negotiation() {
+        let local = VersionRange::supported();
+        assert_eq!(local.negotiate(local).unwrap(), ProtocolVersion::CURRENT);
+        
+        // A newer peer that still speaks V1_0 settles on V1_0
+        let newer_peer = VersionRange::new(0x0100, 0x0300).unwrap();
+        assert_eq!(local.negotiate(newer_peer).unwrap(), ProtocolVersion::V1_0);
+        assert_eq!(newer_peer.negotiate(local).unwrap(), ProtocolVersion::V1_0);
+        
+        assert_eq!(VersionRange::from_bytes(&newer_peer.to_bytes()).unwrap(), newer_peer);
+        assert!(VersionRange::from_bytes(&[0x02, 0x00, 0x01, 0x00]).is_err());
+        assert!(VersionRange::from_bytes(&[0x01, 0x00]).is_err());
+    }
+    
+    #[test]
+    fn test_version_range_no_overlap() {
+        let future_only = VersionRange::new(0x0200, 0x0300).unwrap();
+        let result = VersionRange::supported().negotiate(future_only);
+        assert!(matches!(
+            result,
+            Err(SecurityError::NoCommonProtocolVersion { peer_min: 0x0200, peer_max: 0x0300, .. })
+        ));
+    }
+    
+    #[test]
+    fn test_unknown_higher_header_version_rejected() {
+        let header = SecurityMessageHeader {
+            protocol_version: ProtocolVersion::V1_0,
+            message_type: MessageType::HandshakeInit,
+            cipher_suite: CipherSuite::High,
+            feature_flags: FeatureFlags {
+                post_quantum_required: true,
+                hybrid_required: true,
+                metadata_protection: false,
+                onion_routing: false,
+                hsm_required: false,
+                perfect_forward_secrecy: true,
+                extended_key_rotation: false,
+            },
+            metadata_level: 2,
+            onion_enabled: false,
+            transport_mode: 1,
+            timestamp: current_timestamp(),
+            message_length: 100,
+            message_id: [0x42; 32],
+            session_id: [0x43; 32],
+            extension_count: 2,
+            signature_length: 64,
+        };
+        header.validate_negotiated(ProtocolVersion::V1_0).expect("Negotiated version should validate");
+        
+        let mut buffer = SecurityBuffer::new(SecurityMessageHeader::SIZE).expect("Buffer creation should succeed");
+        header.serialize_security(&mut buffer).expect("Header serialization should succeed");
+        let mut bytes = buffer.as_slice().to_vec();
+        bytes[..2].copy_from_slice(&[0x02, 0x00]);
+        
+        let mut buffer = SecurityBuffer::new(bytes.len()).expect("Buffer creation should succeed");
+        buffer.write_slice(&bytes).expect("Write should succeed");
+        buffer.set_position(0).expect("Set position should succeed");
+        let result = SecurityMessageHeader::parse_security(&mut buffer);
+        assert!(matches!(result, Err(SecurityError::InvalidProtocolVersion { actual: 0x0200, .. })));
+    }
 }
\ No newline at end of file
@@ -14,7 +14,7 @@ pub mod audit;
 
 // Re-export commonly used security types
 pub use hardened_core::{
-    SecurityResult, SecurityError, SecurityBuffer, SecurityStateMachine,
+    SecurityResult, SecurityError, SecurityBuffer, SecretBuffer, SecurityStateMachine, VersionRange,
     constant_time_eq_security, checked_add_security, checked_sub_security,
     checked_mul_security, checked_div_security
 };
@@ -28,11 +28,12 @@ pub use crypto::{
 pub use handshake::{
     SecurityHybridParser, SecurityHybridCiphertext, SecurityHybridSignature,
     SecurityHandshakeMessageParser, SecurityHandshakeInit, SecurityHandshakeResponse,
-    SecurityHandshakeComplete, SecurityHandshakeStateMachine
+    SecurityHandshakeComplete, SecurityHandshakeStateMachine, VersionNegotiation
 };
 pub use network::{
     SecurityNetworkParser, SecurityNetworkMessage, SecurityHandshakeMessage,
     SecurityDataMessage, SecurityValidationSettings, SecurityStreamingValidator,
+    SecurityIncrementalParser, SecurityStreamStatus,
     MAX_MESSAGE_SIZE, MAX_HEADER_SIZE, MAX_EXTENSION_SIZE, MAX_HANDSHAKE_SIZE
 };
 pub use migration_guide::{
@@ -40,7 +41,8 @@ pub use migration_guide::{
 };
 pub use fuzzing::{
     FuzzingConfig, MutationStrategy, CoverageTarget, SecurityFuzzingOrchestrator,
-    FuzzingResults, FuzzingResult, ProtocolViolation, TimingLeak, InvalidTransition
+    FuzzingResults, FuzzingResult, ProtocolViolation, TimingLeak, InvalidTransition,
+    seed_corpus, SEED_CORPUS_TIMESTAMP
 };
 pub use audit::{
     ReproducibleBuildConfig, DependencyAuditConfig, SecurityVulnerability,
@@ -4,8 +4,11 @@
 //! for all network protocol parsing operations.
 
 use crate::security::{
-    SecurityResult, SecurityError, SecurityBuffer, MessageType, CipherSuite, SecurityMessageHeader
+    SecurityResult, SecurityError, SecurityBuffer, MessageType, CipherSuite, SecurityMessageHeader,
+    checked_add_security
 };
+use crate::time::{Clock, SystemClock};
+use std::sync::Arc;
 
 /// Ukuran maksimum pesan jaringan (1 MiB)
 pub const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
@@ -22,6 +25,8 @@ pub struct SecurityNetworkParser {
     max_header_size: usize,
     max_extension_size: usize,
     strict_validation: bool,
+    /// Time source for header timestamp validation
+    clock: Arc<dyn Clock>,
 }
 
 impl SecurityNetworkParser {
@@ -32,6 +37,7 @@ impl SecurityNetworkParser {
             max_header_size: MAX_HEADER_SIZE,
             max_extension_size: MAX_EXTENSION_SIZE,
             strict_validation: true,
+            clock: Arc::new(SystemClock),
         }
     }
     
@@ -68,8 +74,17 @@ impl SecurityNetworkParser {
             max_header_size,
             max_extension_size,
             strict_validation: true,
+            clock: Arc::new(SystemClock),
         })
     }
+
+    /// Judge header timestamps against `clock` instead of the system clock
+    ///
+    /// Lets fuzz targets and tests replay messages stamped at a fixed time.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
     
     /// Parse complete network message with header and payload
     pub fn parse_message(&self, data: &[u8]) -> SecurityResult<SecurityNetworkMessage> {
@@ -97,7 +112,7 @@ impl SecurityNetworkParser {
         let header = SecurityMessageHeader::parse_security(&mut buffer)?;
         
         // Validate header
-        header.validate_security()?;
+        header.validate_security_at(self.clock.now_unix_secs())?;
         
         // Validate payload size matches header
         let payload_size = data.len() - SecurityMessageHeader::SIZE;
@@ -133,7 +148,7 @@ impl SecurityNetworkParser {
         buffer.set_position(0)?;
         
         let header = SecurityMessageHeader::parse_security(&mut buffer)?;
-        header.validate_security()?;
+        header.validate_security_at(self.clock.now_unix_secs())?;
         
         Ok(header)
     }
@@ -216,7 +231,7 @@ impl SecurityNetworkParser {
         buffer.set_position(0)?;
         
         let header = SecurityMessageHeader::parse_security(&mut buffer)?;
-        header.validate_security()?;
+        header.validate_security_at(self.clock.now_unix_secs())?;
         
         // Validate payload size consistency
         let payload_size = data.len() - SecurityMessageHeader::SIZE;
@@ -381,6 +396,142 @@ impl SecurityStreamingValidator {
     }
 }
 
+/// Progress of a [`SecurityIncrementalParser`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecurityStreamStatus {
+    /// Frame belum lengkap; dibutuhkan minimal `at_least` bytes lagi
+    NeedMore {
+        /// Jumlah minimum bytes tambahan yang dibutuhkan
+        at_least: usize,
+    },
+    /// Satu frame lengkap yang sudah divalidasi
+    Message(SecurityNetworkMessage),
+}
+
+/// Incremental frame parser for partial socket reads
+///
+/// Bytes are fed as they arrive. The header is validated as soon as it is
+/// complete, and the payload buffer is only allocated once the declared
+/// length is known to fit within the parser's message size limit. Input
+/// beyond the end of the current frame is never consumed, so the parser
+/// holds at most one declared frame.
+///
+/// After an error the stream is out of sync; the parser resets itself and
+/// the connection should be dropped.
+pub struct SecurityIncrementalParser {
+    parser: SecurityNetworkParser,
+    header: [u8; SecurityMessageHeader::SIZE],
+    header_len: usize,
+    /// Header plus payload received so far; allocated once the header is valid
+    frame: Vec<u8>,
+    /// Declared frame size, zero while the header is incomplete
+    frame_len: usize,
+}
+
+impl SecurityIncrementalParser {
+    /// Buat parser inkremental dengan batas ukuran default
+    pub fn new() -> Self {
+        Self::with_parser(SecurityNetworkParser::new())
+    }
+
+    /// Buat parser inkremental dengan parser (dan batas ukuran) kustom
+    pub fn with_parser(parser: SecurityNetworkParser) -> Self {
+        SecurityIncrementalParser {
+            parser,
+            header: [0u8; SecurityMessageHeader::SIZE],
+            header_len: 0,
+            frame: Vec::new(),
+            frame_len: 0,
+        }
+    }
+
+    /// Feed received bytes
+    ///
+    /// Returns how many bytes of `data` were consumed together with the
+    /// parser's progress. Unconsumed bytes belong to the next frame and must
+    /// be fed again after a [`SecurityStreamStatus::Message`].
+    pub fn feed(&mut self, data: &[u8]) -> SecurityResult<(usize, SecurityStreamStatus)> {
+        let result = self.feed_inner(data);
+        if result.is_err() {
+            self.reset();
+        }
+        result
+    }
+
+    fn feed_inner(&mut self, data: &[u8]) -> SecurityResult<(usize, SecurityStreamStatus)> {
+        let mut consumed = 0;
+
+        if self.frame_len == 0 {
+            let wanted = SecurityMessageHeader::SIZE - self.header_len;
+            let take = wanted.min(data.len());
+            self.header[self.header_len..self.header_len + take].copy_from_slice(&data[..take]);
+            self.header_len += take;
+            consumed += take;
+
+            if self.header_len < SecurityMessageHeader::SIZE {
+                return Ok((consumed, SecurityStreamStatus::NeedMore {
+                    at_least: SecurityMessageHeader::SIZE - self.header_len,
+                }));
+            }
+
+            let mut buffer = SecurityBuffer::from_slice(&self.header)?;
+            let header = SecurityMessageHeader::parse_security(&mut buffer)?;
+            header.validate_security_at(self.parser.clock.now_unix_secs())?;
+
+            // Bound the declared frame before allocating for it
+            let frame_len = checked_add_security(SecurityMessageHeader::SIZE, header.payload_length as usize)?;
+            if frame_len > self.parser.max_message_size {
+                return Err(SecurityError::ResourceExhaustionProtection {
+                    resource: "message_size".to_string(),
+                    limit: self.parser.max_message_size,
+                    requested: frame_len,
+                });
+            }
+
+            self.frame = Vec::with_capacity(frame_len);
+            self.frame.extend_from_slice(&self.header);
+            self.frame_len = frame_len;
+        }
+
+        let take = (self.frame_len - self.frame.len()).min(data.len() - consumed);
+        self.frame.extend_from_slice(&data[consumed..consumed + take]);
+        consumed += take;
+
+        if self.frame.len() < self.frame_len {
+            return Ok((consumed, SecurityStreamStatus::NeedMore {
+                at_least: self.frame_len - self.frame.len(),
+            }));
+        }
+
+        let frame = std::mem::take(&mut self.frame);
+        self.reset();
+        let message = self.parser.parse_message(&frame)?;
+        Ok((consumed, SecurityStreamStatus::Message(message)))
+    }
+
+    /// Drop any partially received frame
+    pub fn reset(&mut self) {
+        self.header_len = 0;
+        self.frame = Vec::new();
+        self.frame_len = 0;
+    }
+
+    /// Number of bytes of the current frame received so far
+    pub fn buffered(&self) -> usize {
+        if self.frame_len == 0 {
+            self.header_len
+        } else {
+            self.frame.len()
+        }
+    }
+}
+
+impl Default for SecurityIncrementalParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -531,4 +682,101 @@ mod tests {
         assert_eq!(header.message_type, MessageType::Data);
         assert_eq!(header.payload_length, 100);
     }
-}
\ No newline at end of file
+
+    fn data_frame(message_id: u64, payload: &[u8]) -> Vec<u8> {
+        let mut buffer = SecurityBuffer::new(SecurityMessageHeader::SIZE + payload.len()).expect("Buffer creation should succeed");
+        buffer.write_slice(&[0x01, 0x00]).expect("Write should succeed"); // Version
+        buffer.write_u8(0x04).expect("Write should succeed"); // Message type (Data)
+        buffer.write_u8(0x03).expect("Write should succeed"); // Cipher suite
+        buffer.write_u64_be(message_id).expect("Write should succeed"); // Message ID
+        buffer.write_u32_be(payload.len() as u32).expect("Write should succeed"); // Payload length
+        buffer.write_u64_be(current_time_secs()).expect("Write should succeed"); // Timestamp
+        buffer.write_slice(payload).expect("Write should succeed");
+        buffer.data().to_vec()
+    }
+
+    /// Feeds `data` in the given chunk sizes, collecting every parsed message
+    fn feed_chunks(parser: &mut SecurityIncrementalParser, data: &[u8], mut chunk_size: impl FnMut() -> usize) -> Vec<SecurityNetworkMessage> {
+        let mut messages = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let end = (offset + chunk_size().max(1)).min(data.len());
+            let mut chunk = &data[offset..end];
+            while !chunk.is_empty() {
+                let (consumed, status) = parser.feed(chunk).expect("Feed should succeed");
+                chunk = &chunk[consumed..];
+                match status {
+                    SecurityStreamStatus::Message(message) => messages.push(message),
+                    SecurityStreamStatus::NeedMore { at_least } => {
+                        assert!(chunk.is_empty());
+                        assert!(at_least > 0);
+                    }
+                }
+            }
+            offset = end;
+        }
+        messages
+    }
+
+    #[test]
+    fn test_incremental_parser_byte_by_byte() {
+        let frame = data_frame(7, &[0x42u8; 100]);
+        let expected = SecurityNetworkParser::new().parse_message(&frame).expect("One-shot parse should succeed");
+
+        let mut parser = SecurityIncrementalParser::new();
+        for (i, byte) in frame.iter().enumerate() {
+            let (consumed, status) = parser.feed(std::slice::from_ref(byte)).expect("Feed should succeed");
+            assert_eq!(consumed, 1);
+            if i + 1 < frame.len() {
+                let at_least = if i + 1 < SecurityMessageHeader::SIZE {
+                    SecurityMessageHeader::SIZE - (i + 1)
+                } else {
+                    frame.len() - (i + 1)
+                };
+                assert_eq!(status, SecurityStreamStatus::NeedMore { at_least });
+            } else {
+                assert_eq!(status, SecurityStreamStatus::Message(expected.clone()));
+            }
+        }
+        assert_eq!(parser.buffered(), 0);
+    }
+
+    #[test]
+    fn test_incremental_parser_random_chunks() {
+        use rand::Rng;
+
+        let frames: Vec<Vec<u8>> = (0..5u8)
+            .map(|i| data_frame(i as u64, &vec![i; 37 * i as usize + 1]))
+            .collect();
+        let one_shot: Vec<SecurityNetworkMessage> = frames
+            .iter()
+            .map(|frame| SecurityNetworkParser::new().parse_message(frame).expect("One-shot parse should succeed"))
+            .collect();
+        let stream = frames.concat();
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let mut parser = SecurityIncrementalParser::new();
+            let messages = feed_chunks(&mut parser, &stream, || rng.gen_range(1..64));
+            assert_eq!(messages, one_shot);
+        }
+
+        // Everything in a single call still stops at each frame boundary
+        let mut parser = SecurityIncrementalParser::new();
+        let (consumed, status) = parser.feed(&stream).expect("Feed should succeed");
+        assert_eq!(consumed, frames[0].len());
+        assert_eq!(status, SecurityStreamStatus::Message(one_shot[0].clone()));
+    }
+
+    #[test]
+    fn test_incremental_parser_rejects_oversized_declared_length() {
+        let parser = SecurityNetworkParser::with_limits(1024, 64, 512).expect("Parser creation should succeed");
+        let mut parser = SecurityIncrementalParser::with_parser(parser);
+        let frame = data_frame(1, &[0u8; 2000]);
+
+        // Rejected from the header alone, before any payload is buffered
+        let result = parser.feed(&frame[..SecurityMessageHeader::SIZE]);
+        assert!(matches!(result, Err(SecurityError::ResourceExhaustionProtection { .. })));
+        assert_eq!(parser.buffered(), 0);
+    }
+}
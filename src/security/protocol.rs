@@ -195,6 +195,12 @@ impl SecurityMessageHeader {
     
     /// Validasi semua field header — versi, panjang payload, dan timestamp
     pub fn validate_security(&self) -> SecurityResult<()> {
+        self.validate_security_at(crate::time::current_time_secs())
+    }
+
+    /// Like [`validate_security`](Self::validate_security), judging the
+    /// timestamp against `now_secs` instead of the system clock
+    pub fn validate_security_at(&self, now_secs: u64) -> SecurityResult<()> {
         // Validate version
         if self.version != ProtocolVersion::V1_0 {
             return Err(SecurityError::InvalidProtocolVersion {
@@ -215,7 +221,7 @@ impl SecurityMessageHeader {
         
         // Validate timestamp (must be within reasonable range)
         const MAX_TIMESTAMP_DRIFT: u64 = 3600; // 1 hour in seconds
-        let current_timestamp = crate::time::current_time_secs();
+        let current_timestamp = now_secs;
         let timestamp_diff = if self.timestamp > current_timestamp {
             self.timestamp - current_timestamp
         } else {
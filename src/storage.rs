@@ -1,12 +1,25 @@
 //! B4AE Encrypted Storage
 //!
 //! Secure storage using Storage Key (STK) from key hierarchy.
-//! Data encrypted with AES-256-GCM; context used as AAD.
+//! Data encrypted with AES-256-GCM (default) or XChaCha20-Poly1305; context
+//! used as AAD.
+//!
+//! Files too large to hold in memory use the chunked [`EncryptedWriter`] /
+//! [`EncryptedReader`] streams from the [`stream`] module.
+
+pub mod stream;
+pub(crate) mod temp_file;
+
+pub use stream::{reencrypt, reencrypt_file, verify_integrity, EncryptedReader, EncryptedWriter};
 
+use crate::crypto::CryptoError;
 use crate::crypto::aes_gcm::{self, AesKey};
+use crate::crypto::chacha20poly1305_wrapper::{decrypt_xchacha, encrypt_xchacha};
 use crate::error::{B4aeError, B4aeResult};
 use crate::key_hierarchy::StorageKey;
 use std::collections::HashMap;
+use std::fmt;
+use std::io;
 
 /// Backend for persistent storage (caller provides implementation).
 pub trait StorageBackend: Send + Sync {
@@ -48,24 +61,128 @@ impl StorageBackend for MemoryStorageBackend {
     }
 }
 
-/// Encrypted storage using STK. Encrypts data with AES-256-GCM; context = AAD.
+/// AEAD used for stored blobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageCipher {
+    /// AES-256-GCM with 12-byte random nonces: `[nonce(12) || ct || tag]`.
+    #[default]
+    Aes256Gcm,
+    /// XChaCha20-Poly1305 with 24-byte random nonces: `[nonce(24) || ct || tag]`.
+    /// Preferred for large archives written under one long-lived STK.
+    XChaCha20Poly1305,
+}
+
+/// Errors from streaming encrypted file I/O
+#[derive(Debug)]
+pub enum StorageError {
+    /// Underlying reader or writer failed
+    Io(io::Error),
+    /// Stream header is malformed or unsupported
+    InvalidHeader(String),
+    /// Key derivation, randomness or chunk encryption failed, or the stream
+    /// was used after `finish`
+    Crypto(CryptoError),
+    /// Chunk failed authentication (tampered, reordered, truncated or wrong key)
+    CorruptChunk {
+        /// Zero-based index of the first chunk that failed
+        index: u64,
+    },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Io(e) => write!(f, "I/O error: {}", e),
+            StorageError::InvalidHeader(msg) => write!(f, "Invalid stream header: {}", msg),
+            StorageError::Crypto(e) => write!(f, "Stream crypto error: {}", e),
+            StorageError::CorruptChunk { index } => write!(f, "Chunk {} failed authentication", index),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::Io(e) => Some(e),
+            StorageError::Crypto(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(err: io::Error) -> Self {
+        if err.get_ref().is_none() {
+            return StorageError::Io(err);
+        }
+        // Unwrap errors that `EncryptedReader::read` boxed into an io::Error
+        let kind = err.kind();
+        match err.into_inner().map(|inner| inner.downcast::<StorageError>()) {
+            Some(Ok(inner)) => *inner,
+            Some(Err(other)) => StorageError::Io(io::Error::new(kind, other)),
+            None => StorageError::Io(io::Error::from(kind)),
+        }
+    }
+}
+
+impl From<StorageError> for io::Error {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::Io(e) => e,
+            other => io::Error::new(io::ErrorKind::InvalidData, other),
+        }
+    }
+}
+
+impl From<StorageError> for B4aeError {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::Io(e) => B4aeError::InternalError(format!("I/O error: {}", e)),
+            StorageError::InvalidHeader(msg) => B4aeError::InvalidInput(format!("Invalid stream header: {}", msg)),
+            StorageError::Crypto(e) => B4aeError::from(e),
+            StorageError::CorruptChunk { .. } => B4aeError::CryptoError(CryptoError::AuthenticationFailed),
+        }
+    }
+}
+
+/// Encrypted storage using STK. Encrypts data with the configured cipher; context = AAD.
+///
+/// Blobs are not tagged with their cipher, so a store must be read back with
+/// the same [`StorageCipher`] it was written with.
 pub struct EncryptedStorage {
     key: StorageKey,
     backend: Box<dyn StorageBackend>,
+    cipher: StorageCipher,
 }
 
 impl EncryptedStorage {
-    /// Create from StorageKey and backend.
+    /// Create from StorageKey and backend (AES-256-GCM).
     pub fn new(key: StorageKey, backend: Box<dyn StorageBackend>) -> Self {
-        Self { key, backend }
+        Self::with_cipher(key, backend, StorageCipher::default())
+    }
+
+    /// Create from StorageKey and backend with an explicit cipher.
+    pub fn with_cipher(key: StorageKey, backend: Box<dyn StorageBackend>, cipher: StorageCipher) -> Self {
+        Self { key, backend, cipher }
+    }
+
+    /// Cipher used for this store.
+    pub fn cipher(&self) -> StorageCipher {
+        self.cipher
     }
 
     /// Store data encrypted. `context` (e.g. "vault:profile") used as AAD.
     pub fn store(&mut self, context: &[u8], id: &[u8], plaintext: &[u8]) -> B4aeResult<()> {
-        let aes_key = AesKey::from_bytes(self.key.as_slice())?;
-        let (nonce, ciphertext) = aes_gcm::encrypt(&aes_key, plaintext, context)?;
-        let mut blob = nonce;
-        blob.extend_from_slice(&ciphertext);
+        let blob = match self.cipher {
+            StorageCipher::Aes256Gcm => {
+                let aes_key = AesKey::from_bytes(self.key.as_slice())?;
+                let (nonce, ciphertext) = aes_gcm::encrypt(&aes_key, plaintext, context)?;
+                let mut blob = nonce;
+                blob.extend_from_slice(&ciphertext);
+                blob
+            }
+            StorageCipher::XChaCha20Poly1305 => encrypt_xchacha(self.key.as_slice(), plaintext, Some(context))?,
+        };
         let storage_id = storage_id(context, id);
         self.backend.write(&storage_id, &blob)
     }
@@ -77,12 +194,17 @@ impl EncryptedStorage {
             Some(b) => b,
             None => return Ok(None),
         };
-        if blob.len() < 12 + 16 {
-            return Err(B4aeError::CryptoError("Storage blob too short".to_string()));
-        }
-        let (nonce, ct) = blob.split_at(12);
-        let aes_key = AesKey::from_bytes(self.key.as_slice())?;
-        let plaintext = aes_gcm::decrypt(&aes_key, nonce, ct, context)?;
+        let plaintext = match self.cipher {
+            StorageCipher::Aes256Gcm => {
+                if blob.len() < 12 + 16 {
+                    return Err(B4aeError::CryptoError(CryptoError::InvalidInput("Storage blob too short".to_string())));
+                }
+                let (nonce, ct) = blob.split_at(12);
+                let aes_key = AesKey::from_bytes(self.key.as_slice())?;
+                aes_gcm::decrypt(&aes_key, nonce, ct, context)?
+            }
+            StorageCipher::XChaCha20Poly1305 => decrypt_xchacha(self.key.as_slice(), &blob, Some(context))?,
+        };
         Ok(Some(plaintext))
     }
 
@@ -120,4 +242,34 @@ mod tests {
         let retrieved = storage.retrieve(b"vault:profiles", b"alice").unwrap().unwrap();
         assert_eq!(retrieved, b"secret data");
     }
+
+    #[test]
+    fn test_encrypted_storage_xchacha_roundtrip() {
+        let mik = MasterIdentityKey::generate().unwrap();
+        let dmk = mik.derive_dmk(b"device-1").unwrap();
+        let stk = dmk.derive_stk(b"archive").unwrap();
+        let backend = Box::new(MemoryStorageBackend::new());
+        let mut storage = EncryptedStorage::with_cipher(stk, backend, StorageCipher::XChaCha20Poly1305);
+
+        let archive = vec![0xA5u8; 64 * 1024];
+        storage.store(b"archive:media", b"2024", &archive).unwrap();
+        let retrieved = storage.retrieve(b"archive:media", b"2024").unwrap().unwrap();
+        assert_eq!(retrieved, archive);
+        assert!(storage.retrieve(b"archive:other", b"2024").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_storage_error_maps_to_matching_b4ae_error() {
+        let corrupt = B4aeError::from(StorageError::CorruptChunk { index: 3 });
+        assert!(matches!(corrupt, B4aeError::CryptoError(CryptoError::AuthenticationFailed)));
+
+        let header = B4aeError::from(StorageError::InvalidHeader("bad magic".to_string()));
+        assert!(matches!(header, B4aeError::InvalidInput(_)));
+
+        let crypto = B4aeError::from(StorageError::Crypto(CryptoError::InvalidInput("stream already finished".to_string())));
+        assert!(matches!(crypto, B4aeError::CryptoError(CryptoError::InvalidInput(_))));
+
+        let io = B4aeError::from(StorageError::Io(io::Error::from(io::ErrorKind::BrokenPipe)));
+        assert!(matches!(io, B4aeError::InternalError(_)));
+    }
 }
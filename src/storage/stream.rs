@@ -0,0 +1,769 @@
+//! Streaming Encrypted File I/O
+//!
+//! Chunked encryption for archives too large to hold in memory, using the
+//! STREAM construction (big-endian 32-bit chunk counter plus a "last chunk"
+//! flag in the nonce). Reordered, dropped or truncated chunks fail
+//! authentication.
+//!
+//! ```text
+//! header = magic "B4SS" (4) || version (1) || algorithm_id (1) || chunk_size (4, BE)
+//!          || stream_id (16) || base_nonce
+//! key    = HKDF(salt = stream_id, ikm = STK, info = "B4AE-stream-v1-chunk-key")
+//! chunk  = AEAD(key, STREAM nonce(base_nonce, i, last), plaintext_i, aad = header)
+//! ```
+//!
+//! Every chunk carries exactly `chunk_size` plaintext bytes except the last,
+//! which carries 0..=`chunk_size`. The base nonce is 7 bytes for AES-256-GCM
+//! and 19 bytes for XChaCha20-Poly1305 (nonce size minus the 5-byte counter).
+//! A 7-byte random nonce alone would collide after a few million streams
+//! under one STK; the random stream ID gives every stream its own key.
+
+use super::temp_file::{create_temp_sibling, sync_parent_dir};
+use super::{StorageCipher, StorageError};
+use crate::crypto::{hkdf, CryptoError};
+use crate::key_hierarchy::StorageKey;
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::{KeyInit, Payload};
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::XChaCha20Poly1305;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// Stream header magic
+pub const STREAM_MAGIC: [u8; 4] = *b"B4SS";
+
+/// Stream format version
+const STREAM_VERSION: u8 = 0x01;
+
+/// Default plaintext bytes per chunk (64 KiB)
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Largest chunk size accepted from a header (bounds reader memory)
+pub const MAX_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+/// AEAD tag size appended to every chunk
+const TAG_SIZE: usize = 16;
+
+/// Header bytes before the stream ID: magic + version + algorithm + chunk size
+const HEADER_PREFIX_SIZE: usize = 4 + 1 + 1 + 4;
+
+/// Random per-stream salt of the chunk key
+const STREAM_ID_SIZE: usize = 16;
+
+/// HKDF info of the per-stream chunk key
+const STREAM_KEY_INFO: &[u8] = b"B4AE-stream-v1-chunk-key";
+
+fn algorithm_id(cipher: StorageCipher) -> u8 {
+    match cipher {
+        StorageCipher::Aes256Gcm => 0x01,
+        StorageCipher::XChaCha20Poly1305 => 0x02,
+    }
+}
+
+fn base_nonce_size(cipher: StorageCipher) -> usize {
+    match cipher {
+        StorageCipher::Aes256Gcm => 7,
+        StorageCipher::XChaCha20Poly1305 => 19,
+    }
+}
+
+/// Parsed stream header
+struct StreamHeader {
+    cipher: StorageCipher,
+    chunk_size: usize,
+    /// Raw header bytes (authenticated as AAD of every chunk)
+    bytes: Vec<u8>,
+}
+
+impl StreamHeader {
+    fn new(cipher: StorageCipher, chunk_size: usize) -> Result<Self, StorageError> {
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(StorageError::InvalidHeader(format!(
+                "chunk size must be 1..={} bytes, got {}",
+                MAX_CHUNK_SIZE, chunk_size
+            )));
+        }
+        let mut random = vec![0u8; STREAM_ID_SIZE + base_nonce_size(cipher)];
+        crate::crypto::random::fill_random(&mut random).map_err(StorageError::Crypto)?;
+
+        let mut bytes = Vec::with_capacity(HEADER_PREFIX_SIZE + random.len());
+        bytes.extend_from_slice(&STREAM_MAGIC);
+        bytes.push(STREAM_VERSION);
+        bytes.push(algorithm_id(cipher));
+        bytes.extend_from_slice(&(chunk_size as u32).to_be_bytes());
+        bytes.extend_from_slice(&random);
+        Ok(StreamHeader { cipher, chunk_size, bytes })
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self, StorageError> {
+        let mut prefix = [0u8; HEADER_PREFIX_SIZE];
+        reader.read_exact(&mut prefix).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => StorageError::InvalidHeader("stream too short".to_string()),
+            _ => StorageError::Io(e),
+        })?;
+        if prefix[..4] != STREAM_MAGIC {
+            return Err(StorageError::InvalidHeader("bad magic".to_string()));
+        }
+        if prefix[4] != STREAM_VERSION {
+            return Err(StorageError::InvalidHeader(format!("unsupported version {}", prefix[4])));
+        }
+        let cipher = match prefix[5] {
+            0x01 => StorageCipher::Aes256Gcm,
+            0x02 => StorageCipher::XChaCha20Poly1305,
+            other => return Err(StorageError::InvalidHeader(format!("unknown algorithm id {:#04x}", other))),
+        };
+        let chunk_size = u32::from_be_bytes([prefix[6], prefix[7], prefix[8], prefix[9]]) as usize;
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(StorageError::InvalidHeader(format!("invalid chunk size {}", chunk_size)));
+        }
+
+        let mut bytes = prefix.to_vec();
+        bytes.resize(HEADER_PREFIX_SIZE + STREAM_ID_SIZE + base_nonce_size(cipher), 0);
+        reader.read_exact(&mut bytes[HEADER_PREFIX_SIZE..]).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => StorageError::InvalidHeader("stream too short".to_string()),
+            _ => StorageError::Io(e),
+        })?;
+        Ok(StreamHeader { cipher, chunk_size, bytes })
+    }
+
+    fn stream_id(&self) -> &[u8] {
+        &self.bytes[HEADER_PREFIX_SIZE..HEADER_PREFIX_SIZE + STREAM_ID_SIZE]
+    }
+
+    fn base_nonce(&self) -> &[u8] {
+        &self.bytes[HEADER_PREFIX_SIZE + STREAM_ID_SIZE..]
+    }
+
+    /// Chunk key of this stream, derived from the STK and the stream ID
+    fn chunk_key(&self, key: &StorageKey) -> Result<Zeroizing<Vec<u8>>, StorageError> {
+        hkdf::derive_key_with_salt(self.stream_id(), &[key.as_slice()], STREAM_KEY_INFO, 32)
+            .map(Zeroizing::new)
+            .map_err(StorageError::Crypto)
+    }
+}
+
+/// STREAM encryptor for either cipher (the AES key schedule is boxed)
+enum Sealer {
+    Aes(Box<EncryptorBE32<Aes256Gcm>>),
+    XChaCha(EncryptorBE32<XChaCha20Poly1305>),
+}
+
+impl Sealer {
+    fn new(key: &StorageKey, header: &StreamHeader) -> Result<Self, StorageError> {
+        let chunk_key = header.chunk_key(key)?;
+        let key = GenericArray::from_slice(chunk_key.as_slice());
+        let nonce = header.base_nonce();
+        Ok(match header.cipher {
+            StorageCipher::Aes256Gcm => {
+                Sealer::Aes(Box::new(EncryptorBE32::from_aead(
+                    Aes256Gcm::new(key),
+                    GenericArray::from_slice(nonce),
+                )))
+            }
+            StorageCipher::XChaCha20Poly1305 => Sealer::XChaCha(EncryptorBE32::from_aead(
+                XChaCha20Poly1305::new(key),
+                GenericArray::from_slice(nonce),
+            )),
+        })
+    }
+
+    fn seal_next(&mut self, msg: &[u8], aad: &[u8]) -> Result<Vec<u8>, StorageError> {
+        let payload = Payload { msg, aad };
+        match self {
+            Sealer::Aes(enc) => enc.encrypt_next(payload),
+            Sealer::XChaCha(enc) => enc.encrypt_next(payload),
+        }
+        .map_err(|_| StorageError::Crypto(CryptoError::EncryptionFailed("chunk encryption failed".to_string())))
+    }
+
+    fn seal_last(self, msg: &[u8], aad: &[u8]) -> Result<Vec<u8>, StorageError> {
+        let payload = Payload { msg, aad };
+        match self {
+            Sealer::Aes(enc) => enc.encrypt_last(payload),
+            Sealer::XChaCha(enc) => enc.encrypt_last(payload),
+        }
+        .map_err(|_| StorageError::Crypto(CryptoError::EncryptionFailed("chunk encryption failed".to_string())))
+    }
+}
+
+/// STREAM decryptor for either cipher
+enum Opener {
+    Aes(Box<DecryptorBE32<Aes256Gcm>>),
+    XChaCha(DecryptorBE32<XChaCha20Poly1305>),
+}
+
+impl Opener {
+    fn new(key: &StorageKey, header: &StreamHeader) -> Result<Self, StorageError> {
+        let chunk_key = header.chunk_key(key)?;
+        let key = GenericArray::from_slice(chunk_key.as_slice());
+        let nonce = header.base_nonce();
+        Ok(match header.cipher {
+            StorageCipher::Aes256Gcm => {
+                Opener::Aes(Box::new(DecryptorBE32::from_aead(
+                    Aes256Gcm::new(key),
+                    GenericArray::from_slice(nonce),
+                )))
+            }
+            StorageCipher::XChaCha20Poly1305 => Opener::XChaCha(DecryptorBE32::from_aead(
+                XChaCha20Poly1305::new(key),
+                GenericArray::from_slice(nonce),
+            )),
+        })
+    }
+
+    /// Decrypts one chunk; `None` means the tag did not verify
+    fn open_next(&mut self, msg: &[u8], aad: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+        let payload = Payload { msg, aad };
+        match self {
+            Opener::Aes(dec) => dec.decrypt_next(payload),
+            Opener::XChaCha(dec) => dec.decrypt_next(payload),
+        }
+        .ok()
+        .map(Zeroizing::new)
+    }
+
+    /// Decrypts the final chunk; `None` means the tag did not verify
+    fn open_last(self, msg: &[u8], aad: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+        let payload = Payload { msg, aad };
+        match self {
+            Opener::Aes(dec) => dec.decrypt_last(payload),
+            Opener::XChaCha(dec) => dec.decrypt_last(payload),
+        }
+        .ok()
+        .map(Zeroizing::new)
+    }
+}
+
+/// Splits a ciphertext stream (after the header) into chunks
+///
+/// Reads one byte past each full chunk to tell whether it is the last: a
+/// chunk is final exactly when the stream ends inside or right after it.
+struct ChunkFramer<R: Read> {
+    inner: R,
+    chunk_len: usize,
+    carry: Option<u8>,
+    done: bool,
+}
+
+impl<R: Read> ChunkFramer<R> {
+    fn new(inner: R, header: &StreamHeader) -> Self {
+        ChunkFramer {
+            inner,
+            chunk_len: header.chunk_size + TAG_SIZE,
+            carry: None,
+            done: false,
+        }
+    }
+
+    /// Returns the next chunk ciphertext and whether it is the last one,
+    /// or `None` after the last chunk has been returned
+    fn next_chunk(&mut self) -> io::Result<Option<(Vec<u8>, bool)>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let mut chunk = vec![0u8; self.chunk_len + 1];
+        let mut filled = 0;
+        if let Some(byte) = self.carry.take() {
+            chunk[0] = byte;
+            filled = 1;
+        }
+        while filled < chunk.len() {
+            match self.inner.read(&mut chunk[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let last = filled <= self.chunk_len;
+        if last {
+            self.done = true;
+            chunk.truncate(filled);
+        } else {
+            self.carry = chunk.pop();
+        }
+        Ok(Some((chunk, last)))
+    }
+}
+
+/// Authenticates and decrypts the chunks of a stream in order
+///
+/// Shared by [`EncryptedReader`] and [`verify_integrity`] so both apply the
+/// same framing and "last chunk" rules.
+struct ChunkDecryptor<R: Read> {
+    framer: ChunkFramer<R>,
+    header: StreamHeader,
+    opener: Option<Opener>,
+    index: u64,
+}
+
+impl<R: Read> ChunkDecryptor<R> {
+    /// Reads and validates the stream header
+    fn new(mut inner: R, key: &StorageKey) -> Result<Self, StorageError> {
+        let header = StreamHeader::read_from(&mut inner)?;
+        let opener = Opener::new(key, &header)?;
+        Ok(ChunkDecryptor {
+            framer: ChunkFramer::new(inner, &header),
+            header,
+            opener: Some(opener),
+            index: 0,
+        })
+    }
+
+    /// Returns the next chunk's plaintext, or `None` once the final chunk
+    /// has been authenticated
+    fn next_chunk(&mut self) -> Result<Option<Zeroizing<Vec<u8>>>, StorageError> {
+        let Some((ciphertext, last)) = self.framer.next_chunk()? else {
+            return Ok(None);
+        };
+        let index = self.index;
+        let corrupt = || StorageError::CorruptChunk { index };
+
+        let plaintext = if last {
+            let opener = self.opener.take().ok_or_else(corrupt)?;
+            opener.open_last(&ciphertext, &self.header.bytes)
+        } else {
+            let opener = self.opener.as_mut().ok_or_else(corrupt)?;
+            opener.open_next(&ciphertext, &self.header.bytes)
+        }
+        .ok_or_else(corrupt)?;
+
+        self.index += 1;
+        Ok(Some(plaintext))
+    }
+}
+
+/// Checks every chunk tag of a stream without returning any plaintext
+///
+/// Walks the whole stream, including the final "last chunk" marker, so a
+/// truncated file is caught here rather than partway through a restore.
+/// Decrypted chunks are zeroized as soon as their tag has been checked.
+///
+/// # Returns
+/// * `Ok(())` - Every chunk authenticated under `key`
+/// * `Err(StorageError::CorruptChunk { index })` - First chunk that failed
+/// * `Err(StorageError)` - Malformed header or I/O failure
+pub fn verify_integrity<R: Read>(reader: R, key: &StorageKey) -> Result<(), StorageError> {
+    let mut chunks = ChunkDecryptor::new(reader, key)?;
+    while chunks.next_chunk()?.is_some() {}
+    Ok(())
+}
+
+/// Re-encrypts a stream from `old_key` to `new_key` chunk by chunk
+///
+/// Keeps the source cipher and chunk size. At most one chunk of plaintext is
+/// held at a time and every intermediate buffer is zeroized. If the source
+/// fails authentication partway through, `writer` holds a partial stream
+/// that will itself fail to verify; use [`reencrypt_file`] to get an
+/// all-or-nothing replacement on disk.
+///
+/// # Returns
+/// * `Ok(W)` - The writer, after the new stream's final chunk was written
+/// * `Err(StorageError)` - Source corrupt or under a different key, or I/O failure
+pub fn reencrypt<R: Read, W: Write>(
+    old_key: &StorageKey,
+    new_key: &StorageKey,
+    reader: R,
+    writer: W,
+) -> Result<W, StorageError> {
+    let mut chunks = ChunkDecryptor::new(reader, old_key)?;
+    let mut output =
+        EncryptedWriter::with_chunk_size(writer, new_key, chunks.header.cipher, chunks.header.chunk_size)?;
+    while let Some(plaintext) = chunks.next_chunk()? {
+        output.write_all(&plaintext)?;
+    }
+    output.finish()
+}
+
+/// Re-encrypts the stream file at `path` from `old_key` to `new_key`
+///
+/// Writes the new stream to a uniquely named sibling temporary file (created
+/// fresh, mode `0600` on Unix), syncs it, then renames it over `path`. A crash
+/// or error at any point leaves either the complete old file or the complete
+/// new file at `path`.
+pub fn reencrypt_file<P: AsRef<Path>>(old_key: &StorageKey, new_key: &StorageKey, path: P) -> Result<(), StorageError> {
+    let path = path.as_ref();
+    let (temp_path, temp) = create_temp_sibling(path)?;
+
+    let result = (|| {
+        let source = BufReader::new(File::open(path)?);
+        let temp = reencrypt(old_key, new_key, source, BufWriter::new(temp))?;
+        let temp = temp.into_inner().map_err(|e| StorageError::Io(e.into_error()))?;
+        temp.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        sync_parent_dir(path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Encrypts everything written to it into a STREAM file
+///
+/// Call [`finish`](Self::finish) after the last write; it seals the final
+/// chunk. A writer dropped without `finish` leaves a truncated stream that
+/// fails authentication when read.
+///
+/// ```rust
+/// use b4ae::key_hierarchy::MasterIdentityKey;
+/// use b4ae::storage::{EncryptedReader, EncryptedWriter, StorageCipher};
+/// use std::io::{Read, Write};
+///
+/// let stk = MasterIdentityKey::generate().unwrap()
+///     .derive_dmk(b"device-1").unwrap()
+///     .derive_stk(b"archive").unwrap();
+///
+/// let mut writer = EncryptedWriter::new(Vec::new(), &stk, StorageCipher::Aes256Gcm).unwrap();
+/// writer.write_all(b"large archive contents").unwrap();
+/// let file = writer.finish().unwrap();
+///
+/// let mut plaintext = Vec::new();
+/// EncryptedReader::new(file.as_slice(), &stk).unwrap().read_to_end(&mut plaintext).unwrap();
+/// assert_eq!(plaintext, b"large archive contents");
+/// ```
+pub struct EncryptedWriter<W: Write> {
+    inner: W,
+    header: StreamHeader,
+    sealer: Option<Sealer>,
+    buffer: Zeroizing<Vec<u8>>,
+}
+
+impl<W: Write> EncryptedWriter<W> {
+    /// Starts a stream with [`DEFAULT_CHUNK_SIZE`] chunks, writing the header
+    pub fn new(inner: W, key: &StorageKey, cipher: StorageCipher) -> Result<Self, StorageError> {
+        Self::with_chunk_size(inner, key, cipher, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Starts a stream with a custom chunk size (1..=[`MAX_CHUNK_SIZE`])
+    pub fn with_chunk_size(
+        mut inner: W,
+        key: &StorageKey,
+        cipher: StorageCipher,
+        chunk_size: usize,
+    ) -> Result<Self, StorageError> {
+        let header = StreamHeader::new(cipher, chunk_size)?;
+        let sealer = Sealer::new(key, &header)?;
+        inner.write_all(&header.bytes)?;
+        Ok(EncryptedWriter {
+            inner,
+            buffer: Zeroizing::new(Vec::with_capacity(chunk_size)),
+            header,
+            sealer: Some(sealer),
+        })
+    }
+
+    /// Seals the final chunk and returns the inner writer
+    pub fn finish(mut self) -> Result<W, StorageError> {
+        let sealer = self
+            .sealer
+            .take()
+            .ok_or_else(|| StorageError::Crypto(CryptoError::InvalidInput("stream already finished".to_string())))?;
+        let ciphertext = sealer.seal_last(&self.buffer, &self.header.bytes)?;
+        self.buffer.clear();
+        self.inner.write_all(&ciphertext)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Seals and writes one full chunk from the front of the buffer
+    fn seal_full_chunk(&mut self) -> Result<(), StorageError> {
+        let sealer = self
+            .sealer
+            .as_mut()
+            .ok_or_else(|| StorageError::Crypto(CryptoError::InvalidInput("stream already finished".to_string())))?;
+        let chunk_size = self.header.chunk_size;
+        let ciphertext = sealer.seal_next(&self.buffer[..chunk_size], &self.header.bytes)?;
+        self.inner.write_all(&ciphertext)?;
+
+        // Shift the remainder down; the vacated tail is overwritten before
+        // being truncated so no plaintext lingers in spare capacity
+        let remaining = self.buffer.len() - chunk_size;
+        self.buffer.copy_within(chunk_size.., 0);
+        self.buffer[remaining..].fill(0);
+        self.buffer.truncate(remaining);
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for EncryptedWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let chunk_size = self.header.chunk_size;
+        let mut written = 0;
+        while written < data.len() {
+            // Hold a full chunk back until more data arrives: only `finish`
+            // knows whether it is the last one
+            if self.buffer.len() == chunk_size {
+                self.seal_full_chunk()?;
+            }
+            let take = (chunk_size - self.buffer.len()).min(data.len() - written);
+            self.buffer.extend_from_slice(&data[written..written + take]);
+            written += take;
+        }
+        Ok(written)
+    }
+
+    /// Flushes the inner writer; buffered plaintext stays until the chunk fills
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decrypts a STREAM file written by [`EncryptedWriter`]
+///
+/// Each chunk is authenticated before any of its plaintext is returned.
+/// Errors surface as `io::Error`s wrapping a [`StorageError`]; a tampered,
+/// reordered or truncated stream yields [`StorageError::CorruptChunk`].
+pub struct EncryptedReader<R: Read> {
+    chunks: ChunkDecryptor<R>,
+    plaintext: Zeroizing<Vec<u8>>,
+    position: usize,
+}
+
+impl<R: Read> EncryptedReader<R> {
+    /// Reads and validates the stream header
+    pub fn new(inner: R, key: &StorageKey) -> Result<Self, StorageError> {
+        Ok(EncryptedReader {
+            chunks: ChunkDecryptor::new(inner, key)?,
+            plaintext: Zeroizing::new(Vec::new()),
+            position: 0,
+        })
+    }
+
+    /// Cipher recorded in the stream header
+    pub fn cipher(&self) -> StorageCipher {
+        self.chunks.header.cipher
+    }
+}
+
+impl<R: Read> Read for EncryptedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.plaintext.len() {
+            match self.chunks.next_chunk()? {
+                Some(plaintext) => {
+                    self.plaintext = plaintext;
+                    self.position = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.plaintext.len() - self.position);
+        buf[..n].copy_from_slice(&self.plaintext[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_hierarchy::MasterIdentityKey;
+
+    fn test_stk(context: &[u8]) -> StorageKey {
+        let mik = MasterIdentityKey::from_bytes(&[7u8; 32]).unwrap();
+        mik.derive_dmk(b"device-1").unwrap().derive_stk(context).unwrap()
+    }
+
+    /// Deterministic, non-repeating test data
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i as u32).wrapping_mul(2_654_435_761).to_be_bytes()[0]).collect()
+    }
+
+    fn encrypt(data: &[u8], key: &StorageKey, cipher: StorageCipher, chunk_size: usize) -> Vec<u8> {
+        let mut writer = EncryptedWriter::with_chunk_size(Vec::new(), key, cipher, chunk_size).unwrap();
+        // Uneven write sizes exercise chunk boundaries
+        for piece in data.chunks(10_007) {
+            writer.write_all(piece).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn decrypt(file: &[u8], key: &StorageKey) -> io::Result<Vec<u8>> {
+        let mut reader = EncryptedReader::new(file, key).map_err(io::Error::from)?;
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    fn corrupt_chunk_index(err: io::Error) -> Option<u64> {
+        match StorageError::from(err) {
+            StorageError::CorruptChunk { index } => Some(index),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_multi_megabyte_roundtrip() {
+        let key = test_stk(b"archive");
+        let data = test_data(3 * 1024 * 1024 + 12_345);
+
+        let file = encrypt(&data, &key, StorageCipher::Aes256Gcm, DEFAULT_CHUNK_SIZE);
+        let chunks = data.len().div_ceil(DEFAULT_CHUNK_SIZE);
+        assert_eq!(file.len(), HEADER_PREFIX_SIZE + STREAM_ID_SIZE + 7 + data.len() + chunks * TAG_SIZE);
+        assert_eq!(decrypt(&file, &key).unwrap(), data);
+
+        let xfile = encrypt(&data, &key, StorageCipher::XChaCha20Poly1305, DEFAULT_CHUNK_SIZE);
+        let mut reader = EncryptedReader::new(xfile.as_slice(), &key).unwrap();
+        assert_eq!(reader.cipher(), StorageCipher::XChaCha20Poly1305);
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn test_exact_and_empty_chunks() {
+        let key = test_stk(b"archive");
+        for len in [0, 1, 4096, 8192, 8193] {
+            let data = test_data(len);
+            let file = encrypt(&data, &key, StorageCipher::Aes256Gcm, 4096);
+            assert_eq!(decrypt(&file, &key).unwrap(), data, "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_flipped_byte_detected() {
+        let key = test_stk(b"archive");
+        let data = test_data(2 * 1024 * 1024);
+        let mut file = encrypt(&data, &key, StorageCipher::Aes256Gcm, DEFAULT_CHUNK_SIZE);
+
+        let middle = file.len() / 2;
+        file[middle] ^= 0x01;
+        let err = decrypt(&file, &key).unwrap_err();
+        let chunk = ((middle - HEADER_PREFIX_SIZE - STREAM_ID_SIZE - 7) / (DEFAULT_CHUNK_SIZE + TAG_SIZE)) as u64;
+        assert_eq!(corrupt_chunk_index(err), Some(chunk));
+
+        // Wrong key fails on the first chunk
+        let file = encrypt(&data, &key, StorageCipher::Aes256Gcm, DEFAULT_CHUNK_SIZE);
+        let err = decrypt(&file, &test_stk(b"other")).unwrap_err();
+        assert_eq!(corrupt_chunk_index(err), Some(0));
+    }
+
+    #[test]
+    fn test_truncation_detected() {
+        let key = test_stk(b"archive");
+        let data = test_data(5 * 4096 + 100);
+        let file = encrypt(&data, &key, StorageCipher::Aes256Gcm, 4096);
+        let header_len = HEADER_PREFIX_SIZE + STREAM_ID_SIZE + 7;
+
+        // Cut inside the last chunk, exactly at a chunk boundary, and header only
+        for cut in [file.len() - 1, header_len + 5 * (4096 + TAG_SIZE), header_len] {
+            let err = decrypt(&file[..cut], &key).unwrap_err();
+            assert!(corrupt_chunk_index(err).is_some(), "cut at {}", cut);
+        }
+
+        assert!(matches!(
+            EncryptedReader::new(&file[..header_len - 1], &key),
+            Err(StorageError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_integrity_good_file() {
+        let key = test_stk(b"archive");
+        for cipher in [StorageCipher::Aes256Gcm, StorageCipher::XChaCha20Poly1305] {
+            let file = encrypt(&test_data(3 * 4096 + 7), &key, cipher, 4096);
+            verify_integrity(file.as_slice(), &key).unwrap();
+        }
+        let empty = encrypt(&[], &key, StorageCipher::Aes256Gcm, 4096);
+        verify_integrity(empty.as_slice(), &key).unwrap();
+    }
+
+    #[test]
+    fn test_verify_integrity_truncated_fails_at_last_chunk() {
+        let key = test_stk(b"archive");
+        let file = encrypt(&test_data(5 * 4096 + 100), &key, StorageCipher::Aes256Gcm, 4096);
+        let header_len = HEADER_PREFIX_SIZE + STREAM_ID_SIZE + 7;
+
+        // Six chunks; losing the tail of chunk 5 fails it
+        let result = verify_integrity(&file[..file.len() - 1], &key);
+        assert!(matches!(result, Err(StorageError::CorruptChunk { index: 5 })));
+
+        // Dropping chunk 5 entirely: chunk 4 is not marked last
+        let boundary = header_len + 5 * (4096 + TAG_SIZE);
+        let result = verify_integrity(&file[..boundary], &key);
+        assert!(matches!(result, Err(StorageError::CorruptChunk { index: 4 })));
+    }
+
+    #[test]
+    fn test_verify_integrity_bit_flip_fails_at_chunk() {
+        let key = test_stk(b"archive");
+        let mut file = encrypt(&test_data(5 * 4096 + 100), &key, StorageCipher::Aes256Gcm, 4096);
+        let header_len = HEADER_PREFIX_SIZE + STREAM_ID_SIZE + 7;
+
+        file[header_len + 2 * (4096 + TAG_SIZE) + 100] ^= 0x80;
+        let result = verify_integrity(file.as_slice(), &key);
+        assert!(matches!(result, Err(StorageError::CorruptChunk { index: 2 })));
+    }
+
+    #[test]
+    fn test_stream_id_keys_each_stream() {
+        let key = test_stk(b"archive");
+        let data = test_data(3 * 4096);
+        let file = encrypt(&data, &key, StorageCipher::Aes256Gcm, 4096);
+        let id = HEADER_PREFIX_SIZE..HEADER_PREFIX_SIZE + STREAM_ID_SIZE;
+
+        // Same STK and base nonce, different stream ID: different chunk key
+        let header = StreamHeader::read_from(&mut file.as_slice()).unwrap();
+        let mut bytes = header.bytes.clone();
+        bytes[id.start] ^= 0x01;
+        let other = StreamHeader::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(header.base_nonce(), other.base_nonce());
+        assert_ne!(*header.chunk_key(&key).unwrap(), *other.chunk_key(&key).unwrap());
+        assert_ne!(*header.chunk_key(&key).unwrap(), key.as_slice().to_vec());
+
+        // The stream ID selects the key, so changing it fails the first chunk
+        let mut tampered = file.clone();
+        tampered[id.start] ^= 0x01;
+        assert_eq!(corrupt_chunk_index(decrypt(&tampered, &key).unwrap_err()), Some(0));
+        assert_eq!(decrypt(&file, &key).unwrap(), data);
+    }
+
+    #[test]
+    fn test_reencrypt_rotates_key() {
+        let old_key = test_stk(b"archive-v1");
+        let new_key = test_stk(b"archive-v2");
+        let data = test_data(3 * 4096 + 55);
+        let file = encrypt(&data, &old_key, StorageCipher::XChaCha20Poly1305, 4096);
+
+        let rotated = reencrypt(&old_key, &new_key, file.as_slice(), Vec::new()).unwrap();
+        assert_eq!(decrypt(&rotated, &new_key).unwrap(), data);
+        assert_eq!(corrupt_chunk_index(decrypt(&rotated, &old_key).unwrap_err()), Some(0));
+        assert_eq!(EncryptedReader::new(rotated.as_slice(), &new_key).unwrap().cipher(), StorageCipher::XChaCha20Poly1305);
+
+        // Source under a different key is rejected
+        assert!(matches!(
+            reencrypt(&new_key, &old_key, file.as_slice(), Vec::new()),
+            Err(StorageError::CorruptChunk { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_reencrypt_file_replaces_atomically() {
+        let old_key = test_stk(b"archive-v1");
+        let new_key = test_stk(b"archive-v2");
+        let data = test_data(100_000);
+        let path = crate::storage::temp_file::test_path("stream", "rotate", "archive.b4ss");
+        let dir_entries = || fs::read_dir(path.parent().unwrap()).unwrap().count();
+        fs::write(&path, encrypt(&data, &old_key, StorageCipher::Aes256Gcm, DEFAULT_CHUNK_SIZE)).unwrap();
+
+        reencrypt_file(&old_key, &new_key, &path).unwrap();
+        let rotated = fs::read(&path).unwrap();
+        assert_eq!(decrypt(&rotated, &new_key).unwrap(), data);
+        assert!(decrypt(&rotated, &old_key).is_err());
+        assert_eq!(dir_entries(), 1);
+
+        // A failed rotation (wrong old key) leaves the current file untouched
+        assert!(reencrypt_file(&old_key, &new_key, &path).is_err());
+        assert_eq!(fs::read(&path).unwrap(), rotated);
+        assert_eq!(dir_entries(), 1);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,111 @@
+//! Temporary sibling files for atomic replace-by-rename
+//!
+//! Shared by [`reencrypt_file`](super::reencrypt_file) and the file-backed
+//! software HSM. Each temporary file gets a random name and is created with
+//! `create_new`, so concurrent writers never share one and a pre-planted file
+//! or symlink at the temporary path is refused rather than followed. On Unix
+//! the file is created with mode `0600`.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Attempts before giving up on finding an unused temporary name
+const MAX_ATTEMPTS: usize = 8;
+
+/// Creates a new, empty temporary file next to `path`
+///
+/// Returns the temporary path and the file opened for writing. The caller
+/// renames it over `path` once written, or removes it on failure.
+pub(crate) fn create_temp_sibling(path: &Path) -> io::Result<(PathBuf, File)> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+
+    for _ in 0..MAX_ATTEMPTS {
+        let mut suffix = [0u8; 8];
+        crate::crypto::random::fill_random(&mut suffix)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(format!(".{}.tmp", hex::encode(suffix)));
+        let temp_path = path.with_file_name(temp_name);
+
+        match open_new_private(&temp_path) {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "no unused temporary file name"))
+}
+
+fn open_new_private(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// Persists a rename in `path`'s directory (directory entries are synced
+/// separately on Unix)
+#[cfg(unix)]
+pub(crate) fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Fresh `file_name` inside a new, uniquely named directory under the system
+/// temp directory; shared by the file-backed tests
+#[cfg(test)]
+pub(crate) fn test_path(prefix: &str, name: &str, file_name: &str) -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let dir = std::env::temp_dir().join(format!("b4ae-{}-{}-{}-{}", prefix, name, std::process::id(), nanos));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_temp_siblings_are_unique() {
+        let path = test_path("temp", "unique", "data.bin");
+        let (first, _) = create_temp_sibling(&path).unwrap();
+        let (second, _) = create_temp_sibling(&path).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(first.parent(), path.parent());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_temp_sibling_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = test_path("temp", "mode", "data.bin");
+        let (temp_path, _) = create_temp_sibling(&path).unwrap();
+        let mode = fs::metadata(&temp_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,366 @@
+//! Tracing spans for handshakes and message operations (feature `otel`)
+//!
+//! With `otel` enabled, handshake steps, `encrypt_message`/`decrypt_message`
+//! and [`PerformanceMonitor`](crate::performance::PerformanceMonitor)
+//! operations run inside `tracing` spans:
+//!
+//! | span             | fields                                                   |
+//! |------------------|----------------------------------------------------------|
+//! | `b4ae.handshake` | `stage`, `mode`, `cipher_suite`, `session_id_hash`       |
+//! | `b4ae.encrypt`   | `mode`, `cipher_suite`, `session_id_hash`                |
+//! | `b4ae.decrypt`   | `mode`, `cipher_suite`, `session_id_hash`                |
+//! | `b4ae.operation` | `operation`                                              |
+//!
+//! `cipher_suite` is the algorithm list the handshake selected, as stored in
+//! the session (e.g. `Kyber1024+Dilithium5+EcdhX25519+...`); on the handshake
+//! span it is filled in once the response has selected it.
+//!
+//! `session_id_hash` is [`hash_for_audit`](crate::audit::hash_for_audit) of
+//! the session ID, the same value audit events carry, so traces and audit
+//! logs can be joined; raw session and peer IDs are never recorded. On the
+//! handshake span it is filled in by the step that creates the session.
+//!
+//! Spans are exported over OTLP/gRPC by [`init_otlp`], or by composing
+//! [`otlp_tracer_provider`] and [`otel_layer`] into your own subscriber:
+//!
+//! ```ignore
+//! use tracing_subscriber::prelude::*;
+//!
+//! let provider = b4ae::telemetry::otlp_tracer_provider("http://collector:4317")?;
+//! tracing_subscriber::registry()
+//!     .with(b4ae::telemetry::otel_layer(&provider))
+//!     .with(tracing_subscriber::fmt::layer())
+//!     .init();
+//! // ... on shutdown, flush pending spans:
+//! provider.shutdown()?;
+//! ```
+//!
+//! Without the feature the span macros expand to nothing: no span is
+//! created and no field (hashes included) is computed.
+
+#[cfg(feature = "otel")]
+use crate::error::{B4aeError, B4aeResult};
+#[cfg(feature = "otel")]
+use crate::protocol::handshake::AlgorithmId;
+#[cfg(feature = "otel")]
+use opentelemetry::trace::TracerProvider as _;
+#[cfg(feature = "otel")]
+use opentelemetry_otlp::WithExportConfig;
+#[cfg(feature = "otel")]
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+#[cfg(feature = "otel")]
+use tracing_opentelemetry::OpenTelemetryLayer;
+
+/// Default OTLP/gRPC collector endpoint
+#[cfg(feature = "otel")]
+pub const DEFAULT_OTLP_ENDPOINT: &str = "http://localhost:4317";
+
+/// Enter a span until the end of the enclosing block
+macro_rules! otel_span {
+    ($($span:tt)+) => {
+        #[cfg(feature = "otel")]
+        let _otel_span = tracing::info_span!($($span)+).entered();
+    };
+}
+
+/// Record a field declared `tracing::field::Empty` on the current span
+macro_rules! otel_record {
+    ($field:literal, $value:expr) => {
+        #[cfg(feature = "otel")]
+        tracing::Span::current().record($field, $value);
+    };
+}
+
+/// Record the selected algorithms as `cipher_suite` on the current span
+macro_rules! otel_record_cipher_suite {
+    ($algorithms:expr) => {
+        $crate::telemetry::otel_record!("cipher_suite", $crate::telemetry::cipher_suite($algorithms).as_str());
+    };
+}
+
+/// Record the fields taken from a freshly created session on the current span
+macro_rules! otel_record_session {
+    ($session:expr) => {
+        $crate::telemetry::otel_record!(
+            "session_id_hash",
+            $crate::audit::hash_for_audit($session.session_id()).as_str()
+        );
+        $crate::telemetry::otel_record_cipher_suite!($session.selected_algorithms());
+    };
+}
+
+/// Enter a `b4ae.handshake` span for one handshake step
+macro_rules! otel_handshake_span {
+    ($stage:expr, $mode:expr) => {
+        $crate::telemetry::otel_span!(
+            "b4ae.handshake",
+            stage = ?$stage,
+            mode = $mode,
+            cipher_suite = tracing::field::Empty,
+            session_id_hash = tracing::field::Empty,
+        );
+    };
+}
+
+/// Enter a `b4ae.encrypt` / `b4ae.decrypt` span for the session with `$peer`
+macro_rules! otel_message_span {
+    ($name:literal, $sessions:expr, $peer:expr, $mode:expr) => {
+        $crate::telemetry::otel_span!(
+            $name,
+            mode = $mode,
+            cipher_suite = %$sessions
+                .get($peer)
+                .map(|s| $crate::telemetry::cipher_suite(s.selected_algorithms()))
+                .unwrap_or_default(),
+            session_id_hash = %$sessions
+                .get($peer)
+                .map(|s| $crate::audit::hash_for_audit(s.session_id()))
+                .unwrap_or_default(),
+        );
+    };
+}
+
+pub(crate) use otel_handshake_span;
+pub(crate) use otel_message_span;
+pub(crate) use otel_record;
+pub(crate) use otel_record_cipher_suite;
+pub(crate) use otel_record_session;
+pub(crate) use otel_span;
+
+/// `cipher_suite` attribute value for the selected algorithms
+#[cfg(feature = "otel")]
+pub(crate) fn cipher_suite(algorithms: &[AlgorithmId]) -> String {
+    algorithms.iter().map(|a| format!("{:?}", a)).collect::<Vec<_>>().join("+")
+}
+
+/// Tracer provider batching spans to the OTLP/gRPC collector at `endpoint`
+///
+/// Must be called within a Tokio runtime, which carries the gRPC channel.
+/// Call `shutdown` on the provider before exiting to flush pending spans.
+#[cfg(feature = "otel")]
+pub fn otlp_tracer_provider(endpoint: &str) -> B4aeResult<SdkTracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|e| B4aeError::ConfigError(format!("OTLP exporter: {}", e)))?;
+    let resource = opentelemetry_sdk::Resource::builder().with_service_name("b4ae").build();
+    Ok(SdkTracerProvider::builder()
+        .with_resource(resource)
+        .with_batch_exporter(exporter)
+        .build())
+}
+
+/// `tracing` layer exporting spans through `provider`
+#[cfg(feature = "otel")]
+pub fn otel_layer<S>(provider: &SdkTracerProvider) -> OpenTelemetryLayer<S, SdkTracer>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer("b4ae"))
+}
+
+/// Install a global subscriber exporting spans to the OTLP collector at `endpoint`
+///
+/// Same requirements as [`otlp_tracer_provider`]; fails if a global
+/// subscriber is already set.
+#[cfg(feature = "otel")]
+pub fn init_otlp(endpoint: &str) -> B4aeResult<SdkTracerProvider> {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let provider = otlp_tracer_provider(endpoint)?;
+    let subscriber = tracing_subscriber::registry().with(otel_layer(&provider));
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|e| B4aeError::ConfigError(format!("Tracing subscriber: {}", e)))?;
+    Ok(provider)
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use crate::audit::{AuditEvent, AuditSink, MemoryAuditSink};
+    use crate::client::{B4aeClient, B4aeConfig};
+    use crate::performance::PerformanceMonitor;
+    use crate::protocol::handshake::AlgorithmId;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Span as the OTLP exporter receives it
+    #[derive(Debug, Clone)]
+    struct ExportedSpan {
+        name: String,
+        fields: HashMap<String, String>,
+    }
+
+    fn spans(exported: &[ExportedSpan], name: &str) -> Vec<ExportedSpan> {
+        exported.iter().filter(|span| span.name == name).cloned().collect()
+    }
+
+    /// Run `f` with spans exported through [`super::otel_layer`] into memory
+    fn capture<R>(f: impl FnOnce() -> R) -> (Vec<ExportedSpan>, R) {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+        let subscriber = tracing_subscriber::registry().with(super::otel_layer(&provider));
+        let result = tracing::subscriber::with_default(subscriber, f);
+
+        // Collected before `provider` is dropped: shutting it down clears the exporter
+        provider.force_flush().unwrap();
+        let exported = exporter
+            .get_finished_spans()
+            .unwrap()
+            .into_iter()
+            .map(|span| ExportedSpan {
+                name: span.name.to_string(),
+                fields: span
+                    .attributes
+                    .iter()
+                    .map(|kv| (kv.key.to_string(), kv.value.to_string()))
+                    .collect(),
+            })
+            .collect();
+        (exported, result)
+    }
+
+    /// Responder config selecting a reduced algorithm list, so the recorded
+    /// suite can only come from the negotiation
+    fn responder_config() -> B4aeConfig {
+        let mut config = B4aeConfig::default();
+        config.handshake_config.supported_algorithms =
+            vec![AlgorithmId::Kyber1024, AlgorithmId::Dilithium5, AlgorithmId::Aes256Gcm];
+        config
+    }
+
+    const SELECTED_SUITE: &str = "Kyber1024+Dilithium5+Aes256Gcm";
+
+    #[test]
+    fn test_handshake_span_attributes() {
+        let sink = Arc::new(MemoryAuditSink::new());
+        let config = B4aeConfig {
+            audit_sink: Some(sink.clone() as Arc<dyn AuditSink>),
+            ..B4aeConfig::default()
+        };
+        let mut alice = B4aeClient::with_config(config).unwrap();
+        let mut bob = B4aeClient::with_config(responder_config()).unwrap();
+
+        let (exported, ()) = capture(|| {
+            let init = alice.initiate_handshake(b"bob").unwrap();
+            let response = bob.respond_to_handshake(b"alice", init).unwrap();
+            let complete = alice.process_response(b"bob", response).unwrap();
+            bob.complete_handshake(b"alice", complete).unwrap();
+            alice.finalize_initiator(b"bob").unwrap();
+
+            let sealed = alice.encrypt_message(b"bob", b"traced").unwrap();
+            for message in &sealed {
+                bob.decrypt_message(b"alice", message).unwrap();
+            }
+        });
+
+        let handshake = spans(&exported, "b4ae.handshake");
+        assert_eq!(handshake.len(), 5);
+        for span in &handshake {
+            assert_eq!(span.fields["mode"], "v1");
+        }
+
+        // Every step after the initiator's first records the selected suite
+        let (unselected, selected): (Vec<_>, Vec<_>) =
+            handshake.iter().partition(|s| !s.fields.contains_key("cipher_suite"));
+        assert_eq!(unselected.len(), 1);
+        assert_eq!(unselected[0].fields["stage"], "Init");
+        for span in &selected {
+            assert_eq!(span.fields["cipher_suite"], SELECTED_SUITE);
+        }
+
+        // The session-creating steps carry the same hashed session ID as the
+        // audit log, and no peer ID is recorded
+        let session_id_hash = sink
+            .entries()
+            .into_iter()
+            .find_map(|e| match e.event {
+                AuditEvent::SessionCreated { session_id_hash } => Some(session_id_hash),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(session_id_hash.len(), 16);
+        for stage in ["Finalize", "Complete"] {
+            let span = handshake.iter().find(|s| s.fields["stage"] == stage).unwrap();
+            assert_eq!(span.fields["session_id_hash"], session_id_hash);
+        }
+        let all = format!("{:?}", exported);
+        assert!(!all.contains("alice") && !all.contains("bob"));
+
+        let encrypt = spans(&exported, "b4ae.encrypt");
+        assert_eq!(encrypt.len(), 1);
+        assert_eq!(encrypt[0].name, "b4ae.encrypt");
+        assert_eq!(encrypt[0].fields["session_id_hash"], session_id_hash);
+        assert_eq!(encrypt[0].fields["cipher_suite"], SELECTED_SUITE);
+        let decrypt = spans(&exported, "b4ae.decrypt");
+        assert!(!decrypt.is_empty());
+        assert_eq!(decrypt[0].fields["cipher_suite"], SELECTED_SUITE);
+    }
+
+    #[test]
+    fn test_performance_operation_span() {
+        let monitor = PerformanceMonitor::new();
+        let (exported, value) = capture(|| monitor.record_operation("kyber_keygen", || 7));
+        assert_eq!(value, 7);
+        let spans = spans(&exported, "b4ae.operation");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].fields["operation"], "kyber_keygen");
+    }
+
+    #[test]
+    fn test_otlp_tracer_provider() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let provider = runtime
+            .block_on(async { super::otlp_tracer_provider(super::DEFAULT_OTLP_ENDPOINT) })
+            .unwrap();
+        drop(provider);
+        let result = runtime.block_on(async { super::otlp_tracer_provider("not a uri") });
+        assert!(matches!(result, Err(crate::error::B4aeError::ConfigError(_))));
+    }
+
+    #[cfg(feature = "v2_protocol")]
+    #[test]
+    fn test_v2_handshake_span_attributes() {
+        use crate::client_v2::B4aeClientV2;
+        use crate::protocol::v2::AuthenticationMode;
+
+        let mut alice = B4aeClientV2::new(AuthenticationMode::ModeB).unwrap();
+        let mut config = responder_config();
+        config.authentication_mode = Some(AuthenticationMode::ModeB);
+        let mut bob = B4aeClientV2::from_config(&config).unwrap();
+        let (exported, ()) = capture(|| {
+            let negotiation = alice.initiate_mode_negotiation(b"bob").unwrap();
+            let selection = bob.respond_mode_negotiation(b"alice", negotiation).unwrap();
+            alice.complete_mode_negotiation(b"bob", selection).unwrap();
+            let hello = alice.send_client_hello(b"bob").unwrap();
+            let challenge = bob.respond_cookie_challenge(b"alice", hello).unwrap();
+            let init = alice.initiate_handshake_v2(b"bob", challenge).unwrap();
+            let response = bob.respond_to_handshake_v2(b"alice", init).unwrap();
+            let complete = alice.process_response_v2(b"bob", response).unwrap();
+            bob.complete_handshake_v2(b"alice", complete).unwrap();
+            alice.finalize_initiator_v2(b"bob").unwrap();
+            alice.encrypt_message_v2(b"bob", b"traced").unwrap();
+        });
+        assert_eq!(alice.session_mode(b"bob"), Some(AuthenticationMode::ModeB));
+
+        let handshake = spans(&exported, "b4ae.handshake");
+        assert_eq!(handshake.len(), 10);
+        for span in &handshake {
+            assert_eq!(span.fields["mode"], "ModeB");
+        }
+        // From the responder's v1 response on, the selected suite is known
+        let suites: Vec<_> = handshake.iter().filter_map(|s| s.fields.get("cipher_suite")).collect();
+        assert_eq!(suites.len(), 4);
+        assert!(suites.iter().all(|suite| *suite == SELECTED_SUITE));
+        let hashes: Vec<_> = handshake.iter().filter_map(|s| s.fields.get("session_id_hash")).collect();
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(hashes[0], hashes[1]);
+
+        let encrypt = spans(&exported, "b4ae.encrypt");
+        assert_eq!(encrypt[0].fields["mode"], "ModeB");
+        assert_eq!(encrypt[0].fields["cipher_suite"], SELECTED_SUITE);
+        assert_eq!(&encrypt[0].fields["session_id_hash"], hashes[0]);
+    }
+}
@@ -1,9 +1,11 @@
 //! B4AE Safe Time Utilities
 //!
 //! Provides panic-free system time access with graceful fallback for
-//! misconfigured or pre-epoch system clocks.
+//! misconfigured or pre-epoch system clocks, plus a [`Clock`] abstraction so
+//! timestamp and expiry checks can be driven by a [`MockClock`] in tests.
 
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Returns Unix timestamp in seconds. Returns 0 if system time is before Unix epoch.
 #[inline]
@@ -22,3 +24,103 @@ pub fn current_time_millis() -> u64 {
         .unwrap_or(Duration::ZERO)
         .as_millis() as u64
 }
+
+/// Source of wall-clock and monotonic time
+///
+/// Wall-clock time (`now_unix_secs`) is for comparing against timestamps
+/// carried in messages; monotonic time (`now_monotonic`) is for local
+/// timeouts and rotation intervals, which must not jump with the system clock.
+pub trait Clock: Send + Sync {
+    /// Unix timestamp in seconds
+    fn now_unix_secs(&self) -> u64;
+
+    /// Monotonic instant
+    fn now_monotonic(&self) -> Instant;
+}
+
+/// [`Clock`] backed by the operating system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix_secs(&self) -> u64 {
+        current_time_secs()
+    }
+
+    fn now_monotonic(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Manually driven [`Clock`] for tests
+///
+/// Time only moves when [`advance`](Self::advance) or
+/// [`set_unix_secs`](Self::set_unix_secs) is called. Share it as
+/// `Arc<MockClock>` and keep a handle to move time from the test.
+#[derive(Debug)]
+pub struct MockClock {
+    unix_secs: AtomicU64,
+    base: Instant,
+    elapsed_nanos: AtomicU64,
+}
+
+impl MockClock {
+    /// Clock reading `unix_secs`
+    pub fn new(unix_secs: u64) -> Self {
+        MockClock {
+            unix_secs: AtomicU64::new(unix_secs),
+            base: Instant::now(),
+            elapsed_nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// Move both wall-clock and monotonic time forward
+    ///
+    /// Wall-clock time advances in whole seconds (sub-second parts are dropped).
+    pub fn advance(&self, by: Duration) {
+        self.unix_secs.fetch_add(by.as_secs(), Ordering::SeqCst);
+        let nanos = u64::try_from(by.as_nanos()).unwrap_or(u64::MAX);
+        self.elapsed_nanos.fetch_add(nanos, Ordering::SeqCst);
+    }
+
+    /// Jump wall-clock time (e.g. an NTP correction); monotonic time is unaffected
+    pub fn set_unix_secs(&self, unix_secs: u64) {
+        self.unix_secs.store(unix_secs, Ordering::SeqCst);
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new(current_time_secs())
+    }
+}
+
+impl Clock for MockClock {
+    fn now_unix_secs(&self) -> u64 {
+        self.unix_secs.load(Ordering::SeqCst)
+    }
+
+    fn now_monotonic(&self) -> Instant {
+        self.base + Duration::from_nanos(self.elapsed_nanos.load(Ordering::SeqCst))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_moves_only_when_told() {
+        let clock = MockClock::new(1_000);
+        let start = clock.now_monotonic();
+        assert_eq!(clock.now_unix_secs(), 1_000);
+
+        clock.advance(Duration::from_millis(2_500));
+        assert_eq!(clock.now_unix_secs(), 1_002);
+        assert_eq!(clock.now_monotonic() - start, Duration::from_millis(2_500));
+
+        clock.set_unix_secs(10);
+        assert_eq!(clock.now_unix_secs(), 10);
+        assert_eq!(clock.now_monotonic() - start, Duration::from_millis(2_500));
+    }
+}
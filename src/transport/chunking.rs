@@ -0,0 +1,309 @@
+//! Fragmentation and reassembly for ciphertexts larger than [`MAX_PACKET_SIZE`].
+//!
+//! Setiap fragmen membawa header terautentikasi:
+//!
+//! ```text
+//! [msg_id u64 BE][frag_index u16 BE][frag_count u16 BE][data][tag 16]
+//! tag = HMAC-SHA3-256(key, header || data)[..16]
+//! ```
+//!
+//! [`Reassembler`] membatasi jumlah pesan in-flight dan membuang pesan yang
+//! tidak lengkap setelah timeout.
+
+use crate::crypto::random::random_u64;
+use crate::error::{B4aeError, B4aeResult};
+use crate::transport::MAX_PACKET_SIZE;
+use hmac::{Hmac, Mac};
+use sha3::Sha3_256;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Fragment header size (msg_id + frag_index + frag_count)
+pub const FRAGMENT_HEADER_SIZE: usize = 8 + 2 + 2;
+/// Truncated HMAC tag size per fragment
+pub const FRAGMENT_TAG_SIZE: usize = 16;
+/// Maximum data bytes per fragment so each fragment fits in one packet
+pub const MAX_FRAGMENT_DATA: usize = MAX_PACKET_SIZE - FRAGMENT_HEADER_SIZE - FRAGMENT_TAG_SIZE;
+/// Default reassembly timeout
+pub const DEFAULT_REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Default maximum number of partially received messages
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 32;
+/// Completed message IDs remembered to ignore late duplicates
+const COMPLETED_HISTORY: usize = 256;
+
+type HmacSha3 = Hmac<Sha3_256>;
+
+fn fragment_mac(key: &[u8]) -> HmacSha3 {
+    <HmacSha3 as Mac>::new_from_slice(key).expect("HMAC accepts any key length")
+}
+
+/// Splits ciphertexts into authenticated fragments
+pub struct Chunker {
+    key: Vec<u8>,
+    next_msg_id: u64,
+}
+
+impl Chunker {
+    /// Buat chunker dengan kunci autentikasi fragmen
+    pub fn new(key: &[u8]) -> Self {
+        Self {
+            key: key.to_vec(),
+            next_msg_id: random_u64(),
+        }
+    }
+
+    /// Split `data` into fragments of at most [`MAX_PACKET_SIZE`] bytes
+    pub fn split(&mut self, data: &[u8]) -> B4aeResult<Vec<Vec<u8>>> {
+        let frag_count = data.len().div_ceil(MAX_FRAGMENT_DATA).max(1);
+        let frag_count = u16::try_from(frag_count).map_err(|_| {
+            B4aeError::InvalidInput(format!("Message too large to chunk: {} bytes", data.len()))
+        })?;
+        let msg_id = self.next_msg_id;
+        self.next_msg_id = self.next_msg_id.wrapping_add(1);
+
+        let mut fragments = Vec::with_capacity(frag_count as usize);
+        for index in 0..frag_count {
+            let start = index as usize * MAX_FRAGMENT_DATA;
+            let end = (start + MAX_FRAGMENT_DATA).min(data.len());
+            let mut fragment = Vec::with_capacity(FRAGMENT_HEADER_SIZE + (end - start) + FRAGMENT_TAG_SIZE);
+            fragment.extend_from_slice(&msg_id.to_be_bytes());
+            fragment.extend_from_slice(&index.to_be_bytes());
+            fragment.extend_from_slice(&frag_count.to_be_bytes());
+            fragment.extend_from_slice(&data[start..end]);
+            let mut mac = fragment_mac(&self.key);
+            mac.update(&fragment);
+            fragment.extend_from_slice(&mac.finalize().into_bytes()[..FRAGMENT_TAG_SIZE]);
+            fragments.push(fragment);
+        }
+        Ok(fragments)
+    }
+}
+
+struct PartialMessage {
+    frag_count: u16,
+    fragments: HashMap<u16, Vec<u8>>,
+    created: Instant,
+}
+
+/// Reassembles fragments per `msg_id`, tolerating reordering and duplicates
+pub struct Reassembler {
+    key: Vec<u8>,
+    timeout: Duration,
+    max_in_flight: usize,
+    pending: HashMap<u64, PartialMessage>,
+    completed: VecDeque<u64>,
+    completed_set: HashSet<u64>,
+}
+
+impl Reassembler {
+    /// Buat reassembler dengan timeout dan batas pesan in-flight
+    pub fn new(key: &[u8], timeout: Duration, max_in_flight: usize) -> Self {
+        Self {
+            key: key.to_vec(),
+            timeout,
+            max_in_flight,
+            pending: HashMap::new(),
+            completed: VecDeque::new(),
+            completed_set: HashSet::new(),
+        }
+    }
+
+    /// Accept one fragment; returns the full message once all fragments arrived.
+    ///
+    /// Duplicate fragments and fragments of already completed messages are
+    /// ignored (`Ok(None)`). Tampered fragments are rejected.
+    pub fn push(&mut self, fragment: &[u8], now: Instant) -> B4aeResult<Option<Vec<u8>>> {
+        if fragment.len() < FRAGMENT_HEADER_SIZE + FRAGMENT_TAG_SIZE || fragment.len() > MAX_PACKET_SIZE {
+            return Err(B4aeError::InvalidInput(format!("Invalid fragment size: {}", fragment.len())));
+        }
+        let (body, tag) = fragment.split_at(fragment.len() - FRAGMENT_TAG_SIZE);
+        let mut mac = fragment_mac(&self.key);
+        mac.update(body);
+        mac.verify_truncated_left(tag).map_err(|_| B4aeError::AuthenticationFailed)?;
+
+        let msg_id = u64::from_be_bytes(body[0..8].try_into().expect("8-byte slice"));
+        let frag_index = u16::from_be_bytes([body[8], body[9]]);
+        let frag_count = u16::from_be_bytes([body[10], body[11]]);
+        let data = &body[FRAGMENT_HEADER_SIZE..];
+        if frag_count == 0 || frag_index >= frag_count {
+            return Err(B4aeError::InvalidInput(format!(
+                "Invalid fragment index {}/{}",
+                frag_index, frag_count
+            )));
+        }
+        if self.completed_set.contains(&msg_id) {
+            return Ok(None);
+        }
+
+        if !self.pending.contains_key(&msg_id) {
+            if self.pending.len() >= self.max_in_flight {
+                return Err(B4aeError::NetworkError(format!(
+                    "Too many messages in flight (max {})",
+                    self.max_in_flight
+                )));
+            }
+            self.pending.insert(
+                msg_id,
+                PartialMessage { frag_count, fragments: HashMap::new(), created: now },
+            );
+        }
+        let partial = self.pending.get_mut(&msg_id).expect("inserted above");
+        if partial.frag_count != frag_count {
+            return Err(B4aeError::ProtocolError(format!(
+                "Fragment count mismatch for message {}: {} != {}",
+                msg_id, frag_count, partial.frag_count
+            )));
+        }
+        partial.fragments.entry(frag_index).or_insert_with(|| data.to_vec());
+        if partial.fragments.len() < frag_count as usize {
+            return Ok(None);
+        }
+
+        let mut partial = self.pending.remove(&msg_id).expect("present");
+        let message = (0..frag_count)
+            .flat_map(|i| partial.fragments.remove(&i).unwrap_or_default())
+            .collect();
+        self.remember_completed(msg_id);
+        Ok(Some(message))
+    }
+
+    /// Drop messages older than the timeout.
+    ///
+    /// Returns an error naming the incomplete messages if any were dropped.
+    pub fn expire(&mut self, now: Instant) -> B4aeResult<()> {
+        let timeout = self.timeout;
+        let mut expired: Vec<String> = Vec::new();
+        self.pending.retain(|msg_id, partial| {
+            let alive = now.saturating_duration_since(partial.created) < timeout;
+            if !alive {
+                expired.push(format!(
+                    "{} ({}/{} fragments)",
+                    msg_id,
+                    partial.fragments.len(),
+                    partial.frag_count
+                ));
+            }
+            alive
+        });
+        if expired.is_empty() {
+            Ok(())
+        } else {
+            Err(B4aeError::NetworkError(format!(
+                "Reassembly timed out for messages: {}",
+                expired.join(", ")
+            )))
+        }
+    }
+
+    /// Number of partially received messages
+    pub fn in_flight(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn remember_completed(&mut self, msg_id: u64) {
+        self.completed.push_back(msg_id);
+        self.completed_set.insert(msg_id);
+        if self.completed.len() > COMPLETED_HISTORY {
+            if let Some(old) = self.completed.pop_front() {
+                self.completed_set.remove(&old);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [0x24; 32];
+
+    fn message(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn reassembler() -> Reassembler {
+        Reassembler::new(&KEY, DEFAULT_REASSEMBLY_TIMEOUT, DEFAULT_MAX_IN_FLIGHT)
+    }
+
+    #[test]
+    fn test_split_10kb_into_packets() {
+        let data = message(10 * 1024);
+        let fragments = Chunker::new(&KEY).split(&data).unwrap();
+        assert_eq!(fragments.len(), 8);
+        assert!(fragments.iter().all(|f| f.len() <= MAX_PACKET_SIZE));
+
+        let mut reassembler = reassembler();
+        let now = Instant::now();
+        let (last, rest) = fragments.split_last().unwrap();
+        for f in rest {
+            assert_eq!(reassembler.push(f, now).unwrap(), None);
+        }
+        assert_eq!(reassembler.push(last, now).unwrap(), Some(data));
+        assert_eq!(reassembler.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_reordered_and_duplicate_delivery() {
+        let data = message(10 * 1024);
+        let fragments = Chunker::new(&KEY).split(&data).unwrap();
+        let mut reassembler = reassembler();
+        let now = Instant::now();
+
+        let order = [7, 3, 3, 0, 5, 1, 6, 0, 2];
+        for &i in &order {
+            assert_eq!(reassembler.push(&fragments[i], now).unwrap(), None);
+        }
+        assert_eq!(reassembler.push(&fragments[4], now).unwrap(), Some(data));
+
+        // Late duplicate of a completed message is ignored
+        assert_eq!(reassembler.push(&fragments[2], now).unwrap(), None);
+        assert_eq!(reassembler.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_dropped_fragment_times_out() {
+        let fragments = Chunker::new(&KEY).split(&message(5000)).unwrap();
+        let mut reassembler = Reassembler::new(&KEY, Duration::from_secs(1), 4);
+        let start = Instant::now();
+        for f in fragments.iter().skip(1) {
+            assert_eq!(reassembler.push(f, start).unwrap(), None);
+        }
+        assert!(reassembler.expire(start + Duration::from_millis(500)).is_ok());
+        assert!(reassembler.expire(start + Duration::from_secs(2)).is_err());
+        assert_eq!(reassembler.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_rejects_tampered_header() {
+        let mut fragments = Chunker::new(&KEY).split(&message(3000)).unwrap();
+        fragments[0][9] ^= 0x01; // frag_index
+        assert!(matches!(
+            reassembler().push(&fragments[0], Instant::now()),
+            Err(B4aeError::AuthenticationFailed)
+        ));
+    }
+
+    #[test]
+    fn test_max_in_flight_bounds_memory() {
+        let mut chunker = Chunker::new(&KEY);
+        let mut reassembler = Reassembler::new(&KEY, DEFAULT_REASSEMBLY_TIMEOUT, 2);
+        let now = Instant::now();
+        for _ in 0..2 {
+            let fragments = chunker.split(&message(3000)).unwrap();
+            assert_eq!(reassembler.push(&fragments[0], now).unwrap(), None);
+        }
+        let fragments = chunker.split(&message(3000)).unwrap();
+        assert!(reassembler.push(&fragments[0], now).is_err());
+    }
+
+    #[test]
+    fn test_small_and_empty_messages() {
+        let mut chunker = Chunker::new(&KEY);
+        let mut reassembler = reassembler();
+        for len in [0, 1, MAX_FRAGMENT_DATA] {
+            let fragments = chunker.split(&message(len)).unwrap();
+            assert_eq!(fragments.len(), 1);
+            assert_eq!(reassembler.push(&fragments[0], Instant::now()).unwrap(), Some(message(len)));
+        }
+    }
+}
@@ -1,14 +1,25 @@
 //! B4AE Transport Layer
 //!
 //! Abstraksi untuk pengiriman data terenkripsi B4AE.
-//! Mendukung integrasi dengan ELARA Protocol untuk transport UDP/NAT traversal.
+//! Mendukung integrasi dengan ELARA Protocol untuk transport UDP/NAT traversal,
+//! serta QUIC (feature `quic`). Koneksi stream lewat SOCKS5/Tor tersedia di
+//! [`socks5`] (feature `async` + `proxy`).
 
 /// Batas ukuran payload per paket (mengikuti ELARA MAX_FRAME_SIZE).
-/// Paket lebih besar dari ini perlu di-chunk.
+/// Paket lebih besar dari ini perlu di-chunk (lihat [`chunking`]).
 pub const MAX_PACKET_SIZE: usize = 1400;
 
+pub mod chunking;
+pub use chunking::{Chunker, Reassembler};
+
 #[cfg(feature = "elara")]
 pub mod elara;
 
 #[cfg(all(feature = "elara", feature = "proxy"))]
 pub mod proxy;
+
+#[cfg(all(feature = "async", feature = "proxy"))]
+pub mod socks5;
+
+#[cfg(feature = "quic")]
+pub mod quic;
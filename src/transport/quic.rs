@@ -0,0 +1,264 @@
+//! QUIC Transport untuk B4AE
+//!
+//! Menggunakan [quinn](https://github.com/quinn-rs/quinn) untuk congestion
+//! control dan multiplexing. TLS milik QUIC hanya dipakai sebagai syarat
+//! protokol (sertifikat self-signed sekali pakai, tanpa verifikasi);
+//! autentikasi dan kerahasiaan end-to-end berasal dari handshake B4AE yang
+//! berjalan di dalam bidirectional stream.
+//!
+//! Frame di dalam stream: `[len u32 BE][bytes]`.
+
+use crate::client::B4aeClient;
+use crate::error::{B4aeError, B4aeResult};
+use crate::protocol::message::EncryptedMessage;
+use bincode::config::DefaultOptions;
+use bincode::Options;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, PrivatePkcs8KeyDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Server name presented in the QUIC TLS handshake
+const QUIC_SERVER_NAME: &str = "b4ae";
+/// Maximum frame size (DoS mitigation; selaras dengan BINCODE_LIMIT elara_node)
+pub const MAX_FRAME_SIZE: usize = 128 * 1024;
+
+impl From<quinn::ConnectionError> for B4aeError {
+    fn from(e: quinn::ConnectionError) -> Self {
+        B4aeError::NetworkError(format!("QUIC connection error: {}", e))
+    }
+}
+
+impl From<quinn::ConnectError> for B4aeError {
+    fn from(e: quinn::ConnectError) -> Self {
+        B4aeError::NetworkError(format!("QUIC connect error: {}", e))
+    }
+}
+
+impl From<quinn::WriteError> for B4aeError {
+    fn from(e: quinn::WriteError) -> Self {
+        B4aeError::NetworkError(format!("QUIC stream write error: {}", e))
+    }
+}
+
+impl From<quinn::ReadExactError> for B4aeError {
+    fn from(e: quinn::ReadExactError) -> Self {
+        B4aeError::NetworkError(format!("QUIC stream read error: {}", e))
+    }
+}
+
+/// Accepts any server certificate; peers are authenticated by the B4AE handshake
+#[derive(Debug)]
+struct B4aeHandshakeAuthenticates(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for B4aeHandshakeAuthenticates {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn config_error(e: impl std::fmt::Display) -> B4aeError {
+    B4aeError::ConfigError(format!("QUIC configuration failed: {}", e))
+}
+
+fn server_config() -> B4aeResult<quinn::ServerConfig> {
+    let cert = rcgen::generate_simple_self_signed(vec![QUIC_SERVER_NAME.to_string()])
+        .map_err(config_error)?;
+    let key = PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der());
+    quinn::ServerConfig::with_single_cert(vec![cert.cert.der().clone()], key.into())
+        .map_err(config_error)
+}
+
+fn client_config() -> B4aeResult<quinn::ClientConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let tls = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .map_err(config_error)?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(B4aeHandshakeAuthenticates(provider)))
+        .with_no_client_auth();
+    let quic = quinn::crypto::rustls::QuicClientConfig::try_from(tls).map_err(config_error)?;
+    Ok(quinn::ClientConfig::new(Arc::new(quic)))
+}
+
+/// QUIC endpoint yang bisa menerima dan membuka koneksi
+pub struct QuicTransport {
+    endpoint: quinn::Endpoint,
+}
+
+impl QuicTransport {
+    /// Bind endpoint (server + client) ke alamat lokal
+    pub fn bind(addr: SocketAddr) -> B4aeResult<Self> {
+        let mut endpoint = quinn::Endpoint::server(server_config()?, addr)
+            .map_err(|e| B4aeError::NetworkError(format!("QUIC bind failed: {}", e)))?;
+        endpoint.set_default_client_config(client_config()?);
+        Ok(Self { endpoint })
+    }
+
+    /// Alamat lokal
+    pub fn local_addr(&self) -> B4aeResult<SocketAddr> {
+        self.endpoint
+            .local_addr()
+            .map_err(|e| B4aeError::NetworkError(e.to_string()))
+    }
+
+    /// Buka koneksi ke peer dan bidirectional stream untuk frame B4AE
+    pub async fn connect(&self, addr: SocketAddr) -> B4aeResult<QuicStream> {
+        let connection = self.endpoint.connect(addr, QUIC_SERVER_NAME)?.await?;
+        let (send, recv) = connection.open_bi().await?;
+        Ok(QuicStream { connection, send, recv })
+    }
+
+    /// Terima koneksi berikutnya dan stream pertamanya
+    pub async fn accept(&self) -> B4aeResult<QuicStream> {
+        let incoming = self
+            .endpoint
+            .accept()
+            .await
+            .ok_or_else(|| B4aeError::NetworkError("QUIC endpoint closed".to_string()))?;
+        let connection = incoming.await?;
+        let (send, recv) = connection.accept_bi().await?;
+        Ok(QuicStream { connection, send, recv })
+    }
+
+    /// Tutup endpoint
+    pub fn close(&self) {
+        self.endpoint.close(0u32.into(), b"closed");
+    }
+}
+
+/// Bidirectional QUIC stream membawa frame B4AE
+pub struct QuicStream {
+    connection: quinn::Connection,
+    send: quinn::SendStream,
+    recv: quinn::RecvStream,
+}
+
+impl QuicStream {
+    /// Alamat peer
+    pub fn remote_addr(&self) -> SocketAddr {
+        self.connection.remote_address()
+    }
+
+    /// Kirim satu frame
+    pub async fn send_frame(&mut self, data: &[u8]) -> B4aeResult<()> {
+        if data.len() > MAX_FRAME_SIZE {
+            return Err(B4aeError::InvalidInput(format!(
+                "Frame too large: {} > {}",
+                data.len(),
+                MAX_FRAME_SIZE
+            )));
+        }
+        self.send.write_all(&(data.len() as u32).to_be_bytes()).await?;
+        self.send.write_all(data).await?;
+        Ok(())
+    }
+
+    /// Terima satu frame
+    pub async fn recv_frame(&mut self) -> B4aeResult<Vec<u8>> {
+        let mut len = [0u8; 4];
+        self.recv.read_exact(&mut len).await?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_FRAME_SIZE {
+            return Err(B4aeError::ProtocolError(format!(
+                "Frame too large: {} > {}",
+                len, MAX_FRAME_SIZE
+            )));
+        }
+        let mut frame = vec![0u8; len];
+        self.recv.read_exact(&mut frame).await?;
+        Ok(frame)
+    }
+
+    async fn send_value<T: serde::Serialize>(&mut self, value: &T) -> B4aeResult<()> {
+        let bytes = bincode::serialize(value).map_err(|e| B4aeError::ProtocolError(e.to_string()))?;
+        self.send_frame(&bytes).await
+    }
+
+    async fn recv_value<T: serde::de::DeserializeOwned>(&mut self) -> B4aeResult<T> {
+        let bytes = self.recv_frame().await?;
+        // Same encoding as `bincode::serialize`, plus a size limit
+        DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(MAX_FRAME_SIZE as u64)
+            .deserialize(&bytes)
+            .map_err(|e| B4aeError::ProtocolError(e.to_string()))
+    }
+
+    /// Jalankan handshake B4AE sebagai initiator di dalam stream
+    pub async fn handshake_initiator(&mut self, client: &mut B4aeClient, peer_id: &[u8]) -> B4aeResult<()> {
+        let init = client.initiate_handshake(peer_id)?;
+        self.send_value(&init).await?;
+        let response = self.recv_value().await?;
+        let complete = client.process_response(peer_id, response)?;
+        self.send_value(&complete).await?;
+        client.finalize_initiator(peer_id)
+    }
+
+    /// Jalankan handshake B4AE sebagai responder di dalam stream
+    pub async fn handshake_responder(&mut self, client: &mut B4aeClient, peer_id: &[u8]) -> B4aeResult<()> {
+        let init = self.recv_value().await?;
+        let response = client.respond_to_handshake(peer_id, init)?;
+        self.send_value(&response).await?;
+        let complete = self.recv_value().await?;
+        client.complete_handshake(peer_id, complete)
+    }
+
+    /// Enkripsi dan kirim pesan (termasuk dummy traffic bila aktif)
+    pub async fn send_message(&mut self, client: &mut B4aeClient, peer_id: &[u8], plaintext: &[u8]) -> B4aeResult<()> {
+        for encrypted in client.encrypt_message(peer_id, plaintext)? {
+            self.send_value(&encrypted).await?;
+        }
+        Ok(())
+    }
+
+    /// Terima dan dekripsi pesan berikutnya; dummy traffic dan ACK dilewati
+    /// berdasarkan tipe pesannya, sehingga pesan asli yang kosong tetap diterima
+    pub async fn recv_message(&mut self, client: &mut B4aeClient, peer_id: &[u8]) -> B4aeResult<Vec<u8>> {
+        loop {
+            let encrypted: EncryptedMessage = self.recv_value().await?;
+            if let Some(plaintext) = client.open_message(peer_id, &encrypted)? {
+                return Ok(plaintext);
+            }
+        }
+    }
+
+    /// Tandai akhir stream kirim
+    pub fn finish(&mut self) -> B4aeResult<()> {
+        self.send
+            .finish()
+            .map_err(|e| B4aeError::NetworkError(format!("QUIC stream finish error: {}", e)))
+    }
+}
@@ -0,0 +1,491 @@
+//! SOCKS5 stream connector for B4AE.
+//!
+//! Buka koneksi TCP ke peer melalui proxy SOCKS5 (RFC 1928, perintah CONNECT)
+//! sebelum handshake B4AE, lalu bungkus stream menjadi [`AsyncTransport`]
+//! dengan framing length-prefix.
+//!
+//! - `socks5://host:port`: nama tujuan di-resolve secara lokal, proxy
+//!   menerima alamat IP.
+//! - `socks5h://host:port`: nama tujuan dikirim apa adanya dan di-resolve
+//!   oleh proxy. Wajib untuk Tor (tidak ada DNS leak, alamat `.onion` bisa
+//!   dipakai).
+//!
+//! Bila `AnonymizationConfig::use_tor` aktif, DNS selalu di-resolve oleh
+//! proxy walaupun skema URL `socks5://`. Tanpa `proxy_url`, koneksi dibuka
+//! langsung, kecuali `use_tor` aktif: konfigurasi itu ditolak agar trafik
+//! tidak pernah keluar tanpa Tor.
+//!
+//! ```rust,ignore
+//! // config.protocol_config.anonymization.proxy_url = Some("socks5h://127.0.0.1:9050")
+//! let mut alice = socks5::connect_initiator(client, b"bob", "bobexample.onion:7000").await?;
+//! alice.send(b"hello").await?;
+//! ```
+
+use crate::client::async_transport::MAX_FRAME_SIZE;
+use crate::client::{AsyncB4aeClient, AsyncTransport, B4aeClient};
+use crate::error::{B4aeError, B4aeResult};
+use crate::protocol::AnonymizationConfig;
+use async_trait::async_trait;
+use std::net::{IpAddr, SocketAddr};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const SOCKS_VERSION: u8 = 0x05;
+const AUTH_NONE: u8 = 0x00;
+const AUTH_NO_ACCEPTABLE: u8 = 0xFF;
+const CMD_CONNECT: u8 = 0x01;
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+
+/// Panjang prefix frame pada [`StreamTransport`]
+const FRAME_HEADER: usize = 4;
+
+/// Proxy SOCKS5 hasil parsing `proxy_url`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Socks5Proxy {
+    /// Host proxy
+    pub host: String,
+    /// Port proxy
+    pub port: u16,
+    /// Resolve nama tujuan di proxy (`socks5h://`)
+    pub remote_dns: bool,
+}
+
+impl Socks5Proxy {
+    /// Parse `socks5://host:port` atau `socks5h://host:port`
+    pub fn parse(url: &str) -> B4aeResult<Self> {
+        let (rest, remote_dns) = if let Some(rest) = url.strip_prefix("socks5h://") {
+            (rest, true)
+        } else if let Some(rest) = url.strip_prefix("socks5://") {
+            (rest, false)
+        } else {
+            return Err(B4aeError::ConfigError(format!(
+                "Unsupported proxy URL (expected socks5:// or socks5h://): {}",
+                url
+            )));
+        };
+        let rest = rest.trim_end_matches('/');
+        let (host, port) = split_host_port(rest).ok_or_else(|| {
+            B4aeError::ConfigError(format!("Invalid proxy address: {}", rest))
+        })?;
+        Ok(Self { host: host.to_string(), port, remote_dns })
+    }
+
+    /// Proxy dari konfigurasi anonymization; `None` bila tanpa `proxy_url`
+    ///
+    /// `use_tor` tanpa `proxy_url` gagal dengan `ConfigError` (fail closed).
+    pub fn from_config(config: &AnonymizationConfig) -> B4aeResult<Option<Self>> {
+        let Some(url) = &config.proxy_url else {
+            if config.use_tor {
+                return Err(B4aeError::ConfigError(
+                    "Tor anonymization requires a proxy_url (e.g. socks5://127.0.0.1:9050)".to_string(),
+                ));
+            }
+            return Ok(None);
+        };
+        let mut proxy = Self::parse(url)?;
+        proxy.remote_dns |= config.use_tor;
+        Ok(Some(proxy))
+    }
+
+    /// Buka koneksi ke `target` (`host:port`) lewat proxy
+    pub async fn connect(&self, target: &str) -> B4aeResult<TcpStream> {
+        let (host, port) = split_host_port(target).ok_or_else(|| {
+            B4aeError::InvalidInput(format!("Invalid target address: {}", target))
+        })?;
+        let destination = match host.parse::<IpAddr>() {
+            Ok(ip) => Destination::Ip(SocketAddr::new(ip, port)),
+            Err(_) if self.remote_dns => Destination::Domain(host.to_string(), port),
+            Err(_) => Destination::Ip(resolve(host, port).await?),
+        };
+
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port))
+            .await
+            .map_err(|e| B4aeError::NetworkError(format!("SOCKS5 proxy unreachable: {}", e)))?;
+        negotiate(&mut stream, &destination).await?;
+        Ok(stream)
+    }
+}
+
+/// Buka koneksi TCP ke `target`, lewat proxy bila `proxy_url` diset
+pub async fn connect(config: &AnonymizationConfig, target: &str) -> B4aeResult<TcpStream> {
+    match Socks5Proxy::from_config(config)? {
+        Some(proxy) => proxy.connect(target).await,
+        None => TcpStream::connect(target)
+            .await
+            .map_err(|e| B4aeError::NetworkError(format!("Connect to {} failed: {}", target, e))),
+    }
+}
+
+/// Hubungkan ke `target` sesuai anonymization config `client`, lalu jalankan handshake sebagai initiator
+pub async fn connect_initiator(
+    client: B4aeClient,
+    peer_id: &[u8],
+    target: &str,
+) -> B4aeResult<AsyncB4aeClient<StreamTransport<TcpStream>>> {
+    let stream = connect(&client.config().protocol_config.anonymization, target).await?;
+    let mut session = AsyncB4aeClient::initiator(client, peer_id, StreamTransport::new(stream));
+    session.handshake().await?;
+    Ok(session)
+}
+
+enum Destination {
+    Ip(SocketAddr),
+    Domain(String, u16),
+}
+
+fn split_host_port(addr: &str) -> Option<(&str, u16)> {
+    let (host, port) = addr.rsplit_once(':')?;
+    let host = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+    if host.is_empty() {
+        return None;
+    }
+    Some((host, port.parse().ok()?))
+}
+
+async fn resolve(host: &str, port: u16) -> B4aeResult<SocketAddr> {
+    tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| B4aeError::NetworkError(format!("DNS lookup for {} failed: {}", host, e)))?
+        .next()
+        .ok_or_else(|| B4aeError::NetworkError(format!("DNS lookup for {} returned no address", host)))
+}
+
+/// Greeting (tanpa autentikasi) lalu CONNECT
+async fn negotiate<S>(stream: &mut S, destination: &Destination) -> B4aeResult<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream.write_all(&[SOCKS_VERSION, 1, AUTH_NONE]).await.map_err(proxy_io)?;
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).await.map_err(proxy_io)?;
+    match choice {
+        [SOCKS_VERSION, AUTH_NONE] => {}
+        [SOCKS_VERSION, AUTH_NO_ACCEPTABLE] => {
+            return Err(B4aeError::NetworkError(
+                "SOCKS5 proxy requires unsupported authentication".to_string(),
+            ))
+        }
+        [version, method] => {
+            return Err(B4aeError::ProtocolError(format!(
+                "Invalid SOCKS5 greeting reply: version {:#04x}, method {:#04x}",
+                version, method
+            )))
+        }
+    }
+
+    let mut request = vec![SOCKS_VERSION, CMD_CONNECT, 0x00];
+    match destination {
+        Destination::Ip(SocketAddr::V4(addr)) => {
+            request.push(ATYP_IPV4);
+            request.extend_from_slice(&addr.ip().octets());
+            request.extend_from_slice(&addr.port().to_be_bytes());
+        }
+        Destination::Ip(SocketAddr::V6(addr)) => {
+            request.push(ATYP_IPV6);
+            request.extend_from_slice(&addr.ip().octets());
+            request.extend_from_slice(&addr.port().to_be_bytes());
+        }
+        Destination::Domain(host, port) => {
+            let len = u8::try_from(host.len()).map_err(|_| {
+                B4aeError::InvalidInput(format!("Target host name too long: {} bytes", host.len()))
+            })?;
+            request.push(ATYP_DOMAIN);
+            request.push(len);
+            request.extend_from_slice(host.as_bytes());
+            request.extend_from_slice(&port.to_be_bytes());
+        }
+    }
+    stream.write_all(&request).await.map_err(proxy_io)?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await.map_err(proxy_io)?;
+    if reply[0] != SOCKS_VERSION {
+        return Err(B4aeError::ProtocolError(format!(
+            "Invalid SOCKS5 reply version: {:#04x}",
+            reply[0]
+        )));
+    }
+    if reply[1] != 0x00 {
+        return Err(B4aeError::NetworkError(format!(
+            "SOCKS5 CONNECT rejected: {}",
+            reply_message(reply[1])
+        )));
+    }
+    // Alamat bind dari proxy tidak dipakai, cukup dibuang
+    let bound_len = match reply[3] {
+        ATYP_IPV4 => 4,
+        ATYP_IPV6 => 16,
+        ATYP_DOMAIN => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await.map_err(proxy_io)?;
+            len[0] as usize
+        }
+        other => {
+            return Err(B4aeError::ProtocolError(format!(
+                "Invalid SOCKS5 reply address type: {:#04x}",
+                other
+            )))
+        }
+    };
+    let mut bound = vec![0u8; bound_len + 2];
+    stream.read_exact(&mut bound).await.map_err(proxy_io)?;
+    Ok(())
+}
+
+fn proxy_io(e: std::io::Error) -> B4aeError {
+    B4aeError::NetworkError(format!("SOCKS5 negotiation failed: {}", e))
+}
+
+fn reply_message(code: u8) -> &'static str {
+    match code {
+        0x01 => "general SOCKS server failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+/// [`AsyncTransport`] di atas byte stream: tiap frame `len (u32 BE) || frame`.
+///
+/// `recv` cancel-safe: byte yang sudah terbaca disimpan di buffer internal.
+pub struct StreamTransport<S> {
+    stream: S,
+    buffer: Vec<u8>,
+}
+
+impl<S> StreamTransport<S> {
+    /// Bungkus stream yang sudah terhubung
+    pub fn new(stream: S) -> Self {
+        Self { stream, buffer: Vec::new() }
+    }
+
+    /// Ambil kembali stream (byte yang sudah di-buffer ikut dibuang)
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    fn take_frame(&mut self) -> B4aeResult<Option<Vec<u8>>> {
+        if self.buffer.len() < FRAME_HEADER {
+            return Ok(None);
+        }
+        let mut len = [0u8; FRAME_HEADER];
+        len.copy_from_slice(&self.buffer[..FRAME_HEADER]);
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_FRAME_SIZE {
+            return Err(B4aeError::ProtocolError(format!(
+                "Frame too large: {} > {}",
+                len, MAX_FRAME_SIZE
+            )));
+        }
+        if self.buffer.len() < FRAME_HEADER + len {
+            return Ok(None);
+        }
+        let frame = self.buffer[FRAME_HEADER..FRAME_HEADER + len].to_vec();
+        self.buffer.drain(..FRAME_HEADER + len);
+        Ok(Some(frame))
+    }
+}
+
+#[async_trait]
+impl<S> AsyncTransport for StreamTransport<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    async fn send(&mut self, frame: Vec<u8>) -> B4aeResult<()> {
+        if frame.len() > MAX_FRAME_SIZE {
+            return Err(B4aeError::InvalidInput(format!(
+                "Frame too large: {} > {}",
+                frame.len(),
+                MAX_FRAME_SIZE
+            )));
+        }
+        let mut out = Vec::with_capacity(FRAME_HEADER + frame.len());
+        out.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+        out.extend_from_slice(&frame);
+        self.stream
+            .write_all(&out)
+            .await
+            .map_err(|e| B4aeError::NetworkError(format!("Stream write failed: {}", e)))
+    }
+
+    async fn recv(&mut self) -> B4aeResult<Vec<u8>> {
+        loop {
+            if let Some(frame) = self.take_frame()? {
+                return Ok(frame);
+            }
+            let read = self
+                .stream
+                .read_buf(&mut self.buffer)
+                .await
+                .map_err(|e| B4aeError::NetworkError(format!("Stream read failed: {}", e)))?;
+            if read == 0 {
+                return Err(B4aeError::NetworkError("peer closed".to_string()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::SecurityProfile;
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    /// Mock SOCKS5 server: satu koneksi, catat request CONNECT, lalu relay ke `upstream`
+    async fn mock_proxy(
+        reply_code: u8,
+        upstream: Option<SocketAddr>,
+    ) -> (SocketAddr, JoinHandle<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let (mut client, _) = listener.accept().await.unwrap();
+            let mut greeting = [0u8; 3];
+            client.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [SOCKS_VERSION, 1, AUTH_NONE]);
+            client.write_all(&[SOCKS_VERSION, AUTH_NONE]).await.unwrap();
+
+            let mut request = vec![0u8; 4];
+            client.read_exact(&mut request).await.unwrap();
+            let rest = match request[3] {
+                ATYP_IPV4 => 4 + 2,
+                ATYP_IPV6 => 16 + 2,
+                ATYP_DOMAIN => {
+                    let mut len = [0u8; 1];
+                    client.read_exact(&mut len).await.unwrap();
+                    request.push(len[0]);
+                    len[0] as usize + 2
+                }
+                other => panic!("unexpected ATYP {}", other),
+            };
+            let mut tail = vec![0u8; rest];
+            client.read_exact(&mut tail).await.unwrap();
+            request.extend_from_slice(&tail);
+
+            client
+                .write_all(&[SOCKS_VERSION, reply_code, 0x00, ATYP_IPV4, 0, 0, 0, 0, 0, 0])
+                .await
+                .unwrap();
+            if let Some(upstream) = upstream {
+                let mut server = TcpStream::connect(upstream).await.unwrap();
+                let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+            }
+            request
+        });
+        (addr, handle)
+    }
+
+    fn anonymization(url: String, use_tor: bool) -> AnonymizationConfig {
+        AnonymizationConfig { proxy_url: Some(url), use_tor }
+    }
+
+    #[test]
+    fn test_parse_proxy_url() {
+        let proxy = Socks5Proxy::parse("socks5://127.0.0.1:9050").unwrap();
+        assert_eq!(proxy, Socks5Proxy { host: "127.0.0.1".into(), port: 9050, remote_dns: false });
+        assert!(Socks5Proxy::parse("socks5h://localhost:9050/").unwrap().remote_dns);
+        assert_eq!(Socks5Proxy::parse("socks5://[::1]:1080").unwrap().host, "::1");
+
+        assert!(Socks5Proxy::parse("http://127.0.0.1:8080").is_err());
+        assert!(Socks5Proxy::parse("socks5://127.0.0.1").is_err());
+        assert!(Socks5Proxy::parse("socks5://:9050").is_err());
+
+        let tor = Socks5Proxy::from_config(&anonymization("socks5://127.0.0.1:9050".into(), true));
+        assert!(tor.unwrap().unwrap().remote_dns);
+        assert!(Socks5Proxy::from_config(&AnonymizationConfig::default()).unwrap().is_none());
+
+        // Tor without a proxy must not fall back to a direct connection
+        let no_proxy = AnonymizationConfig { proxy_url: None, use_tor: true };
+        assert!(matches!(Socks5Proxy::from_config(&no_proxy), Err(B4aeError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_tor_without_proxy_does_not_connect_directly() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = listener.local_addr().unwrap().to_string();
+        let no_proxy = AnonymizationConfig { proxy_url: None, use_tor: true };
+        assert!(matches!(connect(&no_proxy, &target).await, Err(B4aeError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_connect_negotiates_ip_target() {
+        let (addr, proxy) = mock_proxy(0x00, None).await;
+        let config = anonymization(format!("socks5://{}", addr), false);
+        connect(&config, "10.1.2.3:7000").await.unwrap();
+
+        let request = proxy.await.unwrap();
+        assert_eq!(request, [SOCKS_VERSION, CMD_CONNECT, 0x00, ATYP_IPV4, 10, 1, 2, 3, 0x1B, 0x58]);
+    }
+
+    #[tokio::test]
+    async fn test_socks5h_defers_dns_to_proxy() {
+        // `.invalid` never resolves locally (RFC 6761), so only the proxy can handle it
+        let (addr, proxy) = mock_proxy(0x00, None).await;
+        let config = anonymization(format!("socks5h://{}", addr), false);
+        connect(&config, "peer.b4ae.invalid:443").await.unwrap();
+
+        let request = proxy.await.unwrap();
+        let host = b"peer.b4ae.invalid";
+        let mut expected = vec![SOCKS_VERSION, CMD_CONNECT, 0x00, ATYP_DOMAIN, host.len() as u8];
+        expected.extend_from_slice(host);
+        expected.extend_from_slice(&443u16.to_be_bytes());
+        assert_eq!(request, expected);
+    }
+
+    #[tokio::test]
+    async fn test_socks5_resolves_locally() {
+        let (addr, proxy) = mock_proxy(0x00, None).await;
+        let config = anonymization(format!("socks5://{}", addr), false);
+        connect(&config, "localhost:80").await.unwrap();
+
+        let request = proxy.await.unwrap();
+        assert_ne!(request[3], ATYP_DOMAIN);
+        assert!(!request.windows(9).any(|w| w == b"localhost"));
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejected_surfaces_error() {
+        let (addr, proxy) = mock_proxy(0x05, None).await;
+        let config = anonymization(format!("socks5h://{}", addr), false);
+        let err = connect(&config, "peer.b4ae.invalid:443").await.unwrap_err();
+        assert!(matches!(&err, B4aeError::NetworkError(msg) if msg.contains("connection refused")));
+        proxy.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_handshake_through_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let peer_addr = listener.local_addr().unwrap();
+        let (proxy_addr, proxy) = mock_proxy(0x00, Some(peer_addr)).await;
+
+        let bob = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let client = B4aeClient::new(SecurityProfile::Standard).unwrap();
+            let mut bob = AsyncB4aeClient::responder(client, b"alice", StreamTransport::new(stream));
+            bob.recv().await.unwrap()
+        });
+
+        let config = crate::client::B4aeConfig::builder()
+            .security_profile(SecurityProfile::Standard)
+            .anonymization(anonymization(format!("socks5h://{}", proxy_addr), true))
+            .build()
+            .unwrap();
+        let client = B4aeClient::with_config(config).unwrap();
+        let mut alice = connect_initiator(client, b"bob", &peer_addr.to_string()).await.unwrap();
+        assert!(alice.is_established());
+        alice.send(b"hello over socks").await.unwrap();
+
+        assert_eq!(bob.await.unwrap(), b"hello over socks");
+        drop(alice);
+        let request = proxy.await.unwrap();
+        assert_eq!(request[3], ATYP_IPV4);
+    }
+}
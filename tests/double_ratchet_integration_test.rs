@@ -218,7 +218,6 @@ mod network_simulation_tests {
     /// 
     /// Requirements: 7.1, 7.2, 7.3, 7.4
     #[test]
-    #[ignore] // requires sufficient key cache for skipped messages
     fn test_out_of_order_message_delivery() {
         let (mut alice, mut bob) = create_session_pair()
             .expect("Failed to create session pair");
@@ -22,8 +22,18 @@ use b4ae::protocol::v2::mode_binding::{
 };
 use b4ae::protocol::v2::types::{
     AuthenticationMode, ModeBinding, HandshakeInit, HandshakeResponse, HandshakeComplete,
+    ClientHelloWithCookie,
 };
 
+/// Placeholder cookie echo; these tests never reach cookie verification
+fn test_cookie() -> ClientHelloWithCookie {
+    ClientHelloWithCookie {
+        client_random: [6u8; 32],
+        cookie: [7u8; 32],
+        timestamp: 1234567890,
+    }
+}
+
 #[test]
 fn test_mode_binding_in_handshake_init() {
     // Simulate mode negotiation
@@ -41,6 +51,7 @@ fn test_mode_binding_in_handshake_init() {
         signature: vec![5u8; 4595],        // Dilithium5 signature size
         timestamp: 1234567890,
         mode_binding: mode_binding.clone(),
+        cookie: test_cookie(),
     };
 
     // Verify mode_binding in message matches expected
@@ -126,6 +137,7 @@ fn test_mode_binding_consistency_across_messages() {
         signature: vec![5u8; 4595],
         timestamp: 1234567890,
         mode_binding: mode_binding.clone(),
+        cookie: test_cookie(),
     };
 
     let handshake_response = HandshakeResponse {
@@ -185,6 +197,7 @@ fn test_downgrade_attack_detection_mode_b_to_a() {
         signature: vec![5u8; 64], // XEdDSA signature (Mode A)
         timestamp: 1234567890,
         mode_binding: mode_binding.clone(),
+        cookie: test_cookie(),
     };
 
     // Server tries to verify with Mode A (attacker's goal)
@@ -228,6 +241,7 @@ fn test_downgrade_attack_detection_modified_binding() {
         signature: vec![5u8; 4595],
         timestamp: 1234567890,
         mode_binding: modified_binding,
+        cookie: test_cookie(),
     };
 
     // Verification should fail
@@ -366,6 +380,7 @@ fn test_handshake_message_validation_with_mode_binding() {
             .unwrap()
             .as_secs(),
         mode_binding,
+        cookie: test_cookie(),
     };
 
     // Should validate successfully
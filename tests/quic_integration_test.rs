@@ -0,0 +1,47 @@
+//! QUIC Integration Tests
+//!
+//! Loopback QUIC connection carrying the B4AE handshake and one encrypted
+//! data message. Requires `--features quic`.
+
+#![cfg(feature = "quic")]
+
+use b4ae::client::B4aeClient;
+use b4ae::protocol::SecurityProfile;
+use b4ae::transport::quic::QuicTransport;
+
+#[tokio::test]
+async fn test_quic_loopback_handshake_and_message() {
+    let server = QuicTransport::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let client_endpoint = QuicTransport::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let server_addr = server.local_addr().unwrap();
+
+    let responder = tokio::spawn(async move {
+        let mut bob = B4aeClient::new(SecurityProfile::Standard).unwrap();
+        let mut stream = server.accept().await.unwrap();
+        let peer_id = stream.remote_addr().to_string().into_bytes();
+        stream.handshake_responder(&mut bob, &peer_id).await.unwrap();
+        // An empty real message is delivered, not mistaken for cover traffic
+        let empty = stream.recv_message(&mut bob, &peer_id).await.unwrap();
+        assert!(empty.is_empty());
+        let received = stream.recv_message(&mut bob, &peer_id).await.unwrap();
+        stream.send_message(&mut bob, &peer_id, b"ack").await.unwrap();
+        stream.finish().unwrap();
+        // Keep the connection open until the reply is read
+        let _ = stream.recv_frame().await;
+        received
+    });
+
+    let mut alice = B4aeClient::new(SecurityProfile::Standard).unwrap();
+    let mut stream = client_endpoint.connect(server_addr).await.unwrap();
+    let peer_id = server_addr.to_string().into_bytes();
+    stream.handshake_initiator(&mut alice, &peer_id).await.unwrap();
+    assert!(alice.has_session(&peer_id));
+
+    stream.send_message(&mut alice, &peer_id, b"").await.unwrap();
+    stream.send_message(&mut alice, &peer_id, b"Hello over QUIC").await.unwrap();
+    let reply = stream.recv_message(&mut alice, &peer_id).await.unwrap();
+    assert_eq!(reply, b"ack");
+    stream.finish().unwrap();
+
+    assert_eq!(responder.await.unwrap(), b"Hello over QUIC");
+}